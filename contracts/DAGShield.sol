@@ -5,6 +5,8 @@ import "@openzeppelin/contracts/token/ERC20/ERC20.sol";
 import "@openzeppelin/contracts/access/Ownable.sol";
 import "@openzeppelin/contracts/utils/ReentrancyGuard.sol";
 import "@openzeppelin/contracts/utils/Pausable.sol";
+import "@openzeppelin/contracts/utils/cryptography/ECDSA.sol";
+import "@openzeppelin/contracts/utils/cryptography/MessageHashUtils.sol";
 
 /**
  * @title DAGShield Core Contract
@@ -36,11 +38,65 @@ contract DAGShield is Ownable, ReentrancyGuard, Pausable {
         string reason
     );
     
+    event StakeAdded(address indexed nodeAddress, uint256 amount, uint256 stake);
+    event StakeWithdrawn(address indexed nodeAddress, uint256 amount, uint256 stake);
+    event NodeDeactivated(address indexed nodeAddress, string reason);
+    
     event RewardDistributed(
         address indexed recipient,
         uint256 amount,
         string rewardType
     );
+    
+    event RewardsClaimed(address indexed recipient, uint256 amount);
+    
+    event DetectionEpochCommitted(
+        address indexed node,
+        uint256 indexed epoch,
+        bytes32 indexed modelHash,
+        bytes32 root,
+        uint256 detections,
+        bytes32 proofHash
+    );
+    
+    event DagEpochCommitted(
+        address indexed node,
+        uint256 indexed epoch,
+        bytes32 root,
+        uint256 transactions
+    );
+    
+    event FirstReporterClaimed(
+        bytes32 indexed detectionHash,
+        address indexed claimant,
+        uint256 firstSeenAt,
+        bytes32 receiptChainHash,
+        string evidenceCid
+    );
+    
+    event GossipEquivocationSlashed(
+        address indexed offender,
+        address indexed reporter,
+        bytes32 indexed topicHash,
+        uint256 sequence
+    );
+    
+    event DisputeOpened(
+        uint256 indexed disputeId,
+        address indexed offender,
+        address indexed reporter,
+        bytes32 evidenceHash,
+        string evidenceCid
+    );
+    
+    event DisputeResolved(uint256 indexed disputeId, bool upheld);
+    
+    event ChallengeCreated(
+        bytes32 indexed challengeId,
+        string challengeType,
+        uint256 reward,
+        uint256 deadline
+    );
 
     // Structs
     struct ThreatAlert {
@@ -67,9 +123,46 @@ contract DAGShield is Ownable, ReentrancyGuard, Pausable {
         uint256 energyEfficiency; // Energy score 0-100
     }
     
+    // Experimental: a node's Merkle commitment to the detections one model produced in an epoch
+    struct EpochCommitment {
+        bytes32 root;
+        uint256 detections;
+        bytes32 proofHash; // keccak256 of the attached proof; zero when none was attached
+        uint256 timestamp;
+    }
+    
+    // A node's blake3 Merkle root over the transactions its DAG finalized in an epoch, and their results
+    struct DagEpoch {
+        bytes32 root;
+        uint256 transactions;
+        uint256 timestamp;
+    }
+    
+    // The earliest signed first-seen receipt put forward for a detection
+    struct FirstReporterClaim {
+        address claimant;
+        uint256 firstSeenAt; // milliseconds, as signed in the claimant's receipt
+        bytes32 receiptChainHash; // keccak256 over the receipt chain pinned with the evidence
+        string evidenceCid;
+        uint256 openedAt; // when the first claim opened the window
+        bool settled;
+    }
+    
+    // Gossip misbehaviour that cannot be proven on-chain, reported with evidence for review
+    struct Dispute {
+        address offender;
+        address reporter;
+        bytes32 evidenceHash; // keccak256 of the evidence bundle pinned at evidenceCid
+        string evidenceCid;
+        uint256 openedAt;
+        bool resolved;
+        bool upheld;
+    }
+    
     struct Challenge {
         bytes32 id;
         string challengeType;
+        string data; // versioned JSON parameters nodes solve the challenge with
         bytes32 expectedResult;
         uint256 reward;
         uint256 deadline;
@@ -82,8 +175,21 @@ contract DAGShield is Ownable, ReentrancyGuard, Pausable {
     mapping(address => Node) public nodes;
     mapping(bytes32 => Challenge) public challenges;
     mapping(address => uint256) public nodeStakes;
+    // When the node's stake fell below MIN_STAKE; 0 while it is at or above it
+    mapping(address => uint256) public understakedSince;
     mapping(address => uint256) public reputationScores;
     mapping(bytes32 => mapping(address => bool)) public hasVoted;
+    // node => keccak256(epoch, modelHash) => commitment
+    mapping(address => mapping(bytes32 => EpochCommitment)) public epochCommitments;
+    mapping(address => mapping(uint256 => DagEpoch)) public dagEpochs;
+    // keccak256(abi.encode(chainId, targetAddress, threatType)) => claim
+    mapping(bytes32 => FirstReporterClaim) public firstReporterClaims;
+    // keccak256(abi.encode(offender, topicHash, sequence)) => already slashed
+    mapping(bytes32 => bool) public equivocationsSlashed;
+    mapping(uint256 => Dispute) public disputes;
+    // Rewards accrued by each recipient and not yet claimed
+    mapping(address => uint256) public pendingRewards;
+    uint256 public disputeCount;
     
     bytes32[] public threatIds;
     address[] public activeNodes;
@@ -95,6 +201,9 @@ contract DAGShield is Ownable, ReentrancyGuard, Pausable {
     uint256 public constant SLASH_PERCENTAGE = 10; // 10% slash for false reports
     uint256 public constant REWARD_MULTIPLIER = 150; // 1.5x reward for accurate reports
     uint256 public constant CHALLENGE_DURATION = 1 hours;
+    uint256 public constant STAKE_GRACE_PERIOD = 3 days; // below MIN_STAKE for longer deactivates the node
+    uint256 public constant FIRST_REPORTER_CLAIM_WINDOW = 1 hours;
+    uint256 public constant MAX_RECEIPT_CLOCK_SKEW = 5 minutes;
     
     address public tokenContract;
     uint256 public totalStaked;
@@ -113,6 +222,7 @@ contract DAGShield is Ownable, ReentrancyGuard, Pausable {
         require(bytes(nodeId).length > 0, "Invalid node ID");
         require(msg.value >= MIN_STAKE, "Insufficient stake");
         require(!nodes[msg.sender].active, "Node already registered");
+        require(nodeStakes[msg.sender] == 0, "Withdraw remaining stake first");
         
         nodes[msg.sender] = Node({
             nodeId: nodeId,
@@ -133,6 +243,61 @@ contract DAGShield is Ownable, ReentrancyGuard, Pausable {
         emit NodeRegistered(msg.sender, nodeId, msg.value, block.timestamp);
     }
     
+    /**
+     * @dev Add to the caller's stake, e.g. to get back above MIN_STAKE after a slash
+     */
+    function addStake() external payable nonReentrant {
+        require(nodes[msg.sender].active, "Node not registered");
+        require(msg.value > 0, "No stake sent");
+        
+        nodeStakes[msg.sender] += msg.value;
+        nodes[msg.sender].stake = nodeStakes[msg.sender];
+        totalStaked += msg.value;
+        if (nodeStakes[msg.sender] >= MIN_STAKE) {
+            understakedSince[msg.sender] = 0;
+        }
+        
+        emit StakeAdded(msg.sender, msg.value, nodeStakes[msg.sender]);
+    }
+    
+    /**
+     * @dev Withdraw stake: what is above MIN_STAKE while the node is active, all of it once
+     * it is deactivated
+     * @param amount Amount to withdraw
+     */
+    function withdrawStake(uint256 amount) external nonReentrant {
+        require(amount > 0 && amount <= nodeStakes[msg.sender], "Invalid amount");
+        if (nodes[msg.sender].active) {
+            require(nodeStakes[msg.sender] - amount >= MIN_STAKE, "Stake would fall below minimum");
+        }
+        
+        nodeStakes[msg.sender] -= amount;
+        nodes[msg.sender].stake = nodeStakes[msg.sender];
+        totalStaked -= amount;
+        payable(msg.sender).transfer(amount);
+        
+        emit StakeWithdrawn(msg.sender, amount, nodeStakes[msg.sender]);
+    }
+    
+    /**
+     * @dev Leave the network, so the whole stake can be withdrawn
+     */
+    function deactivateNode() external {
+        require(nodes[msg.sender].active, "Node not registered");
+        _deactivate(msg.sender, "unstaked");
+    }
+    
+    /**
+     * @dev Deactivate a node whose stake stayed below MIN_STAKE for the grace period
+     * @param nodeAddress Address of the node
+     */
+    function deactivateUnderstakedNode(address nodeAddress) external {
+        require(nodes[nodeAddress].active, "Node not registered");
+        uint256 since = understakedSince[nodeAddress];
+        require(since != 0 && block.timestamp >= since + STAKE_GRACE_PERIOD, "Grace period not over");
+        _deactivate(nodeAddress, "insufficient_stake");
+    }
+    
     /**
      * @dev Report a threat detected by AI analysis
      * @param threatType Type of threat (phishing, scam, exploit, etc.)
@@ -187,6 +352,146 @@ contract DAGShield is Ownable, ReentrancyGuard, Pausable {
         );
     }
     
+    /**
+     * @dev Commit to the detections a node produced with one model during a reporting epoch.
+     * Experimental: the proof is not verified on-chain yet, only its hash is kept for later checks
+     * @param epoch Reporting epoch number
+     * @param modelHash Hash of the model the detections were produced with
+     * @param root Merkle root over the epoch's detections
+     * @param detections Number of detections committed to
+     * @param proof Optional succinct proof of the statement; empty when none
+     */
+    function commitDetectionEpoch(
+        uint256 epoch,
+        bytes32 modelHash,
+        bytes32 root,
+        uint256 detections,
+        bytes calldata proof
+    ) external whenNotPaused {
+        require(nodes[msg.sender].active, "Node not registered");
+        require(root != bytes32(0) && detections > 0, "Empty commitment");
+        
+        bytes32 key = keccak256(abi.encode(epoch, modelHash));
+        require(epochCommitments[msg.sender][key].timestamp == 0, "Epoch already committed");
+        
+        bytes32 proofHash = proof.length > 0 ? keccak256(proof) : bytes32(0);
+        epochCommitments[msg.sender][key] = EpochCommitment({
+            root: root,
+            detections: detections,
+            proofHash: proofHash,
+            timestamp: block.timestamp
+        });
+        nodes[msg.sender].lastActivity = block.timestamp;
+        
+        emit DetectionEpochCommitted(msg.sender, epoch, modelHash, root, detections, proofHash);
+    }
+    
+    /**
+     * @dev Commit to the transactions a node's DAG finalized in an epoch, settling later disputes
+     * over what it processed against the root it committed to at the time
+     * @param epoch The node's DAG epoch, the height of the checkpoint it seals
+     * @param root blake3 Merkle root over the epoch's transactions and their execution results
+     * @param transactions Number of transactions sealed
+     */
+    function commitDagEpoch(uint256 epoch, bytes32 root, uint256 transactions) external whenNotPaused {
+        require(nodes[msg.sender].active, "Node not registered");
+        require(root != bytes32(0) && transactions > 0, "Empty epoch");
+        require(dagEpochs[msg.sender][epoch].timestamp == 0, "Epoch already committed");
+        
+        dagEpochs[msg.sender][epoch] = DagEpoch({
+            root: root,
+            transactions: transactions,
+            timestamp: block.timestamp
+        });
+        nodes[msg.sender].lastActivity = block.timestamp;
+        
+        emit DagEpochCommitted(msg.sender, epoch, root, transactions);
+    }
+    
+    /**
+     * @dev Get a node's DAG epoch root
+     */
+    function getDagEpoch(address node, uint256 epoch) external view returns (DagEpoch memory) {
+        return dagEpochs[node][epoch];
+    }
+    
+    /**
+     * @dev Get a node's commitment for an epoch and model
+     */
+    function getEpochCommitment(
+        address node,
+        uint256 epoch,
+        bytes32 modelHash
+    ) external view returns (EpochCommitment memory) {
+        return epochCommitments[node][keccak256(abi.encode(epoch, modelHash))];
+    }
+    
+    /**
+     * @dev Claim the first-reporter reward for a detection with the claimant's signed first-seen
+     * receipt. An earlier receipt supersedes the standing claim until the window closes
+     * @param detectionHash keccak256(abi.encode(chainId, targetAddress, threatType))
+     * @param firstSeenAt When the claimant first saw the detection, in milliseconds
+     * @param signature personal_sign of keccak256(abi.encode(detectionHash, firstSeenAt, claimant))
+     * @param receiptChainHash Hash of the peers' receipt chain pinned with the evidence
+     * @param evidenceCid IPFS CID of the evidence bundle carrying the receipt chain
+     */
+    function claimFirstReporter(
+        bytes32 detectionHash,
+        uint256 firstSeenAt,
+        bytes calldata signature,
+        bytes32 receiptChainHash,
+        string calldata evidenceCid
+    ) external whenNotPaused {
+        require(nodes[msg.sender].active, "Node not registered");
+        require(firstSeenAt <= (block.timestamp + MAX_RECEIPT_CLOCK_SKEW) * 1000, "Receipt from the future");
+        
+        bytes32 digest = MessageHashUtils.toEthSignedMessageHash(
+            keccak256(abi.encode(detectionHash, firstSeenAt, msg.sender))
+        );
+        require(ECDSA.recover(digest, signature) == msg.sender, "Invalid receipt signature");
+        
+        FirstReporterClaim storage claim = firstReporterClaims[detectionHash];
+        uint256 openedAt = block.timestamp;
+        if (claim.claimant != address(0)) {
+            require(!claim.settled && block.timestamp < claim.openedAt + FIRST_REPORTER_CLAIM_WINDOW, "Claim window closed");
+            require(firstSeenAt < claim.firstSeenAt, "Not the earliest receipt");
+            openedAt = claim.openedAt;
+        }
+        
+        firstReporterClaims[detectionHash] = FirstReporterClaim({
+            claimant: msg.sender,
+            firstSeenAt: firstSeenAt,
+            receiptChainHash: receiptChainHash,
+            evidenceCid: evidenceCid,
+            openedAt: openedAt,
+            settled: false
+        });
+        nodes[msg.sender].lastActivity = block.timestamp;
+        
+        emit FirstReporterClaimed(detectionHash, msg.sender, firstSeenAt, receiptChainHash, evidenceCid);
+    }
+    
+    /**
+     * @dev Pay the first-reporter reward to the standing claimant once the claim window closes
+     * @param detectionHash Detection the claim was made for
+     */
+    function settleFirstReporter(bytes32 detectionHash) external nonReentrant whenNotPaused {
+        FirstReporterClaim storage claim = firstReporterClaims[detectionHash];
+        require(claim.claimant != address(0), "No claim");
+        require(!claim.settled, "Claim already settled");
+        require(block.timestamp >= claim.openedAt + FIRST_REPORTER_CLAIM_WINDOW, "Claim window open");
+        
+        claim.settled = true;
+        _distributeReward(claim.claimant, "first_reporter");
+    }
+    
+    /**
+     * @dev Get the standing first-reporter claim for a detection
+     */
+    function getFirstReporterClaim(bytes32 detectionHash) external view returns (FirstReporterClaim memory) {
+        return firstReporterClaims[detectionHash];
+    }
+    
     /**
      * @dev Vote on a threat alert for community verification
      * @param alertId ID of the threat alert
@@ -219,11 +524,13 @@ contract DAGShield is Ownable, ReentrancyGuard, Pausable {
     /**
      * @dev Create a gamified challenge for nodes
      * @param challengeType Type of challenge
+     * @param data Challenge parameters, as the node client expects them for challengeType
      * @param expectedResult Expected result hash
      * @param reward Reward amount for completion
      */
     function createChallenge(
         string memory challengeType,
+        string memory data,
         bytes32 expectedResult,
         uint256 reward
     ) external onlyOwner {
@@ -236,6 +543,7 @@ contract DAGShield is Ownable, ReentrancyGuard, Pausable {
         challenges[challengeId] = Challenge({
             id: challengeId,
             challengeType: challengeType,
+            data: data,
             expectedResult: expectedResult,
             reward: reward,
             deadline: block.timestamp + CHALLENGE_DURATION,
@@ -244,6 +552,31 @@ contract DAGShield is Ownable, ReentrancyGuard, Pausable {
         });
         
         activeChallenges.push(challengeId);
+        
+        emit ChallengeCreated(challengeId, challengeType, reward, block.timestamp + CHALLENGE_DURATION);
+    }
+    
+    /**
+     * @dev Get the challenges still open for solutions: not completed and not past their deadline
+     */
+    function getActiveChallenges() external view returns (Challenge[] memory) {
+        uint256 open = 0;
+        for (uint256 i = 0; i < activeChallenges.length; i++) {
+            Challenge storage challenge = challenges[activeChallenges[i]];
+            if (!challenge.completed && block.timestamp <= challenge.deadline) {
+                open++;
+            }
+        }
+        
+        Challenge[] memory result = new Challenge[](open);
+        uint256 next = 0;
+        for (uint256 i = 0; i < activeChallenges.length; i++) {
+            Challenge storage challenge = challenges[activeChallenges[i]];
+            if (!challenge.completed && block.timestamp <= challenge.deadline) {
+                result[next++] = challenge;
+            }
+        }
+        return result;
     }
     
     /**
@@ -296,10 +629,118 @@ contract DAGShield is Ownable, ReentrancyGuard, Pausable {
      */
     function slashNode(address nodeAddress, string memory reason) external onlyOwner {
         require(nodes[nodeAddress].active, "Node not registered");
+        _slash(nodeAddress, reason);
+    }
+    
+    /**
+     * @dev Slash a node that signed two different gossip messages under one topic and sequence
+     * number, rewarding the reporter. Both signatures are checked here, so no review is needed
+     * @param offender Node whose key signed both messages
+     * @param topicHash keccak256 of the gossip topic
+     * @param sequence Sequence number both messages were signed under
+     * @param payloadHashA keccak256 of the first message's payload
+     * @param signatureA personal_sign of keccak256(abi.encode(topicHash, sequence, payloadHashA))
+     * @param payloadHashB keccak256 of the second message's payload
+     * @param signatureB personal_sign of keccak256(abi.encode(topicHash, sequence, payloadHashB))
+     */
+    function submitEquivocationEvidence(
+        address offender,
+        bytes32 topicHash,
+        uint256 sequence,
+        bytes32 payloadHashA,
+        bytes calldata signatureA,
+        bytes32 payloadHashB,
+        bytes calldata signatureB
+    ) external nonReentrant whenNotPaused {
+        require(nodes[msg.sender].active, "Node not registered");
+        require(nodes[offender].active, "Offender not registered");
+        require(offender != msg.sender, "Cannot report yourself");
+        require(payloadHashA != payloadHashB, "Messages are identical");
+        
+        bytes32 key = keccak256(abi.encode(offender, topicHash, sequence));
+        require(!equivocationsSlashed[key], "Equivocation already slashed");
+        require(
+            _gossipSigner(topicHash, sequence, payloadHashA, signatureA) == offender
+                && _gossipSigner(topicHash, sequence, payloadHashB, signatureB) == offender,
+            "Invalid gossip signature"
+        );
+        
+        equivocationsSlashed[key] = true;
+        _slash(offender, "gossip_equivocation");
+        _distributeReward(msg.sender, "slashing_evidence");
+        
+        emit GossipEquivocationSlashed(offender, msg.sender, topicHash, sequence);
+    }
+    
+    /**
+     * @dev Open a dispute against a node over signed gossip that is invalid but cannot be judged
+     * on-chain, e.g. a malformed payload. The owner reviews the pinned evidence and resolves it
+     * @param offender Node whose key signed the offending messages
+     * @param evidenceHash keccak256 of the evidence bundle
+     * @param evidenceCid IPFS CID of the evidence bundle
+     */
+    function openDispute(
+        address offender,
+        bytes32 evidenceHash,
+        string calldata evidenceCid
+    ) external whenNotPaused returns (uint256) {
+        require(nodes[msg.sender].active, "Node not registered");
+        require(nodes[offender].active, "Offender not registered");
+        require(offender != msg.sender, "Cannot report yourself");
+        
+        uint256 disputeId = disputeCount++;
+        disputes[disputeId] = Dispute({
+            offender: offender,
+            reporter: msg.sender,
+            evidenceHash: evidenceHash,
+            evidenceCid: evidenceCid,
+            openedAt: block.timestamp,
+            resolved: false,
+            upheld: false
+        });
+        
+        emit DisputeOpened(disputeId, offender, msg.sender, evidenceHash, evidenceCid);
+        return disputeId;
+    }
+    
+    /**
+     * @dev Resolve a dispute; an upheld dispute slashes the offender and rewards the reporter
+     * @param disputeId Dispute to resolve
+     * @param upheld Whether the evidence shows misbehaviour
+     */
+    function resolveDispute(uint256 disputeId, bool upheld) external onlyOwner nonReentrant {
+        Dispute storage dispute = disputes[disputeId];
+        require(dispute.reporter != address(0), "No dispute");
+        require(!dispute.resolved, "Dispute already resolved");
         
+        dispute.resolved = true;
+        dispute.upheld = upheld;
+        if (upheld) {
+            _slash(dispute.offender, "gossip_dispute");
+            _distributeReward(dispute.reporter, "slashing_evidence");
+        }
+        
+        emit DisputeResolved(disputeId, upheld);
+    }
+    
+    /**
+     * @dev Get a dispute by ID
+     */
+    function getDispute(uint256 disputeId) external view returns (Dispute memory) {
+        return disputes[disputeId];
+    }
+    
+    /**
+     * @dev Cut a node's stake and reputation
+     */
+    function _slash(address nodeAddress, string memory reason) internal {
         uint256 slashAmount = (nodeStakes[nodeAddress] * SLASH_PERCENTAGE) / 100;
         nodeStakes[nodeAddress] -= slashAmount;
+        nodes[nodeAddress].stake = nodeStakes[nodeAddress];
         totalStaked -= slashAmount;
+        if (nodeStakes[nodeAddress] < MIN_STAKE && understakedSince[nodeAddress] == 0) {
+            understakedSince[nodeAddress] = block.timestamp;
+        }
         
         nodes[nodeAddress].reputation = nodes[nodeAddress].reputation > 20 
             ? nodes[nodeAddress].reputation - 20 
@@ -309,7 +750,53 @@ contract DAGShield is Ownable, ReentrancyGuard, Pausable {
     }
     
     /**
-     * @dev Internal function to distribute rewards
+     * @dev Take a node out of the active set; its stake stays withdrawable
+     */
+    function _deactivate(address nodeAddress, string memory reason) internal {
+        nodes[nodeAddress].active = false;
+        understakedSince[nodeAddress] = 0;
+        for (uint256 i = 0; i < activeNodes.length; i++) {
+            if (activeNodes[i] == nodeAddress) {
+                activeNodes[i] = activeNodes[activeNodes.length - 1];
+                activeNodes.pop();
+                break;
+            }
+        }
+        
+        emit NodeDeactivated(nodeAddress, reason);
+    }
+    
+    /**
+     * @dev Recover the key that signed a gossip message
+     */
+    function _gossipSigner(
+        bytes32 topicHash,
+        uint256 sequence,
+        bytes32 payloadHash,
+        bytes calldata signature
+    ) internal pure returns (address) {
+        bytes32 digest = MessageHashUtils.toEthSignedMessageHash(
+            keccak256(abi.encode(topicHash, sequence, payloadHash))
+        );
+        return ECDSA.recover(digest, signature);
+    }
+    
+    /**
+     * @dev Pay out every reward accrued to the caller
+     */
+    function claimRewards() external nonReentrant {
+        uint256 amount = pendingRewards[msg.sender];
+        require(amount > 0, "No rewards to claim");
+        
+        pendingRewards[msg.sender] = 0;
+        // Transfer reward (simplified - would integrate with token contract)
+        payable(msg.sender).transfer(amount);
+        
+        emit RewardsClaimed(msg.sender, amount);
+    }
+    
+    /**
+     * @dev Internal function to distribute rewards; they accrue until the recipient claims them
      * @param recipient Address to receive reward
      * @param rewardType Type of reward being distributed
      */
@@ -318,8 +805,7 @@ contract DAGShield is Ownable, ReentrancyGuard, Pausable {
         uint256 reputationMultiplier = nodes[recipient].reputation / 100;
         uint256 finalReward = baseReward * (100 + reputationMultiplier) / 100;
         
-        // Transfer reward (simplified - would integrate with token contract)
-        payable(recipient).transfer(finalReward);
+        pendingRewards[recipient] += finalReward;
         
         emit RewardDistributed(recipient, finalReward, rewardType);
     }
@@ -0,0 +1,50 @@
+//! DAG processor throughput per dependency topology, without a node around it
+//!
+//! Every iteration drains a fresh processor, so transactions from earlier iterations never
+//! slow down the next one's readiness checks.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dagshield_node::config::NodeConfig;
+use dagshield_node::dag::{DAGProcessor, DagShape};
+use dagshield_node::ResourceGovernor;
+
+const TRANSACTIONS: usize = 1_000;
+
+fn dag_shapes(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().expect("tokio runtime");
+    let config = NodeConfig::default();
+    let governor = Arc::new(ResourceGovernor::new(&config.workers, &config.work_tokens).expect("resource governor"));
+    
+    let mut group = c.benchmark_group("dag_processing");
+    group.throughput(Throughput::Elements(TRANSACTIONS as u64));
+    group.sample_size(10);
+    for shape in DagShape::standard() {
+        group.bench_with_input(BenchmarkId::new(shape.name(), TRANSACTIONS), &shape, |b, shape| {
+            b.iter_custom(|iters| {
+                runtime.block_on(async {
+                    let mut elapsed = Duration::ZERO;
+                    for _ in 0..iters {
+                        let processor = Arc::new(
+                            DAGProcessor::new(&config, Arc::clone(&governor)).await.expect("DAG processor"),
+                        );
+                        let draining = Arc::clone(&processor);
+                        let worker = tokio::spawn(async move { draining.start().await });
+                        
+                        let start = Instant::now();
+                        processor.benchmark(TRANSACTIONS, *shape).await.expect("benchmark run");
+                        elapsed += start.elapsed();
+                        worker.abort();
+                    }
+                    elapsed
+                })
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, dag_shapes);
+criterion_main!(benches);
@@ -0,0 +1,38 @@
+//! Detection latency over the golden fixtures, without a node around it
+//!
+//! Runs on rules alone unless `ai.model_path` points at a model. The verdict cache is disabled,
+//! so every iteration runs the full detection path.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::sync::Arc;
+
+use dagshield_node::config::NodeConfig;
+use dagshield_node::fixtures::load_fixtures;
+use dagshield_node::{ResourceGovernor, ThreatDetector, Transaction};
+
+const FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/golden");
+
+fn threat_detection(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().expect("tokio runtime");
+    let mut config = NodeConfig::default();
+    config.ai.detection_cache_ttl_secs = 0;
+    let governor = Arc::new(ResourceGovernor::new(&config.workers, &config.work_tokens).expect("resource governor"));
+    let detector = runtime
+        .block_on(ThreatDetector::new(&config.ai, governor))
+        .expect("threat detector");
+    let transactions: Vec<Transaction> = load_fixtures(FIXTURES_DIR)
+        .expect("golden fixtures")
+        .into_iter()
+        .map(|(_, fixture)| fixture.transaction)
+        .collect();
+    
+    let mut group = c.benchmark_group("threat_detection");
+    group.throughput(Throughput::Elements(transactions.len() as u64));
+    group.bench_with_input(BenchmarkId::new("golden_fixtures", transactions.len()), &transactions, |b, transactions| {
+        b.iter(|| runtime.block_on(detector.detect_threats_batch(transactions)).expect("detection"));
+    });
+    group.finish();
+}
+
+criterion_group!(benches, threat_detection);
+criterion_main!(benches);
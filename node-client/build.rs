@@ -0,0 +1,25 @@
+use std::path::Path;
+
+/// Contracts `deploy-contracts` can deploy, embedded from the Hardhat build when present
+const DEPLOYABLE_CONTRACTS: &[&str] = &["DAGToken", "DAGShield", "DAGOracle"];
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tonic_build::configure()
+        .build_server(false)
+        .compile(&["proto/fleet.proto"], &["proto"])?;
+
+    // Self-update picks the release binary built for the same target
+    println!("cargo:rustc-env=BUILD_TARGET={}", std::env::var("TARGET")?);
+
+    // Binaries built without `npx hardhat compile` embed empty artifacts and refuse to deploy
+    let out_dir = std::env::var("OUT_DIR")?;
+    let embedded = Path::new(&out_dir).join("contracts");
+    std::fs::create_dir_all(&embedded)?;
+    for name in DEPLOYABLE_CONTRACTS {
+        let artifact = format!("../artifacts/contracts/{name}.sol/{name}.json");
+        println!("cargo:rerun-if-changed={artifact}");
+        let contents = std::fs::read(&artifact).unwrap_or_else(|_| b"{}".to_vec());
+        std::fs::write(embedded.join(format!("{name}.json")), contents)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,45 @@
+# Install the DAGShield node as a Windows service and, given a token, provision it headlessly:
+#
+#   .\install.ps1 -BinaryUrl https://releases.dagshield.io/node/v0.1.0/dagshield-node-windows-x86_64.exe `
+#       -ProvisionUrl https://fleet.example.com/provision -Token ... -TrustedSigner 0x...
+#
+# Without -Token the service is created but not started.
+param(
+    [Parameter(Mandatory = $true)][string]$BinaryUrl,
+    [string]$BinarySha256 = "",
+    [string]$ProvisionUrl = "",
+    [string]$Qr = "",
+    [string]$Token = "",
+    [string]$TrustedSigner = "",
+    [string]$InstallDir = "C:\dagshield"
+)
+$ErrorActionPreference = "Stop"
+
+$Binary = Join-Path $InstallDir "dagshield-node.exe"
+$Config = Join-Path $InstallDir "config.toml"
+New-Item -ItemType Directory -Force -Path $InstallDir | Out-Null
+
+Invoke-WebRequest -Uri $BinaryUrl -OutFile "$Binary.download"
+if ($BinarySha256) {
+    $actual = (Get-FileHash -Algorithm SHA256 "$Binary.download").Hash
+    if ($actual -ne $BinarySha256.ToUpper()) { throw "Checksum mismatch: expected $BinarySha256, got $actual" }
+}
+Move-Item -Force "$Binary.download" $Binary
+
+if (-not (Get-Service DAGShieldNode -ErrorAction SilentlyContinue)) {
+    sc.exe create DAGShieldNode binPath= "`"$Binary`" --service --config `"$Config`"" start= auto | Out-Null
+}
+
+if (-not $Token) {
+    Write-Host "No -Token; skipping provisioning. Service DAGShieldNode is installed but not started."
+    exit 0
+}
+if (-not $TrustedSigner) { throw "-TrustedSigner is required to provision" }
+
+$source = if ($Qr) { @("--qr", $Qr) } elseif ($ProvisionUrl) { @("--url", $ProvisionUrl) } else { throw "-ProvisionUrl or -Qr is required" }
+& $Binary --config $Config provision @source --token $Token --trusted-signer $TrustedSigner
+if ($LASTEXITCODE -ne 0) { throw "Provisioning failed" }
+
+# The config now holds the device key; keep it to administrators and the service account
+icacls $Config /inheritance:r /grant:r "SYSTEM:F" "Administrators:F" | Out-Null
+Start-Service DAGShieldNode
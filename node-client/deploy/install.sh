@@ -0,0 +1,96 @@
+#!/bin/sh
+# Install the DAGShield node on Linux (systemd) or macOS (launchd) and, given a token,
+# provision it headlessly:
+#
+#   DAGSHIELD_BINARY_URL=https://releases.dagshield.io/node/v0.1.0/dagshield-node-linux-x86_64 \
+#   DAGSHIELD_PROVISION_URL=https://fleet.example.com/provision \
+#   DAGSHIELD_PROVISION_TOKEN=... \
+#   DAGSHIELD_TRUSTED_SIGNER=0x... \
+#   sh install.sh
+#
+# Without DAGSHIELD_PROVISION_TOKEN the node is installed but not started; write its config
+# by hand or run `dagshield-node provision` later.
+set -eu
+
+: "${DAGSHIELD_BINARY_URL:?set DAGSHIELD_BINARY_URL to the release binary for this platform}"
+DAGSHIELD_BINARY_SHA256="${DAGSHIELD_BINARY_SHA256:-}"
+INSTALL_DIR=/opt/dagshield
+CONFIG_DIR=/etc/dagshield
+DATA_DIR=/var/lib/dagshield
+BINARY="$INSTALL_DIR/dagshield-node"
+
+if [ "$(id -u)" -ne 0 ]; then
+    echo "install.sh must run as root" >&2
+    exit 1
+fi
+
+mkdir -p "$INSTALL_DIR" "$CONFIG_DIR" "$DATA_DIR"
+curl -fsSL "$DAGSHIELD_BINARY_URL" -o "$BINARY.download"
+if [ -n "$DAGSHIELD_BINARY_SHA256" ]; then
+    echo "$DAGSHIELD_BINARY_SHA256  $BINARY.download" | shasum -a 256 -c -
+fi
+chmod 755 "$BINARY.download"
+mv "$BINARY.download" "$BINARY"
+
+provision() {
+    if [ -z "${DAGSHIELD_PROVISION_TOKEN:-}" ]; then
+        echo "No DAGSHIELD_PROVISION_TOKEN; skipping provisioning"
+        return 1
+    fi
+    : "${DAGSHIELD_TRUSTED_SIGNER:?set DAGSHIELD_TRUSTED_SIGNER to the operator address signing bundles}"
+    if [ -n "${DAGSHIELD_PROVISION_QR:-}" ]; then
+        "$BINARY" --config "$CONFIG_DIR/config.toml" provision --qr "$DAGSHIELD_PROVISION_QR" \
+            --token "$DAGSHIELD_PROVISION_TOKEN" --trusted-signer "$DAGSHIELD_TRUSTED_SIGNER"
+    else
+        : "${DAGSHIELD_PROVISION_URL:?set DAGSHIELD_PROVISION_URL or DAGSHIELD_PROVISION_QR}"
+        "$BINARY" --config "$CONFIG_DIR/config.toml" provision --url "$DAGSHIELD_PROVISION_URL" \
+            --token "$DAGSHIELD_PROVISION_TOKEN" --trusted-signer "$DAGSHIELD_TRUSTED_SIGNER"
+    fi
+}
+
+case "$(uname -s)" in
+    Linux)
+        if ! id dagshield >/dev/null 2>&1; then
+            useradd --system --home-dir "$DATA_DIR" --shell /usr/sbin/nologin dagshield
+        fi
+        # The unit shipped next to this script, else the one published with the releases
+        if [ -f "$(dirname "$0")/dagshield-node.service" ]; then
+            cp "$(dirname "$0")/dagshield-node.service" /etc/systemd/system/dagshield-node.service
+        else
+            curl -fsSL "${DAGSHIELD_UNIT_URL:-https://releases.dagshield.io/node/dagshield-node.service}" \
+                -o /etc/systemd/system/dagshield-node.service
+        fi
+        systemctl daemon-reload
+        if provision; then
+            chown -R dagshield:dagshield "$CONFIG_DIR" "$DATA_DIR"
+            systemctl enable --now dagshield-node
+        fi
+        ;;
+    Darwin)
+        cat > /Library/LaunchDaemons/io.dagshield.node.plist <<PLIST
+<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key><string>io.dagshield.node</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>$BINARY</string>
+        <string>--config</string>
+        <string>$CONFIG_DIR/config.toml</string>
+    </array>
+    <key>WorkingDirectory</key><string>$DATA_DIR</string>
+    <key>KeepAlive</key><true/>
+    <key>RunAtLoad</key><true/>
+</dict>
+</plist>
+PLIST
+        if provision; then
+            launchctl load -w /Library/LaunchDaemons/io.dagshield.node.plist
+        fi
+        ;;
+    *)
+        echo "Unsupported platform $(uname -s); on Windows use install.ps1" >&2
+        exit 1
+        ;;
+esac
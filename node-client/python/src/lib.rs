@@ -0,0 +1,136 @@
+//! `dagshield`: the node's threat detection pipeline for Python
+//!
+//! Wraps the same [`ThreatDetector`] the node runs, built from the same config file, so features
+//! computed for training and verdicts reviewed in a notebook are exactly what production
+//! would produce for the transaction:
+//!
+//! ```python
+//! import dagshield
+//!
+//! detector = dagshield.Detector("config.toml")
+//! tx = {"id": "0xabc...", "from": "0x...", "to": "0x...", "target_address": "0x...",
+//!       "chain_id": 1, "data": bytes.fromhex("095ea7b3..."), "timestamp": 1700000000,
+//!       "dependencies": []}
+//! detector.features(tx)       # model input, laid out as detector.feature_layout()
+//! detector.detect(tx)         # verdict as a dict, as the screening API returns it
+//! ```
+//!
+//! Transactions are dicts in the node's JSON shape; `data` may be `bytes` or a list of ints.
+
+use anyhow::Result;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+use pythonize::{depythonize, pythonize};
+use std::sync::Arc;
+
+use dagshield_node::ai::graph::{AddressGraph, GraphFeatures};
+use dagshield_node::{NodeConfig, NodeStorage, ResourceGovernor, ThreatDetector, Transaction};
+
+/// The detection pipeline configured from a node config file
+#[pyclass]
+struct Detector {
+    runtime: tokio::runtime::Runtime,
+    detector: Arc<ThreatDetector>,
+}
+
+#[pymethods]
+impl Detector {
+    /// `address_graph` defaults to the config's setting. The graph, and the calibration when
+    /// enabled, are read from the config's data directory, which a running node holds locked;
+    /// point a copy of the config at a snapshot of it, or pass `address_graph=False` to leave
+    /// the graph features zeroed.
+    #[new]
+    #[pyo3(signature = (config_path = "config.toml", address_graph = None))]
+    fn new(py: Python<'_>, config_path: &str, address_graph: Option<bool>) -> PyResult<Self> {
+        let config = NodeConfig::load(config_path).map_err(value_error)?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+        
+        let with_graph = address_graph.unwrap_or(config.address_graph.enabled);
+        let detector = py
+            .allow_threads(|| runtime.block_on(build_detector(&config, with_graph)))
+            .map_err(runtime_error)?;
+        Ok(Self { runtime, detector })
+    }
+    
+    /// Verdict for one transaction
+    fn detect(&self, py: Python<'_>, transaction: &PyAny) -> PyResult<PyObject> {
+        let transaction = to_transaction(transaction)?;
+        let result = py
+            .allow_threads(|| self.runtime.block_on(self.detector.detect_threat(&transaction)))
+            .map_err(runtime_error)?;
+        Ok(pythonize(py, &result)?)
+    }
+    
+    /// Verdicts for many transactions, scored in parallel like a node batch
+    fn detect_batch(&self, py: Python<'_>, transactions: Vec<&PyAny>) -> PyResult<Vec<PyObject>> {
+        let transactions = transactions.into_iter().map(to_transaction).collect::<PyResult<Vec<_>>>()?;
+        let results = py
+            .allow_threads(|| self.runtime.block_on(self.detector.detect_threats_batch(&transactions)))
+            .map_err(runtime_error)?;
+        results.iter().map(|result| Ok(pythonize(py, result)?)).collect()
+    }
+    
+    /// The model input vector for a transaction
+    fn features(&self, py: Python<'_>, transaction: &PyAny) -> PyResult<Vec<f32>> {
+        let transaction = to_transaction(transaction)?;
+        py.allow_threads(|| self.runtime.block_on(self.detector.feature_vector(&transaction)))
+            .map_err(runtime_error)
+    }
+    
+    /// `(extractor name, width)` for each slice of the feature vector, in order
+    fn feature_layout(&self) -> Vec<(String, usize)> {
+        self.detector.feature_layout()
+    }
+    
+    /// Identifies the model, patterns and thresholds; record it alongside any exported features
+    #[getter]
+    fn pipeline_fingerprint(&self) -> String {
+        self.detector.pipeline_fingerprint()
+    }
+}
+
+/// The detector as the node assembles it, extractors registered in the node's order
+async fn build_detector(config: &NodeConfig, with_graph: bool) -> Result<Arc<ThreatDetector>> {
+    let governor = Arc::new(ResourceGovernor::new(&config.workers, &config.work_tokens)?);
+    let detector = Arc::new(ThreatDetector::new(&config.ai, governor).await?);
+    if with_graph || config.ai.calibration.enabled {
+        let storage = Arc::new(NodeStorage::new(&config.storage).await?);
+        detector.attach_calibration_store(Arc::clone(&storage)).await?;
+        if with_graph {
+            let graph = Arc::new(AddressGraph::new(&config.address_graph, storage));
+            detector.register_feature_extractor(Arc::new(GraphFeatures(graph))).await;
+        }
+    }
+    Ok(detector)
+}
+
+fn to_transaction(transaction: &PyAny) -> PyResult<Transaction> {
+    let fields: &PyDict = transaction.downcast()?;
+    // Raw calldata reads naturally as bytes, but the node's JSON shape is a list of ints
+    let fields = fields.copy()?;
+    if let Some(data) = fields.get_item("data")? {
+        if let Ok(bytes) = data.downcast::<PyBytes>() {
+            fields.set_item("data", bytes.as_bytes().to_vec())?;
+        }
+    }
+    depythonize(fields).map_err(|e| PyValueError::new_err(format!("Invalid transaction: {}", e)))
+}
+
+fn value_error(e: anyhow::Error) -> PyErr {
+    PyValueError::new_err(format!("{:#}", e))
+}
+
+fn runtime_error(e: anyhow::Error) -> PyErr {
+    PyRuntimeError::new_err(format!("{:#}", e))
+}
+
+#[pymodule]
+fn dagshield(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
+    module.add_class::<Detector>()?;
+    module.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
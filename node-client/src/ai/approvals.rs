@@ -0,0 +1,195 @@
+//! Token approval risk: who a transaction lets spend the sender's tokens, and how much
+//!
+//! Approvals move nothing themselves, which is why drainers ask for them: the theft happens
+//! later, from the spender's side. ERC-20 `approve`/`increaseAllowance` and ERC-721/1155
+//! `setApprovalForAll` calldata is decoded, the spender is resolved against drainer lists,
+//! network alerts and its deployed code, and the result is scored.
+
+use anyhow::{Context, Result};
+use ethers::abi::{self, ParamType, Token};
+use ethers::types::{Address, U256};
+use ethers::utils::id;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tracing::info;
+
+use crate::config::ApprovalConfig;
+use crate::dag::Transaction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalKind {
+    /// `approve(address,uint256)`; ERC-721 shares the selector, so the amount may be a token id
+    Approve,
+    IncreaseAllowance,
+    /// Every token the sender holds in the collection, present and future
+    SetApprovalForAll,
+}
+
+/// A decoded approval call
+#[derive(Debug, Clone)]
+pub struct ApprovalCall {
+    pub kind: ApprovalKind,
+    pub token: Address,
+    pub spender: Address,
+    /// Raw units for allowances; `None` for operator approvals
+    pub amount: Option<U256>,
+    /// Allowance set to zero or operator approval withdrawn
+    pub revocation: bool,
+}
+
+impl ApprovalCall {
+    /// Allowances this large are never spent down in practice
+    pub fn unlimited(&self) -> bool {
+        self.amount.is_some_and(|amount| amount >= U256::one() << 255)
+    }
+}
+
+/// What is known about a spender beyond the call itself
+#[derive(Debug, Clone, Default)]
+pub struct SpenderInfo {
+    pub known_drainer: bool,
+    pub verified_alerts: usize,
+    /// `None` when the spender's code could not be looked up
+    pub is_contract: Option<bool>,
+    pub verified_source: Option<bool>,
+}
+
+/// The approval behind a verdict, as carried in [`crate::ai::ThreatDetectionResult`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRisk {
+    pub kind: ApprovalKind,
+    pub token: String,
+    pub spender: String,
+    /// Decimal raw units; `None` for operator approvals
+    pub amount: Option<String>,
+    pub unlimited: bool,
+    pub revocation: bool,
+    pub known_drainer: bool,
+    pub spender_alerts: usize,
+    pub spender_is_contract: Option<bool>,
+    pub score: f32,
+    pub findings: Vec<String>,
+}
+
+pub fn decode(transaction: &Transaction) -> Option<ApprovalCall> {
+    let selector = transaction.data.get(..4)?;
+    let token: Address = transaction.to.parse().ok()?;
+    let args = &transaction.data[4..];
+    
+    if selector == id("approve(address,uint256)") || selector == id("increaseAllowance(address,uint256)") {
+        let kind = if selector == id("approve(address,uint256)") {
+            ApprovalKind::Approve
+        } else {
+            ApprovalKind::IncreaseAllowance
+        };
+        match abi::decode(&[ParamType::Address, ParamType::Uint(256)], args).ok().as_deref() {
+            Some([Token::Address(spender), Token::Uint(amount)]) => Some(ApprovalCall {
+                kind,
+                token,
+                spender: *spender,
+                amount: Some(*amount),
+                revocation: kind == ApprovalKind::Approve && amount.is_zero(),
+            }),
+            _ => None,
+        }
+    } else if selector == id("setApprovalForAll(address,bool)") {
+        match abi::decode(&[ParamType::Address, ParamType::Bool], args).ok().as_deref() {
+            Some([Token::Address(operator), Token::Bool(approved)]) => Some(ApprovalCall {
+                kind: ApprovalKind::SetApprovalForAll,
+                token,
+                spender: *operator,
+                amount: None,
+                revocation: !approved,
+            }),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+/// Known drainer and phishing spenders, from the config and list files
+pub struct DrainerList {
+    addresses: HashSet<Address>,
+}
+
+impl DrainerList {
+    /// List files hold one address per line; `#` starts a comment
+    pub fn load(config: &ApprovalConfig) -> Result<Self> {
+        let mut addresses = HashSet::new();
+        for address in &config.drainer_addresses {
+            addresses.insert(address.parse().with_context(|| format!("Invalid drainer address {}", address))?);
+        }
+        for path in &config.drainer_list_files {
+            let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read drainer list {}", path))?;
+            for line in content.lines() {
+                let line = line.split('#').next().unwrap_or_default().trim();
+                if line.is_empty() {
+                    continue;
+                }
+                addresses.insert(line.parse().with_context(|| format!("Invalid address {} in {}", line, path))?);
+            }
+        }
+        if !addresses.is_empty() {
+            info!("🪝 Loaded {} known drainer addresses", addresses.len());
+        }
+        Ok(Self { addresses })
+    }
+    
+    pub fn contains(&self, address: &Address) -> bool {
+        self.addresses.contains(address)
+    }
+}
+
+/// Score an approval from the call and what is known of its spender
+pub fn assess(call: &ApprovalCall, spender: &SpenderInfo) -> ApprovalRisk {
+    let unlimited = call.unlimited();
+    let mut findings = Vec::new();
+    
+    let score = if call.revocation {
+        findings.push("revokes an earlier approval".to_string());
+        0.0
+    } else if spender.known_drainer {
+        findings.push("spender is a known drainer".to_string());
+        0.98
+    } else if spender.verified_alerts > 0 {
+        findings.push(format!("spender has {} network-verified alerts", spender.verified_alerts));
+        0.9
+    } else {
+        let mut score: f32 = match (call.kind, unlimited) {
+            (ApprovalKind::SetApprovalForAll, _) => {
+                findings.push("grants control of the whole collection".to_string());
+                0.45
+            }
+            (_, true) => {
+                findings.push("unlimited allowance".to_string());
+                0.35
+            }
+            _ => 0.1,
+        };
+        // Protocols spend approvals through contracts; a wallet asking for one is the ice-phishing shape
+        if spender.is_contract == Some(false) {
+            findings.push("spender is an externally owned account".to_string());
+            score += 0.35;
+        } else if spender.verified_source == Some(false) {
+            findings.push("spender contract source is not verified".to_string());
+            score += 0.2;
+        }
+        score.min(1.0)
+    };
+    
+    ApprovalRisk {
+        kind: call.kind,
+        token: format!("{:?}", call.token),
+        spender: format!("{:?}", call.spender),
+        amount: call.amount.map(|amount| amount.to_string()),
+        unlimited,
+        revocation: call.revocation,
+        known_drainer: spender.known_drainer,
+        spender_alerts: spender.verified_alerts,
+        spender_is_contract: spender.is_contract,
+        score,
+        findings,
+    }
+}
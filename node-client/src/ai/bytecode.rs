@@ -0,0 +1,233 @@
+//! Contract bytecode analysis for smart contract exploit detection
+//!
+//! Calldata says little about what the called contract will do. The target's deployed code is
+//! fetched once, walked opcode by opcode for self-destructs and delegatecalls, checked for
+//! proxy layouts and, when an explorer is configured, for published source. Proxies are followed
+//! one hop, so the implementation's code is what gets judged.
+
+use anyhow::{bail, Result};
+use ethers::types::{Address, H256};
+use lru::LruCache;
+use serde::Deserialize;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
+
+use crate::blockchain::BlockchainClient;
+use crate::config::BytecodeConfig;
+
+const SELFDESTRUCT: u8 = 0xff;
+const DELEGATECALL: u8 = 0xf4;
+const CALLCODE: u8 = 0xf2;
+const PUSH1: u8 = 0x60;
+const PUSH32: u8 = 0x7f;
+
+/// `bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)`
+const EIP1967_IMPLEMENTATION_SLOT: [u8; 32] = [
+    0x36, 0x08, 0x94, 0xa1, 0x3b, 0xa1, 0xa3, 0x21, 0x06, 0x67, 0xc8, 0x28, 0x49, 0x2d, 0xb9, 0x8d,
+    0xca, 0x3e, 0x20, 0x76, 0xcc, 0x37, 0x35, 0xa9, 0x20, 0xa3, 0xca, 0x50, 0x5d, 0x38, 0x2b, 0xbc,
+];
+
+/// EIP-1167 minimal proxy: this prefix, the implementation address, then a fixed suffix
+const MINIMAL_PROXY_PREFIX: [u8; 10] = [0x36, 0x3d, 0x3d, 0x37, 0x3d, 0x3d, 0x3d, 0x36, 0x3d, 0x73];
+
+#[derive(Debug, Clone, Default)]
+pub struct BytecodeProfile {
+    /// 0 for externally owned accounts
+    pub code_size: usize,
+    /// Opcode findings describe the implementation when the target is a proxy
+    pub selfdestruct: bool,
+    pub delegatecalls: usize,
+    pub callcode: bool,
+    /// Implementation behind an EIP-1967 or EIP-1167 proxy
+    pub implementation: Option<String>,
+    /// Whether the explorer has published source; `None` without an explorer or on lookup failure
+    pub verified: Option<bool>,
+}
+
+impl BytecodeProfile {
+    pub fn is_contract(&self) -> bool {
+        self.code_size > 0
+    }
+    
+    /// Exploit likelihood from the code alone, with the findings behind it
+    pub fn risk(&self) -> (f32, Vec<&'static str>) {
+        if !self.is_contract() {
+            return (0.0, Vec::new());
+        }
+        
+        let mut score = 0.0;
+        let mut findings = Vec::new();
+        if self.selfdestruct {
+            score += 0.35;
+            findings.push("can self-destruct");
+        }
+        // A proxy delegates by design; anything else delegating hands its storage to foreign code
+        if self.delegatecalls > 0 && self.implementation.is_none() {
+            score += 0.35;
+            findings.push("delegatecalls outside a proxy layout");
+        }
+        if self.callcode {
+            score += 0.2;
+            findings.push("uses deprecated CALLCODE");
+        }
+        if self.verified == Some(false) {
+            score += 0.25;
+            findings.push("source not verified");
+        }
+        (f32::min(score, 1.0), findings)
+    }
+}
+
+/// Opcode-level facts about one piece of code, skipping PUSH immediates
+fn scan(code: &[u8]) -> BytecodeProfile {
+    let mut profile = BytecodeProfile {
+        code_size: code.len(),
+        ..Default::default()
+    };
+    
+    let mut pc = 0;
+    while pc < code.len() {
+        let opcode = code[pc];
+        match opcode {
+            SELFDESTRUCT => profile.selfdestruct = true,
+            DELEGATECALL => profile.delegatecalls += 1,
+            CALLCODE => profile.callcode = true,
+            PUSH1..=PUSH32 => pc += (opcode - PUSH1 + 1) as usize,
+            _ => {}
+        }
+        pc += 1;
+    }
+    profile
+}
+
+fn minimal_proxy_target(code: &[u8]) -> Option<Address> {
+    let rest = code.strip_prefix(&MINIMAL_PROXY_PREFIX[..])?;
+    (rest.len() >= 20).then(|| Address::from_slice(&rest[..20]))
+}
+
+/// Target code profiles, fetched once per address over the node's RPC endpoint
+pub struct BytecodeAnalyzer {
+    config: BytecodeConfig,
+    client: Arc<BlockchainClient>,
+    http: reqwest::Client,
+    profiles: parking_lot::Mutex<LruCache<Address, Arc<BytecodeProfile>>>,
+}
+
+impl BytecodeAnalyzer {
+    pub fn new(config: &BytecodeConfig, client: Arc<BlockchainClient>) -> Result<Self> {
+        Ok(Self {
+            config: config.clone(),
+            client,
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_millis(config.fetch_timeout_ms))
+                .build()?,
+            profiles: parking_lot::Mutex::new(LruCache::new(
+                NonZeroUsize::new(config.cache_size).unwrap_or(NonZeroUsize::MIN),
+            )),
+        })
+    }
+    
+    /// Profile of a target on the node's chain; `None` for other chains, bad addresses or a failed fetch
+    pub async fn profile(&self, chain_id: u64, target: &str) -> Option<Arc<BytecodeProfile>> {
+        if chain_id != self.client.chain_id() {
+            return None;
+        }
+        let address: Address = target.parse().ok()?;
+        if let Some(profile) = self.profiles.lock().get(&address) {
+            return Some(Arc::clone(profile));
+        }
+        
+        let timeout = Duration::from_millis(self.config.fetch_timeout_ms);
+        match tokio::time::timeout(timeout, self.analyze(address)).await {
+            Ok(Ok(profile)) => {
+                let profile = Arc::new(profile);
+                self.profiles.lock().put(address, Arc::clone(&profile));
+                Some(profile)
+            }
+            Ok(Err(e)) => {
+                debug!("Bytecode analysis of {:?} failed: {}", address, e);
+                None
+            }
+            Err(_) => {
+                debug!("Bytecode analysis of {:?} timed out", address);
+                None
+            }
+        }
+    }
+    
+    async fn analyze(&self, address: Address) -> Result<BytecodeProfile> {
+        let code = self.client.get_code(address).await?;
+        let mut profile = scan(&code);
+        if !profile.is_contract() {
+            return Ok(profile);
+        }
+        
+        let implementation = match minimal_proxy_target(&code) {
+            Some(target) => Some(target),
+            None if profile.delegatecalls > 0 => {
+                let slot = self.client.get_storage_at(address, H256(EIP1967_IMPLEMENTATION_SLOT)).await?;
+                let target = Address::from_slice(&slot.as_bytes()[12..]);
+                (!target.is_zero()).then_some(target)
+            }
+            None => None,
+        };
+        
+        // Judge a proxy by the code it runs
+        let verified_address = match implementation {
+            Some(target) => {
+                let implementation_code = self.client.get_code(target).await?;
+                profile = scan(&implementation_code);
+                profile.code_size = code.len();
+                profile.implementation = Some(format!("{:?}", target));
+                target
+            }
+            None => address,
+        };
+        
+        if let Some(explorer_url) = &self.config.explorer_api_url {
+            profile.verified = match self.source_verified(explorer_url, verified_address).await {
+                Ok(verified) => Some(verified),
+                Err(e) => {
+                    debug!("Explorer lookup for {:?} failed: {}", verified_address, e);
+                    None
+                }
+            };
+        }
+        Ok(profile)
+    }
+    
+    /// Etherscan-compatible `getsourcecode`, which answers with empty source for unverified code
+    async fn source_verified(&self, explorer_url: &str, address: Address) -> Result<bool> {
+        #[derive(Deserialize)]
+        struct SourceResponse {
+            status: String,
+            result: serde_json::Value,
+        }
+        
+        let response: SourceResponse = self.http
+            .get(explorer_url)
+            .query(&[
+                ("module", "contract"),
+                ("action", "getsourcecode"),
+                ("address", &format!("{:?}", address)),
+                ("apikey", &self.config.api_key),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        
+        if response.status != "1" {
+            bail!("Explorer answered {}: {}", response.status, response.result);
+        }
+        let source = response.result
+            .get(0)
+            .and_then(|entry| entry.get("SourceCode"))
+            .and_then(|source| source.as_str())
+            .unwrap_or_default();
+        Ok(!source.is_empty())
+    }
+}
@@ -0,0 +1,271 @@
+//! Confidence calibration of the model's threat scores
+//!
+//! A classifier's softmax output is rarely a probability. Trained to separate classes, it tends
+//! to be overconfident, so a fixed threshold blocks and reports more than its value suggests.
+//! Graded verdicts pair the raw threat score with the real outcome; once enough are collected a
+//! monotone map is fitted to them and applied to every model score before it is thresholded.
+//!
+//! Samples and the fitted map are stored with the hash of the model they describe, and dropped
+//! when a different model loads.
+
+use anyhow::Result;
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, OnceLock};
+use tracing::{info, warn};
+
+use crate::config::{CalibrationConfig, CalibrationMethod};
+use crate::storage::NodeStorage;
+
+pub const CALIBRATION_NAMESPACE: &str = "calibration";
+
+const STATE_KEY: &str = "state";
+
+/// Raw scores kept for grading by later feedback
+const PENDING_SCORES: usize = 10_000;
+
+/// Temperatures searched when fitting, as natural logs
+const LN_TEMPERATURE_RANGE: (f64, f64) = (-3.0, 3.0);
+
+const FIT_ITERATIONS: usize = 60;
+
+/// Keeps logits and log losses finite at scores of exactly 0 or 1
+const SCORE_EPSILON: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CalibrationSample {
+    /// The model's raw threat score
+    pub score: f32,
+    pub malicious: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum CalibrationMap {
+    /// Calibrated score is `sigmoid(logit(score) / temperature)`; above 1 softens an overconfident model
+    Temperature { temperature: f64 },
+    /// A step function: scores up to `bounds[i]` map to `values[i]`, both increasing
+    Isotonic { bounds: Vec<f32>, values: Vec<f32> },
+}
+
+impl CalibrationMap {
+    pub fn fit(method: CalibrationMethod, samples: &[CalibrationSample]) -> Self {
+        match method {
+            CalibrationMethod::Temperature => Self::Temperature { temperature: fit_temperature(samples) },
+            CalibrationMethod::Isotonic => {
+                let (bounds, values) = fit_isotonic(samples);
+                Self::Isotonic { bounds, values }
+            }
+        }
+    }
+    
+    pub fn apply(&self, score: f32) -> f32 {
+        match self {
+            Self::Temperature { temperature } => sigmoid(logit(score as f64) / temperature) as f32,
+            Self::Isotonic { bounds, values } => {
+                let block = bounds.partition_point(|&bound| bound < score).min(values.len().saturating_sub(1));
+                values.get(block).copied().unwrap_or(score)
+            }
+        }
+    }
+    
+    fn describe(&self) -> String {
+        match self {
+            Self::Temperature { temperature } => format!("temperature {:.3}", temperature),
+            Self::Isotonic { bounds, .. } => format!("isotonic, {} steps", bounds.len()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CalibrationState {
+    /// Model the samples were scored by; `None` while running rule-based
+    model_hash: Option<String>,
+    samples: VecDeque<CalibrationSample>,
+    map: Option<CalibrationMap>,
+    /// Samples added since `map` was fitted
+    since_fit: usize,
+}
+
+pub struct Calibrator {
+    config: CalibrationConfig,
+    state: parking_lot::RwLock<CalibrationState>,
+    /// Raw score of each recent model verdict, until feedback grades it
+    pending: parking_lot::Mutex<LruCache<String, f32>>,
+    storage: OnceLock<Arc<NodeStorage>>,
+}
+
+impl Calibrator {
+    pub fn new(config: &CalibrationConfig) -> Self {
+        Self {
+            config: config.clone(),
+            state: parking_lot::RwLock::new(CalibrationState::default()),
+            pending: parking_lot::Mutex::new(LruCache::new(
+                NonZeroUsize::new(PENDING_SCORES).unwrap_or(NonZeroUsize::MIN),
+            )),
+            storage: OnceLock::new(),
+        }
+    }
+    
+    /// Restore the samples and map stored for `model_hash`, and persist every change from here on
+    pub fn attach_storage(&self, storage: Arc<NodeStorage>, model_hash: Option<&str>) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        if self.storage.set(Arc::clone(&storage)).is_err() {
+            warn!("⚠️ Calibration store already attached");
+            return Ok(());
+        }
+        
+        match storage.get::<CalibrationState>(CALIBRATION_NAMESPACE, STATE_KEY)? {
+            Some(saved) if saved.model_hash.as_deref() == model_hash => {
+                info!("🎯 Restored {} calibration samples{}", saved.samples.len(),
+                      saved.map.as_ref().map_or(String::new(), |map| format!(", {}", map.describe())));
+                *self.state.write() = saved;
+            }
+            Some(_) => {
+                info!("🎯 Stored calibration describes another model, starting over");
+                self.reset(model_hash)?;
+            }
+            None => self.reset(model_hash)?,
+        }
+        Ok(())
+    }
+    
+    /// The calibrated threat score, remembering the raw one for grading
+    pub fn calibrate(&self, transaction_id: &str, score: f32) -> f32 {
+        if !self.config.enabled {
+            return score;
+        }
+        self.pending.lock().put(transaction_id.to_string(), score);
+        match &self.state.read().map {
+            Some(map) => map.apply(score),
+            None => score,
+        }
+    }
+    
+    /// Add a graded model verdict to the samples; true when the map was refitted
+    pub fn record_outcome(&self, transaction_id: &str, malicious: bool) -> Result<bool> {
+        let Some(score) = self.pending.lock().pop(transaction_id) else {
+            return Ok(false);
+        };
+        
+        let mut state = self.state.write();
+        state.samples.push_back(CalibrationSample { score, malicious });
+        while state.samples.len() > self.config.max_samples {
+            state.samples.pop_front();
+        }
+        state.since_fit += 1;
+        
+        let refit = state.samples.len() >= self.config.min_samples
+            && (state.map.is_none() || state.since_fit >= self.config.refit_every);
+        if refit {
+            let samples: Vec<CalibrationSample> = state.samples.iter().copied().collect();
+            let map = CalibrationMap::fit(self.config.method, &samples);
+            info!("🎯 Calibration refitted on {} graded verdicts: {}, log loss {:.4} -> {:.4}",
+                  samples.len(), map.describe(), log_loss(&samples, None), log_loss(&samples, Some(&map)));
+            state.map = Some(map);
+            state.since_fit = 0;
+        }
+        self.persist(&state)?;
+        Ok(refit)
+    }
+    
+    /// Drop samples and map scored by another model; true when they were dropped
+    pub fn model_changed(&self, model_hash: Option<&str>) -> Result<bool> {
+        if !self.config.enabled || self.state.read().model_hash.as_deref() == model_hash {
+            return Ok(false);
+        }
+        info!("🎯 Model changed, calibration starts over");
+        self.pending.lock().clear();
+        self.reset(model_hash)?;
+        Ok(true)
+    }
+    
+    /// The map scores are calibrated with, `None` while scores are used raw
+    pub fn map(&self) -> Option<CalibrationMap> {
+        self.state.read().map.clone()
+    }
+    
+    fn reset(&self, model_hash: Option<&str>) -> Result<()> {
+        let mut state = self.state.write();
+        *state = CalibrationState {
+            model_hash: model_hash.map(str::to_string),
+            ..CalibrationState::default()
+        };
+        self.persist(&state)
+    }
+    
+    fn persist(&self, state: &CalibrationState) -> Result<()> {
+        match self.storage.get() {
+            Some(storage) => storage.put(CALIBRATION_NAMESPACE, STATE_KEY, state),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Golden-section search for the temperature minimizing log loss, which is unimodal in it
+fn fit_temperature(samples: &[CalibrationSample]) -> f64 {
+    let loss = |ln_t: f64| log_loss(samples, Some(&CalibrationMap::Temperature { temperature: ln_t.exp() }));
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut low, mut high) = LN_TEMPERATURE_RANGE;
+    
+    for _ in 0..FIT_ITERATIONS {
+        let left = high - ratio * (high - low);
+        let right = low + ratio * (high - low);
+        if loss(left) < loss(right) {
+            high = right;
+        } else {
+            low = left;
+        }
+    }
+    ((low + high) / 2.0).exp()
+}
+
+/// Pool-adjacent-violators: merge neighbouring score blocks until their outcome rates increase
+fn fit_isotonic(samples: &[CalibrationSample]) -> (Vec<f32>, Vec<f32>) {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.score.total_cmp(&b.score));
+    
+    // (upper score bound, malicious count, sample count)
+    let mut blocks: Vec<(f32, f64, f64)> = Vec::new();
+    for sample in sorted {
+        blocks.push((sample.score, if sample.malicious { 1.0 } else { 0.0 }, 1.0));
+        while blocks.len() > 1 {
+            let (_, last_sum, last_count) = blocks[blocks.len() - 1];
+            let (_, prev_sum, prev_count) = blocks[blocks.len() - 2];
+            if prev_sum / prev_count < last_sum / last_count {
+                break;
+            }
+            let (bound, sum, count) = blocks.pop().expect("two blocks");
+            let merged = blocks.last_mut().expect("two blocks");
+            *merged = (bound, merged.1 + sum, merged.2 + count);
+        }
+    }
+    
+    blocks.into_iter().map(|(bound, sum, count)| (bound, (sum / count) as f32)).unzip()
+}
+
+/// Mean negative log likelihood of the outcomes under the calibrated, or raw, scores
+fn log_loss(samples: &[CalibrationSample], map: Option<&CalibrationMap>) -> f64 {
+    let total: f64 = samples
+        .iter()
+        .map(|sample| {
+            let score = map.map_or(sample.score, |map| map.apply(sample.score)) as f64;
+            let p = score.clamp(SCORE_EPSILON, 1.0 - SCORE_EPSILON);
+            if sample.malicious { -p.ln() } else { -(1.0 - p).ln() }
+        })
+        .sum();
+    total / samples.len().max(1) as f64
+}
+
+fn logit(score: f64) -> f64 {
+    let p = score.clamp(SCORE_EPSILON, 1.0 - SCORE_EPSILON);
+    (p / (1.0 - p)).ln()
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
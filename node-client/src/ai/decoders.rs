@@ -0,0 +1,369 @@
+//! Chain-specific calldata decoders for L2 wrapping and compression formats
+//!
+//! Rollup traffic often hides the call that matters: OP-stack messengers and portals, Arbitrum
+//! retryable tickets and zkSync priority requests wrap an inner call, and OP batcher transactions
+//! carry whole compressed channels of L2 transactions. Decoding these lets detection look at the
+//! inner calls exactly as it would on mainnet.
+
+use anyhow::{bail, Result};
+use ethers::abi::{self, ParamType, Token};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{NameOrAddress, U256};
+use ethers::utils::{hex, id, rlp::Rlp};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use tracing::debug;
+
+use crate::dag::Transaction;
+
+/// Upper bound on decompressed channel size, guarding against compression bombs
+const MAX_CHANNEL_BYTES: u64 = 10 * 1024 * 1024;
+/// Inner calls evaluated per outer transaction
+pub const MAX_INNER_CALLS: usize = 256;
+
+/// OP-stack batch inbox addresses share this prefix (`0xff00…<chain id>`)
+const OP_BATCH_INBOX_PREFIX: &str = "0xff000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum L2Format {
+    OptimismMessenger,
+    OptimismDeposit,
+    OptimismBatch,
+    ArbitrumRetryable,
+    ZkSyncPriorityRequest,
+    BlobReference,
+}
+
+impl L2Format {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            L2Format::OptimismMessenger => "optimism_messenger",
+            L2Format::OptimismDeposit => "optimism_deposit",
+            L2Format::OptimismBatch => "optimism_batch",
+            L2Format::ArbitrumRetryable => "arbitrum_retryable",
+            L2Format::ZkSyncPriorityRequest => "zksync_priority_request",
+            L2Format::BlobReference => "blob_reference",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InnerCall {
+    pub to: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DecodedCalldata {
+    pub format: L2Format,
+    pub calls: Vec<InnerCall>,
+    /// EIP-4844 versioned hashes of the blobs the transaction commits to
+    pub blob_hashes: Vec<String>,
+}
+
+impl DecodedCalldata {
+    /// The inner calls as standalone transactions, inheriting the outer transaction's context
+    pub fn inner_transactions(&self, outer: &Transaction) -> Vec<Transaction> {
+        self.calls
+            .iter()
+            .take(MAX_INNER_CALLS)
+            .enumerate()
+            .map(|(i, call)| Transaction {
+                id: format!("{}#{}", outer.id, i),
+                from: outer.from.clone(),
+                to: call.to.clone(),
+                target_address: call.to.clone(),
+                chain_id: outer.chain_id,
+                data: call.data.clone(),
+                timestamp: outer.timestamp,
+                dependencies: vec![],
+                blob_versioned_hashes: vec![],
+                value: U256::zero(),
+                logs: vec![],
+                origin: outer.origin.clone(),
+                // Inner calls spend the outer transaction's nonce, not one of their own
+                nonce: None,
+                storage_slots: vec![],
+                priority_fee: outer.priority_fee,
+                peer_synced: outer.peer_synced,
+            })
+            .collect()
+    }
+}
+
+/// Decode a wrapped or compressed L2 transaction; `None` when the calldata is not a known format
+pub fn decode(transaction: &Transaction) -> Option<DecodedCalldata> {
+    let result = if let Some(decoded) = decode_wrapper(&transaction.data) {
+        Ok(decoded)
+    } else if is_op_batch_inbox(&transaction.target_address) && transaction.data.first() == Some(&0x00) {
+        decode_op_frames(&transaction.data[1..])
+    } else if !transaction.blob_versioned_hashes.is_empty() {
+        decode_blob_references(&transaction.blob_versioned_hashes)
+    } else {
+        return None;
+    };
+    
+    match result {
+        Ok(decoded) => Some(decoded),
+        Err(e) => {
+            debug!("🧩 Could not decode L2 payload of {}: {}", transaction.id, e);
+            None
+        }
+    }
+}
+
+struct WrapperSignature {
+    signature: &'static str,
+    format: L2Format,
+    params: fn() -> Vec<ParamType>,
+    /// Positions of the inner target address and calldata in the decoded arguments
+    to_index: usize,
+    data_index: usize,
+}
+
+const WRAPPERS: &[WrapperSignature] = &[
+    WrapperSignature {
+        signature: "relayMessage(uint256,address,address,uint256,uint256,bytes)",
+        format: L2Format::OptimismMessenger,
+        params: relay_message_params,
+        to_index: 2,
+        data_index: 5,
+    },
+    WrapperSignature {
+        signature: "sendMessage(address,bytes,uint32)",
+        format: L2Format::OptimismMessenger,
+        params: send_message_params,
+        to_index: 0,
+        data_index: 1,
+    },
+    WrapperSignature {
+        signature: "depositTransaction(address,uint256,uint64,bool,bytes)",
+        format: L2Format::OptimismDeposit,
+        params: deposit_transaction_params,
+        to_index: 0,
+        data_index: 4,
+    },
+    WrapperSignature {
+        signature: "createRetryableTicket(address,uint256,uint256,address,address,uint256,uint256,bytes)",
+        format: L2Format::ArbitrumRetryable,
+        params: retryable_params,
+        to_index: 0,
+        data_index: 7,
+    },
+    WrapperSignature {
+        signature: "unsafeCreateRetryableTicket(address,uint256,uint256,address,address,uint256,uint256,bytes)",
+        format: L2Format::ArbitrumRetryable,
+        params: retryable_params,
+        to_index: 0,
+        data_index: 7,
+    },
+    WrapperSignature {
+        signature: "requestL2Transaction(address,uint256,bytes,uint256,uint256,bytes[],address)",
+        format: L2Format::ZkSyncPriorityRequest,
+        params: l2_transaction_request_params,
+        to_index: 0,
+        data_index: 2,
+    },
+];
+
+fn relay_message_params() -> Vec<ParamType> {
+    vec![
+        ParamType::Uint(256),
+        ParamType::Address,
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Bytes,
+    ]
+}
+
+fn send_message_params() -> Vec<ParamType> {
+    vec![ParamType::Address, ParamType::Bytes, ParamType::Uint(32)]
+}
+
+fn deposit_transaction_params() -> Vec<ParamType> {
+    vec![
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Uint(64),
+        ParamType::Bool,
+        ParamType::Bytes,
+    ]
+}
+
+fn l2_transaction_request_params() -> Vec<ParamType> {
+    vec![
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Bytes,
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Array(Box::new(ParamType::Bytes)),
+        ParamType::Address,
+    ]
+}
+
+fn retryable_params() -> Vec<ParamType> {
+    vec![
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Address,
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Bytes,
+    ]
+}
+
+/// Unwrap cross-domain messages, deposits, retryable tickets and priority requests
+fn decode_wrapper(data: &[u8]) -> Option<DecodedCalldata> {
+    let selector = data.get(..4)?;
+    let wrapper = WRAPPERS.iter().find(|w| id(w.signature) == selector)?;
+    let tokens = abi::decode(&(wrapper.params)(), &data[4..]).ok()?;
+    
+    let to = match tokens.get(wrapper.to_index)? {
+        Token::Address(address) => format!("{:?}", address),
+        _ => return None,
+    };
+    let data = match tokens.get(wrapper.data_index)? {
+        Token::Bytes(bytes) => bytes.clone(),
+        _ => return None,
+    };
+    
+    Some(DecodedCalldata {
+        format: wrapper.format,
+        calls: vec![InnerCall { to, data }],
+        blob_hashes: vec![],
+    })
+}
+
+fn is_op_batch_inbox(address: &str) -> bool {
+    address.to_lowercase().starts_with(OP_BATCH_INBOX_PREFIX)
+}
+
+struct Frame {
+    channel_id: [u8; 16],
+    number: u16,
+    data: Vec<u8>,
+    is_last: bool,
+}
+
+/// Parse derivation-version-0 frames and decode every channel completed within this transaction
+fn decode_op_frames(mut payload: &[u8]) -> Result<DecodedCalldata> {
+    let mut frames = Vec::new();
+    while !payload.is_empty() {
+        if payload.len() < 23 {
+            bail!("truncated frame header");
+        }
+        let mut channel_id = [0u8; 16];
+        channel_id.copy_from_slice(&payload[..16]);
+        let number = u16::from_be_bytes([payload[16], payload[17]]);
+        let length = u32::from_be_bytes([payload[18], payload[19], payload[20], payload[21]]) as usize;
+        if payload.len() < 22 + length + 1 {
+            bail!("truncated frame data");
+        }
+        let data = payload[22..22 + length].to_vec();
+        let is_last = payload[22 + length] == 1;
+        payload = &payload[22 + length + 1..];
+        
+        frames.push(Frame { channel_id, number, data, is_last });
+    }
+    
+    let mut channels: HashMap<[u8; 16], Vec<Frame>> = HashMap::new();
+    for frame in frames {
+        channels.entry(frame.channel_id).or_default().push(frame);
+    }
+    
+    let mut calls = Vec::new();
+    for (channel_id, mut frames) in channels {
+        frames.sort_by_key(|f| f.number);
+        
+        // Channels spread over several transactions are left to the outer-transaction analysis
+        let complete = frames.iter().enumerate().all(|(i, f)| f.number as usize == i)
+            && frames.last().map(|f| f.is_last).unwrap_or(false);
+        if !complete {
+            debug!("🧩 Channel {} is incomplete in this transaction", hex::encode(channel_id));
+            continue;
+        }
+        
+        let compressed: Vec<u8> = frames.into_iter().flat_map(|f| f.data).collect();
+        calls.extend(decode_channel(&compressed)?);
+    }
+    
+    Ok(DecodedCalldata {
+        format: L2Format::OptimismBatch,
+        calls,
+        blob_hashes: vec![],
+    })
+}
+
+/// Decompress a channel and extract the transactions of its singular batches
+fn decode_channel(compressed: &[u8]) -> Result<Vec<InnerCall>> {
+    // zlib streams start with a CM=8 header byte; brotli channels (version byte 0x01) are not supported
+    if compressed.first().map(|b| b & 0x0f) != Some(8) {
+        bail!("unsupported channel compression");
+    }
+    
+    let mut channel = Vec::new();
+    flate2::read::ZlibDecoder::new(compressed)
+        .take(MAX_CHANNEL_BYTES)
+        .read_to_end(&mut channel)?;
+    
+    let mut calls = Vec::new();
+    let mut offset = 0;
+    while offset < channel.len() && calls.len() < MAX_INNER_CALLS {
+        let item = Rlp::new(&channel[offset..]);
+        let info = item.payload_info()?;
+        let batch = item.data()?;
+        offset += info.total();
+        
+        match batch.first() {
+            // Singular batch: rlp([parent_hash, epoch_number, epoch_hash, timestamp, transactions])
+            Some(0x00) => {
+                let fields = Rlp::new(&batch[1..]);
+                for tx in fields.at(4)?.iter() {
+                    let raw: Vec<u8> = tx.data()?.to_vec();
+                    if let Some(call) = decode_l2_transaction(&raw) {
+                        calls.push(call);
+                    }
+                }
+            }
+            _ => debug!("🧩 Skipping span batch in channel"),
+        }
+    }
+    
+    Ok(calls)
+}
+
+fn decode_l2_transaction(raw: &[u8]) -> Option<InnerCall> {
+    let (tx, _signature) = TypedTransaction::decode_signed(&Rlp::new(raw)).ok()?;
+    let to = match tx.to()? {
+        NameOrAddress::Address(address) => format!("{:?}", address),
+        NameOrAddress::Name(name) => name.clone(),
+    };
+    
+    Some(InnerCall {
+        to,
+        data: tx.data().map(|d| d.to_vec()).unwrap_or_default(),
+    })
+}
+
+/// Blob contents live on the consensus layer; surface the validated references themselves
+fn decode_blob_references(hashes: &[String]) -> Result<DecodedCalldata> {
+    for hash in hashes {
+        let bytes = hex::decode(hash.trim_start_matches("0x"))?;
+        // Versioned hashes are 32 bytes tagged with the KZG version byte
+        if bytes.len() != 32 || bytes[0] != 0x01 {
+            bail!("invalid blob versioned hash {}", hash);
+        }
+    }
+    
+    Ok(DecodedCalldata {
+        format: L2Format::BlobReference,
+        calls: vec![],
+        blob_hashes: hashes.to_vec(),
+    })
+}
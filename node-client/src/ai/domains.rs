@@ -0,0 +1,272 @@
+//! Phishing domain intelligence for the URLs a transaction carries
+//!
+//! Hosts come from the dApp page the wallet was signing on, when the submitter passes it, and
+//! from URLs written into calldata, where scam tokens and airdrops advertise their claim sites.
+//! They are checked against blocklist feeds in the eth-phishing-detect format: an allowlisted
+//! domain is never flagged, a blocklisted one or any of its subdomains is, and a domain within
+//! edit distance `tolerance` of a fuzzy-list entry is reported as a lookalike.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+use crate::config::DomainReputationConfig;
+use crate::dag::Transaction;
+
+/// Hosts taken from one transaction's calldata
+const MAX_CALLDATA_HOSTS: usize = 16;
+
+/// A feed in the eth-phishing-detect `config.json` format
+#[derive(Debug, Default, Deserialize)]
+struct PhishingFeed {
+    #[serde(default)]
+    tolerance: usize,
+    #[serde(default)]
+    fuzzylist: Vec<String>,
+    #[serde(default)]
+    whitelist: Vec<String>,
+    #[serde(default)]
+    blacklist: Vec<String>,
+}
+
+/// All feeds merged; the largest tolerance of any feed with a fuzzy list applies
+#[derive(Debug, Default)]
+struct DomainLists {
+    blocklist: HashSet<String>,
+    allowlist: HashSet<String>,
+    fuzzylist: Vec<String>,
+    tolerance: usize,
+    /// Content hash, so verdicts cached under older lists can be told apart
+    version: String,
+}
+
+impl DomainLists {
+    fn merge(feeds: Vec<PhishingFeed>) -> Self {
+        let mut lists = Self::default();
+        let mut hasher = blake3::Hasher::new();
+        for feed in feeds {
+            if !feed.fuzzylist.is_empty() {
+                lists.tolerance = lists.tolerance.max(feed.tolerance);
+            }
+            for domain in feed.blacklist {
+                hasher.update(b"b").update(domain.as_bytes());
+                lists.blocklist.insert(domain.to_lowercase());
+            }
+            for domain in feed.whitelist {
+                hasher.update(b"w").update(domain.as_bytes());
+                lists.allowlist.insert(domain.to_lowercase());
+            }
+            for domain in feed.fuzzylist {
+                hasher.update(b"f").update(domain.as_bytes());
+                lists.fuzzylist.push(domain.to_lowercase());
+            }
+        }
+        hasher.update(&(lists.tolerance as u64).to_le_bytes());
+        lists.version = hasher.finalize().to_hex()[..16].to_string();
+        lists
+    }
+}
+
+/// Why a host was flagged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DomainMatch {
+    /// The host or a parent domain is on a blocklist
+    Blocklisted(String),
+    /// The host's domain is a near-miss of the listed one
+    Lookalike(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct DomainFinding {
+    pub host: String,
+    pub matched: DomainMatch,
+    pub score: f32,
+}
+
+impl DomainFinding {
+    pub fn describe(&self) -> String {
+        match &self.matched {
+            DomainMatch::Blocklisted(listed) if *listed == self.host => format!("{} is a blocklisted phishing domain", self.host),
+            DomainMatch::Blocklisted(listed) => format!("{} is under blocklisted phishing domain {}", self.host, listed),
+            DomainMatch::Lookalike(target) => format!("{} imitates {}", self.host, target),
+        }
+    }
+}
+
+pub struct DomainReputation {
+    config: DomainReputationConfig,
+    http: reqwest::Client,
+    lists: parking_lot::RwLock<Arc<DomainLists>>,
+}
+
+impl DomainReputation {
+    /// Loads the feed files now; remote feeds arrive with the first [`refresh`](Self::refresh)
+    pub fn new(config: &DomainReputationConfig) -> Result<Self> {
+        let feeds = config.feed_files
+            .iter()
+            .map(|path| read_feed_file(path))
+            .collect::<Result<Vec<_>>>()?;
+        let lists = DomainLists::merge(feeds);
+        if !lists.blocklist.is_empty() || !lists.fuzzylist.is_empty() {
+            info!("🎣 Loaded {} blocklisted and {} fuzzy-matched phishing domains", lists.blocklist.len(), lists.fuzzylist.len());
+        }
+        
+        Ok(Self {
+            config: config.clone(),
+            http: reqwest::Client::builder().timeout(Duration::from_secs(30)).build()?,
+            lists: parking_lot::RwLock::new(Arc::new(lists)),
+        })
+    }
+    
+    pub fn has_remote_feeds(&self) -> bool {
+        !self.config.feed_urls.is_empty()
+    }
+    
+    pub fn version(&self) -> String {
+        self.lists.read().version.clone()
+    }
+    
+    /// Re-read every feed; returns true when the merged lists changed.
+    ///
+    /// A feed that fails to load keeps the previous lists in service.
+    pub async fn refresh(&self) -> Result<bool> {
+        let mut feeds = Vec::with_capacity(self.config.feed_files.len() + self.config.feed_urls.len());
+        for path in &self.config.feed_files {
+            feeds.push(read_feed_file(path)?);
+        }
+        for url in &self.config.feed_urls {
+            let feed: PhishingFeed = self.http
+                .get(url)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .with_context(|| format!("Failed to fetch phishing feed {}", url))?
+                .json()
+                .await
+                .with_context(|| format!("Phishing feed {} is not in eth-phishing-detect format", url))?;
+            feeds.push(feed);
+        }
+        
+        let lists = DomainLists::merge(feeds);
+        if lists.version == self.version() {
+            return Ok(false);
+        }
+        info!("🎣 Phishing domain lists updated: {} blocklisted, {} allowlisted, {} fuzzy-matched",
+              lists.blocklist.len(), lists.allowlist.len(), lists.fuzzylist.len());
+        *self.lists.write() = Arc::new(lists);
+        Ok(true)
+    }
+    
+    /// Check one host; `None` when it is allowlisted or on no list
+    pub fn check(&self, host: &str) -> Option<DomainFinding> {
+        let host = host.trim_end_matches('.').to_lowercase();
+        let lists = Arc::clone(&self.lists.read());
+        
+        if parent_domains(&host).any(|domain| lists.allowlist.contains(domain)) {
+            return None;
+        }
+        let listed = parent_domains(&host).find(|domain| lists.blocklist.contains(*domain)).map(str::to_string);
+        if let Some(listed) = listed {
+            return Some(DomainFinding {
+                matched: DomainMatch::Blocklisted(listed),
+                score: self.config.blocklist_confidence,
+                host,
+            });
+        }
+        
+        // Lookalikes register a near-miss of the real domain, so only the last two labels are compared
+        let domain = registrable_domain(&host);
+        let target = lists.fuzzylist
+            .iter()
+            .find(|target| target.as_str() != domain && levenshtein(domain, target) <= lists.tolerance)?;
+        Some(DomainFinding {
+            matched: DomainMatch::Lookalike(target.clone()),
+            score: self.config.lookalike_confidence,
+            host,
+        })
+    }
+    
+    /// The most dangerous host the transaction points at
+    pub fn assess(&self, transaction: &Transaction) -> Option<DomainFinding> {
+        transaction_hosts(transaction)
+            .iter()
+            .filter_map(|host| self.check(host))
+            .max_by(|a, b| a.score.total_cmp(&b.score))
+    }
+}
+
+fn read_feed_file(path: &str) -> Result<PhishingFeed> {
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read phishing feed {}", path))?;
+    serde_json::from_str(&content).with_context(|| format!("Phishing feed {} is not in eth-phishing-detect format", path))
+}
+
+/// The host and each parent domain, down to the last two labels
+fn parent_domains(host: &str) -> impl Iterator<Item = &str> {
+    let labels = host.matches('.').count();
+    std::iter::successors(Some(host), |domain| domain.split_once('.').map(|(_, parent)| parent)).take(labels.max(1))
+}
+
+fn registrable_domain(host: &str) -> &str {
+    match host.rmatch_indices('.').nth(1) {
+        Some((i, _)) => &host[i + 1..],
+        None => host,
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + (ca != *cb) as usize;
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Hosts from the dApp origin and from URLs or bare domains written into calldata
+pub fn transaction_hosts(transaction: &Transaction) -> Vec<String> {
+    let mut hosts = Vec::new();
+    if let Some(host) = transaction.origin.as_deref().and_then(url_host) {
+        hosts.push(host);
+    }
+    
+    let text = String::from_utf8_lossy(&transaction.data);
+    let tokens = text.split(|c: char| !c.is_ascii_graphic() || matches!(c, '"' | '\'' | '<' | '>' | '(' | ')' | ','));
+    for token in tokens {
+        if hosts.len() >= MAX_CALLDATA_HOSTS {
+            break;
+        }
+        if let Some(host) = url_host(token).filter(|host| !hosts.contains(host)) {
+            hosts.push(host);
+        }
+    }
+    hosts
+}
+
+/// Host of a URL, or of a bare domain like `claim-rewards.xyz/airdrop`
+fn url_host(text: &str) -> Option<String> {
+    let rest = text
+        .strip_prefix("https://")
+        .or_else(|| text.strip_prefix("http://"))
+        .unwrap_or(text);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?.split(':').next()?.trim_end_matches('.').to_lowercase();
+    
+    let labels: Vec<&str> = host.split('.').collect();
+    let tld = labels.last()?;
+    let valid = labels.len() >= 2
+        && (2..=12).contains(&tld.len())
+        && tld.bytes().all(|b| b.is_ascii_lowercase())
+        && labels.iter().all(|label| {
+            !label.is_empty() && label.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        });
+    valid.then_some(host)
+}
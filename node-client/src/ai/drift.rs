@@ -0,0 +1,241 @@
+//! Drift monitoring for the model's inputs and outputs
+//!
+//! The first `window_size` model verdicts after a model loads become the reference, and later
+//! verdicts fill a sliding window compared against it. Confidence drift is the population
+//! stability index over confidence deciles; feature drift is the largest move of a feature's
+//! window mean, measured in reference standard deviations. Traffic the model was never trained
+//! on shows up here long before anyone grades its verdicts.
+
+use anyhow::Result;
+use prometheus::{Gauge, IntGauge};
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+use tracing::{error, info};
+
+use crate::config::DriftConfig;
+use crate::metrics::register_once;
+
+const CONFIDENCE_BINS: usize = 10;
+
+/// Floor for empty bins, so the index stays finite
+const MIN_BIN_SHARE: f64 = 1e-4;
+
+/// Reference spread below which a feature is treated as constant and not compared
+const MIN_FEATURE_STD: f64 = 1e-6;
+
+#[derive(Debug, Clone)]
+struct DriftReport {
+    confidence_psi: f64,
+    max_feature_shift: f64,
+    /// Position in the model input of the feature that moved most
+    feature_index: Option<usize>,
+    drifted: bool,
+}
+
+/// Per-bin confidence counts and per-feature sums over a set of samples
+#[derive(Debug, Clone, Default)]
+struct Summary {
+    count: u64,
+    bins: [u64; CONFIDENCE_BINS],
+    sums: Vec<f64>,
+    squares: Vec<f64>,
+}
+
+impl Summary {
+    fn add(&mut self, confidence: f32, features: &[f32]) {
+        self.count += 1;
+        self.bins[bin(confidence)] += 1;
+        if self.sums.len() < features.len() {
+            self.sums.resize(features.len(), 0.0);
+            self.squares.resize(features.len(), 0.0);
+        }
+        for (i, &value) in features.iter().enumerate() {
+            self.sums[i] += value as f64;
+            self.squares[i] += (value as f64).powi(2);
+        }
+    }
+    
+    fn remove(&mut self, confidence: f32, features: &[f32]) {
+        self.count -= 1;
+        self.bins[bin(confidence)] -= 1;
+        for (i, &value) in features.iter().enumerate() {
+            self.sums[i] -= value as f64;
+            self.squares[i] -= (value as f64).powi(2);
+        }
+    }
+    
+    fn share(&self, bin: usize) -> f64 {
+        (self.bins[bin] as f64 / self.count.max(1) as f64).max(MIN_BIN_SHARE)
+    }
+    
+    fn mean(&self, feature: usize) -> f64 {
+        self.sums.get(feature).copied().unwrap_or(0.0) / self.count.max(1) as f64
+    }
+    
+    fn std(&self, feature: usize) -> f64 {
+        let mean = self.mean(feature);
+        let mean_square = self.squares.get(feature).copied().unwrap_or(0.0) / self.count.max(1) as f64;
+        (mean_square - mean * mean).max(0.0).sqrt()
+    }
+}
+
+fn bin(confidence: f32) -> usize {
+    ((confidence.clamp(0.0, 1.0) * CONFIDENCE_BINS as f32) as usize).min(CONFIDENCE_BINS - 1)
+}
+
+#[derive(Default)]
+struct DriftState {
+    /// Fills until `window_size` samples, then stays fixed until the model changes
+    reference: Summary,
+    window: Summary,
+    samples: VecDeque<(f32, Vec<f32>)>,
+    since_check: usize,
+    last_report: Option<DriftReport>,
+}
+
+fn psi_gauge() -> prometheus::Result<Gauge> {
+    Gauge::new(
+        "dagshield_model_confidence_psi",
+        "Population stability index of model confidences against the reference window",
+    )
+}
+
+fn feature_shift_gauge() -> prometheus::Result<Gauge> {
+    Gauge::new(
+        "dagshield_model_feature_shift",
+        "Largest feature mean shift against the reference window, in reference standard deviations",
+    )
+}
+
+fn drifted_gauge() -> prometheus::Result<IntGauge> {
+    IntGauge::new("dagshield_model_drifted", "1 while model inputs or outputs have drifted")
+}
+
+pub struct DriftMonitor {
+    config: DriftConfig,
+    state: parking_lot::Mutex<DriftState>,
+    psi_gauge: Gauge,
+    feature_shift_gauge: Gauge,
+    drifted_gauge: IntGauge,
+}
+
+impl DriftMonitor {
+    pub fn new(config: &DriftConfig) -> Result<Self> {
+        static PSI_GAUGE: OnceLock<Gauge> = OnceLock::new();
+        static FEATURE_SHIFT_GAUGE: OnceLock<Gauge> = OnceLock::new();
+        static DRIFTED_GAUGE: OnceLock<IntGauge> = OnceLock::new();
+        Ok(Self::with_gauges(
+            config,
+            register_once(&PSI_GAUGE, psi_gauge)?,
+            register_once(&FEATURE_SHIFT_GAUGE, feature_shift_gauge)?,
+            register_once(&DRIFTED_GAUGE, drifted_gauge)?,
+        ))
+    }
+    
+    /// A monitor whose gauges are not exported, for a detector running next to the node's own
+    pub fn unexported(config: &DriftConfig) -> Result<Self> {
+        Ok(Self::with_gauges(config, psi_gauge()?, feature_shift_gauge()?, drifted_gauge()?))
+    }
+    
+    fn with_gauges(config: &DriftConfig, psi_gauge: Gauge, feature_shift_gauge: Gauge, drifted_gauge: IntGauge) -> Self {
+        Self {
+            config: config.clone(),
+            state: parking_lot::Mutex::new(DriftState::default()),
+            psi_gauge,
+            feature_shift_gauge,
+            drifted_gauge,
+        }
+    }
+    
+    /// Record one model verdict; returns true when this sample changed the drifted state
+    pub fn observe(&self, confidence: f32, features: &[f32]) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+        let window_size = self.config.window_size.max(1);
+        let mut state = self.state.lock();
+        
+        if (state.reference.count as usize) < window_size {
+            state.reference.add(confidence, features);
+            return false;
+        }
+        
+        state.window.add(confidence, features);
+        state.samples.push_back((confidence, features.to_vec()));
+        if state.samples.len() > window_size {
+            if let Some((old_confidence, old_features)) = state.samples.pop_front() {
+                state.window.remove(old_confidence, &old_features);
+            }
+        }
+        
+        // Compared every tenth of a window once the window is full
+        state.since_check += 1;
+        if state.samples.len() < window_size || state.since_check < (window_size / 10).max(1) {
+            return false;
+        }
+        state.since_check = 0;
+        
+        let report = self.compare(&state.reference, &state.window);
+        self.psi_gauge.set(report.confidence_psi);
+        self.feature_shift_gauge.set(report.max_feature_shift);
+        self.drifted_gauge.set(report.drifted as i64);
+        
+        let was_drifted = state.last_report.as_ref().is_some_and(|r| r.drifted);
+        if report.drifted && !was_drifted {
+            error!("🚨 Model drift detected: confidence PSI {:.3} (threshold {}), feature {:?} moved {:.1} std (threshold {}){}",
+                   report.confidence_psi, self.config.psi_threshold, report.feature_index,
+                   report.max_feature_shift, self.config.feature_shift_threshold,
+                   if self.config.fallback_to_rules { ", falling back to rule-based detection" } else { "" });
+        } else if !report.drifted && was_drifted {
+            info!("📉 Model drift subsided: confidence PSI {:.3}, max feature shift {:.1} std",
+                  report.confidence_psi, report.max_feature_shift);
+        }
+        state.last_report = Some(report.clone());
+        report.drifted != was_drifted
+    }
+    
+    fn compare(&self, reference: &Summary, window: &Summary) -> DriftReport {
+        let confidence_psi = (0..CONFIDENCE_BINS)
+            .map(|bin| {
+                let (expected, actual) = (reference.share(bin), window.share(bin));
+                (actual - expected) * (actual / expected).ln()
+            })
+            .sum::<f64>();
+        
+        let (feature_index, max_feature_shift) = (0..reference.sums.len().min(window.sums.len()))
+            .filter(|i| !self.config.ignored_features.contains(i))
+            .filter_map(|i| {
+                let std = reference.std(i);
+                (std > MIN_FEATURE_STD).then(|| (i, (window.mean(i) - reference.mean(i)).abs() / std))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or((None, 0.0), |(i, shift)| (Some(i), shift));
+        
+        DriftReport {
+            confidence_psi,
+            max_feature_shift,
+            feature_index,
+            drifted: confidence_psi > self.config.psi_threshold
+                || max_feature_shift > self.config.feature_shift_threshold,
+        }
+    }
+    
+    pub fn is_drifted(&self) -> bool {
+        self.state.lock().last_report.as_ref().is_some_and(|r| r.drifted)
+    }
+    
+    /// Whether detection should bypass the model.
+    ///
+    /// The model produces no new samples while bypassed, so this holds until the model changes.
+    pub fn should_fall_back(&self) -> bool {
+        self.config.fallback_to_rules && self.is_drifted()
+    }
+    
+    /// Start over with a fresh reference, after the model changed
+    pub fn reset(&self) {
+        *self.state.lock() = DriftState::default();
+        self.psi_gauge.set(0.0);
+        self.feature_shift_gauge.set(0.0);
+        self.drifted_gauge.set(0);
+    }
+}
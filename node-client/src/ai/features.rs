@@ -0,0 +1,115 @@
+//! Pluggable feature extraction for the ONNX model
+//!
+//! The model input is the concatenation of every registered extractor's output, in registration
+//! order, padded or truncated to `ai.max_sequence_length`. Each extractor owns a fixed-width
+//! slice of that vector, so adding one never shifts the positions of the features before it.
+
+use std::sync::Arc;
+
+use super::token_flow::{self, TokenFlow};
+use crate::dag::Transaction;
+
+pub trait FeatureExtractor: Send + Sync {
+    /// Stable name, part of the detection pipeline fingerprint
+    fn name(&self) -> &str;
+    
+    /// Number of values this extractor contributes; must not change once registered
+    fn dimensions(&self) -> usize;
+    
+    /// Append this extractor's features for `transaction` to `features`
+    fn extract(&self, transaction: &Transaction, features: &mut Vec<f32>);
+}
+
+/// Size, chain, address and calldata shape, plus dependency features
+pub struct MetadataFeatures;
+
+impl FeatureExtractor for MetadataFeatures {
+    fn name(&self) -> &str {
+        "metadata"
+    }
+    
+    fn dimensions(&self) -> usize {
+        9
+    }
+    
+    fn extract(&self, transaction: &Transaction, features: &mut Vec<f32>) {
+        // Transaction metadata features
+        features.push(transaction.data.len() as f32);
+        features.push(transaction.timestamp as f32);
+        features.push(transaction.chain_id as f32);
+        
+        // Address features (simplified)
+        features.push(transaction.from.len() as f32);
+        features.push(transaction.to.len() as f32);
+        features.push(transaction.target_address.len() as f32);
+        
+        // Data pattern features
+        features.push(calculate_entropy(&transaction.data));
+        
+        // Behavioral features
+        features.push(if transaction.dependencies.is_empty() { 0.0 } else { 1.0 });
+        features.push(transaction.dependencies.len() as f32);
+    }
+}
+
+/// Native value and token transfer features, see [`TokenFlow`]
+pub struct TokenFlowFeatures;
+
+impl FeatureExtractor for TokenFlowFeatures {
+    fn name(&self) -> &str {
+        "token_flow"
+    }
+    
+    fn dimensions(&self) -> usize {
+        token_flow::FEATURE_COUNT
+    }
+    
+    fn extract(&self, transaction: &Transaction, features: &mut Vec<f32>) {
+        let flow = TokenFlow::extract(transaction);
+        features.extend_from_slice(&flow.features(&transaction.from));
+    }
+}
+
+/// The extractors every detector starts with
+pub fn default_extractors() -> Vec<Arc<dyn FeatureExtractor>> {
+    vec![Arc::new(MetadataFeatures), Arc::new(TokenFlowFeatures)]
+}
+
+/// Run `extractors` in order, holding each to its declared width
+pub fn extract_all(extractors: &[Arc<dyn FeatureExtractor>], transaction: &Transaction, input_size: usize) -> Vec<f32> {
+    let mut features = Vec::with_capacity(input_size);
+    
+    for extractor in extractors {
+        let start = features.len();
+        extractor.extract(transaction, &mut features);
+        // A misbehaving extractor must not shift the slices of those after it
+        features.resize(start + extractor.dimensions(), 0.0);
+    }
+    
+    // Pad or truncate to the model's input size
+    features.resize(input_size, 0.0);
+    features
+}
+
+fn calculate_entropy(data: &[u8]) -> f32 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    
+    let mut counts = [0u32; 256];
+    for &byte in data {
+        counts[byte as usize] += 1;
+    }
+    
+    let len = data.len() as f32;
+    let mut entropy = 0.0;
+    
+    for &count in &counts {
+        if count > 0 {
+            let p = count as f32 / len;
+            entropy -= p * p.log2();
+        }
+    }
+    
+    entropy
+}
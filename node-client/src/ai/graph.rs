@@ -0,0 +1,414 @@
+//! Address interaction graph: who calls and funds whom, and the features derived from it
+//!
+//! Every processed transaction adds caller -> callee edges, and token or native value moved to a
+//! never-seen address records who funded it. Funding ancestry is followed back a few hops to
+//! spot addresses bankrolled by mixers; fresh-address bursts and wide fan-out are typical of
+//! drainers spraying stolen funds. Known-bad addresses, configured or carrying network-verified
+//! alerts, are located a few hops out in either direction: drainer networks reuse the same
+//! collectors and funders, so a fresh address next to one is rarely a coincidence.
+//!
+//! The busiest targets get their target-side features precomputed in the background, so their
+//! transactions only pay for the sender-side lookups during detection.
+
+use anyhow::Result;
+use dashmap::{DashMap, DashSet};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use super::features::FeatureExtractor;
+use super::token_flow::TokenFlow;
+use crate::alert_cache::VerifiedAlertCache;
+use crate::config::AddressGraphConfig;
+use crate::dag::Transaction;
+use crate::storage::NodeStorage;
+
+pub const ADDRESS_GRAPH_NAMESPACE: &str = "address_graph";
+
+/// Addresses expanded per known-bad search, so a hub with thousands of edges stays cheap
+const MAX_CLUSTER_VISITS: usize = 512;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Edge {
+    pub count: u64,
+    pub first_seen: u64,
+    pub last_seen: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AddressNode {
+    pub first_seen: u64,
+    pub last_seen: u64,
+    /// The address that first sent this one value, if seen
+    pub funded_by: Option<String>,
+    pub outgoing: HashMap<String, Edge>,
+    pub incoming: HashMap<String, Edge>,
+}
+
+impl AddressNode {
+    fn new(now: u64) -> Self {
+        Self {
+            first_seen: now,
+            last_seen: now,
+            ..Default::default()
+        }
+    }
+}
+
+/// Target-side features, which depend on the target's history rather than the transaction
+#[derive(Debug, Clone)]
+struct TargetProfile {
+    first_seen: Option<u64>,
+    fan_in: usize,
+    mixer_proximity: f32,
+    bad_proximity: f32,
+}
+
+/// The interaction graph over the configured lookback window, cached in memory and
+/// persisted to `NodeStorage` on every flush
+pub struct AddressGraph {
+    config: AddressGraphConfig,
+    storage: Arc<NodeStorage>,
+    nodes: DashMap<String, AddressNode>,
+    dirty: DashSet<String>,
+    mixers: HashSet<String>,
+    known_bad: HashSet<String>,
+    /// Addresses with network-verified alerts count as known-bad too
+    alert_cache: OnceLock<Arc<VerifiedAlertCache>>,
+    /// Transactions per target since hot targets were last ranked
+    traffic: DashMap<String, u64>,
+    /// Profiles of the busiest targets, so their transactions skip the ancestry walk
+    hot_targets: DashMap<String, TargetProfile>,
+}
+
+impl AddressGraph {
+    pub fn new(config: &AddressGraphConfig, storage: Arc<NodeStorage>) -> Self {
+        Self {
+            config: config.clone(),
+            storage,
+            nodes: DashMap::new(),
+            dirty: DashSet::new(),
+            mixers: config.mixer_addresses.iter().map(|a| a.to_lowercase()).collect(),
+            known_bad: config.known_bad_addresses.iter().map(|a| a.to_lowercase()).collect(),
+            alert_cache: OnceLock::new(),
+            traffic: DashMap::new(),
+            hot_targets: DashMap::new(),
+        }
+    }
+    
+    pub fn attach_alert_cache(&self, cache: Arc<VerifiedAlertCache>) {
+        if self.alert_cache.set(cache).is_err() {
+            warn!("⚠️ Alert cache already attached to address graph");
+        }
+    }
+    
+    fn is_known_bad(&self, address: &str) -> bool {
+        self.known_bad.contains(address)
+            || self.alert_cache.get().is_some_and(|cache| !cache.lookup(address).is_empty())
+    }
+    
+    /// The node for `address`, loading it from storage into the cache on first use
+    fn node(&self, address: &str) -> Option<AddressNode> {
+        if let Some(node) = self.nodes.get(address) {
+            return Some(node.clone());
+        }
+        match self.storage.get::<AddressNode>(ADDRESS_GRAPH_NAMESPACE, address) {
+            Ok(Some(node)) => {
+                self.nodes.insert(address.to_string(), node.clone());
+                Some(node)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                warn!("Failed to load address graph node {}: {}", address, e);
+                None
+            }
+        }
+    }
+    
+    fn update(&self, address: &str, now: u64, apply: impl FnOnce(&mut AddressNode)) {
+        if !self.nodes.contains_key(address) {
+            // Pull the persisted node in first so its history isn't overwritten
+            self.node(address);
+        }
+        let mut node = self.nodes.entry(address.to_string()).or_insert_with(|| AddressNode::new(now));
+        node.last_seen = now;
+        apply(&mut node);
+        self.dirty.insert(address.to_string());
+    }
+    
+    /// Add a processed transaction's calls and value flows to the graph
+    pub fn record(&self, transaction: &Transaction) {
+        let now = transaction.timestamp;
+        let from = transaction.from.to_lowercase();
+        let max_edges = self.config.max_edges_per_address;
+        
+        if self.config.hot_targets > 0 && !transaction.target_address.is_empty() {
+            *self.traffic.entry(transaction.target_address.to_lowercase()).or_default() += 1;
+        }
+        
+        let mut links: Vec<(String, String, bool)> = vec![];
+        for callee in [&transaction.to, &transaction.target_address] {
+            let callee = callee.to_lowercase();
+            if !callee.is_empty() && callee != from && !links.iter().any(|(_, to, _)| *to == callee) {
+                links.push((from.clone(), callee, false));
+            }
+        }
+        // Native value funds the direct recipient; token transfers fund their recipients
+        if !transaction.value.is_zero() {
+            let to = transaction.to.to_lowercase();
+            links.retain(|(_, callee, _)| *callee != to);
+            links.push((from.clone(), to, true));
+        }
+        for transfer in TokenFlow::extract(transaction).transfers {
+            links.push((format!("{:?}", transfer.from), format!("{:?}", transfer.to), true));
+        }
+        
+        for (source, target, funds) in links {
+            let is_new = self.node(&target).is_none();
+            self.update(&source, now, |node| add_edge(&mut node.outgoing, &target, now, max_edges));
+            self.update(&target, now, |node| {
+                add_edge(&mut node.incoming, &source, now, max_edges);
+                if funds && is_new {
+                    node.funded_by = Some(source.clone());
+                }
+            });
+        }
+    }
+    
+    /// Hops back through funding ancestry to a mixer, if one is within `ancestry_depth`
+    fn hops_to_mixer(&self, address: &str) -> Option<usize> {
+        let mut current = address.to_string();
+        let mut visited = HashSet::new();
+        
+        for hop in 0..=self.config.ancestry_depth {
+            if self.mixers.contains(&current) {
+                return Some(hop);
+            }
+            if !visited.insert(current.clone()) {
+                return None;
+            }
+            current = self.node(&current)?.funded_by?;
+        }
+        None
+    }
+    
+    /// Hops along recent edges, in either direction, to the nearest known-bad address
+    fn hops_to_known_bad(&self, address: &str, window_start: u64) -> Option<usize> {
+        let mut queue = VecDeque::from([(address.to_string(), 0)]);
+        let mut visited = HashSet::from([address.to_string()]);
+        
+        while let Some((current, hops)) = queue.pop_front() {
+            if self.is_known_bad(&current) {
+                return Some(hops);
+            }
+            if hops == self.config.bad_cluster_depth || visited.len() >= MAX_CLUSTER_VISITS {
+                continue;
+            }
+            let Some(node) = self.node(&current) else {
+                continue;
+            };
+            for (neighbour, edge) in node.outgoing.iter().chain(node.incoming.iter()) {
+                if edge.last_seen >= window_start && visited.insert(neighbour.clone()) {
+                    queue.push_back((neighbour.clone(), hops + 1));
+                }
+            }
+        }
+        None
+    }
+    
+    fn target_profile(&self, address: &str, now: u64) -> TargetProfile {
+        let window_start = now.saturating_sub(self.config.lookback_hours * 3600);
+        let target = self.node(address);
+        TargetProfile {
+            first_seen: target.as_ref().map(|n| n.first_seen),
+            fan_in: target.as_ref().map_or(0, |n| n.incoming.values().filter(|e| e.last_seen >= window_start).count()),
+            mixer_proximity: proximity(self.hops_to_mixer(address)),
+            bad_proximity: proximity(self.hops_to_known_bad(address, window_start)),
+        }
+    }
+    
+    /// Rank targets by traffic since the last round and precompute profiles for the busiest
+    pub fn refresh_hot_targets(&self) {
+        let mut traffic: Vec<(String, u64)> = self.traffic.iter().map(|e| (e.key().clone(), *e.value())).collect();
+        self.traffic.clear();
+        traffic.sort_by_key(|(_, count)| Reverse(*count));
+        traffic.truncate(self.config.hot_targets);
+        
+        let now = now_secs();
+        let profiles: HashMap<String, TargetProfile> = traffic
+            .into_iter()
+            .map(|(address, _)| {
+                let profile = self.target_profile(&address, now);
+                (address, profile)
+            })
+            .collect();
+        self.hot_targets.retain(|address, _| profiles.contains_key(address));
+        let hot = profiles.len();
+        for (address, profile) in profiles {
+            self.hot_targets.insert(address, profile);
+        }
+        debug!("🔥 Precomputed graph features for {} hot targets", hot);
+    }
+    
+    fn features(&self, transaction: &Transaction) -> [f32; GRAPH_FEATURE_COUNT] {
+        let now = transaction.timestamp;
+        let window_start = now.saturating_sub(self.config.lookback_hours * 3600);
+        let burst_start = now.saturating_sub(self.config.burst_window_secs);
+        
+        let sender = self.node(&transaction.from.to_lowercase());
+        let target_address = transaction.target_address.to_lowercase();
+        let target = match self.hot_targets.get(&target_address) {
+            Some(profile) => profile.clone(),
+            None => self.target_profile(&target_address, now),
+        };
+        
+        let age_hours = |first_seen: Option<u64>| {
+            first_seen.map_or(0.0, |first_seen| (now.saturating_sub(first_seen) as f32 / 3600.0).ln_1p())
+        };
+        let in_window = |edges: &HashMap<String, Edge>| edges.values().filter(|e| e.last_seen >= window_start).count();
+        
+        let fan_out = sender.as_ref().map_or(0, |n| in_window(&n.outgoing));
+        // Counterparties of the sender that had never been seen before it reached them
+        let new_address_burst = sender.as_ref().map_or(0, |n| {
+            n.outgoing
+                .iter()
+                .filter(|(_, edge)| edge.first_seen >= burst_start)
+                .filter(|(address, edge)| self.node(address).is_some_and(|c| c.first_seen >= edge.first_seen))
+                .count()
+        });
+        let bad_counterparties = sender.as_ref().map_or(0, |n| {
+            n.outgoing
+                .iter()
+                .chain(n.incoming.iter())
+                .filter(|(address, edge)| edge.last_seen >= window_start && self.is_known_bad(address))
+                .count()
+        });
+        let repeat_calls = sender
+            .as_ref()
+            .and_then(|n| n.outgoing.get(&target_address))
+            .map_or(0, |e| e.count);
+        
+        [
+            age_hours(sender.as_ref().map(|n| n.first_seen)),
+            age_hours(target.first_seen),
+            fan_out as f32,
+            target.fan_in as f32,
+            new_address_burst as f32,
+            proximity(self.hops_to_mixer(&transaction.from.to_lowercase())),
+            target.mixer_proximity,
+            (repeat_calls as f32).ln_1p(),
+            proximity(self.hops_to_known_bad(&transaction.from.to_lowercase(), window_start)),
+            target.bad_proximity,
+            bad_counterparties as f32,
+        ]
+    }
+    
+    /// Persist changed nodes, and drop edges and nodes that fell out of the lookback window
+    pub fn flush(&self) -> Result<()> {
+        let window_start = now_secs().saturating_sub(self.config.lookback_hours * 3600);
+        
+        let mut batch = self.storage.batch();
+        let dirty: Vec<String> = self.dirty.iter().map(|a| a.clone()).collect();
+        for address in &dirty {
+            self.dirty.remove(address);
+            let Some(mut node) = self.nodes.get_mut(address) else {
+                continue;
+            };
+            node.outgoing.retain(|_, e| e.last_seen >= window_start);
+            node.incoming.retain(|_, e| e.last_seen >= window_start);
+            batch.put(ADDRESS_GRAPH_NAMESPACE, address, &*node)?;
+        }
+        // Committed before anything leaves the cache, so a reload never sees older state
+        self.storage.commit(batch)?;
+        
+        // Quiet addresses leave the cache, and storage too once they are out of the window
+        let over_capacity = self.nodes.len().saturating_sub(self.config.max_cached_addresses);
+        let mut evictable: Vec<(String, u64)> = self.nodes
+            .iter()
+            .filter(|n| !self.dirty.contains(n.key()))
+            .map(|n| (n.key().clone(), n.last_seen))
+            .collect();
+        evictable.sort_by_key(|(_, last_seen)| *last_seen);
+        
+        let mut batch = self.storage.batch();
+        let mut expired = 0;
+        for (i, (address, last_seen)) in evictable.into_iter().enumerate() {
+            if last_seen >= window_start && i >= over_capacity {
+                break;
+            }
+            self.nodes.remove(&address);
+            if last_seen < window_start {
+                batch.delete(ADDRESS_GRAPH_NAMESPACE, &address);
+                expired += 1;
+            }
+        }
+        self.storage.commit(batch)?;
+        
+        debug!("🕸️ Flushed {} address graph nodes, expired {}", dirty.len(), expired);
+        Ok(())
+    }
+    
+    pub async fn start(&self) -> Result<()> {
+        info!("🕸️ Address graph tracking {}h of interactions", self.config.lookback_hours);
+        
+        let mut flush = tokio::time::interval(Duration::from_secs(self.config.flush_interval_secs.max(1)));
+        let mut hot_targets = tokio::time::interval(Duration::from_secs(self.config.hot_target_refresh_secs.max(1)));
+        loop {
+            tokio::select! {
+                _ = flush.tick() => {
+                    if let Err(e) = self.flush() {
+                        warn!("Failed to flush address graph: {}", e);
+                    }
+                }
+                _ = hot_targets.tick(), if self.config.hot_targets > 0 => self.refresh_hot_targets(),
+            }
+        }
+    }
+}
+
+fn add_edge(edges: &mut HashMap<String, Edge>, address: &str, now: u64, max_edges: usize) {
+    if let Some(edge) = edges.get_mut(address) {
+        edge.count += 1;
+        edge.last_seen = now;
+        return;
+    }
+    if edges.len() >= max_edges {
+        // Make room by forgetting the stalest counterparty
+        if let Some(stalest) = edges.iter().min_by_key(|(_, e)| e.last_seen).map(|(a, _)| a.clone()) {
+            edges.remove(&stalest);
+        }
+    }
+    edges.insert(address.to_string(), Edge { count: 1, first_seen: now, last_seen: now });
+}
+
+/// 1 at the address itself, falling with each hop; 0 when out of reach
+fn proximity(hops: Option<usize>) -> f32 {
+    hops.map_or(0.0, |hops| 1.0 / (hops as f32 + 1.0))
+}
+
+const GRAPH_FEATURE_COUNT: usize = 11;
+
+/// Sender and target age, fan-out and fan-in, new-address bursts, mixer funding, repeat calls,
+/// and sender and target proximity to known-bad addresses
+pub struct GraphFeatures(pub Arc<AddressGraph>);
+
+impl FeatureExtractor for GraphFeatures {
+    fn name(&self) -> &str {
+        "address_graph"
+    }
+    
+    fn dimensions(&self) -> usize {
+        GRAPH_FEATURE_COUNT
+    }
+    
+    fn extract(&self, transaction: &Transaction, features: &mut Vec<f32>) {
+        features.extend_from_slice(&self.0.features(transaction));
+    }
+}
+
+fn now_secs() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
@@ -0,0 +1,186 @@
+//! Streaming detection: processed transactions flow from the DAG through a pool of detection
+//! workers to the node, over bounded queues
+//!
+//! Each queue applies backpressure to the stage before it. When the node falls behind on
+//! verdicts the workers wait to hand theirs over; when inference falls behind the ingestion
+//! queue fills, and [`DetectionIngest::submit`] holds the DAG processor on the transaction it is
+//! handing over until a worker frees a slot. Urgent transactions, those touching watched
+//! addresses, have a queue of their own that workers always drain first.
+
+use anyhow::{bail, Result};
+use prometheus::{IntCounter, IntGaugeVec, Opts};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::Mutex;
+
+use super::{ThreatDetectionResult, ThreatDetector};
+use crate::config::DetectionPipelineConfig;
+use crate::dag::Transaction;
+use crate::metrics::register_once;
+
+/// Decides which transactions jump the ingestion queue
+pub type UrgencyCheck = Arc<dyn Fn(&Transaction) -> bool + Send + Sync>;
+
+pub struct Detection {
+    pub transaction: Transaction,
+    pub result: Result<ThreatDetectionResult>,
+}
+
+/// The DAG processor's end of the pipeline
+#[derive(Clone)]
+pub struct DetectionIngest {
+    urgent: mpsc::Sender<Transaction>,
+    normal: mpsc::Sender<Transaction>,
+    is_urgent: Option<UrgencyCheck>,
+    stalls: IntCounter,
+    depth: IntGaugeVec,
+}
+
+impl DetectionIngest {
+    /// Queue a processed transaction for detection, waiting while the queue is full
+    pub async fn submit(&self, transaction: Transaction) -> Result<()> {
+        let queue = match &self.is_urgent {
+            Some(is_urgent) if is_urgent(&transaction) => &self.urgent,
+            _ => &self.normal,
+        };
+        match queue.try_send(transaction) {
+            Ok(()) => {}
+            Err(TrySendError::Full(transaction)) => {
+                self.stalls.inc();
+                if queue.send(transaction).await.is_err() {
+                    bail!("Detection pipeline stopped");
+                }
+            }
+            Err(TrySendError::Closed(_)) => bail!("Detection pipeline stopped"),
+        }
+        self.depth.with_label_values(&["ingestion"]).set(self.queued() as i64);
+        Ok(())
+    }
+    
+    /// Transactions waiting for a worker
+    pub fn queued(&self) -> usize {
+        [&self.urgent, &self.normal]
+            .iter()
+            .map(|queue| queue.max_capacity() - queue.capacity())
+            .sum()
+    }
+}
+
+pub struct DetectionPipeline {
+    detector: Arc<ThreatDetector>,
+    workers: usize,
+    ingest: DetectionIngest,
+    urgent: Mutex<mpsc::Receiver<Transaction>>,
+    normal: Mutex<mpsc::Receiver<Transaction>>,
+    results_tx: mpsc::Sender<Detection>,
+    results: Mutex<mpsc::Receiver<Detection>>,
+    /// Taken off the ingestion queue and not yet handed to the node
+    in_flight: AtomicUsize,
+}
+
+impl DetectionPipeline {
+    pub(super) fn new(
+        detector: Arc<ThreatDetector>,
+        config: &DetectionPipelineConfig,
+        default_workers: usize,
+        is_urgent: Option<UrgencyCheck>,
+    ) -> Result<Self> {
+        let capacity = config.ingestion_queue.max(1);
+        let (urgent_tx, urgent) = mpsc::channel(capacity);
+        let (normal_tx, normal) = mpsc::channel(capacity);
+        let (results_tx, results) = mpsc::channel(config.results_queue.max(1));
+        
+        static STALLS: OnceLock<IntCounter> = OnceLock::new();
+        static DEPTH: OnceLock<IntGaugeVec> = OnceLock::new();
+        let stalls = register_once(&STALLS, || {
+            IntCounter::new(
+                "dagshield_detection_backpressure_total",
+                "Transactions the DAG processor waited to hand over because the detection queue was full",
+            )
+        })?;
+        let depth = register_once(&DEPTH, || {
+            IntGaugeVec::new(
+                Opts::new("dagshield_detection_queue_depth", "Entries waiting in the detection pipeline's queues"),
+                &["queue"],
+            )
+        })?;
+        
+        Ok(Self {
+            detector,
+            workers: if config.workers == 0 { default_workers.max(1) } else { config.workers },
+            ingest: DetectionIngest {
+                urgent: urgent_tx,
+                normal: normal_tx,
+                is_urgent,
+                stalls,
+                depth,
+            },
+            urgent: Mutex::new(urgent),
+            normal: Mutex::new(normal),
+            results_tx,
+            results: Mutex::new(results),
+            in_flight: AtomicUsize::new(0),
+        })
+    }
+    
+    pub fn ingest(&self) -> DetectionIngest {
+        self.ingest.clone()
+    }
+    
+    /// Workers to run, each as its own [`run_worker`](Self::run_worker) task
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+    
+    /// Detect queued transactions one at a time, until the task is aborted
+    pub async fn run_worker(&self) {
+        while let Some(transaction) = self.next_transaction().await {
+            // A panicking detection still counts its transaction out
+            let in_flight = InFlight(&self.in_flight);
+            let result = self.detector.detect_threat(&transaction).await;
+            if self.results_tx.send(Detection { transaction, result }).await.is_err() {
+                return;
+            }
+            // Counted out by `next_detection` now
+            std::mem::forget(in_flight);
+            let waiting = self.results_tx.max_capacity() - self.results_tx.capacity();
+            self.ingest.depth.with_label_values(&["results"]).set(waiting as i64);
+        }
+    }
+    
+    /// The next verdict, waiting for one
+    pub async fn next_detection(&self) -> Option<Detection> {
+        let detection = self.results.lock().await.recv().await?;
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+        Some(detection)
+    }
+    
+    /// Nothing queued, being detected or waiting for the node
+    pub fn is_idle(&self) -> bool {
+        self.ingest.queued() == 0 && self.in_flight.load(Ordering::Relaxed) == 0
+    }
+    
+    async fn next_transaction(&self) -> Option<Transaction> {
+        // One worker waits on the queues at a time, locking them in the same order
+        let mut urgent = self.urgent.lock().await;
+        let mut normal = self.normal.lock().await;
+        let transaction = tokio::select! {
+            biased;
+            Some(transaction) = urgent.recv() => transaction,
+            Some(transaction) = normal.recv() => transaction,
+            else => return None,
+        };
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        self.ingest.depth.with_label_values(&["ingestion"]).set(self.ingest.queued() as i64);
+        Some(transaction)
+    }
+}
+
+struct InFlight<'a>(&'a AtomicUsize);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,531 @@
+//! Named model versions kept side by side, with shadow evaluation of a candidate
+//!
+//! Registered versions are copies of model files under the registry directory, their metadata
+//! and stats in the `model_registry` namespace. The active version is the one at `model_path`,
+//! serving verdicts; a candidate runs in shadow on the same features without its verdicts being
+//! used. Feedback grades both, so operators can compare them before promoting the candidate, and
+//! roll back to the previously active version when a promotion disappoints.
+//!
+//! Only the global model is tracked; per-chain models are deployed outside the registry.
+
+use anyhow::{anyhow, bail, Context, Result};
+use axum::{extract::Path, http::StatusCode, response::IntoResponse, routing::{delete, get, post}, Json, Router};
+use lru::LruCache;
+use ort::session::Session;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use super::{ThreatDetectionResult, ThreatDetector, RECENT_VERDICTS};
+use crate::config::ModelRegistryConfig;
+use crate::dag::Transaction;
+use crate::rollback::{ArtifactGuard, ArtifactKind};
+use crate::storage::NodeStorage;
+
+pub const MODEL_REGISTRY_NAMESPACE: &str = "model_registry";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionRole {
+    Active,
+    Candidate,
+    Inactive,
+}
+
+/// Verdicts a version produced while active
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VersionStats {
+    pub predictions: u64,
+    pub flagged: u64,
+    /// Predictions graded by feedback
+    pub graded: u64,
+    pub correct: u64,
+}
+
+impl VersionStats {
+    pub fn accuracy(&self) -> Option<f64> {
+        (self.graded > 0).then(|| self.correct as f64 / self.graded as f64)
+    }
+}
+
+/// A candidate's shadow verdicts since it was last made candidate
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShadowStats {
+    pub started_at: u64,
+    pub predictions: u64,
+    pub flagged: u64,
+    /// Shadow predictions the active model flagged alike
+    pub agreements: u64,
+    pub graded: u64,
+    pub correct: u64,
+    /// The active model's correct verdicts on the same graded transactions
+    pub active_correct: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelVersion {
+    pub name: String,
+    /// blake3 of the model file
+    pub hash: String,
+    pub registered_at: u64,
+    pub role: VersionRole,
+    /// Unix time it last became active
+    pub activated_at: Option<u64>,
+    pub stats: VersionStats,
+    pub shadow: Option<ShadowStats>,
+}
+
+impl ModelVersion {
+    fn new(name: &str, hash: String) -> Self {
+        Self {
+            name: name.to_string(),
+            hash,
+            registered_at: now_secs(),
+            role: VersionRole::Inactive,
+            activated_at: None,
+            stats: VersionStats::default(),
+            shadow: None,
+        }
+    }
+}
+
+/// The candidate against the active model, on the transactions both were graded on
+#[derive(Debug, Clone, Serialize)]
+pub struct ShadowComparison {
+    pub candidate: String,
+    pub active: Option<String>,
+    pub graded: u64,
+    pub candidate_accuracy: Option<f64>,
+    pub active_accuracy: Option<f64>,
+    /// Share of shadow predictions both models flagged alike
+    pub agreement: Option<f64>,
+    /// Graded `min_graded` times and less accurate than the active model
+    pub underperforming: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegistryReport {
+    pub versions: Vec<ModelVersion>,
+    pub shadow: Option<ShadowComparison>,
+}
+
+pub struct ModelRegistry {
+    config: ModelRegistryConfig,
+    storage: Arc<NodeStorage>,
+    versions: parking_lot::Mutex<BTreeMap<String, ModelVersion>>,
+    /// The candidate's name and session, built once when shadowing starts
+    candidate: parking_lot::RwLock<Option<(String, Arc<Session>)>>,
+    /// Active version and whether it flagged each recent transaction, until feedback grades it
+    live_verdicts: parking_lot::Mutex<LruCache<String, (String, bool)>>,
+    /// Candidate, its shadow verdict and the active model's on each recent transaction, until graded
+    shadow_verdicts: parking_lot::Mutex<LruCache<String, (String, bool, bool)>>,
+}
+
+impl ModelRegistry {
+    pub fn open(config: &ModelRegistryConfig, storage: Arc<NodeStorage>) -> Result<Self> {
+        std::fs::create_dir_all(&config.dir)
+            .with_context(|| format!("Failed to create model registry directory {}", config.dir))?;
+        let versions: BTreeMap<_, _> = storage.scan::<ModelVersion>(MODEL_REGISTRY_NAMESPACE)?.into_iter().collect();
+        info!("📦 Model registry holds {} versions", versions.len());
+        
+        let pending = NonZeroUsize::new(RECENT_VERDICTS).unwrap_or(NonZeroUsize::MIN);
+        Ok(Self {
+            config: config.clone(),
+            storage,
+            versions: parking_lot::Mutex::new(versions),
+            candidate: parking_lot::RwLock::new(None),
+            live_verdicts: parking_lot::Mutex::new(LruCache::new(pending)),
+            shadow_verdicts: parking_lot::Mutex::new(LruCache::new(pending)),
+        })
+    }
+    
+    pub fn report(&self) -> RegistryReport {
+        RegistryReport {
+            versions: self.versions.lock().values().cloned().collect(),
+            shadow: self.comparison(),
+        }
+    }
+    
+    pub fn comparison(&self) -> Option<ShadowComparison> {
+        let versions = self.versions.lock();
+        let candidate = versions.values().find(|version| version.role == VersionRole::Candidate)?;
+        let shadow = candidate.shadow.clone().unwrap_or_default();
+        let ratio = |count: u64, total: u64| (total > 0).then(|| count as f64 / total as f64);
+        Some(ShadowComparison {
+            candidate: candidate.name.clone(),
+            active: versions.values().find(|version| version.role == VersionRole::Active).map(|version| version.name.clone()),
+            graded: shadow.graded,
+            candidate_accuracy: ratio(shadow.correct, shadow.graded),
+            active_accuracy: ratio(shadow.active_correct, shadow.graded),
+            agreement: ratio(shadow.agreements, shadow.predictions),
+            underperforming: shadow.graded >= self.config.min_graded && shadow.correct < shadow.active_correct,
+        })
+    }
+    
+    /// Copy a model file into the registry under `name`; it must load and not be quarantined
+    pub fn register(&self, detector: &ThreatDetector, name: &str, source: &str) -> Result<ModelVersion> {
+        validate_name(name)?;
+        if self.versions.lock().contains_key(name) {
+            bail!("Model version {} is already registered", name);
+        }
+        let bytes = std::fs::read(source).with_context(|| format!("Failed to read model {}", source))?;
+        if let Some(guard) = detector.artifact_guard.get() {
+            guard.ensure_not_quarantined(ArtifactKind::Model, &bytes)?;
+        }
+        detector.build_session(&bytes).with_context(|| format!("Model {} doesn't load", source))?;
+        
+        let version = ModelVersion::new(name, ArtifactGuard::artifact_hash(&bytes));
+        let mut versions = self.versions.lock();
+        if versions.contains_key(name) {
+            bail!("Model version {} is already registered", name);
+        }
+        std::fs::write(self.version_file(name), &bytes)?;
+        self.save(&version)?;
+        versions.insert(name.to_string(), version.clone());
+        
+        info!("📦 Registered model version {} ({})", name, &version.hash[..12]);
+        Ok(version)
+    }
+    
+    /// Run `name` in shadow of the active model, replacing any current candidate
+    pub fn start_shadow(&self, detector: &ThreatDetector, name: &str) -> Result<()> {
+        let bytes = self.read_version(name)?;
+        let session = Arc::new(detector.build_session(&bytes)?);
+        
+        let mut versions = self.versions.lock();
+        match versions.get(name).map(|version| version.role) {
+            Some(VersionRole::Active) => bail!("Model version {} is already active", name),
+            Some(_) => {}
+            None => bail!("No model version {}", name),
+        }
+        for version in versions.values_mut().filter(|version| version.role == VersionRole::Candidate) {
+            version.role = VersionRole::Inactive;
+            self.save(version)?;
+        }
+        let version = versions.get_mut(name).expect("checked above");
+        version.role = VersionRole::Candidate;
+        version.shadow = Some(ShadowStats { started_at: now_secs(), ..Default::default() });
+        self.save(version)?;
+        *self.candidate.write() = Some((name.to_string(), session));
+        
+        info!("🌗 Shadowing model version {} against the active model", name);
+        Ok(())
+    }
+    
+    /// Stop shadowing; `false` when there was no candidate
+    pub fn stop_shadow(&self) -> Result<bool> {
+        let Some((name, _)) = self.candidate.write().take() else {
+            return Ok(false);
+        };
+        if let Some(version) = self.versions.lock().get_mut(&name) {
+            version.role = VersionRole::Inactive;
+            self.save(version)?;
+        }
+        info!("🌗 Stopped shadowing model version {}", name);
+        Ok(true)
+    }
+    
+    /// Deploy `name` to `model_path` and hot-reload it
+    pub fn promote(&self, detector: &ThreatDetector, name: &str) -> Result<()> {
+        if self.versions.lock().get(name).is_some_and(|version| version.role == VersionRole::Active) {
+            bail!("Model version {} is already active", name);
+        }
+        let bytes = self.read_version(name)?;
+        let model_path = &detector.config.model_path;
+        if let Some(guard) = detector.artifact_guard.get() {
+            guard.ensure_not_quarantined(ArtifactKind::Model, &bytes)?;
+            let previous = std::fs::read(model_path).ok();
+            write_atomically(model_path, &bytes)?;
+            guard.artifact_applied(ArtifactKind::Model, &bytes, previous.as_deref())?;
+        } else {
+            write_atomically(model_path, &bytes)?;
+        }
+        
+        self.activate(&mut self.versions.lock(), name)?;
+        detector.request_model_reload();
+        info!("🚀 Promoted model version {}; hot-reloading", name);
+        Ok(())
+    }
+    
+    /// Promote the version that was active before the current one; returns its name
+    pub fn roll_back(&self, detector: &ThreatDetector) -> Result<String> {
+        let previous = self.versions
+            .lock()
+            .values()
+            .filter(|version| version.role != VersionRole::Active)
+            .filter_map(|version| Some((version.activated_at?, version.name.clone())))
+            .max()
+            .map(|(_, name)| name)
+            .ok_or_else(|| anyhow!("No previously active model version to roll back to"))?;
+        warn!("⏪ Rolling back to model version {}", previous);
+        self.promote(detector, &previous)?;
+        Ok(previous)
+    }
+    
+    /// Mark the version with `hash` active, registering the model at `model_path` if it isn't.
+    ///
+    /// Called whenever the global model is (re)loaded, so models deployed by hand, by the fleet or
+    /// by an automatic rollback are tracked too.
+    pub(super) fn sync_active(&self, hash: &str, model_path: &str) -> Result<()> {
+        let mut versions = self.versions.lock();
+        let name = match versions.values().find(|version| version.hash == hash) {
+            Some(version) if version.role == VersionRole::Active => return Ok(()),
+            Some(version) => version.name.clone(),
+            None => {
+                let name = format!("model-{}", &hash[..12]);
+                std::fs::write(self.version_file(&name), std::fs::read(model_path)?)?;
+                versions.insert(name.clone(), ModelVersion::new(&name, hash.to_string()));
+                info!("📦 Registered deployed model as version {}", name);
+                name
+            }
+        };
+        self.activate(&mut versions, &name)
+    }
+    
+    /// Restore the stored candidate's session after a restart
+    pub(super) fn resume_shadow(&self, detector: &ThreatDetector) -> Result<()> {
+        let candidate = self.versions
+            .lock()
+            .values()
+            .find(|version| version.role == VersionRole::Candidate)
+            .map(|version| version.name.clone());
+        if let Some(name) = candidate {
+            let session = detector.build_session(&self.read_version(&name)?)?;
+            *self.candidate.write() = Some((name.clone(), Arc::new(session)));
+            info!("🌗 Resumed shadowing model version {}", name);
+        }
+        Ok(())
+    }
+    
+    /// Track the active model's verdict on a transaction and run the candidate on the same features
+    pub(super) async fn observe(&self, detector: &ThreatDetector, transaction: &Transaction, features: &[f32], result: &ThreatDetectionResult) {
+        let flagged = detector.is_flagged(transaction, result);
+        let Some(active) = self.count_prediction(flagged) else {
+            return;
+        };
+        self.live_verdicts.lock().put(transaction.id.clone(), (active, flagged));
+        
+        let Some((candidate, session)) = self.candidate.read().clone() else {
+            return;
+        };
+        if self.config.shadow_sample_rate < 1.0 && ethers::core::rand::random::<f64>() >= self.config.shadow_sample_rate {
+            return;
+        }
+        // The calibration was fitted to the active model, so the candidate is judged on raw scores
+        let shadow = match detector.features_to_tensor(features) {
+            Ok(input) => detector.infer(&session, input).await.and_then(|predictions| detector.parse_model_output(&predictions)),
+            Err(e) => Err(e),
+        };
+        let shadow_flagged = match shadow {
+            Ok(shadow) => detector.is_flagged(transaction, &shadow),
+            Err(e) => {
+                warn!("⚠️ Shadow inference with {} failed: {:#}", candidate, e);
+                return;
+            }
+        };
+        
+        if let Some(stats) = self.versions.lock().get_mut(&candidate).and_then(|version| version.shadow.as_mut()) {
+            stats.predictions += 1;
+            if shadow_flagged {
+                stats.flagged += 1;
+            }
+            if shadow_flagged == flagged {
+                stats.agreements += 1;
+            }
+        }
+        self.shadow_verdicts.lock().put(transaction.id.clone(), (candidate, shadow_flagged, flagged));
+    }
+    
+    /// Grade the active and shadow verdicts on a transaction against its real outcome
+    pub(super) fn record_outcome(&self, tx_id: &str, malicious: bool) -> Result<()> {
+        let live = self.live_verdicts.lock().pop(tx_id);
+        let shadow = self.shadow_verdicts.lock().pop(tx_id);
+        let mut versions = self.versions.lock();
+        
+        if let Some(version) = live.and_then(|(name, flagged)| {
+            let version = versions.get_mut(&name)?;
+            version.stats.graded += 1;
+            if flagged == malicious {
+                version.stats.correct += 1;
+            }
+            Some(version)
+        }) {
+            self.save(version)?;
+        }
+        
+        let Some((name, flagged, active_flagged)) = shadow else {
+            return Ok(());
+        };
+        let Some(version) = versions.get_mut(&name).filter(|version| version.shadow.is_some()) else {
+            return Ok(());
+        };
+        let stats = version.shadow.as_mut().expect("filtered above");
+        stats.graded += 1;
+        if flagged == malicious {
+            stats.correct += 1;
+        }
+        if active_flagged == malicious {
+            stats.active_correct += 1;
+        }
+        if stats.graded == self.config.min_graded && stats.correct < stats.active_correct {
+            warn!("⚠️ Candidate model {} is less accurate than the active model after {} graded verdicts ({} vs {} correct)",
+                  name, stats.graded, stats.correct, stats.active_correct);
+        }
+        self.save(version)
+    }
+    
+    fn count_prediction(&self, flagged: bool) -> Option<String> {
+        let mut versions = self.versions.lock();
+        let active = versions.values_mut().find(|version| version.role == VersionRole::Active)?;
+        active.stats.predictions += 1;
+        if flagged {
+            active.stats.flagged += 1;
+        }
+        Some(active.name.clone())
+    }
+    
+    fn activate(&self, versions: &mut BTreeMap<String, ModelVersion>, name: &str) -> Result<()> {
+        if !versions.contains_key(name) {
+            bail!("No model version {}", name);
+        }
+        for version in versions.values_mut() {
+            let role = if version.name == name {
+                VersionRole::Active
+            } else if version.role == VersionRole::Active {
+                VersionRole::Inactive
+            } else {
+                continue;
+            };
+            version.role = role;
+            if role == VersionRole::Active {
+                version.activated_at = Some(now_secs());
+            }
+            self.save(version)?;
+        }
+        
+        let mut candidate = self.candidate.write();
+        if candidate.as_ref().is_some_and(|(candidate, _)| candidate == name) {
+            *candidate = None;
+        }
+        Ok(())
+    }
+    
+    /// A version's model file, checked against the hash it was registered with
+    fn read_version(&self, name: &str) -> Result<Vec<u8>> {
+        let hash = self.versions
+            .lock()
+            .get(name)
+            .map(|version| version.hash.clone())
+            .ok_or_else(|| anyhow!("No model version {}", name))?;
+        let bytes = std::fs::read(self.version_file(name))
+            .with_context(|| format!("Failed to read model version {}", name))?;
+        if ArtifactGuard::artifact_hash(&bytes) != hash {
+            bail!("Model file of version {} no longer matches its registered hash", name);
+        }
+        Ok(bytes)
+    }
+    
+    fn version_file(&self, name: &str) -> PathBuf {
+        PathBuf::from(&self.config.dir).join(format!("{}.onnx", name))
+    }
+    
+    fn save(&self, version: &ModelVersion) -> Result<()> {
+        self.storage.put(MODEL_REGISTRY_NAMESPACE, &version.name, version)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RegisterRequest {
+    name: String,
+    /// Model file on the node's disk
+    path: String,
+}
+
+/// `/models` admin endpoints: list, register, shadow, promote and roll back model versions
+pub fn admin_routes(registry: Arc<ModelRegistry>, detector: Arc<ThreatDetector>) -> Router {
+    let list = Arc::clone(&registry);
+    let (register, register_detector) = (Arc::clone(&registry), Arc::clone(&detector));
+    let (shadow, shadow_detector) = (Arc::clone(&registry), Arc::clone(&detector));
+    let unshadow = Arc::clone(&registry);
+    let (promote, promote_detector) = (Arc::clone(&registry), Arc::clone(&detector));
+    
+    Router::new()
+        .route(
+            "/models",
+            get(move || async move { Json(list.report()) })
+                .post(move |Json(request): Json<RegisterRequest>| async move {
+                    match register.register(&register_detector, &request.name, &request.path) {
+                        Ok(version) => (StatusCode::CREATED, Json(version)).into_response(),
+                        Err(e) => rejected("registration", e),
+                    }
+                }),
+        )
+        .route(
+            "/models/:name/shadow",
+            post(move |Path(name): Path<String>| async move {
+                match shadow.start_shadow(&shadow_detector, &name) {
+                    Ok(()) => StatusCode::NO_CONTENT.into_response(),
+                    Err(e) => rejected("shadow", e),
+                }
+            }),
+        )
+        .route(
+            "/models/shadow",
+            delete(move || async move {
+                match unshadow.stop_shadow() {
+                    Ok(true) => StatusCode::NO_CONTENT.into_response(),
+                    Ok(false) => StatusCode::NOT_FOUND.into_response(),
+                    Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+                }
+            }),
+        )
+        .route(
+            "/models/:name/promote",
+            post(move |Path(name): Path<String>| async move {
+                match promote.promote(&promote_detector, &name) {
+                    Ok(()) => StatusCode::NO_CONTENT.into_response(),
+                    Err(e) => rejected("promotion", e),
+                }
+            }),
+        )
+        .route(
+            "/models/rollback",
+            post(move || async move {
+                match registry.roll_back(&detector) {
+                    Ok(active) => Json(serde_json::json!({ "active": active })).into_response(),
+                    Err(e) => rejected("rollback", e),
+                }
+            }),
+        )
+}
+
+fn rejected(operation: &str, e: anyhow::Error) -> axum::response::Response {
+    warn!("Rejected model {}: {:#}", operation, e);
+    (StatusCode::BAD_REQUEST, format!("{:#}", e)).into_response()
+}
+
+/// Names become file names, so they are kept to a safe alphabet
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        bail!("Invalid model version name {:?}: use up to 64 letters, digits, '-', '_' or '.'", name);
+    }
+    Ok(())
+}
+
+fn write_atomically(path: &str, contents: &[u8]) -> Result<()> {
+    let tmp_path = format!("{}.registry-tmp", path);
+    std::fs::write(&tmp_path, contents)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+fn now_secs() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
@@ -0,0 +1,110 @@
+//! Adversarial robustness: how easily known-malicious transactions slip past detection
+//!
+//! Each transaction the detector flags is rewritten the way an attacker dodging a fixed
+//! signature would, without changing what it does on-chain, and scored again. A mutation
+//! evades when the rewritten transaction is no longer flagged.
+
+use ethers::abi::{self, Token};
+use ethers::utils::id;
+use serde::{Deserialize, Serialize};
+
+use crate::dag::Transaction;
+
+/// Words of zero padding appended by [`Mutation::Padding`]
+const PADDING_WORDS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mutation {
+    /// Trailing zero words after the calldata, which ABI decoding ignores
+    Padding,
+    /// The 4-byte selector reordered, as when a proxy routes an alias selector to the same code
+    SelectorShuffle,
+    /// The call sent through a `multicall(bytes[])` wrapper that looks like routine batching
+    BenignWrapper,
+}
+
+impl Mutation {
+    pub const ALL: [Mutation; 3] = [Self::Padding, Self::SelectorShuffle, Self::BenignWrapper];
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Padding => "padding",
+            Self::SelectorShuffle => "selector_shuffle",
+            Self::BenignWrapper => "benign_wrapper",
+        }
+    }
+    
+    /// The rewritten transaction, under an id of its own so no verdict is reused
+    pub fn apply(&self, transaction: &Transaction) -> Transaction {
+        let mut mutated = transaction.clone();
+        mutated.id = format!("{}:{}", transaction.id, self.as_str());
+        match self {
+            Self::Padding => {
+                mutated.data.extend(std::iter::repeat_n(0u8, 32 * PADDING_WORDS));
+            }
+            Self::SelectorShuffle => {
+                if let Some(selector) = mutated.data.get_mut(..4) {
+                    selector.rotate_left(1);
+                }
+            }
+            Self::BenignWrapper => {
+                let mut wrapped = id("multicall(bytes[])").to_vec();
+                wrapped.extend(abi::encode(&[Token::Array(vec![Token::Bytes(transaction.data.clone())])]));
+                mutated.data = wrapped;
+            }
+        }
+        mutated
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MutationOutcome {
+    pub mutation: Mutation,
+    pub attempts: usize,
+    pub evaded: usize,
+    /// Share of attempts no longer flagged; 0 without attempts
+    pub evasion_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RobustnessReport {
+    pub samples: usize,
+    /// Samples flagged before mutation; only these are mutated
+    pub detected: usize,
+    pub mutations: Vec<MutationOutcome>,
+    /// Across every mutation attempt
+    pub evasion_rate: f64,
+}
+
+impl RobustnessReport {
+    pub fn new(samples: usize, detected: usize, mutations: Vec<MutationOutcome>) -> Self {
+        let attempts: usize = mutations.iter().map(|outcome| outcome.attempts).sum();
+        let evaded: usize = mutations.iter().map(|outcome| outcome.evaded).sum();
+        Self {
+            samples,
+            detected,
+            mutations,
+            evasion_rate: rate(evaded, attempts),
+        }
+    }
+}
+
+impl MutationOutcome {
+    pub fn new(mutation: Mutation, attempts: usize, evaded: usize) -> Self {
+        Self {
+            mutation,
+            attempts,
+            evaded,
+            evasion_rate: rate(evaded, attempts),
+        }
+    }
+}
+
+fn rate(evaded: usize, attempts: usize) -> f64 {
+    if attempts == 0 {
+        0.0
+    } else {
+        evaded as f64 / attempts as f64
+    }
+}
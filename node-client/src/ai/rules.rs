@@ -0,0 +1,724 @@
+//! Operator-defined detection rules
+//!
+//! Rules are loaded from TOML or YAML files and written in a small expression language:
+//!
+//! ```text
+//! selector == 0x095ea7b3 && arg(1) >= 0xffffffffffffffff && !(target in ["0xabc...", "0xdef..."])
+//! data contains "flashLoan" || (chain_id == 56 && data_len > 1024)
+//! ```
+//!
+//! Fields: `selector`, `data` (bytes), `from`, `to`, `target` (addresses), `data_len`, `chain_id`,
+//! `timestamp`, `value` (wei), `dependency_count`, `blob_count` and `arg(n)` (n-th 32-byte calldata
+//! word) (numbers). Operators: `== != < <= > >= in contains starts_with`, combined with `&& || !`
+//! and parentheses. `in @name` checks against a list from the file's `[lists]` table.
+//!
+//! Besides rules, files can define behavioral signatures: named expressions that threat patterns
+//! list among their `signatures`, replacing the built-in check of the same name.
+
+use anyhow::{anyhow, bail, Context, Result};
+use ethers::types::U256;
+use ethers::utils::hex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::RwLock;
+use tracing::{debug, error, info};
+
+use crate::dag::Transaction;
+use crate::threat::ThreatClass;
+
+/// A rule as written by the operator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleDefinition {
+    pub id: String,
+    pub threat_type: ThreatClass,
+    pub confidence: f32,
+    #[serde(default)]
+    pub description: String,
+    pub when: String,
+}
+
+/// A behavioral signature as written by the operator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub when: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuleFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<RuleDefinition>,
+    #[serde(default, rename = "signature")]
+    signatures: Vec<SignatureDefinition>,
+    /// Named lists usable as `in @name`
+    #[serde(default)]
+    lists: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompiledRule {
+    pub definition: RuleDefinition,
+    expr: Expr,
+}
+
+impl CompiledRule {
+    pub fn compile(definition: RuleDefinition, lists: &HashMap<String, Vec<String>>) -> Result<Self> {
+        if !(0.0..=1.0).contains(&definition.confidence) {
+            bail!("rule '{}': confidence must be between 0 and 1", definition.id);
+        }
+        
+        let expr = parse_expression_with_lists(&definition.when, lists)
+            .with_context(|| format!("rule '{}'", definition.id))?;
+        
+        Ok(Self { definition, expr })
+    }
+    
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        self.expr.eval(transaction)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CompiledSignature {
+    pub definition: SignatureDefinition,
+    expr: Expr,
+}
+
+impl CompiledSignature {
+    pub fn compile(definition: SignatureDefinition, lists: &HashMap<String, Vec<String>>) -> Result<Self> {
+        if definition.name.is_empty() {
+            bail!("signature with an empty name");
+        }
+        
+        let expr = parse_expression_with_lists(&definition.when, lists)
+            .with_context(|| format!("signature '{}'", definition.name))?;
+        
+        Ok(Self { definition, expr })
+    }
+    
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        self.expr.eval(transaction)
+    }
+}
+
+/// Parse and type-check a rule expression
+pub fn parse_expression(source: &str) -> Result<Expr> {
+    parse_expression_with_lists(source, &HashMap::new())
+}
+
+/// Parse and type-check a rule expression that may reference named lists
+pub fn parse_expression_with_lists(source: &str, lists: &HashMap<String, Vec<String>>) -> Result<Expr> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens, pos: 0, lists };
+    let expr = parser.parse_or()?;
+    
+    if parser.pos != parser.tokens.len() {
+        bail!("unexpected token {:?} at position {}", parser.tokens[parser.pos], parser.pos);
+    }
+    
+    Ok(expr)
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Field, Op, Literal),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    Selector,
+    Data,
+    From,
+    To,
+    Target,
+    DataLen,
+    ChainId,
+    Timestamp,
+    Value,
+    DependencyCount,
+    BlobCount,
+    Arg(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FieldKind {
+    Number,
+    Address,
+    Bytes,
+}
+
+impl Field {
+    fn kind(&self) -> FieldKind {
+        match self {
+            Field::Selector | Field::Data => FieldKind::Bytes,
+            Field::From | Field::To | Field::Target => FieldKind::Address,
+            _ => FieldKind::Number,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+    Contains,
+    StartsWith,
+}
+
+#[derive(Debug, Clone)]
+pub enum Literal {
+    Number(U256),
+    Text(String),
+    Bytes(Vec<u8>),
+    List(Vec<Literal>),
+}
+
+impl Expr {
+    pub fn eval(&self, tx: &Transaction) -> bool {
+        match self {
+            Expr::And(a, b) => a.eval(tx) && b.eval(tx),
+            Expr::Or(a, b) => a.eval(tx) || b.eval(tx),
+            Expr::Not(a) => !a.eval(tx),
+            Expr::Compare(field, op, literal) => compare(tx, *field, *op, literal),
+        }
+    }
+}
+
+fn compare(tx: &Transaction, field: Field, op: Op, literal: &Literal) -> bool {
+    match field.kind() {
+        FieldKind::Number => {
+            let value = match numeric_field(tx, field) {
+                Some(value) => value,
+                None => return false,
+            };
+            match (op, literal) {
+                (Op::In, Literal::List(items)) => items.iter().any(|item| matches!(item, Literal::Number(n) if *n == value)),
+                (_, Literal::Number(n)) => match op {
+                    Op::Eq => value == *n,
+                    Op::Ne => value != *n,
+                    Op::Lt => value < *n,
+                    Op::Le => value <= *n,
+                    Op::Gt => value > *n,
+                    Op::Ge => value >= *n,
+                    _ => false,
+                },
+                _ => false,
+            }
+        }
+        FieldKind::Address => {
+            let value = match field {
+                Field::From => tx.from.to_lowercase(),
+                Field::To => tx.to.to_lowercase(),
+                _ => tx.target_address.to_lowercase(),
+            };
+            match (op, literal) {
+                (Op::In, Literal::List(items)) => items.iter().any(|item| matches!(item, Literal::Text(t) if *t == value)),
+                (Op::Eq, Literal::Text(t)) => value == *t,
+                (Op::Ne, Literal::Text(t)) => value != *t,
+                (Op::StartsWith, Literal::Text(t)) => value.starts_with(t.as_str()),
+                _ => false,
+            }
+        }
+        FieldKind::Bytes => {
+            let value: &[u8] = match field {
+                Field::Selector => tx.data.get(..4).unwrap_or(&[]),
+                _ => &tx.data,
+            };
+            match (op, literal) {
+                (Op::In, Literal::List(items)) => items.iter().any(|item| matches!(item, Literal::Bytes(b) if b.as_slice() == value)),
+                (Op::Eq, Literal::Bytes(b)) => value == b.as_slice(),
+                (Op::Ne, Literal::Bytes(b)) => value != b.as_slice(),
+                (Op::StartsWith, Literal::Bytes(b)) => value.starts_with(b),
+                (Op::Contains, Literal::Bytes(b)) => !b.is_empty() && value.windows(b.len()).any(|w| w == b.as_slice()),
+                _ => false,
+            }
+        }
+    }
+}
+
+fn numeric_field(tx: &Transaction, field: Field) -> Option<U256> {
+    match field {
+        Field::DataLen => Some(U256::from(tx.data.len())),
+        Field::ChainId => Some(U256::from(tx.chain_id)),
+        Field::Timestamp => Some(U256::from(tx.timestamp)),
+        Field::Value => Some(tx.value),
+        Field::DependencyCount => Some(U256::from(tx.dependencies.len())),
+        Field::BlobCount => Some(U256::from(tx.blob_versioned_hashes.len())),
+        Field::Arg(n) => {
+            // Calldata words start after the 4-byte selector
+            let start = 4 + n * 32;
+            tx.data.get(start..start + 32).map(U256::from_big_endian)
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(String),
+    Hex(String),
+    Text(String),
+    ListRef(String),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    And,
+    Or,
+    Not,
+    Cmp(Op),
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => { tokens.push(Token::LParen); i += 1; }
+            ')' => { tokens.push(Token::RParen); i += 1; }
+            '[' => { tokens.push(Token::LBracket); i += 1; }
+            ']' => { tokens.push(Token::RBracket); i += 1; }
+            ',' => { tokens.push(Token::Comma); i += 1; }
+            '@' => {
+                let start = i + 1;
+                let mut end = start;
+                while end < chars.len() && (chars[end].is_ascii_alphanumeric() || chars[end] == '_') {
+                    end += 1;
+                }
+                if end == start {
+                    bail!("empty list name at position {}", i);
+                }
+                tokens.push(Token::ListRef(chars[start..end].iter().collect()));
+                i = end;
+            }
+            '&' if chars.get(i + 1) == Some(&'&') => { tokens.push(Token::And); i += 2; }
+            '|' if chars.get(i + 1) == Some(&'|') => { tokens.push(Token::Or); i += 2; }
+            '=' if chars.get(i + 1) == Some(&'=') => { tokens.push(Token::Cmp(Op::Eq)); i += 2; }
+            '!' if chars.get(i + 1) == Some(&'=') => { tokens.push(Token::Cmp(Op::Ne)); i += 2; }
+            '!' => { tokens.push(Token::Not); i += 1; }
+            '<' if chars.get(i + 1) == Some(&'=') => { tokens.push(Token::Cmp(Op::Le)); i += 2; }
+            '<' => { tokens.push(Token::Cmp(Op::Lt)); i += 1; }
+            '>' if chars.get(i + 1) == Some(&'=') => { tokens.push(Token::Cmp(Op::Ge)); i += 2; }
+            '>' => { tokens.push(Token::Cmp(Op::Gt)); i += 1; }
+            '"' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&ch| ch == '"')
+                    .ok_or_else(|| anyhow!("unterminated string at position {}", i))?;
+                tokens.push(Token::Text(chars[i + 1..i + 1 + end].iter().collect()));
+                i += end + 2;
+            }
+            '0' if matches!(chars.get(i + 1), Some('x') | Some('X')) => {
+                let start = i + 2;
+                let mut end = start;
+                while end < chars.len() && chars[end].is_ascii_hexdigit() {
+                    end += 1;
+                }
+                if end == start {
+                    bail!("empty hex literal at position {}", i);
+                }
+                tokens.push(Token::Hex(chars[start..end].iter().collect()));
+                i = end;
+            }
+            c if c.is_ascii_digit() => {
+                let start = i;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                tokens.push(Token::Number(chars[start..i].iter().collect()));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                tokens.push(match word.as_str() {
+                    "in" => Token::Cmp(Op::In),
+                    "contains" => Token::Cmp(Op::Contains),
+                    "starts_with" => Token::Cmp(Op::StartsWith),
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Ident(word),
+                });
+            }
+            other => bail!("unexpected character '{}' at position {}", other, i),
+        }
+    }
+    
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    lists: &'a HashMap<String, Vec<String>>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+    
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+    
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            other => bail!("expected {:?}, found {:?}", expected, other),
+        }
+    }
+    
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            left = Expr::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+    
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut left = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            left = Expr::And(Box::new(left), Box::new(self.parse_unary()?));
+        }
+        Ok(left)
+    }
+    
+    fn parse_unary(&mut self) -> Result<Expr> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let expr = self.parse_or()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            _ => self.parse_comparison(),
+        }
+    }
+    
+    fn parse_comparison(&mut self) -> Result<Expr> {
+        let field = self.parse_field()?;
+        let op = match self.next() {
+            Some(Token::Cmp(op)) => op,
+            other => bail!("expected comparison operator after {:?}, found {:?}", field, other),
+        };
+        
+        let raw = self.parse_literal()?;
+        let literal = coerce_literal(field, op, raw)?;
+        Ok(Expr::Compare(field, op, literal))
+    }
+    
+    fn parse_field(&mut self) -> Result<Field> {
+        let name = match self.next() {
+            Some(Token::Ident(name)) => name,
+            other => bail!("expected field name, found {:?}", other),
+        };
+        
+        Ok(match name.as_str() {
+            "selector" => Field::Selector,
+            "data" => Field::Data,
+            "from" => Field::From,
+            "to" => Field::To,
+            "target" => Field::Target,
+            "data_len" => Field::DataLen,
+            "chain_id" => Field::ChainId,
+            "timestamp" => Field::Timestamp,
+            "value" => Field::Value,
+            "dependency_count" => Field::DependencyCount,
+            "blob_count" => Field::BlobCount,
+            "arg" => {
+                self.expect(Token::LParen)?;
+                let index = match self.next() {
+                    Some(Token::Number(n)) => n.parse::<usize>()?,
+                    other => bail!("expected argument index, found {:?}", other),
+                };
+                self.expect(Token::RParen)?;
+                Field::Arg(index)
+            }
+            other => bail!("unknown field '{}'", other),
+        })
+    }
+    
+    fn parse_literal(&mut self) -> Result<RawLiteral> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(RawLiteral::Number(n)),
+            Some(Token::Hex(h)) => Ok(RawLiteral::Hex(h)),
+            Some(Token::Text(t)) => Ok(RawLiteral::Text(t)),
+            Some(Token::ListRef(name)) => {
+                let items = self.lists
+                    .get(&name)
+                    .ok_or_else(|| anyhow!("unknown list '@{}'", name))?;
+                Ok(RawLiteral::List(items.iter().map(|item| list_item(item)).collect()))
+            }
+            Some(Token::LBracket) => {
+                let mut items = Vec::new();
+                loop {
+                    if self.peek() == Some(&Token::RBracket) {
+                        self.pos += 1;
+                        break;
+                    }
+                    items.push(self.parse_literal()?);
+                    match self.next() {
+                        Some(Token::Comma) => continue,
+                        Some(Token::RBracket) => break,
+                        other => bail!("expected ',' or ']' in list, found {:?}", other),
+                    }
+                }
+                Ok(RawLiteral::List(items))
+            }
+            other => bail!("expected literal, found {:?}", other),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum RawLiteral {
+    Number(String),
+    Hex(String),
+    Text(String),
+    List(Vec<RawLiteral>),
+}
+
+/// Read a `[lists]` entry the way it would be read if written inline
+fn list_item(item: &str) -> RawLiteral {
+    match item.strip_prefix("0x").or_else(|| item.strip_prefix("0X")) {
+        Some(hex) => RawLiteral::Hex(hex.to_string()),
+        None if !item.is_empty() && item.chars().all(|c| c.is_ascii_digit()) => RawLiteral::Number(item.to_string()),
+        None => RawLiteral::Text(item.to_string()),
+    }
+}
+
+/// Type-check a literal against the field and operator, converting it to its runtime form
+fn coerce_literal(field: Field, op: Op, raw: RawLiteral) -> Result<Literal> {
+    let kind = field.kind();
+    
+    let allowed = match kind {
+        FieldKind::Number => matches!(op, Op::Eq | Op::Ne | Op::Lt | Op::Le | Op::Gt | Op::Ge | Op::In),
+        FieldKind::Address => matches!(op, Op::Eq | Op::Ne | Op::In | Op::StartsWith),
+        FieldKind::Bytes => matches!(op, Op::Eq | Op::Ne | Op::In | Op::Contains | Op::StartsWith),
+    };
+    if !allowed {
+        bail!("operator {:?} is not supported for field {:?}", op, field);
+    }
+    
+    match raw {
+        RawLiteral::List(items) => {
+            if op != Op::In {
+                bail!("lists are only allowed with 'in' (field {:?})", field);
+            }
+            let items = items
+                .into_iter()
+                .map(|item| coerce_scalar(field, kind, item))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Literal::List(items))
+        }
+        scalar => {
+            if op == Op::In {
+                bail!("'in' requires a list (field {:?})", field);
+            }
+            coerce_scalar(field, kind, scalar)
+        }
+    }
+}
+
+fn coerce_scalar(field: Field, kind: FieldKind, raw: RawLiteral) -> Result<Literal> {
+    match (kind, raw) {
+        (FieldKind::Number, RawLiteral::Number(n)) => Ok(Literal::Number(
+            U256::from_dec_str(&n).map_err(|e| anyhow!("invalid number {}: {}", n, e))?,
+        )),
+        (FieldKind::Number, RawLiteral::Hex(h)) => Ok(Literal::Number(
+            U256::from_str_radix(&h, 16).map_err(|e| anyhow!("invalid number 0x{}: {}", h, e))?,
+        )),
+        (FieldKind::Address, RawLiteral::Hex(h)) => Ok(Literal::Text(format!("0x{}", h.to_lowercase()))),
+        (FieldKind::Address, RawLiteral::Text(t)) => Ok(Literal::Text(t.to_lowercase())),
+        (FieldKind::Bytes, RawLiteral::Hex(h)) => {
+            let bytes = hex::decode(&h).map_err(|e| anyhow!("invalid hex 0x{}: {}", h, e))?;
+            if field == Field::Selector && bytes.len() != 4 {
+                bail!("selector literals must be 4 bytes, got {}", bytes.len());
+            }
+            Ok(Literal::Bytes(bytes))
+        }
+        (FieldKind::Bytes, RawLiteral::Text(t)) => Ok(Literal::Bytes(t.into_bytes())),
+        (_, raw) => bail!("literal {:?} does not match the type of field {:?}", raw, field),
+    }
+}
+
+/// Everything compiled from the configured rule files
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    pub rules: Vec<CompiledRule>,
+    /// Keyed by signature name
+    pub signatures: HashMap<String, CompiledSignature>,
+}
+
+/// Load and compile every rule and signature in a TOML or YAML rule file
+pub fn load_rule_file<P: AsRef<Path>>(path: P) -> Result<RuleSet> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read rule file {}", path.display()))?;
+    
+    let file: RuleFile = match path.extension().and_then(|e| e.to_str()) {
+        Some("yaml") | Some("yml") => serde_yaml::from_str(&content)?,
+        _ => toml::from_str(&content)?,
+    };
+    
+    let mut errors = Vec::new();
+    let mut set = RuleSet::default();
+    for definition in file.rules {
+        match CompiledRule::compile(definition, &file.lists) {
+            Ok(rule) => set.rules.push(rule),
+            Err(e) => errors.push(format!("{:#}", e)),
+        }
+    }
+    for definition in file.signatures {
+        match CompiledSignature::compile(definition, &file.lists) {
+            Ok(signature) => {
+                if set.signatures.contains_key(&signature.definition.name) {
+                    errors.push(format!("signature '{}' defined twice", signature.definition.name));
+                }
+                set.signatures.insert(signature.definition.name.clone(), signature);
+            }
+            Err(e) => errors.push(format!("{:#}", e)),
+        }
+    }
+    
+    if !errors.is_empty() {
+        bail!("{}: {}", path.display(), errors.join("; "));
+    }
+    
+    Ok(set)
+}
+
+/// Compiled operator rules and signatures, reloaded whenever one of the rule files changes
+pub struct RuleEngine {
+    files: Vec<String>,
+    rules: RwLock<Arc<RuleSet>>,
+    modified: RwLock<HashMap<String, SystemTime>>,
+}
+
+impl RuleEngine {
+    pub fn new(files: &[String]) -> Result<Self> {
+        let engine = Self {
+            files: files.to_vec(),
+            rules: RwLock::new(Arc::new(RuleSet::default())),
+            modified: RwLock::new(HashMap::new()),
+        };
+        
+        // Invalid rules at startup are a configuration error, not something to run without
+        let rules = engine.compile_all()?;
+        info!("📜 Loaded {} operator rules and {} signatures from {} files",
+              rules.rules.len(), rules.signatures.len(), files.len());
+        *engine.rules.try_write().expect("engine not shared yet") = Arc::new(rules);
+        
+        Ok(engine)
+    }
+    
+    fn compile_all(&self) -> Result<RuleSet> {
+        let mut set = RuleSet::default();
+        for file in &self.files {
+            let loaded = load_rule_file(file)?;
+            set.rules.extend(loaded.rules);
+            for (name, signature) in loaded.signatures {
+                if set.signatures.insert(name.clone(), signature).is_some() {
+                    bail!("{}: signature '{}' is already defined in another rule file", file, name);
+                }
+            }
+        }
+        Ok(set)
+    }
+    
+    pub async fn rules(&self) -> Arc<RuleSet> {
+        self.rules.read().await.clone()
+    }
+    
+    /// Highest-confidence operator rule matching the transaction
+    pub async fn evaluate(&self, transaction: &Transaction) -> Option<CompiledRule> {
+        let rules = self.rules().await;
+        rules
+            .rules
+            .iter()
+            .filter(|rule| rule.matches(transaction))
+            .max_by(|a, b| a.definition.confidence.total_cmp(&b.definition.confidence))
+            .cloned()
+    }
+    
+    /// Whether the operator signature `name` matches; `None` when no rule file defines it
+    pub async fn check_signature(&self, name: &str, transaction: &Transaction) -> Option<bool> {
+        self.rules.read().await
+            .signatures
+            .get(name)
+            .map(|signature| signature.matches(transaction))
+    }
+    
+    /// Poll rule files for changes and hot-reload them; a broken edit keeps the previous rules
+    pub async fn watch(&self, interval_secs: u64) -> Result<()> {
+        if self.files.is_empty() {
+            return Ok(());
+        }
+        
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            
+            if !self.files_changed().await {
+                continue;
+            }
+            
+            match self.compile_all() {
+                Ok(rules) => {
+                    info!("🔄 Reloaded {} operator rules and {} signatures", rules.rules.len(), rules.signatures.len());
+                    *self.rules.write().await = Arc::new(rules);
+                }
+                Err(e) => error!("❌ Rule reload rejected, keeping previous rules: {:#}", e),
+            }
+        }
+    }
+    
+    async fn files_changed(&self) -> bool {
+        let mut modified = self.modified.write().await;
+        let mut changed = false;
+        
+        for file in &self.files {
+            let mtime = std::fs::metadata(file).and_then(|m| m.modified()).ok();
+            if let Some(mtime) = mtime {
+                if modified.insert(file.clone(), mtime).map(|prev| prev != mtime).unwrap_or(false) {
+                    debug!("📜 Rule file changed: {}", file);
+                    changed = true;
+                }
+            }
+        }
+        
+        changed
+    }
+}
@@ -0,0 +1,235 @@
+//! Native value and token transfers moved by a transaction, as detection features
+//!
+//! Transfers are read from receipt or simulation logs when the transaction carries them, and
+//! otherwise from the calldata of direct ERC-20/721/1155 calls. Drains and rug pulls show up as
+//! outflows of many tokens, or large amounts, from the sender converging on few recipients.
+
+use ethers::abi::{self, ParamType, Token};
+use ethers::types::{Address, H256, U256};
+use ethers::utils::{id, keccak256};
+use std::collections::HashSet;
+
+use crate::dag::{Transaction, TransactionLog};
+
+/// Number of values [`TokenFlow::features`] appends to the model input
+pub const FEATURE_COUNT: usize = 10;
+/// Transfers decoded per transaction, guarding against log-stuffed payloads
+const MAX_TRANSFERS: usize = 1024;
+/// Distinct tokens or NFTs leaving the sender for one recipient that look like a drain
+const DRAIN_MIN_ASSETS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenStandard {
+    Erc20,
+    Erc721,
+    Erc1155,
+}
+
+#[derive(Debug, Clone)]
+pub struct TokenTransfer {
+    pub standard: TokenStandard,
+    pub token: Address,
+    pub from: Address,
+    pub to: Address,
+    /// Raw token units; 1 for ERC-721
+    pub amount: U256,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TokenFlow {
+    pub native_value: U256,
+    pub transfers: Vec<TokenTransfer>,
+}
+
+impl TokenFlow {
+    pub fn extract(transaction: &Transaction) -> Self {
+        let mut transfers = if transaction.logs.is_empty() {
+            calldata_transfers(transaction)
+        } else {
+            transaction.logs.iter().flat_map(log_transfers).collect()
+        };
+        transfers.truncate(MAX_TRANSFERS);
+        
+        Self {
+            native_value: transaction.value,
+            transfers,
+        }
+    }
+    
+    /// Transfers paid out of `sender`
+    fn outflows(&self, sender: Option<Address>) -> impl Iterator<Item = &TokenTransfer> {
+        self.transfers.iter().filter(move |t| Some(t.from) == sender)
+    }
+    
+    /// Flow-size and counterparty features, scaled to stay in a small numeric range
+    pub fn features(&self, sender: &str) -> [f32; FEATURE_COUNT] {
+        let sender = sender.parse::<Address>().ok();
+        
+        let count = |standard: TokenStandard| self.transfers.iter().filter(|t| t.standard == standard).count();
+        let tokens: HashSet<Address> = self.transfers.iter().map(|t| t.token).collect();
+        let counterparties: HashSet<Address> = self.transfers
+            .iter()
+            .flat_map(|t| [t.from, t.to])
+            .filter(|a| Some(*a) != sender)
+            .collect();
+        let outflows = self.outflows(sender).count();
+        let recipients: HashSet<Address> = self.outflows(sender).map(|t| t.to).collect();
+        let max_fungible = self.transfers
+            .iter()
+            .filter(|t| t.standard != TokenStandard::Erc721)
+            .map(|t| t.amount)
+            .max()
+            .unwrap_or_default();
+        
+        [
+            (u256_to_f64(self.native_value) / 1e18).ln_1p() as f32,
+            self.transfers.len() as f32,
+            count(TokenStandard::Erc20) as f32,
+            (count(TokenStandard::Erc721) + count(TokenStandard::Erc1155)) as f32,
+            tokens.len() as f32,
+            counterparties.len() as f32,
+            outflows as f32,
+            if self.transfers.is_empty() { 0.0 } else { outflows as f32 / self.transfers.len() as f32 },
+            recipients.len() as f32,
+            // Decimals are unknown here, so raw units on a log scale
+            u256_to_f64(max_fungible).ln_1p() as f32,
+        ]
+    }
+    
+    /// Several distinct tokens or NFTs leaving the sender, all for a single recipient
+    pub fn looks_like_drain(&self, sender: &str) -> bool {
+        let sender = sender.parse::<Address>().ok();
+        
+        let recipients: HashSet<Address> = self.outflows(sender).map(|t| t.to).collect();
+        let assets: HashSet<(Address, U256)> = self.outflows(sender)
+            .map(|t| match t.standard {
+                // Each NFT counts on its own; fungible tokens once per contract
+                TokenStandard::Erc721 | TokenStandard::Erc1155 => (t.token, t.amount),
+                TokenStandard::Erc20 => (t.token, U256::zero()),
+            })
+            .collect();
+        
+        recipients.len() == 1 && assets.len() >= DRAIN_MIN_ASSETS
+    }
+}
+
+fn log_transfers(log: &TransactionLog) -> Vec<TokenTransfer> {
+    let (Ok(token), Some(Ok(signature))) = (log.address.parse::<Address>(), log.topics.first().map(|t| t.parse::<H256>())) else {
+        return vec![];
+    };
+    let topic_address = |index: usize| -> Option<Address> {
+        let topic = log.topics.get(index)?.parse::<H256>().ok()?;
+        Some(Address::from_slice(&topic.as_bytes()[12..]))
+    };
+    let transfer = |standard, from, to, amount| TokenTransfer { standard, token, from, to, amount };
+    
+    if signature.0 == keccak256("Transfer(address,address,uint256)") {
+        let (Some(from), Some(to)) = (topic_address(1), topic_address(2)) else {
+            return vec![];
+        };
+        match log.topics.len() {
+            // ERC-20 leaves the amount unindexed, ERC-721 indexes the token id
+            3 if log.data.len() >= 32 => vec![transfer(TokenStandard::Erc20, from, to, U256::from_big_endian(&log.data[..32]))],
+            4 => vec![transfer(TokenStandard::Erc721, from, to, U256::one())],
+            _ => vec![],
+        }
+    } else if signature.0 == keccak256("TransferSingle(address,address,address,uint256,uint256)") {
+        let (Some(from), Some(to)) = (topic_address(2), topic_address(3)) else {
+            return vec![];
+        };
+        match abi::decode(&[ParamType::Uint(256), ParamType::Uint(256)], &log.data).ok().as_deref() {
+            Some([Token::Uint(_), Token::Uint(value)]) => vec![transfer(TokenStandard::Erc1155, from, to, *value)],
+            _ => vec![],
+        }
+    } else if signature.0 == keccak256("TransferBatch(address,address,address,uint256[],uint256[])") {
+        let (Some(from), Some(to)) = (topic_address(2), topic_address(3)) else {
+            return vec![];
+        };
+        let params = [
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+        ];
+        match abi::decode(&params, &log.data).ok().as_deref() {
+            Some([Token::Array(_), Token::Array(values)]) => values
+                .iter()
+                .take(MAX_TRANSFERS)
+                .filter_map(|v| v.clone().into_uint())
+                .map(|value| transfer(TokenStandard::Erc1155, from, to, value))
+                .collect(),
+            _ => vec![],
+        }
+    } else {
+        vec![]
+    }
+}
+
+/// Transfers evident from a direct token call when no logs are available
+fn calldata_transfers(transaction: &Transaction) -> Vec<TokenTransfer> {
+    use ParamType::{Address as Addr, Array, Bytes, Uint};
+    
+    let (Some(selector), Ok(token)) = (transaction.data.get(..4), transaction.to.parse::<Address>()) else {
+        return vec![];
+    };
+    let args = &transaction.data[4..];
+    let decode = |params: &[ParamType]| abi::decode(params, args).ok();
+    let transfer = |standard, from, to, amount| TokenTransfer { standard, token, from, to, amount };
+    
+    if selector == id("transfer(address,uint256)") {
+        let Ok(sender) = transaction.from.parse::<Address>() else {
+            return vec![];
+        };
+        match decode(&[Addr, Uint(256)]).as_deref() {
+            Some([Token::Address(to), Token::Uint(amount)]) => vec![transfer(TokenStandard::Erc20, sender, *to, *amount)],
+            _ => vec![],
+        }
+    } else if selector == id("transferFrom(address,address,uint256)") {
+        // Same selector for ERC-721; counted as fungible since calldata can't tell them apart
+        match decode(&[Addr, Addr, Uint(256)]).as_deref() {
+            Some([Token::Address(from), Token::Address(to), Token::Uint(amount)]) => {
+                vec![transfer(TokenStandard::Erc20, *from, *to, *amount)]
+            }
+            _ => vec![],
+        }
+    } else if selector == id("safeTransferFrom(address,address,uint256)") {
+        match decode(&[Addr, Addr, Uint(256)]).as_deref() {
+            Some([Token::Address(from), Token::Address(to), Token::Uint(_)]) => {
+                vec![transfer(TokenStandard::Erc721, *from, *to, U256::one())]
+            }
+            _ => vec![],
+        }
+    } else if selector == id("safeTransferFrom(address,address,uint256,bytes)") {
+        match decode(&[Addr, Addr, Uint(256), Bytes]).as_deref() {
+            Some([Token::Address(from), Token::Address(to), Token::Uint(_), _]) => {
+                vec![transfer(TokenStandard::Erc721, *from, *to, U256::one())]
+            }
+            _ => vec![],
+        }
+    } else if selector == id("safeTransferFrom(address,address,uint256,uint256,bytes)") {
+        match decode(&[Addr, Addr, Uint(256), Uint(256), Bytes]).as_deref() {
+            Some([Token::Address(from), Token::Address(to), Token::Uint(_), Token::Uint(value), _]) => {
+                vec![transfer(TokenStandard::Erc1155, *from, *to, *value)]
+            }
+            _ => vec![],
+        }
+    } else if selector == id("safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)") {
+        let params = [Addr, Addr, Array(Box::new(Uint(256))), Array(Box::new(Uint(256))), Bytes];
+        match decode(&params).as_deref() {
+            Some([Token::Address(from), Token::Address(to), _, Token::Array(values), _]) => values
+                .iter()
+                .take(MAX_TRANSFERS)
+                .filter_map(|v| v.clone().into_uint())
+                .map(|value| transfer(TokenStandard::Erc1155, *from, *to, value))
+                .collect(),
+            _ => vec![],
+        }
+    } else {
+        vec![]
+    }
+}
+
+fn u256_to_f64(value: U256) -> f64 {
+    value.0
+        .iter()
+        .rev()
+        .fold(0.0, |acc, limb| acc * 18446744073709551616.0 + *limb as f64)
+}
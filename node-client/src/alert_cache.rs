@@ -0,0 +1,253 @@
+//! Local cache of network-verified threat alerts, kept current from the chain indexer
+
+use anyhow::Result;
+use dashmap::{DashMap, DashSet};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::{broadcast, Mutex};
+use tracing::{debug, info, warn};
+
+use crate::blockchain::{BlockchainClient, IndexedThreatAlert, THREAT_ALERT_NAMESPACE};
+use crate::config::AlertCacheConfig;
+use crate::storage::NodeStorage;
+use crate::threat::ThreatClass;
+
+pub const VERIFIED_ALERT_NAMESPACE: &str = "verified_alerts";
+
+/// On-chain state of a threat alert as of `refreshed_at`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifiedAlert {
+    pub alert_id: String,
+    pub target_address: String,
+    pub chain_id: u64,
+    pub threat_type: ThreatClass,
+    pub confidence: u32,
+    pub verified: bool,
+    pub votes: u64,
+    pub refreshed_at: u64,
+}
+
+/// The network's final say on an alert: verified by peer votes, or left unverified past
+/// `alert_cache.rejection_window_secs`
+#[derive(Debug, Clone)]
+pub struct AlertOutcome {
+    pub alert: VerifiedAlert,
+    pub verified: bool,
+}
+
+/// Serves "is this address known-bad on the network?" lookups from memory.
+///
+/// Entries are invalidated when the indexer sees a new `ThreatDetected` event and are
+/// re-read from the contract in the background; lookups never trigger an RPC call and
+/// ignore entries older than the configured staleness bound.
+pub struct VerifiedAlertCache {
+    config: AlertCacheConfig,
+    storage: Arc<NodeStorage>,
+    alerts: DashMap<String, VerifiedAlert>,
+    by_address: DashMap<String, HashSet<String>>,
+    pending: Mutex<HashSet<String>>,
+    outcomes: broadcast::Sender<AlertOutcome>,
+    /// Alerts whose outcome has been published
+    decided: DashSet<String>,
+}
+
+impl VerifiedAlertCache {
+    pub fn new(config: &AlertCacheConfig, storage: Arc<NodeStorage>) -> Result<Self> {
+        let cache = Self {
+            config: config.clone(),
+            storage,
+            alerts: DashMap::new(),
+            by_address: DashMap::new(),
+            pending: Mutex::new(HashSet::new()),
+            outcomes: broadcast::channel(256).0,
+            decided: DashSet::new(),
+        };
+        
+        for (_, alert) in cache.storage.scan::<VerifiedAlert>(VERIFIED_ALERT_NAMESPACE)? {
+            cache.insert(alert);
+        }
+        
+        // Alerts indexed while the cache was not running still need their on-chain state
+        let mut pending = HashSet::new();
+        for (alert_id, _) in cache.storage.scan::<IndexedThreatAlert>(THREAT_ALERT_NAMESPACE)? {
+            if !cache.alerts.contains_key(&alert_id) {
+                pending.insert(alert_id);
+            }
+        }
+        
+        info!("🗂️ Verified alert cache loaded: {} alerts, {} pending refresh", cache.alerts.len(), pending.len());
+        *cache.pending.try_lock().expect("cache not shared yet") = pending;
+        
+        Ok(cache)
+    }
+    
+    /// Re-read every stored alert, e.g. after a replica sync replaced them
+    pub fn reload(&self) -> Result<()> {
+        let stored = self.storage.scan::<VerifiedAlert>(VERIFIED_ALERT_NAMESPACE)?;
+        let ids: HashSet<String> = stored.iter().map(|(_, alert)| alert.alert_id.clone()).collect();
+        
+        for (_, alert) in stored {
+            self.insert(alert);
+        }
+        self.alerts.retain(|alert_id, _| ids.contains(alert_id));
+        self.by_address.retain(|_, alert_ids| {
+            alert_ids.retain(|alert_id| ids.contains(alert_id));
+            !alert_ids.is_empty()
+        });
+        Ok(())
+    }
+    
+    fn insert(&self, alert: VerifiedAlert) {
+        let address = alert.target_address.to_lowercase();
+        self.by_address
+            .entry(address)
+            .or_default()
+            .insert(alert.alert_id.clone());
+        self.alerts.insert(alert.alert_id.clone(), alert);
+    }
+    
+    fn is_fresh(&self, alert: &VerifiedAlert) -> bool {
+        let now = chrono::Utc::now().timestamp() as u64;
+        now.saturating_sub(alert.refreshed_at) <= self.config.max_staleness_secs
+    }
+    
+    /// Fresh, network-verified alerts against an address, highest confidence first
+    pub fn lookup(&self, address: &str) -> Vec<VerifiedAlert> {
+        let ids = match self.by_address.get(&address.to_lowercase()) {
+            Some(ids) => ids.clone(),
+            None => return Vec::new(),
+        };
+        
+        let mut alerts: Vec<VerifiedAlert> = ids
+            .iter()
+            .filter_map(|id| self.alerts.get(id).map(|a| a.clone()))
+            .filter(|alert| alert.verified && self.is_fresh(alert))
+            .collect();
+        alerts.sort_by_key(|alert| Reverse(alert.confidence));
+        alerts
+    }
+    
+    /// Lowercase addresses with cached alerts, verified or not
+    pub fn addresses(&self) -> Vec<String> {
+        self.by_address.iter().map(|entry| entry.key().clone()).collect()
+    }
+    
+    /// Mark an alert as changed so the next refresh re-reads it from the contract
+    /// Alerts as they are verified or rejected, for grading this node's own verdicts
+    pub fn subscribe_outcomes(&self) -> broadcast::Receiver<AlertOutcome> {
+        self.outcomes.subscribe()
+    }
+    
+    fn publish_outcome(&self, alert: &VerifiedAlert, now: u64) -> Result<()> {
+        if self.decided.contains(&alert.alert_id) {
+            return Ok(());
+        }
+        
+        let verified = if alert.verified {
+            true
+        } else {
+            let window = self.config.rejection_window_secs;
+            let indexed: Option<IndexedThreatAlert> = self.storage.get(THREAT_ALERT_NAMESPACE, &alert.alert_id)?;
+            match indexed {
+                Some(indexed) if window > 0 && now.saturating_sub(indexed.timestamp) >= window => false,
+                _ => return Ok(()),
+            }
+        };
+        
+        self.decided.insert(alert.alert_id.clone());
+        // Nobody listening just means feedback is not wired up
+        let _ = self.outcomes.send(AlertOutcome { alert: alert.clone(), verified });
+        Ok(())
+    }
+    
+    pub async fn invalidate(&self, alert_id: &str) {
+        self.pending.lock().await.insert(alert_id.to_string());
+    }
+    
+    pub async fn start(&self, client: Arc<BlockchainClient>) -> Result<()> {
+        info!("🗂️ Starting verified alert cache refresh");
+        
+        let mut events = client.subscribe_threat_alerts();
+        let mut interval = tokio::time::interval(
+            std::time::Duration::from_secs(self.config.refresh_interval_secs.max(1))
+        );
+        
+        loop {
+            tokio::select! {
+                event = events.recv() => match event {
+                    Ok(alert_id) => self.invalidate(&alert_id).await,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("⚠️ Alert cache missed {} indexer events, re-reading stored alerts", missed);
+                        self.requeue_indexed().await?;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                },
+                _ = interval.tick() => self.refresh(&client).await?,
+            }
+        }
+    }
+    
+    async fn requeue_indexed(&self) -> Result<()> {
+        let mut pending = self.pending.lock().await;
+        for (alert_id, _) in self.storage.scan::<IndexedThreatAlert>(THREAT_ALERT_NAMESPACE)? {
+            pending.insert(alert_id);
+        }
+        Ok(())
+    }
+    
+    /// Re-read invalidated alerts plus cached ones about to go stale
+    async fn refresh(&self, client: &BlockchainClient) -> Result<()> {
+        let refresh_margin = self.config.refresh_interval_secs * 2;
+        let now = chrono::Utc::now().timestamp() as u64;
+        
+        let mut due: Vec<String> = {
+            let mut pending = self.pending.lock().await;
+            pending.drain().collect()
+        };
+        due.extend(
+            self.alerts
+                .iter()
+                .filter(|a| now.saturating_sub(a.refreshed_at) + refresh_margin >= self.config.max_staleness_secs)
+                .map(|a| a.alert_id.clone())
+        );
+        due.sort();
+        due.dedup();
+        
+        if due.is_empty() {
+            return Ok(());
+        }
+        
+        let limit = self.config.max_refresh_per_round.max(1);
+        if due.len() > limit {
+            // Keep the rest for the next round instead of hammering the RPC endpoint
+            self.pending.lock().await.extend(due.split_off(limit));
+        }
+        
+        let mut batch = self.storage.batch();
+        for alert_id in &due {
+            // Read from the deployment the alert was raised on
+            let chain_id = match self.storage.get::<IndexedThreatAlert>(THREAT_ALERT_NAMESPACE, alert_id)? {
+                Some(indexed) => indexed.chain_id,
+                None => client.chain_id(),
+            };
+            match client.get_threat_alert(alert_id, chain_id).await {
+                Ok(mut alert) => {
+                    alert.refreshed_at = now;
+                    self.publish_outcome(&alert, now)?;
+                    batch.put(VERIFIED_ALERT_NAMESPACE, alert_id, &alert)?;
+                    self.insert(alert);
+                }
+                Err(e) => {
+                    warn!("Failed to refresh alert {}: {}", alert_id, e);
+                    self.pending.lock().await.insert(alert_id.clone());
+                }
+            }
+        }
+        self.storage.commit(batch)?;
+        
+        debug!("🗂️ Refreshed {} alerts ({} cached)", due.len(), self.alerts.len());
+        Ok(())
+    }
+}
@@ -0,0 +1,421 @@
+//! Archival of expiring detections to S3/GCS-compatible object storage
+//!
+//! Before the retention janitor drops a submitted threat report, or unpins its evidence, the
+//! report is exported to cold storage together with its evidence bundle and first-seen receipt
+//! chain. Archived data stays queryable on demand through its manifest.
+//!
+//! # Format, version 1
+//!
+//! All objects live under `<prefix>/v1/<node address>/`:
+//!
+//! - `segments/<first reported_at>-<uuid>.jsonl.gz`: gzip-compressed JSON Lines, one
+//!   [`ArchivedDetection`] per line, ordered by `record.reported_at`. A segment is written once
+//!   and never changed.
+//! - `manifest.json`: an [`ArchiveManifest`] listing every segment with its entry count, time
+//!   range, chains, sorted lowercased target addresses and the sha2-256 of its compressed bytes.
+//!   A query reads the manifest, then only the segments that can hold matches.
+//!
+//! A segment is uploaded before the manifest that lists it, and a report is only expired once
+//! both are written, so a failed upload leaves at most an unlisted segment behind and the report
+//! is archived again on the next sweep.
+
+use anyhow::{bail, Context, Result};
+use axum::{http::StatusCode, response::IntoResponse, routing::{get, post}, Json, Router};
+use ethers::types::Address;
+use ethers::utils::hex;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use object_store::aws::AmazonS3Builder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::local::LocalFileSystem;
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, PutPayload};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::config::{ArchiveBackend, ArchiveConfig};
+use crate::ipfs::{EvidenceBundle, IpfsClient};
+use crate::node::ThreatReportRecord;
+use crate::receipts::{detection_hash, DetectionReceipt, RECEIPT_NAMESPACE};
+use crate::status::Report;
+use crate::storage::NodeStorage;
+
+/// Reports already archived, keyed by transaction hash, with the segment holding them
+pub const ARCHIVED_NAMESPACE: &str = "archived_reports";
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedDetection {
+    pub record: ThreatReportRecord,
+    /// The pinned evidence, when it could still be fetched
+    pub evidence: Option<EvidenceBundle>,
+    /// First-seen receipts for the detection, earliest first
+    pub receipts: Vec<DetectionReceipt>,
+    pub archived_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub version: u32,
+    pub node: Address,
+    pub updated_at: u64,
+    pub segments: Vec<SegmentEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentEntry {
+    /// Object key, relative to the bucket
+    pub key: String,
+    pub entries: usize,
+    pub bytes: u64,
+    /// Hex sha2-256 of the compressed object
+    pub sha256: String,
+    pub first_reported_at: u64,
+    pub last_reported_at: u64,
+    pub chain_ids: Vec<u64>,
+    /// Lowercased, sorted
+    pub targets: Vec<String>,
+    pub created_at: u64,
+}
+
+impl SegmentEntry {
+    fn may_match(&self, query: &ArchiveQuery) -> bool {
+        query.from.is_none_or(|from| self.last_reported_at >= from)
+            && query.to.is_none_or(|to| self.first_reported_at <= to)
+            && query.chain_id.is_none_or(|chain_id| self.chain_ids.contains(&chain_id))
+            && query.address.as_ref().is_none_or(|address| self.targets.binary_search(&address.to_lowercase()).is_ok())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveQuery {
+    pub address: Option<String>,
+    pub chain_id: Option<u64>,
+    /// Unix seconds, inclusive
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    pub limit: usize,
+}
+
+impl Default for ArchiveQuery {
+    fn default() -> Self {
+        Self { address: None, chain_id: None, from: None, to: None, limit: 100 }
+    }
+}
+
+impl ArchiveQuery {
+    fn matches(&self, record: &ThreatReportRecord) -> bool {
+        self.from.is_none_or(|from| record.reported_at >= from)
+            && self.to.is_none_or(|to| record.reported_at <= to)
+            && self.chain_id.is_none_or(|chain_id| record.chain_id == chain_id)
+            && self.address.as_ref().is_none_or(|address| record.target_address.eq_ignore_ascii_case(address))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveQueryResult {
+    pub segments_scanned: usize,
+    /// Matches, oldest first, up to the query's limit
+    pub detections: Vec<ArchivedDetection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveSummary {
+    pub backend: ArchiveBackend,
+    pub root: String,
+    pub segments: usize,
+    pub detections: usize,
+    pub bytes: u64,
+    pub first_reported_at: Option<u64>,
+    pub last_reported_at: Option<u64>,
+}
+
+pub struct Archiver {
+    config: ArchiveConfig,
+    store: Arc<dyn ObjectStore>,
+    storage: Arc<NodeStorage>,
+    /// Fetches evidence before it is unpinned; without it only the CID is archived
+    ipfs: Option<Arc<IpfsClient>>,
+    node: Address,
+    root: String,
+    /// Loaded from the store on first use; one writer at a time
+    manifest: tokio::sync::Mutex<Option<ArchiveManifest>>,
+}
+
+impl Archiver {
+    pub fn new(config: &ArchiveConfig, storage: Arc<NodeStorage>, ipfs: Option<Arc<IpfsClient>>, node: Address) -> Result<Self> {
+        let store: Arc<dyn ObjectStore> = match config.backend {
+            ArchiveBackend::S3 => {
+                let mut builder = AmazonS3Builder::from_env()
+                    .with_bucket_name(&config.bucket)
+                    .with_region(&config.region);
+                if !config.endpoint.is_empty() {
+                    builder = builder.with_endpoint(&config.endpoint).with_allow_http(config.endpoint.starts_with("http://"));
+                }
+                if !config.access_key_id.is_empty() {
+                    builder = builder
+                        .with_access_key_id(&config.access_key_id)
+                        .with_secret_access_key(&config.secret_access_key);
+                }
+                Arc::new(builder.build().context("Invalid S3 archive settings")?)
+            }
+            ArchiveBackend::Gcs => {
+                let mut builder = GoogleCloudStorageBuilder::from_env().with_bucket_name(&config.bucket);
+                if !config.service_account_path.is_empty() {
+                    builder = builder.with_service_account_path(&config.service_account_path);
+                }
+                Arc::new(builder.build().context("Invalid GCS archive settings")?)
+            }
+            ArchiveBackend::File => {
+                std::fs::create_dir_all(&config.directory)
+                    .with_context(|| format!("Failed to create {}", config.directory))?;
+                Arc::new(LocalFileSystem::new_with_prefix(&config.directory)?)
+            }
+        };
+        
+        let root = format!("{}/v{}/{:?}", config.prefix.trim_matches('/'), ARCHIVE_FORMAT_VERSION, node)
+            .trim_start_matches('/')
+            .to_string();
+        info!("🗄️ Archiving expiring detections to {:?} under {}", config.backend, root);
+        Ok(Self {
+            config: config.clone(),
+            store,
+            storage,
+            ipfs,
+            node,
+            root,
+            manifest: tokio::sync::Mutex::new(None),
+        })
+    }
+    
+    /// How many of `records` are not archived yet
+    pub fn pending(&self, records: &[(String, ThreatReportRecord)]) -> Result<usize> {
+        let mut pending = 0;
+        for (tx_hash, _) in records {
+            if self.storage.get::<String>(ARCHIVED_NAMESPACE, tx_hash)?.is_none() {
+                pending += 1;
+            }
+        }
+        Ok(pending)
+    }
+    
+    /// Export the reports among `records` not archived yet, returning how many were
+    pub async fn archive(&self, records: &[(String, ThreatReportRecord)]) -> Result<usize> {
+        let mut pending = Vec::new();
+        for (tx_hash, record) in records {
+            if self.storage.get::<String>(ARCHIVED_NAMESPACE, tx_hash)?.is_none() {
+                pending.push((tx_hash.clone(), record.clone()));
+            }
+        }
+        if pending.is_empty() {
+            return Ok(0);
+        }
+        pending.sort_by_key(|(_, record)| record.reported_at);
+        
+        let mut manifest = self.manifest.lock().await;
+        if manifest.is_none() {
+            *manifest = Some(self.load_manifest().await?);
+        }
+        let manifest = manifest.as_mut().expect("manifest loaded above");
+        
+        for chunk in pending.chunks(self.config.max_segment_entries.max(1)) {
+            let mut entries = Vec::with_capacity(chunk.len());
+            for (_, record) in chunk {
+                entries.push(self.collect(record).await?);
+            }
+            let segment = self.write_segment(&entries).await?;
+            
+            manifest.segments.push(segment.clone());
+            manifest.updated_at = chrono::Utc::now().timestamp() as u64;
+            if let Err(e) = self.put_json(&self.manifest_key(), &*manifest).await {
+                // Unlisted, the segment is invisible; its reports are archived again next sweep
+                manifest.segments.pop();
+                return Err(e.context("Failed to update the archive manifest"));
+            }
+            
+            let mut batch = self.storage.batch();
+            for (tx_hash, _) in chunk {
+                batch.put(ARCHIVED_NAMESPACE, tx_hash, &segment.key)?;
+            }
+            self.storage.commit(batch)?;
+            info!("🗄️ Archived {} detections to {}", segment.entries, segment.key);
+        }
+        Ok(pending.len())
+    }
+    
+    async fn collect(&self, record: &ThreatReportRecord) -> Result<ArchivedDetection> {
+        let evidence = match (&record.evidence_cid, &self.ipfs) {
+            (Some(cid), Some(ipfs)) => match ipfs.fetch_evidence(cid).await {
+                Ok(bundle) => Some(bundle),
+                // The record keeps the CID, for whoever still pins it
+                Err(e) => {
+                    warn!("⚠️ Archiving {} without its evidence {}: {:#}", record.tx_hash, cid, e);
+                    None
+                }
+            },
+            _ => None,
+        };
+        let hash = detection_hash(record.chain_id, &record.target_address, record.threat_type.as_str());
+        let receipts = self.storage
+            .get::<Vec<DetectionReceipt>>(RECEIPT_NAMESPACE, &format!("0x{}", hex::encode(hash)))?
+            .unwrap_or_default();
+        Ok(ArchivedDetection {
+            record: record.clone(),
+            evidence,
+            receipts,
+            archived_at: chrono::Utc::now().timestamp() as u64,
+        })
+    }
+    
+    async fn write_segment(&self, entries: &[ArchivedDetection]) -> Result<SegmentEntry> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::new(self.config.compression_level.min(9)));
+        for entry in entries {
+            serde_json::to_writer(&mut encoder, entry)?;
+            encoder.write_all(b"\n")?;
+        }
+        let compressed = encoder.finish()?;
+        
+        let first_reported_at = entries.first().map_or(0, |entry| entry.record.reported_at);
+        let key = format!("{}/segments/{}-{}.jsonl.gz", self.root, first_reported_at, Uuid::new_v4());
+        let segment = SegmentEntry {
+            key: key.clone(),
+            entries: entries.len(),
+            bytes: compressed.len() as u64,
+            sha256: hex::encode(Sha256::digest(&compressed)),
+            first_reported_at,
+            last_reported_at: entries.last().map_or(0, |entry| entry.record.reported_at),
+            chain_ids: entries.iter().map(|entry| entry.record.chain_id).collect::<BTreeSet<_>>().into_iter().collect(),
+            targets: entries
+                .iter()
+                .map(|entry| entry.record.target_address.to_lowercase())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect(),
+            created_at: chrono::Utc::now().timestamp() as u64,
+        };
+        self.store
+            .put(&ObjectPath::from(key.as_str()), PutPayload::from(compressed))
+            .await
+            .with_context(|| format!("Failed to upload archive segment {}", key))?;
+        Ok(segment)
+    }
+    
+    /// Archived detections matching `query`, read from the segments the manifest points at
+    pub async fn query(&self, query: &ArchiveQuery) -> Result<ArchiveQueryResult> {
+        let segments: Vec<SegmentEntry> = self.current_manifest().await?
+            .segments
+            .into_iter()
+            .filter(|segment| segment.may_match(query))
+            .collect();
+        
+        let mut result = ArchiveQueryResult { segments_scanned: 0, detections: Vec::new() };
+        for segment in segments {
+            if result.detections.len() >= query.limit {
+                break;
+            }
+            result.segments_scanned += 1;
+            for entry in self.read_segment(&segment).await? {
+                if query.matches(&entry.record) && result.detections.len() < query.limit {
+                    result.detections.push(entry);
+                }
+            }
+        }
+        Ok(result)
+    }
+    
+    async fn read_segment(&self, segment: &SegmentEntry) -> Result<Vec<ArchivedDetection>> {
+        let compressed = self.store
+            .get(&ObjectPath::from(segment.key.as_str()))
+            .await
+            .with_context(|| format!("Failed to fetch archive segment {}", segment.key))?
+            .bytes()
+            .await?;
+        if hex::encode(Sha256::digest(&compressed)) != segment.sha256 {
+            bail!("Archive segment {} does not match its manifest checksum", segment.key);
+        }
+        
+        let mut entries = Vec::with_capacity(segment.entries);
+        for line in BufReader::new(GzDecoder::new(&compressed[..])).lines() {
+            let line = line?;
+            if !line.is_empty() {
+                entries.push(serde_json::from_str(&line).with_context(|| format!("Malformed entry in {}", segment.key))?);
+            }
+        }
+        Ok(entries)
+    }
+    
+    pub async fn summary(&self) -> Result<ArchiveSummary> {
+        let manifest = self.current_manifest().await?;
+        Ok(ArchiveSummary {
+            backend: self.config.backend,
+            root: self.root.clone(),
+            segments: manifest.segments.len(),
+            detections: manifest.segments.iter().map(|segment| segment.entries).sum(),
+            bytes: manifest.segments.iter().map(|segment| segment.bytes).sum(),
+            first_reported_at: manifest.segments.iter().map(|segment| segment.first_reported_at).min(),
+            last_reported_at: manifest.segments.iter().map(|segment| segment.last_reported_at).max(),
+        })
+    }
+    
+    async fn current_manifest(&self) -> Result<ArchiveManifest> {
+        let mut manifest = self.manifest.lock().await;
+        if manifest.is_none() {
+            *manifest = Some(self.load_manifest().await?);
+        }
+        Ok(manifest.clone().expect("manifest loaded above"))
+    }
+    
+    async fn load_manifest(&self) -> Result<ArchiveManifest> {
+        match self.store.get(&ObjectPath::from(self.manifest_key().as_str())).await {
+            Ok(object) => {
+                let manifest: ArchiveManifest = serde_json::from_slice(&object.bytes().await?)
+                    .context("Malformed archive manifest")?;
+                if manifest.version != ARCHIVE_FORMAT_VERSION {
+                    bail!("Archive manifest is format version {}, this node writes {}", manifest.version, ARCHIVE_FORMAT_VERSION);
+                }
+                debug!("Loaded archive manifest with {} segments", manifest.segments.len());
+                Ok(manifest)
+            }
+            Err(object_store::Error::NotFound { .. }) => Ok(ArchiveManifest {
+                version: ARCHIVE_FORMAT_VERSION,
+                node: self.node,
+                updated_at: 0,
+                segments: Vec::new(),
+            }),
+            Err(e) => Err(anyhow::Error::from(e).context("Failed to read the archive manifest")),
+        }
+    }
+    
+    async fn put_json<T: Serialize>(&self, key: &str, value: &T) -> Result<()> {
+        let content = serde_json::to_vec_pretty(value)?;
+        self.store.put(&ObjectPath::from(key), PutPayload::from(content)).await?;
+        Ok(())
+    }
+    
+    fn manifest_key(&self) -> String {
+        format!("{}/manifest.json", self.root)
+    }
+}
+
+/// `GET /archive` summarises the archive; `POST /archive/query` searches it
+pub fn admin_routes(archiver: Arc<Archiver>) -> Router {
+    let summarising = Arc::clone(&archiver);
+    Router::new()
+        .route("/archive", get(move || async move {
+            match summarising.summary().await {
+                Ok(summary) => Json(Report::new("archive", summary)).into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)).into_response(),
+            }
+        }))
+        .route("/archive/query", post(move |Json(query): Json<ArchiveQuery>| async move {
+            match archiver.query(&query).await {
+                Ok(result) => Json(Report::new("archive_query", result)).into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)).into_response(),
+            }
+        }))
+}
@@ -0,0 +1,228 @@
+//! Append-only audit log of node state changes, and replay of it to any past moment
+//!
+//! Stats, peer blocks and applied configuration are recorded as events, and the node rebuilds
+//! its counters from them on startup. Replaying the log up to a timestamp reconstructs what the
+//! node believed at that moment, which is what incident forensics needs.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::config::NodeConfig;
+use crate::peers::PeerBlock;
+use crate::storage::NodeStorage;
+use crate::threat::ThreatClass;
+
+/// Keyed `<unix millis>-<sequence>`, zero-padded so key order is time order
+pub const AUDIT_NAMESPACE: &str = "audit_log";
+
+/// Config keys whose values never reach the audit log
+const REDACTED_KEYS: &[&str] = &["private_key", "api_key", "auth_token"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AuditEvent {
+    ThreatDetected {
+        transaction_id: String,
+        threat_type: ThreatClass,
+        confidence: f32,
+    },
+    ChallengeCompleted {
+        challenge_id: String,
+    },
+    ReputationChanged {
+        score: u32,
+    },
+    EnergyEfficiencyChanged {
+        score: u32,
+    },
+    PeerBlocked {
+        peer_id: String,
+        block: PeerBlock,
+    },
+    PeerUnblocked {
+        peer_id: String,
+    },
+    /// `config` is the applied configuration as JSON, with secrets redacted
+    ConfigApplied {
+        source: String,
+        digest: String,
+        config: String,
+    },
+    /// Stands in for events pruned by retention: `state` is what they added up to
+    Compacted {
+        state: Box<AuditState>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub at_ms: u64,
+    pub event: AuditEvent,
+}
+
+/// Node state as reconstructed from the audit log
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditState {
+    /// Time of the last event applied
+    pub as_of_ms: Option<u64>,
+    pub events: usize,
+    pub threats_detected: u64,
+    pub challenges_completed: u64,
+    pub reputation_score: Option<u32>,
+    pub energy_efficiency: Option<u32>,
+    /// Blocks in force at `as_of_ms`, including ones that have since expired
+    pub blocked_peers: BTreeMap<String, PeerBlock>,
+    pub config_source: Option<String>,
+    pub config_digest: Option<String>,
+    pub config: Option<String>,
+}
+
+impl AuditState {
+    pub fn apply(&mut self, record: &AuditRecord) {
+        self.as_of_ms = Some(record.at_ms);
+        self.events += 1;
+        
+        match &record.event {
+            AuditEvent::ThreatDetected { .. } => self.threats_detected += 1,
+            AuditEvent::ChallengeCompleted { .. } => self.challenges_completed += 1,
+            AuditEvent::ReputationChanged { score } => self.reputation_score = Some(*score),
+            AuditEvent::EnergyEfficiencyChanged { score } => self.energy_efficiency = Some(*score),
+            AuditEvent::PeerBlocked { peer_id, block } => {
+                self.blocked_peers.insert(peer_id.clone(), block.clone());
+            }
+            AuditEvent::PeerUnblocked { peer_id } => {
+                self.blocked_peers.remove(peer_id);
+            }
+            AuditEvent::ConfigApplied { source, digest, config } => {
+                self.config_source = Some(source.clone());
+                self.config_digest = Some(digest.clone());
+                self.config = Some(config.clone());
+            }
+            AuditEvent::Compacted { state } => {
+                *self = AuditState {
+                    as_of_ms: Some(record.at_ms),
+                    events: self.events - 1 + state.events,
+                    ..(**state).clone()
+                };
+            }
+        }
+    }
+    
+    /// Peer blocks still in force at `at_secs`
+    pub fn active_blocks(&self, at_secs: u64) -> impl Iterator<Item = (&String, &PeerBlock)> {
+        self.blocked_peers
+            .iter()
+            .filter(move |(_, block)| block.until.is_none_or(|until| at_secs < until))
+    }
+}
+
+pub struct AuditLog {
+    storage: Arc<NodeStorage>,
+    sequence: AtomicU64,
+}
+
+impl AuditLog {
+    pub fn new(storage: Arc<NodeStorage>) -> Self {
+        Self {
+            storage,
+            sequence: AtomicU64::new(0),
+        }
+    }
+    
+    /// Append an event; a failed write is logged rather than blocking the state change
+    pub fn record(&self, event: AuditEvent) {
+        let at_ms = chrono::Utc::now().timestamp_millis() as u64;
+        let sequence = self.sequence.fetch_add(1, Ordering::SeqCst);
+        let key = format!("{:020}-{:010}", at_ms, sequence);
+        
+        if let Err(e) = self.storage.put(AUDIT_NAMESPACE, &key, &AuditRecord { at_ms, event }) {
+            warn!("Failed to append to the audit log: {}", e);
+        }
+    }
+    
+    /// Record the configuration in effect, unless it is the one last recorded
+    pub fn record_config(&self, source: &str, config: &NodeConfig, last_digest: Option<&str>) -> Result<()> {
+        let mut value = serde_json::to_value(config)?;
+        redact(&mut value);
+        let config = serde_json::to_string(&value)?;
+        let digest = blake3::hash(config.as_bytes()).to_hex().to_string();
+        
+        if last_digest != Some(digest.as_str()) {
+            self.record(AuditEvent::ConfigApplied {
+                source: source.to_string(),
+                digest,
+                config,
+            });
+        }
+        Ok(())
+    }
+    
+    /// Events up to and including `until_ms`, oldest first
+    pub fn events(&self, until_ms: Option<u64>) -> Result<Vec<AuditRecord>> {
+        Ok(self.storage
+            .scan::<AuditRecord>(AUDIT_NAMESPACE)?
+            .into_iter()
+            .map(|(_, record)| record)
+            .take_while(|record| until_ms.is_none_or(|until| record.at_ms <= until))
+            .collect())
+    }
+    
+    /// Replace events before `before_ms` with one `Compacted` event carrying their state,
+    /// returning how many were, or with `dry_run` would be, replaced
+    pub fn compact(&self, before_ms: u64, dry_run: bool) -> Result<usize> {
+        let expired: Vec<(String, AuditRecord)> = self.storage
+            .scan::<AuditRecord>(AUDIT_NAMESPACE)?
+            .into_iter()
+            .take_while(|(_, record)| record.at_ms < before_ms)
+            .collect();
+        // A lone earlier compaction has nothing left to fold in
+        let prunable = match expired.as_slice() {
+            [(_, AuditRecord { event: AuditEvent::Compacted { .. }, .. })] => 0,
+            _ => expired.len(),
+        };
+        if dry_run || prunable == 0 {
+            return Ok(prunable);
+        }
+
+        let mut state = AuditState::default();
+        let mut batch = self.storage.batch();
+        for (key, record) in &expired {
+            state.apply(record);
+            batch.delete(AUDIT_NAMESPACE, key);
+        }
+        // Sorts after every pruned event and before every kept one
+        let at_ms = before_ms - 1;
+        let key = format!("{:020}-{:010}", at_ms, u32::MAX);
+        batch.put(AUDIT_NAMESPACE, &key, &AuditRecord { at_ms, event: AuditEvent::Compacted { state: Box::new(state) } })?;
+        self.storage.commit(batch)?;
+        Ok(prunable)
+    }
+
+    /// Reconstruct node state as of `until_ms`, or as of now
+    pub fn replay(&self, until_ms: Option<u64>) -> Result<AuditState> {
+        let mut state = AuditState::default();
+        for record in self.events(until_ms)? {
+            state.apply(&record);
+        }
+        Ok(state)
+    }
+}
+
+fn redact(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if REDACTED_KEYS.contains(&key.as_str()) {
+                    *value = serde_json::Value::String("[redacted]".to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
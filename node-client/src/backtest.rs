@@ -0,0 +1,280 @@
+//! Backtesting the detection pipeline against a curated list of historical exploits
+//!
+//! Each exploit in the corpus lists the attacker's transactions on chain: setup such as deploying
+//! the exploit contract or taking approvals, then the drain. They are fetched from an archive node
+//! with their receipts' logs, as a simulation would have produced them, and replayed through the
+//! current pipeline. The report says which exploits would have been caught, at what confidence,
+//! and how long before the drain the first flag came.
+
+use anyhow::{anyhow, Context, Result};
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::ai::ThreatDetector;
+use crate::config::{BacktestConfig, NodeConfig};
+use crate::dag::{StorageSlot, Transaction, TransactionLog};
+use crate::governor::{ResourceGovernor, WorkClass};
+use crate::threat::ThreatClass;
+
+#[derive(Debug, Deserialize)]
+struct ExploitCorpus {
+    #[serde(default)]
+    exploits: Vec<Exploit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exploit {
+    pub name: String,
+    pub chain_id: u64,
+    /// Attacker transactions before the drain, oldest first
+    #[serde(default)]
+    pub setup: Vec<String>,
+    /// The transaction that moved the funds out
+    pub drain: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionRole {
+    Setup,
+    Drain,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayedTransaction {
+    pub hash: String,
+    pub role: TransactionRole,
+    /// Unix time of the block it was mined in
+    pub timestamp: u64,
+    pub threat_type: ThreatClass,
+    pub confidence: f32,
+    pub flagged: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExploitOutcome {
+    pub name: String,
+    pub chain_id: u64,
+    pub caught: bool,
+    /// Highest confidence among the flagged transactions
+    pub confidence: Option<f32>,
+    /// Seconds from the first flag to the drain; 0 when only the drain itself was flagged
+    pub lead_time_secs: Option<u64>,
+    pub transactions: Vec<ReplayedTransaction>,
+    /// Why the exploit could not be replayed, e.g. no archive RPC for its chain
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    pub generated_at: u64,
+    /// The pipeline the exploits were replayed through
+    pub pipeline_fingerprint: String,
+    pub exploits: usize,
+    pub replayed: usize,
+    pub caught: usize,
+    pub outcomes: Vec<ExploitOutcome>,
+}
+
+pub struct Backtester {
+    config: BacktestConfig,
+    providers: HashMap<u64, Provider<Http>>,
+    /// Historical transactions don't change; each is fetched once per process
+    fetched: parking_lot::Mutex<HashMap<(u64, String), Transaction>>,
+}
+
+impl Backtester {
+    pub fn new(config: &NodeConfig) -> Result<Self> {
+        let mut providers = HashMap::from([(
+            config.blockchain.chain_id,
+            Provider::<Http>::try_from(config.blockchain.rpc_url.as_str())?,
+        )]);
+        for archive in &config.backtest.archive_rpcs {
+            let provider = Provider::<Http>::try_from(archive.rpc_url.as_str())
+                .with_context(|| format!("Invalid archive RPC for chain {}", archive.chain_id))?;
+            providers.insert(archive.chain_id, provider);
+        }
+        
+        Ok(Self {
+            config: config.backtest.clone(),
+            providers,
+            fetched: parking_lot::Mutex::new(HashMap::new()),
+        })
+    }
+    
+    /// Replay every exploit in the corpus, re-read on each run so edits need no restart
+    pub async fn run(&self, detector: &ThreatDetector) -> Result<BacktestReport> {
+        let content = std::fs::read_to_string(&self.config.exploits_file)
+            .with_context(|| format!("Failed to read exploit corpus {}", self.config.exploits_file))?;
+        let corpus: ExploitCorpus = toml::from_str(&content)
+            .with_context(|| format!("Invalid exploit corpus {}", self.config.exploits_file))?;
+        info!("🕰️ Backtesting the pipeline against {} known exploits", corpus.exploits.len());
+        
+        let mut outcomes = Vec::with_capacity(corpus.exploits.len());
+        for exploit in corpus.exploits {
+            let outcome = match self.replay(detector, &exploit).await {
+                Ok(transactions) => summarize(&exploit, transactions),
+                Err(e) => {
+                    warn!("⚠️ Could not replay {}: {:#}", exploit.name, e);
+                    ExploitOutcome {
+                        name: exploit.name,
+                        chain_id: exploit.chain_id,
+                        caught: false,
+                        confidence: None,
+                        lead_time_secs: None,
+                        transactions: Vec::new(),
+                        error: Some(format!("{:#}", e)),
+                    }
+                }
+            };
+            outcomes.push(outcome);
+        }
+        
+        Ok(BacktestReport {
+            generated_at: chrono::Utc::now().timestamp() as u64,
+            pipeline_fingerprint: detector.pipeline_fingerprint(),
+            exploits: outcomes.len(),
+            replayed: outcomes.iter().filter(|outcome| outcome.error.is_none()).count(),
+            caught: outcomes.iter().filter(|outcome| outcome.caught).count(),
+            outcomes,
+        })
+    }
+    
+    /// Run the backtest every `interval_hours`, writing each report to `report_dir`
+    pub async fn start(&self, detector: Arc<ThreatDetector>, governor: Arc<ResourceGovernor>) -> Result<()> {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval_hours.max(1) * 3600));
+        
+        loop {
+            interval.tick().await;
+            let _token = governor.acquire_work(WorkClass::Backtest).await;
+            
+            let report = match self.run(&detector).await {
+                Ok(report) => report,
+                Err(e) => {
+                    warn!("⚠️ Backtest failed: {:#}", e);
+                    continue;
+                }
+            };
+            info!("🕰️ Backtest: {}/{} exploits caught, {} could not be replayed",
+                  report.caught, report.replayed, report.exploits - report.replayed);
+            match self.write_report(&report) {
+                Ok(path) => info!("📝 Backtest report written to {}", path.display()),
+                Err(e) => warn!("⚠️ Failed to write backtest report: {:#}", e),
+            }
+        }
+    }
+    
+    pub fn write_report(&self, report: &BacktestReport) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.config.report_dir)
+            .with_context(|| format!("Failed to create {}", self.config.report_dir))?;
+        let path = PathBuf::from(&self.config.report_dir).join(format!("backtest-{}.json", report.generated_at));
+        std::fs::write(&path, serde_json::to_string_pretty(report)? + "\n")?;
+        Ok(path)
+    }
+    
+    async fn replay(&self, detector: &ThreatDetector, exploit: &Exploit) -> Result<Vec<ReplayedTransaction>> {
+        let hashes = exploit.setup
+            .iter()
+            .map(|hash| (hash, TransactionRole::Setup))
+            .chain(std::iter::once((&exploit.drain, TransactionRole::Drain)));
+        
+        let mut replayed = Vec::new();
+        for (hash, role) in hashes {
+            let transaction = self.fetch(exploit.chain_id, hash).await?;
+            let result = detector.detect_threat(&transaction).await?;
+            replayed.push(ReplayedTransaction {
+                hash: hash.clone(),
+                role,
+                timestamp: transaction.timestamp,
+                flagged: detector.is_flagged(&transaction, &result),
+                threat_type: result.threat_type,
+                confidence: result.confidence,
+            });
+        }
+        Ok(replayed)
+    }
+    
+    async fn fetch(&self, chain_id: u64, hash: &str) -> Result<Transaction> {
+        let key = (chain_id, hash.to_lowercase());
+        if let Some(transaction) = self.fetched.lock().get(&key) {
+            return Ok(transaction.clone());
+        }
+        
+        let provider = self.providers
+            .get(&chain_id)
+            .ok_or_else(|| anyhow!("No archive RPC configured for chain {}", chain_id))?;
+        let tx_hash: H256 = hash.parse().with_context(|| format!("Invalid transaction hash {}", hash))?;
+        let tx = provider
+            .get_transaction(tx_hash)
+            .await?
+            .ok_or_else(|| anyhow!("Transaction {} not found on chain {}", hash, chain_id))?;
+        let receipt = provider
+            .get_transaction_receipt(tx_hash)
+            .await?
+            .ok_or_else(|| anyhow!("Transaction {} has no receipt on chain {}", hash, chain_id))?;
+        let block_number = receipt.block_number.ok_or_else(|| anyhow!("Transaction {} is not mined", hash))?;
+        let block = provider
+            .get_block(block_number)
+            .await?
+            .ok_or_else(|| anyhow!("Block {} not found on chain {}", block_number, chain_id))?;
+        
+        // Contract creations are judged by the contract they deploy
+        let target = tx.to.or(receipt.contract_address).map(|address| format!("{:?}", address)).unwrap_or_default();
+        let transaction = Transaction {
+            id: hash.to_string(),
+            from: format!("{:?}", tx.from),
+            to: target.clone(),
+            target_address: target,
+            chain_id,
+            data: tx.input.to_vec(),
+            timestamp: block.timestamp.as_u64(),
+            dependencies: vec![],
+            blob_versioned_hashes: vec![],
+            value: tx.value,
+            logs: receipt.logs
+                .iter()
+                .map(|log| TransactionLog {
+                    address: format!("{:?}", log.address),
+                    topics: log.topics.iter().map(|topic| format!("{:?}", topic)).collect(),
+                    data: log.data.to_vec(),
+                })
+                .collect(),
+            origin: None,
+            nonce: Some(tx.nonce.as_u64()),
+            storage_slots: tx.access_list.as_ref().map(StorageSlot::from_access_list).unwrap_or_default(),
+            priority_fee: tx.max_priority_fee_per_gas.or(tx.gas_price).unwrap_or_default(),
+            peer_synced: false,
+        };
+        
+        self.fetched.lock().insert(key, transaction.clone());
+        Ok(transaction)
+    }
+}
+
+fn summarize(exploit: &Exploit, transactions: Vec<ReplayedTransaction>) -> ExploitOutcome {
+    let flagged: Vec<&ReplayedTransaction> = transactions.iter().filter(|tx| tx.flagged).collect();
+    let drain_at = transactions
+        .iter()
+        .find(|tx| tx.role == TransactionRole::Drain)
+        .map(|tx| tx.timestamp);
+    let first_flag = flagged.iter().map(|tx| tx.timestamp).min();
+    
+    ExploitOutcome {
+        name: exploit.name.clone(),
+        chain_id: exploit.chain_id,
+        caught: !flagged.is_empty(),
+        confidence: flagged.iter().map(|tx| tx.confidence).reduce(f32::max),
+        lead_time_secs: match (first_flag, drain_at) {
+            (Some(first), Some(drain)) => Some(drain.saturating_sub(first)),
+            _ => None,
+        },
+        transactions,
+        error: None,
+    }
+}
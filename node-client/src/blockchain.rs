@@ -1,148 +1,579 @@
 //! Blockchain client for interacting with DAGShield smart contracts
+//!
+//! Calls and transactions go over HTTP to `rpc_url`, failing over to `fallback_rpc_urls` when it
+//! is down or falls behind (see `rpc_pool`). Writes about a threat on one of `blockchain.chains`,
+//! its report, votes on its alert, detection commitments, first-reporter claims and challenges,
+//! go to that chain's deployment instead, signed with the same key; node-wide state such as
+//! registration, stake and DAG epochs stays on the configured chain. Contract events are followed
+//! on every deployment, each chain with its own cursor. The configured chain's are either polled
+//! through a log filter or, with `events = "ws"` or `"ipc"`, followed over an `eth_subscribe`
+//! subscription at `events_endpoint`; the other chains are polled. A dropped subscription is
+//! reconnected with backoff, and the logs missed while it was down are read from the event cursor
+//! onwards before new ones are taken from the fresh subscription.
+//!
+//! Transactions bid EIP-1559 fees (legacy `gasPrice` on chains configured without it): the gas
+//! oracle's percentiles when it has them, else the latest fee history, re-read at most every
+//! `fees.refresh_interval_secs`, else the fixed `gas_price_gwei`. `fees.strategy` moves every
+//! transaction's urgency up or down a step, and `fees.max_tx_fee_gwei` caps what one may cost.
+//!
+//! Writes on a chain are sent one at a time with nonces assigned here, so concurrent reports and
+//! votes never race for one. A send refused with "nonce too low" is retried at the chain's pending
+//! nonce, one refused with "replacement underpriced" at higher fees. A transaction not mined
+//! within `transactions.stuck_after_secs` is replaced at the same nonce with fees raised by
+//! `bump_percent`, and whichever of its versions is mined first is the result; one that reverted
+//! is an error. A replacement refused with "nonce too low" while none of the versions is mined
+//! means the nonce went to another transaction, which is an error too.
 
 use anyhow::Result;
+use async_trait::async_trait;
+use dashmap::DashMap;
 use ethers::{
+    contract::EthLogDecode,
     prelude::*,
-    providers::{Http, Provider},
-    signers::{LocalWallet, Signer},
-    types::{Address, U256},
+    providers::{Provider, PubsubClient, Ws},
+    signers::Signer,
+    types::{transaction::eip2718::TypedTransaction, Address, BlockNumber, U256},
+    utils::hex,
 };
-use std::sync::Arc;
-use tracing::{debug, info, warn, error};
+use prometheus::{IntCounter, IntCounterVec, Opts};
+use serde::{Deserialize, Serialize};
+use futures::future::try_join_all;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, Mutex};
+use tracing::{debug, info, warn};
 
-use crate::config::BlockchainConfig;
+use crate::alert_cache::VerifiedAlert;
+use crate::chaos;
+use crate::config::{BlockchainConfig, ChainEndpoint, EventTransport, TransactionConfig};
+use crate::contract_guard::{parse_checksummed_address, ContractGuard};
+use crate::cursor::EventCursor;
+use crate::gas_oracle::{Fees, GasOracle, GasUrgency};
+use crate::maintenance::{MaintenanceControl, Stage};
+use crate::metrics::register_once;
 use crate::node::Challenge;
+use crate::penalty::PenaltyKind;
+use crate::rewards::{self, RewardEntry, RewardEntryKind, REWARD_LEDGER_NAMESPACE};
+use crate::rpc_pool::{PooledProvider, RpcMetrics, RpcPool};
+use crate::signer::NodeSigner;
+use crate::stake::StakeStatus;
+use crate::storage::StorageBatch;
+use crate::threat::ThreatClass;
 
 // ABI for DAGShield contract (simplified)
 abigen!(
     DAGShieldContract,
     r#"[
         function registerNode(string memory nodeId) external payable
+        function addStake() external payable
+        function withdrawStake(uint256 amount) external
+        function deactivateNode() external
+        function nodeStakes(address nodeAddress) external view returns (uint256)
+        function understakedSince(address nodeAddress) external view returns (uint256)
+        function MIN_STAKE() external view returns (uint256)
+        function STAKE_GRACE_PERIOD() external view returns (uint256)
         function reportThreat(string memory threatType, string memory targetAddress, uint256 confidence, uint256 chainId) external
         function voteOnThreat(bytes32 alertId, bool support) external
         function submitChallengeSolution(bytes32 challengeId, bytes32 solution) external
+        function commitDetectionEpoch(uint256 epoch, bytes32 modelHash, bytes32 root, uint256 detections, bytes calldata proof) external
+        function commitDagEpoch(uint256 epoch, bytes32 root, uint256 transactions) external
+        function getDagEpoch(address node, uint256 epoch) external view returns (tuple(bytes32 root, uint256 transactions, uint256 timestamp))
+        function claimFirstReporter(bytes32 detectionHash, uint256 firstSeenAt, bytes calldata signature, bytes32 receiptChainHash, string calldata evidenceCid) external
+        function settleFirstReporter(bytes32 detectionHash) external
+        function submitEquivocationEvidence(address offender, bytes32 topicHash, uint256 sequence, bytes32 payloadHashA, bytes calldata signatureA, bytes32 payloadHashB, bytes calldata signatureB) external
+        function openDispute(address offender, bytes32 evidenceHash, string calldata evidenceCid) external returns (uint256)
+        function claimRewards() external
+        function pendingRewards(address recipient) external view returns (uint256)
         function getNode(address nodeAddress) external view returns (tuple(string nodeId, address nodeAddress, uint256 stake, uint256 reputation, uint256 totalReports, uint256 accurateReports, bool active, uint256 lastActivity, uint256 energyEfficiency))
         function getNetworkStats() external view returns (uint256 totalNodes, uint256 totalStaked, uint256 totalThreats, uint256 verifiedThreats)
+        struct OpenChallenge { bytes32 id; string challengeType; string data; bytes32 expectedResult; uint256 reward; uint256 deadline; bool completed; address winner; }
+        function getActiveChallenges() external view returns (OpenChallenge[])
         function getThreatAlert(bytes32 alertId) external view returns (tuple(bytes32 id, address reporter, uint256 chainId, string threatType, string targetAddress, uint256 confidence, uint256 timestamp, bool verified, uint256 votes))
         event ThreatDetected(bytes32 indexed alertId, address indexed reporter, uint256 indexed chainId, string threatType, uint256 confidence, uint256 timestamp)
         event NodeRegistered(address indexed nodeAddress, string nodeId, uint256 stake, uint256 timestamp)
         event RewardDistributed(address indexed recipient, uint256 amount, string rewardType)
+        event RewardsClaimed(address indexed recipient, uint256 amount)
+        event NodeSlashed(address indexed nodeAddress, uint256 slashAmount, string reason)
+        event NodeDeactivated(address indexed nodeAddress, string reason)
     ]"#
 );
 
+/// Namespace in `NodeStorage` holding alerts indexed from `ThreatDetected` events
+pub const THREAT_ALERT_NAMESPACE: &str = "threat_alerts";
+/// Blocks read per `eth_getLogs` call when catching up on missed events
+const BACKFILL_BLOCKS: u64 = 2_000;
+const RESUBSCRIBE_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const RESUBSCRIBE_MAX_BACKOFF: Duration = Duration::from_secs(60);
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Challenges closer than this to their deadline are skipped, as a solution would not be mined in time
+const CHALLENGE_DEADLINE_MARGIN_SECS: u64 = 60;
+/// Priority fee percentiles read from fee history for low, normal and high urgency
+const REWARD_PERCENTILES: [f64; 3] = [25.0, 50.0, 90.0];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedThreatAlert {
+    pub alert_id: String,
+    pub reporter: String,
+    pub chain_id: u64,
+    pub threat_type: ThreatClass,
+    pub confidence: u32,
+    pub timestamp: u64,
+}
+
+/// A contract write that was mined but reverted; sent again, it would revert again
+#[derive(Debug, thiserror::Error)]
+#[error("Transaction {tx_hash:?} on chain {chain_id} reverted in block {block}")]
+pub struct Reverted {
+    pub tx_hash: TxHash,
+    pub chain_id: u64,
+    pub block: u64,
+}
+
+/// A write the chain would not take or did not mine in time; it may go through later
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct Unconfirmed(String);
+
+type Client = SignerMiddleware<Arc<PooledProvider>, NodeSigner>;
+type Contract = DAGShieldContract<Client>;
+
+/// The deployment on one of `blockchain.chains`
+struct ChainContract {
+    endpoint: ChainEndpoint,
+    provider: Arc<PooledProvider>,
+    contract: Contract,
+    guard: ContractGuard,
+}
+
+/// Where writes about one chain's threats go
+struct Deployment<'a> {
+    contract: &'a Contract,
+    guard: &'a ContractGuard,
+    gas_limit: u64,
+    /// The chain transactions are sent on
+    chain_id: u64,
+}
+
 pub struct BlockchainClient {
     config: BlockchainConfig,
-    provider: Arc<Provider<Http>>,
-    wallet: LocalWallet,
-    contract: DAGShieldContract<SignerMiddleware<Provider<Http>, LocalWallet>>,
+    provider: Arc<PooledProvider>,
+    wallet: NodeSigner,
+    contract: Contract,
+    guard: ContractGuard,
+    /// By chain ID
+    chains: HashMap<u64, ChainContract>,
+    nonces: NonceLanes,
+    transactions: IntCounterVec,
+    alert_events: broadcast::Sender<String>,
+    /// Slashes and deactivations of this node
+    penalty_events: broadcast::Sender<PenaltyKind>,
+    gas_oracle: OnceLock<Arc<GasOracle>>,
+    /// Fees read from fee history, by chain and urgency, with when they were read
+    fee_cache: DashMap<(u64, GasUrgency), (Instant, Fees)>,
+    maintenance: OnceLock<Arc<MaintenanceControl>>,
+    resubscriptions: IntCounter,
+    /// Open challenges as last read from the contract, with when they were read
+    challenges: Mutex<Option<(Instant, Vec<Challenge>)>>,
 }
 
 impl BlockchainClient {
     pub async fn new(config: &BlockchainConfig) -> Result<Self> {
         info!("🔗 Initializing blockchain client for chain ID: {}", config.chain_id);
+        if config.events != EventTransport::Poll && config.events_endpoint.is_empty() {
+            anyhow::bail!("blockchain.events_endpoint is required to subscribe to events over {:?}", config.events);
+        }
         
-        // Create provider
-        let provider = Provider::<Http>::try_from(&config.rpc_url)?;
-        let provider = Arc::new(provider);
+        // Create provider, failing over between rpc_url and its fallbacks
+        let rpc_metrics = RpcMetrics::new()?;
+        let provider = Arc::new(pooled_provider(config.chain_id, &config.rpc_url, &config.fallback_rpc_urls, config, &rpc_metrics)?);
         
-        // Create wallet
-        let wallet: LocalWallet = config.private_key.parse()?;
-        let wallet = wallet.with_chain_id(config.chain_id);
+        // Create signer
+        let wallet = NodeSigner::connect(config).await?;
         
         // Create signer middleware
         let client = SignerMiddleware::new(provider.clone(), wallet.clone());
         
-        // Create contract instance
-        let contract_address: Address = config.contract_address.parse()?;
+        // Create contract instance, failing fast on a bad address or wrong network
+        let contract_address = parse_checksummed_address(&config.contract_address)?;
+        let guard = ContractGuard::new(provider.clone(), contract_address, config.chain_id);
+        guard.verify_deployment(&DAGSHIELDCONTRACT_ABI, config.verify_contract_interface).await?;
+        
         let contract = DAGShieldContract::new(contract_address, Arc::new(client));
         
+        let mut chains = HashMap::new();
+        for endpoint in &config.chains {
+            if endpoint.chain_id == 0 || endpoint.chain_id == config.chain_id || chains.contains_key(&endpoint.chain_id) {
+                anyhow::bail!("blockchain.chains needs one entry per chain other than {}, found chain {} again", config.chain_id, endpoint.chain_id);
+            }
+            let chain = Self::connect_chain(endpoint, &wallet, config, &rpc_metrics).await?;
+            info!("🔗 Reporting threats on chain {} to {}", endpoint.chain_id, endpoint.contract_address);
+            chains.insert(endpoint.chain_id, chain);
+        }
+        
+        if config.transactions.bump_percent < 10 {
+            anyhow::bail!("blockchain.transactions.bump_percent must be at least 10");
+        }
+        let nonces = NonceLanes::new(std::iter::once(config.chain_id).chain(chains.keys().copied()));
+        
         info!("✅ Blockchain client initialized");
         info!("   Wallet address: {:?}", wallet.address());
         info!("   Contract address: {}", config.contract_address);
         
+        static RESUBSCRIPTIONS: OnceLock<IntCounter> = OnceLock::new();
+        static TRANSACTIONS: OnceLock<IntCounterVec> = OnceLock::new();
+        let resubscriptions = register_once(&RESUBSCRIPTIONS, || {
+            IntCounter::new(
+                "dagshield_chain_event_resubscriptions_total",
+                "Contract event subscriptions reopened after their socket dropped or failed to connect",
+            )
+        })?;
+        let transactions = register_once(&TRANSACTIONS, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_chain_transactions_total", "Contract writes sent, retried, replaced, mined and reverted, per chain"),
+                &["chain_id", "outcome"],
+            )
+        })?;
+        
         Ok(Self {
             config: config.clone(),
             provider,
             wallet,
             contract,
+            guard,
+            chains,
+            nonces,
+            transactions,
+            alert_events: broadcast::channel(1024).0,
+            penalty_events: broadcast::channel(64).0,
+            gas_oracle: OnceLock::new(),
+            fee_cache: DashMap::new(),
+            maintenance: OnceLock::new(),
+            resubscriptions,
+            challenges: Mutex::new(None),
         })
     }
     
-    pub async fn register_node(&self, node_id: &str, stake_amount: u64) -> Result<String> {
+    async fn connect_chain(
+        endpoint: &ChainEndpoint,
+        wallet: &NodeSigner,
+        config: &BlockchainConfig,
+        rpc_metrics: &RpcMetrics,
+    ) -> Result<ChainContract> {
+        let provider = Arc::new(pooled_provider(endpoint.chain_id, &endpoint.rpc_url, &endpoint.fallback_rpc_urls, config, rpc_metrics)?);
+        let client = SignerMiddleware::new(provider.clone(), wallet.clone().with_chain_id(endpoint.chain_id));
+        let contract_address = parse_checksummed_address(&endpoint.contract_address)?;
+        let guard = ContractGuard::new(provider.clone(), contract_address, endpoint.chain_id);
+        guard.verify_deployment(&DAGSHIELDCONTRACT_ABI, config.verify_contract_interface).await?;
+        
+        Ok(ChainContract {
+            endpoint: endpoint.clone(),
+            contract: DAGShieldContract::new(contract_address, Arc::new(client)),
+            provider,
+            guard,
+        })
+    }
+    
+    pub async fn register_node(&self, node_id: &str, stake_gwei: u64) -> Result<String> {
         info!("📝 Registering node on blockchain: {}", node_id);
+        chaos::rpc("register_node")?;
+        self.guard.ensure_network().await?;
         
-        let stake_wei = U256::from(stake_amount);
+        let stake_wei = U256::from(stake_gwei) * U256::exp10(9);
         
-        let tx = self.contract
+        let call = self.contract
             .register_node(node_id.to_string())
             .value(stake_wei)
-            .gas(self.config.gas_limit)
-            .gas_price(U256::from(self.config.gas_price_gwei) * U256::exp10(9))
-            .send()
-            .await?;
-        
-        let receipt = tx.await?;
-        let tx_hash = receipt.unwrap().transaction_hash;
+            .gas(self.config.gas_limit);
+        let tx_hash = self.submit(call.tx, self.config.chain_id, GasUrgency::Normal).await?;
         
         info!("✅ Node registered successfully: {:?}", tx_hash);
         Ok(format!("{:?}", tx_hash))
     }
     
+    /// This node's stake against the contract minimum
+    pub async fn stake_status(&self) -> Result<StakeStatus> {
+        chaos::rpc("stake_status")?;
+        let address = self.wallet.address();
+        let node = self.contract.get_node(address).call().await?;
+        let stake = self.contract.node_stakes(address).call().await?;
+        let minimum = self.contract.min_stake().call().await?;
+        let understaked_since = self.contract.understaked_since(address).call().await?;
+        let deactivates_at = if understaked_since.is_zero() {
+            None
+        } else {
+            let grace_period = self.contract.stake_grace_period().call().await?;
+            Some((understaked_since + grace_period).as_u64())
+        };
+        
+        Ok(StakeStatus {
+            address,
+            active: node.6,
+            stake,
+            minimum,
+            deactivates_at,
+        })
+    }
+    
+    pub async fn add_stake(&self, amount: U256) -> Result<String> {
+        info!("🪙 Adding {} to the node stake", amount);
+        chaos::rpc("add_stake")?;
+        self.guard.ensure_network().await?;
+        
+        let call = self.contract.add_stake().value(amount).gas(self.config.gas_limit);
+        let tx_hash = self.submit(call.tx, self.config.chain_id, GasUrgency::Normal).await?;
+        
+        info!("✅ Stake added: {:?}", tx_hash);
+        Ok(format!("{:?}", tx_hash))
+    }
+    
+    pub async fn withdraw_stake(&self, amount: U256) -> Result<String> {
+        info!("🪙 Withdrawing {} of the node stake", amount);
+        chaos::rpc("withdraw_stake")?;
+        self.guard.ensure_network().await?;
+        
+        let call = self.contract.withdraw_stake(amount).gas(self.config.gas_limit);
+        let tx_hash = self.submit(call.tx, self.config.chain_id, GasUrgency::Normal).await?;
+        
+        info!("✅ Stake withdrawn: {:?}", tx_hash);
+        Ok(format!("{:?}", tx_hash))
+    }
+    
+    /// Leave the network; the contract then rejects this node's reports and votes
+    pub async fn deactivate_node(&self) -> Result<String> {
+        chaos::rpc("deactivate_node")?;
+        self.guard.ensure_network().await?;
+        
+        let call = self.contract.deactivate_node().gas(self.config.gas_limit);
+        let tx_hash = self.submit(call.tx, self.config.chain_id, GasUrgency::Normal).await?;
+        
+        info!("✅ Node deactivated: {:?}", tx_hash);
+        Ok(format!("{:?}", tx_hash))
+    }
+    
     pub async fn report_threat(
         &self,
-        threat_type: &str,
+        threat_type: &ThreatClass,
         target_address: &str,
         confidence: u32,
         chain_id: u64,
     ) -> Result<String> {
         debug!("🚨 Reporting threat: {} (confidence: {}%)", threat_type, confidence);
+        chaos::rpc("report_threat")?;
+        let deployment = self.deployment(chain_id);
+        self.ensure_writable(&deployment).await?;
         
-        let tx = self.contract
+        let call = deployment
+            .contract
             .report_threat(
                 threat_type.to_string(),
                 target_address.to_string(),
                 U256::from(confidence),
                 U256::from(chain_id),
             )
-            .gas(self.config.gas_limit)
-            .gas_price(U256::from(self.config.gas_price_gwei) * U256::exp10(9))
-            .send()
-            .await?;
-        
-        let receipt = tx.await?;
-        let tx_hash = receipt.unwrap().transaction_hash;
+            .gas(deployment.gas_limit);
+        let tx_hash = self.submit(call.tx, deployment.chain_id, GasUrgency::Normal).await?;
         
         debug!("✅ Threat reported successfully: {:?}", tx_hash);
         Ok(format!("{:?}", tx_hash))
     }
     
-    pub async fn vote_on_threat(&self, alert_id: &str, support: bool) -> Result<String> {
+    /// Vote on an alert about a threat on `chain_id`, on the deployment it was raised on
+    pub async fn vote_on_threat(&self, alert_id: &str, chain_id: u64, support: bool) -> Result<String> {
         debug!("🗳️ Voting on threat alert: {} (support: {})", alert_id, support);
+        chaos::rpc("vote_on_threat")?;
+        let deployment = self.deployment(chain_id);
+        self.ensure_writable(&deployment).await?;
+        
+        if self.maintenance.get().is_some_and(|m| m.is_paused(Stage::Voting)) {
+            anyhow::bail!("Deferring vote on {}: voting is paused for maintenance", alert_id);
+        }
+        
+        // Votes are not time-critical, so they wait out fee spikes
+        if self.gas_oracle.get().map(|o| o.is_congested(deployment.chain_id)).unwrap_or(false) {
+            anyhow::bail!("Deferring vote on {}: gas prices are above the congestion threshold", alert_id);
+        }
         
         let alert_bytes: [u8; 32] = hex::decode(alert_id.trim_start_matches("0x"))?
             .try_into()
             .map_err(|_| anyhow::anyhow!("Invalid alert ID length"))?;
         
-        let tx = self.contract
+        let call = deployment.contract
             .vote_on_threat(alert_bytes, support)
-            .gas(self.config.gas_limit)
-            .gas_price(U256::from(self.config.gas_price_gwei) * U256::exp10(9))
-            .send()
-            .await?;
-        
-        let receipt = tx.await?;
-        let tx_hash = receipt.unwrap().transaction_hash;
+            .gas(deployment.gas_limit);
+        let tx_hash = self.submit(call.tx, deployment.chain_id, GasUrgency::Low).await?;
         
         debug!("✅ Vote submitted successfully: {:?}", tx_hash);
         Ok(format!("{:?}", tx_hash))
     }
     
+    /// Post an epoch's commitment to the detections on `chain_id`; it waits out reporting pauses
+    /// and fee spikes like a vote
+    pub async fn commit_detection_epoch(
+        &self,
+        chain_id: u64,
+        epoch: u64,
+        model_hash: [u8; 32],
+        root: [u8; 32],
+        detections: u64,
+        proof: Vec<u8>,
+    ) -> Result<String> {
+        debug!("🌳 Committing detection epoch {} ({} detections)", epoch, detections);
+        chaos::rpc("commit_detection_epoch")?;
+        let deployment = self.deployment(chain_id);
+        self.ensure_writable(&deployment).await?;
+        
+        if self.maintenance.get().is_some_and(|m| m.is_paused(Stage::Reporting)) {
+            anyhow::bail!("Deferring commitment of epoch {}: reporting is paused for maintenance", epoch);
+        }
+        if self.gas_oracle.get().map(|o| o.is_congested(deployment.chain_id)).unwrap_or(false) {
+            anyhow::bail!("Deferring commitment of epoch {}: gas prices are above the congestion threshold", epoch);
+        }
+        
+        let call = deployment.contract
+            .commit_detection_epoch(U256::from(epoch), model_hash, root, U256::from(detections), proof.into())
+            .gas(deployment.gas_limit);
+        let tx_hash = self.submit(call.tx, deployment.chain_id, GasUrgency::Low).await?;
+        
+        debug!("✅ Detection epoch committed: {:?}", tx_hash);
+        Ok(format!("{:?}", tx_hash))
+    }
+    
+    /// Post a DAG epoch root; like a detection commitment it waits out reporting pauses and fee spikes.
+    /// The DAG spans every chain, so its roots stay on this one
+    pub async fn commit_dag_epoch(&self, epoch: u64, root: [u8; 32], transactions: u64) -> Result<String> {
+        debug!("📍 Committing DAG epoch {} ({} transactions)", epoch, transactions);
+        chaos::rpc("commit_dag_epoch")?;
+        self.guard.ensure_network().await?;
+        
+        if self.maintenance.get().is_some_and(|m| m.is_paused(Stage::Reporting)) {
+            anyhow::bail!("Deferring commitment of DAG epoch {}: reporting is paused for maintenance", epoch);
+        }
+        if self.gas_oracle.get().map(|o| o.is_congested(self.config.chain_id)).unwrap_or(false) {
+            anyhow::bail!("Deferring commitment of DAG epoch {}: gas prices are above the congestion threshold", epoch);
+        }
+        
+        let call = self.contract
+            .commit_dag_epoch(U256::from(epoch), root, U256::from(transactions))
+            .gas(self.config.gas_limit);
+        let tx_hash = self.submit(call.tx, self.config.chain_id, GasUrgency::Low).await?;
+        
+        debug!("✅ DAG epoch committed: {:?}", tx_hash);
+        Ok(format!("{:?}", tx_hash))
+    }
+    
+    /// Claim the first-reporter reward for a detection on `chain_id` with this node's signed
+    /// first-seen receipt
+    pub async fn claim_first_reporter(
+        &self,
+        chain_id: u64,
+        detection_hash: [u8; 32],
+        first_seen_at: u64,
+        signature: &str,
+        receipt_chain_hash: [u8; 32],
+        evidence_cid: &str,
+    ) -> Result<String> {
+        debug!("🥇 Claiming first report of 0x{}", hex::encode(detection_hash));
+        chaos::rpc("claim_first_reporter")?;
+        let deployment = self.deployment(chain_id);
+        self.ensure_writable(&deployment).await?;
+        
+        if self.maintenance.get().is_some_and(|m| m.is_paused(Stage::Reporting)) {
+            anyhow::bail!("Deferring first-reporter claim: reporting is paused for maintenance");
+        }
+        
+        let signature = hex::decode(signature.trim_start_matches("0x"))?;
+        // Claims race peers' earlier receipts within the window, so they are not held back for fees
+        let call = deployment.contract
+            .claim_first_reporter(
+                detection_hash,
+                U256::from(first_seen_at),
+                signature.into(),
+                receipt_chain_hash,
+                evidence_cid.to_string(),
+            )
+            .gas(deployment.gas_limit);
+        let tx_hash = self.submit(call.tx, deployment.chain_id, GasUrgency::Normal).await?;
+        
+        debug!("✅ First-reporter claim submitted: {:?}", tx_hash);
+        Ok(format!("{:?}", tx_hash))
+    }
+    
+    /// Pay out a first-reporter claim on `chain_id` once its window has closed
+    pub async fn settle_first_reporter(&self, chain_id: u64, detection_hash: [u8; 32]) -> Result<String> {
+        chaos::rpc("settle_first_reporter")?;
+        let deployment = self.deployment(chain_id);
+        self.ensure_writable(&deployment).await?;
+        
+        if self.gas_oracle.get().map(|o| o.is_congested(deployment.chain_id)).unwrap_or(false) {
+            anyhow::bail!("Deferring first-reporter settlement: gas prices are above the congestion threshold");
+        }
+        
+        let call = deployment.contract
+            .settle_first_reporter(detection_hash)
+            .gas(deployment.gas_limit);
+        let tx_hash = self.submit(call.tx, deployment.chain_id, GasUrgency::Low).await?;
+        
+        debug!("✅ First-reporter claim settled: {:?}", tx_hash);
+        Ok(format!("{:?}", tx_hash))
+    }
+    
+    /// Prove a node signed two different gossip messages under one topic and sequence number
+    pub async fn submit_equivocation_evidence(
+        &self,
+        offender: Address,
+        topic_hash: [u8; 32],
+        sequence: u64,
+        first: ([u8; 32], &str),
+        second: ([u8; 32], &str),
+    ) -> Result<String> {
+        debug!("⚖️ Submitting equivocation evidence against {:?}", offender);
+        chaos::rpc("submit_equivocation_evidence")?;
+        self.guard.ensure_network().await?;
+        
+        let call = self.contract
+            .submit_equivocation_evidence(
+                offender,
+                topic_hash,
+                U256::from(sequence),
+                first.0,
+                hex::decode(first.1.trim_start_matches("0x"))?.into(),
+                second.0,
+                hex::decode(second.1.trim_start_matches("0x"))?.into(),
+            )
+            .gas(self.config.gas_limit);
+        let tx_hash = self.submit(call.tx, self.config.chain_id, GasUrgency::Low).await?;
+        
+        debug!("✅ Equivocation evidence submitted: {:?}", tx_hash);
+        Ok(format!("{:?}", tx_hash))
+    }
+    
+    /// Open a dispute against a node over the evidence bundle pinned at `evidence_cid`
+    pub async fn open_dispute(&self, offender: Address, evidence_hash: [u8; 32], evidence_cid: &str) -> Result<String> {
+        debug!("⚖️ Opening dispute against {:?}", offender);
+        chaos::rpc("open_dispute")?;
+        self.guard.ensure_network().await?;
+        
+        let call = self.contract
+            .open_dispute(offender, evidence_hash, evidence_cid.to_string())
+            .gas(self.config.gas_limit);
+        let tx_hash = self.submit(call.tx, self.config.chain_id, GasUrgency::Low).await?;
+        
+        debug!("✅ Dispute opened: {:?}", tx_hash);
+        Ok(format!("{:?}", tx_hash))
+    }
+    
+    /// Solve a challenge on the deployment of `chain_id` it was read from
     pub async fn submit_challenge_solution(
         &self,
+        chain_id: u64,
         challenge_id: &str,
         solution: &str,
     ) -> Result<String> {
         info!("🎯 Submitting challenge solution: {}", challenge_id);
+        chaos::rpc("submit_challenge_solution")?;
+        let deployment = self.deployment(chain_id);
+        self.ensure_writable(&deployment).await?;
         
         let challenge_bytes: [u8; 32] = hex::decode(challenge_id.trim_start_matches("0x"))?
             .try_into()
@@ -153,21 +584,47 @@ impl BlockchainClient {
             solution_hash
         };
         
-        let tx = self.contract
+        let call = deployment.contract
             .submit_challenge_solution(challenge_bytes, solution_bytes)
-            .gas(self.config.gas_limit)
-            .gas_price(U256::from(self.config.gas_price_gwei) * U256::exp10(9))
-            .send()
-            .await?;
-        
-        let receipt = tx.await?;
-        let tx_hash = receipt.unwrap().transaction_hash;
+            .gas(deployment.gas_limit);
+        let tx_hash = self.submit(call.tx, deployment.chain_id, GasUrgency::High).await?;
         
         info!("✅ Challenge solution submitted: {:?}", tx_hash);
         Ok(format!("{:?}", tx_hash))
     }
     
-    pub async fn get_node_reputation(&self, node_id: &str) -> Result<u32> {
+    /// Rewards accrued to this node on `chain_id`'s deployment and not yet claimed, in wei
+    pub async fn pending_rewards(&self, chain_id: u64) -> Result<U256> {
+        chaos::rpc("pending_rewards")?;
+        Ok(self.deployment(chain_id).contract.pending_rewards(self.wallet.address()).call().await?)
+    }
+    
+    /// Claim every reward accrued on `chain_id`'s deployment; `None` when there is nothing to claim
+    pub async fn claim_rewards(&self, chain_id: u64) -> Result<Option<(U256, String)>> {
+        let pending = self.pending_rewards(chain_id).await?;
+        if pending.is_zero() {
+            return Ok(None);
+        }
+        
+        info!("💸 Claiming {} in rewards on chain {}", pending, chain_id);
+        let deployment = self.deployment(chain_id);
+        self.ensure_writable(&deployment).await?;
+        let call = deployment.contract.claim_rewards().gas(deployment.gas_limit);
+        let tx_hash = self.submit(call.tx, deployment.chain_id, GasUrgency::Low).await?;
+        
+        info!("✅ Rewards claimed: {:?}", tx_hash);
+        Ok(Some((pending, format!("{:?}", tx_hash))))
+    }
+    
+    /// The DAG epoch root `node` committed for `epoch`, if it committed one
+    pub async fn dag_epoch_root(&self, node: Address, epoch: u64) -> Result<Option<[u8; 32]>> {
+        chaos::rpc("dag_epoch_root")?;
+        let (root, _, committed_at) = self.contract.get_dag_epoch(node, U256::from(epoch)).call().await?;
+        Ok((!committed_at.is_zero()).then_some(root))
+    }
+    
+    pub async fn get_node_reputation(&self, _node_id: &str) -> Result<u32> {
+        chaos::rpc("get_node_reputation")?;
         let node_address: Address = self.wallet.address();
         
         let node_info = self.contract
@@ -178,7 +635,15 @@ impl BlockchainClient {
         Ok(node_info.3.as_u32()) // reputation is the 4th field
     }
     
+    /// The node's reputation and whether it is active, in one read
+    pub async fn get_node_standing(&self) -> Result<(u32, bool)> {
+        chaos::rpc("get_node_standing")?;
+        let node = self.contract.get_node(self.wallet.address()).call().await?;
+        Ok((node.3.as_u32(), node.6))
+    }
+    
     pub async fn get_network_stats(&self) -> Result<(u64, u64, u64, u64)> {
+        chaos::rpc("get_network_stats")?;
         let stats = self.contract
             .get_network_stats()
             .call()
@@ -192,40 +657,65 @@ impl BlockchainClient {
         ))
     }
     
+    /// Challenges still open on every deployment with time left to solve them, re-read at most
+    /// every `challenge_refresh_secs`
     pub async fn get_active_challenges(&self) -> Result<Vec<Challenge>> {
-        // In a real implementation, this would query the contract for active challenges
-        // For now, return mock challenges for testing
-        
-        let mock_challenges = vec![
-            Challenge {
-                id: "0x1234567890abcdef".to_string(),
-                challenge_type: "threat_detection_accuracy".to_string(),
-                data: r#"[{"id":"test_1","threat_type":"phishing","expected":true}]"#.to_string(),
-                reward: 1000,
-                deadline: chrono::Utc::now().timestamp() as u64 + 3600,
-            },
-            Challenge {
-                id: "0xabcdef1234567890".to_string(),
-                challenge_type: "dag_processing_speed".to_string(),
-                data: r#"{"transactions":100,"target_tps":50}"#.to_string(),
-                reward: 500,
-                deadline: chrono::Utc::now().timestamp() as u64 + 1800,
-            },
-        ];
+        let mut cached = self.challenges.lock().await;
+        let refresh = Duration::from_secs(self.config.challenge_refresh_secs);
+        let stale = cached.as_ref().is_none_or(|(read_at, _)| read_at.elapsed() >= refresh);
+        if stale {
+            chaos::rpc("get_active_challenges")?;
+            let mut challenges = Vec::new();
+            for chain_id in self.chain_ids() {
+                let open = self.deployment(chain_id).contract.get_active_challenges().call().await?;
+                challenges.extend(open.into_iter().map(|(id, challenge_type, data, _, reward, deadline, _, _)| Challenge {
+                    id: format!("0x{}", hex::encode(id)),
+                    chain_id,
+                    challenge_type,
+                    data,
+                    reward: reward.min(U256::from(u64::MAX)).as_u64(),
+                    deadline: deadline.min(U256::from(u64::MAX)).as_u64(),
+                }));
+            }
+            debug!("🎯 {} open challenges on chain", challenges.len());
+            *cached = Some((Instant::now(), challenges));
+        }
         
-        Ok(mock_challenges)
+        let cutoff = chrono::Utc::now().timestamp() as u64 + CHALLENGE_DEADLINE_MARGIN_SECS;
+        Ok(cached
+            .as_ref()
+            .map(|(_, challenges)| challenges.iter().filter(|c| c.deadline > cutoff).cloned().collect())
+            .unwrap_or_default())
     }
     
-    pub async fn listen_for_events(&self) -> Result<()> {
-        info!("👂 Starting to listen for blockchain events...");
-        
-        let events = self.contract.events();
-        let mut stream = events.stream().await?;
+    /// Follow contract events on every deployment from its chain's cursor on, keyed by chain ID,
+    /// until one of them fails
+    pub async fn listen_for_events(&self, cursors: &mut HashMap<u64, EventCursor>) -> Result<()> {
+        try_join_all(cursors.iter_mut().map(|(chain_id, cursor)| self.listen_on(*chain_id, cursor))).await?;
+        Ok(())
+    }
+    
+    /// Follow one deployment's events; only this chain's go over `blockchain.events`
+    async fn listen_on(&self, chain_id: u64, cursor: &mut EventCursor) -> Result<()> {
+        info!("👂 Starting to listen for blockchain events on chain {} from block {}...", chain_id, cursor.next_block());
+        match self.chains.get(&chain_id) {
+            Some(chain) => self.poll_events(chain_id, &chain.contract, cursor).await,
+            None if chain_id != self.config.chain_id => anyhow::bail!("Chain {} is not configured", chain_id),
+            None => match self.config.events {
+                EventTransport::Poll => self.poll_events(chain_id, &self.contract, cursor).await,
+                EventTransport::Ws | EventTransport::Ipc => self.subscribe_events(cursor).await,
+            },
+        }
+    }
+    
+    async fn poll_events(&self, chain_id: u64, contract: &Contract, cursor: &mut EventCursor) -> Result<()> {
+        let events = contract.events().from_block(cursor.next_block());
+        let mut stream = events.stream_with_meta().await?;
         
         while let Some(log) = stream.next().await {
             match log {
-                Ok(event) => {
-                    self.handle_contract_event(event).await?;
+                Ok((event, meta)) => {
+                    self.process_event(chain_id, cursor, event, meta.block_number.as_u64(), meta.log_index.as_u64()).await?;
                 }
                 Err(e) => {
                     warn!("Error receiving event: {}", e);
@@ -236,11 +726,157 @@ impl BlockchainClient {
         Ok(())
     }
     
-    async fn handle_contract_event(&self, event: DAGShieldContractEvents) -> Result<()> {
+    /// Follow a log subscription until the task is aborted, resubscribing whenever it drops
+    async fn subscribe_events(&self, cursor: &mut EventCursor) -> Result<()> {
+        let endpoint = &self.config.events_endpoint;
+        let mut backoff = RESUBSCRIBE_INITIAL_BACKOFF;
+        loop {
+            let followed = match self.config.events {
+                EventTransport::Ipc => match Provider::connect_ipc(endpoint).await {
+                    Ok(provider) => self.follow_subscription(&provider, cursor).await,
+                    Err(e) => Err(e.into()),
+                },
+                _ => match Provider::<Ws>::connect(endpoint).await {
+                    Ok(provider) => self.follow_subscription(&provider, cursor).await,
+                    Err(e) => Err(e.into()),
+                },
+            };
+            match followed {
+                Ok(()) => {
+                    warn!("⚠️ Contract event subscription at {} dropped, resubscribing", endpoint);
+                    backoff = RESUBSCRIBE_INITIAL_BACKOFF;
+                }
+                Err(e) => warn!("⚠️ Contract event subscription at {} failed, retrying in {:?}: {:#}", endpoint, backoff, e),
+            }
+            self.resubscriptions.inc();
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(RESUBSCRIBE_MAX_BACKOFF);
+        }
+    }
+    
+    /// Subscribe, catch up on what the cursor has not seen, then take logs from the subscription
+    /// until its socket closes
+    async fn follow_subscription<P: PubsubClient>(&self, provider: &Provider<P>, cursor: &mut EventCursor) -> Result<()> {
+        let filter = self.contract.events().filter;
+        // Subscribed first, so nothing between the backfill and the subscription is missed;
+        // what both deliver is skipped by the cursor
+        let mut stream = provider.subscribe_logs(&filter).await?;
+        self.backfill_events(&filter, cursor).await?;
+        info!("👂 Subscribed to contract events over {:?} from block {}", self.config.events, cursor.next_block());
+        
+        while let Some(log) = stream.next().await {
+            // Reorged out; the replacing chain's logs arrive on their own
+            if log.removed == Some(true) {
+                continue;
+            }
+            let (Some(block_number), Some(log_index)) = (log.block_number, log.log_index) else {
+                continue;
+            };
+            match DAGShieldContractEvents::decode_log(&log.into()) {
+                Ok(event) => self.process_event(self.config.chain_id, cursor, event, block_number.as_u64(), log_index.as_u64()).await?,
+                Err(e) => warn!("Undecodable contract event at block {}: {}", block_number, e),
+            }
+        }
+        Ok(())
+    }
+    
+    /// Process the contract's logs from the cursor up to the head, over `rpc_url`
+    async fn backfill_events(&self, filter: &Filter, cursor: &mut EventCursor) -> Result<()> {
+        let head = self.get_block_number().await?;
+        let mut from = cursor.next_block();
+        while from <= head {
+            let to = (from + BACKFILL_BLOCKS - 1).min(head);
+            let logs = self.provider.get_logs(&filter.clone().from_block(from).to_block(to)).await?;
+            if !logs.is_empty() {
+                debug!("Catching up on {} contract events in blocks {} to {}", logs.len(), from, to);
+            }
+            for log in logs {
+                let (Some(block_number), Some(log_index)) = (log.block_number, log.log_index) else {
+                    continue;
+                };
+                match DAGShieldContractEvents::decode_log(&log.into()) {
+                    Ok(event) => self.process_event(self.config.chain_id, cursor, event, block_number.as_u64(), log_index.as_u64()).await?,
+                    Err(e) => warn!("Undecodable contract event at block {}: {}", block_number, e),
+                }
+            }
+            from = to + 1;
+        }
+        Ok(())
+    }
+    
+    /// Handle one event unless the cursor is past it, committing its effects with the cursor
+    async fn process_event(
+        &self,
+        chain_id: u64,
+        cursor: &mut EventCursor,
+        event: DAGShieldContractEvents,
+        block_number: u64,
+        log_index: u64,
+    ) -> Result<()> {
+        // Skip events whose effects were committed before a restart
+        if cursor.is_processed(block_number, log_index) {
+            debug!("⏭️ Skipping already processed event at block {} log {}", block_number, log_index);
+            return Ok(());
+        }
+        
+        let alert_id = match &event {
+            DAGShieldContractEvents::ThreatDetectedFilter(threat_event) => Some(format!("0x{}", hex::encode(threat_event.alert_id))),
+            _ => None,
+        };
+        let penalty = self.own_penalty(&event);
+        
+        let mut effects = cursor.batch();
+        self.handle_contract_event(chain_id, event, block_number, log_index, &mut effects).await?;
+        cursor.commit(block_number, log_index, effects)?;
+        
+        // Only announce alerts once they are persisted
+        if let Some(alert_id) = alert_id {
+            let _ = self.alert_events.send(alert_id);
+        }
+        if let Some(penalty) = penalty {
+            let _ = self.penalty_events.send(penalty);
+        }
+        Ok(())
+    }
+    
+    /// The penalty `event` puts on this node, if any
+    fn own_penalty(&self, event: &DAGShieldContractEvents) -> Option<PenaltyKind> {
+        let address = self.wallet.address();
+        match event {
+            DAGShieldContractEvents::NodeSlashedFilter(slash_event) if slash_event.node_address == address => {
+                Some(PenaltyKind::Slashed { amount: slash_event.slash_amount, reason: slash_event.reason.clone() })
+            }
+            DAGShieldContractEvents::NodeDeactivatedFilter(deactivation_event) if deactivation_event.node_address == address => {
+                Some(PenaltyKind::Deactivated { reason: deactivation_event.reason.clone() })
+            }
+            _ => None,
+        }
+    }
+    
+    /// Handle a contract event, staging any persistent effects in `effects` so they are
+    /// committed atomically with the listener cursor
+    async fn handle_contract_event(
+        &self,
+        chain_id: u64,
+        event: DAGShieldContractEvents,
+        block_number: u64,
+        log_index: u64,
+        effects: &mut StorageBatch,
+    ) -> Result<()> {
         match event {
             DAGShieldContractEvents::ThreatDetectedFilter(threat_event) => {
                 info!("🚨 Threat detected event: {:?}", threat_event.alert_id);
-                // Handle threat detection event
+                
+                // Index the alert so local lookups don't need an RPC round-trip
+                let alert = IndexedThreatAlert {
+                    alert_id: format!("0x{}", hex::encode(threat_event.alert_id)),
+                    reporter: format!("{:?}", threat_event.reporter),
+                    chain_id: threat_event.chain_id.as_u64(),
+                    threat_type: threat_event.threat_type.into(),
+                    confidence: threat_event.confidence.as_u32(),
+                    timestamp: threat_event.timestamp.as_u64(),
+                };
+                effects.put(THREAT_ALERT_NAMESPACE, &alert.alert_id, &alert)?;
             }
             DAGShieldContractEvents::NodeRegisteredFilter(node_event) => {
                 info!("📝 Node registered event: {:?}", node_event.node_address);
@@ -249,13 +885,316 @@ impl BlockchainClient {
             DAGShieldContractEvents::RewardDistributedFilter(reward_event) => {
                 info!("💰 Reward distributed event: {} tokens to {:?}", 
                       reward_event.amount, reward_event.recipient);
-                // Handle reward distribution event
+                if reward_event.recipient == self.wallet.address() {
+                    let entry = RewardEntry {
+                        kind: RewardEntryKind::Earned,
+                        amount: reward_event.amount,
+                        reward_type: Some(reward_event.reward_type),
+                        block_number,
+                    };
+                    effects.put(REWARD_LEDGER_NAMESPACE, &rewards::ledger_key(chain_id, block_number, log_index), &entry)?;
+                }
+            }
+            DAGShieldContractEvents::RewardsClaimedFilter(claim_event) => {
+                if claim_event.recipient == self.wallet.address() {
+                    info!("💸 Claimed {} in rewards", claim_event.amount);
+                    let entry = RewardEntry {
+                        kind: RewardEntryKind::Claimed,
+                        amount: claim_event.amount,
+                        reward_type: None,
+                        block_number,
+                    };
+                    effects.put(REWARD_LEDGER_NAMESPACE, &rewards::ledger_key(chain_id, block_number, log_index), &entry)?;
+                }
+            }
+            DAGShieldContractEvents::NodeSlashedFilter(slash_event) => {
+                info!("⚔️ Node slashed event: {} from {:?} for {}",
+                      slash_event.slash_amount, slash_event.node_address, slash_event.reason);
+            }
+            DAGShieldContractEvents::NodeDeactivatedFilter(deactivation_event) => {
+                info!("🚪 Node deactivated event: {:?} ({})", deactivation_event.node_address, deactivation_event.reason);
             }
         }
         
         Ok(())
     }
     
+    /// Check every RPC endpoint's head and latency until the task is aborted, so lagging ones
+    /// are failed over from
+    pub async fn monitor_endpoints(&self) -> Result<()> {
+        let pools: Vec<&RpcPool> = std::iter::once(&self.provider)
+            .chain(self.chains.values().map(|chain| &chain.provider))
+            .map(|provider| AsRef::<RpcPool>::as_ref(&**provider))
+            .collect();
+        let mut interval = tokio::time::interval(pools[0].health_check_interval());
+        loop {
+            interval.tick().await;
+            for pool in &pools {
+                pool.check_health().await;
+            }
+        }
+    }
+    
+    /// Price transactions from the gas oracle instead of the static `gas_price_gwei`
+    pub fn attach_gas_oracle(&self, oracle: Arc<GasOracle>) {
+        oracle.add_chain(self.config.chain_id, Arc::clone(&self.provider));
+        for (chain_id, chain) in &self.chains {
+            oracle.add_chain(*chain_id, Arc::clone(&chain.provider));
+        }
+        if self.gas_oracle.set(oracle).is_err() {
+            warn!("⚠️ Gas oracle already attached to blockchain client");
+        }
+    }
+    
+    /// Defer governance votes while an operator has voting paused
+    pub fn attach_maintenance(&self, control: Arc<MaintenanceControl>) {
+        if self.maintenance.set(control).is_err() {
+            warn!("⚠️ Maintenance control already attached to blockchain client");
+        }
+    }
+    
+    /// This chain's ID followed by those of `blockchain.chains`, ascending
+    pub fn chain_ids(&self) -> Vec<u64> {
+        let mut others: Vec<u64> = self.chains.keys().copied().collect();
+        others.sort_unstable();
+        std::iter::once(self.config.chain_id).chain(others).collect()
+    }
+    
+    /// The chain's own deployment when there is one, otherwise this chain's on its behalf
+    fn deployment(&self, chain_id: u64) -> Deployment<'_> {
+        match self.chains.get(&chain_id) {
+            Some(chain) => Deployment {
+                contract: &chain.contract,
+                guard: &chain.guard,
+                gas_limit: chain.endpoint.gas_limit,
+                chain_id,
+            },
+            None => Deployment {
+                contract: &self.contract,
+                guard: &self.guard,
+                gas_limit: self.config.gas_limit,
+                chain_id: self.config.chain_id,
+            },
+        }
+    }
+    
+    /// Fail unless `deployment` can be written to; writes held on this chain are held on all of them
+    async fn ensure_writable(&self, deployment: &Deployment<'_>) -> Result<()> {
+        self.guard.ensure_network().await?;
+        if deployment.chain_id != self.config.chain_id {
+            deployment.guard.ensure_network().await?;
+        }
+        Ok(())
+    }
+    
+    /// Send `tx` on `chain_id` and wait until it or one of its replacements is mined; an error
+    /// when the mined one reverted
+    async fn submit(&self, mut tx: TypedTransaction, chain_id: u64, urgency: GasUrgency) -> Result<TxHash> {
+        let (provider, client) = match self.chains.get(&chain_id) {
+            Some(chain) => (&chain.provider, chain.contract.client()),
+            None => (&self.provider, self.contract.client()),
+        };
+        let label = chain_id.to_string();
+        let sender = ChainSender {
+            address: self.wallet.address(),
+            provider,
+            client: &client,
+        };
+        let mut fees = self.price(&tx, chain_id, urgency).await;
+        let settings = &self.config.transactions;
+        let mut hashes = vec![self.nonces.send(chain_id, &mut tx, &mut fees, settings, &sender, &self.transactions).await?];
+        
+        let started = Instant::now();
+        let mut sent_at = started;
+        let mut replacements = 0;
+        loop {
+            tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+            if let Some(receipt) = Self::mined(provider, &hashes).await? {
+                return self.settled(receipt, chain_id);
+            }
+            if started.elapsed() >= Duration::from_secs(settings.receipt_timeout_secs) {
+                self.transactions.with_label_values(&[&label, "timed_out"]).inc();
+                return Err(Unconfirmed(format!("Transaction {:?} on chain {} not mined within {}s", hashes[0], chain_id,
+                                               settings.receipt_timeout_secs)).into());
+            }
+            if sent_at.elapsed() < Duration::from_secs(settings.stuck_after_secs) || replacements >= settings.max_replacements {
+                continue;
+            }
+            
+            let bumped = fees.bumped(settings.bump_percent);
+            if self.fee_ceiling(&tx).is_some_and(|ceiling| bumped.max_fee() > ceiling) {
+                debug!("⛽ Not speeding up {:?} on chain {}: max_tx_fee_gwei reached", hashes[0], chain_id);
+                replacements = settings.max_replacements;
+                continue;
+            }
+            bumped.apply(&mut tx);
+            replacements += 1;
+            sent_at = Instant::now();
+            match sender.broadcast(&tx).await {
+                Ok(tx_hash) => {
+                    warn!("⛽ Speeding up {:?} on chain {} after {}s (replacement {})", hashes[0], chain_id,
+                          settings.stuck_after_secs, replacements);
+                    self.transactions.with_label_values(&[&label, "replaced"]).inc();
+                    hashes.push(tx_hash);
+                    fees = bumped;
+                }
+                // Either one of the versions already sent was mined meanwhile, or the nonce went to
+                // another transaction of this key and none of them ever will be
+                Err(e) if is_nonce_too_low(&e) => {
+                    if let Some(receipt) = Self::mined(provider, &hashes).await? {
+                        return self.settled(receipt, chain_id);
+                    }
+                    self.transactions.with_label_values(&[&label, "displaced"]).inc();
+                    return Err(Unconfirmed(format!("Transaction {:?} on chain {} lost its nonce to another transaction: {}",
+                                                   hashes[0], chain_id, e)).into());
+                }
+                // Tried again higher next time
+                Err(e) if is_underpriced(&e) => fees = bumped,
+                Err(e) => warn!("⚠️ Replacing {:?} on chain {} failed: {}", hashes[0], chain_id, e),
+            }
+        }
+    }
+    
+    /// The receipt of whichever of a transaction's versions was mined, if any
+    async fn mined(provider: &PooledProvider, hashes: &[TxHash]) -> Result<Option<TransactionReceipt>> {
+        for hash in hashes {
+            if let Some(receipt) = provider.get_transaction_receipt(*hash).await? {
+                return Ok(Some(receipt));
+            }
+        }
+        Ok(None)
+    }
+    
+    /// The mined transaction's hash, unless it reverted
+    fn settled(&self, receipt: TransactionReceipt, chain_id: u64) -> Result<TxHash> {
+        let label = chain_id.to_string();
+        if receipt.status == Some(U64::zero()) {
+            self.transactions.with_label_values(&[&label, "reverted"]).inc();
+            return Err(Reverted {
+                tx_hash: receipt.transaction_hash,
+                chain_id,
+                block: receipt.block_number.unwrap_or_default().as_u64(),
+            }
+            .into());
+        }
+        self.transactions.with_label_values(&[&label, "mined"]).inc();
+        Ok(receipt.transaction_hash)
+    }
+    
+    /// `tx`'s fees for `chain_id`, within `max_tx_fee_gwei` at its gas limit
+    async fn price(&self, tx: &TypedTransaction, chain_id: u64, urgency: GasUrgency) -> Fees {
+        let urgency = urgency.with_strategy(self.config.fees.strategy);
+        let fees = self.fees(chain_id, urgency).await;
+        match self.fee_ceiling(tx) {
+            Some(ceiling) if fees.max_fee() > ceiling => {
+                debug!("⛽ Capping fees on chain {} at {} wei per gas (max_tx_fee_gwei)", chain_id, ceiling);
+                fees.capped(ceiling)
+            }
+            _ => fees,
+        }
+    }
+    
+    /// The most `tx` may bid per unit of gas under `max_tx_fee_gwei`
+    fn fee_ceiling(&self, tx: &TypedTransaction) -> Option<U256> {
+        let gas = tx.gas().copied().filter(|gas| !gas.is_zero())?;
+        (self.config.fees.max_tx_fee_gwei > 0).then(|| U256::from(self.config.fees.max_tx_fee_gwei) * U256::exp10(9) / gas)
+    }
+    
+    async fn fees(&self, chain_id: u64, urgency: GasUrgency) -> Fees {
+        let (provider, legacy, fallback_gwei) = match self.chains.get(&chain_id) {
+            Some(chain) => (&chain.provider, chain.endpoint.legacy_fees, chain.endpoint.gas_price_gwei),
+            None => (&self.provider, self.config.fees.legacy, self.config.gas_price_gwei),
+        };
+        if let Some(fees) = self.gas_oracle.get().and_then(|oracle| oracle.suggest(chain_id, urgency, legacy)) {
+            return fees;
+        }
+        
+        let refresh = Duration::from_secs(self.config.fees.refresh_interval_secs);
+        if let Some(cached) = self.fee_cache.get(&(chain_id, urgency)) {
+            if cached.0.elapsed() < refresh {
+                return cached.1;
+            }
+        }
+        match Self::read_fees(provider, urgency, legacy).await {
+            Ok(fees) => {
+                self.fee_cache.insert((chain_id, urgency), (Instant::now(), fees));
+                fees
+            }
+            Err(e) => {
+                debug!("Fees unavailable on chain {} ({:#}), using gas_price_gwei", chain_id, e);
+                Fees::Legacy { gas_price: U256::from(fallback_gwei) * U256::exp10(9) }
+            }
+        }
+    }
+    
+    /// Fees from the chain itself: the next block's base fee, doubled to survive a few full
+    /// blocks, plus the urgency's percentile of recent priority fees; `eth_gasPrice` on legacy chains
+    async fn read_fees(provider: &PooledProvider, urgency: GasUrgency, legacy: bool) -> Result<Fees> {
+        if legacy {
+            return Ok(Fees::Legacy { gas_price: provider.get_gas_price().await? });
+        }
+        let history = provider.fee_history(1u64, BlockNumber::Latest, &REWARD_PERCENTILES).await?;
+        let Some(base_fee) = history.base_fee_per_gas.last().copied() else {
+            anyhow::bail!("Empty fee history");
+        };
+        let percentile = match urgency {
+            GasUrgency::Low => 0,
+            GasUrgency::Normal => 1,
+            GasUrgency::High => 2,
+        };
+        let priority_fee = history.reward
+            .last()
+            .and_then(|rewards| rewards.get(percentile))
+            .copied()
+            .unwrap_or_default();
+        Ok(Fees::Eip1559 { max_fee: base_fee * 2 + priority_fee, priority_fee })
+    }
+    
+    /// IDs of newly indexed threat alerts, published after they are committed to storage
+    pub fn subscribe_threat_alerts(&self) -> broadcast::Receiver<String> {
+        self.alert_events.subscribe()
+    }
+    
+    /// Slashes and deactivations of this node, published after they are committed to storage
+    pub fn subscribe_penalties(&self) -> broadcast::Receiver<PenaltyKind> {
+        self.penalty_events.subscribe()
+    }
+    
+    /// Read an alert about a threat on `chain_id` from the deployment it was raised on
+    pub async fn get_threat_alert(&self, alert_id: &str, chain_id: u64) -> Result<VerifiedAlert> {
+        chaos::rpc("get_threat_alert")?;
+        let alert_bytes: [u8; 32] = hex::decode(alert_id.trim_start_matches("0x"))?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Invalid alert ID length"))?;
+        
+        let alert = self.deployment(chain_id).contract
+            .get_threat_alert(alert_bytes)
+            .call()
+            .await?;
+        
+        Ok(VerifiedAlert {
+            alert_id: alert_id.to_string(),
+            target_address: alert.4,
+            chain_id: alert.2.as_u64(),
+            threat_type: alert.3.into(),
+            confidence: alert.5.as_u32(),
+            verified: alert.7,
+            votes: alert.8.as_u64(),
+            refreshed_at: 0,
+        })
+    }
+    
+    /// The node's operator address
+    pub fn wallet_address(&self) -> Address {
+        self.wallet.address()
+    }
+    
+    /// Ethereum personal_sign over `message` with the node wallet, as 0x-prefixed hex
+    pub async fn sign_message(&self, message: &[u8]) -> Result<String> {
+        let signature = self.wallet.sign_message(message).await?;
+        Ok(format!("0x{}", signature))
+    }
+    
     pub async fn get_wallet_balance(&self) -> Result<U256> {
         let balance = self.provider
             .get_balance(self.wallet.address(), None)
@@ -270,15 +1209,65 @@ impl BlockchainClient {
             .data(data.to_vec())
             .from(self.wallet.address());
         
-        let gas_estimate = self.provider.estimate_gas(&tx, None).await?;
+        let gas_estimate = self.provider.estimate_gas(&tx.into(), None).await?;
         Ok(gas_estimate)
     }
     
     pub async fn get_current_gas_price(&self) -> Result<U256> {
+        if let Some(price) = self.gas_oracle.get().and_then(|o| o.suggest_gas_price(self.config.chain_id, GasUrgency::Normal)) {
+            return Ok(price);
+        }
+        
         let gas_price = self.provider.get_gas_price().await?;
         Ok(gas_price)
     }
     
+    pub fn chain_id(&self) -> u64 {
+        self.config.chain_id
+    }
+    
+    /// Refuse every write until released, with `reason` as the error
+    pub fn hold_writes(&self, reason: String) {
+        self.guard.hold(reason);
+    }
+    
+    pub fn release_writes(&self) {
+        self.guard.release();
+    }
+    
+    /// Why writes are held, while they are
+    pub fn writes_held(&self) -> Option<String> {
+        self.guard.held()
+    }
+    
+    /// Deployed code at `address`; empty for externally owned accounts
+    pub async fn get_code(&self, address: Address) -> Result<Bytes> {
+        Ok(self.provider.get_code(address, None).await?)
+    }
+    
+    pub async fn get_storage_at(&self, address: Address, slot: H256) -> Result<H256> {
+        Ok(self.provider.get_storage_at(address, slot, None).await?)
+    }
+    
+    pub async fn get_block_number(&self) -> Result<u64> {
+        Ok(self.provider.get_block_number().await?.as_u64())
+    }
+    
+    /// A block with its full transactions; `None` past the head
+    pub async fn get_block_with_txs(&self, number: u64) -> Result<Option<Block<Transaction>>> {
+        Ok(self.provider.get_block_with_txs(number).await?)
+    }
+    
+    /// Receipts of every transaction in a block, in block order
+    pub async fn get_block_receipts(&self, number: u64) -> Result<Vec<TransactionReceipt>> {
+        Ok(self.provider.get_block_receipts(number).await?)
+    }
+    
+    /// Parity-style call traces of a block; needs an archive or tracing node
+    pub async fn trace_block(&self, number: u64) -> Result<Vec<Trace>> {
+        Ok(self.provider.trace_block(BlockNumber::Number(number.into())).await?)
+    }
+    
     pub async fn wait_for_transaction(&self, tx_hash: &str) -> Result<Option<TransactionReceipt>> {
         let hash: H256 = tx_hash.parse()?;
         let receipt = self.provider
@@ -289,6 +1278,18 @@ impl BlockchainClient {
     }
 }
 
+/// `rpc_url` first, then its fallbacks
+fn pooled_provider(
+    chain_id: u64,
+    rpc_url: &str,
+    fallback_rpc_urls: &[String],
+    config: &BlockchainConfig,
+    metrics: &RpcMetrics,
+) -> Result<PooledProvider> {
+    let urls: Vec<String> = std::iter::once(rpc_url.to_string()).chain(fallback_rpc_urls.iter().cloned()).collect();
+    Ok(RpcPool::new(chain_id, &urls, &config.rpc_pool, metrics.clone())?.into_provider())
+}
+
 // Helper function for keccak256 hashing
 fn keccak256(data: &[u8]) -> [u8; 32] {
     use sha3::{Digest, Keccak256};
@@ -296,3 +1297,275 @@ fn keccak256(data: &[u8]) -> [u8; 32] {
     hasher.update(data);
     hasher.finalize().into()
 }
+
+/// Where [`NonceLanes::send`] broadcasts, so nonce handling can be exercised without a chain
+#[async_trait]
+trait Broadcast: Sync {
+    /// The key's next nonce, counting its pending transactions
+    async fn pending_nonce(&self) -> Result<U256>;
+    /// Send a signed transaction; the node's message when it refuses it
+    async fn broadcast(&self, tx: &TypedTransaction) -> std::result::Result<TxHash, String>;
+}
+
+/// A chain's RPC pool with the client signing for it
+struct ChainSender<'a> {
+    address: Address,
+    provider: &'a PooledProvider,
+    client: &'a Client,
+}
+
+#[async_trait]
+impl Broadcast for ChainSender<'_> {
+    async fn pending_nonce(&self) -> Result<U256> {
+        Ok(self.provider.get_transaction_count(self.address, Some(BlockNumber::Pending.into())).await?)
+    }
+    
+    async fn broadcast(&self, tx: &TypedTransaction) -> std::result::Result<TxHash, String> {
+        match self.client.send_transaction(tx.clone(), None).await {
+            Ok(pending) => Ok(pending.tx_hash()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// Next nonce to send with on each chain, read from the chain when unknown; held while sending
+struct NonceLanes {
+    lanes: HashMap<u64, Mutex<Option<U256>>>,
+}
+
+impl NonceLanes {
+    fn new(chain_ids: impl IntoIterator<Item = u64>) -> Self {
+        Self {
+            lanes: chain_ids.into_iter().map(|chain_id| (chain_id, Mutex::new(None))).collect(),
+        }
+    }
+    
+    /// Broadcast `tx` at the chain's next nonce, holding the chain's nonce while doing so
+    async fn send(
+        &self,
+        chain_id: u64,
+        tx: &mut TypedTransaction,
+        fees: &mut Fees,
+        settings: &TransactionConfig,
+        sender: &impl Broadcast,
+        transactions: &IntCounterVec,
+    ) -> Result<TxHash> {
+        let label = chain_id.to_string();
+        let Some(lane) = self.lanes.get(&chain_id) else {
+            anyhow::bail!("No nonce lane for chain {}", chain_id);
+        };
+        let mut next_nonce = lane.lock().await;
+        let mut nonce = match *next_nonce {
+            Some(nonce) => nonce,
+            None => sender.pending_nonce().await?,
+        };
+        
+        let mut attempt = 1;
+        loop {
+            tx.set_nonce(nonce);
+            fees.apply(tx);
+            let error = match sender.broadcast(tx).await {
+                Ok(tx_hash) => {
+                    *next_nonce = Some(nonce + 1);
+                    transactions.with_label_values(&[&label, "sent"]).inc();
+                    return Ok(tx_hash);
+                }
+                Err(e) => e,
+            };
+            if attempt >= settings.max_send_attempts {
+                // Re-read before the next send, in case this one reached the mempool after all
+                *next_nonce = None;
+                return Err(Unconfirmed(format!("Sending on chain {} failed after {} attempts: {}", chain_id, attempt, error)).into());
+            }
+            if is_nonce_too_low(&error) {
+                // Taken by a transaction sent elsewhere with this key, or before a restart
+                nonce = sender.pending_nonce().await?.max(nonce + 1);
+            } else if is_underpriced(&error) {
+                // An earlier transaction of this key is still pending at this nonce
+                *fees = fees.bumped(settings.bump_percent);
+            } else {
+                *next_nonce = None;
+                return Err(Unconfirmed(format!("Sending on chain {} failed: {}", chain_id, error)).into());
+            }
+            debug!("Retrying send on chain {} at nonce {}: {}", chain_id, nonce, error);
+            transactions.with_label_values(&[&label, "retried"]).inc();
+            attempt += 1;
+        }
+    }
+}
+
+/// Whether `error` is the chain failing to answer or to take a write, rather than the contract
+/// rejecting it or the node failing on its side: only these mark chain RPC as down
+pub fn is_rpc_failure(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause.is::<ProviderError>()
+            || cause.is::<HttpClientError>()
+            || cause.is::<Unconfirmed>()
+            || cause
+                .downcast_ref::<ContractError<Client>>()
+                .is_some_and(|e| matches!(e, ContractError::MiddlewareError { .. } | ContractError::ProviderError { .. }))
+    })
+}
+
+/// Whether `error` is a write the contract rejected, which sending again would not change
+pub fn is_reverted(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .any(|cause| cause.is::<Reverted>() || cause.to_string().to_lowercase().contains("execution reverted"))
+}
+
+fn is_nonce_too_low(error: &str) -> bool {
+    let error = error.to_lowercase();
+    error.contains("nonce too low") || error.contains("nonce has already been used")
+}
+
+fn is_underpriced(error: &str) -> bool {
+    let error = error.to_lowercase();
+    error.contains("replacement underpriced") || error.contains("replacement transaction underpriced")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    
+    /// A chain whose pending nonce is set by the test, refusing the next broadcasts with the
+    /// queued messages and recording the nonces of the rest
+    #[derive(Default)]
+    struct FakeChain {
+        pending: parking_lot::Mutex<u64>,
+        pending_reads: parking_lot::Mutex<u32>,
+        refusals: parking_lot::Mutex<VecDeque<&'static str>>,
+        sent: parking_lot::Mutex<Vec<u64>>,
+    }
+    
+    impl FakeChain {
+        fn at(pending: u64) -> Self {
+            let chain = Self::default();
+            *chain.pending.lock() = pending;
+            chain
+        }
+    }
+    
+    #[async_trait]
+    impl Broadcast for FakeChain {
+        async fn pending_nonce(&self) -> Result<U256> {
+            *self.pending_reads.lock() += 1;
+            Ok(U256::from(*self.pending.lock()))
+        }
+        
+        async fn broadcast(&self, tx: &TypedTransaction) -> std::result::Result<TxHash, String> {
+            if let Some(refusal) = self.refusals.lock().pop_front() {
+                return Err(refusal.to_string());
+            }
+            let nonce = tx.nonce().expect("a nonce").as_u64();
+            self.sent.lock().push(nonce);
+            Ok(TxHash::from_low_u64_be(nonce))
+        }
+    }
+    
+    fn counter() -> IntCounterVec {
+        IntCounterVec::new(Opts::new("test_chain_transactions_total", "Test"), &["chain_id", "outcome"]).unwrap()
+    }
+    
+    async fn send(lanes: &NonceLanes, chain_id: u64, chain: &FakeChain) -> Result<TxHash> {
+        let mut tx = TypedTransaction::Legacy(TransactionRequest::new());
+        let mut fees = Fees::Legacy { gas_price: U256::from(100) };
+        lanes.send(chain_id, &mut tx, &mut fees, &TransactionConfig::default(), chain, &counter()).await
+    }
+    
+    #[tokio::test]
+    async fn each_chain_counts_its_own_nonces() {
+        let lanes = NonceLanes::new([1, 137]);
+        let home = FakeChain::at(5);
+        let other = FakeChain::at(40);
+        
+        send(&lanes, 1, &home).await.unwrap();
+        send(&lanes, 137, &other).await.unwrap();
+        send(&lanes, 1, &home).await.unwrap();
+        send(&lanes, 137, &other).await.unwrap();
+        
+        assert_eq!(*home.sent.lock(), vec![5, 6]);
+        assert_eq!(*other.sent.lock(), vec![40, 41]);
+        // Read once per chain, then counted locally
+        assert_eq!(*home.pending_reads.lock(), 1);
+        assert_eq!(*other.pending_reads.lock(), 1);
+    }
+    
+    #[tokio::test]
+    async fn nonce_too_low_retries_at_the_pending_nonce() {
+        let lanes = NonceLanes::new([1]);
+        let chain = FakeChain::at(5);
+        send(&lanes, 1, &chain).await.unwrap();
+        
+        // The key sent three transactions elsewhere meanwhile
+        *chain.pending.lock() = 9;
+        chain.refusals.lock().push_back("nonce too low");
+        send(&lanes, 1, &chain).await.unwrap();
+        send(&lanes, 1, &chain).await.unwrap();
+        
+        assert_eq!(*chain.sent.lock(), vec![5, 9, 10]);
+    }
+    
+    #[tokio::test]
+    async fn underpriced_retries_at_the_same_nonce_with_higher_fees() {
+        let lanes = NonceLanes::new([1]);
+        let chain = FakeChain::at(3);
+        chain.refusals.lock().push_back("replacement transaction underpriced");
+        
+        let mut tx = TypedTransaction::Legacy(TransactionRequest::new());
+        let mut fees = Fees::Legacy { gas_price: U256::from(100) };
+        lanes.send(1, &mut tx, &mut fees, &TransactionConfig::default(), &chain, &counter()).await.unwrap();
+        
+        assert_eq!(*chain.sent.lock(), vec![3]);
+        assert_eq!(fees.max_fee(), U256::from(115));
+        assert_eq!(tx.gas_price(), Some(U256::from(115)));
+    }
+    
+    #[tokio::test]
+    async fn a_failed_send_rereads_the_nonce() {
+        let lanes = NonceLanes::new([1]);
+        let chain = FakeChain::at(3);
+        chain.refusals.lock().push_back("insufficient funds for gas * price + value");
+        assert!(send(&lanes, 1, &chain).await.is_err());
+        
+        // It may have reached the mempool after all
+        *chain.pending.lock() = 4;
+        send(&lanes, 1, &chain).await.unwrap();
+        
+        assert_eq!(*chain.sent.lock(), vec![4]);
+        assert_eq!(*chain.pending_reads.lock(), 2);
+    }
+    
+    #[tokio::test]
+    async fn sends_give_up_after_max_send_attempts() {
+        let lanes = NonceLanes::new([1]);
+        let chain = FakeChain::at(3);
+        chain.refusals.lock().extend(["nonce too low"; 3]);
+        
+        let error = send(&lanes, 1, &chain).await.unwrap_err();
+        assert!(error.to_string().contains("after 3 attempts"), "{}", error);
+        assert!(chain.sent.lock().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn unknown_chains_have_no_lane() {
+        let lanes = NonceLanes::new([1]);
+        assert!(send(&lanes, 137, &FakeChain::at(0)).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn only_unreachable_chains_count_as_rpc_failures() {
+        let chain = FakeChain::at(0);
+        chain.refusals.lock().push_back("connection reset");
+        let failed_send = send(&NonceLanes::new([1]), 1, &chain).await.unwrap_err();
+        assert!(is_rpc_failure(&failed_send));
+        let provider = anyhow::Error::from(ProviderError::CustomError("timed out".to_string())).context("Reading reputation");
+        assert!(is_rpc_failure(&provider));
+        
+        let reverted = anyhow::Error::from(Reverted { tx_hash: TxHash::zero(), chain_id: 1, block: 1 });
+        assert!(!is_rpc_failure(&reverted));
+        let deferred = anyhow::anyhow!("Deferring vote on 0x01: gas prices are above the congestion threshold");
+        assert!(!is_rpc_failure(&deferred));
+    }
+}
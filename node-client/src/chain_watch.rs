@@ -0,0 +1,327 @@
+//! Chain ID and deployment drift checks for every configured RPC endpoint
+//!
+//! An RPC URL pointed at another network is the classic way to report threats to the wrong
+//! chain. At startup and every `interval_secs`, each configured endpoint's `eth_chainId` is
+//! compared with the chain the config expects of it, and the configured contracts are checked
+//! for code where `blockchain.rpc_url` points. While the write endpoint or a deployment disagrees
+//! with the config, on-chain writes are held and reports queue locally; the other endpoints feed
+//! simulations and backtests rather than the chain, so their mismatches are only logged.
+//!
+//! `blockchain.chain_id = 0` detects the chain from `blockchain.rpc_url` at startup. The chain a
+//! data directory was last run against is remembered, so moving an existing node to another
+//! network is called out even when the config was changed to match.
+
+use anyhow::{anyhow, Context, Result};
+use axum::{http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use ethers::providers::{Http, Middleware, Provider, Ws};
+use ethers::types::Address;
+use prometheus::{IntGaugeVec, Opts};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::blockchain::BlockchainClient;
+use crate::config::{BlockchainConfig, ChainWatchConfig, ExecutorKind, NodeConfig};
+use crate::contract_guard::parse_checksummed_address;
+use crate::metrics::register_once;
+use crate::status::Report;
+use crate::storage::NodeStorage;
+
+const CHAIN_NAMESPACE: &str = "chain_watch";
+const CHAIN_KEY: &str = "chain_id";
+const RPC_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointStatus {
+    Matches,
+    /// Serves another chain, or the configured contracts are not deployed on it
+    Mismatch,
+    Unreachable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointCheck {
+    /// Config key the endpoint comes from
+    pub name: String,
+    /// Scheme, host and port only; paths often carry API keys
+    pub endpoint: String,
+    pub expected_chain_id: u64,
+    pub actual_chain_id: Option<u64>,
+    pub status: EndpointStatus,
+    /// Configured contracts without code at the endpoint
+    pub missing_contracts: Vec<String>,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainCheckReport {
+    pub checked_at: u64,
+    /// Why on-chain writes are held, while they are
+    pub writes_held: Option<String>,
+    pub endpoints: Vec<EndpointCheck>,
+}
+
+struct Endpoint {
+    name: String,
+    url: String,
+    expected_chain_id: u64,
+    /// Whether the node's transactions go through it
+    writes: bool,
+}
+
+pub struct ChainWatch {
+    config: ChainWatchConfig,
+    endpoints: Vec<Endpoint>,
+    /// Contracts that must have code on the write endpoint, by config key
+    contracts: Vec<(String, Address)>,
+    blockchain: Arc<BlockchainClient>,
+    last: parking_lot::RwLock<Option<ChainCheckReport>>,
+    mismatched: IntGaugeVec,
+}
+
+impl ChainWatch {
+    pub fn new(config: &ChainWatchConfig, node: &NodeConfig, blockchain: Arc<BlockchainClient>) -> Result<Self> {
+        let chain_id = node.blockchain.chain_id;
+        let mut endpoints = vec![Endpoint {
+            name: "blockchain.rpc_url".to_string(),
+            url: node.blockchain.rpc_url.clone(),
+            expected_chain_id: chain_id,
+            writes: true,
+        }];
+        if node.executor.kind == ExecutorKind::EvmCall && !node.executor.rpc_url.is_empty() {
+            endpoints.push(Endpoint {
+                name: "executor.rpc_url".to_string(),
+                url: node.executor.rpc_url.clone(),
+                expected_chain_id: chain_id,
+                writes: false,
+            });
+        }
+        if node.mempool.enabled && !node.mempool.ws_url.is_empty() {
+            endpoints.push(Endpoint {
+                name: "mempool.ws_url".to_string(),
+                url: node.mempool.ws_url.clone(),
+                expected_chain_id: chain_id,
+                writes: false,
+            });
+        }
+        if node.backtest.enabled {
+            for archive in &node.backtest.archive_rpcs {
+                endpoints.push(Endpoint {
+                    name: format!("backtest.archive_rpcs (chain {})", archive.chain_id),
+                    url: archive.rpc_url.clone(),
+                    expected_chain_id: archive.chain_id,
+                    writes: false,
+                });
+            }
+        }
+        
+        let mut contracts = vec![(
+            "blockchain.contract_address".to_string(),
+            parse_checksummed_address(&node.blockchain.contract_address)?,
+        )];
+        for (name, address) in [
+            ("blockchain.oracle_address", &node.blockchain.oracle_address),
+            ("blockchain.token_address", &node.blockchain.token_address),
+        ] {
+            if let Some(address) = address {
+                contracts.push((name.to_string(), parse_checksummed_address(address)?));
+            }
+        }
+        
+        static MISMATCHED: OnceLock<IntGaugeVec> = OnceLock::new();
+        let mismatched = register_once(&MISMATCHED, || {
+            IntGaugeVec::new(
+                Opts::new("dagshield_chain_mismatch", "Whether a configured RPC endpoint disagrees with the config (1) or not (0)"),
+                &["endpoint"],
+            )
+        })?;
+        
+        Ok(Self {
+            config: config.clone(),
+            endpoints,
+            contracts,
+            blockchain,
+            last: parking_lot::RwLock::new(None),
+            mismatched,
+        })
+    }
+    
+    /// Check every endpoint, holding or releasing on-chain writes as the write endpoint answers
+    pub async fn check(&self) -> ChainCheckReport {
+        let mut endpoints = Vec::with_capacity(self.endpoints.len());
+        for endpoint in &self.endpoints {
+            let check = self.check_endpoint(endpoint).await;
+            self.mismatched
+                .with_label_values(&[endpoint.name.as_str()])
+                .set((check.status == EndpointStatus::Mismatch) as i64);
+            match check.status {
+                EndpointStatus::Mismatch if endpoint.writes => {
+                    error!("🚨 {} ({}) disagrees with the config: {}", check.name, check.endpoint, check.detail);
+                }
+                EndpointStatus::Mismatch => {
+                    warn!("⚠️ {} ({}) disagrees with the config: {}", check.name, check.endpoint, check.detail);
+                }
+                EndpointStatus::Unreachable => warn!("⚠️ {} ({}) not checked: {}", check.name, check.endpoint, check.detail),
+                EndpointStatus::Matches => {}
+            }
+            endpoints.push(check);
+        }
+        
+        // An unreachable endpoint fails writes on its own; only a wrong network is held here
+        let drift = endpoints
+            .iter()
+            .zip(&self.endpoints)
+            .find(|(check, endpoint)| endpoint.writes && check.status == EndpointStatus::Mismatch)
+            .map(|(check, _)| format!("{} disagrees with the config: {}", check.name, check.detail));
+        let was_held = self.blockchain.writes_held();
+        match (&drift, self.config.refuse_writes) {
+            (Some(reason), true) => {
+                if was_held.is_none() {
+                    error!("🛑 Holding on-chain writes until the chain matches the config; reports queue locally");
+                }
+                self.blockchain.hold_writes(reason.clone());
+            }
+            (Some(_), false) => warn!("⚠️ chain_watch.refuse_writes is off; transactions may go to the wrong network"),
+            (None, _) => {
+                if was_held.is_some() {
+                    info!("✅ Chain matches the config again, on-chain writes resumed");
+                    self.blockchain.release_writes();
+                }
+            }
+        }
+        
+        let report = ChainCheckReport {
+            checked_at: chrono::Utc::now().timestamp() as u64,
+            writes_held: self.blockchain.writes_held(),
+            endpoints,
+        };
+        *self.last.write() = Some(report.clone());
+        report
+    }
+    
+    async fn check_endpoint(&self, endpoint: &Endpoint) -> EndpointCheck {
+        let mut check = EndpointCheck {
+            name: endpoint.name.clone(),
+            endpoint: redact(&endpoint.url),
+            expected_chain_id: endpoint.expected_chain_id,
+            actual_chain_id: None,
+            status: EndpointStatus::Unreachable,
+            missing_contracts: Vec::new(),
+            detail: String::new(),
+        };
+        let actual = match query_chain_id(&endpoint.url).await {
+            Ok(actual) => actual,
+            Err(e) => {
+                check.detail = format!("{:#}", e);
+                return check;
+            }
+        };
+        check.actual_chain_id = Some(actual);
+        if actual != endpoint.expected_chain_id {
+            check.status = EndpointStatus::Mismatch;
+            check.detail = format!("serves chain {}, the config expects {}", actual, endpoint.expected_chain_id);
+            return check;
+        }
+        
+        if endpoint.writes {
+            for (name, address) in &self.contracts {
+                match self.blockchain.get_code(*address).await {
+                    Ok(code) if code.is_empty() => check.missing_contracts.push(name.clone()),
+                    Ok(_) => {}
+                    Err(e) => {
+                        check.detail = format!("chain {}, but {} not checked: {:#}", actual, name, e);
+                        return check;
+                    }
+                }
+            }
+            if !check.missing_contracts.is_empty() {
+                check.status = EndpointStatus::Mismatch;
+                check.detail = format!("chain {}, but no contract code at {}", actual, check.missing_contracts.join(", "));
+                return check;
+            }
+        }
+        
+        check.status = EndpointStatus::Matches;
+        check.detail = format!("chain {}", actual);
+        check
+    }
+    
+    /// The most recent check
+    pub fn report(&self) -> Option<ChainCheckReport> {
+        self.last.read().clone()
+    }
+    
+    /// Re-check every `interval_secs`; the startup check is run by whoever builds the watch
+    pub async fn start(&self) -> Result<()> {
+        let mut checks = tokio::time::interval(Duration::from_secs(self.config.interval_secs.max(30)));
+        // The first tick is immediate, right after the startup check
+        checks.tick().await;
+        loop {
+            checks.tick().await;
+            self.check().await;
+        }
+    }
+}
+
+/// Fill in `chain_id = 0` from the RPC endpoint
+pub async fn resolve_chain_id(config: &mut BlockchainConfig) -> Result<()> {
+    if config.chain_id != 0 {
+        return Ok(());
+    }
+    let detected = query_chain_id(&config.rpc_url)
+        .await
+        .context("blockchain.chain_id = 0, but the chain could not be detected")?;
+    info!("🔗 Detected chain {} from {}", detected, redact(&config.rpc_url));
+    config.chain_id = detected;
+    Ok(())
+}
+
+/// Warn when the data directory was last run against another chain, then remember this one
+pub fn remember_chain(storage: &NodeStorage, chain_id: u64) -> Result<()> {
+    match storage.get::<u64>(CHAIN_NAMESPACE, CHAIN_KEY)? {
+        Some(previous) if previous == chain_id => return Ok(()),
+        Some(previous) => {
+            error!("🚨 This data directory was last run against chain {}, now chain {}; \
+                    queued reports, cursors and receipts in it belong to chain {}", previous, chain_id, previous);
+        }
+        None => {}
+    }
+    storage.put(CHAIN_NAMESPACE, CHAIN_KEY, &chain_id)
+}
+
+async fn query_chain_id(url: &str) -> Result<u64> {
+    let query = async {
+        let chain_id = if url.starts_with("ws://") || url.starts_with("wss://") {
+            Provider::<Ws>::connect(url).await?.get_chainid().await?
+        } else {
+            Provider::<Http>::try_from(url)?.get_chainid().await?
+        };
+        Ok::<_, anyhow::Error>(chain_id.as_u64())
+    };
+    tokio::time::timeout(RPC_TIMEOUT, query)
+        .await
+        .map_err(|_| anyhow!("eth_chainId timed out after {}s", RPC_TIMEOUT.as_secs()))?
+}
+
+pub(crate) fn redact(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) => match (parsed.host_str(), parsed.port()) {
+            (Some(host), Some(port)) => format!("{}://{}:{}", parsed.scheme(), host, port),
+            (Some(host), None) => format!("{}://{}", parsed.scheme(), host),
+            (None, _) => parsed.scheme().to_string(),
+        },
+        Err(_) => "<invalid url>".to_string(),
+    }
+}
+
+/// `GET /chain` reports the most recent chain ID and deployment check
+pub fn admin_routes(watch: Arc<ChainWatch>) -> Router {
+    Router::new().route("/chain", get(move || async move {
+        match watch.report() {
+            Some(report) => Json(Report::new("chain_check", report)).into_response(),
+            None => (StatusCode::NOT_FOUND, "No chain check yet").into_response(),
+        }
+    }))
+}
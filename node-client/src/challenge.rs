@@ -0,0 +1,312 @@
+//! Typed, versioned schemas for on-chain challenge data
+//!
+//! Challenge payloads come from the chain and must be treated as untrusted: every format is
+//! parsed into a serde model and validated before a solver sees it. Payloads carry a
+//! `version` field; unversioned payloads are accepted in their legacy shape.
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::dag::Transaction;
+
+/// Largest challenge payload we are willing to parse
+pub const MAX_CHALLENGE_DATA_BYTES: usize = 1024 * 1024;
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+const MAX_ACCURACY_CASES: usize = 1_000;
+const MAX_SPEED_TRANSACTIONS: u32 = 100_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccuracyCase {
+    pub transaction: Transaction,
+    /// Ground-truth label, e.g. "phishing" or "safe"
+    pub expected_threat_type: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccuracyChallenge {
+    pub cases: Vec<AccuracyCase>,
+    /// Accuracy (0-1) the solution has to reach; unlabelled legacy challenges have none
+    #[serde(default)]
+    pub min_accuracy: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeedChallenge {
+    pub transactions: u32,
+    pub target_tps: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EfficiencyChallenge {
+    /// Efficiency score (0-100) the node has to reach
+    pub target_efficiency: u32,
+}
+
+#[derive(Debug, Clone)]
+pub enum ChallengeSpec {
+    ThreatDetectionAccuracy(AccuracyChallenge),
+    DagProcessingSpeed(SpeedChallenge),
+    EnergyEfficiency(EfficiencyChallenge),
+}
+
+#[derive(Deserialize)]
+struct Versioned<T> {
+    version: u32,
+    #[serde(flatten)]
+    body: T,
+}
+
+impl ChallengeSpec {
+    /// Parse and validate the data of a challenge of the given type
+    pub fn parse(challenge_type: &str, data: &str) -> Result<Self> {
+        if data.len() > MAX_CHALLENGE_DATA_BYTES {
+            bail!("challenge data of {} bytes exceeds the {} byte limit", data.len(), MAX_CHALLENGE_DATA_BYTES);
+        }
+        
+        let spec = match challenge_type {
+            "threat_detection_accuracy" => ChallengeSpec::ThreatDetectionAccuracy(parse_accuracy(data)?),
+            "dag_processing_speed" => ChallengeSpec::DagProcessingSpeed(parse_versioned(data)?),
+            "energy_efficiency" => ChallengeSpec::EnergyEfficiency(parse_efficiency(data)?),
+            other => bail!("unknown challenge type '{}'", other),
+        };
+        
+        spec.validate()?;
+        Ok(spec)
+    }
+    
+    fn validate(&self) -> Result<()> {
+        match self {
+            ChallengeSpec::ThreatDetectionAccuracy(c) => {
+                if c.cases.is_empty() || c.cases.len() > MAX_ACCURACY_CASES {
+                    bail!("accuracy challenge needs 1-{} cases, got {}", MAX_ACCURACY_CASES, c.cases.len());
+                }
+                if let Some(min) = c.min_accuracy {
+                    if !(0.0..=1.0).contains(&min) {
+                        bail!("min_accuracy must be between 0 and 1, got {}", min);
+                    }
+                }
+                let mut ids = HashSet::new();
+                for case in &c.cases {
+                    if case.transaction.id.is_empty() || !ids.insert(case.transaction.id.as_str()) {
+                        bail!("accuracy cases need unique, non-empty transaction ids");
+                    }
+                }
+            }
+            ChallengeSpec::DagProcessingSpeed(c) => {
+                if c.transactions == 0 || c.transactions > MAX_SPEED_TRANSACTIONS {
+                    bail!("speed challenge needs 1-{} transactions, got {}", MAX_SPEED_TRANSACTIONS, c.transactions);
+                }
+                if !c.target_tps.is_finite() || c.target_tps <= 0.0 {
+                    bail!("target_tps must be a positive number, got {}", c.target_tps);
+                }
+            }
+            ChallengeSpec::EnergyEfficiency(c) => {
+                if c.target_efficiency > 100 {
+                    bail!("target_efficiency must be at most 100, got {}", c.target_efficiency);
+                }
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+/// Parse `{"version": 1, ...}`, or the same fields without a version for legacy payloads
+fn parse_versioned<T: for<'de> Deserialize<'de>>(data: &str) -> Result<T> {
+    let value: serde_json::Value = serde_json::from_str(data)?;
+    
+    if value.get("version").is_some() {
+        let versioned: Versioned<T> = serde_json::from_value(value)?;
+        if versioned.version != CURRENT_SCHEMA_VERSION {
+            bail!("unsupported challenge schema version {}", versioned.version);
+        }
+        Ok(versioned.body)
+    } else {
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+fn parse_accuracy(data: &str) -> Result<AccuracyChallenge> {
+    // Legacy accuracy challenges are a bare array of unlabelled transactions
+    if data.trim_start().starts_with('[') {
+        let transactions: Vec<Transaction> = serde_json::from_str(data)?;
+        return Ok(AccuracyChallenge {
+            cases: transactions
+                .into_iter()
+                .map(|transaction| AccuracyCase {
+                    transaction,
+                    expected_threat_type: String::new(),
+                })
+                .collect(),
+            min_accuracy: None,
+        });
+    }
+    
+    parse_versioned(data)
+}
+
+fn parse_efficiency(data: &str) -> Result<EfficiencyChallenge> {
+    // Legacy efficiency challenges are the plain string `target_efficiency:<n>`
+    if let Some(value) = data.trim().strip_prefix("target_efficiency:") {
+        let target_efficiency = value
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("invalid legacy target_efficiency '{}'", value.trim()))?;
+        return Ok(EfficiencyChallenge { target_efficiency });
+    }
+    
+    parse_versioned(data)
+}
+
+impl AccuracyChallenge {
+    /// Whether every case carries a ground-truth label
+    pub fn is_labelled(&self) -> bool {
+        self.cases.iter().all(|c| !c.expected_threat_type.is_empty())
+    }
+    
+    pub fn transactions(&self) -> Vec<Transaction> {
+        self.cases.iter().map(|c| c.transaction.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dag::DagShape;
+    use proptest::prelude::*;
+    use serde_json::json;
+
+    fn transactions(count: usize) -> Vec<Transaction> {
+        DagShape::Wide.transactions("case", count, 1)
+    }
+
+    fn parse_err(challenge_type: &str, data: &str) -> String {
+        format!("{:#}", ChallengeSpec::parse(challenge_type, data).expect_err("invalid challenge"))
+    }
+
+    #[test]
+    fn parses_legacy_shapes() {
+        let data = serde_json::to_string(&transactions(2)).unwrap();
+        match ChallengeSpec::parse("threat_detection_accuracy", &data).unwrap() {
+            ChallengeSpec::ThreatDetectionAccuracy(c) => {
+                assert_eq!(c.cases.len(), 2);
+                assert!(!c.is_labelled());
+                assert_eq!(c.min_accuracy, None);
+            }
+            other => panic!("parsed as {:?}", other),
+        }
+
+        let data = json!({ "transactions": 500, "target_tps": 250.0 }).to_string();
+        match ChallengeSpec::parse("dag_processing_speed", &data).unwrap() {
+            ChallengeSpec::DagProcessingSpeed(c) => assert_eq!((c.transactions, c.target_tps), (500, 250.0)),
+            other => panic!("parsed as {:?}", other),
+        }
+
+        match ChallengeSpec::parse("energy_efficiency", " target_efficiency: 80 ").unwrap() {
+            ChallengeSpec::EnergyEfficiency(c) => assert_eq!(c.target_efficiency, 80),
+            other => panic!("parsed as {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_versioned_shapes() {
+        let cases: Vec<_> = transactions(3)
+            .into_iter()
+            .map(|transaction| json!({ "transaction": transaction, "expected_threat_type": "safe" }))
+            .collect();
+        let data = json!({ "version": 1, "cases": cases, "min_accuracy": 0.9 }).to_string();
+        match ChallengeSpec::parse("threat_detection_accuracy", &data).unwrap() {
+            ChallengeSpec::ThreatDetectionAccuracy(c) => {
+                assert_eq!(c.cases.len(), 3);
+                assert!(c.is_labelled());
+                assert_eq!(c.min_accuracy, Some(0.9));
+            }
+            other => panic!("parsed as {:?}", other),
+        }
+
+        let data = json!({ "version": 1, "transactions": 10, "target_tps": 1.5 }).to_string();
+        match ChallengeSpec::parse("dag_processing_speed", &data).unwrap() {
+            ChallengeSpec::DagProcessingSpeed(c) => assert_eq!((c.transactions, c.target_tps), (10, 1.5)),
+            other => panic!("parsed as {:?}", other),
+        }
+
+        let data = json!({ "version": 1, "target_efficiency": 95 }).to_string();
+        match ChallengeSpec::parse("energy_efficiency", &data).unwrap() {
+            ChallengeSpec::EnergyEfficiency(c) => assert_eq!(c.target_efficiency, 95),
+            other => panic!("parsed as {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_versions_and_types() {
+        let data = json!({ "version": 2, "transactions": 10, "target_tps": 1.5 }).to_string();
+        assert!(parse_err("dag_processing_speed", &data).contains("unsupported challenge schema version 2"));
+        let data = json!({ "version": 0, "target_efficiency": 50 }).to_string();
+        assert!(parse_err("energy_efficiency", &data).contains("unsupported challenge schema version 0"));
+        assert!(parse_err("gas_estimation", "{}").contains("unknown challenge type 'gas_estimation'"));
+    }
+
+    #[test]
+    fn rejects_data_failing_validation() {
+        let speed = |transactions: u64, target_tps: f64| {
+            json!({ "version": 1, "transactions": transactions, "target_tps": target_tps }).to_string()
+        };
+        assert!(parse_err("dag_processing_speed", &speed(0, 10.0)).contains("needs 1-100000 transactions"));
+        assert!(parse_err("dag_processing_speed", &speed(100_001, 10.0)).contains("needs 1-100000 transactions"));
+        assert!(parse_err("dag_processing_speed", &speed(10, 0.0)).contains("target_tps must be a positive number"));
+
+        assert!(parse_err("energy_efficiency", "target_efficiency:101").contains("at most 100"));
+        assert!(parse_err("energy_efficiency", "target_efficiency:high").contains("invalid legacy target_efficiency 'high'"));
+
+        assert!(parse_err("threat_detection_accuracy", "[]").contains("needs 1-1000 cases"));
+        let mut duplicated = transactions(2);
+        duplicated[1].id = duplicated[0].id.clone();
+        let data = serde_json::to_string(&duplicated).unwrap();
+        assert!(parse_err("threat_detection_accuracy", &data).contains("unique, non-empty transaction ids"));
+        let cases = json!([{ "transaction": transactions(1)[0], "expected_threat_type": "safe" }]);
+        let data = json!({ "version": 1, "cases": cases, "min_accuracy": 1.5 }).to_string();
+        assert!(parse_err("threat_detection_accuracy", &data).contains("min_accuracy must be between 0 and 1"));
+
+        let oversized = " ".repeat(MAX_CHALLENGE_DATA_BYTES + 1);
+        assert!(parse_err("energy_efficiency", &oversized).contains("exceeds the"));
+    }
+
+    fn challenge_type() -> impl Strategy<Value = String> {
+        prop_oneof![
+            Just("threat_detection_accuracy".to_string()),
+            Just("dag_processing_speed".to_string()),
+            Just("energy_efficiency".to_string()),
+            ".*",
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn parse_never_panics_on_arbitrary_data(challenge_type in challenge_type(), data in ".*") {
+            let _ = ChallengeSpec::parse(&challenge_type, &data);
+        }
+
+        #[test]
+        fn parse_never_panics_on_near_valid_data(
+            challenge_type in challenge_type(),
+            version in prop::option::of(any::<i64>()),
+            fields in prop::collection::vec(
+                ("(transactions|target_tps|target_efficiency|cases|min_accuracy|version)", any::<f64>()),
+                0..4,
+            ),
+            legacy in "target_efficiency:[ -~]{0,12}",
+        ) {
+            let mut data = serde_json::Map::new();
+            if let Some(version) = version {
+                data.insert("version".to_string(), json!(version));
+            }
+            for (field, value) in fields {
+                data.insert(field, json!(value));
+            }
+            let _ = ChallengeSpec::parse(&challenge_type, &serde_json::Value::Object(data).to_string());
+            let _ = ChallengeSpec::parse(&challenge_type, &legacy);
+        }
+    }
+}
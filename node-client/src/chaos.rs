@@ -0,0 +1,136 @@
+//! Fault injection for resilience testing.
+//!
+//! Every hook here is a no-op unless the node is built with the `chaos` feature. Test builds
+//! expose `/chaos` on the metrics server to change faults at runtime:
+//!
+//! - `GET /chaos` / `PUT /chaos` read and replace the active [`FaultConfig`]
+//! - `GET /chaos/tasks` lists killable subsystem tasks, `POST /chaos/kill/:task` aborts one
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FaultConfig {
+    /// Fraction (0-1) of RPC calls that fail before reaching the endpoint
+    pub rpc_drop_rate: f64,
+    /// Delay added to every storage write
+    pub storage_write_delay_ms: u64,
+    /// Fraction (0-1) of outgoing gossip messages that get corrupted
+    pub gossip_corrupt_rate: f64,
+}
+
+/// Fail an RPC call with the configured probability
+#[inline]
+pub fn rpc(method: &str) -> Result<()> {
+    #[cfg(feature = "chaos")]
+    if imp::roll(imp::faults().rpc_drop_rate) {
+        // Typed as a provider failure, so it is handled as a real dropped call would be
+        return Err(ethers::providers::ProviderError::CustomError(format!("chaos: dropped RPC call {}", method)).into());
+    }
+    #[cfg(not(feature = "chaos"))]
+    let _ = method;
+    Ok(())
+}
+
+/// Stall the calling storage write by the configured delay
+#[inline]
+pub fn storage_write() {
+    #[cfg(feature = "chaos")]
+    {
+        let delay = imp::faults().storage_write_delay_ms;
+        if delay > 0 {
+            std::thread::sleep(std::time::Duration::from_millis(delay));
+        }
+    }
+}
+
+/// Flip a byte of an outgoing gossip payload with the configured probability
+#[inline]
+pub fn corrupt_gossip(payload: &mut [u8]) {
+    #[cfg(feature = "chaos")]
+    if !payload.is_empty() && imp::roll(imp::faults().gossip_corrupt_rate) {
+        let index = ethers::core::rand::random::<usize>() % payload.len();
+        payload[index] ^= 0xff;
+    }
+    #[cfg(not(feature = "chaos"))]
+    let _ = payload;
+}
+
+/// Make a subsystem task killable through the admin API
+#[inline]
+pub fn register_task<T>(name: &str, handle: &JoinHandle<T>) {
+    #[cfg(feature = "chaos")]
+    imp::tasks().insert(name.to_string(), handle.abort_handle());
+    #[cfg(not(feature = "chaos"))]
+    let _ = (name, handle);
+}
+
+#[cfg(feature = "chaos")]
+pub use imp::admin_routes;
+
+#[cfg(feature = "chaos")]
+mod imp {
+    use super::*;
+    use axum::extract::Path;
+    use axum::http::StatusCode;
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use dashmap::DashMap;
+    use parking_lot::RwLock;
+    use std::sync::OnceLock;
+    use tokio::task::AbortHandle;
+    use tracing::warn;
+    
+    fn state() -> &'static RwLock<FaultConfig> {
+        static FAULTS: OnceLock<RwLock<FaultConfig>> = OnceLock::new();
+        FAULTS.get_or_init(|| RwLock::new(FaultConfig::default()))
+    }
+    
+    pub fn faults() -> FaultConfig {
+        state().read().clone()
+    }
+    
+    pub fn tasks() -> &'static DashMap<String, AbortHandle> {
+        static TASKS: OnceLock<DashMap<String, AbortHandle>> = OnceLock::new();
+        TASKS.get_or_init(DashMap::new)
+    }
+    
+    pub fn roll(rate: f64) -> bool {
+        rate > 0.0 && ethers::core::rand::random::<f64>() < rate
+    }
+    
+    pub fn admin_routes() -> Router {
+        Router::new()
+            .route("/chaos", get(|| async { Json(faults()) }).put(set_faults))
+            .route("/chaos/tasks", get(list_tasks))
+            .route("/chaos/kill/:task", post(kill_task))
+    }
+    
+    async fn set_faults(Json(config): Json<FaultConfig>) -> Json<FaultConfig> {
+        warn!("💥 Chaos faults set: {:?}", config);
+        *state().write() = config.clone();
+        Json(config)
+    }
+    
+    async fn list_tasks() -> Json<Vec<(String, bool)>> {
+        let mut tasks: Vec<(String, bool)> = tasks()
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().is_finished()))
+            .collect();
+        tasks.sort();
+        Json(tasks)
+    }
+    
+    async fn kill_task(Path(task): Path<String>) -> StatusCode {
+        match tasks().get(&task) {
+            Some(handle) => {
+                warn!("💥 Chaos: killing subsystem task {}", task);
+                handle.abort();
+                StatusCode::NO_CONTENT
+            }
+            None => StatusCode::NOT_FOUND,
+        }
+    }
+}
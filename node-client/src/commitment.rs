@@ -0,0 +1,361 @@
+//! Experimental succinct commitments to each reporting epoch's detections
+//!
+//! Flagged detections are collected per epoch, model and chain as Merkle leaves. Once an epoch
+//! closes, the root of each tree is committed on its chain's deployment with the detection count
+//! and the model hash: the statement "N detections above threshold on this chain were produced by
+//! model M". The node can
+//! later prove any single detection's inclusion without the others being revealed, and an
+//! attached [`EpochProver`] can prove the statement as a whole.
+//!
+//! Leaves and inner nodes are keccak256 with distinct prefixes, so roots are cheap to check in
+//! the EVM. No prover ships yet; commitments carry no proof unless one is attached.
+
+use anyhow::{bail, Result};
+use axum::{extract::Path, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use ethers::abi::{encode, Token};
+use ethers::types::U256;
+use ethers::utils::{hex, keccak256};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::blockchain::BlockchainClient;
+use crate::config::CommitmentConfig;
+use crate::storage::NodeStorage;
+
+pub const EPOCH_LEAF_NAMESPACE: &str = "epoch_leaves";
+pub const EPOCH_COMMITMENT_NAMESPACE: &str = "epoch_commitments";
+
+/// Stands in for the model hash of detections made on rules alone
+const RULES_MODEL_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionLeaf {
+    pub transaction_id: String,
+    pub chain_id: u64,
+    pub threat_type: String,
+    /// Confidence in basis points
+    pub confidence_bps: u32,
+    /// The threshold it cleared, in basis points
+    pub threshold_bps: u32,
+    pub detected_at: u64,
+}
+
+impl DetectionLeaf {
+    pub fn hash(&self) -> [u8; 32] {
+        let encoded = encode(&[
+            Token::FixedBytes(keccak256(self.transaction_id.as_bytes()).to_vec()),
+            Token::Uint(U256::from(self.chain_id)),
+            Token::FixedBytes(keccak256(self.threat_type.as_bytes()).to_vec()),
+            Token::Uint(U256::from(self.confidence_bps)),
+            Token::Uint(U256::from(self.threshold_bps)),
+            Token::Uint(U256::from(self.detected_at)),
+        ]);
+        keccak256([&[LEAF_PREFIX][..], &encoded].concat())
+    }
+}
+
+/// What an epoch commitment claims
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochStatement {
+    pub epoch: u64,
+    /// Hex hash of the model; all zeros for detections made on rules alone
+    pub model_hash: String,
+    /// The chain the detections were made on
+    #[serde(default)]
+    pub chain_id: u64,
+    pub root: String,
+    pub detections: u64,
+    /// Lowest threshold any committed detection cleared, in basis points
+    pub min_threshold_bps: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochCommitment {
+    pub statement: EpochStatement,
+    /// Name of the prover, when one was attached
+    pub proof_system: Option<String>,
+    pub proof: Option<String>,
+    /// `None` until the commitment is on-chain, or when on-chain posting is disabled
+    pub tx_hash: Option<String>,
+    pub committed_at: u64,
+}
+
+/// One detection's path to its epoch root, sibling hashes from the leaf up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub epoch: u64,
+    pub model_hash: String,
+    pub leaf: DetectionLeaf,
+    pub index: usize,
+    pub siblings: Vec<String>,
+    pub root: String,
+}
+
+/// Produces a succinct proof of an epoch statement over its leaves
+pub trait EpochProver: Send + Sync {
+    fn name(&self) -> &str;
+    fn prove(&self, statement: &EpochStatement, leaves: &[DetectionLeaf]) -> Result<Vec<u8>>;
+}
+
+pub struct EpochCommitter {
+    config: CommitmentConfig,
+    storage: Arc<NodeStorage>,
+    /// `None` when commitments are kept locally only
+    blockchain: Option<Arc<BlockchainClient>>,
+    prover: OnceLock<Arc<dyn EpochProver>>,
+}
+
+impl EpochCommitter {
+    pub fn new(config: &CommitmentConfig, storage: Arc<NodeStorage>, blockchain: Option<Arc<BlockchainClient>>) -> Result<Self> {
+        if config.epoch_secs == 0 {
+            bail!("commitments.epoch_secs must be positive");
+        }
+        Ok(Self {
+            config: config.clone(),
+            storage,
+            blockchain,
+            prover: OnceLock::new(),
+        })
+    }
+    
+    /// Prove each epoch statement before it is committed
+    pub fn attach_prover(&self, prover: Arc<dyn EpochProver>) {
+        if self.prover.set(prover).is_err() {
+            warn!("⚠️ Epoch prover already attached");
+        }
+    }
+    
+    pub fn epoch_of(&self, at_secs: u64) -> u64 {
+        at_secs / self.config.epoch_secs
+    }
+    
+    /// Add a flagged detection to the current epoch's tree for `model_hash`
+    pub fn record(&self, leaf: DetectionLeaf, model_hash: Option<&str>) -> Result<()> {
+        let key = format!("{:020}-{}-{}", self.epoch_of(leaf.detected_at), model_hash.unwrap_or(RULES_MODEL_HASH), leaf.transaction_id);
+        self.storage.put(EPOCH_LEAF_NAMESPACE, &key, &leaf)
+    }
+    
+    /// Commit epochs as they close, retrying ones that failed to go on-chain
+    pub async fn start(&self) -> Result<()> {
+        info!("🌳 Committing detections every {}s epoch{}", self.config.epoch_secs,
+              if self.blockchain.is_some() { ", on-chain" } else { "" });
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.epoch_secs.min(60)));
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.commit_closed_epochs().await {
+                error!("❌ Detection epoch commitment failed: {:#}", e);
+            }
+        }
+    }
+    
+    /// Stored commitments, oldest epoch first
+    pub fn commitments(&self) -> Result<Vec<EpochCommitment>> {
+        Ok(self.storage.scan(EPOCH_COMMITMENT_NAMESPACE)?.into_iter().map(|(_, commitment)| commitment).collect())
+    }
+    
+    /// Inclusion proof of a committed detection, while its epoch is retained
+    pub fn inclusion_proof(&self, transaction_id: &str) -> Result<Option<InclusionProof>> {
+        for ((epoch, model_hash, chain_id), leaves) in self.leaves_by_tree()? {
+            let Some(index) = leaves.iter().position(|leaf| leaf.transaction_id == transaction_id) else {
+                continue;
+            };
+            let commitment_key = commitment_key(epoch, &model_hash, chain_id);
+            if self.storage.get::<EpochCommitment>(EPOCH_COMMITMENT_NAMESPACE, &commitment_key)?.is_none() {
+                // Its epoch is still open
+                return Ok(None);
+            }
+            let hashes: Vec<[u8; 32]> = leaves.iter().map(DetectionLeaf::hash).collect();
+            return Ok(Some(InclusionProof {
+                epoch,
+                model_hash,
+                leaf: leaves[index].clone(),
+                index,
+                siblings: merkle_path(&hashes, index).iter().map(|hash| format!("0x{}", hex::encode(hash))).collect(),
+                root: format!("0x{}", hex::encode(merkle_root(&hashes))),
+            }));
+        }
+        Ok(None)
+    }
+    
+    async fn commit_closed_epochs(&self) -> Result<()> {
+        let now = chrono::Utc::now().timestamp() as u64;
+        let current = self.epoch_of(now);
+        
+        for ((epoch, model_hash, chain_id), leaves) in self.leaves_by_tree()? {
+            if epoch >= current {
+                continue;
+            }
+            let key = commitment_key(epoch, &model_hash, chain_id);
+            let mut commitment = match self.storage.get::<EpochCommitment>(EPOCH_COMMITMENT_NAMESPACE, &key)? {
+                Some(commitment) if commitment.tx_hash.is_some() || self.blockchain.is_none() => continue,
+                Some(commitment) => commitment,
+                None => self.build_commitment(epoch, &model_hash, chain_id, &leaves, now)?,
+            };
+            
+            if let Some(blockchain) = &self.blockchain {
+                let proof = commitment.proof.as_deref().map(|proof| hex::decode(proof.trim_start_matches("0x"))).transpose()?;
+                let posted = blockchain
+                    .commit_detection_epoch(
+                        chain_id,
+                        epoch,
+                        bytes32(&model_hash)?,
+                        bytes32(&commitment.statement.root)?,
+                        commitment.statement.detections,
+                        proof.unwrap_or_default(),
+                    )
+                    .await;
+                match posted {
+                    Ok(tx_hash) => commitment.tx_hash = Some(tx_hash),
+                    Err(e) => warn!("⚠️ Epoch {} commitment on chain {} not posted yet: {:#}", epoch, chain_id, e),
+                }
+            }
+            self.storage.put(EPOCH_COMMITMENT_NAMESPACE, &key, &commitment)?;
+            info!("🌳 Committed epoch {} on chain {}: {} detections by model {} under root {}{}", epoch, chain_id,
+                  commitment.statement.detections, &model_hash[..12], commitment.statement.root,
+                  commitment.tx_hash.as_deref().map_or(String::new(), |tx_hash| format!(" ({})", tx_hash)));
+        }
+        
+        self.prune(current)
+    }
+    
+    fn build_commitment(
+        &self,
+        epoch: u64,
+        model_hash: &str,
+        chain_id: u64,
+        leaves: &[DetectionLeaf],
+        now: u64,
+    ) -> Result<EpochCommitment> {
+        let hashes: Vec<[u8; 32]> = leaves.iter().map(DetectionLeaf::hash).collect();
+        let statement = EpochStatement {
+            epoch,
+            model_hash: model_hash.to_string(),
+            chain_id,
+            root: format!("0x{}", hex::encode(merkle_root(&hashes))),
+            detections: leaves.len() as u64,
+            min_threshold_bps: leaves.iter().map(|leaf| leaf.threshold_bps).min().unwrap_or_default(),
+        };
+        let (proof_system, proof) = match self.prover.get() {
+            Some(prover) => (Some(prover.name().to_string()), Some(format!("0x{}", hex::encode(prover.prove(&statement, leaves)?)))),
+            None => (None, None),
+        };
+        Ok(EpochCommitment { statement, proof_system, proof, tx_hash: None, committed_at: now })
+    }
+    
+    /// Leaves of each epoch's tree per model and chain, in the order they were recorded
+    fn leaves_by_tree(&self) -> Result<BTreeMap<(u64, String, u64), Vec<DetectionLeaf>>> {
+        let mut trees: BTreeMap<(u64, String, u64), Vec<DetectionLeaf>> = BTreeMap::new();
+        for (key, leaf) in self.storage.scan::<DetectionLeaf>(EPOCH_LEAF_NAMESPACE)? {
+            let mut parts = key.splitn(3, '-');
+            let (Some(epoch), Some(model_hash)) = (parts.next().and_then(|epoch| epoch.parse().ok()), parts.next()) else {
+                continue;
+            };
+            trees.entry((epoch, model_hash.to_string(), leaf.chain_id)).or_default().push(leaf);
+        }
+        for leaves in trees.values_mut() {
+            // Storage order is by key; the tree is ordered by detection time, then transaction
+            leaves.sort_by(|a, b| (a.detected_at, &a.transaction_id).cmp(&(b.detected_at, &b.transaction_id)));
+        }
+        Ok(trees)
+    }
+    
+    /// Drop leaves and commitments of epochs past retention
+    fn prune(&self, current: u64) -> Result<()> {
+        let oldest = format!("{:020}", current.saturating_sub(self.config.retained_epochs));
+        let mut batch = self.storage.batch();
+        let leaves = self.storage.scan::<DetectionLeaf>(EPOCH_LEAF_NAMESPACE)?.into_iter().map(|(key, _)| key);
+        let commitments = self.storage.scan::<EpochCommitment>(EPOCH_COMMITMENT_NAMESPACE)?.into_iter().map(|(key, _)| key);
+        for key in leaves.take_while(|key| key.as_str() < oldest.as_str()) {
+            batch.delete(EPOCH_LEAF_NAMESPACE, &key);
+        }
+        for key in commitments.take_while(|key| key.as_str() < oldest.as_str()) {
+            batch.delete(EPOCH_COMMITMENT_NAMESPACE, &key);
+        }
+        if !batch.is_empty() {
+            self.storage.commit(batch)?;
+        }
+        Ok(())
+    }
+}
+
+/// Key of an epoch's commitment for one model and chain; starts with the epoch, so pruning
+/// stops at the first retained one
+fn commitment_key(epoch: u64, model_hash: &str, chain_id: u64) -> String {
+    format!("{:020}-{}-{}", epoch, model_hash, chain_id)
+}
+
+/// Root over leaf hashes, pairing left to right and carrying an odd node up unchanged
+pub fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    if leaves.is_empty() {
+        return [0; 32];
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_pair(left, right),
+                [single] => *single,
+                _ => unreachable!("chunks of two"),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+/// Sibling hashes from the leaf at `index` up to the root; levels where it has none are skipped
+pub fn merkle_path(leaves: &[[u8; 32]], mut index: usize) -> Vec<[u8; 32]> {
+    let mut path = Vec::new();
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        let sibling = index ^ 1;
+        if sibling < level.len() {
+            path.push(level[sibling]);
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_pair(left, right),
+                [single] => *single,
+                _ => unreachable!("chunks of two"),
+            })
+            .collect();
+        index /= 2;
+    }
+    path
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    keccak256([&[NODE_PREFIX][..], left, right].concat())
+}
+
+fn bytes32(value: &str) -> Result<[u8; 32]> {
+    hex::decode(value.trim_start_matches("0x"))?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("{} is not 32 bytes", value))
+}
+
+/// `/commitments` and `/commitments/:transaction_id` admin endpoints
+pub fn admin_routes(committer: Arc<EpochCommitter>) -> Router {
+    let list = Arc::clone(&committer);
+    Router::new()
+        .route("/commitments", get(move || async move {
+            match list.commitments() {
+                Ok(commitments) => Json(commitments).into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            }
+        }))
+        .route("/commitments/:transaction_id", get(move |Path(transaction_id): Path<String>| async move {
+            match committer.inclusion_proof(&transaction_id) {
+                Ok(Some(proof)) => Json(proof).into_response(),
+                Ok(None) => StatusCode::NOT_FOUND.into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            }
+        }))
+}
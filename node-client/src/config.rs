@@ -4,8 +4,26 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
+/// Subsystem switches come first: TOML needs plain keys ahead of any table.
+/// Turning them off runs slim roles from the one binary, e.g. a pure sensor (`enable_oracle`
+/// off), a pure oracle voter (`enable_ai` and `enable_p2p` off) or a pure relay (only `enable_p2p`).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeConfig {
+    /// Screen transactions with the model and rules, and report what is flagged
+    #[serde(default = "default_true")]
+    pub enable_ai: bool,
+    /// Follow contract events into the verified alert cache and solve accuracy challenges
+    #[serde(default = "default_true")]
+    pub enable_oracle: bool,
+    /// Accept transactions from chains other than `blockchain.chain_id`
+    #[serde(default = "default_true")]
+    pub enable_cross_chain: bool,
+    /// Join the gossip mesh: share intel, relay it and cross-check verdicts with peers
+    #[serde(default = "default_true")]
+    pub enable_p2p: bool,
+    /// Serve the peers, watchlist, maintenance and status endpoints next to `/metrics`
+    #[serde(default = "default_true")]
+    pub enable_admin_api: bool,
     pub node: NodeSettings,
     pub blockchain: BlockchainConfig,
     pub ai: AIConfig,
@@ -13,96 +31,1984 @@ pub struct NodeConfig {
     pub storage: StorageConfig,
     pub energy: EnergyConfig,
     pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub workers: WorkerPoolConfig,
+    #[serde(default)]
+    pub work_tokens: WorkTokenConfig,
+    #[serde(default)]
+    pub fleet: FleetConfig,
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+    #[serde(default)]
+    pub memory: MemoryConfig,
+    #[serde(default)]
+    pub screening: ScreeningConfig,
+    #[serde(default)]
+    pub alert_cache: AlertCacheConfig,
+    #[serde(default)]
+    pub ipfs: IpfsConfig,
+    #[serde(default)]
+    pub gas_oracle: GasOracleConfig,
+    #[serde(default)]
+    pub rollback: RollbackConfig,
+    #[serde(default)]
+    pub updater: UpdaterConfig,
+    #[serde(default)]
+    pub stats_reporting: StatsReportingConfig,
+    #[serde(default)]
+    pub watchlists: WatchlistConfig,
+    #[serde(default)]
+    pub address_graph: AddressGraphConfig,
+    #[serde(default)]
+    pub pattern_feed: PatternFeedConfig,
+    #[serde(default)]
+    pub replication: ReplicationConfig,
+    #[serde(default)]
+    pub backtest: BacktestConfig,
+    #[serde(default)]
+    pub crash_reports: CrashReportConfig,
+    #[serde(default)]
+    pub query: QueryConfig,
+    #[serde(default)]
+    pub commitments: CommitmentConfig,
+    #[serde(default)]
+    pub mempool: MempoolConfig,
+    #[serde(default)]
+    pub light_client: LightClientConfig,
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    #[serde(default)]
+    pub mirror: MirrorConfig,
+    #[serde(default)]
+    pub dag_checkpoints: DagCheckpointConfig,
+    #[serde(default)]
+    pub receipts: ReceiptConfig,
+    #[serde(default)]
+    pub executor: ExecutorConfig,
+    #[serde(default)]
+    pub loadgen: LoadGenConfig,
+    #[serde(default)]
+    pub chain_watch: ChainWatchConfig,
+    #[serde(default)]
+    pub archive: ArchiveConfig,
+    #[serde(default)]
+    pub dag_conflicts: DagConflictConfig,
+    #[serde(default)]
+    pub dag_inference: DagInferenceConfig,
+    #[serde(default)]
+    pub dag_orphans: DagOrphanConfig,
+    #[serde(default)]
+    pub dag_backpressure: DagBackpressureConfig,
+    #[serde(default)]
+    pub dag_epochs: DagEpochConfig,
+    #[serde(default)]
+    pub dag_retry: DagRetryConfig,
+    #[serde(default)]
+    pub ingest: IngestConfig,
+    #[serde(default)]
+    pub reporting_guard: ReportingGuardConfig,
+    #[serde(default)]
+    pub report_queue: ReportQueueConfig,
+    #[serde(default)]
+    pub dag_sync: DagSyncConfig,
+    #[serde(default)]
+    pub penalty_alerts: PenaltyAlertConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeSettings {
-    pub stake_amount: u64,
+    /// Stake sent when registering, in gwei
+    #[serde(alias = "stake_amount")]
+    pub stake_amount_gwei: u64,
     pub reputation_threshold: u32,
     pub max_concurrent_tasks: usize,
+    /// Main-loop cadence at startup, and under steady load when the heartbeat is adaptive
     pub heartbeat_interval_secs: u64,
     pub challenge_timeout_secs: u64,
+    #[serde(default)]
+    pub adaptive_heartbeat: AdaptiveHeartbeatConfig,
+    #[serde(default)]
+    pub stake: StakeConfig,
+}
+
+/// Watching the node's stake against the contract minimum
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakeConfig {
+    pub check_interval_secs: u64,
+    /// Warn once the stake is within this percentage above the minimum
+    pub warn_margin_percent: u64,
+    /// Top the stake back up to `stake_amount_gwei` from the node wallet once it is within the margin
+    pub auto_restake: bool,
+}
+
+impl Default for StakeConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: 300,
+            warn_margin_percent: 10,
+            auto_restake: false,
+        }
+    }
+}
+
+/// Main-loop cadence following activity: tighter during ingestion or threat spikes, looser when idle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptiveHeartbeatConfig {
+    pub enabled: bool,
+    pub min_interval_secs: u64,
+    pub max_interval_secs: u64,
+    /// Rate over its long-run average, in either ingestion or threats, that counts as a spike
+    pub spike_factor: f64,
+}
+
+impl Default for AdaptiveHeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_interval_secs: 5,
+            max_interval_secs: 120,
+            spike_factor: 2.0,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockchainConfig {
     pub rpc_url: String,
+    /// Used in order while `rpc_url` is down or lagging behind the chain
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
+    #[serde(default)]
+    pub rpc_pool: RpcPoolConfig,
+    /// 0 detects the chain from `rpc_url` at startup
     pub chain_id: u64,
     pub contract_address: String,
+    /// DAGOracle deployment, written by `deploy-contracts`
+    #[serde(default)]
+    pub oracle_address: Option<String>,
+    /// DAG token deployment, written by `deploy-contracts`
+    #[serde(default)]
+    pub token_address: Option<String>,
+    /// Hex node key; leave empty when `key` says where it comes from instead
     pub private_key: String,
+    #[serde(default)]
+    pub key: Option<KeySource>,
+    /// What signs transactions and messages
+    #[serde(default)]
+    pub signer: SignerConfig,
     pub gas_limit: u64,
     pub gas_price_gwei: u64,
+    /// Check at startup that the contract exposes every expected function selector
+    #[serde(default = "default_true")]
+    pub verify_contract_interface: bool,
+    /// How contract events are followed
+    #[serde(default)]
+    pub events: EventTransport,
+    /// `ws://` or `wss://` URL with `events = "ws"`, socket path with `events = "ipc"`
+    #[serde(default)]
+    pub events_endpoint: String,
+    /// Other chains with their own DAGShield deployment; threats on them are reported there,
+    /// signed with the same key, rather than on this chain
+    #[serde(default)]
+    pub chains: Vec<ChainEndpoint>,
+    #[serde(default)]
+    pub fees: FeeConfig,
+    #[serde(default)]
+    pub transactions: TransactionConfig,
+    /// How long the contract's open challenges are cached before being read again
+    #[serde(default = "default_challenge_refresh_secs")]
+    pub challenge_refresh_secs: u64,
+}
+
+/// A node key kept out of the config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum KeySource {
+    /// Encrypted JSON keystore, e.g. one written by `dagshield-node keygen`; its passphrase is
+    /// read from `password_env`, or asked for at the terminal when that is unset
+    Keystore {
+        path: String,
+        #[serde(default = "default_password_env")]
+        password_env: String,
+    },
+    /// BIP-39 mnemonic read from `mnemonic_env`
+    Mnemonic {
+        mnemonic_env: String,
+        #[serde(default = "default_derivation_path")]
+        derivation_path: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SignerConfig {
+    /// The key from `private_key` or `key`, in memory
+    #[default]
+    Local,
+    /// An AWS KMS `ECC_SECG_P256K1` key; needs the `aws-kms` feature. Credentials come from the
+    /// standard `AWS_*` variables, the region from them too when `region` is empty
+    AwsKms {
+        key_id: String,
+        #[serde(default)]
+        region: String,
+    },
+    /// A Cloud KMS `EC_SIGN_SECP256K1_SHA256` key version with the address it signs as; needs the
+    /// `gcp-kms` feature. Credentials come from `GOOGLE_APPLICATION_CREDENTIALS` or the metadata server
+    GcpKms {
+        key_version: String,
+        address: String,
+    },
+    /// A JSON-RPC signer such as Clef or Web3Signer, holding `address`; Clef also fronts a Ledger
+    Remote {
+        url: String,
+        address: String,
+        /// Variable holding a bearer token for the signer, if it wants one
+        #[serde(default)]
+        auth_token_env: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainEndpoint {
+    pub chain_id: u64,
+    pub rpc_url: String,
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
+    pub contract_address: String,
+    pub gas_limit: u64,
+    /// Used while the gas oracle has no price for the chain
+    pub gas_price_gwei: u64,
+    /// Send legacy `gasPrice` transactions, for chains without EIP-1559
+    #[serde(default)]
+    pub legacy_fees: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcPoolConfig {
+    /// Consecutive transport failures after which an endpoint is skipped
+    pub max_failures: u32,
+    /// How long a failing endpoint is skipped before it is tried again
+    pub cooldown_secs: u64,
+    pub health_check_interval_secs: u64,
+    /// An endpoint further behind the best head among its pool is skipped until it catches up
+    pub max_lag_blocks: u64,
+}
+
+impl Default for RpcPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_failures: 3,
+            cooldown_secs: 30,
+            health_check_interval_secs: 15,
+            max_lag_blocks: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeConfig {
+    pub strategy: FeeStrategy,
+    /// Send legacy `gasPrice` transactions on `rpc_url`, for chains without EIP-1559
+    pub legacy: bool,
+    /// How long fees read from fee history are reused while the gas oracle has none
+    pub refresh_interval_secs: u64,
+    /// Most a single transaction may cost at its gas limit, in gwei; 0 = unbounded
+    pub max_tx_fee_gwei: u64,
+}
+
+impl Default for FeeConfig {
+    fn default() -> Self {
+        Self {
+            strategy: FeeStrategy::Standard,
+            legacy: false,
+            refresh_interval_secs: 12,
+            max_tx_fee_gwei: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionConfig {
+    /// A sent transaction not mined after this long is replaced with higher fees
+    pub stuck_after_secs: u64,
+    /// Fee increase per replacement; nodes reject replacements below 10
+    pub bump_percent: u64,
+    pub max_replacements: u32,
+    /// Sends retried after "nonce too low" or "replacement underpriced"
+    pub max_send_attempts: u32,
+    /// Give up waiting for any of a transaction's replacements after this long
+    pub receipt_timeout_secs: u64,
+}
+
+impl Default for TransactionConfig {
+    fn default() -> Self {
+        Self {
+            stuck_after_secs: 90,
+            bump_percent: 15,
+            max_replacements: 3,
+            max_send_attempts: 3,
+            receipt_timeout_secs: 900,
+        }
+    }
+}
+
+/// How far above the going rate transactions bid
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeStrategy {
+    /// One urgency up, e.g. votes priced like reports
+    Fast,
+    #[default]
+    Standard,
+    /// One urgency down, e.g. reports priced like votes
+    Economy,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventTransport {
+    /// A log filter polled over `rpc_url`
+    #[default]
+    Poll,
+    /// An `eth_subscribe` log subscription over a websocket
+    Ws,
+    /// An `eth_subscribe` log subscription over an IPC socket
+    Ipc,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AIConfig {
+    pub model_path: String,
+    /// Expected blake3 hash of the model at `model_path`, checked by `preflight`
+    #[serde(default)]
+    pub model_checksum: Option<String>,
+    pub confidence_threshold: f32,
+    pub batch_size: usize,
+    /// Width of the model input; extracted features are padded or truncated to it
+    pub max_sequence_length: usize,
+    /// How often the signed pattern feed is polled, when enabled
+    pub update_interval_hours: u64,
+    /// TOML/YAML files with operator-defined detection rules
+    #[serde(default)]
+    pub rule_files: Vec<String>,
+    #[serde(default = "default_rule_reload_secs")]
+    pub rule_reload_interval_secs: u64,
+    /// How often `model_path` is checked for a replaced model
+    #[serde(default = "default_model_reload_secs")]
+    pub model_reload_interval_secs: u64,
+    /// Dummy inferences run through each model before it serves, so the first real
+    /// transactions don't pay for graph initialization
+    #[serde(default = "default_warmup_inferences")]
+    pub warmup_inferences: usize,
+    /// Verdicts kept in the detection cache; the least recently used are evicted first
+    #[serde(default = "default_detection_cache_max_entries")]
+    pub detection_cache_max_entries: usize,
+    /// Age after which a cached verdict is recomputed
+    #[serde(default = "default_detection_cache_ttl_secs")]
+    pub detection_cache_ttl_secs: u64,
+    /// Per-chain overrides of the model and threshold; other chains use the global ones
+    #[serde(default)]
+    pub chain_models: Vec<ChainModelConfig>,
+    #[serde(default)]
+    pub drift: DriftConfig,
+    #[serde(default)]
+    pub ensemble: EnsembleConfig,
+    #[serde(default)]
+    pub calibration: CalibrationConfig,
+    #[serde(default)]
+    pub bytecode: BytecodeConfig,
+    #[serde(default)]
+    pub approvals: ApprovalConfig,
+    #[serde(default)]
+    pub domains: DomainReputationConfig,
+    #[serde(default)]
+    pub pipeline: DetectionPipelineConfig,
+    #[serde(default)]
+    pub registry: ModelRegistryConfig,
+}
+
+impl AIConfig {
+    pub fn chain_model(&self, chain_id: u64) -> Option<&ChainModelConfig> {
+        self.chain_models.iter().find(|chain| chain.chain_id == chain_id)
+    }
+
+    pub fn confidence_threshold_for(&self, chain_id: u64) -> f32 {
+        self.chain_model(chain_id)
+            .and_then(|chain| chain.confidence_threshold)
+            .unwrap_or(self.confidence_threshold)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainModelConfig {
+    pub chain_id: u64,
+    /// Model trained on this chain's traffic; the global model when unset
+    #[serde(default)]
+    pub model_path: Option<String>,
+    #[serde(default)]
+    pub confidence_threshold: Option<f32>,
+}
+
+/// Comparison of live model traffic against the traffic seen just after the model loaded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftConfig {
+    pub enabled: bool,
+    /// Model verdicts in the reference window and in the sliding window compared against it
+    pub window_size: usize,
+    /// Population stability index of confidences above which the model has drifted
+    pub psi_threshold: f64,
+    /// Feature mean shift, in reference standard deviations, above which the model has drifted
+    pub feature_shift_threshold: f64,
+    /// Model input positions left out of the feature comparison; the timestamp grows by design
+    pub ignored_features: Vec<usize>,
+    /// Detect with rules instead of the drifted model until a new model is loaded
+    pub fallback_to_rules: bool,
+}
+
+impl Default for DriftConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_size: 2_000,
+            psi_threshold: 0.25,
+            feature_shift_threshold: 4.0,
+            ignored_features: vec![1],
+            fallback_to_rules: false,
+        }
+    }
+}
+
+/// The streaming pipeline between the DAG processor and the node's reporting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionPipelineConfig {
+    /// Detection workers; 0 = one per inference thread
+    pub workers: usize,
+    /// Processed transactions waiting for a worker before the DAG processor is held back;
+    /// urgent transactions have a queue of this size of their own
+    pub ingestion_queue: usize,
+    /// Verdicts waiting for the node before the workers are held back
+    pub results_queue: usize,
+}
+
+impl Default for DetectionPipelineConfig {
+    fn default() -> Self {
+        Self {
+            workers: 0,
+            ingestion_queue: 1024,
+            results_queue: 256,
+        }
+    }
+}
+
+/// Named model versions kept side by side, with shadow evaluation of a candidate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelRegistryConfig {
+    pub enabled: bool,
+    /// Where registered model files are kept
+    pub dir: String,
+    /// Share of the active model's transactions the candidate is also run on
+    pub shadow_sample_rate: f64,
+    /// Graded shadow verdicts before a candidate is judged against the active model
+    pub min_graded: u64,
+}
+
+impl Default for ModelRegistryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dir: "./models/registry".to_string(),
+            shadow_sample_rate: 1.0,
+            min_graded: 200,
+        }
+    }
+}
+
+/// Running the model and the rules together and weighing their scores into one verdict
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnsembleConfig {
+    /// When disabled the model decides alone, and rules only stand in while no model is loaded
+    pub enabled: bool,
+    pub model_weight: f32,
+    pub rules_weight: f32,
+    /// Threat score at which a single source flags the transaction whatever the other says
+    pub override_confidence: f32,
+}
+
+impl Default for EnsembleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model_weight: 0.6,
+            rules_weight: 0.4,
+            override_confidence: 0.9,
+        }
+    }
+}
+
+/// Mapping the model's raw threat scores to probabilities fitted on graded verdicts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationConfig {
+    pub enabled: bool,
+    pub method: CalibrationMethod,
+    /// Graded verdicts needed before a map is fitted; scores are used raw until then
+    pub min_samples: usize,
+    /// Graded verdicts kept for fitting, oldest dropped first
+    pub max_samples: usize,
+    /// Graded verdicts between refits
+    pub refit_every: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalibrationMethod {
+    /// One fitted constant dividing the score's logit; works from a few hundred verdicts
+    Temperature,
+    /// Any monotone map; needs a few thousand verdicts to beat temperature scaling
+    Isotonic,
+}
+
+impl Default for CalibrationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            method: CalibrationMethod::Temperature,
+            min_samples: 200,
+            max_samples: 5_000,
+            refit_every: 50,
+        }
+    }
+}
+
+/// Fetching and scanning target contract code on the node's own chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BytecodeConfig {
+    pub enabled: bool,
+    /// Contract profiles kept; each costs up to three RPC calls to build
+    pub cache_size: usize,
+    /// Budget for fetching a profile during detection; detection proceeds without one past it
+    pub fetch_timeout_ms: u64,
+    /// Etherscan-compatible API used to check for verified source; unverified code is not scored without it
+    pub explorer_api_url: Option<String>,
+    pub api_key: String,
+}
+
+impl Default for BytecodeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cache_size: 50_000,
+            fetch_timeout_ms: 2_000,
+            explorer_api_url: None,
+            api_key: String::new(),
+        }
+    }
+}
+
+/// Decoding and scoring of token approvals by who they let spend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalConfig {
+    pub enabled: bool,
+    /// Known drainer spenders
+    pub drainer_addresses: Vec<String>,
+    /// Files with one drainer address per line, read at startup
+    pub drainer_list_files: Vec<String>,
+}
+
+impl Default for ApprovalConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            drainer_addresses: Vec::new(),
+            drainer_list_files: Vec::new(),
+        }
+    }
+}
+
+/// Phishing domain blocklists checked against the URLs a transaction carries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainReputationConfig {
+    pub enabled: bool,
+    /// eth-phishing-detect format feeds fetched over HTTP
+    pub feed_urls: Vec<String>,
+    /// Local feeds in the same format, read at startup and on every refresh
+    pub feed_files: Vec<String>,
+    pub refresh_interval_secs: u64,
+    /// Confidence of a phishing verdict for a blocklisted domain
+    pub blocklist_confidence: f32,
+    /// Confidence of a phishing verdict for a lookalike of a fuzzy-listed domain
+    pub lookalike_confidence: f32,
+}
+
+impl Default for DomainReputationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            feed_urls: Vec::new(),
+            feed_files: Vec::new(),
+            refresh_interval_secs: 3600,
+            blocklist_confidence: 0.95,
+            lookalike_confidence: 0.75,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    pub listen_port: u16,
+    pub bootstrap_peers: Vec<String>,
+    pub max_peers: usize,
+    pub discovery_interval_secs: u64,
+    /// Peer IDs that are never served and whose intel is ignored
+    #[serde(default)]
+    pub blocked_peers: Vec<String>,
+    #[serde(default)]
+    pub reciprocity: ReciprocityConfig,
+    #[serde(default)]
+    pub cross_check: CrossCheckConfig,
+    #[serde(default)]
+    pub gossip: GossipConfig,
+}
+
+/// How intel requests from peers are prioritised when the node is busy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReciprocityConfig {
+    /// Deprioritise free-riders while congested; when disabled every peer is served in arrival order
+    pub enabled: bool,
+    /// Useful intel received per request served below which a peer counts as a free-rider
+    pub min_give_take_ratio: f64,
+    /// Requests served to a new peer before its ratio is taken into account
+    pub grace_requests: u64,
+    /// Pending intel requests at which the node counts as congested
+    pub congestion_queue_depth: usize,
+    /// Deprioritised requests kept waiting; the oldest are refused beyond this
+    pub max_deferred_requests: usize,
+    /// Invalid messages after which a peer is blocked automatically (0 disables)
+    pub auto_block_invalid_messages: u64,
+    pub block_duration_secs: u64,
+    pub flush_interval_secs: u64,
+}
+
+impl Default for ReciprocityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_give_take_ratio: 0.1,
+            grace_requests: 50,
+            congestion_queue_depth: 64,
+            max_deferred_requests: 256,
+            auto_block_invalid_messages: 20,
+            block_duration_secs: 3600,
+            flush_interval_secs: 60,
+        }
+    }
+}
+
+/// Periodic comparison of this node's verdicts with its peers'
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossCheckConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// Recent on-chain transactions sampled per round
+    pub sample_size: usize,
+    pub peers_per_round: usize,
+    /// Peer verdicts a transaction needs before it is compared
+    pub min_peer_answers: usize,
+    /// Compared transactions a round needs before its divergence is judged
+    pub min_compared: usize,
+    /// Share of compared transactions disagreeing with the peer majority that raises an alert
+    pub divergence_threshold: f64,
+}
+
+impl Default for CrossCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 600,
+            sample_size: 200,
+            peers_per_round: 8,
+            min_peer_answers: 3,
+            min_compared: 20,
+            divergence_threshold: 0.2,
+        }
+    }
+}
+
+/// Signing gossip with the node key and keeping evidence of peers that misuse theirs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GossipConfig {
+    /// Messages sequenced further ahead of this node's clock are dropped
+    pub max_clock_skew_secs: u64,
+    /// Messages sequenced longer ago are dropped as replays
+    pub max_message_age_secs: u64,
+    /// Recent messages remembered to catch a signer equivocating
+    pub max_tracked_messages: usize,
+    /// Submit captured evidence to the contract; disputes other than equivocation need IPFS
+    pub submit_evidence: bool,
+    pub submit_interval_secs: u64,
+    pub retained_days: u64,
+}
+
+impl Default for GossipConfig {
+    fn default() -> Self {
+        Self {
+            max_clock_skew_secs: 30,
+            max_message_age_secs: 600,
+            max_tracked_messages: 50_000,
+            submit_evidence: false,
+            submit_interval_secs: 600,
+            retained_days: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageConfig {
+    pub data_dir: String,
+    pub max_db_size_gb: u64,
+    pub backup_interval_hours: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergyConfig {
+    pub monitoring_enabled: bool,
+    pub target_efficiency_score: u32,
+    pub power_limit_watts: f32,
+    pub carbon_tracking_enabled: bool,
+    /// Seconds between readings kept in the energy history; 0 keeps none
+    #[serde(default = "default_energy_history_interval_secs")]
+    pub history_interval_secs: u64,
+    #[serde(default = "default_energy_history_retention_hours")]
+    pub history_retention_hours: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    pub enabled: bool,
+    pub port: u16,
+    pub export_interval_secs: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkerPoolConfig {
+    /// Threads dedicated to AI inference (0 = half of the available cores)
+    pub inference_threads: usize,
+    /// DAG transactions executed at once (0 = half of the available cores)
+    pub dag_threads: usize,
+    /// Pin inference threads to the cores listed below
+    pub pin_cores: bool,
+    pub inference_cores: Vec<usize>,
+}
+
+/// When heavy background work (backfill, compaction, benchmarks, learning rounds) may run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkTokenConfig {
+    /// Heavy work items allowed to run at the same time
+    pub max_concurrent: usize,
+    pub max_temperature_celsius: f32,
+    /// Defer heavy work on battery below this charge level
+    pub min_battery_percent: f32,
+    /// Defer heavy work above this fraction of `energy.power_limit_watts`
+    pub max_power_fraction: f32,
+    pub retry_after_secs: u64,
+}
+
+impl Default for WorkTokenConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 2,
+            max_temperature_celsius: 80.0,
+            min_battery_percent: 30.0,
+            max_power_fraction: 0.9,
+            retry_after_secs: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetConfig {
+    /// Run as a managed agent of a fleet controller
+    pub enabled: bool,
+    pub controller_url: String,
+    /// Identifies this fleet in the signed update digest, so an update signed for another
+    /// fleet is rejected here
+    pub fleet_id: String,
+    /// Addresses whose signatures are accepted on configuration/model/pattern updates
+    pub trusted_signers: Vec<String>,
+    pub health_interval_secs: u64,
+    pub max_reconnect_backoff_secs: u64,
+    /// Where configuration updates from the controller are written
+    pub config_path: String,
+}
+
+impl Default for FleetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            controller_url: "https://fleet.dagshield.local:7443".to_string(),
+            fleet_id: String::new(),
+            trusted_signers: vec![],
+            health_interval_secs: 30,
+            max_reconnect_backoff_secs: 300,
+            config_path: "config.toml".to_string(),
+        }
+    }
+}
+
+/// Linux-only process hardening (landlock + seccomp)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SandboxConfig {
+    pub enabled: bool,
+    /// Additional read-only paths, e.g. for plugins
+    pub extra_read_paths: Vec<String>,
+    pub extra_write_paths: Vec<String>,
+    /// Syscalls to exempt from the default deny list
+    pub allow_syscalls: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryConfig {
+    /// Process memory budget in MB (0 disables enforcement)
+    pub max_memory_mb: u64,
+    /// Fraction of the budget at which caches start being shrunk
+    pub pressure_threshold: f32,
+    /// Minimum fraction of each cache dropped per pressure round
+    pub shrink_fraction: f32,
+    pub check_interval_secs: u64,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            max_memory_mb: 2048,
+            pressure_threshold: 0.85,
+            shrink_fraction: 0.25,
+            check_interval_secs: 15,
+        }
+    }
+}
+
+/// Screening API offered to external customers (tenants)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreeningConfig {
+    pub enabled: bool,
+    pub listen_port: u16,
+    /// How often per-tenant billing counters are persisted
+    pub usage_flush_interval_secs: u64,
+    #[serde(default)]
+    pub tenants: Vec<TenantConfig>,
+}
+
+impl Default for ScreeningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen_port: 8081,
+            usage_flush_interval_secs: 60,
+            tenants: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantConfig {
+    pub id: String,
+    pub api_key: String,
+    /// Overrides `ai.confidence_threshold` for this tenant's verdicts
+    #[serde(default)]
+    pub confidence_threshold: Option<f32>,
+    /// Addresses that are never flagged for this tenant
+    #[serde(default)]
+    pub allowlist: Vec<String>,
+    /// Addresses that are always flagged for this tenant
+    #[serde(default)]
+    pub denylist: Vec<String>,
+    #[serde(default = "default_tenant_rate_limit")]
+    pub rate_limit_per_minute: u32,
+    /// Where asynchronous screening results are delivered
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+}
+
+/// Local cache of on-chain verified threat alerts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertCacheConfig {
+    pub enabled: bool,
+    pub refresh_interval_secs: u64,
+    /// Cached alerts older than this are ignored by lookups until refreshed
+    pub max_staleness_secs: u64,
+    /// Upper bound on contract reads per refresh round
+    pub max_refresh_per_round: usize,
+    /// Alerts still unverified this long after being raised count as rejected when grading
+    /// verdicts; 0 only grades verified alerts
+    #[serde(default = "default_rejection_window_secs")]
+    pub rejection_window_secs: u64,
+}
+
+impl Default for AlertCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            refresh_interval_secs: 30,
+            max_staleness_secs: 900,
+            max_refresh_per_round: 200,
+            rejection_window_secs: default_rejection_window_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpfsConfig {
+    pub enabled: bool,
+    /// Kubo RPC endpoint of a local or embedded node; empty for gateway-only (no pinning)
+    pub api_url: String,
+    pub gateway_url: String,
+    pub pin: bool,
+    pub timeout_secs: u64,
+    /// Evidence is stored as one raw block so its CID matches the on-chain digest
+    pub max_block_bytes: usize,
+}
+
+impl Default for IpfsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            api_url: "http://127.0.0.1:5001".to_string(),
+            gateway_url: "https://ipfs.io".to_string(),
+            pin: true,
+            timeout_secs: 30,
+            max_block_bytes: 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasOracleConfig {
+    pub enabled: bool,
+    pub sample_interval_secs: u64,
+    /// Number of per-block samples kept per chain
+    pub window_size: usize,
+    /// Blocks requested from eth_feeHistory per sampling round
+    pub fee_history_blocks: u64,
+    /// Estimates older than this are ignored and senders fall back to `gas_price_gwei`
+    pub max_estimate_age_secs: u64,
+    /// Deferrable transactions (votes) wait while the base fee is above this percentile
+    pub congestion_percentile: String,
+}
+
+impl Default for GasOracleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sample_interval_secs: 12,
+            window_size: 300,
+            fee_history_blocks: 10,
+            max_estimate_age_secs: 120,
+            congestion_percentile: "p75".to_string(),
+        }
+    }
+}
+
+/// When a model or pattern update is rolled back for losing accuracy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollbackConfig {
+    pub enabled: bool,
+    /// Graded outcomes needed after an update before it is judged
+    pub min_samples: u64,
+    /// Roll back when accuracy falls this far (0-1) below the pre-update baseline
+    pub max_accuracy_drop: f64,
+    /// Accuracy floor used when there were too few outcomes before the update for a baseline
+    pub min_accuracy: f64,
+    /// Recent outcomes the baseline is computed over
+    pub baseline_window: usize,
+}
+
+impl Default for RollbackConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_samples: 100,
+            max_accuracy_drop: 0.05,
+            min_accuracy: 0.8,
+            baseline_window: 500,
+        }
+    }
+}
+
+/// Self-update from a signed release manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdaterConfig {
+    pub enabled: bool,
+    /// Release channel to follow ("stable" or "beta")
+    pub channel: String,
+    pub manifest_url: String,
+    /// Addresses whose signatures are accepted on release manifests
+    pub trusted_signers: Vec<String>,
+    pub check_interval_secs: u64,
+    /// Binary to replace; empty for the running executable
+    pub binary_path: String,
+    /// Exit code used to ask the service manager for a restart (pair with systemd's RestartForceExitStatus)
+    pub restart_exit_code: i32,
+    /// Starts of a new binary that never comes up before the previous one is restored
+    pub max_boot_attempts: u32,
+}
+
+impl Default for UpdaterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            channel: "stable".to_string(),
+            manifest_url: "https://releases.dagshield.io/node/manifest.json".to_string(),
+            trusted_signers: vec![],
+            check_interval_secs: 6 * 3600,
+            binary_path: String::new(),
+            restart_exit_code: 75,
+            max_boot_attempts: 3,
+        }
+    }
+}
+
+/// Signed per-chain processing statistics for the network stats aggregator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsReportingConfig {
+    pub enabled: bool,
+    pub endpoint: String,
+    pub interval_secs: u64,
+    /// Counts are rounded to the nearest multiple of this (1 = exact)
+    pub count_granularity: u64,
+    /// Chains with fewer analysed transactions in a period are left out of its report
+    pub min_transactions_per_chain: u64,
+    /// Uptime is rounded down to a multiple of this
+    pub uptime_granularity_secs: u64,
+}
+
+impl Default for StatsReportingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "https://stats.dagshield.io/v1/node-stats".to_string(),
+            interval_secs: 3600,
+            count_granularity: 10,
+            min_transactions_per_chain: 20,
+            uptime_granularity_secs: 3600,
+        }
+    }
+}
+
+/// Addresses the node watches closely: treasuries, admin keys, bridges
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchlistConfig {
+    pub enabled: bool,
+    /// Threshold applied to transactions touching a watched address, instead of `ai.confidence_threshold`
+    pub confidence_threshold: f32,
+    /// Alert on every transaction touching a watched address, not just flagged ones
+    pub alert_on_any_activity: bool,
+    pub webhook_url: Option<String>,
+    pub telegram_bot_token: Option<String>,
+    pub telegram_chat_id: Option<String>,
+    #[serde(default)]
+    pub addresses: Vec<WatchedAddress>,
+}
+
+impl Default for WatchlistConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            confidence_threshold: 0.4,
+            alert_on_any_activity: false,
+            webhook_url: None,
+            telegram_bot_token: None,
+            telegram_chat_id: None,
+            addresses: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchedAddress {
+    pub address: String,
+    #[serde(default)]
+    pub label: String,
+    /// e.g. "treasury", "admin_key", "bridge"
+    #[serde(default)]
+    pub category: String,
+    /// Overrides `watchlists.confidence_threshold` for this address
+    #[serde(default)]
+    pub confidence_threshold: Option<f32>,
+}
+
+/// Address interaction graph feeding graph features to the detector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressGraphConfig {
+    pub enabled: bool,
+    /// Interactions older than this are forgotten
+    pub lookback_hours: u64,
+    /// Window in which fresh counterparties of a sender count as a burst
+    pub burst_window_secs: u64,
+    /// Funding hops followed back when looking for mixers
+    pub ancestry_depth: usize,
+    /// Known mixer contracts; addresses funded from them within `ancestry_depth` hops are marked
+    pub mixer_addresses: Vec<String>,
+    pub max_edges_per_address: usize,
+    pub max_cached_addresses: usize,
+    pub flush_interval_secs: u64,
+    /// Busiest targets per refresh whose target-side features are precomputed; 0 disables
+    #[serde(default = "default_hot_targets")]
+    pub hot_targets: usize,
+    /// How often hot targets are re-ranked and their features recomputed
+    #[serde(default = "default_hot_target_refresh_secs")]
+    pub hot_target_refresh_secs: u64,    /// Addresses known to belong to drainer or scam clusters, besides those with verified alerts
+    #[serde(default)]
+    pub known_bad_addresses: Vec<String>,
+    /// Hops searched, along edges in either direction, for a known-bad address
+    #[serde(default = "default_bad_cluster_depth")]
+    pub bad_cluster_depth: usize,
+}
+
+impl Default for AddressGraphConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lookback_hours: 24 * 7,
+            burst_window_secs: 3600,
+            ancestry_depth: 4,
+            mixer_addresses: vec![],
+            max_edges_per_address: 256,
+            max_cached_addresses: 200_000,
+            flush_interval_secs: 60,
+            hot_targets: default_hot_targets(),
+            hot_target_refresh_secs: default_hot_target_refresh_secs(),
+            known_bad_addresses: vec![],
+            bad_cluster_depth: default_bad_cluster_depth(),
+        }
+    }
+}
+
+/// Signed threat pattern bundles pulled every `ai.update_interval_hours`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternFeedConfig {
+    pub enabled: bool,
+    /// `https://` URL or `ipfs://<cid>` of the signed bundle
+    pub url: String,
+    /// Addresses whose signatures are accepted on pattern bundles
+    pub trusted_signers: Vec<String>,
+    pub max_bundle_bytes: usize,
+}
+
+impl Default for PatternFeedConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: "https://feeds.dagshield.io/patterns/latest.json".to_string(),
+            trusted_signers: vec![],
+            max_bundle_bytes: 4 * 1024 * 1024,
+        }
+    }
+}
+
+/// Storage snapshots for read replicas, and read-replica mode itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationConfig {
+    /// Serve storage snapshots to replicas on the metrics port (`GET /replication/snapshot`)
+    pub serve_snapshots: bool,
+    /// Bearer token replicas present to the primary; required on both sides
+    pub auth_token: String,
+    /// Metrics URL of the primary; when set the node runs as a read replica of it
+    pub primary_url: String,
+    pub sync_interval_secs: u64,
+}
+
+impl ReplicationConfig {
+    pub fn is_replica(&self) -> bool {
+        !self.primary_url.is_empty()
+    }
+}
+
+impl Default for ReplicationConfig {
+    fn default() -> Self {
+        Self {
+            serve_snapshots: false,
+            auth_token: String::new(),
+            primary_url: String::new(),
+            sync_interval_secs: 300,
+        }
+    }
+}
+
+/// Periodic replay of known exploits through the current detection pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestConfig {
+    pub enabled: bool,
+    /// TOML list of exploits and the transactions they were carried out with
+    pub exploits_file: String,
+    pub interval_hours: u64,
+    /// Each run's report is written here as `backtest-<unix time>.json`
+    pub report_dir: String,
+    /// Archive RPC per chain; the node's own chain falls back to `blockchain.rpc_url`
+    pub archive_rpcs: Vec<ArchiveRpcConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveRpcConfig {
+    pub chain_id: u64,
+    pub rpc_url: String,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            exploits_file: "./fixtures/exploits.toml".to_string(),
+            interval_hours: 24,
+            report_dir: "./data/backtests".to_string(),
+            archive_rpcs: Vec::new(),
+        }
+    }
+}
+
+/// What happens when a subsystem task panics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReportConfig {
+    /// Every crash report is also posted here as JSON
+    pub webhook_url: Option<String>,
+    /// Crash reports kept in storage, oldest dropped first
+    pub max_stored: usize,
+    /// Consecutive crashes a task is restarted after; it is left stopped after one more
+    pub max_restarts: u32,
+    /// Delay before the first restart, doubled with each consecutive crash
+    pub restart_backoff_secs: u64,
+    pub max_restart_backoff_secs: u64,
+    /// A task that ran this long before crashing starts its count of consecutive crashes over
+    pub healthy_after_secs: u64,
+}
+
+impl Default for CrashReportConfig {
+    fn default() -> Self {
+        Self {
+            webhook_url: None,
+            max_stored: 100,
+            max_restarts: 5,
+            restart_backoff_secs: 1,
+            max_restart_backoff_secs: 300,
+            healthy_after_secs: 600,
+        }
+    }
+}
+
+/// Read-only SQL over stored detections, events, energy history and the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryConfig {
+    /// Serve `POST /query` on the admin API
+    pub enabled: bool,
+    /// Rows returned per query; the result is marked truncated beyond this
+    pub max_rows: usize,
+    /// A query running longer is interrupted
+    pub timeout_ms: u64,
+    /// Queries within this long of each other share one snapshot of storage
+    pub snapshot_ttl_secs: u64,
+}
+
+impl Default for QueryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_rows: 1000,
+            timeout_ms: 5000,
+            snapshot_ttl_secs: 30,
+        }
+    }
+}
+
+/// Experimental per-epoch Merkle commitments to the node's detections
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitmentConfig {
+    /// Requires the AI detector
+    pub enabled: bool,
+    /// Length of a reporting epoch; detections are committed once their epoch closes
+    pub epoch_secs: u64,
+    /// Post each commitment to the contract rather than only storing it
+    pub post_on_chain: bool,
+    /// Epochs whose leaves and commitments are kept for inclusion proofs
+    pub retained_epochs: u64,
+}
+
+impl Default for CommitmentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            epoch_secs: 3600,
+            post_on_chain: true,
+            retained_epochs: 168,
+        }
+    }
+}
+
+/// Scanning pending transactions for threats before they are mined
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolConfig {
+    /// Requires the AI detector
+    pub enabled: bool,
+    /// Websocket endpoint for `eth_subscribe`; when empty, `txpool_content` is polled over `rpc_url`
+    pub ws_url: String,
+    pub poll_interval_ms: u64,
+    /// Pending transactions taken into the DAG per second; the rest are counted and dropped
+    pub max_per_second: usize,
+    /// Skip value transfers without calldata
+    pub skip_plain_transfers: bool,
+    /// Transaction hashes remembered so each is scanned once
+    pub seen_capacity: usize,
+}
+
+impl Default for MempoolConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ws_url: String::new(),
+            poll_interval_ms: 1000,
+            max_per_second: 200,
+            skip_plain_transfers: true,
+            seen_capacity: 100_000,
+        }
+    }
 }
 
+/// Feeding confirmed blocks from `blockchain.rpc_url` into the DAG
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AIConfig {
-    pub model_path: String,
-    pub confidence_threshold: f32,
-    pub batch_size: usize,
-    pub max_sequence_length: usize,
-    pub update_interval_hours: u64,
+pub struct IngestConfig {
+    /// Requires the AI detector
+    pub enabled: bool,
+    /// Blocks behind the head before a block is ingested
+    pub confirmations: u64,
+    /// First block on a fresh data directory; 0 starts at the confirmed head
+    pub start_block: u64,
+    pub poll_interval_ms: u64,
+    /// Blocks ingested per poll while catching up
+    pub max_blocks_per_poll: u64,
+    /// Unprocessed DAG transactions above which ingestion waits
+    pub max_pending: usize,
+    /// Dependencies inferred per transaction from addresses it shares with earlier ones
+    pub max_dependencies: usize,
+    /// Add the addresses internal calls touched, from `trace_block`; needs a tracing node
+    pub traces: bool,
+    /// Skip value transfers without calldata
+    pub skip_plain_transfers: bool,
 }
 
+impl Default for IngestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            confirmations: 12,
+            start_block: 0,
+            poll_interval_ms: 2000,
+            max_blocks_per_poll: 20,
+            max_pending: 50_000,
+            max_dependencies: 4,
+            traces: false,
+            skip_plain_transfers: true,
+        }
+    }
+}
+
+/// WebSocket risk lookups and push warnings for end-user wallets
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NetworkConfig {
+pub struct LightClientConfig {
+    pub enabled: bool,
     pub listen_port: u16,
-    pub bootstrap_peers: Vec<String>,
-    pub max_peers: usize,
-    pub discovery_interval_secs: u64,
+    pub max_connections: usize,
+    /// Addresses and prefixes one connection may subscribe to
+    pub max_subscriptions: usize,
+    /// Requests one connection may make per minute; the rest are answered with an error
+    pub requests_per_minute: u32,
+    /// Answer queries by address-hash prefix, so wallets need not reveal the address
+    pub allow_anonymous: bool,
+    /// Shortest prefix accepted, in hex characters; shorter ones would enumerate the index
+    pub min_prefix_len: usize,
+    /// Risky addresses returned per prefix query
+    pub max_prefix_matches: usize,
+}
+
+impl Default for LightClientConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen_port: 8082,
+            max_connections: 1000,
+            max_subscriptions: 64,
+            requests_per_minute: 120,
+            allow_anonymous: true,
+            min_prefix_len: 4,
+            max_prefix_matches: 256,
+        }
+    }
 }
 
+/// How long each category of stored data is kept; an unset category is kept forever
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StorageConfig {
-    pub data_dir: String,
-    pub max_db_size_gb: u64,
-    pub backup_interval_hours: u64,
+pub struct RetentionConfig {
+    /// Run the janitor on a schedule; previews and sweeps on the admin API work either way
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// Only log what scheduled sweeps would expire
+    pub dry_run: bool,
+    /// Threat reports this node submitted
+    pub detections_days: Option<u64>,
+    /// Evidence pins; never longer than `detections_days`
+    pub evidence_days: Option<u64>,
+    /// Stored energy readings, on top of `energy.history_retention_hours`
+    pub energy_days: Option<u64>,
+    /// Older audit events are folded into one, so rebuilt counters are unaffected
+    pub audit_days: Option<u64>,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: 3600,
+            dry_run: false,
+            detections_days: None,
+            evidence_days: None,
+            energy_days: None,
+            audit_days: None,
+        }
+    }
 }
 
+/// Live transactions duplicated to a second, side-effect-free detection pipeline and compared
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct EnergyConfig {
-    pub monitoring_enabled: bool,
-    pub target_efficiency_score: u32,
-    pub power_limit_watts: f32,
-    pub carbon_tracking_enabled: bool,
+pub struct MirrorConfig {
+    pub enabled: bool,
+    /// TOML file whose `[ai]` table configures the shadow pipeline's model, rules and thresholds;
+    /// a copy of the node's own config file will do
+    pub shadow_config_path: String,
+    /// Share of live transactions mirrored, from 0.0 to 1.0
+    pub sample_rate: f64,
+    /// Mirrored transactions waiting for the shadow; beyond it they are dropped, never waited on
+    pub queue_capacity: usize,
+    /// Confidence gap counted as a divergence even when both pipelines flag alike
+    pub confidence_tolerance: f32,
+    /// Most recent divergences kept for the report
+    pub max_divergences: usize,
+    /// Each interval's report is written here as `mirror-<unix time>.json`
+    pub report_dir: String,
+    pub report_interval_secs: u64,
+}
+
+impl Default for MirrorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            shadow_config_path: "./config.shadow.toml".to_string(),
+            sample_rate: 1.0,
+            queue_capacity: 1024,
+            confidence_tolerance: 0.1,
+            max_divergences: 500,
+            report_dir: "./data/mirror".to_string(),
+            report_interval_secs: 3600,
+        }
+    }
 }
 
+/// Processed DAG transactions are finalized into stored checkpoints and pruned from memory
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MetricsConfig {
+pub struct DagCheckpointConfig {
     pub enabled: bool,
-    pub port: u16,
-    pub export_interval_secs: u64,
+    pub interval_secs: u64,
+    /// Checkpoints a finalized transaction stays in memory for before it is pruned; 0 prunes
+    /// it as soon as it is checkpointed
+    pub retention_depth: u64,
+    /// Checkpoints kept in storage, 0 for all. Transactions depending on one finalized in an
+    /// older checkpoint are no longer recognised as ready
+    pub retained_checkpoints: u64,
+}
+
+impl Default for DagCheckpointConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 60,
+            retention_depth: 2,
+            retained_checkpoints: 10_080,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// The transaction that entered the DAG first
+    FirstSeen,
+    /// The highest priority fee, then the first seen
+    HighestPriority,
+}
+
+/// Which of the DAG's transactions touching the same nonce or storage slot goes first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagConflictConfig {
+    pub enabled: bool,
+    /// Picks the one transaction that executes of those spending the same nonce, and the order
+    /// of those writing the same storage slot
+    pub policy: ConflictPolicy,
+}
+
+impl Default for DagConflictConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            policy: ConflictPolicy::FirstSeen,
+        }
+    }
+}
+
+/// Dependencies derived for transactions submitted without any
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagInferenceConfig {
+    pub enabled: bool,
+    /// Depend on the sender's transaction with the previous nonce
+    pub by_nonce: bool,
+    /// Depend on the latest transaction to the same target address
+    pub by_target: bool,
+}
+
+impl Default for DagInferenceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            by_nonce: true,
+            by_target: true,
+        }
+    }
+}
+
+/// Transactions waiting for a dependency that has not reached the DAG yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagOrphanConfig {
+    pub enabled: bool,
+    /// How long a transaction waits for its dependencies before it is dropped
+    pub timeout_secs: u64,
+    /// Transactions arriving while this many wait are dropped
+    pub max_orphans: usize,
+}
+
+impl Default for DagOrphanConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            timeout_secs: 300,
+            max_orphans: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackpressureMode {
+    /// Fail the submission with a `Backpressure` error
+    Reject,
+    /// Hold the submission until the DAG drains, up to `max_wait_ms`
+    Wait,
+}
+
+/// Limits on the unprocessed transactions the DAG holds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagBackpressureConfig {
+    pub enabled: bool,
+    /// Submissions are pushed back once this many transactions are unprocessed
+    pub high_watermark: usize,
+    /// and accepted again once no more than this many are
+    pub low_watermark: usize,
+    pub mode: BackpressureMode,
+    pub max_wait_ms: u64,
+}
+
+impl Default for DagBackpressureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            high_watermark: 100_000,
+            low_watermark: 80_000,
+            mode: BackpressureMode::Reject,
+            max_wait_ms: 5_000,
+        }
+    }
+}
+
+/// On-chain commitment of the epoch roots DAG checkpoints seal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagEpochConfig {
+    /// Requires `dag_checkpoints`; epochs are sealed either way
+    pub commit_on_chain: bool,
+    /// How often sealed epochs are committed, and failed commitments retried
+    pub commit_interval_secs: u64,
+}
+
+impl Default for DagEpochConfig {
+    fn default() -> Self {
+        Self {
+            commit_on_chain: false,
+            commit_interval_secs: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailurePolicy {
+    /// Dependents run as if the failed transaction had succeeded
+    Skip,
+    /// Dependents fail without running, and theirs in turn
+    Cascade,
+}
+
+/// Retries of transactions the executor could not run, e.g. while its RPC endpoint was down
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagRetryConfig {
+    /// Attempts in all, including the first; 1 never retries
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubling for each one after
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    /// What happens to the dependents of a transaction that failed for good or was rejected
+    pub on_failure: FailurePolicy,
+}
+
+impl Default for DagRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+            on_failure: FailurePolicy::Skip,
+        }
+    }
+}
+
+/// DAG synchronization with peers, over the P2P network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagSyncConfig {
+    pub enabled: bool,
+    /// How often tips are reconciled with a random peer
+    pub interval_secs: u64,
+    /// Recent checkpoints asked for by a node starting without any; only adopted from a peer
+    /// that commits its DAG epochs on-chain
+    pub max_checkpoints: usize,
+    /// Unfinalized transactions advertised to a peer per round, newest first
+    pub max_tips: usize,
+    pub max_transactions_per_request: usize,
+}
+
+impl Default for DagSyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 30,
+            max_checkpoints: 100,
+            max_tips: 1000,
+            max_transactions_per_request: 256,
+        }
+    }
+}
+
+/// Signed first-seen receipts exchanged with peers, and first-reporter claims made with them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptConfig {
+    pub enabled: bool,
+    /// How far ahead of this node's clock a peer's receipt may be stamped
+    pub max_clock_skew_secs: u64,
+    /// Days a detection's receipt chain is kept after its first receipt
+    pub retained_days: u64,
+    /// Claim the first-reporter reward on-chain when this node's receipt is the earliest known
+    pub claim_rewards: bool,
+    /// How often stale chains are pruned and closed claims are settled
+    pub sweep_interval_secs: u64,
+}
+
+impl Default for ReceiptConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_clock_skew_secs: 30,
+            retained_days: 30,
+            claim_rewards: false,
+            sweep_interval_secs: 600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutorKind {
+    Noop,
+    EvmCall,
+}
+
+/// How the DAG processor executes transactions once their dependencies are done
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutorConfig {
+    pub kind: ExecutorKind,
+    /// Endpoint the `evm_call` executor simulates against; `blockchain.rpc_url` when empty
+    pub rpc_url: String,
+    pub timeout_ms: u64,
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        Self {
+            kind: ExecutorKind::Noop,
+            rpc_url: String::new(),
+            timeout_ms: 2000,
+        }
+    }
+}
+
+/// Synthetic load runs started through the admin API, for soak testing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadGenConfig {
+    pub enabled: bool,
+    /// Highest target rate a run may ask for, in transactions per second
+    pub max_tps: f64,
+    pub report_dir: String,
+}
+
+impl Default for LoadGenConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_tps: 5000.0,
+            report_dir: "./data/loadgen".to_string(),
+        }
+    }
+}
+
+/// Checks that every configured RPC serves the chain the config expects of it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainWatchConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// Hold on-chain writes while `blockchain.rpc_url` or the contract deployments disagree with
+    /// the config; when off, mismatches are only logged
+    pub refuse_writes: bool,
+}
+
+impl Default for ChainWatchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 300,
+            refuse_writes: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveBackend {
+    S3,
+    Gcs,
+    /// A local directory, for tests and for mounted storage
+    File,
+}
+
+/// Cold storage for detections the retention janitor is about to expire
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveConfig {
+    pub enabled: bool,
+    pub backend: ArchiveBackend,
+    pub bucket: String,
+    /// Key prefix inside the bucket
+    pub prefix: String,
+    /// S3-compatible endpoint (MinIO, R2, ...); AWS when empty
+    pub endpoint: String,
+    pub region: String,
+    /// S3 credentials; the standard `AWS_*` environment variables when empty
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// GCS service account key; `GOOGLE_*` environment variables when empty
+    pub service_account_path: String,
+    /// Root for the `file` backend
+    pub directory: String,
+    pub max_segment_entries: usize,
+    /// gzip level, 0-9
+    pub compression_level: u32,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: ArchiveBackend::S3,
+            bucket: String::new(),
+            prefix: "dagshield".to_string(),
+            endpoint: String::new(),
+            region: "us-east-1".to_string(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            service_account_path: String::new(),
+            directory: "./data/archive".to_string(),
+            max_segment_entries: 10_000,
+            compression_level: 6,
+        }
+    }
+}
+
+/// Suspends auto-reporting when the share of flagged verdicts runs away from its baseline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportingGuardConfig {
+    pub enabled: bool,
+    pub window_secs: u64,
+    /// Normal windows averaged into the baseline
+    pub baseline_windows: usize,
+    /// Window flag rate, as a multiple of the baseline, that suspends reporting
+    pub trip_multiplier: f64,
+    /// Verdicts a window needs before it is judged or joins the baseline
+    pub min_verdicts: u64,
+    /// Floor under the baseline, so a quiet baseline does not trip on a handful of flags
+    pub min_baseline_rate: f64,
+    /// Receives a JSON alert when reporting is suspended
+    pub webhook_url: Option<String>,
+}
+
+impl Default for ReportingGuardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 300,
+            baseline_windows: 12,
+            trip_multiplier: 3.0,
+            min_verdicts: 200,
+            min_baseline_rate: 0.01,
+            webhook_url: None,
+        }
+    }
+}
+
+/// Threat reports queued while they cannot go on-chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportQueueConfig {
+    /// Reports of the same threat on the same target and chain within this window are queued once
+    pub dedup_window_secs: u64,
+    /// Wait before retrying a failed report, doubled with every further failure
+    pub initial_backoff_secs: u64,
+    pub max_backoff_secs: u64,
+    /// Failed submissions after which a report moves to the dead-letter list
+    pub max_attempts: u32,
+}
+
+impl Default for ReportQueueConfig {
+    fn default() -> Self {
+        Self {
+            dedup_window_secs: 3600,
+            initial_backoff_secs: 30,
+            max_backoff_secs: 3600,
+            max_attempts: 10,
+        }
+    }
+}
+
+/// Alerts when the contract slashes or deactivates this node, or its reputation drops
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PenaltyAlertConfig {
+    pub enabled: bool,
+    /// How often the node's reputation and active flag are read from the contract
+    pub check_interval_secs: u64,
+    /// Reputation lost between two checks that counts as a penalty
+    pub reputation_drop_threshold: u32,
+    pub actions: Vec<PenaltyAction>,
+    /// Receives a JSON alert with the `webhook` action
+    pub webhook_url: Option<String>,
+}
+
+impl Default for PenaltyAlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            check_interval_secs: 60,
+            reputation_drop_threshold: 10,
+            actions: vec![PenaltyAction::Log],
+            webhook_url: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PenaltyAction {
+    Log,
+    Webhook,
+    /// Pause on-chain reporting, as `maintenance` does, until the operator resumes it
+    PauseReporting,
 }
 
 impl Default for NodeConfig {
     fn default() -> Self {
         Self {
+            enable_ai: true,
+            enable_oracle: true,
+            enable_cross_chain: true,
+            enable_p2p: true,
+            enable_admin_api: true,
             node: NodeSettings {
-                stake_amount: 100_000_000_000_000_000_000, // 100 tokens in wei
+                stake_amount_gwei: 100_000_000_000, // 100 tokens
                 reputation_threshold: 70,
                 max_concurrent_tasks: 10,
                 heartbeat_interval_secs: 30,
                 challenge_timeout_secs: 3600,
+                adaptive_heartbeat: AdaptiveHeartbeatConfig::default(),
+                stake: StakeConfig::default(),
             },
             blockchain: BlockchainConfig {
                 rpc_url: "http://localhost:8545".to_string(),
+                fallback_rpc_urls: Vec::new(),
+                rpc_pool: RpcPoolConfig::default(),
                 chain_id: 1337,
                 contract_address: "0x0000000000000000000000000000000000000000".to_string(),
+                oracle_address: None,
+                token_address: None,
                 private_key: "".to_string(),
+                key: None,
+                signer: SignerConfig::Local,
                 gas_limit: 500_000,
                 gas_price_gwei: 20,
+                verify_contract_interface: true,
+                events: EventTransport::Poll,
+                events_endpoint: String::new(),
+                chains: Vec::new(),
+                fees: FeeConfig::default(),
+                transactions: TransactionConfig::default(),
+                challenge_refresh_secs: default_challenge_refresh_secs(),
             },
             ai: AIConfig {
                 model_path: "./models/threat_detection.onnx".to_string(),
+                model_checksum: None,
                 confidence_threshold: 0.7,
                 batch_size: 32,
                 max_sequence_length: 512,
                 update_interval_hours: 24,
+                rule_files: Vec::new(),
+                rule_reload_interval_secs: default_rule_reload_secs(),
+                model_reload_interval_secs: default_model_reload_secs(),
+                warmup_inferences: default_warmup_inferences(),
+                detection_cache_max_entries: default_detection_cache_max_entries(),
+                detection_cache_ttl_secs: default_detection_cache_ttl_secs(),
+                chain_models: Vec::new(),
+                drift: DriftConfig::default(),
+                ensemble: EnsembleConfig::default(),
+                calibration: CalibrationConfig::default(),
+                bytecode: BytecodeConfig::default(),
+                approvals: ApprovalConfig::default(),
+                domains: DomainReputationConfig::default(),
+                pipeline: DetectionPipelineConfig::default(),
+                registry: ModelRegistryConfig::default(),
             },
             network: NetworkConfig {
                 listen_port: 9000,
                 bootstrap_peers: vec![],
                 max_peers: 50,
                 discovery_interval_secs: 60,
+                blocked_peers: vec![],
+                reciprocity: ReciprocityConfig::default(),
+                cross_check: CrossCheckConfig::default(),
+                gossip: GossipConfig::default(),
             },
             storage: StorageConfig {
                 data_dir: "./data".to_string(),
@@ -114,16 +2020,123 @@ impl Default for NodeConfig {
                 target_efficiency_score: 80,
                 power_limit_watts: 100.0,
                 carbon_tracking_enabled: true,
+                history_interval_secs: default_energy_history_interval_secs(),
+                history_retention_hours: default_energy_history_retention_hours(),
             },
             metrics: MetricsConfig {
                 enabled: true,
                 port: 9090,
                 export_interval_secs: 60,
             },
+            workers: WorkerPoolConfig::default(),
+            work_tokens: WorkTokenConfig::default(),
+            fleet: FleetConfig::default(),
+            sandbox: SandboxConfig::default(),
+            memory: MemoryConfig::default(),
+            screening: ScreeningConfig::default(),
+            alert_cache: AlertCacheConfig::default(),
+            ipfs: IpfsConfig::default(),
+            gas_oracle: GasOracleConfig::default(),
+            rollback: RollbackConfig::default(),
+            updater: UpdaterConfig::default(),
+            stats_reporting: StatsReportingConfig::default(),
+            watchlists: WatchlistConfig::default(),
+            address_graph: AddressGraphConfig::default(),
+            pattern_feed: PatternFeedConfig::default(),
+            replication: ReplicationConfig::default(),
+            backtest: BacktestConfig::default(),
+            crash_reports: CrashReportConfig::default(),
+            query: QueryConfig::default(),
+            commitments: CommitmentConfig::default(),
+            mempool: MempoolConfig::default(),
+            light_client: LightClientConfig::default(),
+            retention: RetentionConfig::default(),
+            mirror: MirrorConfig::default(),
+            dag_checkpoints: DagCheckpointConfig::default(),
+            receipts: ReceiptConfig::default(),
+            executor: ExecutorConfig::default(),
+            loadgen: LoadGenConfig::default(),
+            chain_watch: ChainWatchConfig::default(),
+            archive: ArchiveConfig::default(),
+            dag_conflicts: DagConflictConfig::default(),
+            dag_inference: DagInferenceConfig::default(),
+            dag_orphans: DagOrphanConfig::default(),
+            dag_backpressure: DagBackpressureConfig::default(),
+            dag_epochs: DagEpochConfig::default(),
+            dag_retry: DagRetryConfig::default(),
+            ingest: IngestConfig::default(),
+            reporting_guard: ReportingGuardConfig::default(),
+            report_queue: ReportQueueConfig::default(),
+            dag_sync: DagSyncConfig::default(),
+            penalty_alerts: PenaltyAlertConfig::default(),
         }
     }
 }
 
+fn default_true() -> bool {
+    true
+}
+
+fn default_password_env() -> String {
+    crate::keys::DEFAULT_PASSWORD_ENV.to_string()
+}
+
+fn default_derivation_path() -> String {
+    crate::keys::DEFAULT_DERIVATION_PATH.to_string()
+}
+
+fn default_challenge_refresh_secs() -> u64 {
+    60
+}
+
+fn default_energy_history_interval_secs() -> u64 {
+    60
+}
+
+fn default_energy_history_retention_hours() -> u64 {
+    24 * 7
+}
+
+fn default_rule_reload_secs() -> u64 {
+    10
+}
+
+fn default_model_reload_secs() -> u64 {
+    30
+}
+
+fn default_warmup_inferences() -> usize {
+    16
+}
+
+fn default_detection_cache_max_entries() -> usize {
+    100_000
+}
+
+fn default_detection_cache_ttl_secs() -> u64 {
+    600
+}
+
+fn default_rejection_window_secs() -> u64 {
+    7 * 24 * 3600
+}
+
+fn default_hot_targets() -> usize {
+    256
+}
+
+fn default_hot_target_refresh_secs() -> u64 {
+    30
+}
+
+fn default_bad_cluster_depth() -> usize {
+    2
+}
+
+fn default_tenant_rate_limit() -> u32 {
+    600
+}
+
 impl NodeConfig {
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = std::fs::read_to_string(path)?;
@@ -0,0 +1,140 @@
+//! Startup and per-call guards for the configured DAGShield contract deployment
+
+use anyhow::{bail, Result};
+use ethers::{
+    abi::Abi,
+    providers::Middleware,
+    types::Address,
+    utils::to_checksum,
+};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+use crate::rpc_pool::PooledProvider;
+
+/// How long a successful network check is trusted before a write re-verifies it
+const NETWORK_RECHECK_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Parse a configured contract address, insisting on its EIP-55 checksum form
+pub fn parse_checksummed_address(configured: &str) -> Result<Address> {
+    let address: Address = match configured.parse() {
+        Ok(address) => address,
+        Err(e) => bail!("Configured contract address '{}' is not a valid address: {}", configured, e),
+    };
+    
+    let checksummed = to_checksum(&address, None);
+    if configured != checksummed {
+        bail!(
+            "Configured contract address '{}' is not EIP-55 checksummed (expected '{}'); \
+             a typo in a lowercase address would silently point at the wrong contract",
+            configured, checksummed
+        );
+    }
+    
+    Ok(address)
+}
+
+pub struct ContractGuard {
+    provider: Arc<PooledProvider>,
+    address: Address,
+    expected_chain_id: u64,
+    last_verified: Mutex<Option<Instant>>,
+    /// Why writes are held, while they are
+    held: parking_lot::RwLock<Option<String>>,
+}
+
+impl ContractGuard {
+    pub fn new(provider: Arc<PooledProvider>, address: Address, expected_chain_id: u64) -> Self {
+        Self {
+            provider,
+            address,
+            expected_chain_id,
+            last_verified: Mutex::new(None),
+            held: parking_lot::RwLock::new(None),
+        }
+    }
+    
+    /// Full startup verification: chain ID, deployed code and interface selectors
+    pub async fn verify_deployment(&self, abi: &Abi, check_interface: bool) -> Result<()> {
+        self.verify_chain_id().await?;
+        
+        let code = self.provider.get_code(self.address, None).await?;
+        if code.is_empty() {
+            bail!(
+                "No contract code at {:?} on chain {}; check contract_address and rpc_url point at the same network",
+                self.address, self.expected_chain_id
+            );
+        }
+        
+        if check_interface {
+            let missing: Vec<String> = abi
+                .functions()
+                .filter(|function| {
+                    let selector = function.short_signature();
+                    !code.windows(4).any(|window| window == selector)
+                })
+                .map(|function| function.signature())
+                .collect();
+            
+            if !missing.is_empty() {
+                bail!(
+                    "Contract at {:?} does not implement the DAGShield interface (missing: {}); \
+                     set verify_contract_interface = false if it is a proxy",
+                    self.address, missing.join(", ")
+                );
+            }
+        } else {
+            warn!("⚠️ Contract interface verification disabled for {:?}", self.address);
+        }
+        
+        *self.last_verified.lock().await = Some(Instant::now());
+        info!("✅ Contract {:?} verified on chain {}", self.address, self.expected_chain_id);
+        Ok(())
+    }
+    
+    /// Refuse writes until released, e.g. while the RPC serves another chain than configured
+    pub fn hold(&self, reason: String) {
+        *self.held.write() = Some(reason);
+    }
+    
+    pub fn release(&self) {
+        *self.held.write() = None;
+    }
+    
+    pub fn held(&self) -> Option<String> {
+        self.held.read().clone()
+    }
+    
+    /// Cheap guard run before every write; re-checks the RPC's chain ID at most every few minutes
+    pub async fn ensure_network(&self) -> Result<()> {
+        if let Some(reason) = self.held() {
+            bail!("Refusing to send transactions: {}", reason);
+        }
+        
+        let mut last_verified = self.last_verified.lock().await;
+        
+        if let Some(at) = *last_verified {
+            if at.elapsed() < NETWORK_RECHECK_INTERVAL {
+                return Ok(());
+            }
+        }
+        
+        debug!("🔎 Re-verifying RPC chain ID before submitting transaction");
+        self.verify_chain_id().await?;
+        *last_verified = Some(Instant::now());
+        Ok(())
+    }
+    
+    async fn verify_chain_id(&self) -> Result<()> {
+        let actual = self.provider.get_chainid().await?.as_u64();
+        if actual != self.expected_chain_id {
+            bail!(
+                "RPC endpoint reports chain ID {} but config expects {}; refusing to send transactions to the wrong network",
+                actual, self.expected_chain_id
+            );
+        }
+        Ok(())
+    }
+}
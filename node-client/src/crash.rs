@@ -0,0 +1,238 @@
+//! Panic isolation and crash reports for subsystem tasks
+//!
+//! A panic in a spawned task used to end it without a trace beyond a line on stderr. Subsystems
+//! now run under the [`Supervisor`]: each attempt is its own task, so a panic stays inside it,
+//! and the panic hook captures the message, location and backtrace of the task it fired in. The
+//! supervisor turns that into a [`CrashReport`], stores it in the `crashes` namespace, posts it to
+//! the configured webhook and restarts the task with backoff, until it has crashed
+//! `max_restarts` times in a row.
+//!
+//! Release builds unwind on panic for this; with `panic = "abort"` the first panic ends the node.
+
+use anyhow::Result;
+use prometheus::{IntCounterVec, Opts};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::future::Future;
+use std::sync::{Arc, Once, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{error, warn};
+
+use crate::config::CrashReportConfig;
+use crate::metrics::register_once;
+use crate::storage::NodeStorage;
+
+pub const CRASH_NAMESPACE: &str = "crashes";
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+tokio::task_local! {
+    /// Where the panic hook leaves what it captured for the supervisor of the current task
+    static PANIC_SLOT: Arc<parking_lot::Mutex<Option<PanicDetails>>>;
+}
+
+#[derive(Debug, Clone)]
+struct PanicDetails {
+    message: String,
+    location: Option<String>,
+    thread: Option<String>,
+    backtrace: String,
+}
+
+impl PanicDetails {
+    fn capture(payload: &(dyn Any + Send), location: Option<String>) -> Self {
+        Self {
+            message: panic_message(payload),
+            location,
+            thread: std::thread::current().name().map(str::to_string),
+            backtrace: Backtrace::force_capture().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub node_id: String,
+    pub version: String,
+    pub subsystem: String,
+    pub message: String,
+    /// `file:line:column` of the panic
+    pub location: Option<String>,
+    /// Worker thread the task was polled on
+    pub thread: Option<String>,
+    /// `None` when the panic hook was replaced by someone else
+    pub backtrace: Option<String>,
+    /// Unix time in milliseconds
+    pub crashed_at: u64,
+    /// Crashes in a row, this one included
+    pub consecutive: u32,
+    /// Whether the task is restarted or left stopped
+    pub restarting: bool,
+}
+
+pub struct Supervisor {
+    config: CrashReportConfig,
+    node_id: String,
+    storage: Arc<NodeStorage>,
+    http: reqwest::Client,
+    panics: IntCounterVec,
+    restarts: IntCounterVec,
+}
+
+impl Supervisor {
+    pub fn new(config: &CrashReportConfig, node_id: &str, storage: Arc<NodeStorage>) -> Result<Self> {
+        install_panic_hook();
+        
+        static PANICS: OnceLock<IntCounterVec> = OnceLock::new();
+        static RESTARTS: OnceLock<IntCounterVec> = OnceLock::new();
+        let panics = register_once(&PANICS, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_task_panics_total", "Panics caught in supervised subsystem tasks"),
+                &["subsystem"],
+            )
+        })?;
+        let restarts = register_once(&RESTARTS, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_task_restarts_total", "Subsystem tasks restarted after a panic"),
+                &["subsystem"],
+            )
+        })?;
+        
+        Ok(Self {
+            config: config.clone(),
+            node_id: node_id.to_string(),
+            storage,
+            http: reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build()?,
+            panics,
+            restarts,
+        })
+    }
+    
+    /// Run `task` as `subsystem`, starting it afresh after every panic; aborting the handle stops it
+    pub fn spawn<F, Fut>(self: &Arc<Self>, subsystem: &'static str, task: F) -> JoinHandle<()>
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let supervisor = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut consecutive = 0;
+            loop {
+                let slot = Arc::new(parking_lot::Mutex::new(None));
+                let started = Instant::now();
+                let mut attempt = AbortOnDrop(tokio::spawn(PANIC_SLOT.scope(Arc::clone(&slot), task())));
+                
+                let panic = match (&mut attempt.0).await {
+                    Ok(()) => return,
+                    Err(e) if e.is_cancelled() => return,
+                    Err(e) => e.into_panic(),
+                };
+                
+                if started.elapsed() >= Duration::from_secs(supervisor.config.healthy_after_secs) {
+                    consecutive = 0;
+                }
+                consecutive += 1;
+                let restarting = consecutive <= supervisor.config.max_restarts;
+                let details = slot.lock().take();
+                supervisor.record(subsystem, details, panic_message(panic.as_ref()), consecutive, restarting).await;
+                
+                if !restarting {
+                    error!("🚨 {} crashed {} times in a row, leaving it stopped", subsystem, consecutive);
+                    return;
+                }
+                let backoff = Duration::from_secs(supervisor.config.restart_backoff_secs)
+                    .saturating_mul(2u32.saturating_pow(consecutive - 1))
+                    .min(Duration::from_secs(supervisor.config.max_restart_backoff_secs));
+                warn!("🔁 Restarting {} in {:?}", subsystem, backoff);
+                tokio::time::sleep(backoff).await;
+                supervisor.restarts.with_label_values(&[subsystem]).inc();
+            }
+        })
+    }
+    
+    /// Stored crash reports, oldest first
+    pub fn reports(&self) -> Result<Vec<CrashReport>> {
+        Ok(self.storage.scan(CRASH_NAMESPACE)?.into_iter().map(|(_, report)| report).collect())
+    }
+    
+    async fn record(&self, subsystem: &str, details: Option<PanicDetails>, payload_message: String, consecutive: u32, restarting: bool) {
+        self.panics.with_label_values(&[subsystem]).inc();
+        let report = CrashReport {
+            node_id: self.node_id.clone(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            subsystem: subsystem.to_string(),
+            message: details.as_ref().map_or(payload_message, |details| details.message.clone()),
+            location: details.as_ref().and_then(|details| details.location.clone()),
+            thread: details.as_ref().and_then(|details| details.thread.clone()),
+            backtrace: details.map(|details| details.backtrace),
+            crashed_at: chrono::Utc::now().timestamp_millis() as u64,
+            consecutive,
+            restarting,
+        };
+        error!("💥 {} panicked at {}: {}", subsystem, report.location.as_deref().unwrap_or("unknown location"), report.message);
+        
+        if let Err(e) = self.store(&report) {
+            error!("Failed to store the crash report for {}: {}", subsystem, e);
+        }
+        
+        if let Some(url) = &self.config.webhook_url {
+            let result = self.http
+                .post(url)
+                .json(&report)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(e) = result {
+                error!("❌ Crash report upload failed: {}", e.without_url());
+            }
+        }
+    }
+    
+    fn store(&self, report: &CrashReport) -> Result<()> {
+        // Zero-padded so keys sort by time
+        let key = format!("{:020}-{}", report.crashed_at, report.subsystem);
+        self.storage.put(CRASH_NAMESPACE, &key, report)?;
+        
+        let stored = self.storage.scan::<CrashReport>(CRASH_NAMESPACE)?;
+        for (key, _) in stored.iter().take(stored.len().saturating_sub(self.config.max_stored)) {
+            self.storage.delete(CRASH_NAMESPACE, key)?;
+        }
+        Ok(())
+    }
+}
+
+/// Aborts the attempt when the supervising task is aborted
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Capture panics in supervised tasks for their crash reports; others go to the previous hook
+fn install_panic_hook() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let captured = PANIC_SLOT.try_with(|slot| {
+                let location = info.location().map(|location| location.to_string());
+                *slot.lock() = Some(PanicDetails::capture(info.payload(), location));
+            });
+            if captured.is_err() {
+                previous(info);
+            }
+        }));
+    });
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string())
+}
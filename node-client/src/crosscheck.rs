@@ -0,0 +1,200 @@
+//! Cross-checking this node's verdicts against its peers'
+//!
+//! Every round the node samples recent on-chain transactions it has judged and asks connected
+//! peers for their verdicts on them. A transaction diverges when this node's flag disagrees
+//! with the majority of the peers that judged it; a divergence rate well above the network's
+//! usual disagreement points at a drifted model or a misconfigured node.
+
+use anyhow::Result;
+use ethers::core::rand::seq::IteratorRandom;
+use libp2p::PeerId;
+use lru::LruCache;
+use prometheus::{Gauge, IntCounter};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::OnceLock;
+use tracing::{debug, error, info};
+
+use crate::ai::ThreatDetectionResult;
+use crate::config::CrossCheckConfig;
+use crate::dag::Transaction;
+use crate::metrics::register_once;
+use crate::threat::ThreatClass;
+
+/// This node's verdicts kept for sampling and for answering peers
+const RECENT_DIGESTS: usize = 20_000;
+
+/// What a node concluded about one transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerdictDigest {
+    pub tx_hash: String,
+    pub flagged: bool,
+    pub threat_type: ThreatClass,
+    /// Out of 100
+    pub confidence: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerdictQuery {
+    pub tx_hashes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerdictResponse {
+    /// Only the queried transactions the peer has judged
+    pub digests: Vec<VerdictDigest>,
+}
+
+/// A round in flight: the sampled verdicts and the peers' answers so far
+struct Round {
+    sample: HashMap<String, VerdictDigest>,
+    answers: HashMap<String, Vec<VerdictDigest>>,
+}
+
+pub struct CrossChecker {
+    config: CrossCheckConfig,
+    recent: parking_lot::Mutex<LruCache<String, VerdictDigest>>,
+    round: parking_lot::Mutex<Option<Round>>,
+    diverged: parking_lot::Mutex<bool>,
+    divergence_gauge: Gauge,
+    rounds: IntCounter,
+}
+
+impl CrossChecker {
+    pub fn new(config: &CrossCheckConfig) -> Result<Self> {
+        static DIVERGENCE_GAUGE: OnceLock<Gauge> = OnceLock::new();
+        static ROUNDS: OnceLock<IntCounter> = OnceLock::new();
+        let divergence_gauge = register_once(&DIVERGENCE_GAUGE, || {
+            Gauge::new(
+                "dagshield_verdict_divergence_ratio",
+                "Share of cross-checked transactions where this node disagreed with the peer majority",
+            )
+        })?;
+        let rounds = register_once(&ROUNDS, || {
+            IntCounter::new("dagshield_verdict_crosscheck_rounds_total", "Completed verdict cross-check rounds")
+        })?;
+        
+        Ok(Self {
+            config: config.clone(),
+            recent: parking_lot::Mutex::new(LruCache::new(
+                NonZeroUsize::new(RECENT_DIGESTS).unwrap_or(NonZeroUsize::MIN),
+            )),
+            round: parking_lot::Mutex::new(None),
+            diverged: parking_lot::Mutex::new(false),
+            divergence_gauge,
+            rounds,
+        })
+    }
+    
+    pub fn interval_secs(&self) -> u64 {
+        self.config.interval_secs
+    }
+    
+    pub fn peers_per_round(&self) -> usize {
+        self.config.peers_per_round
+    }
+    
+    /// Remember a verdict on an on-chain transaction; local submissions have nothing to compare against
+    pub fn record(&self, transaction: &Transaction, result: &ThreatDetectionResult, flagged: bool) {
+        if !is_tx_hash(&transaction.id) {
+            return;
+        }
+        let tx_hash = transaction.id.to_lowercase();
+        self.recent.lock().put(tx_hash.clone(), VerdictDigest {
+            tx_hash,
+            flagged,
+            threat_type: result.threat_type.clone(),
+            confidence: (result.confidence * 100.0).clamp(0.0, 100.0) as u32,
+        });
+    }
+    
+    pub fn answer(&self, query: &VerdictQuery) -> VerdictResponse {
+        let mut recent = self.recent.lock();
+        let digests = query.tx_hashes
+            .iter()
+            .take(self.config.sample_size)
+            .filter_map(|hash| recent.get(&hash.to_lowercase()).cloned())
+            .collect();
+        VerdictResponse { digests }
+    }
+    
+    /// Close the running round and sample the next one; `None` when there is nothing to ask about
+    pub fn next_round(&self) -> Option<VerdictQuery> {
+        if let Some(round) = self.round.lock().take() {
+            self.evaluate(round);
+        }
+        
+        let sample: HashMap<String, VerdictDigest> = self.recent
+            .lock()
+            .iter()
+            .choose_multiple(&mut ethers::core::rand::thread_rng(), self.config.sample_size)
+            .into_iter()
+            .map(|(hash, digest)| (hash.clone(), digest.clone()))
+            .collect();
+        if sample.is_empty() {
+            return None;
+        }
+        
+        let query = VerdictQuery { tx_hashes: sample.keys().cloned().collect() };
+        *self.round.lock() = Some(Round { sample, answers: HashMap::new() });
+        Some(query)
+    }
+    
+    /// Add a peer's answer to the running round; answers to an earlier round are ignored
+    pub fn collect(&self, peer: &PeerId, response: VerdictResponse) {
+        let mut round = self.round.lock();
+        let Some(round) = round.as_mut() else {
+            return;
+        };
+        for digest in response.digests {
+            let hash = digest.tx_hash.to_lowercase();
+            if round.sample.contains_key(&hash) {
+                round.answers.entry(hash).or_default().push(digest);
+            }
+        }
+        debug!("🔁 Verdict digests received from {}", peer);
+    }
+    
+    fn evaluate(&self, round: Round) {
+        let mut compared = 0;
+        let mut diverging = 0;
+        for (hash, ours) in &round.sample {
+            let Some(answers) = round.answers.get(hash) else {
+                continue;
+            };
+            if answers.len() < self.config.min_peer_answers {
+                continue;
+            }
+            compared += 1;
+            let flagged = answers.iter().filter(|digest| digest.flagged).count();
+            let majority_flagged = flagged * 2 > answers.len();
+            if ours.flagged != majority_flagged {
+                diverging += 1;
+            }
+        }
+        self.rounds.inc();
+        
+        if compared < self.config.min_compared {
+            debug!("🔁 Cross-check round compared only {} transactions, not judging divergence", compared);
+            return;
+        }
+        
+        let ratio = diverging as f64 / compared as f64;
+        self.divergence_gauge.set(ratio);
+        
+        let mut diverged = self.diverged.lock();
+        let now_diverged = ratio > self.config.divergence_threshold;
+        if now_diverged && !*diverged {
+            error!("🚨 Verdicts diverge from the peer majority on {}/{} cross-checked transactions ({:.0}%); check the model and configuration",
+                   diverging, compared, ratio * 100.0);
+        } else if !now_diverged && *diverged {
+            info!("🔁 Verdicts back in line with peers ({:.0}% divergence)", ratio * 100.0);
+        }
+        *diverged = now_diverged;
+    }
+}
+
+fn is_tx_hash(id: &str) -> bool {
+    id.len() == 66 && id.starts_with("0x") && id[2..].bytes().all(|b| b.is_ascii_hexdigit())
+}
@@ -0,0 +1,93 @@
+//! Persistent "last processed event" cursors shared by all chain listeners
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+use crate::storage::{NodeStorage, StorageBatch};
+
+const CURSOR_NAMESPACE: &str = "cursor";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct CursorPosition {
+    pub block_number: u64,
+    pub log_index: u64,
+}
+
+/// Tracks the last event a listener has fully processed on one chain.
+///
+/// The cursor is advanced in the same storage batch as the event's effects, so after a
+/// crash a listener resumes exactly after the last event whose effects were persisted:
+/// nothing is lost and nothing is applied twice.
+pub struct EventCursor {
+    storage: Arc<NodeStorage>,
+    key: String,
+    position: Option<CursorPosition>,
+    start_block: u64,
+}
+
+impl EventCursor {
+    /// Load the cursor for `listener` on `chain_id`, starting at `start_block` if none was stored yet
+    pub fn load(storage: Arc<NodeStorage>, listener: &str, chain_id: u64, start_block: u64) -> Result<Self> {
+        let key = format!("{}:{}", listener, chain_id);
+        let position: Option<CursorPosition> = storage.get(CURSOR_NAMESPACE, &key)?;
+        
+        match position {
+            Some(p) => info!("📍 Resuming {} listener on chain {} after block {} (log {})",
+                             listener, chain_id, p.block_number, p.log_index),
+            None => info!("📍 Starting {} listener on chain {} from block {}",
+                          listener, chain_id, start_block),
+        }
+        
+        Ok(Self {
+            storage,
+            key,
+            position,
+            start_block,
+        })
+    }
+    
+    pub fn position(&self) -> Option<CursorPosition> {
+        self.position
+    }
+    
+    /// First block that may still contain unprocessed events
+    pub fn next_block(&self) -> u64 {
+        self.position
+            .map(|p| p.block_number)
+            .unwrap_or(self.start_block)
+    }
+    
+    /// Whether the event at `(block_number, log_index)` was already committed
+    pub fn is_processed(&self, block_number: u64, log_index: u64) -> bool {
+        self.position
+            .map(|p| CursorPosition { block_number, log_index } <= p)
+            .unwrap_or(false)
+    }
+    
+    /// Persist the event's effects together with the advanced cursor in one atomic write
+    pub fn commit(&mut self, block_number: u64, log_index: u64, mut effects: StorageBatch) -> Result<()> {
+        let position = CursorPosition { block_number, log_index };
+        if self.position.map(|p| position <= p).unwrap_or(false) {
+            debug!("⏭️ Cursor {} already past block {} log {}", self.key, block_number, log_index);
+            return Ok(());
+        }
+        
+        effects.put(CURSOR_NAMESPACE, &self.key, &position)?;
+        self.storage.commit(effects)?;
+        self.position = Some(position);
+        
+        Ok(())
+    }
+    
+    /// Mark a block range as scanned when it contained no events
+    pub fn advance_to_block(&mut self, block_number: u64) -> Result<()> {
+        // A completed block has no log beyond u64::MAX, so everything in it counts as processed
+        self.commit(block_number, u64::MAX, self.storage.batch())
+    }
+    
+    pub fn batch(&self) -> StorageBatch {
+        self.storage.batch()
+    }
+}
@@ -0,0 +1,304 @@
+//! DAG synchronization between peers
+//!
+//! A node starting without checkpoints asks a peer for its latest `dag_sync.max_checkpoints`,
+//! checks that their digests chain and match the transactions they finalized, and adopts them
+//! as its own history, so transactions depending on what the network already finalized are
+//! ready on arrival rather than orphaned. A self-consistent history is not enough: each
+//! checkpoint that finalized anything must come with the epoch it sealed, whose root the peer
+//! committed on-chain under its stake (`dag_epochs.commit_on_chain`). Only the run of
+//! checkpoints so vouched for is adopted, and a root that differs from the committed one counts
+//! against the peer.
+//!
+//! Every round the node also reconciles tips with a random peer (anti-entropy): it asks for
+//! the peer's newest unfinalized transactions, fetches those it has neither in its DAG nor
+//! finalized, then any of their dependencies it still lacks, and adds them to its own DAG to be
+//! executed and judged like any other. Both sides run rounds, so a gap closes from either end.
+//! Only transactions that were asked for are accepted; a peer sending others, or checkpoints
+//! that do not verify, is counted against in the peer ledger. A peer chooses the ID it sends
+//! with a transaction, so synced transactions are marked `peer_synced` and never reported
+//! on-chain, whatever they are judged to be.
+
+use anyhow::{bail, Context, Result};
+use dashmap::DashMap;
+use ethers::utils::hex;
+use libp2p::PeerId;
+use prometheus::{IntCounterVec, Opts};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use crate::blockchain::BlockchainClient;
+use crate::config::DagSyncConfig;
+use crate::dag::{Backpressure, CheckpointHistory, DAGProcessor, Transaction};
+use crate::metrics::register_once;
+use crate::peers::PeerLedger;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DagSyncRequest {
+    /// The responder's newest unfinalized transactions
+    Tips { limit: usize },
+    /// The responder's latest stored checkpoints
+    Checkpoints { limit: usize },
+    Transactions(Vec<String>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DagSyncResponse {
+    Tips(Vec<String>),
+    Checkpoints(CheckpointHistory),
+    /// Only the requested transactions the responder still holds
+    Transactions(Vec<Transaction>),
+}
+
+/// Verified responses waiting to go into the DAG
+enum Intake {
+    Transaction(PeerId, Box<Transaction>),
+    Checkpoints(PeerId, CheckpointHistory),
+}
+
+pub struct DagSync {
+    config: DagSyncConfig,
+    dag_processor: Arc<DAGProcessor>,
+    ledger: Arc<PeerLedger>,
+    /// Where peers' epoch roots are checked
+    blockchain: Arc<BlockchainClient>,
+    /// Transactions asked for and not received yet, by the peer asked and when
+    requested: DashMap<String, (PeerId, Instant)>,
+    intake_tx: mpsc::UnboundedSender<Intake>,
+    intake_rx: tokio::sync::Mutex<Option<mpsc::UnboundedReceiver<Intake>>>,
+    synced: IntCounterVec,
+}
+
+impl DagSync {
+    pub fn new(
+        config: &DagSyncConfig,
+        dag_processor: Arc<DAGProcessor>,
+        ledger: Arc<PeerLedger>,
+        blockchain: Arc<BlockchainClient>,
+    ) -> Result<Self> {
+        if config.interval_secs == 0 {
+            bail!("dag_sync.interval_secs must be positive");
+        }
+        static SYNCED: OnceLock<IntCounterVec> = OnceLock::new();
+        let synced = register_once(&SYNCED, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_dag_synced_total", "Transactions and checkpoints received from peers by DAG sync, by outcome"),
+                &["item", "outcome"],
+            )
+        })?;
+        let (intake_tx, intake_rx) = mpsc::unbounded_channel();
+        
+        Ok(Self {
+            config: config.clone(),
+            dag_processor,
+            ledger,
+            blockchain,
+            requested: DashMap::new(),
+            intake_tx,
+            intake_rx: tokio::sync::Mutex::new(Some(intake_rx)),
+            synced,
+        })
+    }
+    
+    pub fn interval_secs(&self) -> u64 {
+        self.config.interval_secs
+    }
+    
+    /// What to ask a peer this round: its tips, and its checkpoints while this node has none
+    pub fn next_round(&self) -> Vec<DagSyncRequest> {
+        // Unanswered requests are asked again of whichever peer has the transactions next
+        let expiry = Duration::from_secs(self.config.interval_secs * 2);
+        self.requested.retain(|_, (_, asked_at)| asked_at.elapsed() < expiry);
+        
+        let mut requests = vec![DagSyncRequest::Tips { limit: self.config.max_tips }];
+        if self.config.max_checkpoints > 0 && self.dag_processor.latest_checkpoint().is_none() {
+            requests.push(DagSyncRequest::Checkpoints { limit: self.config.max_checkpoints });
+        }
+        requests
+    }
+    
+    pub fn answer(&self, request: &DagSyncRequest) -> Result<DagSyncResponse> {
+        Ok(match request {
+            DagSyncRequest::Tips { limit } => DagSyncResponse::Tips(self.dag_processor.tips((*limit).min(self.config.max_tips))),
+            DagSyncRequest::Checkpoints { limit } => {
+                let mut history = self.dag_processor.recent_checkpoints((*limit).min(self.config.max_checkpoints))?;
+                history.sealed_by = Some(self.blockchain.wallet_address());
+                DagSyncResponse::Checkpoints(history)
+            }
+            DagSyncRequest::Transactions(ids) => {
+                let ids = &ids[..ids.len().min(self.config.max_transactions_per_request)];
+                DagSyncResponse::Transactions(self.dag_processor.export_transactions(ids))
+            }
+        })
+    }
+    
+    /// Take in a peer's response, returning what to ask the same peer next
+    pub fn collect(&self, peer: &PeerId, response: DagSyncResponse) -> Vec<DagSyncRequest> {
+        match response {
+            DagSyncResponse::Tips(tips) => {
+                let tips = &tips[..tips.len().min(self.config.max_tips)];
+                self.request_missing(peer, tips.iter().cloned())
+            }
+            DagSyncResponse::Checkpoints(history) => {
+                if !history.segments.is_empty() {
+                    let _ = self.intake_tx.send(Intake::Checkpoints(*peer, history));
+                }
+                Vec::new()
+            }
+            DagSyncResponse::Transactions(transactions) => {
+                let mut received = HashSet::new();
+                let mut dependencies = Vec::new();
+                for transaction in transactions {
+                    let asked = self.requested
+                        .remove_if(&transaction.id, |_, (asked, _)| asked == peer)
+                        .is_some();
+                    if !asked {
+                        self.synced.with_label_values(&["transaction", "unrequested"]).inc();
+                        self.ledger.record_invalid(&peer.to_string());
+                        continue;
+                    }
+                    received.insert(transaction.id.clone());
+                    dependencies.extend(transaction.dependencies.iter().cloned());
+                    let _ = self.intake_tx.send(Intake::Transaction(*peer, Box::new(transaction)));
+                }
+                // Waiting in the orphan pool until these arrive
+                self.request_missing(peer, dependencies.into_iter().filter(|tx_id| !received.contains(tx_id)))
+            }
+        }
+    }
+    
+    /// Ask `peer` for those of `ids` this node has not seen or asked for
+    fn request_missing(&self, peer: &PeerId, ids: impl Iterator<Item = String>) -> Vec<DagSyncRequest> {
+        let mut missing = Vec::new();
+        for tx_id in ids {
+            if self.requested.contains_key(&tx_id) || self.dag_processor.contains(&tx_id) {
+                continue;
+            }
+            match self.dag_processor.is_finalized(&tx_id) {
+                Ok(false) => {}
+                Ok(true) => continue,
+                Err(e) => {
+                    debug!("Not syncing {}: {:#}", tx_id, e);
+                    continue;
+                }
+            }
+            self.requested.insert(tx_id.clone(), (*peer, Instant::now()));
+            missing.push(tx_id);
+        }
+        if !missing.is_empty() {
+            debug!("🔃 Fetching {} DAG transactions from {}", missing.len(), peer);
+        }
+        missing
+            .chunks(self.config.max_transactions_per_request.max(1))
+            .map(|chunk| DagSyncRequest::Transactions(chunk.to_vec()))
+            .collect()
+    }
+    
+    /// Add what peers sent to the DAG until the task is aborted
+    pub async fn start(&self) -> Result<()> {
+        let mut intake = self.intake_rx
+            .lock()
+            .await
+            .take()
+            .context("DAG sync already started")?;
+        
+        while let Some(item) = intake.recv().await {
+            match item {
+                Intake::Transaction(peer, transaction) => self.add(peer, *transaction).await?,
+                Intake::Checkpoints(peer, history) => self.import(peer, history).await,
+            }
+        }
+        Ok(())
+    }
+    
+    async fn add(&self, peer: PeerId, mut transaction: Transaction) -> Result<()> {
+        transaction.peer_synced = true;
+        let tx_id = transaction.id.clone();
+        // Arrived meanwhile, e.g. from the mempool or another peer
+        if self.dag_processor.contains(&tx_id) || self.dag_processor.is_finalized(&tx_id)? {
+            self.synced.with_label_values(&["transaction", "duplicate"]).inc();
+            return Ok(());
+        }
+        let outcome = loop {
+            match self.dag_processor.add_transaction(transaction.clone()).await {
+                Ok(()) => break "added",
+                Err(e) if e.is::<Backpressure>() => self.dag_processor.wait_for_capacity().await,
+                // Not necessarily the peer's fault, e.g. while ingestion is paused
+                Err(e) => {
+                    debug!("DAG transaction {} from {} not added: {:#}", tx_id, peer, e);
+                    break "rejected";
+                }
+            }
+        };
+        self.synced.with_label_values(&["transaction", outcome]).inc();
+        Ok(())
+    }
+    
+    async fn import(&self, peer: PeerId, history: CheckpointHistory) {
+        // Another peer's checkpoints were adopted meanwhile
+        if self.dag_processor.latest_checkpoint().is_some() {
+            return;
+        }
+        let history = match history.verify() {
+            Ok(()) => self.anchored(history).await,
+            Err(e) => Err(e),
+        };
+        let history = match history {
+            Ok(Some(history)) => history,
+            Ok(None) => {
+                debug!("DAG checkpoints from {} not adopted: none has an epoch root committed on-chain", peer);
+                self.synced.with_label_values(&["checkpoint", "unanchored"]).inc();
+                return;
+            }
+            Err(e) => {
+                warn!("⚠️ Rejected DAG checkpoints from {}: {:#}", peer, e);
+                self.synced.with_label_values(&["checkpoint", "invalid"]).inc();
+                self.ledger.record_invalid(&peer.to_string());
+                return;
+            }
+        };
+        match self.dag_processor.import_checkpoints(&history).await {
+            Ok(imported) => self.synced.with_label_values(&["checkpoint", "imported"]).inc_by(imported as u64),
+            Err(e) => warn!("⚠️ DAG checkpoints from {} not imported: {:#}", peer, e),
+        }
+    }
+    
+    /// The first run of verified `history` whose epoch roots its sealer committed on-chain;
+    /// `None` when there is none. A checkpoint finalizing nothing needs no root
+    async fn anchored(&self, mut history: CheckpointHistory) -> Result<Option<CheckpointHistory>> {
+        let Some(sealer) = history.sealed_by else {
+            return Ok(None);
+        };
+        let (mut start, mut end) = (None, 0);
+        for (index, segment) in history.segments.iter().enumerate() {
+            let anchored = match &segment.epoch {
+                _ if segment.members.is_empty() => start.is_some(),
+                Some(epoch) => match self.blockchain.dag_epoch_root(sealer, epoch.epoch).await? {
+                    Some(root) if format!("0x{}", hex::encode(root)) == epoch.root => true,
+                    Some(_) => bail!("Epoch {} root differs from the one {:?} committed", epoch.epoch, sealer),
+                    None => false,
+                },
+                None => false,
+            };
+            if anchored {
+                start.get_or_insert(index);
+                end = index + 1;
+            } else if start.is_some() {
+                break;
+            }
+        }
+        
+        let Some(start) = start else {
+            return Ok(None);
+        };
+        if start > 0 {
+            history.previous_digest = Some(history.segments[start - 1].checkpoint.digest.clone());
+        }
+        history.segments.truncate(end);
+        history.segments.drain(..start);
+        Ok(Some(history))
+    }
+}
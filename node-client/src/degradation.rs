@@ -0,0 +1,210 @@
+//! Graceful degradation when optional subsystems fail
+//!
+//! A failing subsystem narrows what the node does instead of stopping it:
+//!
+//! | Subsystem | Fallback | Level |
+//! |-----------|----------|-------|
+//! | AI model | detect with rules only | degraded |
+//! | Chain RPC | queue reports locally, submit them once the chain answers | degraded |
+//! | P2P | chain-only mode: no intel is shared, alerts still come from the chain | degraded |
+//! | Storage | read-only on disk, new writes held in RAM and lost on restart | critical |
+//!
+//! The current level is reported in `/health` and as `dagshield_degradation_level`.
+
+use anyhow::Result;
+use prometheus::{IntGauge, IntGaugeVec, Opts};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::metrics::register_once;
+use crate::storage::NodeStorage;
+
+/// How often storage is checked for a switch to RAM mode
+const STORAGE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// How often a node running from RAM repeats its alert
+const STORAGE_ALERT_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    Ai,
+    ChainRpc,
+    P2p,
+    Storage,
+}
+
+impl Subsystem {
+    const ALL: [Subsystem; 4] = [Self::Ai, Self::ChainRpc, Self::P2p, Self::Storage];
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ai => "ai",
+            Self::ChainRpc => "chain_rpc",
+            Self::P2p => "p2p",
+            Self::Storage => "storage",
+        }
+    }
+    
+    /// What the node does instead while the subsystem is down
+    pub fn fallback(&self) -> &'static str {
+        match self {
+            Self::Ai => "detecting with rules only",
+            Self::ChainRpc => "queuing threat reports locally",
+            Self::P2p => "running chain-only, intel is not shared with peers",
+            Self::Storage => "running from RAM, writes since the failure are lost on restart",
+        }
+    }
+    
+    fn level(&self) -> DegradationLevel {
+        match self {
+            Self::Storage => DegradationLevel::Critical,
+            _ => DegradationLevel::Degraded,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DegradationLevel {
+    Normal,
+    /// Running on a fallback; detection and alerting continue
+    Degraded,
+    /// State is no longer durable; an operator has to step in
+    Critical,
+}
+
+impl DegradationLevel {
+    fn gauge_value(&self) -> i64 {
+        match self {
+            Self::Normal => 0,
+            Self::Degraded => 1,
+            Self::Critical => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubsystemFailure {
+    pub reason: String,
+    pub fallback: String,
+    /// Unix time the subsystem went down
+    pub since: u64,
+}
+
+/// The degradation state, as surfaced in `/health` and `status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DegradationReport {
+    pub level: DegradationLevel,
+    pub subsystems: BTreeMap<Subsystem, SubsystemFailure>,
+}
+
+pub struct Degradation {
+    failures: parking_lot::RwLock<BTreeMap<Subsystem, SubsystemFailure>>,
+    level_gauge: IntGauge,
+    subsystem_gauge: IntGaugeVec,
+}
+
+impl Degradation {
+    pub fn new() -> Result<Self> {
+        static LEVEL_GAUGE: OnceLock<IntGauge> = OnceLock::new();
+        static SUBSYSTEM_GAUGE: OnceLock<IntGaugeVec> = OnceLock::new();
+        let level_gauge = register_once(&LEVEL_GAUGE, || {
+            IntGauge::new(
+                "dagshield_degradation_level",
+                "Degradation level: 0 normal, 1 degraded (running on a fallback), 2 critical (storage lost)",
+            )
+        })?;
+        let subsystem_gauge = register_once(&SUBSYSTEM_GAUGE, || {
+            IntGaugeVec::new(
+                Opts::new("dagshield_subsystem_degraded", "Whether a subsystem has failed and its fallback is in use"),
+                &["subsystem"],
+            )
+        })?;
+        for subsystem in Subsystem::ALL {
+            subsystem_gauge.with_label_values(&[subsystem.as_str()]).set(0);
+        }
+        
+        Ok(Self {
+            failures: parking_lot::RwLock::new(BTreeMap::new()),
+            level_gauge,
+            subsystem_gauge,
+        })
+    }
+    
+    /// Switch a subsystem to its fallback; repeated failures only update the reason
+    pub fn degrade(&self, subsystem: Subsystem, reason: impl Display) {
+        let reason = reason.to_string();
+        let mut failures = self.failures.write();
+        if let Some(failure) = failures.get_mut(&subsystem) {
+            failure.reason = reason;
+            return;
+        }
+        
+        match subsystem.level() {
+            DegradationLevel::Critical => error!("🚨 {} failed, {}: {}", subsystem.as_str(), subsystem.fallback(), reason),
+            _ => warn!("⚠️ {} failed, {}: {}", subsystem.as_str(), subsystem.fallback(), reason),
+        }
+        failures.insert(subsystem, SubsystemFailure {
+            reason,
+            fallback: subsystem.fallback().to_string(),
+            since: chrono::Utc::now().timestamp() as u64,
+        });
+        self.subsystem_gauge.with_label_values(&[subsystem.as_str()]).set(1);
+        self.level_gauge.set(level_of(&failures).gauge_value());
+    }
+    
+    pub fn recover(&self, subsystem: Subsystem) {
+        let mut failures = self.failures.write();
+        if failures.remove(&subsystem).is_none() {
+            return;
+        }
+        info!("✅ {} recovered, leaving its fallback", subsystem.as_str());
+        self.subsystem_gauge.with_label_values(&[subsystem.as_str()]).set(0);
+        self.level_gauge.set(level_of(&failures).gauge_value());
+    }
+    
+    pub fn is_degraded(&self, subsystem: Subsystem) -> bool {
+        self.failures.read().contains_key(&subsystem)
+    }
+    
+    pub fn level(&self) -> DegradationLevel {
+        level_of(&self.failures.read())
+    }
+    
+    pub fn report(&self) -> DegradationReport {
+        let failures = self.failures.read();
+        DegradationReport {
+            level: level_of(&failures),
+            subsystems: failures.clone(),
+        }
+    }
+    
+    /// Notice storage switching to RAM mode and keep alerting for as long as it stays there
+    pub async fn watch_storage(self: Arc<Self>, storage: Arc<NodeStorage>) {
+        let mut interval = tokio::time::interval(STORAGE_POLL_INTERVAL);
+        let mut last_alert: Option<tokio::time::Instant> = None;
+        
+        loop {
+            interval.tick().await;
+            let Some(reason) = storage.failure() else {
+                continue;
+            };
+            if !self.is_degraded(Subsystem::Storage) {
+                self.degrade(Subsystem::Storage, &reason);
+                last_alert = Some(tokio::time::Instant::now());
+            } else if last_alert.is_none_or(|at| at.elapsed() >= STORAGE_ALERT_INTERVAL) {
+                error!("🚨 Storage is still failing, the node is running from RAM and will lose {} pending writes on restart: {}",
+                       storage.pending_writes(), reason);
+                last_alert = Some(tokio::time::Instant::now());
+            }
+        }
+    }
+}
+
+fn level_of(failures: &BTreeMap<Subsystem, SubsystemFailure>) -> DegradationLevel {
+    failures.keys().map(Subsystem::level).max().unwrap_or(DegradationLevel::Normal)
+}
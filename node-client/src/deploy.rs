@@ -0,0 +1,192 @@
+//! `deploy-contracts`: stand up the DAGShield contracts on a private or enterprise chain
+//!
+//! Deploys the token, core and oracle contracts from the Hardhat artifacts embedded at build
+//! time, funds the reward pool, authorizes the node wallet on the oracle, writes the addresses
+//! into the config file and smoke-tests the result with a regular blockchain client.
+
+use anyhow::{anyhow, bail, Context, Result};
+use ethers::abi::Abi;
+use ethers::contract::{Contract, ContractFactory};
+use ethers::middleware::SignerMiddleware;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::Signer;
+use ethers::types::{Address, Bytes, U256};
+use ethers::utils::{parse_ether, to_checksum};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::blockchain::BlockchainClient;
+use crate::config::NodeConfig;
+use crate::signer::NodeSigner;
+
+const TOKEN_ARTIFACT: &str = include_str!(concat!(env!("OUT_DIR"), "/contracts/DAGToken.json"));
+const SHIELD_ARTIFACT: &str = include_str!(concat!(env!("OUT_DIR"), "/contracts/DAGShield.json"));
+const ORACLE_ARTIFACT: &str = include_str!(concat!(env!("OUT_DIR"), "/contracts/DAGOracle.json"));
+
+/// Public networks the helper refuses to deploy to without `--allow-public-chain`
+const PUBLIC_CHAIN_IDS: &[u64] = &[1, 10, 56, 137, 8453, 42161, 43114, 11155111, 17000];
+
+type DeployClient = SignerMiddleware<Provider<Http>, NodeSigner>;
+
+/// The subset of a Hardhat artifact needed to deploy it
+#[derive(Debug, Deserialize)]
+struct Artifact {
+    #[serde(rename = "contractName")]
+    contract_name: String,
+    abi: Abi,
+    bytecode: Bytes,
+}
+
+impl Artifact {
+    fn load(name: &str, embedded: &str) -> Result<Self> {
+        let artifact: Artifact = serde_json::from_str(embedded).map_err(|_| {
+            anyhow!("{} was not embedded in this build; run `npx hardhat compile` in the repository root and rebuild", name)
+        })?;
+        if artifact.bytecode.is_empty() {
+            bail!("Embedded {} artifact has no bytecode", artifact.contract_name);
+        }
+        Ok(artifact)
+    }
+}
+
+#[derive(Debug)]
+pub struct Deployment {
+    pub token: Address,
+    pub shield: Address,
+    pub oracle: Address,
+}
+
+pub struct DeployOptions {
+    /// DAG tokens moved into the core contract's reward pool
+    pub reward_pool_tokens: u64,
+    pub allow_public_chain: bool,
+}
+
+pub async fn deploy_contracts(config: &NodeConfig, config_path: &str, options: &DeployOptions) -> Result<Deployment> {
+    let chain_id = config.blockchain.chain_id;
+    if PUBLIC_CHAIN_IDS.contains(&chain_id) && !options.allow_public_chain {
+        bail!("Chain {} is a public network; deploy there with the Hardhat scripts, or pass --allow-public-chain", chain_id);
+    }
+    
+    let token_artifact = Artifact::load("DAGToken", TOKEN_ARTIFACT)?;
+    let shield_artifact = Artifact::load("DAGShield", SHIELD_ARTIFACT)?;
+    let oracle_artifact = Artifact::load("DAGOracle", ORACLE_ARTIFACT)?;
+    
+    let provider = Provider::<Http>::try_from(&config.blockchain.rpc_url)?;
+    let reported_chain_id = provider.get_chainid().await?.as_u64();
+    if reported_chain_id != chain_id {
+        bail!("RPC endpoint {} serves chain {}, but the config says {}", config.blockchain.rpc_url, reported_chain_id, chain_id);
+    }
+    let wallet = NodeSigner::connect(&config.blockchain).await.context("The node key is the deployer key")?;
+    let wallet = wallet.with_chain_id(chain_id);
+    let deployer = wallet.address();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+    
+    info!("🏗️ Deploying DAGShield contracts to chain {} from {:?}", chain_id, deployer);
+    
+    let token = deploy(&client, &token_artifact, ()).await?;
+    let shield = deploy(&client, &shield_artifact, token.address()).await?;
+    let oracle = deploy(&client, &oracle_artifact, ()).await?;
+    
+    if options.reward_pool_tokens > 0 {
+        let amount = parse_ether(options.reward_pool_tokens)?;
+        token.method::<_, bool>("transfer", (shield.address(), amount))?
+            .send()
+            .await?
+            .await?;
+        info!("💰 Moved {} DAG into the reward pool", options.reward_pool_tokens);
+    }
+    
+    // The node reports with the same key it deployed with
+    oracle.method::<_, ()>("authorizeNode", deployer)?
+        .send()
+        .await?
+        .await?;
+    info!("🔑 Authorized {:?} as an oracle node", deployer);
+    
+    let deployment = Deployment {
+        token: token.address(),
+        shield: shield.address(),
+        oracle: oracle.address(),
+    };
+    write_addresses(config_path, &deployment)?;
+    info!("📝 Contract addresses written to {}", config_path);
+    
+    smoke_test(config_path, &oracle).await?;
+    Ok(deployment)
+}
+
+async fn deploy<T: ethers::abi::Tokenize>(client: &Arc<DeployClient>, artifact: &Artifact, args: T) -> Result<Contract<DeployClient>> {
+    let factory = ContractFactory::new(artifact.abi.clone(), artifact.bytecode.clone(), Arc::clone(client));
+    let contract = factory
+        .deploy(args)?
+        .send()
+        .await
+        .with_context(|| format!("Deploying {} failed", artifact.contract_name))?;
+    info!("✅ {} deployed to {}", artifact.contract_name, to_checksum(&contract.address(), None));
+    Ok(contract)
+}
+
+/// Set the contract addresses in the `[blockchain]` table, keeping the rest of the file as written
+fn write_addresses(config_path: &str, deployment: &Deployment) -> Result<()> {
+    let mut content = std::fs::read_to_string(config_path)?;
+    content = set_blockchain_key(&content, "contract_address", &to_checksum(&deployment.shield, None));
+    content = set_blockchain_key(&content, "oracle_address", &to_checksum(&deployment.oracle, None));
+    content = set_blockchain_key(&content, "token_address", &to_checksum(&deployment.token, None));
+    
+    // Only replace the file with something the node will load
+    toml::from_str::<NodeConfig>(&content).context("Updated config no longer parses")?;
+    let tmp_path = format!("{}.deploy-tmp", config_path);
+    std::fs::write(&tmp_path, &content)?;
+    std::fs::rename(&tmp_path, config_path)?;
+    Ok(())
+}
+
+fn set_blockchain_key(content: &str, key: &str, value: &str) -> String {
+    let line = format!("{} = \"{}\"", key, value);
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    
+    let Some(start) = lines.iter().position(|l| l.trim() == "[blockchain]") else {
+        lines.push(String::new());
+        lines.push("[blockchain]".to_string());
+        lines.push(line);
+        return lines.join("\n") + "\n";
+    };
+    let end = lines[start + 1..]
+        .iter()
+        .position(|l| l.trim_start().starts_with('['))
+        .map_or(lines.len(), |offset| start + 1 + offset);
+    
+    let existing = lines[start + 1..end].iter().position(|l| {
+        l.trim_start().strip_prefix(key).is_some_and(|rest| rest.trim_start().starts_with('='))
+    });
+    match existing {
+        Some(offset) => lines[start + 1 + offset] = line,
+        None => {
+            // After the table's last setting, ahead of any blank lines separating the next one
+            let last_setting = lines[start..end].iter().rposition(|l| !l.trim().is_empty()).unwrap_or(0);
+            lines.insert(start + last_setting + 1, line);
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Start a blockchain client against the written config, the way the node will at startup
+async fn smoke_test(config_path: &str, oracle: &Contract<DeployClient>) -> Result<()> {
+    let config = NodeConfig::load(config_path)?;
+    let client = BlockchainClient::new(&config.blockchain).await?;
+    
+    let (total_nodes, _, total_threats, _) = client.get_network_stats().await?;
+    if total_nodes != 0 || total_threats != 0 {
+        bail!("Fresh DAGShield deployment reports {} nodes and {} threats", total_nodes, total_threats);
+    }
+    
+    let chains: Vec<U256> = oracle.method("getSupportedChains", ())?.call().await?;
+    if chains.is_empty() {
+        bail!("DAGOracle deployed without any supported chains");
+    }
+    
+    info!("🧪 Smoke test passed: core contract answering, oracle serving {} chains", chains.len());
+    Ok(())
+}
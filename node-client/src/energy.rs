@@ -1,15 +1,25 @@
 //! Energy monitoring and optimization for sustainable DePIN operations
 
 use anyhow::Result;
-use battery::Manager;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use sysinfo::{CpuExt, System, SystemExt};
+use std::sync::{Arc, OnceLock};
+use sysinfo::System;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
+use crate::challenge::EfficiencyChallenge;
 use crate::config::EnergyConfig;
+use crate::governor::{PowerConditions, ResourceGovernor};
 use crate::node::EnergyStats;
+use crate::storage::NodeStorage;
+
+pub const ENERGY_HISTORY_NAMESPACE: &str = "energy_history";
+
+/// Seconds between sweeps of readings past retention
+const HISTORY_PRUNE_INTERVAL_SECS: u64 = 3600;
+
+/// Where Linux lists batteries and their charge
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnergyMetrics {
@@ -34,38 +44,31 @@ pub struct PowerProfile {
 pub struct EnergyMonitor {
     config: EnergyConfig,
     system: Arc<RwLock<System>>,
-    battery_manager: Arc<RwLock<Option<Manager>>>,
     current_metrics: Arc<RwLock<EnergyMetrics>>,
     power_profiles: Arc<RwLock<Vec<PowerProfile>>>,
     baseline_power: Arc<RwLock<f32>>,
+    governor: Arc<ResourceGovernor>,
+    history: OnceLock<Arc<NodeStorage>>,
+    /// Unix times of the last stored reading and the last retention sweep
+    history_marks: parking_lot::Mutex<(u64, u64)>,
 }
 
 impl EnergyMonitor {
-    pub async fn new(config: &EnergyConfig) -> Result<Self> {
+    pub async fn new(config: &EnergyConfig, governor: Arc<ResourceGovernor>) -> Result<Self> {
         info!("⚡ Initializing energy monitoring system...");
         
         let mut system = System::new_all();
         system.refresh_all();
         
-        let battery_manager = if cfg!(target_os = "linux") || cfg!(target_os = "windows") || cfg!(target_os = "macos") {
-            match Manager::new() {
-                Ok(manager) => Some(manager),
-                Err(e) => {
-                    warn!("Battery manager not available: {}", e);
-                    None
-                }
-            }
-        } else {
-            None
-        };
-        
         let monitor = Self {
             config: config.clone(),
             system: Arc::new(RwLock::new(system)),
-            battery_manager: Arc::new(RwLock::new(battery_manager)),
             current_metrics: Arc::new(RwLock::new(EnergyMetrics::default())),
             power_profiles: Arc::new(RwLock::new(Vec::new())),
             baseline_power: Arc::new(RwLock::new(0.0)),
+            governor,
+            history: OnceLock::new(),
+            history_marks: parking_lot::Mutex::new((0, 0)),
         };
         
         // Initialize power profiles
@@ -96,6 +99,47 @@ impl EnergyMonitor {
         }
     }
     
+    /// Keep a reading every `history_interval_secs` in storage, for `dagshield-node query`
+    pub fn attach_history(&self, storage: Arc<NodeStorage>) {
+        if self.history.set(storage).is_err() {
+            warn!("⚠️ Energy history already attached");
+        }
+    }
+    
+    fn record_history(&self, metrics: &EnergyMetrics) -> Result<()> {
+        let Some(storage) = self.history.get() else {
+            return Ok(());
+        };
+        if self.config.history_interval_secs == 0 {
+            return Ok(());
+        }
+        
+        let mut marks = self.history_marks.lock();
+        let (last_recorded, last_pruned) = &mut *marks;
+        if metrics.timestamp < *last_recorded + self.config.history_interval_secs {
+            return Ok(());
+        }
+        // Zero-padded so keys sort by time
+        storage.put(ENERGY_HISTORY_NAMESPACE, &format!("{:020}", metrics.timestamp), metrics)?;
+        *last_recorded = metrics.timestamp;
+        
+        if metrics.timestamp >= *last_pruned + HISTORY_PRUNE_INTERVAL_SECS {
+            *last_pruned = metrics.timestamp;
+            let cutoff = metrics.timestamp.saturating_sub(self.config.history_retention_hours * 3600);
+            let mut batch = storage.batch();
+            for (key, reading) in storage.scan::<EnergyMetrics>(ENERGY_HISTORY_NAMESPACE)? {
+                if reading.timestamp >= cutoff {
+                    break;
+                }
+                batch.delete(ENERGY_HISTORY_NAMESPACE, &key);
+            }
+            if !batch.is_empty() {
+                storage.commit(batch)?;
+            }
+        }
+        Ok(())
+    }
+    
     async fn initialize_power_profiles(&self) -> Result<()> {
         let mut profiles = self.power_profiles.write().await;
         
@@ -199,6 +243,18 @@ impl EnergyMonitor {
             timestamp: chrono::Utc::now().timestamp() as u64,
         };
         
+        // Let the governor gate heavy work on the latest readings
+        self.governor.update_conditions(PowerConditions {
+            power_watts: power_consumption,
+            power_limit_watts: self.config.power_limit_watts,
+            temperature_celsius: temperature,
+            battery_level_percent: battery_level,
+        });
+        
+        if let Err(e) = self.record_history(&metrics) {
+            warn!("⚠️ Failed to store energy reading: {:#}", e);
+        }
+        
         let mut current_metrics = self.current_metrics.write().await;
         *current_metrics = metrics.clone();
         
@@ -227,21 +283,19 @@ impl EnergyMonitor {
         Ok(total_power)
     }
     
+    /// Charge of the first battery the kernel lists, in percent; `None` without one or off Linux
     async fn get_battery_level(&self) -> Result<Option<f32>> {
-        let battery_manager = self.battery_manager.read().await;
-        
-        if let Some(ref manager) = *battery_manager {
-            if let Ok(batteries) = manager.batteries() {
-                for battery in batteries {
-                    if let Ok(battery) = battery {
-                        let state_of_charge = battery.state_of_charge().get::<battery::units::ratio::percent>();
-                        return Ok(Some(state_of_charge));
-                    }
-                }
+        let Ok(entries) = std::fs::read_dir(POWER_SUPPLY_DIR) else {
+            return Ok(None);
+        };
+        Ok(entries.flatten().find_map(|entry| {
+            let path = entry.path();
+            let kind = std::fs::read_to_string(path.join("type")).ok()?;
+            if kind.trim() != "Battery" {
+                return None;
             }
-        }
-        
-        Ok(None)
+            std::fs::read_to_string(path.join("capacity")).ok()?.trim().parse().ok()
+        }))
     }
     
     async fn calculate_efficiency_score(
@@ -273,7 +327,7 @@ impl EnergyMonitor {
             }
         }
         
-        score.max(0.0).min(100.0) as u32
+        score.clamp(0.0, 100.0) as u32
     }
     
     async fn calculate_carbon_footprint(&self, power_consumption_watts: f32) -> f64 {
@@ -333,10 +387,11 @@ impl EnergyMonitor {
         info!("⚙️ Applying power profile: {} (target efficiency: {}%)", 
               profile.profile_name, profile.target_efficiency);
         
-        // In a real implementation, this would:
+        // Scale inference and DAG parallelism to the profile's CPU share
+        self.governor.apply_power_profile(profile);
+        
+        // In a real implementation, this would also:
         // - Adjust CPU frequency scaling
-        // - Modify thread pool sizes
-        // - Change processing batch sizes
         // - Adjust network polling intervals
         
         Ok(())
@@ -356,7 +411,7 @@ impl EnergyMonitor {
     
     async fn update_carbon_footprint(&self) -> Result<()> {
         if !self.config.carbon_tracking_enabled {
-            return Ok();
+            return Ok(());
         }
         
         let metrics = self.current_metrics.read().await;
@@ -384,15 +439,10 @@ impl EnergyMonitor {
         Ok(metrics.power_consumption_watts)
     }
     
-    pub async fn solve_efficiency_challenge(&self, challenge_data: &str) -> Result<Option<String>> {
-        debug!("🎯 Solving energy efficiency challenge: {}", challenge_data);
-        
-        // Parse challenge requirements
-        let target_efficiency: u32 = challenge_data
-            .split("target_efficiency:")
-            .nth(1)
-            .and_then(|s| s.trim().parse().ok())
-            .unwrap_or(80);
+    pub async fn solve_efficiency_challenge(&self, challenge: &EfficiencyChallenge) -> Result<Option<String>> {
+        debug!("🎯 Solving energy efficiency challenge: target {}", challenge.target_efficiency);
+        
+        let target_efficiency = challenge.target_efficiency;
         
         // Apply optimizations to meet target
         self.apply_efficiency_optimizations().await?;
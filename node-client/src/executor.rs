@@ -0,0 +1,205 @@
+//! Pluggable execution of DAG transactions
+//!
+//! The DAG processor hands each transaction whose dependencies are done to a
+//! [`TransactionExecutor`], which validates it, executes it and produces an
+//! [`ExecutionReceipt`] kept on its DAG node. Transactions run concurrently, up to the DAG
+//! batch size and the governor's DAG parallelism, on the node's async runtime; executors with
+//! CPU-bound work should move it to `spawn_blocking` rather than block the runtime.
+//!
+//! Without an attached executor the processor uses [`NoopExecutor`], which accepts everything
+//! and executes nothing. [`EvmCallExecutor`] simulates each transaction as an `eth_call`.
+//! Execution never holds a transaction back from detection: a rejected or failed transaction is
+//! still marked processed, with its receipt saying why.
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use ethers::providers::{Http, Middleware, Provider, RpcError};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{Address, TransactionRequest};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::config::ExecutorConfig;
+use crate::dag::{Transaction, TransactionLog};
+
+/// Validates and executes DAG transactions
+#[async_trait]
+pub trait TransactionExecutor: Send + Sync {
+    fn name(&self) -> &str;
+    
+    /// Reject a transaction the executor cannot or should not run
+    async fn validate(&self, transaction: &Transaction) -> Result<()>;
+    
+    /// Run a validated transaction; a revert is an outcome, not an error
+    async fn execute(&self, transaction: &Transaction) -> Result<ExecutionOutcome>;
+    
+    /// The receipt kept for an executed transaction
+    fn receipt(&self, transaction: &Transaction, outcome: ExecutionOutcome) -> ExecutionReceipt {
+        ExecutionReceipt {
+            transaction_id: transaction.id.clone(),
+            executor: self.name().to_string(),
+            status: if outcome.reverted { ExecutionStatus::Reverted } else { ExecutionStatus::Succeeded },
+            gas_used: outcome.gas_used,
+            output: outcome.output,
+            logs: outcome.logs,
+            error: None,
+            elapsed_us: 0,
+            executed_at: chrono::Utc::now().timestamp() as u64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExecutionOutcome {
+    pub reverted: bool,
+    pub gas_used: u64,
+    /// Return data, or revert data when reverted
+    pub output: Vec<u8>,
+    pub logs: Vec<TransactionLog>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionStatus {
+    Succeeded,
+    Reverted,
+    /// Refused by the executor's validation
+    Rejected,
+    /// The executor could not run it, e.g. its RPC endpoint was unreachable
+    Failed,
+    /// Lost a nonce conflict to another transaction in the DAG and was not executed
+    Conflicted,
+    /// Not executed because a dependency failed, under `dag_retry.on_failure = "cascade"`
+    DependencyFailed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionReceipt {
+    pub transaction_id: String,
+    pub executor: String,
+    pub status: ExecutionStatus,
+    pub gas_used: u64,
+    pub output: Vec<u8>,
+    pub logs: Vec<TransactionLog>,
+    /// Why the transaction was rejected or failed
+    pub error: Option<String>,
+    /// Time spent validating and executing, filled in by the DAG processor
+    pub elapsed_us: u64,
+    pub executed_at: u64,
+}
+
+impl ExecutionReceipt {
+    /// Receipt for a transaction that was rejected or could not be executed
+    pub fn unexecuted(transaction: &Transaction, executor: &str, status: ExecutionStatus, error: &anyhow::Error) -> Self {
+        Self {
+            transaction_id: transaction.id.clone(),
+            executor: executor.to_string(),
+            status,
+            gas_used: 0,
+            output: Vec::new(),
+            logs: Vec::new(),
+            error: Some(format!("{:#}", error)),
+            elapsed_us: 0,
+            executed_at: chrono::Utc::now().timestamp() as u64,
+        }
+    }
+}
+
+/// Accepts every transaction and executes nothing
+pub struct NoopExecutor;
+
+#[async_trait]
+impl TransactionExecutor for NoopExecutor {
+    fn name(&self) -> &str {
+        "noop"
+    }
+    
+    async fn validate(&self, _transaction: &Transaction) -> Result<()> {
+        Ok(())
+    }
+    
+    async fn execute(&self, _transaction: &Transaction) -> Result<ExecutionOutcome> {
+        Ok(ExecutionOutcome::default())
+    }
+}
+
+/// Simulates each transaction as an `eth_call` against one chain's RPC endpoint, at its latest block
+pub struct EvmCallExecutor {
+    provider: Provider<Http>,
+    chain_id: u64,
+    timeout: Duration,
+}
+
+impl EvmCallExecutor {
+    pub fn new(config: &ExecutorConfig, rpc_url: &str, chain_id: u64) -> Result<Self> {
+        let provider = Provider::<Http>::try_from(rpc_url)
+            .map_err(|e| anyhow!("Invalid executor RPC URL {}: {}", rpc_url, e))?;
+        Ok(Self {
+            provider,
+            chain_id,
+            timeout: Duration::from_millis(config.timeout_ms.max(1)),
+        })
+    }
+    
+    fn call_request(transaction: &Transaction) -> Result<TypedTransaction> {
+        let from: Address = transaction.from.parse()
+            .map_err(|_| anyhow!("Sender {} is not an address", transaction.from))?;
+        let to: Address = transaction.to.parse()
+            .map_err(|_| anyhow!("Recipient {} is not an address", transaction.to))?;
+        Ok(TransactionRequest::new()
+            .from(from)
+            .to(to)
+            .data(transaction.data.clone())
+            .value(transaction.value)
+            .into())
+    }
+}
+
+#[async_trait]
+impl TransactionExecutor for EvmCallExecutor {
+    fn name(&self) -> &str {
+        "evm_call"
+    }
+    
+    async fn validate(&self, transaction: &Transaction) -> Result<()> {
+        if transaction.chain_id != self.chain_id {
+            bail!("EVM-call executor simulates chain {} only, not {}", self.chain_id, transaction.chain_id);
+        }
+        Self::call_request(transaction)?;
+        Ok(())
+    }
+    
+    async fn execute(&self, transaction: &Transaction) -> Result<ExecutionOutcome> {
+        let request = Self::call_request(transaction)?;
+        let called = tokio::time::timeout(self.timeout, self.provider.call(&request, None))
+            .await
+            .map_err(|_| anyhow!("eth_call timed out after {}ms", self.timeout.as_millis()))?;
+        
+        match called {
+            Ok(output) => {
+                // Estimation is best-effort; the call already succeeded
+                let gas_used = tokio::time::timeout(self.timeout, self.provider.estimate_gas(&request, None))
+                    .await
+                    .ok()
+                    .and_then(|estimate| estimate.ok())
+                    .map_or(0, |gas| gas.as_u64());
+                Ok(ExecutionOutcome {
+                    reverted: false,
+                    gas_used,
+                    output: output.to_vec(),
+                    logs: Vec::new(),
+                })
+            }
+            // A JSON-RPC error response is the node refusing the call, i.e. a revert
+            Err(e) => match e.as_error_response() {
+                Some(response) => Ok(ExecutionOutcome {
+                    reverted: true,
+                    gas_used: 0,
+                    output: response.as_revert_data().map(|data| data.to_vec()).unwrap_or_default(),
+                    logs: Vec::new(),
+                }),
+                None => Err(e.into()),
+            },
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! On-chain finality for DAG epochs
+//!
+//! Every DAG checkpoint seals the transactions it finalizes, with their execution results, into
+//! a [`DagEpoch`] root. The finalizer commits sealed roots to the DAGShield contract, retrying
+//! those that failed to go on-chain, so a reward or challenge dispute over what this node
+//! processed is settled against the root it committed to at the time, with a
+//! [`DagEpochProof`](crate::dag::DagEpochProof) for the transaction in question.
+
+use anyhow::{bail, Result};
+use prometheus::IntGauge;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::blockchain::BlockchainClient;
+use crate::config::DagEpochConfig;
+use crate::dag::{DagEpoch, DAG_EPOCH_NAMESPACE};
+use crate::metrics::register_once;
+use crate::storage::NodeStorage;
+
+pub struct EpochFinalizer {
+    config: DagEpochConfig,
+    storage: Arc<NodeStorage>,
+    blockchain: Arc<BlockchainClient>,
+    committed: IntGauge,
+}
+
+impl EpochFinalizer {
+    pub fn new(config: &DagEpochConfig, storage: Arc<NodeStorage>, blockchain: Arc<BlockchainClient>) -> Result<Self> {
+        if config.commit_interval_secs == 0 {
+            bail!("dag_epochs.commit_interval_secs must be positive");
+        }
+        static COMMITTED: OnceLock<IntGauge> = OnceLock::new();
+        let committed = register_once(&COMMITTED, || {
+            IntGauge::new("dagshield_dag_epoch_committed", "Latest DAG epoch committed on-chain")
+        })?;
+        
+        Ok(Self {
+            config: config.clone(),
+            storage,
+            blockchain,
+            committed,
+        })
+    }
+    
+    /// Commit sealed epochs every `commit_interval_secs` until the task is aborted
+    pub async fn start(&self) -> Result<()> {
+        info!("📍 Committing DAG epoch roots on-chain every {}s", self.config.commit_interval_secs);
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.commit_interval_secs));
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.commit_sealed().await {
+                warn!("⚠️ DAG epoch commitment failed: {:#}", e);
+            }
+        }
+    }
+    
+    /// Commit every sealed epoch not on-chain yet, oldest first
+    async fn commit_sealed(&self) -> Result<()> {
+        for (key, mut epoch) in self.storage.scan::<DagEpoch>(DAG_EPOCH_NAMESPACE)? {
+            let Some((root, transactions)) = epoch.commitment()? else {
+                continue;
+            };
+            // Later epochs would fail for the same reason; retried next round
+            let tx_hash = self.blockchain.commit_dag_epoch(epoch.epoch, root, transactions).await?;
+            
+            info!("📍 Committed DAG epoch {}: {} transactions under root {} ({})",
+                  epoch.epoch, epoch.leaves.len(), epoch.root, tx_hash);
+            epoch.tx_hash = Some(tx_hash);
+            self.storage.put(DAG_EPOCH_NAMESPACE, &key, &epoch)?;
+            self.committed.set(epoch.epoch as i64);
+        }
+        Ok(())
+    }
+}
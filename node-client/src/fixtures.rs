@@ -0,0 +1,125 @@
+//! Golden-verdict fixtures: serialized transactions with expected verdicts used to
+//! detect verdict drift before promoting new models or rules
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::ai::ThreatDetector;
+use crate::config::NodeConfig;
+use crate::dag::Transaction;
+use crate::governor::ResourceGovernor;
+use crate::threat::ThreatClass;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoldenFixture {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub transaction: Transaction,
+    pub expected: ExpectedVerdict,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpectedVerdict {
+    pub threat_type: ThreatClass,
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VerdictDrift {
+    pub fixture: String,
+    pub expected: ExpectedVerdict,
+    pub actual: ExpectedVerdict,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VerificationReport {
+    pub total: usize,
+    pub passed: usize,
+    pub drifted: Vec<VerdictDrift>,
+}
+
+impl VerificationReport {
+    pub fn is_clean(&self) -> bool {
+        self.drifted.is_empty()
+    }
+}
+
+/// Load every `*.json` fixture in `dir`, sorted by file name so runs are reproducible
+pub fn load_fixtures<P: AsRef<Path>>(dir: P) -> Result<Vec<(PathBuf, GoldenFixture)>> {
+    let dir = dir.as_ref();
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read fixtures directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().map(|ext| ext == "json").unwrap_or(false))
+        .collect();
+    paths.sort();
+    
+    paths
+        .into_iter()
+        .map(|path| {
+            let content = std::fs::read_to_string(&path)?;
+            let fixture: GoldenFixture = serde_json::from_str(&content)
+                .with_context(|| format!("Invalid fixture {}", path.display()))?;
+            Ok((path, fixture))
+        })
+        .collect()
+}
+
+/// Run the active detection pipeline against the golden corpus.
+/// With `bless`, the fixtures are rewritten with the current verdicts instead.
+pub async fn verify_model(config: &NodeConfig, fixtures_dir: &str, tolerance: f32, bless: bool) -> Result<VerificationReport> {
+    let fixtures = load_fixtures(fixtures_dir)?;
+    if fixtures.is_empty() {
+        bail!("No golden fixtures found in {}", fixtures_dir);
+    }
+    
+    info!("🧪 Verifying detection pipeline against {} golden fixtures", fixtures.len());
+    
+    let governor = Arc::new(ResourceGovernor::new(&config.workers, &config.work_tokens)?);
+    let detector = ThreatDetector::new(&config.ai, governor).await?;
+    
+    let mut report = VerificationReport {
+        total: fixtures.len(),
+        passed: 0,
+        drifted: Vec::new(),
+    };
+    
+    for (path, mut fixture) in fixtures {
+        let result = detector.detect_threat(&fixture.transaction).await?;
+        let actual = ExpectedVerdict {
+            threat_type: result.threat_type.clone(),
+            confidence: result.confidence,
+        };
+        
+        if bless {
+            fixture.expected = actual;
+            std::fs::write(&path, serde_json::to_string_pretty(&fixture)? + "\n")?;
+            info!("✍️ Blessed {}", fixture.name);
+            report.passed += 1;
+            continue;
+        }
+        
+        let matches = actual.threat_type == fixture.expected.threat_type
+            && (actual.confidence - fixture.expected.confidence).abs() <= tolerance;
+        
+        if matches {
+            report.passed += 1;
+        } else {
+            warn!("❌ Verdict drift in {}: expected {} ({:.2}), got {} ({:.2})",
+                  fixture.name, fixture.expected.threat_type, fixture.expected.confidence,
+                  actual.threat_type, actual.confidence);
+            report.drifted.push(VerdictDrift {
+                fixture: fixture.name,
+                expected: fixture.expected,
+                actual,
+            });
+        }
+    }
+    
+    info!("🧪 Golden verification: {}/{} fixtures match", report.passed, report.total);
+    Ok(report)
+}
@@ -0,0 +1,342 @@
+//! Fleet agent mode: outbound control-plane connection for centrally managed edge nodes
+
+use anyhow::{anyhow, bail, Result};
+use ethers::types::Address;
+use ethers::utils::keccak256;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, error, info, warn};
+
+use crate::ai::{ThreatDetector, ThreatPattern};
+use crate::audit::AuditLog;
+use crate::config::{FleetConfig, NodeConfig};
+use crate::energy::EnergyMonitor;
+use crate::ipfs::{spawn_model_pin, IpfsClient};
+use crate::node::NodeStats;
+use crate::rollback::{ArtifactGuard, ArtifactKind};
+use crate::signature::{parse_signers, verify_signed_payload};
+use crate::storage::NodeStorage;
+
+pub mod proto {
+    tonic::include_proto!("dagshield.fleet.v1");
+}
+
+use proto::fleet_controller_client::FleetControllerClient;
+use proto::{agent_message, control_message, AgentMessage, SignedUpdate, UpdateKind};
+
+const FLEET_VERSION_NAMESPACE: &str = "fleet_versions";
+
+pub struct FleetAgent {
+    config: FleetConfig,
+    node_id: String,
+    chain_id: u64,
+    model_path: String,
+    trusted_signers: Vec<Address>,
+    stats: Arc<RwLock<NodeStats>>,
+    energy_monitor: Arc<EnergyMonitor>,
+    threat_detector: Option<Arc<ThreatDetector>>,
+    storage: Arc<NodeStorage>,
+    ipfs: Option<Arc<IpfsClient>>,
+    artifact_guard: Option<Arc<ArtifactGuard>>,
+    audit_log: Arc<AuditLog>,
+}
+
+impl FleetAgent {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        node_config: &NodeConfig,
+        node_id: &str,
+        stats: Arc<RwLock<NodeStats>>,
+        energy_monitor: Arc<EnergyMonitor>,
+        threat_detector: Option<Arc<ThreatDetector>>,
+        storage: Arc<NodeStorage>,
+        ipfs: Option<Arc<IpfsClient>>,
+        artifact_guard: Option<Arc<ArtifactGuard>>,
+        audit_log: Arc<AuditLog>,
+    ) -> Result<Self> {
+        let trusted_signers = parse_signers(&node_config.fleet.trusted_signers)?;
+        if trusted_signers.is_empty() {
+            bail!("Fleet agent mode requires at least one trusted signer");
+        }
+        if node_config.fleet.fleet_id.is_empty() {
+            bail!("Fleet agent mode requires fleet.fleet_id");
+        }
+        
+        Ok(Self {
+            config: node_config.fleet.clone(),
+            node_id: node_id.to_string(),
+            chain_id: node_config.blockchain.chain_id,
+            model_path: node_config.ai.model_path.clone(),
+            trusted_signers,
+            stats,
+            energy_monitor,
+            threat_detector,
+            storage,
+            ipfs,
+            artifact_guard,
+            audit_log,
+        })
+    }
+    
+    /// Keep a session with the controller open, reconnecting with backoff when it drops
+    pub async fn start(&self) -> Result<()> {
+        info!("🛰️ Fleet agent connecting to controller: {}", self.config.controller_url);
+        
+        let mut backoff = Duration::from_secs(1);
+        let max_backoff = Duration::from_secs(self.config.max_reconnect_backoff_secs.max(1));
+        
+        loop {
+            match self.run_session().await {
+                Ok(()) => {
+                    info!("🛰️ Fleet controller closed the session");
+                    backoff = Duration::from_secs(1);
+                }
+                Err(e) => warn!("Fleet session error: {}", e),
+            }
+            
+            debug!("🛰️ Reconnecting to fleet controller in {:?}", backoff);
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(max_backoff);
+        }
+    }
+    
+    async fn run_session(&self) -> Result<()> {
+        let mut client = FleetControllerClient::connect(self.config.controller_url.clone()).await?;
+        
+        let (tx, rx) = mpsc::channel::<AgentMessage>(32);
+        tx.send(enroll_message(&self.node_id, self.chain_id)).await?;
+        
+        let mut inbound = client.session(ReceiverStream::new(rx)).await?.into_inner();
+        info!("✅ Enrolled with fleet controller as {}", self.node_id);
+        
+        let mut health_interval = tokio::time::interval(
+            Duration::from_secs(self.config.health_interval_secs)
+        );
+        
+        loop {
+            tokio::select! {
+                _ = health_interval.tick() => {
+                    tx.send(self.health_report().await).await?;
+                }
+                message = inbound.message() => {
+                    let Some(message) = message? else {
+                        return Ok(());
+                    };
+                    
+                    if let Some(control_message::Payload::Update(update)) = message.payload {
+                        let ack = self.handle_update(&update).await;
+                        tx.send(AgentMessage {
+                            payload: Some(agent_message::Payload::Ack(ack)),
+                        }).await?;
+                    }
+                }
+            }
+        }
+    }
+    
+    async fn health_report(&self) -> AgentMessage {
+        let stats = self.stats.read().await.clone();
+        let power_watts = self.energy_monitor
+            .get_current_power_usage()
+            .await
+            .unwrap_or(0.0);
+        
+        AgentMessage {
+            payload: Some(agent_message::Payload::Health(proto::HealthReport {
+                node_id: self.node_id.clone(),
+                timestamp: chrono::Utc::now().timestamp() as u64,
+                threats_detected: stats.threats_detected,
+                challenges_completed: stats.challenges_completed,
+                reputation_score: stats.reputation_score,
+                energy_efficiency: stats.energy_efficiency,
+                uptime_seconds: stats.uptime_seconds,
+                power_watts,
+            })),
+        }
+    }
+    
+    async fn handle_update(&self, update: &SignedUpdate) -> proto::UpdateAck {
+        match self.apply_update(update).await {
+            Ok(message) => {
+                info!("🛰️ Applied fleet update {} (v{}): {}", update.update_id, update.version, message);
+                proto::UpdateAck {
+                    update_id: update.update_id.clone(),
+                    applied: true,
+                    message,
+                }
+            }
+            Err(e) => {
+                error!("🛰️ Rejected fleet update {}: {}", update.update_id, e);
+                proto::UpdateAck {
+                    update_id: update.update_id.clone(),
+                    applied: false,
+                    message: e.to_string(),
+                }
+            }
+        }
+    }
+    
+    async fn apply_update(&self, update: &SignedUpdate) -> Result<String> {
+        let kind = UpdateKind::try_from(update.kind)
+            .map_err(|_| anyhow!("Unknown update kind {}", update.kind))?;
+        
+        let digest = update_digest(&self.config.fleet_id, self.chain_id, update);
+        let signer = verify_signed_payload(&digest, &update.signature, &self.trusted_signers)?;
+        debug!("🔏 Fleet update {} signed by {:?}", update.update_id, signer);
+        
+        // Reject replays of older updates for the same kind
+        let version_key = kind.as_str_name();
+        let applied: Option<u64> = self.storage.get(FLEET_VERSION_NAMESPACE, version_key)?;
+        if let Some(applied) = applied {
+            if update.version <= applied {
+                bail!("Stale {} update v{} (already at v{})", version_key, update.version, applied);
+            }
+        }
+        
+        let message = match kind {
+            UpdateKind::Config => self.apply_config(&update.payload)?,
+            UpdateKind::Model => self.apply_model(&update.payload)?,
+            UpdateKind::Patterns => self.apply_patterns(&update.payload).await?,
+            UpdateKind::Unspecified => bail!("Update kind not specified"),
+        };
+        
+        self.storage.put(FLEET_VERSION_NAMESPACE, version_key, &update.version)?;
+        Ok(message)
+    }
+    
+    fn apply_config(&self, payload: &[u8]) -> Result<String> {
+        let content = std::str::from_utf8(payload)?;
+        
+        // Validate before touching the file on disk
+        let config: NodeConfig = toml::from_str(content)?;
+        write_atomically(&self.config.config_path, payload)?;
+        let last_digest = self.audit_log.replay(None)?.config_digest;
+        self.audit_log.record_config("fleet", &config, last_digest.as_deref())?;
+        
+        Ok(format!("config written to {}; restart required", self.config.config_path))
+    }
+    
+    fn apply_model(&self, payload: &[u8]) -> Result<String> {
+        if let Some(guard) = &self.artifact_guard {
+            guard.ensure_not_quarantined(ArtifactKind::Model, payload)?;
+            let previous = std::fs::read(&self.model_path).ok();
+            write_atomically(&self.model_path, payload)?;
+            guard.artifact_applied(ArtifactKind::Model, payload, previous.as_deref())?;
+        } else {
+            write_atomically(&self.model_path, payload)?;
+        }
+        
+        // Swap the new model in without waiting for the watcher to notice the file
+        if let Some(detector) = &self.threat_detector {
+            detector.request_model_reload();
+        }
+        
+        // Redistribute the new model to peers
+        if let Some(ipfs) = &self.ipfs {
+            spawn_model_pin(Arc::clone(ipfs), Arc::clone(&self.storage), self.model_path.clone());
+        }
+        
+        Ok(format!("model written to {} ({} bytes); hot-reloading", self.model_path, payload.len()))
+    }
+    
+    async fn apply_patterns(&self, payload: &[u8]) -> Result<String> {
+        let patterns: Vec<ThreatPattern> = serde_json::from_slice(payload)?;
+        let count = patterns.len();
+        
+        match &self.threat_detector {
+            Some(detector) => {
+                if let Some(guard) = &self.artifact_guard {
+                    guard.ensure_not_quarantined(ArtifactKind::Patterns, payload)?;
+                    // Keep the full pattern set the update merges into, so it can be restored
+                    let previous: Vec<ThreatPattern> = detector.get_threat_patterns().await.into_values().collect();
+                    detector.update_threat_patterns(patterns).await?;
+                    guard.artifact_applied(ArtifactKind::Patterns, payload, Some(&serde_json::to_vec(&previous)?))?;
+                } else {
+                    detector.update_threat_patterns(patterns).await?;
+                }
+                Ok(format!("{} threat patterns merged", count))
+            }
+            None => bail!("AI detection disabled on this node"),
+        }
+    }
+}
+
+/// Enroll once and hang up, so a freshly provisioned device is known to the controller
+/// before its first start
+pub async fn enroll(config: &FleetConfig, node_id: &str, chain_id: u64) -> Result<()> {
+    let mut client = FleetControllerClient::connect(config.controller_url.clone()).await?;
+    let (tx, rx) = mpsc::channel::<AgentMessage>(1);
+    tx.send(enroll_message(node_id, chain_id)).await?;
+    drop(tx);
+    
+    client.session(ReceiverStream::new(rx)).await?;
+    info!("✅ Enrolled with fleet controller as {}", node_id);
+    Ok(())
+}
+
+fn enroll_message(node_id: &str, chain_id: u64) -> AgentMessage {
+    AgentMessage {
+        payload: Some(agent_message::Payload::Enroll(proto::Enroll {
+            node_id: node_id.to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            chain_id,
+        })),
+    }
+}
+
+/// Digest the controller signs: the fleet and chain it is meant for, then the update, with
+/// each variable-length field prefixed by its length
+fn update_digest(fleet_id: &str, chain_id: u64, update: &SignedUpdate) -> [u8; 32] {
+    let mut message = Vec::with_capacity(fleet_id.len() + update.update_id.len() + 28 + update.payload.len());
+    message.extend_from_slice(&(fleet_id.len() as u32).to_be_bytes());
+    message.extend_from_slice(fleet_id.as_bytes());
+    message.extend_from_slice(&chain_id.to_be_bytes());
+    message.extend_from_slice(&(update.update_id.len() as u32).to_be_bytes());
+    message.extend_from_slice(update.update_id.as_bytes());
+    message.extend_from_slice(&(update.kind as u32).to_be_bytes());
+    message.extend_from_slice(&update.version.to_be_bytes());
+    message.extend_from_slice(&update.payload);
+    keccak256(message)
+}
+
+fn write_atomically(path: &str, contents: &[u8]) -> Result<()> {
+    let tmp_path = format!("{}.fleet-tmp", path);
+    std::fs::write(&tmp_path, contents)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn update(update_id: &str, kind: UpdateKind, payload: &[u8]) -> SignedUpdate {
+        SignedUpdate {
+            update_id: update_id.to_string(),
+            kind: kind as i32,
+            version: 1,
+            payload: payload.to_vec(),
+            signature: String::new(),
+        }
+    }
+    
+    #[test]
+    fn the_update_id_cannot_run_into_the_fields_after_it() {
+        // Unprefixed, both would sign "a", 00000001, 00000000 00000001, 00000000, "x"
+        let config = update("a", UpdateKind::Config, b"\0\0\0\0x");
+        let mut shifted = update("a\0\0\0\x01", UpdateKind::Unspecified, b"x");
+        shifted.version = 1 << 32;
+        assert_ne!(update_digest("fleet", 1, &config), update_digest("fleet", 1, &shifted));
+    }
+    
+    #[test]
+    fn updates_are_bound_to_their_fleet_and_chain() {
+        let update = update("u1", UpdateKind::Patterns, b"[]");
+        let digest = update_digest("fleet-a", 1, &update);
+        assert_eq!(digest, update_digest("fleet-a", 1, &update));
+        assert_ne!(digest, update_digest("fleet-b", 1, &update));
+        assert_ne!(digest, update_digest("fleet-a", 137, &update));
+    }
+}
@@ -0,0 +1,417 @@
+//! Background gas price oracle with per-chain percentile tracking
+
+use anyhow::{bail, Result};
+use dashmap::DashMap;
+use ethers::providers::Middleware;
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::{BlockNumber, U256};
+use prometheus::{GaugeVec, Opts};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, OnceLock};
+use tracing::{debug, info, warn};
+
+use crate::config::{FeeStrategy, GasOracleConfig};
+use crate::metrics::register_once;
+use crate::rpc_pool::PooledProvider;
+
+const GWEI: f64 = 1e9;
+const PERCENTILES: [&str; 5] = ["p10", "p25", "p50", "p75", "p90"];
+
+/// How quickly a transaction needs to be included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GasUrgency {
+    /// Can wait for a cheap block (e.g. consensus votes)
+    Low,
+    Normal,
+    /// Must land promptly (e.g. challenge solutions near their deadline)
+    High,
+}
+
+impl GasUrgency {
+    pub fn with_strategy(self, strategy: FeeStrategy) -> Self {
+        match (strategy, self) {
+            (FeeStrategy::Fast, GasUrgency::Low) => GasUrgency::Normal,
+            (FeeStrategy::Fast, _) => GasUrgency::High,
+            (FeeStrategy::Economy, GasUrgency::High) => GasUrgency::Normal,
+            (FeeStrategy::Economy, _) => GasUrgency::Low,
+            (FeeStrategy::Standard, urgency) => urgency,
+        }
+    }
+}
+
+/// What a transaction bids per unit of gas, in wei
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fees {
+    Legacy { gas_price: U256 },
+    Eip1559 { max_fee: U256, priority_fee: U256 },
+}
+
+impl Fees {
+    /// The most a unit of gas can cost
+    pub fn max_fee(&self) -> U256 {
+        match self {
+            Fees::Legacy { gas_price } => *gas_price,
+            Fees::Eip1559 { max_fee, .. } => *max_fee,
+        }
+    }
+    
+    /// The same bid with no more than `ceiling` per unit of gas
+    pub fn capped(self, ceiling: U256) -> Self {
+        match self {
+            Fees::Legacy { gas_price } => Fees::Legacy { gas_price: gas_price.min(ceiling) },
+            Fees::Eip1559 { max_fee, priority_fee } => {
+                let max_fee = max_fee.min(ceiling);
+                Fees::Eip1559 { max_fee, priority_fee: priority_fee.min(max_fee) }
+            }
+        }
+    }
+    
+    /// The same bid raised by `percent`, rounded up so a replacement clears the node's minimum
+    pub fn bumped(self, percent: u64) -> Self {
+        let bump = |fee: U256| (fee * (100 + percent) + 99) / 100;
+        match self {
+            Fees::Legacy { gas_price } => Fees::Legacy { gas_price: bump(gas_price) },
+            Fees::Eip1559 { max_fee, priority_fee } => Fees::Eip1559 {
+                max_fee: bump(max_fee),
+                priority_fee: bump(priority_fee),
+            },
+        }
+    }
+    
+    /// Price `tx`, turning it into a legacy transaction for legacy fees
+    pub fn apply(&self, tx: &mut TypedTransaction) {
+        match *self {
+            Fees::Legacy { gas_price } => {
+                if let TypedTransaction::Eip1559(inner) = tx {
+                    *tx = TypedTransaction::Legacy(inner.clone().into());
+                }
+                tx.set_gas_price(gas_price);
+            }
+            Fees::Eip1559 { max_fee, priority_fee } => match tx {
+                TypedTransaction::Eip1559(inner) => {
+                    inner.max_fee_per_gas = Some(max_fee);
+                    inner.max_priority_fee_per_gas = Some(priority_fee);
+                }
+                // Only built as legacy transactions on purpose, so they keep a single price
+                other => {
+                    other.set_gas_price(max_fee);
+                }
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GasSample {
+    base_fee: U256,
+    priority_fee: U256,
+}
+
+/// Fee percentiles over the sampling window, in wei
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeePercentiles {
+    pub p10: U256,
+    pub p25: U256,
+    pub p50: U256,
+    pub p75: U256,
+    pub p90: U256,
+}
+
+impl FeePercentiles {
+    /// Nearest-rank percentiles; all zero without values
+    fn from_values(mut values: Vec<U256>) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        values.sort();
+        let at = |q: f64| values[((values.len() - 1) as f64 * q).round() as usize];
+        Self {
+            p10: at(0.10),
+            p25: at(0.25),
+            p50: at(0.50),
+            p75: at(0.75),
+            p90: at(0.90),
+        }
+    }
+    
+    fn get(&self, name: &str) -> U256 {
+        match name {
+            "p10" => self.p10,
+            "p25" => self.p25,
+            "p50" => self.p50,
+            "p75" => self.p75,
+            _ => self.p90,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasEstimate {
+    pub chain_id: u64,
+    pub base_fee: FeePercentiles,
+    pub priority_fee: FeePercentiles,
+    pub latest_base_fee: U256,
+    pub samples: usize,
+    pub updated_at: u64,
+}
+
+/// The latest `window_size` samples of a chain, oldest first
+struct SampleWindow {
+    samples: VecDeque<GasSample>,
+    size: usize,
+}
+
+impl SampleWindow {
+    fn new(size: usize) -> Self {
+        let size = size.max(1);
+        Self {
+            samples: VecDeque::with_capacity(size),
+            size,
+        }
+    }
+    
+    /// Add samples, evicting the oldest beyond the window
+    fn extend(&mut self, samples: impl IntoIterator<Item = GasSample>) {
+        for sample in samples {
+            if self.samples.len() == self.size {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(sample);
+        }
+    }
+    
+    fn estimate(&self, chain_id: u64) -> Option<GasEstimate> {
+        let latest = self.samples.back()?;
+        Some(GasEstimate {
+            chain_id,
+            base_fee: FeePercentiles::from_values(self.samples.iter().map(|s| s.base_fee).collect()),
+            priority_fee: FeePercentiles::from_values(self.samples.iter().map(|s| s.priority_fee).collect()),
+            latest_base_fee: latest.base_fee,
+            samples: self.samples.len(),
+            updated_at: chrono::Utc::now().timestamp() as u64,
+        })
+    }
+}
+
+struct ChainGas {
+    provider: Arc<PooledProvider>,
+    window: SampleWindow,
+    estimate: Option<GasEstimate>,
+}
+
+/// Samples base and priority fees per chain and serves percentile-based fee suggestions,
+/// so transaction senders never have to fetch gas prices ad hoc.
+pub struct GasOracle {
+    config: GasOracleConfig,
+    chains: DashMap<u64, ChainGas>,
+    gauges: GaugeVec,
+}
+
+impl GasOracle {
+    pub fn new(config: &GasOracleConfig) -> Result<Self> {
+        if !PERCENTILES.contains(&config.congestion_percentile.as_str()) {
+            bail!("congestion_percentile must be one of {:?}", PERCENTILES);
+        }
+        
+        static GAUGES: OnceLock<GaugeVec> = OnceLock::new();
+        let gauges = register_once(&GAUGES, || {
+            GaugeVec::new(
+                Opts::new("dagshield_gas_fee_gwei", "Sampled fee percentiles per chain"),
+                &["chain_id", "fee", "percentile"],
+            )
+        })?;
+        
+        Ok(Self {
+            config: config.clone(),
+            chains: DashMap::new(),
+            gauges,
+        })
+    }
+    
+    pub fn add_chain(&self, chain_id: u64, provider: Arc<PooledProvider>) {
+        self.chains.insert(chain_id, ChainGas {
+            provider,
+            window: SampleWindow::new(self.config.window_size),
+            estimate: None,
+        });
+    }
+    
+    pub async fn start(&self) -> Result<()> {
+        info!("⛽ Starting gas price oracle for {} chains", self.chains.len());
+        
+        let mut interval = tokio::time::interval(
+            std::time::Duration::from_secs(self.config.sample_interval_secs.max(1))
+        );
+        
+        loop {
+            interval.tick().await;
+            
+            let chain_ids: Vec<u64> = self.chains.iter().map(|c| *c.key()).collect();
+            for chain_id in chain_ids {
+                if let Err(e) = self.sample_chain(chain_id).await {
+                    warn!("Gas sampling failed on chain {}: {}", chain_id, e);
+                }
+            }
+        }
+    }
+    
+    async fn sample_chain(&self, chain_id: u64) -> Result<()> {
+        let provider = match self.chains.get(&chain_id) {
+            Some(chain) => Arc::clone(&chain.provider),
+            None => return Ok(()),
+        };
+        
+        let samples = match provider
+            .fee_history(self.config.fee_history_blocks, BlockNumber::Latest, &[50.0])
+            .await
+        {
+            Ok(history) => history
+                .base_fee_per_gas
+                .iter()
+                .zip(history.reward.iter())
+                .map(|(base_fee, rewards)| GasSample {
+                    base_fee: *base_fee,
+                    priority_fee: rewards.first().copied().unwrap_or_default(),
+                })
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                // Chains without EIP-1559 only expose a single gas price
+                debug!("eth_feeHistory unavailable on chain {} ({}), using eth_gasPrice", chain_id, e);
+                vec![GasSample {
+                    base_fee: provider.get_gas_price().await?,
+                    priority_fee: U256::zero(),
+                }]
+            }
+        };
+        
+        let mut chain = match self.chains.get_mut(&chain_id) {
+            Some(chain) => chain,
+            None => return Ok(()),
+        };
+        
+        chain.window.extend(samples);
+        let Some(estimate) = chain.window.estimate(chain_id) else {
+            return Ok(());
+        };
+        self.export_metrics(&estimate);
+        chain.estimate = Some(estimate);
+        
+        Ok(())
+    }
+    
+    fn export_metrics(&self, estimate: &GasEstimate) {
+        let chain_id = estimate.chain_id.to_string();
+        for name in PERCENTILES {
+            self.gauges
+                .with_label_values(&[&chain_id, "base", name])
+                .set(estimate.base_fee.get(name).as_u128() as f64 / GWEI);
+            self.gauges
+                .with_label_values(&[&chain_id, "priority", name])
+                .set(estimate.priority_fee.get(name).as_u128() as f64 / GWEI);
+        }
+    }
+    
+    /// Latest percentile estimate, if the chain has been sampled recently enough
+    pub fn estimate(&self, chain_id: u64) -> Option<GasEstimate> {
+        let estimate = self.chains.get(&chain_id)?.estimate.clone()?;
+        let age = (chrono::Utc::now().timestamp() as u64).saturating_sub(estimate.updated_at);
+        (age <= self.config.max_estimate_age_secs).then_some(estimate)
+    }
+    
+    /// Suggested `(max_fee_per_gas, max_priority_fee_per_gas)` for the given urgency
+    pub fn suggest_fees(&self, chain_id: u64, urgency: GasUrgency) -> Option<(U256, U256)> {
+        let estimate = self.estimate(chain_id)?;
+        let (base, priority) = match urgency {
+            GasUrgency::Low => (estimate.base_fee.p25, estimate.priority_fee.p25),
+            GasUrgency::Normal => (estimate.base_fee.p50, estimate.priority_fee.p50),
+            GasUrgency::High => (estimate.base_fee.p90, estimate.priority_fee.p90),
+        };
+        
+        // Never bid below the current base fee, or the transaction cannot be included at all
+        let base = base.max(estimate.latest_base_fee);
+        Some((base + priority, priority))
+    }
+    
+    /// Fees for the given urgency, as a legacy gas price when `legacy` is set
+    pub fn suggest(&self, chain_id: u64, urgency: GasUrgency, legacy: bool) -> Option<Fees> {
+        let (max_fee, priority_fee) = self.suggest_fees(chain_id, urgency)?;
+        Some(if legacy {
+            Fees::Legacy { gas_price: max_fee }
+        } else {
+            Fees::Eip1559 { max_fee, priority_fee }
+        })
+    }
+    
+    /// Legacy gas price (base + priority) for the given urgency
+    pub fn suggest_gas_price(&self, chain_id: u64, urgency: GasUrgency) -> Option<U256> {
+        self.suggest_fees(chain_id, urgency).map(|(max_fee, _)| max_fee)
+    }
+    
+    /// Whether the current base fee is above the configured percentile, i.e. deferrable work should wait
+    pub fn is_congested(&self, chain_id: u64) -> bool {
+        self.estimate(chain_id)
+            .map(|e| e.latest_base_fee > e.base_fee.get(&self.config.congestion_percentile))
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn gwei(values: &[u64]) -> Vec<U256> {
+        values.iter().map(|v| U256::from(*v) * U256::exp10(9)).collect()
+    }
+    
+    fn sample(base_fee: u64, priority_fee: u64) -> GasSample {
+        GasSample {
+            base_fee: U256::from(base_fee),
+            priority_fee: U256::from(priority_fee),
+        }
+    }
+    
+    #[test]
+    fn percentiles_use_the_nearest_rank() {
+        // 21 samples in reverse: every rank falls on a whole sample
+        let fees = FeePercentiles::from_values(gwei(&(1..=21).rev().collect::<Vec<_>>()));
+        assert_eq!([fees.p10, fees.p25, fees.p50, fees.p75, fees.p90], gwei(&[3, 6, 11, 16, 19])[..]);
+        
+        // Between ranks, the nearer sample is taken: 0.25 * 3 = 0.75 rounds to the second
+        let fees = FeePercentiles::from_values(gwei(&[40, 10, 30, 20]));
+        assert_eq!([fees.p10, fees.p25, fees.p50, fees.p75, fees.p90], gwei(&[10, 20, 30, 30, 40])[..]);
+    }
+    
+    #[test]
+    fn percentiles_of_one_sample_or_none() {
+        let fees = FeePercentiles::from_values(gwei(&[25]));
+        assert!(PERCENTILES.iter().all(|name| fees.get(name) == gwei(&[25])[0]));
+        
+        let fees = FeePercentiles::from_values(Vec::new());
+        assert!(PERCENTILES.iter().all(|name| fees.get(name).is_zero()));
+    }
+    
+    #[test]
+    fn the_window_evicts_the_oldest_samples() {
+        let mut window = SampleWindow::new(3);
+        assert!(window.estimate(1).is_none());
+        
+        window.extend([sample(100, 1), sample(200, 2)]);
+        window.extend([sample(300, 3), sample(400, 4), sample(500, 5)]);
+        let estimate = window.estimate(1).unwrap();
+        assert_eq!(estimate.samples, 3);
+        assert_eq!(estimate.base_fee.p10, U256::from(300));
+        assert_eq!(estimate.base_fee.p90, U256::from(500));
+        assert_eq!(estimate.priority_fee.p50, U256::from(4));
+        assert_eq!(estimate.latest_base_fee, U256::from(500));
+    }
+    
+    #[test]
+    fn a_zero_window_keeps_the_latest_sample() {
+        let mut window = SampleWindow::new(0);
+        window.extend([sample(100, 1), sample(200, 2)]);
+        let estimate = window.estimate(1).unwrap();
+        assert_eq!(estimate.samples, 1);
+        assert_eq!(estimate.base_fee.p50, U256::from(200));
+    }
+}
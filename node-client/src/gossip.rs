@@ -0,0 +1,394 @@
+//! Gossip signed with the node key, and slashing evidence against nodes that misuse theirs
+//!
+//! Gossipsub signs every message with the libp2p peer key, but that key is generated at each
+//! start and costs nothing, so it holds no one to account. Every payload on the mesh is
+//! therefore wrapped in a [`SignedGossip`] envelope signed with the node's wallet key, the
+//! identity it staked under, together with its topic and a per-topic sequence number.
+//!
+//! A message whose signature does not verify only counts against the peer that sent it. One that
+//! does verify binds its signer, and two kinds of it are kept as [`SlashingEvidence`]: two
+//! different payloads under one topic and sequence number (equivocation), which the contract
+//! checks and slashes on its own, and a payload the mesh rejects as invalid, which is pinned to
+//! IPFS and opened as a dispute for the owner to review. Misrouted or relayed envelopes prove
+//! nothing about their signer, since any peer can republish them, and are only dropped.
+
+use anyhow::{bail, Context, Result};
+use axum::{http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use dashmap::DashMap;
+use ethers::abi::{encode, Token};
+use ethers::types::{Address, Signature, U256};
+use ethers::utils::{hex, keccak256};
+use lru::LruCache;
+use prometheus::{IntCounterVec, Opts};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::blockchain::BlockchainClient;
+use crate::config::GossipConfig;
+use crate::ipfs::IpfsClient;
+use crate::metrics::register_once;
+use crate::status::Report;
+use crate::storage::NodeStorage;
+
+/// Evidence bundles, keyed by 0x-hex evidence ID
+pub const EVIDENCE_NAMESPACE: &str = "slashing_evidence";
+/// Last sequence number signed per topic, so a restart or clock step never reuses one
+const SEQUENCE_NAMESPACE: &str = "gossip_sequences";
+/// Messages kept per invalid-payload bundle; one is proof enough, a few show it was no accident
+const MAX_EVIDENCE_MESSAGES: usize = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedGossip {
+    pub topic: String,
+    /// Unix milliseconds, bumped past the previous message's so it never repeats on a topic
+    pub sequence: u64,
+    /// The JSON-encoded message, signed as-is
+    pub payload: String,
+    pub signer: Address,
+    /// personal_sign by `signer` of keccak256(abi.encode(keccak256(topic), sequence, keccak256(payload)))
+    pub signature: String,
+}
+
+impl SignedGossip {
+    pub fn payload_hash(&self) -> [u8; 32] {
+        keccak256(self.payload.as_bytes())
+    }
+    
+    fn digest(&self) -> [u8; 32] {
+        gossip_digest(topic_hash(&self.topic), self.sequence, self.payload_hash())
+    }
+    
+    /// Check the signature is the claimed signer's own
+    pub fn verify(&self) -> Result<()> {
+        let signature: Signature = self.signature
+            .trim_start_matches("0x")
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Malformed gossip signature: {}", e))?;
+        let recovered = signature.recover(&self.digest()[..])?;
+        if recovered != self.signer {
+            bail!("Gossip from {:?} signed by {:?}", self.signer, recovered);
+        }
+        Ok(())
+    }
+}
+
+pub fn topic_hash(topic: &str) -> [u8; 32] {
+    keccak256(topic.as_bytes())
+}
+
+fn gossip_digest(topic_hash: [u8; 32], sequence: u64, payload_hash: [u8; 32]) -> [u8; 32] {
+    keccak256(encode(&[
+        Token::FixedBytes(topic_hash.to_vec()),
+        Token::Uint(U256::from(sequence)),
+        Token::FixedBytes(payload_hash.to_vec()),
+    ]))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvidenceKind {
+    /// Two different payloads signed under one topic and sequence number
+    Equivocation,
+    /// Signed payloads the mesh rejects
+    InvalidPayload,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlashingEvidence {
+    /// 0x-hex; for equivocation, the contract's keccak256(abi.encode(offender, topicHash, sequence))
+    pub id: String,
+    pub kind: EvidenceKind,
+    pub offender: Address,
+    /// Peer the first offending message came from
+    pub peer: String,
+    pub reason: String,
+    pub messages: Vec<SignedGossip>,
+    pub captured_at: u64,
+    /// Transaction that submitted the evidence, once submitted
+    pub submitted: Option<String>,
+}
+
+pub struct GossipAuth {
+    config: GossipConfig,
+    storage: Arc<NodeStorage>,
+    blockchain: Arc<BlockchainClient>,
+    ipfs: Option<Arc<IpfsClient>>,
+    node: Address,
+    sequences: parking_lot::Mutex<HashMap<String, u64>>,
+    /// Recent messages by signer, topic and sequence number
+    seen: parking_lot::Mutex<LruCache<(Address, String, u64), SignedGossip>>,
+    /// Node key each peer first published under
+    peer_signers: DashMap<String, Address>,
+    messages: IntCounterVec,
+}
+
+impl GossipAuth {
+    pub fn new(
+        config: &GossipConfig,
+        storage: Arc<NodeStorage>,
+        blockchain: Arc<BlockchainClient>,
+        ipfs: Option<Arc<IpfsClient>>,
+    ) -> Result<Self> {
+        static MESSAGES: OnceLock<IntCounterVec> = OnceLock::new();
+        let messages = register_once(&MESSAGES, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_gossip_messages_total", "Gossip messages received, by signature outcome"),
+                &["outcome"],
+            )
+        })?;
+        
+        let tracked = NonZeroUsize::new(config.max_tracked_messages.max(1)).unwrap();
+        Ok(Self {
+            config: config.clone(),
+            node: blockchain.wallet_address(),
+            storage,
+            blockchain,
+            ipfs,
+            sequences: parking_lot::Mutex::new(HashMap::new()),
+            seen: parking_lot::Mutex::new(LruCache::new(tracked)),
+            peer_signers: DashMap::new(),
+            messages,
+        })
+    }
+    
+    /// Wrap a message for `topic` in an envelope signed with the node key
+    pub async fn seal<T: Serialize>(&self, topic: &str, message: &T) -> Result<Vec<u8>> {
+        let payload = serde_json::to_string(message)?;
+        let sequence = self.next_sequence(topic)?;
+        let signature = self.blockchain
+            .sign_message(&gossip_digest(topic_hash(topic), sequence, keccak256(payload.as_bytes())))
+            .await?;
+        Ok(serde_json::to_vec(&SignedGossip {
+            topic: topic.to_string(),
+            sequence,
+            payload,
+            signer: self.node,
+            signature,
+        })?)
+    }
+    
+    /// Check an envelope received on `topic` from `peer`, keeping evidence when its signer equivocated
+    pub fn open(&self, peer: &str, topic: &str, data: &[u8]) -> Result<SignedGossip> {
+        let opened = self.check(peer, topic, data);
+        let outcome = if opened.is_ok() { "authentic" } else { "rejected" };
+        self.messages.with_label_values(&[outcome]).inc();
+        opened
+    }
+    
+    fn check(&self, peer: &str, topic: &str, data: &[u8]) -> Result<SignedGossip> {
+        let message: SignedGossip = serde_json::from_slice(data).context("Malformed gossip envelope")?;
+        message.verify()?;
+        if message.topic != topic {
+            bail!("Gossip signed for {} arrived on {}", message.topic, topic);
+        }
+        let now = now_millis();
+        if message.sequence > now + self.config.max_clock_skew_secs * 1000 {
+            bail!("Gossip sequenced {}ms ahead of this node's clock", message.sequence - now);
+        }
+        if message.sequence + self.config.max_message_age_secs * 1000 < now {
+            bail!("Gossip sequenced {}ms ago, dropped as a replay", now - message.sequence);
+        }
+        
+        // A peer publishing under several node keys is relaying or running Sybils; either way not its own word
+        let bound = *self.peer_signers.entry(peer.to_string()).or_insert(message.signer);
+        if bound != message.signer {
+            bail!("Peer publishes as {:?} but signed as {:?}", bound, message.signer);
+        }
+        
+        let key = (message.signer, message.topic.clone(), message.sequence);
+        let earlier = {
+            let mut seen = self.seen.lock();
+            match seen.get(&key) {
+                Some(earlier) if earlier.payload != message.payload => Some(earlier.clone()),
+                Some(_) => None,
+                None => {
+                    seen.put(key, message.clone());
+                    None
+                }
+            }
+        };
+        if let Some(earlier) = earlier {
+            let id = equivocation_id(message.signer, &message.topic, message.sequence);
+            let reason = format!("Two payloads signed under {} sequence {}", message.topic, message.sequence);
+            self.capture(id, EvidenceKind::Equivocation, peer, vec![earlier, message.clone()], reason);
+            bail!("{:?} equivocated on {} sequence {}", message.signer, message.topic, message.sequence);
+        }
+        Ok(message)
+    }
+    
+    /// Keep an authentic message whose payload the mesh rejected as evidence against its signer
+    pub fn invalid_payload(&self, peer: &str, message: &SignedGossip, reason: &str) {
+        let id = format!("0x{}", hex::encode(keccak256(encode(&[
+            Token::Address(message.signer),
+            Token::String("invalid_payload".to_string()),
+        ]))));
+        self.capture(id, EvidenceKind::InvalidPayload, peer, vec![message.clone()], reason.to_string());
+    }
+    
+    fn capture(&self, id: String, kind: EvidenceKind, peer: &str, messages: Vec<SignedGossip>, reason: String) {
+        let offender = messages[0].signer;
+        if offender == self.node {
+            return;
+        }
+        let captured = self.storage.get::<SlashingEvidence>(EVIDENCE_NAMESPACE, &id).and_then(|known| {
+            let evidence = match known {
+                // Submitted evidence stands as it was sent
+                Some(evidence) if evidence.submitted.is_some() => return Ok(false),
+                Some(mut evidence) if evidence.messages.len() < MAX_EVIDENCE_MESSAGES && kind == EvidenceKind::InvalidPayload => {
+                    evidence.messages.extend(messages);
+                    evidence
+                }
+                Some(_) => return Ok(false),
+                None => SlashingEvidence {
+                    id: id.clone(),
+                    kind,
+                    offender,
+                    peer: peer.to_string(),
+                    reason,
+                    messages,
+                    captured_at: chrono::Utc::now().timestamp() as u64,
+                    submitted: None,
+                },
+            };
+            self.storage.put(EVIDENCE_NAMESPACE, &id, &evidence)?;
+            Ok(true)
+        });
+        match captured {
+            Ok(true) => {
+                self.messages.with_label_values(&["evidence"]).inc();
+                warn!("⚖️ Captured {:?} evidence against {:?} via peer {}", kind, offender, peer);
+            }
+            Ok(false) => {}
+            Err(e) => warn!("⚠️ Failed to store slashing evidence against {:?}: {:#}", offender, e),
+        }
+    }
+    
+    /// Captured evidence, newest first
+    pub fn evidence(&self) -> Result<Vec<SlashingEvidence>> {
+        let mut evidence: Vec<_> = self.storage.scan::<SlashingEvidence>(EVIDENCE_NAMESPACE)?
+            .into_iter()
+            .map(|(_, evidence)| evidence)
+            .collect();
+        evidence.sort_by_key(|evidence| Reverse(evidence.captured_at));
+        Ok(evidence)
+    }
+    
+    /// Prune expired evidence and, when enabled, submit what has not been submitted yet
+    pub async fn start(&self) -> Result<()> {
+        if self.config.submit_evidence {
+            info!("⚖️ Submitting gossip slashing evidence every {}s", self.config.submit_interval_secs.max(60));
+        }
+        let mut sweeps = tokio::time::interval(Duration::from_secs(self.config.submit_interval_secs.max(60)));
+        loop {
+            sweeps.tick().await;
+            if let Err(e) = self.prune() {
+                warn!("⚠️ Failed to prune slashing evidence: {:#}", e);
+            }
+            if self.config.submit_evidence {
+                if let Err(e) = self.submit_pending().await {
+                    warn!("⚠️ Failed to submit slashing evidence: {:#}", e);
+                }
+            }
+        }
+    }
+    
+    fn prune(&self) -> Result<()> {
+        let cutoff = (chrono::Utc::now().timestamp() as u64).saturating_sub(self.config.retained_days * 86_400);
+        let mut batch = self.storage.batch();
+        let mut pruned = 0;
+        for (key, evidence) in self.storage.scan::<SlashingEvidence>(EVIDENCE_NAMESPACE)? {
+            if evidence.captured_at < cutoff {
+                batch.delete(EVIDENCE_NAMESPACE, &key);
+                pruned += 1;
+            }
+        }
+        if pruned > 0 {
+            self.storage.commit(batch)?;
+            debug!("⚖️ Pruned {} expired slashing evidence bundles", pruned);
+        }
+        Ok(())
+    }
+    
+    async fn submit_pending(&self) -> Result<()> {
+        for (key, mut evidence) in self.storage.scan::<SlashingEvidence>(EVIDENCE_NAMESPACE)? {
+            if evidence.submitted.is_some() {
+                continue;
+            }
+            match self.submit(&evidence).await {
+                Ok(tx_hash) => {
+                    info!("⚖️ Submitted {:?} evidence against {:?}: {}", evidence.kind, evidence.offender, tx_hash);
+                    evidence.submitted = Some(tx_hash);
+                    self.storage.put(EVIDENCE_NAMESPACE, &key, &evidence)?;
+                }
+                // Left for the next sweep, until it expires
+                Err(e) => debug!("Evidence against {:?} not submitted: {:#}", evidence.offender, e),
+            }
+        }
+        Ok(())
+    }
+    
+    async fn submit(&self, evidence: &SlashingEvidence) -> Result<String> {
+        match evidence.kind {
+            EvidenceKind::Equivocation => {
+                let [first, second] = &evidence.messages[..] else {
+                    bail!("Equivocation evidence needs exactly two messages");
+                };
+                self.blockchain.submit_equivocation_evidence(
+                    evidence.offender,
+                    topic_hash(&first.topic),
+                    first.sequence,
+                    (first.payload_hash(), &first.signature),
+                    (second.payload_hash(), &second.signature),
+                ).await
+            }
+            EvidenceKind::InvalidPayload => {
+                let ipfs = self.ipfs.as_ref().context("Disputes need IPFS to pin their evidence")?;
+                let bundle = serde_json::to_vec(evidence)?;
+                let evidence_hash = keccak256(&bundle);
+                let cid = ipfs.add_file(bundle, &format!("evidence-{}.json", evidence.id)).await?;
+                self.blockchain.open_dispute(evidence.offender, evidence_hash, &cid).await
+            }
+        }
+    }
+    
+    /// Next sequence number for `topic`: the wall clock, or just past the last one if it stepped back
+    fn next_sequence(&self, topic: &str) -> Result<u64> {
+        let mut sequences = self.sequences.lock();
+        let last = match sequences.get(topic) {
+            Some(last) => *last,
+            None => self.storage.get::<u64>(SEQUENCE_NAMESPACE, topic)?.unwrap_or(0),
+        };
+        let next = now_millis().max(last + 1);
+        // Stored before signing, so a crash cannot leave a signed sequence number to be reused
+        self.storage.put(SEQUENCE_NAMESPACE, topic, &next)?;
+        sequences.insert(topic.to_string(), next);
+        Ok(next)
+    }
+}
+
+fn equivocation_id(offender: Address, topic: &str, sequence: u64) -> String {
+    format!("0x{}", hex::encode(keccak256(encode(&[
+        Token::Address(offender),
+        Token::FixedBytes(topic_hash(topic).to_vec()),
+        Token::Uint(U256::from(sequence)),
+    ]))))
+}
+
+fn now_millis() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
+}
+
+/// `GET /gossip/evidence` lists captured slashing evidence, newest first
+pub fn admin_routes(auth: Arc<GossipAuth>) -> Router {
+    Router::new().route("/gossip/evidence", get(move || async move {
+        match auth.evidence() {
+            Ok(evidence) => Json(Report::new("gossip_evidence", evidence)).into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)).into_response(),
+        }
+    }))
+}
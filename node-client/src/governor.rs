@@ -0,0 +1,312 @@
+//! Resource governance: a dedicated worker pool for AI inference, DAG execution parallelism
+//! scaled by the power profile, and work tokens gating heavy background jobs on power, thermal
+//! and battery state
+
+use anyhow::Result;
+use parking_lot::RwLock;
+use prometheus::{IntCounterVec, Opts};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::{debug, info, warn};
+
+use crate::config::{WorkTokenConfig, WorkerPoolConfig};
+use crate::energy::PowerProfile;
+use crate::metrics::register_once;
+
+/// Conditions older than this are treated as unknown
+const CONDITIONS_MAX_AGE: Duration = Duration::from_secs(60);
+
+/// How many work items run at once: a fixed size, scaled down under power profiles
+pub struct Parallelism {
+    name: &'static str,
+    size: usize,
+    active_limit: AtomicUsize,
+}
+
+impl Parallelism {
+    fn new(name: &'static str, size: usize) -> Self {
+        Self {
+            name,
+            size,
+            active_limit: AtomicUsize::new(size),
+        }
+    }
+    
+    pub fn size(&self) -> usize {
+        self.size
+    }
+    
+    /// Number of work items to run at once under the current profile
+    pub fn get(&self) -> usize {
+        self.active_limit.load(Ordering::Relaxed)
+    }
+    
+    fn scale(&self, factor: f32) {
+        let limit = ((self.size as f32 * factor).round() as usize).clamp(1, self.size);
+        self.active_limit.store(limit, Ordering::Relaxed);
+        debug!("🔧 {} parallelism set to {}/{}", self.name, limit, self.size);
+    }
+}
+
+pub struct WorkerPool {
+    pool: Arc<rayon::ThreadPool>,
+    parallelism: Parallelism,
+}
+
+impl WorkerPool {
+    fn new(name: &'static str, size: usize, cores: Vec<usize>, pin: bool) -> Result<Self> {
+        let core_ids = if pin {
+            let available = core_affinity::get_core_ids().unwrap_or_default();
+            let selected: Vec<_> = available
+                .into_iter()
+                .filter(|core| cores.contains(&core.id))
+                .collect();
+            if selected.is_empty() {
+                warn!("⚠️ No matching cores for {} pool, running unpinned", name);
+            }
+            selected
+        } else {
+            Vec::new()
+        };
+        
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(size)
+            .thread_name(move |i| format!("dagshield-{}-{}", name, i))
+            .start_handler(move |i| {
+                if !core_ids.is_empty() {
+                    let core = core_ids[i % core_ids.len()];
+                    if !core_affinity::set_for_current(core) {
+                        warn!("Failed to pin {} worker {} to core {}", name, i, core.id);
+                    }
+                }
+            })
+            .build()?;
+        
+        Ok(Self {
+            pool: Arc::new(pool),
+            parallelism: Parallelism::new(name, size),
+        })
+    }
+    
+    /// Run a CPU-bound closure on this pool, waiting for it from a blocking task so no runtime
+    /// worker is held up
+    pub async fn install<F, R>(&self, f: F) -> Result<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let pool = Arc::clone(&self.pool);
+        Ok(tokio::task::spawn_blocking(move || pool.install(f)).await?)
+    }
+    
+    pub fn size(&self) -> usize {
+        self.parallelism.size()
+    }
+    
+    /// Number of work items the pool should be handed at once under the current profile
+    pub fn parallelism(&self) -> usize {
+        self.parallelism.get()
+    }
+}
+
+/// Heavy background jobs that must ask the governor before running
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WorkClass {
+    Backfill,
+    Compaction,
+    Benchmark,
+    FederatedLearning,
+    Backtest,
+}
+
+impl WorkClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkClass::Backfill => "backfill",
+            WorkClass::Compaction => "compaction",
+            WorkClass::Benchmark => "benchmark",
+            WorkClass::FederatedLearning => "federated_learning",
+            WorkClass::Backtest => "backtest",
+        }
+    }
+}
+
+/// Latest power, thermal and battery readings, reported by the energy monitor
+#[derive(Debug, Clone)]
+pub struct PowerConditions {
+    pub power_watts: f32,
+    pub power_limit_watts: f32,
+    pub temperature_celsius: f32,
+    pub battery_level_percent: Option<f32>,
+}
+
+/// Permission to run one heavy job; the slot is released when the token is dropped
+pub struct WorkToken {
+    class: WorkClass,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl WorkToken {
+    pub fn class(&self) -> WorkClass {
+        self.class
+    }
+}
+
+pub enum WorkDecision {
+    Granted(WorkToken),
+    Deferred { reason: String, retry_after: Duration },
+}
+
+pub struct ResourceGovernor {
+    inference: WorkerPool,
+    dag: Parallelism,
+    work_config: WorkTokenConfig,
+    work_permits: Arc<Semaphore>,
+    conditions: RwLock<Option<(PowerConditions, Instant)>>,
+    work_decisions: IntCounterVec,
+}
+
+impl ResourceGovernor {
+    pub fn new(config: &WorkerPoolConfig, work_config: &WorkTokenConfig) -> Result<Self> {
+        let cores = num_cpus();
+        let half = (cores / 2).max(1);
+        
+        let inference_threads = if config.inference_threads == 0 { half } else { config.inference_threads };
+        let dag_threads = if config.dag_threads == 0 { half } else { config.dag_threads };
+        
+        if inference_threads + dag_threads > cores {
+            warn!("⚠️ Inference threads and DAG workers ({} + {}) oversubscribe {} cores",
+                  inference_threads, dag_threads, cores);
+        }
+        
+        let inference = WorkerPool::new(
+            "inference",
+            inference_threads,
+            config.inference_cores.clone(),
+            config.pin_cores,
+        )?;
+        let dag = Parallelism::new("DAG", dag_threads);
+        
+        info!("🧵 {} inference threads (pinned: {}), {} DAG workers",
+              inference_threads, config.pin_cores, dag_threads);
+        
+        static WORK_DECISIONS: OnceLock<IntCounterVec> = OnceLock::new();
+        let work_decisions = register_once(&WORK_DECISIONS, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_work_token_decisions_total", "Heavy work token requests by class and decision"),
+                &["class", "decision"],
+            )
+        })?;
+        
+        Ok(Self {
+            inference,
+            dag,
+            work_config: work_config.clone(),
+            work_permits: Arc::new(Semaphore::new(work_config.max_concurrent.max(1))),
+            conditions: RwLock::new(None),
+            work_decisions,
+        })
+    }
+    
+    pub fn inference_pool(&self) -> &WorkerPool {
+        &self.inference
+    }
+    
+    /// DAG transactions executed at once, each on its own task
+    pub fn dag_parallelism(&self) -> &Parallelism {
+        &self.dag
+    }
+    
+    /// Scale inference and DAG parallelism to the CPU share allowed by a power profile
+    pub fn apply_power_profile(&self, profile: &PowerProfile) {
+        let factor = (profile.max_cpu_usage / 100.0).clamp(0.0, 1.0);
+        self.inference.parallelism.scale(factor);
+        self.dag.scale(factor);
+        
+        info!("⚙️ Worker parallelism scaled to {:.0}% for profile {}",
+              factor * 100.0, profile.profile_name);
+    }
+    
+    /// Halve DAG parallelism, never dropping below a single worker
+    pub fn reduce_dag_intensity(&self) {
+        let current = self.dag.get();
+        self.dag.scale((current as f32 / 2.0) / self.dag.size() as f32);
+    }
+    
+    pub fn update_conditions(&self, conditions: PowerConditions) {
+        *self.conditions.write() = Some((conditions, Instant::now()));
+    }
+    
+    /// Why heavy work should wait under the current conditions, if it should
+    fn deferral_reason(&self) -> Option<String> {
+        let guard = self.conditions.read();
+        let conditions = match guard.as_ref() {
+            Some((conditions, at)) if at.elapsed() <= CONDITIONS_MAX_AGE => conditions,
+            // Without fresh readings there is nothing to base a deferral on
+            _ => return None,
+        };
+        let config = &self.work_config;
+        
+        if conditions.temperature_celsius > config.max_temperature_celsius {
+            return Some(format!("temperature {:.1}°C above {:.1}°C",
+                                conditions.temperature_celsius, config.max_temperature_celsius));
+        }
+        if let Some(battery) = conditions.battery_level_percent {
+            if battery < config.min_battery_percent {
+                return Some(format!("battery at {:.0}% below {:.0}%", battery, config.min_battery_percent));
+            }
+        }
+        let power_budget = conditions.power_limit_watts * config.max_power_fraction;
+        if conditions.power_watts > power_budget {
+            return Some(format!("power draw {:.1}W above {:.1}W", conditions.power_watts, power_budget));
+        }
+        
+        None
+    }
+    
+    /// Ask for a heavy work slot without waiting
+    pub fn try_acquire_work(&self, class: WorkClass) -> WorkDecision {
+        let retry_after = Duration::from_secs(self.work_config.retry_after_secs.max(1));
+        
+        let decision = if let Some(reason) = self.deferral_reason() {
+            WorkDecision::Deferred { reason, retry_after }
+        } else {
+            match Arc::clone(&self.work_permits).try_acquire_owned() {
+                Ok(permit) => WorkDecision::Granted(WorkToken { class, _permit: permit }),
+                Err(_) => WorkDecision::Deferred {
+                    reason: format!("{} heavy jobs already running", self.work_config.max_concurrent),
+                    retry_after,
+                },
+            }
+        };
+        
+        let label = match &decision {
+            WorkDecision::Granted(_) => "granted",
+            WorkDecision::Deferred { reason, .. } => {
+                debug!("⏸️ Deferring {} work: {}", class.as_str(), reason);
+                "deferred"
+            }
+        };
+        self.work_decisions.with_label_values(&[class.as_str(), label]).inc();
+        
+        decision
+    }
+    
+    /// Wait until a heavy work slot is granted
+    pub async fn acquire_work(&self, class: WorkClass) -> WorkToken {
+        loop {
+            match self.try_acquire_work(class) {
+                WorkDecision::Granted(token) => return token,
+                WorkDecision::Deferred { retry_after, .. } => tokio::time::sleep(retry_after).await,
+            }
+        }
+    }
+}
+
+fn num_cpus() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
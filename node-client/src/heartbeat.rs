@@ -0,0 +1,110 @@
+//! Adaptive main-loop cadence
+//!
+//! Every heartbeat reports how many transactions it scored and how many it flagged. Their rates
+//! are tracked twice, as a fast average of the last few heartbeats and a slow long-run one. A
+//! fast rate well above its long-run average is a spike and halves the interval; a heartbeat
+//! with nothing to do stretches it; steady load moves it back towards `heartbeat_interval_secs`.
+
+use anyhow::Result;
+use prometheus::Gauge;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::debug;
+
+use crate::config::NodeSettings;
+use crate::metrics::register_once;
+
+/// Weight of the newest heartbeat in the fast averages
+const FAST_SMOOTHING: f64 = 0.5;
+/// Weight of the newest heartbeat in the long-run averages
+const SLOW_SMOOTHING: f64 = 0.05;
+/// Growth of the interval per idle heartbeat
+const IDLE_STRETCH: f64 = 1.5;
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Rate {
+    fast: f64,
+    slow: f64,
+}
+
+impl Rate {
+    /// Fold in a new per-second rate; true when it spikes above the long-run average
+    fn observe(&mut self, rate: f64, spike_factor: f64) -> bool {
+        self.fast = FAST_SMOOTHING * rate + (1.0 - FAST_SMOOTHING) * self.fast;
+        // Judged against the average before this heartbeat, so a burst can't hide in it
+        let spike = rate > 0.0 && self.fast > spike_factor * self.slow;
+        self.slow = SLOW_SMOOTHING * rate + (1.0 - SLOW_SMOOTHING) * self.slow;
+        spike
+    }
+}
+
+pub struct HeartbeatPacer {
+    enabled: bool,
+    base: Duration,
+    min: Duration,
+    max: Duration,
+    spike_factor: f64,
+    current: Duration,
+    ingestion: Rate,
+    threats: Rate,
+    interval_gauge: Gauge,
+}
+
+impl HeartbeatPacer {
+    pub fn new(settings: &NodeSettings) -> Result<Self> {
+        let adaptive = &settings.adaptive_heartbeat;
+        let base = Duration::from_secs(settings.heartbeat_interval_secs.max(1));
+        let min = Duration::from_secs(adaptive.min_interval_secs.max(1));
+        let max = Duration::from_secs(adaptive.max_interval_secs).max(min);
+        
+        static INTERVAL_GAUGE: OnceLock<Gauge> = OnceLock::new();
+        let interval_gauge = register_once(&INTERVAL_GAUGE, || {
+            Gauge::new("dagshield_heartbeat_interval_seconds", "Current main-loop heartbeat interval")
+        })?;
+        interval_gauge.set(base.as_secs_f64());
+        
+        Ok(Self {
+            enabled: adaptive.enabled,
+            base,
+            min,
+            max,
+            spike_factor: adaptive.spike_factor,
+            current: base,
+            ingestion: Rate::default(),
+            threats: Rate::default(),
+            interval_gauge,
+        })
+    }
+    
+    pub fn interval(&self) -> Duration {
+        self.current
+    }
+    
+    /// Account for one heartbeat's work over `elapsed` and return the interval until the next
+    pub fn observe(&mut self, elapsed: Duration, transactions: usize, threats: usize) -> Duration {
+        if !self.enabled {
+            return self.current;
+        }
+        
+        let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+        let ingestion_spike = self.ingestion.observe(transactions as f64 / secs, self.spike_factor);
+        let threat_spike = self.threats.observe(threats as f64 / secs, self.spike_factor);
+        
+        let next = if ingestion_spike || threat_spike {
+            self.current / 2
+        } else if transactions == 0 {
+            self.current.mul_f64(IDLE_STRETCH)
+        } else {
+            (self.current + self.base) / 2
+        };
+        let next = next.clamp(self.min, self.max);
+        
+        if next != self.current {
+            debug!("💓 Heartbeat interval {:?} -> {:?} ({} transactions, {} threats in {:?})",
+                   self.current, next, transactions, threats, elapsed);
+            self.current = next;
+            self.interval_gauge.set(next.as_secs_f64());
+        }
+        self.current
+    }
+}
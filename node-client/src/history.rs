@@ -0,0 +1,119 @@
+//! Per-address threat report history and risk summaries for the query API
+
+use anyhow::Result;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::alert_cache::VerifiedAlert;
+use crate::node::{ReportPipeline, ThreatReportRecord, REPORT_PIPELINE_NAMESPACE, THREAT_REPORT_NAMESPACE};
+use crate::storage::NodeStorage;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportHistoryEntry {
+    #[serde(flatten)]
+    pub record: ThreatReportRecord,
+    /// Produced by a detection pipeline that has since been replaced
+    pub stale: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressRisk {
+    pub address: String,
+    pub risk_score: u32,
+    pub reports: usize,
+    pub stale_reports: usize,
+    pub verified_alerts: usize,
+    pub last_reported_at: Option<u64>,
+}
+
+/// Threat reports indexed by target address, loaded from storage and kept current as reports land
+pub struct ReportHistory {
+    storage: Arc<NodeStorage>,
+    /// Keyed by lowercase target address, oldest report first
+    by_address: DashMap<String, Vec<ThreatReportRecord>>,
+}
+
+impl ReportHistory {
+    pub fn new(storage: Arc<NodeStorage>) -> Result<Self> {
+        let history = Self {
+            storage,
+            by_address: DashMap::new(),
+        };
+        history.reload()?;
+        Ok(history)
+    }
+    
+    /// Rebuild the index from storage, e.g. after a replica sync replaced the reports
+    pub fn reload(&self) -> Result<()> {
+        let mut records = self.storage.scan::<ThreatReportRecord>(THREAT_REPORT_NAMESPACE)?;
+        records.sort_by_key(|(_, record)| record.reported_at);
+        
+        let mut rebuilt: HashMap<String, Vec<ThreatReportRecord>> = HashMap::new();
+        for (_, record) in records {
+            rebuilt.entry(record.target_address.to_lowercase()).or_default().push(record);
+        }
+        // Swapped per address, so concurrent lookups never see an empty index
+        self.by_address.retain(|address, _| rebuilt.contains_key(address));
+        for (address, records) in rebuilt {
+            self.by_address.insert(address, records);
+        }
+        info!("🗃️ Report history indexed for {} addresses", self.by_address.len());
+        Ok(())
+    }
+    
+    pub fn record(&self, record: ThreatReportRecord) {
+        self.by_address
+            .entry(record.target_address.to_lowercase())
+            .or_default()
+            .push(record);
+    }
+    
+    /// Lowercase addresses with at least one report
+    pub fn addresses(&self) -> Vec<String> {
+        self.by_address.iter().map(|entry| entry.key().clone()).collect()
+    }
+    
+    /// Reports against an address, newest first
+    pub fn reports(&self, address: &str) -> Result<Vec<ReportHistoryEntry>> {
+        let records = match self.by_address.get(&address.to_lowercase()) {
+            Some(records) => records.clone(),
+            None => return Ok(Vec::new()),
+        };
+        
+        let mut entries = Vec::with_capacity(records.len());
+        for record in records.into_iter().rev() {
+            let pipeline: Option<ReportPipeline> = self.storage.get(REPORT_PIPELINE_NAMESPACE, &record.tx_hash)?;
+            entries.push(ReportHistoryEntry {
+                stale: pipeline.is_some_and(|p| p.stale),
+                record,
+            });
+        }
+        Ok(entries)
+    }
+    
+    /// Combine current reports and network-verified alerts into one score out of 100
+    pub fn risk(&self, address: &str, alerts: &[VerifiedAlert]) -> Result<AddressRisk> {
+        let reports = self.reports(address)?;
+        let current = reports.iter().filter(|entry| !entry.stale);
+        
+        let risk_score = current
+            .clone()
+            .map(|entry| entry.record.confidence)
+            .chain(alerts.iter().map(|alert| alert.confidence))
+            .max()
+            .unwrap_or(0)
+            .min(100);
+        
+        Ok(AddressRisk {
+            address: address.to_string(),
+            risk_score,
+            reports: current.count(),
+            stale_reports: reports.iter().filter(|entry| entry.stale).count(),
+            verified_alerts: alerts.len(),
+            last_reported_at: reports.first().map(|entry| entry.record.reported_at),
+        })
+    }
+}
@@ -0,0 +1,268 @@
+//! Confirmed-block ingestion, so live chain traffic flows through the DAG
+//!
+//! Blocks `confirmations` behind the head are read from `BlockchainClient` in order, and each
+//! transaction enters the DAG with its receipt logs and, when `traces` is set, the addresses its
+//! internal calls touched. A transaction depends on the latest earlier one in the same block
+//! touching an address it touches, so the DAG executes the block's unrelated transactions in
+//! parallel and the related ones in block order. Pending transactions come in separately through
+//! the mempool scanner; a mined transaction it already scanned is counted as a duplicate.
+//!
+//! The last ingested block is kept as a cursor, so a restart resumes after it. The DAG is given
+//! time to drain while it holds more than `max_pending` unprocessed transactions.
+
+use anyhow::{bail, Context, Result};
+use ethers::types::{Action, Block, Res, Trace, Transaction as ChainTransaction, TransactionReceipt, H256};
+use prometheus::{IntCounterVec, IntGauge, Opts};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::blockchain::BlockchainClient;
+use crate::config::IngestConfig;
+use crate::cursor::EventCursor;
+use crate::dag::{Backpressure, DAGProcessor, StorageSlot, Transaction, TransactionLog};
+use crate::metrics::register_once;
+use crate::storage::NodeStorage;
+
+const CURSOR_LISTENER: &str = "block_ingest";
+
+pub struct BlockIngester {
+    config: IngestConfig,
+    blockchain: Arc<BlockchainClient>,
+    dag_processor: Arc<DAGProcessor>,
+    storage: Arc<NodeStorage>,
+    transactions: IntCounterVec,
+    ingested_block: IntGauge,
+}
+
+impl BlockIngester {
+    pub fn new(
+        config: &IngestConfig,
+        blockchain: Arc<BlockchainClient>,
+        dag_processor: Arc<DAGProcessor>,
+        storage: Arc<NodeStorage>,
+    ) -> Result<Self> {
+        if config.max_blocks_per_poll == 0 {
+            bail!("ingest.max_blocks_per_poll must be positive");
+        }
+        static TRANSACTIONS: OnceLock<IntCounterVec> = OnceLock::new();
+        static INGESTED_BLOCK: OnceLock<IntGauge> = OnceLock::new();
+        let transactions = register_once(&TRANSACTIONS, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_ingested_transactions_total", "Confirmed transactions read by block ingestion, by outcome"),
+                &["outcome"],
+            )
+        })?;
+        let ingested_block = register_once(&INGESTED_BLOCK, || {
+            IntGauge::new("dagshield_ingested_block", "Last block fully ingested into the DAG")
+        })?;
+        
+        Ok(Self {
+            config: config.clone(),
+            blockchain,
+            dag_processor,
+            storage,
+            transactions,
+            ingested_block,
+        })
+    }
+    
+    /// Follow the chain until the task is aborted
+    pub async fn start(&self) -> Result<()> {
+        let chain_id = self.blockchain.chain_id();
+        let start_block = match self.config.start_block {
+            0 => self.blockchain.get_block_number().await?.saturating_sub(self.config.confirmations),
+            start => start,
+        };
+        let mut cursor = EventCursor::load(Arc::clone(&self.storage), CURSOR_LISTENER, chain_id, start_block)?;
+        info!("📥 Ingesting blocks on chain {} {} confirmations behind the head{}", chain_id,
+              self.config.confirmations, if self.config.traces { ", with traces" } else { "" });
+        
+        let mut interval = tokio::time::interval(Duration::from_millis(self.config.poll_interval_ms.max(100)));
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.catch_up(&mut cursor).await {
+                warn!("⚠️ Block ingestion stalled: {:#}", e);
+            }
+        }
+    }
+    
+    /// Ingest up to `max_blocks_per_poll` confirmed blocks past the cursor
+    async fn catch_up(&self, cursor: &mut EventCursor) -> Result<()> {
+        let confirmed = self.blockchain.get_block_number().await?.saturating_sub(self.config.confirmations);
+        // Ingested blocks are advanced to in full, so the cursor's block is done
+        let mut next = cursor.position().map_or(cursor.next_block(), |position| position.block_number + 1);
+        let mut ingested = 0;
+        while next <= confirmed && ingested < self.config.max_blocks_per_poll {
+            if self.dag_processor.pending() > self.config.max_pending {
+                debug!("DAG backlog above {}, pausing ingestion at block {}", self.config.max_pending, next);
+                return Ok(());
+            }
+            self.ingest_block(next).await?;
+            cursor.advance_to_block(next)?;
+            self.ingested_block.set(next as i64);
+            next += 1;
+            ingested += 1;
+        }
+        Ok(())
+    }
+    
+    async fn ingest_block(&self, number: u64) -> Result<()> {
+        let Some(block) = self.blockchain.get_block_with_txs(number).await? else {
+            bail!("Block {} not found", number);
+        };
+        let receipts: HashMap<H256, TransactionReceipt> = self.blockchain
+            .get_block_receipts(number)
+            .await?
+            .into_iter()
+            .map(|receipt| (receipt.transaction_hash, receipt))
+            .collect();
+        let traced = if self.config.traces {
+            match self.blockchain.trace_block(number).await {
+                Ok(traces) => traced_addresses(&traces),
+                // Dependencies fall back to senders, recipients and log emitters
+                Err(e) => {
+                    warn!("⚠️ Traces unavailable for block {}: {:#}", number, e);
+                    HashMap::new()
+                }
+            }
+        } else {
+            HashMap::new()
+        };
+        
+        let transactions = self.to_transactions(&block, &receipts, &traced);
+        let count = transactions.len();
+        for transaction in transactions {
+            let id = transaction.id.clone();
+            // Scanned from the mempool before it was mined
+            if self.dag_processor.contains(&id) {
+                self.transactions.with_label_values(&["duplicate"]).inc();
+                continue;
+            }
+            // Retried with the whole block, e.g. while ingestion is paused for maintenance; the
+            // transactions already added are duplicates then
+            loop {
+                match self.dag_processor.add_transaction(transaction.clone()).await {
+                    Ok(()) => break,
+                    // Confirmed blocks can wait for the DAG to drain; none of them is skipped
+                    Err(e) if e.is::<Backpressure>() => {
+                        self.transactions.with_label_values(&["backpressure"]).inc();
+                        self.dag_processor.wait_for_capacity().await;
+                    }
+                    Err(e) => return Err(e).with_context(|| format!("Block {} not ingested at {}", number, id)),
+                }
+            }
+            self.transactions.with_label_values(&["ingested"]).inc();
+        }
+        debug!("📥 Ingested block {} ({} transactions)", number, count);
+        Ok(())
+    }
+    
+    /// The block's transactions as DAG transactions, each depending on the latest earlier one
+    /// touching an address it touches
+    fn to_transactions(
+        &self,
+        block: &Block<ChainTransaction>,
+        receipts: &HashMap<H256, TransactionReceipt>,
+        traced: &HashMap<H256, HashSet<String>>,
+    ) -> Vec<Transaction> {
+        let timestamp = block.timestamp.as_u64();
+        let mut last_touch: HashMap<String, String> = HashMap::new();
+        let mut transactions = Vec::with_capacity(block.transactions.len());
+        
+        for tx in &block.transactions {
+            let receipt = receipts.get(&tx.hash);
+            if self.config.skip_plain_transfers && tx.input.is_empty() && tx.to.is_some() {
+                self.transactions.with_label_values(&["skipped"]).inc();
+                continue;
+            }
+            
+            let id = format!("{:?}", tx.hash);
+            // Contract creations are judged by the contract they deploy
+            let target = tx.to
+                .or(receipt.and_then(|receipt| receipt.contract_address))
+                .map(|address| format!("{:?}", address))
+                .unwrap_or_default();
+            let logs: Vec<TransactionLog> = receipt
+                .map(|receipt| receipt.logs
+                    .iter()
+                    .map(|log| TransactionLog {
+                        address: format!("{:?}", log.address),
+                        topics: log.topics.iter().map(|topic| format!("{:?}", topic)).collect(),
+                        data: log.data.to_vec(),
+                    })
+                    .collect())
+                .unwrap_or_default();
+            
+            // Ordered, so the same block always yields the same dependencies
+            let touched: BTreeSet<String> = [format!("{:?}", tx.from), target.clone()]
+                .into_iter()
+                .chain(logs.iter().map(|log| log.address.clone()))
+                .chain(traced.get(&tx.hash).into_iter().flatten().cloned())
+                .filter(|address| !address.is_empty())
+                .collect();
+            
+            let mut dependencies: Vec<String> = Vec::new();
+            for address in &touched {
+                if let Some(previous) = last_touch.get(address) {
+                    if !dependencies.contains(previous) && dependencies.len() < self.config.max_dependencies {
+                        dependencies.push(previous.clone());
+                    }
+                }
+            }
+            for address in touched {
+                last_touch.insert(address, id.clone());
+            }
+            
+            transactions.push(Transaction {
+                id,
+                from: format!("{:?}", tx.from),
+                to: target.clone(),
+                target_address: target,
+                chain_id: tx.chain_id.map_or(self.blockchain.chain_id(), |chain_id| chain_id.as_u64()),
+                data: tx.input.to_vec(),
+                timestamp,
+                dependencies,
+                blob_versioned_hashes: vec![],
+                value: tx.value,
+                logs,
+                origin: None,
+                nonce: Some(tx.nonce.as_u64()),
+                storage_slots: tx.access_list.as_ref().map(StorageSlot::from_access_list).unwrap_or_default(),
+                priority_fee: tx.max_priority_fee_per_gas.or(tx.gas_price).unwrap_or_default(),
+                peer_synced: false,
+            });
+        }
+        transactions
+    }
+}
+
+/// Addresses each transaction's internal calls touched, by transaction hash
+fn traced_addresses(traces: &[Trace]) -> HashMap<H256, HashSet<String>> {
+    let mut touched: HashMap<H256, HashSet<String>> = HashMap::new();
+    for trace in traces {
+        let Some(hash) = trace.transaction_hash else {
+            continue;
+        };
+        let addresses = touched.entry(hash).or_default();
+        match &trace.action {
+            Action::Call(call) => {
+                addresses.insert(format!("{:?}", call.from));
+                addresses.insert(format!("{:?}", call.to));
+            }
+            Action::Create(create) => {
+                addresses.insert(format!("{:?}", create.from));
+            }
+            Action::Suicide(suicide) => {
+                addresses.insert(format!("{:?}", suicide.address));
+                addresses.insert(format!("{:?}", suicide.refund_address));
+            }
+            _ => {}
+        }
+        if let Some(Res::Create(created)) = &trace.result {
+            addresses.insert(format!("{:?}", created.address));
+        }
+    }
+    touched
+}
@@ -0,0 +1,289 @@
+//! IPFS integration: pinning evidence bundles and model artifacts, fetching peers' evidence
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use ethers::utils::hex;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::ai::ThreatDetectionResult;
+use crate::config::IpfsConfig;
+use crate::dag::Transaction;
+use crate::receipts::DetectionReceipt;
+use crate::storage::NodeStorage;
+
+const MODEL_METADATA_NAMESPACE: &str = "model_metadata";
+
+/// CIDv1 prefix for a raw block hashed with sha2-256
+const RAW_SHA256_CID_PREFIX: [u8; 4] = [0x01, 0x55, 0x12, 0x20];
+
+/// Everything a peer needs to re-check one of our threat reports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvidenceBundle {
+    pub version: u32,
+    pub node_id: String,
+    pub transaction: Transaction,
+    pub verdict: ThreatDetectionResult,
+    pub detected_at: u64,
+    /// Signed first-seen receipts for the detection, earliest first
+    #[serde(default)]
+    pub receipts: Vec<DetectionReceipt>,
+}
+
+/// Content address of a model artifact as distributed over IPFS
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelMetadata {
+    pub path: String,
+    pub sha256: String,
+    pub cid: String,
+    pub size: u64,
+    pub pinned_at: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct AddResponse {
+    #[serde(rename = "Hash")]
+    hash: String,
+}
+
+/// Client for a Kubo-compatible RPC API (local or embedded node) with a read-only gateway fallback.
+///
+/// Evidence is added as a single raw sha2-256 block, so its CID and the 32-byte digest
+/// stored on-chain are interchangeable (see [`cid_from_digest`]).
+pub struct IpfsClient {
+    config: IpfsConfig,
+    http: reqwest::Client,
+}
+
+impl IpfsClient {
+    pub fn new(config: &IpfsConfig) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+            .build()?;
+        
+        if config.api_url.is_empty() {
+            info!("🌐 IPFS in gateway-only mode via {} (pinning disabled)", config.gateway_url);
+        } else {
+            info!("🌐 IPFS node API at {}", config.api_url);
+        }
+        
+        Ok(Self {
+            config: config.clone(),
+            http,
+        })
+    }
+    
+    fn can_pin(&self) -> bool {
+        !self.config.api_url.is_empty()
+    }
+    
+    /// Add content as a single raw block, so its CID is derivable from its sha2-256 digest
+    pub async fn add_block(&self, content: Vec<u8>, name: &str) -> Result<String> {
+        if content.len() > self.config.max_block_bytes {
+            bail!("Content of {} bytes exceeds the single-block limit of {} bytes",
+                  content.len(), self.config.max_block_bytes);
+        }
+        
+        let expected = cid_from_digest(Sha256::digest(&content).into());
+        let cid = self.add(content, name, &format!("&chunker=size-{}", self.config.max_block_bytes)).await?;
+        
+        // The node must agree with our content address, otherwise on-chain digests would not resolve
+        if cid != expected {
+            bail!("IPFS node returned CID {} but content hashes to {}", cid, expected);
+        }
+        
+        Ok(cid)
+    }
+    
+    /// Add arbitrarily large content (chunked), returning the root CID
+    pub async fn add_file(&self, content: Vec<u8>, name: &str) -> Result<String> {
+        self.add(content, name, "").await
+    }
+    
+    async fn add(&self, content: Vec<u8>, name: &str, extra_params: &str) -> Result<String> {
+        if !self.can_pin() {
+            bail!("IPFS node API not configured; cannot add content");
+        }
+        
+        let part = reqwest::multipart::Part::bytes(content).file_name(name.to_string());
+        let form = reqwest::multipart::Form::new().part("file", part);
+        
+        let url = format!(
+            "{}/api/v0/add?cid-version=1&raw-leaves=true&hash=sha2-256&pin={}{}",
+            self.config.api_url.trim_end_matches('/'),
+            self.config.pin,
+            extra_params,
+        );
+        let response: AddResponse = self.http
+            .post(&url)
+            .multipart(form)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        
+        debug!("📌 Added {} to IPFS as {}", name, response.hash);
+        Ok(response.hash)
+    }
+    
+    /// Pin content that already exists on the network, e.g. a peer's evidence
+    pub async fn pin(&self, cid: &str) -> Result<()> {
+        if !self.can_pin() {
+            return Ok(());
+        }
+        
+        let url = format!("{}/api/v0/pin/add?arg={}", self.config.api_url.trim_end_matches('/'), cid);
+        self.http.post(&url).send().await?.error_for_status()?;
+        Ok(())
+    }
+    
+    /// Release a pin, e.g. on evidence past its retention
+    pub async fn unpin(&self, cid: &str) -> Result<()> {
+        if !self.can_pin() {
+            return Ok(());
+        }
+        
+        let url = format!("{}/api/v0/pin/rm?arg={}", self.config.api_url.trim_end_matches('/'), cid);
+        self.http.post(&url).send().await?.error_for_status()?;
+        Ok(())
+    }
+    
+    /// Fetch a single-block object by CID and check it against the digest the CID commits to
+    pub async fn fetch(&self, cid: &str) -> Result<Vec<u8>> {
+        let digest = digest_from_cid(cid)?;
+        
+        let request = if self.can_pin() {
+            self.http.post(format!("{}/api/v0/cat?arg={}", self.config.api_url.trim_end_matches('/'), cid))
+        } else {
+            self.http.get(format!("{}/ipfs/{}", self.config.gateway_url.trim_end_matches('/'), cid))
+        };
+        
+        let content = request.send().await?.error_for_status()?.bytes().await?.to_vec();
+        if <[u8; 32]>::from(Sha256::digest(&content)) != digest {
+            bail!("Content fetched for {} does not match its CID", cid);
+        }
+        
+        Ok(content)
+    }
+    
+    pub async fn pin_evidence(&self, bundle: &EvidenceBundle) -> Result<String> {
+        let content = serde_json::to_vec(bundle)?;
+        self.add_block(content, &format!("evidence-{}.json", bundle.transaction.id)).await
+    }
+    
+    pub async fn fetch_evidence(&self, cid: &str) -> Result<EvidenceBundle> {
+        let content = self.fetch(cid).await?;
+        serde_json::from_slice(&content).context("Malformed evidence bundle")
+    }
+    
+    /// Pin a model file and record its CID in the model metadata
+    pub async fn pin_model(&self, path: &str, storage: &NodeStorage) -> Result<ModelMetadata> {
+        let content = tokio::fs::read(path)
+            .await
+            .with_context(|| format!("Failed to read model {}", path))?;
+        let sha256 = hex::encode(Sha256::digest(&content));
+        
+        if let Some(existing) = storage.get::<ModelMetadata>(MODEL_METADATA_NAMESPACE, path)? {
+            if existing.sha256 == sha256 {
+                debug!("📌 Model {} already pinned as {}", path, existing.cid);
+                return Ok(existing);
+            }
+        }
+        
+        let size = content.len() as u64;
+        let file_name = std::path::Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "model.onnx".to_string());
+        let cid = self.add_file(content, &file_name).await?;
+        
+        let metadata = ModelMetadata {
+            path: path.to_string(),
+            sha256,
+            cid,
+            size,
+            pinned_at: chrono::Utc::now().timestamp() as u64,
+        };
+        storage.put(MODEL_METADATA_NAMESPACE, path, &metadata)?;
+        
+        info!("📌 Model {} pinned as {}", path, metadata.cid);
+        Ok(metadata)
+    }
+}
+
+/// Pin a model in the background; distribution is best-effort and never blocks startup
+pub fn spawn_model_pin(ipfs: Arc<IpfsClient>, storage: Arc<NodeStorage>, path: String) {
+    tokio::spawn(async move {
+        if let Err(e) = ipfs.pin_model(&path, &storage).await {
+            warn!("Failed to pin model {} to IPFS: {}", path, e);
+        }
+    });
+}
+
+/// CIDv1 (raw, sha2-256) for a 32-byte digest, in the default base32 multibase form
+pub fn cid_from_digest(digest: [u8; 32]) -> String {
+    let mut bytes = RAW_SHA256_CID_PREFIX.to_vec();
+    bytes.extend_from_slice(&digest);
+    format!("b{}", base32_encode(&bytes))
+}
+
+/// Digest committed to by a raw sha2-256 CIDv1
+pub fn digest_from_cid(cid: &str) -> Result<[u8; 32]> {
+    let encoded = cid
+        .strip_prefix('b')
+        .ok_or_else(|| anyhow!("Unsupported CID encoding: {}", cid))?;
+    let bytes = base32_decode(encoded).ok_or_else(|| anyhow!("Invalid base32 in CID {}", cid))?;
+    
+    if bytes.len() != 36 || bytes[..4] != RAW_SHA256_CID_PREFIX {
+        bail!("CID {} is not a raw sha2-256 CIDv1", cid);
+    }
+    
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(&bytes[4..]);
+    Ok(digest)
+}
+
+const BASE32_ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity((data.len() * 8).div_ceil(5));
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    
+    out
+}
+
+fn base32_decode(encoded: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(encoded.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    
+    for c in encoded.bytes() {
+        let value = BASE32_ALPHABET.iter().position(|&a| a == c.to_ascii_lowercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    
+    Some(out)
+}
@@ -0,0 +1,160 @@
+//! Where the node key comes from
+//!
+//! `blockchain.private_key` holds the key in plain hex. Alternatively `blockchain.key` names an
+//! encrypted JSON keystore, unlocked with the passphrase in `password_env` or one typed at the
+//! terminal, or a BIP-39 mnemonic in `mnemonic_env` with the derivation path to use. Keystores
+//! are unlocked once per process, however many components ask for the wallet.
+//!
+//! `dagshield-node keygen` creates a keystore for a fresh key, optionally derived from a new
+//! mnemonic shown once for the operator to write down.
+
+use anyhow::{anyhow, bail, Context, Result};
+use ethers::signers::coins_bip39::{English, Mnemonic};
+use ethers::signers::{LocalWallet, MnemonicBuilder, Signer};
+use ethers::types::Address;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::info;
+
+use crate::config::{BlockchainConfig, KeySource};
+
+pub const DEFAULT_DERIVATION_PATH: &str = "m/44'/60'/0'/0/0";
+pub const DEFAULT_PASSWORD_ENV: &str = "DAGSHIELD_KEYSTORE_PASSWORD";
+const MNEMONIC_WORDS: usize = 24;
+
+/// Unlocked keystores by path, so the passphrase is asked for once
+static UNLOCKED: OnceLock<Mutex<HashMap<PathBuf, LocalWallet>>> = OnceLock::new();
+
+/// The node wallet, from `private_key` or `key`, without a chain ID
+pub fn load_wallet(config: &BlockchainConfig) -> Result<LocalWallet> {
+    match (&config.key, config.private_key.trim()) {
+        (None, "") => bail!("Set blockchain.private_key or blockchain.key"),
+        (None, private_key) => private_key
+            .trim_start_matches("0x")
+            .parse::<LocalWallet>()
+            .map_err(|e| anyhow!("blockchain.private_key is not a valid private key: {}", e)),
+        (Some(_), private_key) if !private_key.is_empty() => {
+            bail!("Set only one of blockchain.private_key and blockchain.key")
+        }
+        (Some(KeySource::Keystore { path, password_env }), _) => unlock_keystore(Path::new(path), password_env),
+        (Some(KeySource::Mnemonic { mnemonic_env, derivation_path }), _) => {
+            let phrase = std::env::var(mnemonic_env)
+                .map_err(|_| anyhow!("Mnemonic variable {} is not set", mnemonic_env))?;
+            derive(phrase.trim(), derivation_path)
+        }
+    }
+}
+
+fn unlock_keystore(path: &Path, password_env: &str) -> Result<LocalWallet> {
+    let unlocked = UNLOCKED.get_or_init(Default::default);
+    if let Some(wallet) = unlocked.lock().get(path) {
+        return Ok(wallet.clone());
+    }
+    
+    let password = match std::env::var(password_env) {
+        Ok(password) => password,
+        Err(_) => prompt(&format!("Passphrase for {}: ", path.display()), password_env)?,
+    };
+    let wallet = LocalWallet::decrypt_keystore(path, password)
+        .with_context(|| format!("Failed to unlock keystore {}", path.display()))?;
+    info!("🔓 Unlocked keystore {} for {:?}", path.display(), wallet.address());
+    unlocked.lock().insert(path.to_path_buf(), wallet.clone());
+    Ok(wallet)
+}
+
+fn derive(phrase: &str, derivation_path: &str) -> Result<LocalWallet> {
+    Ok(MnemonicBuilder::<English>::default()
+        .phrase(phrase)
+        .derivation_path(derivation_path)?
+        .build()?)
+}
+
+fn prompt(message: &str, password_env: &str) -> Result<String> {
+    if !std::io::stdin().is_terminal() {
+        bail!("No terminal to ask for the passphrase on; set {}", password_env);
+    }
+    Ok(rpassword::prompt_password(message)?)
+}
+
+pub struct KeygenOptions {
+    /// Directory the keystore is written to
+    pub dir: String,
+    /// Derive the key from a new mnemonic rather than generating it directly
+    pub mnemonic: bool,
+    pub derivation_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratedKey {
+    pub address: Address,
+    pub keystore_path: String,
+    /// The path the key was derived at, when it came from a mnemonic
+    #[serde(default)]
+    pub derivation_path: Option<String>,
+}
+
+/// Create a key and store it encrypted in `options.dir`; the new mnemonic, if any, is returned
+/// separately so callers show it once rather than report it
+pub fn keygen(options: &KeygenOptions) -> Result<(GeneratedKey, Option<String>)> {
+    let mut rng = ethers::core::rand::thread_rng();
+    let (wallet, phrase) = if options.mnemonic {
+        let phrase = Mnemonic::<English>::new_with_count(&mut rng, MNEMONIC_WORDS)?.to_phrase();
+        (derive(&phrase, &options.derivation_path)?, Some(phrase))
+    } else {
+        (LocalWallet::new(&mut rng), None)
+    };
+    
+    let password = new_password()?;
+    let dir = Path::new(&options.dir);
+    create_private_dir(dir)?;
+    let name = format!("{:?}.json", wallet.address());
+    let keystore_path = dir.join(&name);
+    if keystore_path.exists() {
+        bail!("{} already exists", keystore_path.display());
+    }
+    LocalWallet::encrypt_keystore(dir, &mut rng, wallet.signer().to_bytes(), password, Some(&name))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&keystore_path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    
+    let generated = GeneratedKey {
+        address: wallet.address(),
+        keystore_path: keystore_path.display().to_string(),
+        derivation_path: options.mnemonic.then(|| options.derivation_path.clone()),
+    };
+    Ok((generated, phrase))
+}
+
+/// From `DAGSHIELD_KEYSTORE_PASSWORD`, or typed twice at the terminal
+fn new_password() -> Result<String> {
+    let password = match std::env::var(DEFAULT_PASSWORD_ENV) {
+        Ok(password) => password,
+        Err(_) => {
+            let password = prompt("New keystore passphrase: ", DEFAULT_PASSWORD_ENV)?;
+            if prompt("Repeat passphrase: ", DEFAULT_PASSWORD_ENV)? != password {
+                bail!("Passphrases do not match");
+            }
+            password
+        }
+    };
+    if password.is_empty() {
+        bail!("The keystore passphrase must not be empty");
+    }
+    Ok(password)
+}
+
+fn create_private_dir(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
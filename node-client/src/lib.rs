@@ -0,0 +1,165 @@
+//! DAGShield node library
+//!
+//! Threat detection, DAG scheduling, contract access and storage for the DAGShield network,
+//! usable from any Rust service; the `dagshield-node` binary is a command line over it.
+//!
+//! The items re-exported at the crate root, and the modules shown in these docs, are the
+//! public API and follow semver. The remaining modules are public so the binary can reach
+//! them, but are hidden from the docs and may change in any release.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use dagshield_node::{NodeConfig, ResourceGovernor, ThreatDetector, Transaction};
+//!
+//! # async fn example(transaction: Transaction) -> anyhow::Result<()> {
+//! let config = NodeConfig::load("config.toml")?;
+//! let governor = Arc::new(ResourceGovernor::new(&config.workers, &config.work_tokens)?);
+//! let detector = ThreatDetector::new(&config.ai, governor).await?;
+//! let verdict = detector.detect_threat(&transaction).await?;
+//! println!("{} ({:.2})", verdict.threat_type, verdict.confidence);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Builds with the default `jemalloc` feature expect the host binary to install jemalloc as its
+//! global allocator; embedders using another allocator should disable default features.
+
+pub mod ai;
+pub mod blockchain;
+pub mod config;
+pub mod dag;
+pub mod executor;
+pub mod governor;
+pub mod storage;
+pub mod threat;
+
+#[doc(hidden)]
+pub mod alert_cache;
+#[doc(hidden)]
+pub mod archive;
+#[doc(hidden)]
+pub mod audit;
+#[doc(hidden)]
+pub mod backtest;
+#[doc(hidden)]
+pub mod challenge;
+#[doc(hidden)]
+pub mod commitment;
+#[doc(hidden)]
+pub mod chain_watch;
+#[doc(hidden)]
+pub mod chaos;
+#[doc(hidden)]
+pub mod contract_guard;
+#[doc(hidden)]
+pub mod crash;
+#[doc(hidden)]
+pub mod crosscheck;
+#[doc(hidden)]
+pub mod cursor;
+#[doc(hidden)]
+pub mod dag_sync;
+#[doc(hidden)]
+pub mod degradation;
+#[doc(hidden)]
+pub mod deploy;
+#[doc(hidden)]
+pub mod energy;
+#[doc(hidden)]
+pub mod finality;
+#[doc(hidden)]
+pub mod fixtures;
+#[doc(hidden)]
+pub mod fleet;
+#[doc(hidden)]
+pub mod gas_oracle;
+#[doc(hidden)]
+pub mod gossip;
+#[doc(hidden)]
+pub mod heartbeat;
+#[doc(hidden)]
+pub mod history;
+#[doc(hidden)]
+pub mod ingest;
+#[doc(hidden)]
+pub mod ipfs;
+#[doc(hidden)]
+pub mod keys;
+#[doc(hidden)]
+pub mod light_client;
+#[doc(hidden)]
+pub mod loadgen;
+#[doc(hidden)]
+pub mod maintenance;
+#[doc(hidden)]
+pub mod memory;
+#[doc(hidden)]
+pub mod mempool;
+#[doc(hidden)]
+pub mod metrics;
+#[doc(hidden)]
+pub mod mirror;
+#[doc(hidden)]
+pub mod network;
+#[doc(hidden)]
+pub mod node;
+#[doc(hidden)]
+pub mod pattern_feed;
+#[doc(hidden)]
+pub mod peers;
+#[doc(hidden)]
+pub mod penalty;
+#[doc(hidden)]
+pub mod preflight;
+#[doc(hidden)]
+pub mod provision;
+#[doc(hidden)]
+pub mod query;
+#[doc(hidden)]
+pub mod receipts;
+#[doc(hidden)]
+pub mod replica;
+#[doc(hidden)]
+pub mod report_queue;
+#[doc(hidden)]
+pub mod reporting_guard;
+#[doc(hidden)]
+pub mod retention;
+#[doc(hidden)]
+pub mod rewards;
+#[doc(hidden)]
+pub mod rollback;
+#[doc(hidden)]
+pub mod rpc_pool;
+#[doc(hidden)]
+pub mod sandbox;
+#[doc(hidden)]
+pub mod screening;
+#[doc(hidden)]
+pub mod service;
+#[doc(hidden)]
+pub mod signature;
+#[doc(hidden)]
+pub mod signer;
+#[doc(hidden)]
+pub mod stake;
+#[doc(hidden)]
+pub mod stats_report;
+#[doc(hidden)]
+pub mod status;
+#[doc(hidden)]
+pub mod tenant;
+#[doc(hidden)]
+pub mod updater;
+#[doc(hidden)]
+pub mod watchlist;
+
+pub use ai::{ThreatDetectionResult, ThreatDetector, ThreatLabel};
+pub use blockchain::BlockchainClient;
+pub use config::NodeConfig;
+pub use dag::{DAGProcessor, Transaction, TransactionLog};
+pub use executor::{ExecutionReceipt, TransactionExecutor};
+pub use governor::ResourceGovernor;
+pub use node::DAGShieldNode;
+pub use storage::{NodeStorage, StorageBatch};
+pub use threat::{Severity, ThreatClass};
@@ -0,0 +1,319 @@
+//! WebSocket service for wallets: risk lookups and push warnings for addresses a user is about
+//! to interact with
+//!
+//! A wallet connects to `/v1/wallet` and sends JSON messages tagged by `type`:
+//!
+//! - `check` / `subscribe` / `unsubscribe` with an `address`
+//! - `check_prefix` / `subscribe_prefix` / `unsubscribe_prefix` with a `prefix`, the leading hex
+//!   of `keccak256` of the lowercase address. This is the anonymous mode: the node answers for
+//!   every risky address sharing the prefix and the wallet picks its own out, so the node never
+//!   learns which address was meant.
+//!
+//! Answers are `verdict` or `prefix_verdicts`; warnings are pushed as `warning` whenever this
+//! node flags a transaction touching a subscribed address. Each connection is rate limited.
+
+use anyhow::Result;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::{extract::State, response::IntoResponse, routing::get, Router};
+use ethers::utils::{hex, keccak256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+use crate::ai::ThreatDetectionResult;
+use crate::alert_cache::VerifiedAlertCache;
+use crate::config::LightClientConfig;
+use crate::dag::Transaction;
+use crate::history::{AddressRisk, ReportHistory};
+use crate::threat::ThreatClass;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WalletRequest {
+    Check { address: String },
+    Subscribe { address: String },
+    Unsubscribe { address: String },
+    CheckPrefix { prefix: String },
+    SubscribePrefix { prefix: String },
+    UnsubscribePrefix { prefix: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WalletMessage {
+    Verdict { risk: AddressRisk },
+    /// Every known risky address whose hash starts with `prefix`
+    PrefixVerdicts { prefix: String, risks: Vec<AddressRisk> },
+    Warning(WalletWarning),
+    Error { message: String },
+}
+
+/// A flagged transaction touching `address`
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletWarning {
+    pub address: String,
+    pub transaction_id: String,
+    pub chain_id: u64,
+    pub threat_type: ThreatClass,
+    pub confidence: f32,
+    pub recommended_action: String,
+}
+
+struct LightClientState {
+    config: LightClientConfig,
+    history: Arc<ReportHistory>,
+    alert_cache: Option<Arc<VerifiedAlertCache>>,
+    warnings: broadcast::Sender<WalletWarning>,
+    connections: AtomicUsize,
+}
+
+pub struct LightClientServer {
+    state: Arc<LightClientState>,
+}
+
+impl LightClientServer {
+    pub fn new(config: &LightClientConfig, history: Arc<ReportHistory>, alert_cache: Option<Arc<VerifiedAlertCache>>) -> Self {
+        let (warnings, _) = broadcast::channel(1024);
+        Self {
+            state: Arc::new(LightClientState {
+                config: config.clone(),
+                history,
+                alert_cache,
+                warnings,
+                connections: AtomicUsize::new(0),
+            }),
+        }
+    }
+    
+    pub async fn start(&self) -> Result<()> {
+        let app = Router::new()
+            .route("/v1/wallet", get(upgrade))
+            .with_state(Arc::clone(&self.state));
+        
+        let addr = std::net::SocketAddr::from(([0, 0, 0, 0], self.state.config.listen_port));
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        info!("👛 Wallet light-client service listening on ws://{}/v1/wallet", addr);
+        
+        axum::serve(listener, app).await?;
+        Ok(())
+    }
+    
+    /// Push a warning to wallets subscribed to any address the flagged transaction touches
+    pub fn warn(&self, transaction: &Transaction, verdict: &ThreatDetectionResult) {
+        let addresses: HashSet<String> = [&transaction.target_address, &transaction.to, &transaction.from]
+            .into_iter()
+            .filter(|address| !address.is_empty())
+            .map(|address| address.to_lowercase())
+            .collect();
+        for address in addresses {
+            // Nobody connected is not an error
+            let _ = self.state.warnings.send(WalletWarning {
+                address,
+                transaction_id: transaction.id.clone(),
+                chain_id: transaction.chain_id,
+                threat_type: verdict.threat_type.clone(),
+                confidence: verdict.confidence,
+                recommended_action: verdict.recommended_action.clone(),
+            });
+        }
+    }
+}
+
+async fn upgrade(State(state): State<Arc<LightClientState>>, ws: WebSocketUpgrade) -> impl IntoResponse {
+    ws.max_message_size(4096)
+        .on_upgrade(move |mut socket| async move {
+            if state.connections.fetch_add(1, Ordering::Relaxed) >= state.config.max_connections {
+                state.connections.fetch_sub(1, Ordering::Relaxed);
+                let _ = send(&mut socket, &WalletMessage::Error { message: "Too many wallet connections".to_string() }).await;
+                return;
+            }
+            Connection::new(Arc::clone(&state)).serve(socket).await;
+            state.connections.fetch_sub(1, Ordering::Relaxed);
+        })
+}
+
+struct Connection {
+    state: Arc<LightClientState>,
+    addresses: HashSet<String>,
+    prefixes: HashSet<String>,
+    /// Start of the current one-minute window and requests made in it
+    window: (Instant, u32),
+}
+
+impl Connection {
+    fn new(state: Arc<LightClientState>) -> Self {
+        Self {
+            state,
+            addresses: HashSet::new(),
+            prefixes: HashSet::new(),
+            window: (Instant::now(), 0),
+        }
+    }
+    
+    async fn serve(mut self, mut socket: WebSocket) {
+        let mut warnings = self.state.warnings.subscribe();
+        loop {
+            tokio::select! {
+                message = socket.recv() => {
+                    let text = match message {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Close(_))) | None | Some(Err(_)) => return,
+                        Some(Ok(_)) => continue,
+                    };
+                    let reply = if self.take_request() {
+                        match serde_json::from_str::<WalletRequest>(&text) {
+                            Ok(request) => self.handle(request),
+                            Err(e) => Some(WalletMessage::Error { message: format!("Invalid request: {}", e) }),
+                        }
+                    } else {
+                        Some(WalletMessage::Error { message: "Rate limit exceeded".to_string() })
+                    };
+                    if let Some(reply) = reply {
+                        if send(&mut socket, &reply).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                warning = warnings.recv() => {
+                    let warning = match warning {
+                        Ok(warning) => warning,
+                        Err(broadcast::error::RecvError::Lagged(missed)) => {
+                            debug!("Wallet connection missed {} warnings", missed);
+                            continue;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return,
+                    };
+                    if self.wants(&warning.address) && send(&mut socket, &WalletMessage::Warning(warning)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+    
+    fn handle(&mut self, request: WalletRequest) -> Option<WalletMessage> {
+        match request {
+            WalletRequest::Check { address } => Some(self.verdict(&address)),
+            WalletRequest::Subscribe { address } => {
+                if let Err(message) = self.check_subscriptions() {
+                    return Some(message);
+                }
+                let verdict = self.verdict(&address);
+                self.addresses.insert(address.to_lowercase());
+                Some(verdict)
+            }
+            WalletRequest::Unsubscribe { address } => {
+                self.addresses.remove(&address.to_lowercase());
+                None
+            }
+            WalletRequest::CheckPrefix { prefix } => Some(self.prefix_verdicts(&prefix)),
+            WalletRequest::SubscribePrefix { prefix } => {
+                if let Err(message) = self.check_subscriptions() {
+                    return Some(message);
+                }
+                let verdicts = self.prefix_verdicts(&prefix);
+                if matches!(verdicts, WalletMessage::PrefixVerdicts { .. }) {
+                    self.prefixes.insert(prefix.to_lowercase());
+                }
+                Some(verdicts)
+            }
+            WalletRequest::UnsubscribePrefix { prefix } => {
+                self.prefixes.remove(&prefix.to_lowercase());
+                None
+            }
+        }
+    }
+    
+    fn verdict(&self, address: &str) -> WalletMessage {
+        match self.state.risk(address) {
+            Ok(risk) => WalletMessage::Verdict { risk },
+            Err(e) => {
+                warn!("Wallet risk lookup failed: {:#}", e);
+                WalletMessage::Error { message: "Lookup failed".to_string() }
+            }
+        }
+    }
+    
+    fn prefix_verdicts(&self, prefix: &str) -> WalletMessage {
+        let config = &self.state.config;
+        if !config.allow_anonymous {
+            return WalletMessage::Error { message: "Anonymous queries are disabled on this node".to_string() };
+        }
+        let prefix = prefix.trim_start_matches("0x").to_lowercase();
+        if prefix.len() < config.min_prefix_len || prefix.len() > 64 || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+            return WalletMessage::Error {
+                message: format!("Prefix must be {} to 64 hex characters", config.min_prefix_len),
+            };
+        }
+        
+        let mut addresses: Vec<String> = self.state.history.addresses();
+        if let Some(cache) = &self.state.alert_cache {
+            addresses.extend(cache.addresses());
+        }
+        addresses.sort();
+        addresses.dedup();
+        
+        let mut risks = Vec::new();
+        for address in addresses.into_iter().filter(|address| address_hash(address).starts_with(&prefix)) {
+            match self.state.risk(&address) {
+                Ok(risk) if risk.risk_score > 0 => risks.push(risk),
+                Ok(_) => {}
+                Err(e) => warn!("Wallet risk lookup failed: {:#}", e),
+            }
+            if risks.len() == config.max_prefix_matches {
+                break;
+            }
+        }
+        WalletMessage::PrefixVerdicts { prefix, risks }
+    }
+    
+    fn check_subscriptions(&self) -> Result<(), WalletMessage> {
+        if self.addresses.len() + self.prefixes.len() >= self.state.config.max_subscriptions {
+            return Err(WalletMessage::Error { message: "Subscription limit reached".to_string() });
+        }
+        Ok(())
+    }
+    
+    fn wants(&self, address: &str) -> bool {
+        if self.addresses.contains(address) {
+            return true;
+        }
+        if self.prefixes.is_empty() {
+            return false;
+        }
+        let hash = address_hash(address);
+        self.prefixes.iter().any(|prefix| hash.starts_with(prefix.as_str()))
+    }
+    
+    fn take_request(&mut self) -> bool {
+        if self.window.0.elapsed() >= Duration::from_secs(60) {
+            self.window = (Instant::now(), 0);
+        }
+        if self.window.1 >= self.state.config.requests_per_minute {
+            return false;
+        }
+        self.window.1 += 1;
+        true
+    }
+}
+
+impl LightClientState {
+    fn risk(&self, address: &str) -> Result<AddressRisk> {
+        let alerts = self.alert_cache.as_ref().map(|cache| cache.lookup(address)).unwrap_or_default();
+        self.history.risk(address, &alerts)
+    }
+}
+
+/// Hex of `keccak256` over the lowercase address, as anonymous prefixes are taken from
+fn address_hash(address: &str) -> String {
+    hex::encode(keccak256(address.to_lowercase().as_bytes()))
+}
+
+async fn send(socket: &mut WebSocket, message: &WalletMessage) -> Result<()> {
+    socket.send(Message::Text(serde_json::to_string(message)?)).await?;
+    Ok(())
+}
@@ -0,0 +1,590 @@
+//! Built-in load generator for soak testing a running node
+//!
+//! A load run feeds synthetic transactions into the node's own DAG at a rate that ramps up to a
+//! target, with optional periodic bursts, a share shaped like known threats and dependencies on
+//! recent transactions. They go through execution and detection like any other transaction, but
+//! end at the verdict: synthetic transactions are never reported, recorded or shared.
+//!
+//! Every sample interval the run records throughput, errors, end-to-end latency and memory, and
+//! the finished run is signed off against the profile's limits on error rate, memory growth and
+//! latency drift. The report is rewritten to `loadgen.report_dir` at every sample, so a run that
+//! takes the node down still leaves its record.
+
+use anyhow::{bail, Context, Result};
+use axum::{http::StatusCode, response::IntoResponse, routing::{get, post}, Json, Router};
+use dashmap::DashMap;
+use ethers::core::rand::{rngs::StdRng, Rng, SeedableRng};
+use ethers::types::U256;
+use prometheus::{IntCounterVec, Opts};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tracing::{debug, info, warn};
+
+use crate::config::LoadGenConfig;
+use crate::dag::{Backpressure, DAGProcessor, Transaction};
+use crate::memory::allocator_stats;
+use crate::metrics::register_once;
+use crate::status::Report;
+
+/// Marks synthetic transactions, so verdict handling can stop them before anything is reported
+pub const LOADGEN_ID_PREFIX: &str = "loadgen-";
+
+/// Recent transactions new ones may depend on
+const DEPENDENCY_POOL: usize = 256;
+const TICK: Duration = Duration::from_millis(100);
+/// How long a finished run waits for its in-flight transactions before counting them lost
+const DRAIN_GRACE: Duration = Duration::from_secs(60);
+
+const APPROVE_SELECTOR: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+const SET_APPROVAL_FOR_ALL_SELECTOR: [u8; 4] = [0xa2, 0x2c, 0xb4, 0x65];
+const TRANSFER_SELECTOR: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+
+pub fn is_synthetic(transaction: &Transaction) -> bool {
+    transaction.id.starts_with(LOADGEN_ID_PREFIX)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoadProfile {
+    pub duration_secs: u64,
+    /// Rate at the start of the ramp, in transactions per second
+    pub start_tps: f64,
+    /// Rate held once the ramp is over
+    pub target_tps: f64,
+    pub ramp_secs: u64,
+    /// Share of transactions shaped like known threats: unlimited approvals and operator grants
+    pub threat_ratio: f64,
+    /// Share of transactions depending on recent ones
+    pub dependency_density: f64,
+    pub max_dependencies: usize,
+    /// Seconds between bursts; no bursts when 0
+    pub burst_every_secs: u64,
+    pub burst_secs: u64,
+    /// Rate multiplier during a burst
+    pub burst_multiplier: f64,
+    /// Chain the transactions claim to be on; the node's chain when unset
+    pub chain_id: Option<u64>,
+    pub sample_interval_secs: u64,
+    /// Seed for a reproducible run
+    pub seed: Option<u64>,
+    /// Sign-off limit on rejected, failed and lost transactions over those submitted
+    pub max_error_rate: f64,
+    /// Sign-off limit on resident memory growth
+    pub max_memory_growth_mb_per_hour: f64,
+    /// Sign-off limit on p99 latency at the end over p99 just after the ramp
+    pub max_latency_drift_pct: f64,
+}
+
+impl Default for LoadProfile {
+    fn default() -> Self {
+        Self {
+            duration_secs: 4 * 3600,
+            start_tps: 10.0,
+            target_tps: 200.0,
+            ramp_secs: 600,
+            threat_ratio: 0.05,
+            dependency_density: 0.3,
+            max_dependencies: 3,
+            burst_every_secs: 0,
+            burst_secs: 30,
+            burst_multiplier: 3.0,
+            chain_id: None,
+            sample_interval_secs: 60,
+            seed: None,
+            max_error_rate: 0.01,
+            max_memory_growth_mb_per_hour: 64.0,
+            max_latency_drift_pct: 50.0,
+        }
+    }
+}
+
+impl LoadProfile {
+    /// Offered rate `elapsed_secs` into the run
+    fn tps_at(&self, elapsed_secs: f64) -> f64 {
+        let ramped = if self.ramp_secs > 0 && elapsed_secs < self.ramp_secs as f64 {
+            self.start_tps + (self.target_tps - self.start_tps) * elapsed_secs / self.ramp_secs as f64
+        } else {
+            self.target_tps
+        };
+        let bursting = self.burst_every_secs > 0
+            && (elapsed_secs as u64 % self.burst_every_secs) < self.burst_secs
+            && elapsed_secs as u64 >= self.burst_every_secs;
+        if bursting { ramped * self.burst_multiplier } else { ramped }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StabilitySample {
+    /// Seconds into the run
+    pub at_secs: u64,
+    pub offered_tps: f64,
+    pub achieved_tps: f64,
+    pub submitted: u64,
+    /// Refused by the DAG, e.g. while ingestion was paused
+    pub rejected: u64,
+    pub judged: u64,
+    pub flagged: u64,
+    /// Detection errors
+    pub failed: u64,
+    /// Submitted and not judged yet
+    pub in_flight: usize,
+    pub latency_p50_ms: Option<f64>,
+    pub latency_p99_ms: Option<f64>,
+    pub resident_bytes: u64,
+    pub allocated_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignOff {
+    pub passed: bool,
+    pub error_rate: f64,
+    pub memory_growth_mb_per_hour: Option<f64>,
+    pub latency_drift_pct: Option<f64>,
+    /// Limits the run broke
+    pub failures: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadReport {
+    pub run_id: String,
+    pub profile: LoadProfile,
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+    /// Stopped by an operator before its duration was up
+    pub stopped: bool,
+    pub submitted: u64,
+    pub rejected: u64,
+    pub judged: u64,
+    pub flagged: u64,
+    pub failed: u64,
+    /// Still in flight when the finished run stopped waiting for them
+    pub lost: u64,
+    pub samples: Vec<StabilitySample>,
+    /// Provisional while the run is going
+    pub sign_off: SignOff,
+}
+
+#[derive(Default)]
+struct Window {
+    submitted: u64,
+    rejected: u64,
+    judged: u64,
+    flagged: u64,
+    failed: u64,
+    latencies_ms: Vec<f64>,
+}
+
+pub struct LoadGenerator {
+    config: LoadGenConfig,
+    dag: Arc<DAGProcessor>,
+    default_chain_id: u64,
+    /// The current or last run
+    run: parking_lot::Mutex<Option<LoadReport>>,
+    window: parking_lot::Mutex<Window>,
+    in_flight: DashMap<String, Instant>,
+    stop: Notify,
+    transactions: IntCounterVec,
+}
+
+impl LoadGenerator {
+    pub fn new(config: &LoadGenConfig, dag: Arc<DAGProcessor>, default_chain_id: u64) -> Result<Self> {
+        static TRANSACTIONS: OnceLock<IntCounterVec> = OnceLock::new();
+        let transactions = register_once(&TRANSACTIONS, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_loadgen_transactions_total", "Synthetic transactions of load runs, by outcome"),
+                &["outcome"],
+            )
+        })?;
+        
+        Ok(Self {
+            config: config.clone(),
+            dag,
+            default_chain_id,
+            run: parking_lot::Mutex::new(None),
+            window: parking_lot::Mutex::new(Window::default()),
+            in_flight: DashMap::new(),
+            stop: Notify::new(),
+            transactions,
+        })
+    }
+    
+    /// Start a run in the background; fails while another is going
+    pub fn start(self: &Arc<Self>, profile: LoadProfile) -> Result<LoadReport> {
+        if profile.target_tps <= 0.0 || profile.target_tps > self.config.max_tps {
+            bail!("target_tps must be above 0 and at most loadgen.max_tps ({})", self.config.max_tps);
+        }
+        if profile.start_tps < 0.0 || profile.start_tps > profile.target_tps {
+            bail!("start_tps must be between 0 and target_tps");
+        }
+        if profile.duration_secs == 0 {
+            bail!("duration_secs must be positive");
+        }
+        
+        let report = {
+            let mut run = self.run.lock();
+            if run.as_ref().is_some_and(|run| run.finished_at.is_none()) {
+                bail!("Load run {} is already going; stop it first", run.as_ref().map_or("", |run| run.run_id.as_str()));
+            }
+            let started_at = chrono::Utc::now().timestamp() as u64;
+            let report = LoadReport {
+                run_id: format!("{}{}", LOADGEN_ID_PREFIX, started_at),
+                profile: profile.clone(),
+                started_at,
+                finished_at: None,
+                stopped: false,
+                submitted: 0,
+                rejected: 0,
+                judged: 0,
+                flagged: 0,
+                failed: 0,
+                lost: 0,
+                samples: Vec::new(),
+                sign_off: sign_off(&profile, &[], 0, 0),
+            };
+            *run = Some(report.clone());
+            report
+        };
+        *self.window.lock() = Window::default();
+        self.in_flight.clear();
+        
+        info!("🏋️ Starting load run {}: {:.0} -> {:.0} TPS over {}s, for {}s",
+              report.run_id, profile.start_tps, profile.target_tps, profile.ramp_secs, profile.duration_secs);
+        let generator = Arc::clone(self);
+        let run_id = report.run_id.clone();
+        tokio::spawn(async move {
+            generator.drive(run_id, profile).await;
+        });
+        Ok(report)
+    }
+    
+    /// End the current run early; its report is finished as usual
+    pub fn stop(&self) {
+        if self.is_running() {
+            self.stop.notify_one();
+        }
+    }
+    
+    pub fn is_running(&self) -> bool {
+        self.run.lock().as_ref().is_some_and(|run| run.finished_at.is_none())
+    }
+    
+    pub fn report(&self) -> Option<LoadReport> {
+        self.run.lock().clone()
+    }
+    
+    /// Count the verdict on a synthetic transaction; `Err` when its detection failed
+    pub fn record_outcome(&self, transaction: &Transaction, flagged: Result<bool, String>) {
+        let latency = self.in_flight.remove(&transaction.id).map(|(_, submitted)| submitted.elapsed());
+        let mut window = self.window.lock();
+        match flagged {
+            Ok(flagged) => {
+                window.judged += 1;
+                if flagged {
+                    window.flagged += 1;
+                }
+                if let Some(latency) = latency {
+                    window.latencies_ms.push(latency.as_secs_f64() * 1000.0);
+                }
+                self.transactions.with_label_values(&[if flagged { "flagged" } else { "passed" }]).inc();
+            }
+            Err(e) => {
+                debug!("Detection failed on synthetic transaction {}: {}", transaction.id, e);
+                window.failed += 1;
+                self.transactions.with_label_values(&["failed"]).inc();
+            }
+        }
+    }
+    
+    async fn drive(&self, run_id: String, profile: LoadProfile) {
+        let mut rng = profile.seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        let chain_id = profile.chain_id.unwrap_or(self.default_chain_id);
+        let started = Instant::now();
+        let duration = Duration::from_secs(profile.duration_secs);
+        let sample_every = Duration::from_secs(profile.sample_interval_secs.max(1));
+        let mut next_sample = sample_every;
+        let mut ticks = tokio::time::interval(TICK);
+        let mut recent: VecDeque<String> = VecDeque::with_capacity(DEPENDENCY_POOL);
+        let mut credit = 0.0;
+        let mut sequence: u64 = 0;
+        let mut stopped = false;
+        
+        while started.elapsed() < duration {
+            tokio::select! {
+                _ = ticks.tick() => {}
+                _ = self.stop.notified() => {
+                    stopped = true;
+                    break;
+                }
+            }
+            
+            let elapsed = started.elapsed();
+            credit += profile.tps_at(elapsed.as_secs_f64()) * TICK.as_secs_f64();
+            while credit >= 1.0 {
+                credit -= 1.0;
+                sequence += 1;
+                let transaction = synthetic_transaction(&mut rng, &profile, &run_id, sequence, chain_id, &recent);
+                self.submit(transaction.clone()).await;
+                if recent.len() == DEPENDENCY_POOL {
+                    recent.pop_front();
+                }
+                recent.push_back(transaction.id);
+            }
+            
+            if elapsed >= next_sample {
+                self.sample(&profile, elapsed);
+                next_sample += sample_every;
+            }
+        }
+        
+        // Give the pipeline time to judge what was submitted before counting the rest as lost
+        let drain_started = Instant::now();
+        while !self.in_flight.is_empty() && drain_started.elapsed() < DRAIN_GRACE {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+        self.sample(&profile, started.elapsed());
+        self.finish(stopped);
+    }
+    
+    async fn submit(&self, transaction: Transaction) {
+        let id = transaction.id.clone();
+        self.in_flight.insert(id.clone(), Instant::now());
+        self.window.lock().submitted += 1;
+        if let Err(e) = self.dag.add_transaction(transaction).await {
+            debug!("Synthetic transaction {} rejected: {:#}", id, e);
+            self.in_flight.remove(&id);
+            self.window.lock().rejected += 1;
+            let outcome = if e.is::<Backpressure>() { "backpressure" } else { "rejected" };
+            self.transactions.with_label_values(&[outcome]).inc();
+        }
+    }
+    
+    /// Close the current window into a sample on the run
+    fn sample(&self, profile: &LoadProfile, elapsed: Duration) {
+        let mut window = std::mem::take(&mut *self.window.lock());
+        window.latencies_ms.sort_by(|a, b| a.total_cmp(b));
+        let memory = allocator_stats();
+        
+        let mut run = self.run.lock();
+        let Some(run) = run.as_mut() else {
+            return;
+        };
+        let since = run.samples.last().map_or(0, |sample| sample.at_secs);
+        let at_secs = elapsed.as_secs();
+        let sample = StabilitySample {
+            at_secs,
+            offered_tps: profile.tps_at(elapsed.as_secs_f64()),
+            achieved_tps: window.judged as f64 / (at_secs.saturating_sub(since)).max(1) as f64,
+            submitted: window.submitted,
+            rejected: window.rejected,
+            judged: window.judged,
+            flagged: window.flagged,
+            failed: window.failed,
+            in_flight: self.in_flight.len(),
+            latency_p50_ms: percentile(&window.latencies_ms, 0.50),
+            latency_p99_ms: percentile(&window.latencies_ms, 0.99),
+            resident_bytes: memory.resident_bytes,
+            allocated_bytes: memory.allocated_bytes,
+        };
+        info!("🏋️ Load run at {}s: {:.0}/{:.0} TPS, {} in flight, p99 {:.1}ms, {} MB resident",
+              sample.at_secs, sample.achieved_tps, sample.offered_tps, sample.in_flight,
+              sample.latency_p99_ms.unwrap_or(0.0), sample.resident_bytes / (1024 * 1024));
+        
+        run.submitted += sample.submitted;
+        run.rejected += sample.rejected;
+        run.judged += sample.judged;
+        run.flagged += sample.flagged;
+        run.failed += sample.failed;
+        run.samples.push(sample);
+        run.sign_off = sign_off(profile, &run.samples, run.submitted, run.rejected + run.failed);
+        if let Err(e) = self.write_report(run) {
+            warn!("⚠️ Failed to write load report: {:#}", e);
+        }
+    }
+    
+    fn finish(&self, stopped: bool) {
+        let lost = self.in_flight.len() as u64;
+        self.in_flight.clear();
+        
+        let mut run = self.run.lock();
+        let Some(run) = run.as_mut() else {
+            return;
+        };
+        run.finished_at = Some(chrono::Utc::now().timestamp() as u64);
+        run.stopped = stopped;
+        run.lost = lost;
+        run.sign_off = sign_off(&run.profile, &run.samples, run.submitted, run.rejected + run.failed + lost);
+        if run.sign_off.passed {
+            info!("✅ Load run {} passed: {} judged, {} lost", run.run_id, run.judged, lost);
+        } else {
+            warn!("❌ Load run {} failed sign-off: {}", run.run_id, run.sign_off.failures.join("; "));
+        }
+        if let Err(e) = self.write_report(run) {
+            warn!("⚠️ Failed to write load report: {:#}", e);
+        }
+    }
+    
+    fn write_report(&self, report: &LoadReport) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.config.report_dir)
+            .with_context(|| format!("Failed to create {}", self.config.report_dir))?;
+        let path = PathBuf::from(&self.config.report_dir).join(format!("{}.json", report.run_id));
+        std::fs::write(&path, serde_json::to_string_pretty(report)? + "\n")?;
+        Ok(path)
+    }
+}
+
+fn synthetic_transaction(
+    rng: &mut StdRng,
+    profile: &LoadProfile,
+    run_id: &str,
+    sequence: u64,
+    chain_id: u64,
+    recent: &VecDeque<String>,
+) -> Transaction {
+    let from = random_address(rng);
+    let token = random_address(rng);
+    let counterparty = random_address(rng);
+    
+    let (to, target_address, data, value) = if rng.gen_bool(profile.threat_ratio.clamp(0.0, 1.0)) {
+        // Grants a drainer would ask for: every token, or every NFT of a collection
+        let data = if rng.gen_bool(0.5) {
+            call_data(APPROVE_SELECTOR, &counterparty, U256::MAX)
+        } else {
+            call_data(SET_APPROVAL_FOR_ALL_SELECTOR, &counterparty, U256::one())
+        };
+        (token, counterparty, data, U256::zero())
+    } else if rng.gen_bool(0.5) {
+        let amount = U256::from(rng.gen_range(1u64..1_000_000)) * U256::exp10(12);
+        (token.clone(), token, call_data(TRANSFER_SELECTOR, &counterparty, amount), U256::zero())
+    } else {
+        let value = U256::from(rng.gen_range(1u64..10_000)) * U256::exp10(14);
+        (counterparty.clone(), counterparty, Vec::new(), value)
+    };
+    
+    let dependencies = if !recent.is_empty() && rng.gen_bool(profile.dependency_density.clamp(0.0, 1.0)) {
+        let count = rng.gen_range(1..=profile.max_dependencies.max(1)).min(recent.len());
+        let mut dependencies: Vec<String> = (0..count)
+            .map(|_| recent[rng.gen_range(0..recent.len())].clone())
+            .collect();
+        dependencies.sort();
+        dependencies.dedup();
+        dependencies
+    } else {
+        Vec::new()
+    };
+    
+    Transaction {
+        id: format!("{}-{}", run_id, sequence),
+        from,
+        to,
+        target_address,
+        chain_id,
+        data,
+        timestamp: chrono::Utc::now().timestamp() as u64,
+        dependencies,
+        blob_versioned_hashes: Vec::new(),
+        value,
+        logs: Vec::new(),
+        origin: None,
+        nonce: None,
+        storage_slots: Vec::new(),
+        priority_fee: U256::zero(),
+        peer_synced: false,
+    }
+}
+
+fn random_address(rng: &mut StdRng) -> String {
+    format!("0x{}", ethers::utils::hex::encode(rng.gen::<[u8; 20]>()))
+}
+
+/// ABI call data for `selector(address, uint256)`
+fn call_data(selector: [u8; 4], address: &str, amount: U256) -> Vec<u8> {
+    let mut data = selector.to_vec();
+    data.extend_from_slice(&[0u8; 12]);
+    data.extend_from_slice(&ethers::utils::hex::decode(address.trim_start_matches("0x")).unwrap_or_default());
+    let mut word = [0u8; 32];
+    amount.to_big_endian(&mut word);
+    data.extend_from_slice(&word);
+    data
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], quantile: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((sorted.len() as f64 * quantile).ceil() as usize).clamp(1, sorted.len());
+    Some(sorted[rank - 1])
+}
+
+fn sign_off(profile: &LoadProfile, samples: &[StabilitySample], submitted: u64, errors: u64) -> SignOff {
+    let error_rate = if submitted > 0 { errors as f64 / submitted as f64 } else { 0.0 };
+    
+    let memory_growth_mb_per_hour = match (samples.first(), samples.last()) {
+        (Some(first), Some(last)) if last.at_secs > first.at_secs => {
+            let grown = last.resident_bytes as f64 - first.resident_bytes as f64;
+            Some(grown / (1024.0 * 1024.0) / ((last.at_secs - first.at_secs) as f64 / 3600.0))
+        }
+        _ => None,
+    };
+    
+    // Latency is only comparable at full rate, so the baseline is the first sample after the ramp
+    let baseline = samples
+        .iter()
+        .filter(|sample| sample.at_secs >= profile.ramp_secs)
+        .find_map(|sample| sample.latency_p99_ms);
+    let latest = samples.iter().rev().find_map(|sample| sample.latency_p99_ms);
+    let latency_drift_pct = match (baseline, latest) {
+        (Some(baseline), Some(latest)) if baseline > 0.0 => Some((latest - baseline) / baseline * 100.0),
+        _ => None,
+    };
+    
+    let mut failures = Vec::new();
+    if error_rate > profile.max_error_rate {
+        failures.push(format!("error rate {:.4} above {:.4}", error_rate, profile.max_error_rate));
+    }
+    if let Some(growth) = memory_growth_mb_per_hour.filter(|growth| *growth > profile.max_memory_growth_mb_per_hour) {
+        failures.push(format!("memory grew {:.1} MB/h, above {:.1}", growth, profile.max_memory_growth_mb_per_hour));
+    }
+    if let Some(drift) = latency_drift_pct.filter(|drift| *drift > profile.max_latency_drift_pct) {
+        failures.push(format!("p99 latency drifted {:.1}%, above {:.1}%", drift, profile.max_latency_drift_pct));
+    }
+    
+    SignOff {
+        passed: failures.is_empty(),
+        error_rate,
+        memory_growth_mb_per_hour,
+        latency_drift_pct,
+        failures,
+    }
+}
+
+/// `GET /loadgen` reports the current or last run; `POST /loadgen` starts one with the
+/// [`LoadProfile`] in the body, and `POST /loadgen/stop` ends it early
+pub fn admin_routes(generator: Arc<LoadGenerator>) -> Router {
+    let reporting = Arc::clone(&generator);
+    let stopping = Arc::clone(&generator);
+    Router::new()
+        .route("/loadgen", get(move || async move {
+            match reporting.report() {
+                Some(report) => Json(Report::new("loadgen", report)).into_response(),
+                None => (StatusCode::NOT_FOUND, "No load run yet").into_response(),
+            }
+        }).post(move |Json(profile): Json<LoadProfile>| async move {
+            let status = if generator.is_running() { StatusCode::CONFLICT } else { StatusCode::BAD_REQUEST };
+            match generator.start(profile) {
+                Ok(report) => Json(Report::new("loadgen", report)).into_response(),
+                Err(e) => (status, format!("{:#}", e)).into_response(),
+            }
+        }))
+        .route("/loadgen/stop", post(move || async move {
+            stopping.stop();
+            match stopping.report() {
+                Some(report) => Json(Report::new("loadgen", report)).into_response(),
+                None => (StatusCode::NOT_FOUND, "No load run yet").into_response(),
+            }
+        }))
+}
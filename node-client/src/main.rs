@@ -4,23 +4,26 @@
 //! Handles DAG processing, AI threat detection, blockchain interaction, and energy monitoring.
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::{de::DeserializeOwned, Serialize};
 use std::sync::Arc;
-use tokio::signal;
-use tracing::{info, error};
-
-mod config;
-mod node;
-mod dag;
-mod ai;
-mod blockchain;
-mod network;
-mod energy;
-mod metrics;
-mod storage;
-
-use config::NodeConfig;
-use node::DAGShieldNode;
+use tracing::{info, error, warn};
+
+use dagshield_node::{alert_cache, archive, audit, backtest, deploy, fixtures, history, keys, loadgen, metrics, mirror, peers, preflight, provision, query, replica, reporting_guard, retention, rewards, sandbox, screening, stake, storage, updater};
+use dagshield_node::blockchain::BlockchainClient;
+use dagshield_node::config::NodeConfig;
+use dagshield_node::dag::DagShape;
+use dagshield_node::node::DAGShieldNode;
+use dagshield_node::{ResourceGovernor, ThreatDetector};
+use dagshield_node::service::{ServiceEvent, ServiceHost, EXIT_CONFIG, EXIT_FAILURE, EXIT_SUCCESS};
+use dagshield_node::status::{
+    AddressHistory, AiBenchmark, BenchmarkReport, DagBenchmark, NodeStatus, Report, StatsReport,
+};
+
+// Installed here rather than in the library, so embedders keep their own allocator
+#[cfg(feature = "jemalloc")]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
 #[derive(Parser)]
 #[command(name = "dagshield-node")]
@@ -38,31 +41,405 @@ struct Cli {
     #[arg(short, long)]
     verbose: bool,
     
-    /// Disable AI threat detection (for testing)
+    /// Disable AI threat detection, whatever `enable_ai` says
     #[arg(long)]
     no_ai: bool,
     
     /// Run in benchmark mode
     #[arg(long)]
     benchmark: bool,
+    
+    /// Result format of the status, stats, benchmark, history, peers, preflight, backtest, provision, keygen, mirror, loadgen, archive and review subcommands.
+    /// `json` prints one document to stdout, in the schemas of `status.rs`, and moves logging to stderr
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, global = true)]
+    output: OutputFormat,
+    
+    /// Run under the Windows service control manager
+    #[cfg(windows)]
+    #[arg(long)]
+    service: bool,
+    
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+#[derive(Subcommand)]
+enum Command {
+    /// Run the active detection pipeline against golden fixtures and report verdict drift
+    VerifyModel {
+        /// Directory containing golden fixture files
+        #[arg(long, default_value = "fixtures/golden")]
+        fixtures: String,
+        
+        /// Maximum allowed confidence difference before a verdict counts as drifted
+        #[arg(long, default_value_t = 0.01)]
+        tolerance: f32,
+        
+        /// Rewrite the fixtures with the current verdicts instead of verifying them
+        #[arg(long)]
+        bless: bool,
+    },
+    /// Show identity, uptime, peers, maintenance mode and failed subsystems of the running node
+    Status,
+    /// Show detection, DAG, model and energy counters of the running node
+    Stats,
+    /// Run the DAG, detection and energy benchmarks, like `--benchmark`
+    Benchmark,
+    /// Show the running node's threat reports and risk score for an address
+    History {
+        address: String,
+    },
+    /// Show per-peer intel give/take ratios and blocks of the running node
+    Peers,
+    /// Run a read-only SQL query over the running node's stored data: the tables `detections`,
+    /// `events` (indexed on-chain alerts), `energy` and `audit_log`
+    Query {
+        sql: String,
+    },
+    /// Preview what the retention policy would expire from the running node's storage
+    Retention {
+        /// Run the sweep rather than preview it
+        #[arg(long)]
+        apply: bool,
+    },
+    /// Show whether the detection-rate guard suspended auto-reporting on the running node, and the
+    /// reports it holds for review; approve or discard them, or resume auto-reporting
+    Review {
+        /// Submit the held reports on-chain
+        #[arg(long, conflicts_with = "discard")]
+        approve: bool,
+        /// Drop the held reports
+        #[arg(long)]
+        discard: bool,
+        /// Held reports to approve or discard, by transaction; all of them when none are given
+        transaction_ids: Vec<String>,
+        /// Resume auto-reporting
+        #[arg(long)]
+        resume: bool,
+    },
+    /// Search the detections the running node archived to cold storage; without filters, summarise
+    /// the archive. Needs `archive.enabled`
+    Archive {
+        /// Target address of the detections
+        #[arg(long)]
+        address: Option<String>,
+        #[arg(long)]
+        chain_id: Option<u64>,
+        /// Reported at or after, as RFC 3339 or unix seconds
+        #[arg(long)]
+        from: Option<String>,
+        /// Reported at or before, as RFC 3339 or unix seconds
+        #[arg(long)]
+        to: Option<String>,
+        #[arg(long, default_value_t = 100)]
+        limit: usize,
+    },
+    /// Show the rewards the running node earned, claimed and can claim now
+    Rewards {
+        /// Claim every accrued reward, signing with the node key
+        #[arg(long)]
+        claim: bool,
+    },
+    /// Show the running node's stake against the contract minimum, or change it with the node key
+    Stake {
+        #[command(subcommand)]
+        action: Option<StakeAction>,
+    },
+    /// Show how the running node's shadow pipeline diverges from the live one on mirrored traffic
+    Mirror {
+        /// Close the current comparison window and start a new one
+        #[arg(long)]
+        reset: bool,
+    },
+    /// Soak-test the running node with synthetic traffic and sign off on its stability; needs
+    /// `loadgen.enabled`. Without options, starts a run with the default profile
+    Loadgen {
+        /// Rate to ramp up to and hold, in transactions per second
+        #[arg(long)]
+        tps: Option<f64>,
+        /// Rate at the start of the ramp
+        #[arg(long)]
+        start_tps: Option<f64>,
+        #[arg(long)]
+        ramp_secs: Option<u64>,
+        #[arg(long)]
+        duration_secs: Option<u64>,
+        /// Share of transactions shaped like known threats, 0.0-1.0
+        #[arg(long)]
+        threat_ratio: Option<f64>,
+        /// Share of transactions depending on recent ones, 0.0-1.0
+        #[arg(long)]
+        dependency_density: Option<f64>,
+        /// Burst every this many seconds
+        #[arg(long)]
+        burst_every_secs: Option<u64>,
+        #[arg(long)]
+        burst_secs: Option<u64>,
+        #[arg(long)]
+        burst_multiplier: Option<f64>,
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Show the current or last run instead of starting one
+        #[arg(long, conflicts_with = "stop")]
+        status: bool,
+        /// Stop the current run early
+        #[arg(long)]
+        stop: bool,
+        /// Wait for the run to finish, exiting non-zero unless it passes sign-off
+        #[arg(long)]
+        wait: bool,
+    },
+    /// Replay the known exploits in `backtest.exploits_file` through the current pipeline and
+    /// report which would have been caught, and how long before the drain
+    Backtest {
+        /// Also write the report to `backtest.report_dir`
+        #[arg(long)]
+        save: bool,
+    },
+    /// Check config, chain RPC, contracts, wallet, model, storage and ports before going live;
+    /// run it with the node stopped
+    Preflight,
+    /// Deploy the DAGShield contracts to a private chain and write their addresses into the config
+    DeployContracts {
+        /// DAG tokens to move into the core contract's reward pool
+        #[arg(long, default_value_t = 1_000_000)]
+        reward_pool_tokens: u64,
+        
+        /// Deploy even though the configured chain ID is a public network
+        #[arg(long)]
+        allow_public_chain: bool,
+    },
+    /// Set up a new device from a signed provisioning bundle: write its config to `--config`
+    /// and enroll it with the fleet controller
+    Provision {
+        /// Device token the bundle was issued for
+        #[arg(long)]
+        token: Option<String>,
+        
+        /// Where to fetch the bundle, with the token as bearer
+        #[arg(long, conflicts_with = "qr")]
+        url: Option<String>,
+        
+        /// Scanned QR text: a `dagshield://provision` link or an inline bundle; `-` reads stdin
+        #[arg(long)]
+        qr: Option<String>,
+        
+        /// Operator address accepted on the bundle; repeat for several
+        #[arg(long = "trusted-signer", required = true)]
+        trusted_signers: Vec<String>,
+        
+        /// Replace an existing config file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Create a node key in an encrypted keystore, with the passphrase from
+    /// `DAGSHIELD_KEYSTORE_PASSWORD` or typed twice; point `blockchain.key` at it
+    Keygen {
+        /// Directory to write the keystore to
+        #[arg(long, default_value = "keys")]
+        dir: String,
+        
+        /// Derive the key from a new 24-word mnemonic, shown once to write down
+        #[arg(long)]
+        mnemonic: bool,
+        
+        #[arg(long, default_value = keys::DEFAULT_DERIVATION_PATH, requires = "mnemonic")]
+        derivation_path: String,
+    },
+    /// Reconstruct node state from the audit log; run against a stopped node or a copy of its data dir
+    ReplayAudit {
+        /// Point in time to reconstruct, as RFC 3339 or unix seconds (default: now)
+        #[arg(long)]
+        at: Option<String>,
+        
+        /// Also list every event up to that point
+        #[arg(long)]
+        events: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum StakeAction {
+    /// Add to the stake from the node wallet
+    Add {
+        /// In tokens, e.g. `25` or `0.5`
+        amount: String,
+    },
+    /// Withdraw stake above the minimum
+    Withdraw {
+        /// In tokens, e.g. `25` or `0.5`
+        #[arg(required_unless_present = "all")]
+        amount: Option<String>,
+        
+        /// Deactivate the node and withdraw its whole stake
+        #[arg(long, conflicts_with = "amount")]
+        all: bool,
+    },
+}
+
+fn main() {
     let cli = Cli::parse();
     
     // Initialize logging
     let log_level = if cli.verbose { "debug" } else { "info" };
-    tracing_subscriber::fmt()
-        .with_env_filter(format!("dagshield_node={},warn", log_level))
-        .init();
+    let logging = tracing_subscriber::fmt().with_env_filter(format!("dagshield_node={},warn", log_level));
+    // Keep stdout to the JSON document alone
+    if cli.output == OutputFormat::Json {
+        logging.with_writer(std::io::stderr).init();
+    } else {
+        logging.init();
+    }
     
     info!("🛡️ Starting DAGShield Node Client v{}", env!("CARGO_PKG_VERSION"));
     
+    #[cfg(windows)]
+    if cli.service {
+        if let Err(e) = service::windows::run(move |host| run_node(cli, host)) {
+            error!("❌ Failed to start as a Windows service: {}", e);
+            std::process::exit(EXIT_FAILURE);
+        }
+        return;
+    }
+    
+    let host = ServiceHost::from_env();
+    std::process::exit(run_node(cli, host));
+}
+
+/// Run the node to completion and return the process exit code
+fn run_node(cli: Cli, host: ServiceHost) -> i32 {
+    // Provisioning writes the config rather than reading it
+    if let Some(Command::Provision { token, url, qr, trusted_signers, force }) = &cli.command {
+        let options = provision::ProvisionOptions {
+            token: token.clone(),
+            url: url.clone(),
+            qr: qr.clone(),
+            trusted_signers: trusted_signers.clone(),
+            config_path: cli.config.clone(),
+            force: *force,
+        };
+        return match run_provision(&options, cli.output) {
+            Ok(()) => EXIT_SUCCESS,
+            Err(e) => {
+                error!("❌ Provisioning failed: {:#}", e);
+                EXIT_FAILURE
+            }
+        };
+    }
+    
+    // A new key comes before the config that will point at it
+    if let Some(Command::Keygen { dir, mnemonic, derivation_path }) = &cli.command {
+        let options = keys::KeygenOptions {
+            dir: dir.clone(),
+            mnemonic: *mnemonic,
+            derivation_path: derivation_path.clone(),
+        };
+        return match run_keygen(&options, cli.output) {
+            Ok(()) => EXIT_SUCCESS,
+            Err(e) => {
+                error!("❌ Key generation failed: {:#}", e);
+                EXIT_FAILURE
+            }
+        };
+    }
+    
     // Load configuration
-    let config = NodeConfig::load(&cli.config)?;
+    let config = match NodeConfig::load(&cli.config) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("❌ Failed to load configuration from {}: {:#}", cli.config, e);
+            return EXIT_CONFIG;
+        }
+    };
     info!("📋 Configuration loaded from: {}", cli.config);
     
+    // Sandbox the process before the runtime spawns threads so every thread inherits it.
+    // deploy-contracts is exempt: it rewrites the config file, which the sandbox keeps read-only
+    let deploying = matches!(cli.command, Some(Command::DeployContracts { .. }));
+    if !deploying {
+        if let Err(e) = sandbox::apply(&config, &cli.config) {
+            error!("❌ Failed to apply sandbox: {:#}", e);
+            return EXIT_FAILURE;
+        }
+    }
+    
+    let result = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(anyhow::Error::from)
+        .and_then(|runtime| runtime.block_on(run(cli, config, host)));
+    
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            error!("❌ Node failed: {:#}", e);
+            EXIT_FAILURE
+        }
+    }
+}
+
+fn run_keygen(options: &keys::KeygenOptions, output: OutputFormat) -> Result<()> {
+    let (generated, phrase) = keys::keygen(options)?;
+    // Straight to the terminal, never into logs or the JSON report
+    if let Some(phrase) = phrase {
+        eprintln!("\nRecovery phrase for {:?}; write it down, it is not shown or stored again:\n\n    {}\n", generated.address, phrase);
+    }
+    if output == OutputFormat::Json {
+        return print_json(&Report::new("keygen", generated));
+    }
+    
+    info!("🔑 Created key {:?} in {}", generated.address, generated.keystore_path);
+    if let Some(path) = &generated.derivation_path {
+        info!("   derived at {}", path);
+    }
+    info!("   use it with: key = {{ source = \"keystore\", path = \"{}\" }} under [blockchain]", generated.keystore_path);
+    Ok(())
+}
+
+fn run_provision(options: &provision::ProvisionOptions, output: OutputFormat) -> Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    let provisioned = runtime.block_on(provision::provision(options))?;
+    if output == OutputFormat::Json {
+        return print_json(&Report::new("provision", provisioned));
+    }
+    
+    info!("🎉 Device provisioned as {}, key {:?} (bundle signed by {:?})",
+          provisioned.node_id, provisioned.address, provisioned.signed_by);
+    match (&provisioned.controller_url, provisioned.enrolled) {
+        (Some(controller), true) => info!("   enrolled with fleet controller {}", controller),
+        (Some(controller), false) => warn!("   not yet enrolled with {}; the node enrolls when started", controller),
+        (None, _) => info!("   no fleet enrollment in the bundle"),
+    }
+    info!("   start it with: dagshield-node --config {} --node-id {}", provisioned.config_path, provisioned.node_id);
+    Ok(())
+}
+
+async fn run(cli: Cli, config: NodeConfig, host: ServiceHost) -> Result<i32> {
+    let benchmark = cli.benchmark || matches!(cli.command, Some(Command::Benchmark));
+    if let Some(command) = cli.command.as_ref().filter(|_| !benchmark) {
+        run_command(command, &config, &cli.config, cli.output).await?;
+        return Ok(EXIT_SUCCESS);
+    }
+    
+    if config.replication.is_replica() {
+        return run_replica(config, host).await;
+    }
+    
+    let updater_config = config.updater.clone();
+    let sandboxed = config.sandbox.enabled;
+    
+    // A freshly installed binary that keeps failing to come up makes way for the previous one
+    if updater_config.enabled {
+        if let Some(exit_code) = updater::check_boot(&updater_config, sandboxed)? {
+            return Ok(exit_code);
+        }
+    }
+    
     // Create and start the node
     let node = Arc::new(
         DAGShieldNode::new(config, cli.node_id, !cli.no_ai).await?
@@ -81,17 +458,52 @@ async fn main() -> Result<()> {
     };
     
     // Run benchmark if requested
-    if cli.benchmark {
+    if benchmark {
         info!("🏃 Running benchmark mode...");
-        run_benchmark(&node).await?;
-        return Ok(());
+        let report = run_benchmark(&node).await?;
+        if cli.output == OutputFormat::Json {
+            print_json(&Report::new("benchmark", report))?;
+        }
+        return Ok(EXIT_SUCCESS);
     }
     
-    // Wait for shutdown signal
+    // Report ready to the service manager only once the models are warm
+    while !node.is_ready() && !node_handle.is_finished() {
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    host.ready();
+    if updater_config.enabled && node.is_ready() {
+        if let Err(e) = updater::confirm_boot(&updater_config) {
+            warn!("Failed to confirm the update: {:#}", e);
+        }
+    }
+    
+    // Wait for a stop request, or for a self-update that needs a restart
     info!("✅ Node is running. Press Ctrl+C to shutdown.");
-    signal::ctrl_c().await?;
+    let restart = loop {
+        tokio::select! {
+            event = host.next_event() => match event? {
+                ServiceEvent::Stop => {
+                    info!("🛑 Shutdown signal received. Stopping node...");
+                    break false;
+                }
+                ServiceEvent::Pause => {
+                    node.pause();
+                    host.paused(true);
+                }
+                ServiceEvent::Resume => {
+                    node.resume();
+                    host.paused(false);
+                }
+            },
+            _ = node.restart_requested() => {
+                info!("♻️ Update installed. Stopping node to restart...");
+                break true;
+            }
+        }
+    };
     
-    info!("🛑 Shutdown signal received. Stopping node...");
+    host.stopping();
     node.stop().await?;
     
     // Wait for node to finish
@@ -99,25 +511,606 @@ async fn main() -> Result<()> {
         error!("Error waiting for node to stop: {}", e);
     }
     
+    if restart {
+        return updater::restart(&updater_config, sandboxed);
+    }
+    
     info!("👋 DAGShield node stopped successfully");
+    Ok(EXIT_SUCCESS)
+}
+
+/// Serve the query APIs from a copy of the primary's storage, without detection or chain access
+async fn run_replica(config: NodeConfig, host: ServiceHost) -> Result<i32> {
+    info!("🪞 Starting as a read replica of {}", config.replication.primary_url);
+    
+    let storage = Arc::new(storage::NodeStorage::new(&config.storage).await?);
+    let alert_cache = Arc::new(alert_cache::VerifiedAlertCache::new(&config.alert_cache, Arc::clone(&storage))?);
+    let history = Arc::new(history::ReportHistory::new(Arc::clone(&storage))?);
+    let sync = replica::ReplicaSync::new(&config.replication, Arc::clone(&storage), Arc::clone(&alert_cache), Arc::clone(&history))?;
+    let metrics = metrics::MetricsCollector::new(&config.metrics, config.enable_admin_api).await?;
+    
+    let mut handles = vec![
+        tokio::spawn(async move {
+            sync.start().await.unwrap_or_else(|e| error!("Replica sync error: {}", e));
+        }),
+        tokio::spawn(async move {
+            metrics.start().await.unwrap_or_else(|e| error!("Metrics collector error: {}", e));
+        }),
+    ];
+    if config.screening.enabled {
+        let server = screening::ScreeningServer::new(&config, None, Some(alert_cache), history, Arc::clone(&storage))?;
+        handles.push(tokio::spawn(async move {
+            server.start().await.unwrap_or_else(|e| error!("Screening API error: {}", e));
+        }));
+    } else {
+        warn!("⚠️ Read replica without [screening] enabled serves no query API");
+    }
+    
+    host.ready();
+    info!("✅ Read replica is running. Press Ctrl+C to shutdown.");
+    
+    // Pausing means nothing to a replica; only a stop request ends it
+    loop {
+        if host.next_event().await? == ServiceEvent::Stop {
+            break;
+        }
+    }
+    
+    host.stopping();
+    for handle in handles {
+        handle.abort();
+    }
+    storage.flush().await?;
+    
+    info!("👋 Read replica stopped");
+    Ok(EXIT_SUCCESS)
+}
+
+async fn run_command(command: &Command, config: &NodeConfig, config_path: &str, output: OutputFormat) -> Result<()> {
+    match command {
+        Command::VerifyModel { fixtures, tolerance, bless } => {
+            let report = fixtures::verify_model(config, fixtures, *tolerance, *bless).await?;
+            if !report.is_clean() {
+                error!("🚫 {} of {} golden verdicts drifted; do not promote this model/ruleset",
+                       report.drifted.len(), report.total);
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Command::Status => {
+            let status: Report<NodeStatus> = query_node(config, "/status").await?;
+            if output == OutputFormat::Json {
+                return print_json(&status);
+            }
+            
+            let status = status.data;
+            info!("🛡️ Node {} v{}, up {}s", status.node_id, status.version, status.uptime_seconds);
+            info!("   AI detection: {}{}", status.pipeline_fingerprint
+                .map_or("disabled".to_string(), |fingerprint| format!("pipeline {}", fingerprint)),
+                  if status.ready { "" } else { " (warming up)" });
+            info!("   connected peers: {}", status.connected_peers);
+            if status.maintenance.is_normal() {
+                info!("   maintenance: none");
+            } else {
+                info!("   maintenance: {:?}", status.maintenance);
+            }
+            for (subsystem, failure) in &status.degradation.subsystems {
+                warn!("   {} down since {}, {}: {}", subsystem.as_str(), failure.since, failure.fallback, failure.reason);
+            }
+            Ok(())
+        }
+        Command::Stats => {
+            let stats: Report<StatsReport> = query_node(config, "/stats").await?;
+            if output == OutputFormat::Json {
+                return print_json(&stats);
+            }
+            
+            let stats = stats.data;
+            info!("📊 {} threats detected, {} challenges completed, up {}s",
+                  stats.threats_detected, stats.challenges_completed, stats.uptime_seconds);
+            info!("   reputation: {}, energy efficiency: {}, heartbeat every {:.1}s",
+                  stats.reputation_score, stats.energy_efficiency, stats.heartbeat_interval_secs);
+            if let Some(penalty) = &stats.last_penalty {
+                warn!("   ⚔️ {} penalties since startup, last {} at {}", stats.penalties, penalty.kind,
+                      chrono::DateTime::from_timestamp(penalty.at as i64, 0).map_or(penalty.at.to_string(), |at| at.to_rfc3339()));
+            }
+            info!("   DAG: {} nodes ({} processed, {} pending, {} conflicted), {} orphaned, queue {}, parallel efficiency {:.2}%",
+                  stats.dag.total_nodes, stats.dag.processed_nodes, stats.dag.pending_nodes,
+                  stats.dag.conflicted_nodes, stats.dag.orphaned_nodes, stats.dag.queue_size, stats.dag.parallel_efficiency);
+            if let Some(model) = &stats.model {
+                let percent = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{:.1}%", v * 100.0));
+                info!("   model: {} predictions, precision {}, recall {}, cache {}/{} hits",
+                      model.total_predictions, percent(model.precision), percent(model.recall),
+                      model.cache_hits, model.cache_hits + model.cache_misses);
+                info!("   inference: p50 {:.2}ms, p95 {:.2}ms, p99 {:.2}ms over the last {}",
+                      model.inference_latency.p50_ms, model.inference_latency.p95_ms,
+                      model.inference_latency.p99_ms, model.inference_latency.samples);
+            }
+            info!("   energy: {:.2}W, efficiency {}/100, {:.4}kg CO2/h",
+                  stats.energy.power_watts, stats.energy.efficiency_score, stats.energy.carbon_footprint_kg_per_hour);
+            Ok(())
+        }
+        Command::Benchmark => unreachable!("benchmarks run against a started node"),
+        Command::Provision { .. } => unreachable!("provisioning runs before a config is loaded"),
+        Command::Keygen { .. } => unreachable!("keygen runs before a config is loaded"),
+        Command::History { address } => {
+            let history: Report<AddressHistory> = query_node(config, &format!("/history/{}", address)).await?;
+            if output == OutputFormat::Json {
+                return print_json(&history);
+            }
+            
+            let history = history.data;
+            info!("📜 {}: risk {}, {} reports ({} stale), {} verified alerts",
+                  history.risk.address, history.risk.risk_score, history.risk.reports,
+                  history.risk.stale_reports, history.risk.verified_alerts);
+            for entry in &history.reports {
+                info!("   {} {} on chain {} ({}%) in {}{}", format_millis(entry.record.reported_at * 1000),
+                      entry.record.threat_type, entry.record.chain_id, entry.record.confidence,
+                      entry.record.transaction_id, if entry.stale { " [stale pipeline]" } else { "" });
+            }
+            Ok(())
+        }
+        Command::Peers => {
+            let peers: Vec<peers::PeerSummary> = query_node(config, "/peers").await?;
+            if output == OutputFormat::Json {
+                return print_json(&Report::new("peers", peers));
+            }
+            
+            info!("🤝 {} known peers (lowest give/take ratio first):", peers.len());
+            for peer in peers {
+                let status = match (&peer.blocked, peer.free_rider) {
+                    (Some(block), _) => format!("blocked: {}", block.reason),
+                    (None, true) => "free-rider".to_string(),
+                    (None, false) => "ok".to_string(),
+                };
+                info!("   {} {} gave {}/{} useful, took {} ({} deprioritized, {} refused), ratio {:.2} [{}]",
+                      if peer.connected { "●" } else { "○" }, peer.peer_id,
+                      peer.useful_intel_received, peer.intel_received, peer.requests_served,
+                      peer.requests_deprioritized, peer.requests_refused, peer.give_take_ratio, status);
+            }
+            Ok(())
+        }
+        Command::Query { sql } => {
+            let request = query::QueryRequest { sql: sql.clone() };
+            let result: Report<query::QueryResult> = post_node(config, "/query", &request).await?;
+            if output == OutputFormat::Json {
+                return print_json(&result);
+            }
+            
+            let result = result.data;
+            info!("🗃️ {} rows{}, from the storage snapshot of {}", result.rows.len(),
+                  if result.truncated { " (truncated)" } else { "" }, format_millis(result.snapshot_at * 1000));
+            info!("   {}", result.columns.join(" | "));
+            for row in &result.rows {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|cell| match cell {
+                        serde_json::Value::String(text) => text.clone(),
+                        other => other.to_string(),
+                    })
+                    .collect();
+                info!("   {}", cells.join(" | "));
+            }
+            Ok(())
+        }
+        Command::Retention { apply } => {
+            let report: Report<retention::RetentionReport> = if *apply {
+                post_node(config, "/retention/sweep", &()).await?
+            } else {
+                query_node(config, "/retention").await?
+            };
+            if output == OutputFormat::Json {
+                return print_json(&report);
+            }
+            
+            let report = report.data;
+            info!("🧹 Retention {}:", if report.dry_run { "preview" } else { "sweep" });
+            for sweep in &report.sweeps {
+                match sweep.retention_days {
+                    Some(days) => info!("   {:?}: {} entries {} (kept {} days)", sweep.category, sweep.expired,
+                                        if report.dry_run { "would expire" } else { "expired" }, days),
+                    None => info!("   {:?}: kept forever", sweep.category),
+                }
+            }
+            if report.archived > 0 {
+                info!("   {} expiring reports {}", report.archived,
+                      if report.dry_run { "would be archived first" } else { "archived first" });
+            }
+            Ok(())
+        }
+        Command::Review { approve, discard, transaction_ids, resume } => {
+            if *approve || *discard {
+                let request = reporting_guard::ReviewRequest {
+                    transaction_ids: transaction_ids.clone(),
+                    approve: *approve,
+                };
+                let outcome: Report<reporting_guard::ReviewOutcome> = post_node(config, "/reporting/review", &request).await?;
+                if output == OutputFormat::Json && !*resume {
+                    return print_json(&outcome);
+                }
+                let outcome = outcome.data;
+                info!("🧾 {} held reports approved, {} discarded, {} still held",
+                      outcome.approved, outcome.discarded, outcome.held);
+            }
+            let status: Report<reporting_guard::GuardStatus> = if *resume {
+                post_node(config, "/reporting/guard/resume", &()).await?
+            } else {
+                query_node(config, "/reporting/guard").await?
+            };
+            if output == OutputFormat::Json {
+                return print_json(&status);
+            }
+            
+            let status = status.data;
+            match &status.suspension {
+                Some(suspension) => info!("🛑 Auto-reporting suspended since {}: {:.1}% of {} verdicts flagged against a {:.1}% baseline",
+                                          format_millis(suspension.since * 1000), suspension.rate * 100.0,
+                                          suspension.verdicts, suspension.baseline * 100.0),
+                None => info!("▶️ Auto-reporting active"),
+            }
+            info!("   current window: {} verdicts, {:.1}% flagged; baseline {}", status.window_verdicts,
+                  status.window_rate * 100.0, status.baseline_rate.map_or("not established".to_string(), |rate| format!("{:.1}%", rate * 100.0)));
+            info!("   {} reports held for review", status.held.len());
+            for held in &status.held {
+                info!("   {} {} on chain {} ({:.2}) in {}", held.target_address, held.threat_type,
+                      held.chain_id, held.confidence, held.transaction_id);
+            }
+            Ok(())
+        }
+        Command::Archive { address, chain_id, from, to, limit } => {
+            if address.is_none() && chain_id.is_none() && from.is_none() && to.is_none() {
+                let summary: Report<archive::ArchiveSummary> = query_node(config, "/archive").await?;
+                if output == OutputFormat::Json {
+                    return print_json(&summary);
+                }
+                
+                let summary = summary.data;
+                info!("🗄️ Archive at {:?} {}: {} detections in {} segments, {} bytes", summary.backend,
+                      summary.root, summary.detections, summary.segments, summary.bytes);
+                if let (Some(first), Some(last)) = (summary.first_reported_at, summary.last_reported_at) {
+                    info!("   reported from {} to {}", format_millis(first * 1000), format_millis(last * 1000));
+                }
+                return Ok(());
+            }
+            
+            let request = archive::ArchiveQuery {
+                address: address.clone(),
+                chain_id: *chain_id,
+                from: from.as_deref().map(parse_audit_time).transpose()?.map(|ms| ms / 1000),
+                to: to.as_deref().map(parse_audit_time).transpose()?.map(|ms| ms / 1000),
+                limit: *limit,
+            };
+            let result: Report<archive::ArchiveQueryResult> = post_node(config, "/archive/query", &request).await?;
+            if output == OutputFormat::Json {
+                return print_json(&result);
+            }
+            
+            let result = result.data;
+            info!("🗄️ {} archived detections, from {} segments:", result.detections.len(), result.segments_scanned);
+            for entry in &result.detections {
+                let record = &entry.record;
+                info!("   {} {} {} on chain {} ({}%) in {}, {} receipts{}", format_millis(record.reported_at * 1000),
+                      record.target_address, record.threat_type, record.chain_id, record.confidence, record.tx_hash,
+                      entry.receipts.len(), if entry.evidence.is_some() { ", with evidence" } else { "" });
+            }
+            Ok(())
+        }
+        Command::Rewards { claim } => {
+            if *claim {
+                // Like stake changes, signed here rather than by the running node
+                let blockchain = BlockchainClient::new(&config.blockchain).await?;
+                let outcome = rewards::claim(&blockchain).await?;
+                if output == OutputFormat::Json {
+                    return print_json(&Report::new("reward_claim", outcome));
+                }
+                if outcome.tx_hashes.is_empty() {
+                    info!("💸 Nothing to claim");
+                } else {
+                    info!("💸 Claimed {} in {}", outcome.amount, outcome.tx_hashes.join(", "));
+                }
+                return Ok(());
+            }
+            
+            let summary: Report<rewards::RewardSummary> = query_node(config, "/rewards").await?;
+            if output == OutputFormat::Json {
+                return print_json(&summary);
+            }
+            
+            let summary = summary.data;
+            info!("💰 {:?}: {} claimable, {} earned, {} claimed", summary.address, summary.pending,
+                  summary.earned, summary.claimed);
+            for (reward_type, amount) in &summary.earned_by_type {
+                info!("   {}: {}", reward_type, amount);
+            }
+            for entry in &summary.recent {
+                info!("   block {} {:?} {} wei{}", entry.block_number, entry.kind, entry.amount,
+                      entry.reward_type.as_ref().map_or(String::new(), |reward_type| format!(" ({})", reward_type)));
+            }
+            Ok(())
+        }
+        Command::Stake { action } => {
+            let status = match action {
+                Some(action) => {
+                    // Signed here with the node key rather than by the running node, whose admin API
+                    // never moves stake
+                    let blockchain = Arc::new(BlockchainClient::new(&config.blockchain).await?);
+                    let manager = stake::StakeManager::new(&config.node, blockchain)?;
+                    let change = match action {
+                        StakeAction::Add { amount } => manager.add(amount).await?,
+                        StakeAction::Withdraw { amount, .. } => manager.withdraw(amount.as_deref()).await?,
+                    };
+                    if output == OutputFormat::Json {
+                        return print_json(&Report::new("stake_change", change));
+                    }
+                    for tx_hash in &change.tx_hashes {
+                        info!("🪙 Sent {}", tx_hash);
+                    }
+                    change.status
+                }
+                None => {
+                    let status: Report<stake::StakeStatus> = query_node(config, "/stake").await?;
+                    if output == OutputFormat::Json {
+                        return print_json(&status);
+                    }
+                    status.data
+                }
+            };
+            
+            info!("🪙 {:?} ({}): {} staked, {} minimum", status.address,
+                  if status.active { "active" } else { "inactive" },
+                  ethers::utils::format_ether(status.stake), ethers::utils::format_ether(status.minimum));
+            if let Some(at) = status.deactivates_at {
+                warn!("   below the minimum; can be deactivated from {}", format_millis(at * 1000));
+            }
+            Ok(())
+        }
+        Command::Mirror { reset } => {
+            let report: Report<mirror::MirrorReport> = if *reset {
+                post_node(config, "/mirror/reset", &()).await?
+            } else {
+                query_node(config, "/mirror").await?
+            };
+            if output == OutputFormat::Json {
+                return print_json(&report);
+            }
+            
+            let report = report.data;
+            info!("🪞 Shadow pipeline {} against live {} since {}:", report.shadow_fingerprint,
+                  report.live_fingerprint, format_millis(report.since * 1000));
+            info!("   {} compared, {} agreed, {} dropped, {} failed", report.compared, report.agreed, report.dropped, report.failed);
+            info!("   flagged by live only: {}, by shadow only: {}, threat type changed: {}, confidence drift: {}",
+                  report.live_only, report.shadow_only, report.threat_type_changed, report.confidence_drift);
+            if let Some(delta) = report.mean_confidence_delta {
+                info!("   mean confidence delta (shadow - live): {:+.3}", delta);
+            }
+            for divergence in report.divergences.iter().rev().take(20) {
+                info!("   {:?} {} on chain {}: live {} {:.2}, shadow {} {:.2}", divergence.kind, divergence.transaction_id,
+                      divergence.chain_id, divergence.live.threat_type, divergence.live.confidence,
+                      divergence.shadow.threat_type, divergence.shadow.confidence);
+            }
+            if *reset {
+                info!("   a new comparison window is open");
+            }
+            Ok(())
+        }
+        Command::Loadgen {
+            tps, start_tps, ramp_secs, duration_secs, threat_ratio, dependency_density,
+            burst_every_secs, burst_secs, burst_multiplier, seed, status, stop, wait,
+        } => {
+            let mut report: Report<loadgen::LoadReport> = if *stop {
+                post_node(config, "/loadgen/stop", &()).await?
+            } else if *status {
+                query_node(config, "/loadgen").await?
+            } else {
+                let defaults = loadgen::LoadProfile::default();
+                let profile = loadgen::LoadProfile {
+                    target_tps: tps.unwrap_or(defaults.target_tps),
+                    start_tps: start_tps.unwrap_or(defaults.start_tps),
+                    ramp_secs: ramp_secs.unwrap_or(defaults.ramp_secs),
+                    duration_secs: duration_secs.unwrap_or(defaults.duration_secs),
+                    threat_ratio: threat_ratio.unwrap_or(defaults.threat_ratio),
+                    dependency_density: dependency_density.unwrap_or(defaults.dependency_density),
+                    burst_every_secs: burst_every_secs.unwrap_or(defaults.burst_every_secs),
+                    burst_secs: burst_secs.unwrap_or(defaults.burst_secs),
+                    burst_multiplier: burst_multiplier.unwrap_or(defaults.burst_multiplier),
+                    seed: *seed,
+                    ..defaults
+                };
+                post_node(config, "/loadgen", &profile).await?
+            };
+            
+            while *wait && report.data.finished_at.is_none() {
+                tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+                report = query_node(config, "/loadgen").await?;
+                if let Some(sample) = report.data.samples.last() {
+                    info!("🏋️ {}s in: {:.0}/{:.0} TPS, {} in flight, p99 {:.1}ms", sample.at_secs, sample.achieved_tps,
+                          sample.offered_tps, sample.in_flight, sample.latency_p99_ms.unwrap_or(0.0));
+                }
+            }
+            let failed = report.data.finished_at.is_some() && !report.data.sign_off.passed;
+            if output == OutputFormat::Json {
+                print_json(&report)?;
+            } else {
+                let run = &report.data;
+                let state = match run.finished_at {
+                    Some(at) if run.stopped => format!("stopped at {}", format_millis(at * 1000)),
+                    Some(at) => format!("finished at {}", format_millis(at * 1000)),
+                    None => "running".to_string(),
+                };
+                info!("🏋️ Load run {} started at {}, {}:", run.run_id, format_millis(run.started_at * 1000), state);
+                info!("   {:.0} -> {:.0} TPS over {}s, threat ratio {:.2}, dependency density {:.2}", run.profile.start_tps,
+                      run.profile.target_tps, run.profile.ramp_secs, run.profile.threat_ratio, run.profile.dependency_density);
+                info!("   {} submitted, {} judged, {} flagged, {} rejected, {} failed, {} lost",
+                      run.submitted, run.judged, run.flagged, run.rejected, run.failed, run.lost);
+                if let Some(sample) = run.samples.last() {
+                    info!("   last sample at {}s: {:.0} TPS, p50 {:.1}ms, p99 {:.1}ms, {} MB resident", sample.at_secs,
+                          sample.achieved_tps, sample.latency_p50_ms.unwrap_or(0.0), sample.latency_p99_ms.unwrap_or(0.0),
+                          sample.resident_bytes / (1024 * 1024));
+                }
+                let sign_off = &run.sign_off;
+                info!("   error rate {:.4}, memory growth {}, p99 drift {}", sign_off.error_rate,
+                      sign_off.memory_growth_mb_per_hour.map_or("n/a".to_string(), |growth| format!("{:.1} MB/h", growth)),
+                      sign_off.latency_drift_pct.map_or("n/a".to_string(), |drift| format!("{:+.1}%", drift)));
+                match (run.finished_at, sign_off.passed) {
+                    (None, _) => info!("   sign-off is provisional until the run finishes"),
+                    (Some(_), true) => info!("✅ Soak test passed"),
+                    (Some(_), false) => warn!("❌ Soak test failed: {}", sign_off.failures.join("; ")),
+                }
+            }
+            if failed && *wait {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Command::Backtest { save } => {
+            let governor = Arc::new(ResourceGovernor::new(&config.workers, &config.work_tokens)?);
+            let detector = ThreatDetector::new(&config.ai, governor).await?;
+            let backtester = backtest::Backtester::new(config)?;
+            let report = backtester.run(&detector).await?;
+            if *save {
+                let path = backtester.write_report(&report)?;
+                info!("📝 Backtest report written to {}", path.display());
+            }
+            if output == OutputFormat::Json {
+                return print_json(&Report::new("backtest", report));
+            }
+            
+            info!("🕰️ Backtest with pipeline {}: {}/{} exploits caught, {} could not be replayed",
+                  report.pipeline_fingerprint, report.caught, report.replayed, report.exploits - report.replayed);
+            for outcome in &report.outcomes {
+                match (&outcome.error, outcome.caught) {
+                    (Some(error), _) => warn!("   {} (chain {}): not replayed, {}", outcome.name, outcome.chain_id, error),
+                    (None, true) => info!("   {} (chain {}): caught at {:.2}, {} before the drain", outcome.name, outcome.chain_id,
+                                          outcome.confidence.unwrap_or_default(),
+                                          outcome.lead_time_secs.map_or("-".to_string(), |secs| format!("{}s", secs))),
+                    (None, false) => warn!("   {} (chain {}): missed", outcome.name, outcome.chain_id),
+                }
+            }
+            Ok(())
+        }
+        Command::Preflight => {
+            let report = preflight::run(config).await;
+            let passed = report.passed();
+            if output == OutputFormat::Json {
+                print_json(&Report::new("preflight", report))?;
+            } else {
+                info!("🛫 Preflight checks for {}:", config_path);
+                for check in &report.checks {
+                    match check.status {
+                        preflight::CheckStatus::Fail => error!("   [{}] {:<16} {}", check.status.as_str(), check.name, check.detail),
+                        preflight::CheckStatus::Warn => warn!("   [{}] {:<16} {}", check.status.as_str(), check.name, check.detail),
+                        _ => info!("   [{}] {:<16} {}", check.status.as_str(), check.name, check.detail),
+                    }
+                }
+            }
+            if !passed {
+                error!("🚫 Preflight failed; fix the checks above before starting the node");
+                std::process::exit(1);
+            }
+            info!("✅ Preflight passed");
+            Ok(())
+        }
+        Command::DeployContracts { reward_pool_tokens, allow_public_chain } => {
+            let options = deploy::DeployOptions {
+                reward_pool_tokens: *reward_pool_tokens,
+                allow_public_chain: *allow_public_chain,
+            };
+            let deployment = deploy::deploy_contracts(config, config_path, &options).await?;
+            info!("🎉 DAGShield deployed: core {:?}, oracle {:?}, token {:?}",
+                  deployment.shield, deployment.oracle, deployment.token);
+            Ok(())
+        }
+        Command::ReplayAudit { at, events } => {
+            let until_ms = at.as_deref().map(parse_audit_time).transpose()?;
+            let storage = Arc::new(storage::NodeStorage::new(&config.storage).await?);
+            let audit_log = audit::AuditLog::new(storage);
+            
+            if *events {
+                for record in audit_log.events(until_ms)? {
+                    info!("   {} {:?}", format_millis(record.at_ms), record.event);
+                }
+            }
+            
+            let state = audit_log.replay(until_ms)?;
+            let as_of = until_ms.unwrap_or_else(|| chrono::Utc::now().timestamp_millis() as u64);
+            info!("🔎 Node state as of {} ({} events, last at {}):", format_millis(as_of), state.events,
+                  state.as_of_ms.map_or("never".to_string(), format_millis));
+            info!("   threats detected: {}, challenges completed: {}",
+                  state.threats_detected, state.challenges_completed);
+            info!("   reputation: {}, energy efficiency: {}",
+                  state.reputation_score.map_or("-".to_string(), |s| s.to_string()),
+                  state.energy_efficiency.map_or("-".to_string(), |s| s.to_string()));
+            info!("   config: {} (from {})",
+                  state.config_digest.as_deref().unwrap_or("-"), state.config_source.as_deref().unwrap_or("-"));
+            for (peer_id, block) in state.active_blocks(as_of / 1000) {
+                info!("   blocked peer {}: {} (until {})", peer_id, block.reason,
+                      block.until.map_or("permanent".to_string(), |until| format_millis(until * 1000)));
+            }
+            if let Some(config) = &state.config {
+                info!("   config in effect: {}", config);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// A report from the running node's metrics port
+async fn query_node<T: DeserializeOwned>(config: &NodeConfig, path: &str) -> Result<T> {
+    let url = format!("http://127.0.0.1:{}{}", config.metrics.port, path);
+    Ok(reqwest::get(&url).await?.error_for_status()?.json().await?)
+}
+
+/// A report from an admin endpoint of the running node that takes a JSON request
+async fn post_node<T: DeserializeOwned, R: Serialize>(config: &NodeConfig, path: &str, request: &R) -> Result<T> {
+    let url = format!("http://127.0.0.1:{}{}", config.metrics.port, path);
+    let response = reqwest::Client::new().post(&url).json(request).send().await?;
+    if !response.status().is_success() {
+        anyhow::bail!("{} failed with {}: {}", path, response.status(), response.text().await?);
+    }
+    Ok(response.json().await?)
+}
+
+fn print_json<T: Serialize>(report: &Report<T>) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(report)?);
     Ok(())
 }
 
-async fn run_benchmark(node: &Arc<DAGShieldNode>) -> Result<()> {
+/// RFC 3339 or unix seconds, as unix milliseconds
+fn parse_audit_time(at: &str) -> Result<u64> {
+    if let Ok(secs) = at.parse::<u64>() {
+        return Ok(secs * 1000);
+    }
+    let time = chrono::DateTime::parse_from_rfc3339(at)
+        .map_err(|e| anyhow::anyhow!("Invalid time {}: {}", at, e))?;
+    Ok(time.timestamp_millis() as u64)
+}
+
+fn format_millis(at_ms: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(at_ms as i64)
+        .map_or_else(|| at_ms.to_string(), |time| time.to_rfc3339())
+}
+
+async fn run_benchmark(node: &Arc<DAGShieldNode>) -> Result<BenchmarkReport> {
     use std::time::Instant;
     
     info!("🔬 Starting DAGShield node benchmarks...");
     
-    // Benchmark DAG processing
+    // Benchmark DAG processing, once per dependency topology
     let start = Instant::now();
-    let dag_results = node.benchmark_dag_processing(1000).await?;
+    let shapes = node.benchmark_dag_processing(1000, &DagShape::standard()).await?;
     let dag_duration = start.elapsed();
+    let dag_transactions = 1000 * shapes.len();
+    let dag_efficiency = shapes.iter().map(|shape| shape.parallel_efficiency).sum::<f64>() / shapes.len().max(1) as f64;
     
     info!("📊 DAG Processing Benchmark:");
-    info!("   Transactions: 1000");
+    info!("   Transactions: {}", dag_transactions);
     info!("   Duration: {:?}", dag_duration);
-    info!("   TPS: {:.2}", 1000.0 / dag_duration.as_secs_f64());
-    info!("   Parallel efficiency: {:.2}%", dag_results.parallel_efficiency);
+    info!("   TPS: {:.2}", dag_transactions as f64 / dag_duration.as_secs_f64());
+    for shape in &shapes {
+        info!("   {}: {:.2} TPS, {:.2}% parallel efficiency, critical path {}, {} workers at peak",
+              shape.shape.name(), shape.throughput_tps, shape.parallel_efficiency, shape.critical_path, shape.peak_busy_workers);
+    }
+    info!("   Parallel efficiency: {:.2}%", dag_efficiency);
     
     // Benchmark AI threat detection
     let start = Instant::now();
@@ -130,6 +1123,17 @@ async fn run_benchmark(node: &Arc<DAGShieldNode>) -> Result<()> {
     info!("   Accuracy: {:.2}%", ai_results.accuracy);
     info!("   Avg latency: {:.2}ms", ai_results.avg_latency_ms);
     
+    // Benchmark evasion of disguised malicious samples
+    let robustness = node.benchmark_robustness(100).await?;
+    
+    info!("🥷 Adversarial Robustness Benchmark:");
+    info!("   Detected before mutation: {}/{}", robustness.detected, robustness.samples);
+    for outcome in &robustness.mutations {
+        info!("   {}: {}/{} evaded ({:.2}%)", outcome.mutation.as_str(), outcome.evaded, outcome.attempts,
+              outcome.evasion_rate * 100.0);
+    }
+    info!("   Overall evasion rate: {:.2}%", robustness.evasion_rate * 100.0);
+    
     // Benchmark energy efficiency
     let energy_stats = node.get_energy_stats().await?;
     info!("⚡ Energy Efficiency:");
@@ -137,5 +1141,21 @@ async fn run_benchmark(node: &Arc<DAGShieldNode>) -> Result<()> {
     info!("   Efficiency score: {}/100", energy_stats.efficiency_score);
     info!("   Carbon footprint: {:.4}kg CO2/h", energy_stats.carbon_footprint_kg_per_hour);
     
-    Ok(())
+    Ok(BenchmarkReport {
+        dag: DagBenchmark {
+            transactions: dag_transactions,
+            duration_ms: dag_duration.as_secs_f64() * 1000.0,
+            tps: dag_transactions as f64 / dag_duration.as_secs_f64(),
+            parallel_efficiency: dag_efficiency,
+            shapes,
+        },
+        ai: AiBenchmark {
+            samples: 100,
+            duration_ms: ai_duration.as_secs_f64() * 1000.0,
+            accuracy: ai_results.accuracy,
+            avg_latency_ms: ai_results.avg_latency_ms,
+        },
+        robustness,
+        energy: energy_stats.into(),
+    })
 }
@@ -0,0 +1,216 @@
+//! Operator maintenance controls: pausing parts of the pipeline and draining to a checkpoint
+//!
+//! Each stage pauses independently. Paused ingestion rejects new transactions, paused reporting
+//! keeps detecting but holds flagged results back until it resumes, and paused voting defers
+//! governance votes. A drain stops ingestion, waits for the DAG to empty and flushes everything
+//! to disk, leaving the node safe to snapshot or stop.
+
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{Notify, RwLock};
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    /// The whole heartbeat: detection, challenges and stats
+    Processing,
+    Ingestion,
+    Reporting,
+    Voting,
+}
+
+impl Stage {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "processing" => Some(Self::Processing),
+            "ingestion" => Some(Self::Ingestion),
+            "reporting" => Some(Self::Reporting),
+            "voting" => Some(Self::Voting),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DrainState {
+    #[default]
+    Idle,
+    Draining,
+    /// Drained and checkpointed; ingestion stays paused until resumed
+    Drained,
+}
+
+/// The current maintenance mode, as surfaced in `/health`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceMode {
+    pub processing_paused: bool,
+    pub ingestion_paused: bool,
+    pub reporting_paused: bool,
+    pub voting_paused: bool,
+    pub drain: DrainState,
+    pub last_checkpoint_at: Option<u64>,
+}
+
+impl MaintenanceMode {
+    pub fn is_normal(&self) -> bool {
+        !(self.processing_paused || self.ingestion_paused || self.reporting_paused || self.voting_paused)
+            && self.drain == DrainState::Idle
+    }
+}
+
+#[derive(Default)]
+pub struct MaintenanceControl {
+    processing: AtomicBool,
+    ingestion: AtomicBool,
+    reporting: AtomicBool,
+    voting: AtomicBool,
+    drain: RwLock<DrainState>,
+    /// Unix time of the last completed drain, 0 if none
+    last_checkpoint_at: AtomicU64,
+    drain_requested: Notify,
+}
+
+impl MaintenanceControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    fn flag(&self, stage: Stage) -> &AtomicBool {
+        match stage {
+            Stage::Processing => &self.processing,
+            Stage::Ingestion => &self.ingestion,
+            Stage::Reporting => &self.reporting,
+            Stage::Voting => &self.voting,
+        }
+    }
+    
+    /// Returns whether the stage was running before
+    pub fn pause(&self, stage: Stage) -> bool {
+        let changed = !self.flag(stage).swap(true, Ordering::SeqCst);
+        if changed {
+            info!("⏸️ Paused {:?}", stage);
+        }
+        changed
+    }
+    
+    /// Returns whether the stage was paused before
+    pub fn resume(&self, stage: Stage) -> bool {
+        let changed = self.flag(stage).swap(false, Ordering::SeqCst);
+        if changed {
+            info!("▶️ Resumed {:?}", stage);
+        }
+        changed
+    }
+    
+    pub fn is_paused(&self, stage: Stage) -> bool {
+        self.flag(stage).load(Ordering::SeqCst)
+    }
+    
+    /// Resume every stage and leave drained mode
+    pub async fn resume_all(&self) {
+        for stage in [Stage::Processing, Stage::Ingestion, Stage::Reporting, Stage::Voting] {
+            self.resume(stage);
+        }
+        let mut drain = self.drain.write().await;
+        if *drain == DrainState::Drained {
+            *drain = DrainState::Idle;
+        }
+    }
+    
+    /// Ask the node to drain; returns false if a drain is already running
+    pub async fn request_drain(&self) -> bool {
+        let mut drain = self.drain.write().await;
+        if *drain == DrainState::Draining {
+            return false;
+        }
+        *drain = DrainState::Draining;
+        self.pause(Stage::Ingestion);
+        self.drain_requested.notify_one();
+        true
+    }
+    
+    /// Resolves when an operator requests a drain
+    pub async fn drain_requested(&self) {
+        self.drain_requested.notified().await
+    }
+    
+    /// Record a completed drain
+    pub async fn drained(&self, checkpoint_at: u64) {
+        self.last_checkpoint_at.store(checkpoint_at, Ordering::SeqCst);
+        *self.drain.write().await = DrainState::Drained;
+    }
+    
+    /// Record a drain that failed; ingestion stays paused for the operator to decide
+    pub async fn drain_failed(&self) {
+        *self.drain.write().await = DrainState::Idle;
+    }
+    
+    pub async fn mode(&self) -> MaintenanceMode {
+        let checkpoint = self.last_checkpoint_at.load(Ordering::SeqCst);
+        MaintenanceMode {
+            processing_paused: self.is_paused(Stage::Processing),
+            ingestion_paused: self.is_paused(Stage::Ingestion),
+            reporting_paused: self.is_paused(Stage::Reporting),
+            voting_paused: self.is_paused(Stage::Voting),
+            drain: *self.drain.read().await,
+            last_checkpoint_at: (checkpoint > 0).then_some(checkpoint),
+        }
+    }
+}
+
+/// `GET /maintenance`, `POST /maintenance/pause/:stage`, `POST /maintenance/resume[/:stage]`
+/// and `POST /maintenance/drain`, each answering with the resulting mode
+pub fn admin_routes(control: Arc<MaintenanceControl>) -> Router {
+    let status = Arc::clone(&control);
+    let pause = Arc::clone(&control);
+    let resume = Arc::clone(&control);
+    let resume_all = Arc::clone(&control);
+    let drain = control;
+    
+    Router::new()
+        .route("/maintenance", get(move || async move { Json(status.mode().await) }))
+        .route(
+            "/maintenance/pause/:stage",
+            post(move |Path(stage): Path<String>| async move {
+                let Some(stage) = Stage::parse(&stage) else {
+                    return StatusCode::NOT_FOUND.into_response();
+                };
+                pause.pause(stage);
+                Json(pause.mode().await).into_response()
+            }),
+        )
+        .route(
+            "/maintenance/resume/:stage",
+            post(move |Path(stage): Path<String>| async move {
+                let Some(stage) = Stage::parse(&stage) else {
+                    return StatusCode::NOT_FOUND.into_response();
+                };
+                resume.resume(stage);
+                Json(resume.mode().await).into_response()
+            }),
+        )
+        .route(
+            "/maintenance/resume",
+            post(move || async move {
+                resume_all.resume_all().await;
+                Json(resume_all.mode().await)
+            }),
+        )
+        .route(
+            "/maintenance/drain",
+            post(move || async move {
+                if !drain.request_drain().await {
+                    return (StatusCode::CONFLICT, Json(drain.mode().await)).into_response();
+                }
+                (StatusCode::ACCEPTED, Json(drain.mode().await)).into_response()
+            }),
+        )
+}
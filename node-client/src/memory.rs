@@ -0,0 +1,158 @@
+//! Process-wide memory budget with allocator statistics and cache pressure callbacks
+
+use anyhow::Result;
+use prometheus::{IntGauge, IntGaugeVec, Opts};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::config::MemoryConfig;
+
+/// A subsystem holding a large in-memory cache that can be shrunk under pressure
+pub trait MemoryConsumer: Send + Sync {
+    fn subsystem(&self) -> &'static str;
+    
+    /// Approximate heap bytes held by the subsystem's caches
+    fn memory_usage(&self) -> usize;
+    
+    /// Drop roughly `fraction` (0.0-1.0) of the cache; returns the approximate bytes freed
+    fn shrink(&self, fraction: f32) -> usize;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllocatorStats {
+    pub allocated_bytes: u64,
+    pub resident_bytes: u64,
+}
+
+pub struct MemoryBudget {
+    config: MemoryConfig,
+    consumers: RwLock<Vec<Arc<dyn MemoryConsumer>>>,
+    allocated_gauge: IntGauge,
+    resident_gauge: IntGauge,
+    budget_gauge: IntGauge,
+    subsystem_gauge: IntGaugeVec,
+    shrink_events: IntGauge,
+}
+
+impl MemoryBudget {
+    pub fn new(config: &MemoryConfig) -> Result<Self> {
+        let allocated_gauge = IntGauge::new("dagshield_memory_allocated_bytes", "Bytes allocated by the application")?;
+        let resident_gauge = IntGauge::new("dagshield_memory_resident_bytes", "Resident memory of the node process")?;
+        let budget_gauge = IntGauge::new("dagshield_memory_budget_bytes", "Configured process memory budget")?;
+        let subsystem_gauge = IntGaugeVec::new(
+            Opts::new("dagshield_memory_subsystem_bytes", "Approximate cache memory per subsystem"),
+            &["subsystem"],
+        )?;
+        let shrink_events = IntGauge::new("dagshield_memory_pressure_shrinks", "Cache shrink rounds triggered by memory pressure")?;
+        
+        for collector in [&allocated_gauge, &resident_gauge, &budget_gauge, &shrink_events] {
+            prometheus::register(Box::new(collector.clone()))?;
+        }
+        prometheus::register(Box::new(subsystem_gauge.clone()))?;
+        
+        budget_gauge.set((config.max_memory_mb * 1024 * 1024) as i64);
+        
+        Ok(Self {
+            config: config.clone(),
+            consumers: RwLock::new(Vec::new()),
+            allocated_gauge,
+            resident_gauge,
+            budget_gauge,
+            subsystem_gauge,
+            shrink_events,
+        })
+    }
+    
+    pub async fn register(&self, consumer: Arc<dyn MemoryConsumer>) {
+        debug!("🧠 Registered memory consumer: {}", consumer.subsystem());
+        self.consumers.write().await.push(consumer);
+    }
+    
+    pub async fn start(&self) -> Result<()> {
+        if self.config.max_memory_mb == 0 {
+            info!("🧠 Memory budget disabled");
+            return Ok(());
+        }
+        
+        info!("🧠 Enforcing memory budget of {} MB (pressure at {:.0}%)",
+              self.config.max_memory_mb, self.config.pressure_threshold * 100.0);
+        
+        let mut check_interval = tokio::time::interval(
+            Duration::from_secs(self.config.check_interval_secs)
+        );
+        
+        loop {
+            check_interval.tick().await;
+            self.check_pressure().await;
+        }
+    }
+    
+    pub async fn check_pressure(&self) {
+        let stats = allocator_stats();
+        self.allocated_gauge.set(stats.allocated_bytes as i64);
+        self.resident_gauge.set(stats.resident_bytes as i64);
+        
+        let consumers = self.consumers.read().await;
+        for consumer in consumers.iter() {
+            self.subsystem_gauge
+                .with_label_values(&[consumer.subsystem()])
+                .set(consumer.memory_usage() as i64);
+        }
+        
+        let budget = self.budget_gauge.get() as u64;
+        let threshold = (budget as f64 * self.config.pressure_threshold as f64) as u64;
+        if stats.resident_bytes < threshold {
+            return;
+        }
+        
+        warn!("🧠 Memory pressure: {} MB resident of {} MB budget, shrinking caches",
+              stats.resident_bytes / (1024 * 1024), budget / (1024 * 1024));
+        self.shrink_events.inc();
+        
+        // Shrink the largest caches first, harder the further over the threshold we are
+        let overshoot = (stats.resident_bytes - threshold) as f32 / budget.max(1) as f32;
+        let fraction = (self.config.shrink_fraction + overshoot).min(1.0);
+        
+        let mut ordered: Vec<_> = consumers.iter().collect();
+        ordered.sort_by_key(|c| std::cmp::Reverse(c.memory_usage()));
+        
+        let mut freed = 0;
+        for consumer in ordered {
+            freed += consumer.shrink(fraction);
+        }
+        
+        info!("🧠 Released ~{} KB from caches", freed / 1024);
+    }
+}
+
+#[cfg(feature = "jemalloc")]
+pub fn allocator_stats() -> AllocatorStats {
+    use tikv_jemalloc_ctl::{epoch, stats};
+    
+    // Statistics are cached by jemalloc until the epoch is advanced
+    if epoch::advance().is_err() {
+        return AllocatorStats::default();
+    }
+    
+    AllocatorStats {
+        allocated_bytes: stats::allocated::read().unwrap_or(0) as u64,
+        resident_bytes: stats::resident::read().unwrap_or(0) as u64,
+    }
+}
+
+#[cfg(not(feature = "jemalloc"))]
+pub fn allocator_stats() -> AllocatorStats {
+    use sysinfo::{Pid, System};
+    
+    let mut system = System::new();
+    let pid = Pid::from(std::process::id() as usize);
+    system.refresh_process(pid);
+    
+    let resident = system.process(pid).map(|p| p.memory()).unwrap_or(0);
+    AllocatorStats {
+        allocated_bytes: resident,
+        resident_bytes: resident,
+    }
+}
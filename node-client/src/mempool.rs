@@ -0,0 +1,197 @@
+//! Pending-transaction scanning, so threats are flagged before they are mined
+//!
+//! Pending transactions are followed over an `eth_subscribe` websocket when `ws_url` is set,
+//! and otherwise by polling the node's `txpool_content`. Each one enters the DAG like any other
+//! transaction, on its way to detection. A busy mempool far outpaces the detector, so intake is
+//! capped per second and plain transfers can be skipped.
+
+use anyhow::{bail, Result};
+use ethers::providers::{Http, Middleware, Provider, StreamExt, Ws};
+use ethers::types::{Transaction as PendingTransaction, H256};
+use ethers::utils::get_contract_address;
+use prometheus::{IntCounterVec, Opts};
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::config::{BlockchainConfig, MempoolConfig};
+use crate::dag::{Backpressure, DAGProcessor, StorageSlot, Transaction};
+use crate::metrics::register_once;
+
+pub struct MempoolScanner {
+    config: MempoolConfig,
+    chain_id: u64,
+    rpc_url: String,
+    dag_processor: Arc<DAGProcessor>,
+    /// Recently seen hashes, oldest first, so a transaction is only scanned once
+    seen: parking_lot::Mutex<(HashSet<H256>, VecDeque<H256>)>,
+    /// Start of the current one-second intake window and transactions taken in it
+    window: parking_lot::Mutex<(Instant, usize)>,
+    transactions: IntCounterVec,
+}
+
+impl MempoolScanner {
+    pub fn new(config: &MempoolConfig, blockchain: &BlockchainConfig, dag_processor: Arc<DAGProcessor>) -> Result<Self> {
+        if config.max_per_second == 0 {
+            bail!("mempool.max_per_second must be positive");
+        }
+        static TRANSACTIONS: OnceLock<IntCounterVec> = OnceLock::new();
+        let transactions = register_once(&TRANSACTIONS, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_mempool_transactions_total", "Pending transactions seen by the mempool scanner, by outcome"),
+                &["outcome"],
+            )
+        })?;
+        
+        Ok(Self {
+            config: config.clone(),
+            chain_id: blockchain.chain_id,
+            rpc_url: blockchain.rpc_url.clone(),
+            dag_processor,
+            seen: parking_lot::Mutex::new((HashSet::new(), VecDeque::new())),
+            window: parking_lot::Mutex::new((Instant::now(), 0)),
+            transactions,
+        })
+    }
+    
+    /// Follow the mempool until the task is aborted, reconnecting when the subscription drops
+    pub async fn start(&self) -> Result<()> {
+        if self.config.ws_url.is_empty() {
+            return self.poll_txpool().await;
+        }
+        
+        let mut backoff = Duration::from_secs(1);
+        loop {
+            match self.subscribe().await {
+                Ok(()) => {
+                    warn!("⚠️ Pending transaction subscription ended, resubscribing");
+                    backoff = Duration::from_secs(1);
+                }
+                Err(e) => {
+                    warn!("⚠️ Pending transaction subscription failed: {:#}, retrying in {:?}", e, backoff);
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(Duration::from_secs(60));
+                }
+            }
+        }
+    }
+    
+    async fn subscribe(&self) -> Result<()> {
+        let provider = Provider::<Ws>::connect(&self.config.ws_url).await?;
+        let mut hashes = provider.subscribe_pending_txs().await?;
+        info!("🕳️ Scanning pending transactions on chain {} over {}", self.chain_id, self.config.ws_url);
+        
+        while let Some(hash) = hashes.next().await {
+            if !self.first_sighting(hash) {
+                continue;
+            }
+            // `None` when dropped or already mined by the time it is fetched
+            match provider.get_transaction(hash).await {
+                Ok(Some(pending)) => self.scan(pending).await,
+                Ok(None) => self.transactions.with_label_values(&["gone"]).inc(),
+                Err(e) => debug!("Pending transaction {:?} not fetched: {}", hash, e),
+            }
+        }
+        Ok(())
+    }
+    
+    /// For nodes without websockets: poll the pending pool over HTTP
+    async fn poll_txpool(&self) -> Result<()> {
+        let provider = Provider::<Http>::try_from(self.rpc_url.as_str())?;
+        info!("🕳️ Scanning pending transactions on chain {} by polling txpool every {}ms",
+              self.chain_id, self.config.poll_interval_ms);
+        
+        let mut interval = tokio::time::interval(Duration::from_millis(self.config.poll_interval_ms));
+        loop {
+            interval.tick().await;
+            let content = match provider.txpool_content().await {
+                Ok(content) => content,
+                Err(e) => {
+                    warn!("⚠️ txpool_content failed: {}", e);
+                    continue;
+                }
+            };
+            for pending in content.pending.into_values().flat_map(|by_nonce| by_nonce.into_values()) {
+                if self.first_sighting(pending.hash) {
+                    self.scan(pending).await;
+                }
+            }
+        }
+    }
+    
+    async fn scan(&self, pending: PendingTransaction) {
+        if self.config.skip_plain_transfers && pending.input.is_empty() && pending.to.is_some() {
+            self.transactions.with_label_values(&["skipped"]).inc();
+            return;
+        }
+        if !self.take_slot() {
+            self.transactions.with_label_values(&["rate_limited"]).inc();
+            return;
+        }
+        
+        let id = format!("{:?}", pending.hash);
+        match self.dag_processor.add_transaction(self.to_transaction(&pending)).await {
+            Ok(()) => self.transactions.with_label_values(&["scanned"]).inc(),
+            // The pending stream cannot be slowed; what the DAG has no room for goes unscanned
+            Err(e) if e.is::<Backpressure>() => self.transactions.with_label_values(&["backpressure"]).inc(),
+            Err(e) => {
+                debug!("Pending transaction {} not scanned: {:#}", id, e);
+                self.transactions.with_label_values(&["rejected"]).inc();
+            }
+        }
+    }
+    
+    fn to_transaction(&self, pending: &PendingTransaction) -> Transaction {
+        // A pending contract creation has no receipt yet; its address follows from sender and nonce
+        let target = pending
+            .to
+            .unwrap_or_else(|| get_contract_address(pending.from, pending.nonce));
+        let target = format!("{:?}", target);
+        Transaction {
+            id: format!("{:?}", pending.hash),
+            from: format!("{:?}", pending.from),
+            to: target.clone(),
+            target_address: target,
+            chain_id: pending.chain_id.map_or(self.chain_id, |chain_id| chain_id.as_u64()),
+            data: pending.input.to_vec(),
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            dependencies: vec![],
+            blob_versioned_hashes: vec![],
+            value: pending.value,
+            logs: vec![],
+            origin: None,
+            nonce: Some(pending.nonce.as_u64()),
+            storage_slots: pending.access_list.as_ref().map(StorageSlot::from_access_list).unwrap_or_default(),
+            priority_fee: pending.max_priority_fee_per_gas.or(pending.gas_price).unwrap_or_default(),
+            peer_synced: false,
+        }
+    }
+    
+    fn first_sighting(&self, hash: H256) -> bool {
+        let mut seen = self.seen.lock();
+        let (set, order) = &mut *seen;
+        if !set.insert(hash) {
+            return false;
+        }
+        order.push_back(hash);
+        while order.len() > self.config.seen_capacity {
+            if let Some(oldest) = order.pop_front() {
+                set.remove(&oldest);
+            }
+        }
+        true
+    }
+    
+    fn take_slot(&self) -> bool {
+        let mut window = self.window.lock();
+        if window.0.elapsed() >= Duration::from_secs(1) {
+            *window = (Instant::now(), 0);
+        }
+        if window.1 >= self.config.max_per_second {
+            return false;
+        }
+        window.1 += 1;
+        true
+    }
+}
@@ -0,0 +1,733 @@
+//! Prometheus metrics and per-stage pipeline latency instrumentation
+
+use anyhow::Result;
+use axum::{extract::Path, http::header, http::HeaderMap, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use dashmap::DashMap;
+use prometheus::proto::{MetricFamily, MetricType};
+use prometheus::core::Collector;
+use prometheus::{Encoder, GaugeVec, HistogramOpts, HistogramVec, IntGaugeVec, Opts, TextEncoder};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+use crate::ai::registry::{self, ModelRegistry};
+use crate::ai::ThreatDetector;
+use crate::config::MetricsConfig;
+use crate::dag::{self, DAGProcessor};
+use crate::degradation::{Degradation, DegradationLevel};
+use crate::gossip::{self, GossipAuth};
+use crate::maintenance::{self, MaintenanceControl};
+use crate::peers::PeerLedger;
+use crate::query::{self, QueryEngine};
+use crate::archive::{self, Archiver};
+use crate::chain_watch::{self, ChainWatch};
+use crate::commitment::{self, EpochCommitter};
+use crate::loadgen::{self, LoadGenerator};
+use crate::mirror::{self, TrafficMirror};
+use crate::retention::{self, RetentionJanitor};
+use crate::replica;
+use crate::reporting_guard::{self, ReportingGuard};
+use crate::rewards::{self, RewardTracker};
+use crate::stake::{self, StakeManager};
+use crate::status::{Report, StatusSource};
+use crate::storage::NodeStorage;
+use crate::watchlist::{self, Watchlists};
+
+const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+const STAGE_LATENCY_METRIC: &str = "dagshield_pipeline_stage_latency_seconds";
+
+/// Latency buckets from 100µs to 30s, tuned for the spread between DAG scheduling and on-chain reporting
+const STAGE_LATENCY_BUCKETS: &[f64] = &[
+    0.0001, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PipelineStage {
+    Ingest,
+    Validation,
+    SchedulingWait,
+    Execution,
+    Detection,
+    Reporting,
+}
+
+impl PipelineStage {
+    pub const ALL: [PipelineStage; 6] = [
+        PipelineStage::Ingest,
+        PipelineStage::Validation,
+        PipelineStage::SchedulingWait,
+        PipelineStage::Execution,
+        PipelineStage::Detection,
+        PipelineStage::Reporting,
+    ];
+    
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PipelineStage::Ingest => "ingest",
+            PipelineStage::Validation => "validation",
+            PipelineStage::SchedulingWait => "scheduling_wait",
+            PipelineStage::Execution => "execution",
+            PipelineStage::Detection => "detection",
+            PipelineStage::Reporting => "reporting",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Exemplar {
+    trace_id: String,
+    value: f64,
+    timestamp: f64,
+}
+
+/// Per-stage latency histograms with the most recent exemplar trace ID kept for each bucket
+pub struct PipelineLatency {
+    histogram: HistogramVec,
+    exemplars: DashMap<(&'static str, usize), Exemplar>,
+}
+
+impl PipelineLatency {
+    fn new() -> Self {
+        let histogram = HistogramVec::new(
+            HistogramOpts::new(STAGE_LATENCY_METRIC, "Latency of each DAGShield pipeline stage")
+                .buckets(STAGE_LATENCY_BUCKETS.to_vec()),
+            &["stage"],
+        )
+        .expect("valid stage latency histogram");
+        
+        // Registration only fails on duplicates, which the OnceLock rules out
+        let _ = prometheus::register(Box::new(histogram.clone()));
+        
+        // Pre-create every stage so dashboards show zeroed series before traffic arrives
+        for stage in PipelineStage::ALL {
+            histogram.with_label_values(&[stage.as_str()]);
+        }
+        
+        Self {
+            histogram,
+            exemplars: DashMap::new(),
+        }
+    }
+    
+    pub fn observe(&self, stage: PipelineStage, trace_id: &str, duration: Duration) {
+        let value = duration.as_secs_f64();
+        self.histogram.with_label_values(&[stage.as_str()]).observe(value);
+        
+        let bucket = STAGE_LATENCY_BUCKETS
+            .iter()
+            .position(|&upper| value <= upper)
+            .unwrap_or(STAGE_LATENCY_BUCKETS.len());
+        
+        self.exemplars.insert((stage.as_str(), bucket), Exemplar {
+            trace_id: trace_id.to_string(),
+            value,
+            timestamp: chrono::Utc::now().timestamp_millis() as f64 / 1000.0,
+        });
+    }
+    
+    /// Start timing a stage; the elapsed time is recorded when the timer is dropped
+    pub fn start(&self, stage: PipelineStage, trace_id: &str) -> StageTimer<'_> {
+        StageTimer {
+            latency: self,
+            stage,
+            trace_id: trace_id.to_string(),
+            started: Instant::now(),
+        }
+    }
+    
+    fn exemplar(&self, stage: &str, bucket: usize) -> Option<Exemplar> {
+        PipelineStage::ALL
+            .iter()
+            .find(|s| s.as_str() == stage)
+            .and_then(|s| self.exemplars.get(&(s.as_str(), bucket)).map(|e| e.clone()))
+    }
+}
+
+pub struct StageTimer<'a> {
+    latency: &'a PipelineLatency,
+    stage: PipelineStage,
+    trace_id: String,
+    started: Instant,
+}
+
+impl Drop for StageTimer<'_> {
+    fn drop(&mut self) {
+        self.latency.observe(self.stage, &self.trace_id, self.started.elapsed());
+    }
+}
+
+/// Process-wide pipeline latency instrumentation
+pub fn pipeline_latency() -> &'static PipelineLatency {
+    static LATENCY: OnceLock<PipelineLatency> = OnceLock::new();
+    LATENCY.get_or_init(PipelineLatency::new)
+}
+
+/// Depth of the DAG's queues: `pending` for unprocessed transactions, `ready` for those
+/// waiting only for a worker
+pub fn dag_queue_depth() -> &'static IntGaugeVec {
+    static DEPTH: OnceLock<IntGaugeVec> = OnceLock::new();
+    DEPTH.get_or_init(|| {
+        let depth = IntGaugeVec::new(
+            Opts::new("dagshield_dag_queue_depth", "Transactions in each DAG queue"),
+            &["queue"],
+        )
+        .expect("valid DAG queue depth gauge");
+        // Registration only fails on duplicates, which the OnceLock rules out
+        let _ = prometheus::register(Box::new(depth.clone()));
+        for queue in ["pending", "ready"] {
+            depth.with_label_values(&[queue]);
+        }
+        depth
+    })
+}
+
+/// Build a collector and register it with the default registry, once per process.
+///
+/// Components can be rebuilt in-process, e.g. in tests; a rebuilt one gets the collector
+/// registered first, so its samples keep reaching `/metrics`. Errors, such as two collectors
+/// sharing a name, are returned instead of leaving the collector unexported.
+pub fn register_once<C>(cell: &'static OnceLock<C>, build: impl FnOnce() -> prometheus::Result<C>) -> Result<C>
+where
+    C: Collector + Clone + 'static,
+{
+    static REGISTERING: Mutex<()> = Mutex::new(());
+    let _registering = REGISTERING.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(collector) = cell.get() {
+        return Ok(collector.clone());
+    }
+    let collector = build()?;
+    prometheus::register(Box::new(collector.clone()))?;
+    Ok(cell.get_or_init(|| collector).clone())
+}
+
+pub struct MetricsCollector {
+    config: MetricsConfig,
+    /// Whether the attached admin endpoints are served; `/metrics` and `/health` always are
+    admin_api: bool,
+    peer_ledger: OnceLock<Arc<PeerLedger>>,
+    dag_processor: OnceLock<Arc<DAGProcessor>>,
+    watchlists: OnceLock<Arc<Watchlists>>,
+    /// Storage and the bearer token replicas must present
+    snapshot_source: OnceLock<(Arc<NodeStorage>, String)>,
+    maintenance: OnceLock<Arc<MaintenanceControl>>,
+    degradation: OnceLock<Arc<Degradation>>,
+    status_source: OnceLock<Arc<StatusSource>>,
+    model_registry: OnceLock<(Arc<ModelRegistry>, Arc<ThreatDetector>)>,
+    query_engine: OnceLock<Arc<QueryEngine>>,
+    epoch_committer: OnceLock<Arc<EpochCommitter>>,
+    retention: OnceLock<Arc<RetentionJanitor>>,
+    mirror: OnceLock<Arc<TrafficMirror>>,
+    gossip_auth: OnceLock<Arc<GossipAuth>>,
+    chain_watch: OnceLock<Arc<ChainWatch>>,
+    archiver: OnceLock<Arc<Archiver>>,
+    reporting_guard: OnceLock<Arc<ReportingGuard>>,
+    rewards: OnceLock<Arc<RewardTracker>>,
+    stake: OnceLock<Arc<StakeManager>>,
+    loadgen: OnceLock<Arc<LoadGenerator>>,
+    detector: OnceLock<Arc<ThreatDetector>>,
+}
+
+impl MetricsCollector {
+    pub async fn new(config: &MetricsConfig, admin_api: bool) -> Result<Self> {
+        // Make sure the stage histograms and queue gauges are registered before the first scrape
+        pipeline_latency();
+        dag_queue_depth();
+        
+        Ok(Self {
+            config: config.clone(),
+            admin_api,
+            peer_ledger: OnceLock::new(),
+            dag_processor: OnceLock::new(),
+            watchlists: OnceLock::new(),
+            snapshot_source: OnceLock::new(),
+            maintenance: OnceLock::new(),
+            degradation: OnceLock::new(),
+            status_source: OnceLock::new(),
+            model_registry: OnceLock::new(),
+            query_engine: OnceLock::new(),
+            epoch_committer: OnceLock::new(),
+            retention: OnceLock::new(),
+            mirror: OnceLock::new(),
+            gossip_auth: OnceLock::new(),
+            chain_watch: OnceLock::new(),
+            archiver: OnceLock::new(),
+            reporting_guard: OnceLock::new(),
+            rewards: OnceLock::new(),
+            stake: OnceLock::new(),
+            loadgen: OnceLock::new(),
+            detector: OnceLock::new(),
+        })
+    }
+    
+    /// Serve the peers admin view (`/peers`) alongside the metrics
+    pub fn attach_peer_ledger(&self, ledger: Arc<PeerLedger>) {
+        let _ = self.peer_ledger.set(ledger);
+    }
+    
+    /// Serve DAG checkpoints (`/dag/checkpoint`, `/dag/checkpoints/:height`) and transaction
+    /// status (`/dag/transactions/:id`) alongside the metrics
+    pub fn attach_dag_processor(&self, processor: Arc<DAGProcessor>) {
+        let _ = self.dag_processor.set(processor);
+    }
+    
+    /// Serve watchlist management (`/watchlist`) alongside the metrics
+    pub fn attach_watchlists(&self, watchlists: Arc<Watchlists>) {
+        let _ = self.watchlists.set(watchlists);
+    }
+    
+    /// Serve storage snapshots to read replicas (`/replication/snapshot`) alongside the metrics
+    pub fn attach_snapshot_source(&self, storage: Arc<NodeStorage>, auth_token: String) {
+        let _ = self.snapshot_source.set((storage, auth_token));
+    }
+    
+    /// Serve maintenance controls (`/maintenance`) and report the current mode in `/health`
+    pub fn attach_maintenance(&self, control: Arc<MaintenanceControl>) {
+        let _ = self.maintenance.set(control);
+    }
+    
+    /// Report failed subsystems and the degradation level in `/health`
+    pub fn attach_degradation(&self, degradation: Arc<Degradation>) {
+        let _ = self.degradation.set(degradation);
+    }
+    
+    /// Serve the node's `/status`, `/stats`, `/history/:address` and `/crashes` reports alongside the metrics
+    pub fn attach_status_source(&self, source: Arc<StatusSource>) {
+        let _ = self.status_source.set(source);
+    }
+    
+    /// Serve model version management (`/models`) alongside the metrics
+    pub fn attach_model_registry(&self, registry: Arc<ModelRegistry>, detector: Arc<ThreatDetector>) {
+        let _ = self.model_registry.set((registry, detector));
+    }
+    
+    /// Serve read-only SQL over stored data (`/query`) alongside the metrics
+    pub fn attach_query_engine(&self, engine: Arc<QueryEngine>) {
+        let _ = self.query_engine.set(engine);
+    }
+    
+    /// Serve detection epoch commitments and inclusion proofs (`/commitments`) alongside the metrics
+    pub fn attach_epoch_committer(&self, committer: Arc<EpochCommitter>) {
+        let _ = self.epoch_committer.set(committer);
+    }
+    
+    /// Serve retention previews and sweeps (`/retention`) alongside the metrics
+    pub fn attach_retention(&self, janitor: Arc<RetentionJanitor>) {
+        let _ = self.retention.set(janitor);
+    }
+    
+    /// Serve load run controls and reports (`/loadgen`) alongside the metrics
+    pub fn attach_loadgen(&self, generator: Arc<LoadGenerator>) {
+        let _ = self.loadgen.set(generator);
+    }
+    
+    /// Serve shadow pipeline divergence reports (`/mirror`) alongside the metrics
+    pub fn attach_mirror(&self, mirror: Arc<TrafficMirror>) {
+        let _ = self.mirror.set(mirror);
+    }
+    
+    /// Serve captured gossip slashing evidence (`/gossip/evidence`) alongside the metrics
+    pub fn attach_gossip_auth(&self, auth: Arc<GossipAuth>) {
+        let _ = self.gossip_auth.set(auth);
+    }
+    
+    /// Serve the latest chain ID and deployment check (`/chain`) alongside the metrics
+    pub fn attach_chain_watch(&self, watch: Arc<ChainWatch>) {
+        let _ = self.chain_watch.set(watch);
+    }
+    
+    /// Serve the archive summary and queries (`/archive`) alongside the metrics
+    pub fn attach_archiver(&self, archiver: Arc<Archiver>) {
+        let _ = self.archiver.set(archiver);
+    }
+    
+    /// Serve the detection-rate guard and its review queue (`/reporting`) alongside the metrics
+    pub fn attach_reporting_guard(&self, guard: Arc<ReportingGuard>) {
+        let _ = self.reporting_guard.set(guard);
+    }
+    
+    /// Serve the reward ledger and claims (`/rewards`) alongside the metrics
+    pub fn attach_rewards(&self, tracker: Arc<RewardTracker>) {
+        let _ = self.rewards.set(tracker);
+    }
+    
+    /// Serve the node's stake and changes to it (`/stake`) alongside the metrics
+    pub fn attach_stake(&self, manager: Arc<StakeManager>) {
+        let _ = self.stake.set(manager);
+    }
+    
+    /// Export the detector's model stats every `export_interval_secs`, and serve them (`/model/stats`)
+    pub fn attach_threat_detector(&self, detector: Arc<ThreatDetector>) {
+        let _ = self.detector.set(detector);
+    }
+    
+    pub async fn start(&self) -> Result<()> {
+        if !self.config.enabled {
+            info!("📉 Metrics export disabled");
+            return Ok(());
+        }
+        
+        let model_stats_export = match self.detector.get() {
+            Some(detector) => Some(tokio::spawn(export_model_stats(
+                Arc::clone(detector),
+                Duration::from_secs(self.config.export_interval_secs.max(1)),
+            )?)),
+            None => None,
+        };
+        
+        let mut app = Router::new().route("/metrics", get(serve_metrics));
+        
+        app = match (self.maintenance.get().cloned(), self.degradation.get().cloned()) {
+            (None, None) => app.route("/health", get(|| async { "OK" })),
+            (maintenance, degradation) => app.route("/health", get(move || {
+                health(maintenance.clone(), degradation.clone())
+            })),
+        };
+        
+        if self.admin_api {
+            app = self.merge_admin_routes(app);
+        } else {
+            info!("🔒 Admin API disabled, serving only /metrics and /health");
+        }
+        
+        if let Some((storage, auth_token)) = self.snapshot_source.get() {
+            app = app.merge(replica::snapshot_routes(Arc::clone(storage), auth_token.clone()));
+        }
+        
+        #[cfg(feature = "chaos")]
+        {
+            tracing::warn!("💥 Chaos build: fault injection API enabled on /chaos");
+            app = app.merge(crate::chaos::admin_routes());
+        }
+        
+        let addr = std::net::SocketAddr::from(([0, 0, 0, 0], self.config.port));
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        
+        info!("📈 Serving Prometheus metrics on http://{}/metrics", addr);
+        let served = axum::serve(listener, app).await;
+        if let Some(handle) = model_stats_export {
+            handle.abort();
+        }
+        
+        Ok(served?)
+    }
+    
+    /// Operator endpoints, left out for roles whose metrics port is widely reachable
+    fn merge_admin_routes(&self, mut app: Router) -> Router {
+        if let Some(control) = self.maintenance.get() {
+            app = app.merge(maintenance::admin_routes(Arc::clone(control)));
+        }
+        
+        if let Some(ledger) = self.peer_ledger.get() {
+            let ledger = Arc::clone(ledger);
+            app = app.route("/peers", get(move || async move { Json(ledger.summaries()) }));
+        }
+        
+        if let Some(watchlists) = self.watchlists.get() {
+            app = app.merge(watchlist::admin_routes(Arc::clone(watchlists)));
+        }
+        
+        if let Some(processor) = self.dag_processor.get() {
+            app = app.merge(dag::admin_routes(Arc::clone(processor)));
+        }
+        
+        if let Some(source) = self.status_source.get() {
+            app = app.merge(status_routes(Arc::clone(source)));
+        }
+        
+        if let Some((model_registry, detector)) = self.model_registry.get() {
+            app = app.merge(registry::admin_routes(Arc::clone(model_registry), Arc::clone(detector)));
+        }
+        
+        if let Some(engine) = self.query_engine.get() {
+            app = app.merge(query::admin_routes(Arc::clone(engine)));
+        }
+        if let Some(committer) = self.epoch_committer.get() {
+            app = app.merge(commitment::admin_routes(Arc::clone(committer)));
+        }
+        if let Some(janitor) = self.retention.get() {
+            app = app.merge(retention::admin_routes(Arc::clone(janitor)));
+        }
+        if let Some(mirror) = self.mirror.get() {
+            app = app.merge(mirror::admin_routes(Arc::clone(mirror)));
+        }
+        if let Some(generator) = self.loadgen.get() {
+            app = app.merge(loadgen::admin_routes(Arc::clone(generator)));
+        }
+        if let Some(auth) = self.gossip_auth.get() {
+            app = app.merge(gossip::admin_routes(Arc::clone(auth)));
+        }
+        if let Some(watch) = self.chain_watch.get() {
+            app = app.merge(chain_watch::admin_routes(Arc::clone(watch)));
+        }
+        if let Some(archiver) = self.archiver.get() {
+            app = app.merge(archive::admin_routes(Arc::clone(archiver)));
+        }
+        if let Some(guard) = self.reporting_guard.get() {
+            app = app.merge(reporting_guard::admin_routes(Arc::clone(guard)));
+        }
+        if let Some(tracker) = self.rewards.get() {
+            app = app.merge(rewards::admin_routes(Arc::clone(tracker)));
+        }
+        if let Some(manager) = self.stake.get() {
+            app = app.merge(stake::admin_routes(Arc::clone(manager)));
+        }
+        if let Some(detector) = self.detector.get() {
+            let detector = Arc::clone(detector);
+            app = app.route("/model/stats", get(move || async move {
+                Json(Report::new("model_stats", detector.get_model_stats().await))
+            }));
+        }
+        app
+    }
+}
+
+/// Mirror the detector's model stats into gauges; they are snapshots, so they are not counters
+fn export_model_stats(detector: Arc<ThreatDetector>, interval: Duration) -> Result<impl std::future::Future<Output = ()>> {
+    static COUNTS: OnceLock<IntGaugeVec> = OnceLock::new();
+    static QUALITY: OnceLock<GaugeVec> = OnceLock::new();
+    static LATENCY: OnceLock<GaugeVec> = OnceLock::new();
+    let counts = register_once(&COUNTS, || {
+        IntGaugeVec::new(
+            Opts::new("dagshield_model_predictions", "Detections and graded verdicts of the threat model, by kind"),
+            &["kind"],
+        )
+    })?;
+    let quality = register_once(&QUALITY, || {
+        GaugeVec::new(
+            Opts::new("dagshield_model_quality", "Precision, recall and F1 of the threat model over graded verdicts"),
+            &["measure"],
+        )
+    })?;
+    let latency = register_once(&LATENCY, || {
+        GaugeVec::new(
+            Opts::new("dagshield_model_inference_latency_ms", "Inference latency percentiles over recent detections"),
+            &["quantile"],
+        )
+    })?;
+    
+    Ok(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let stats = detector.get_model_stats().await;
+            for (kind, value) in [
+                ("total", stats.total_predictions),
+                ("accurate", stats.accurate_predictions),
+                ("true_positive", stats.true_positives),
+                ("false_positive", stats.false_positives),
+                ("false_negative", stats.false_negatives),
+                ("cache_hit", stats.cache_hits),
+                ("cache_miss", stats.cache_misses),
+            ] {
+                counts.with_label_values(&[kind]).set(value as i64);
+            }
+            for (measure, value) in [("precision", stats.precision()), ("recall", stats.recall()), ("f1", stats.f1())] {
+                // Left out until enough verdicts are graded, rather than reported as zero
+                if let Some(value) = value {
+                    quality.with_label_values(&[measure]).set(value);
+                }
+            }
+            for (quantile, value) in [
+                ("0.5", stats.inference_latency.p50_ms),
+                ("0.95", stats.inference_latency.p95_ms),
+                ("0.99", stats.inference_latency.p99_ms),
+            ] {
+                latency.with_label_values(&[quantile]).set(value);
+            }
+        }
+    })
+}
+
+/// A failed subsystem outranks maintenance: it is the one that needs an operator
+async fn health(maintenance: Option<Arc<MaintenanceControl>>, degradation: Option<Arc<Degradation>>) -> Json<serde_json::Value> {
+    let mode = match &maintenance {
+        Some(control) => Some(control.mode().await),
+        None => None,
+    };
+    let degradation = degradation.map(|degradation| degradation.report());
+    let level = degradation.as_ref().map_or(DegradationLevel::Normal, |report| report.level);
+    let status = match level {
+        DegradationLevel::Critical => "critical",
+        DegradationLevel::Degraded => "degraded",
+        DegradationLevel::Normal if mode.as_ref().is_some_and(|mode| !mode.is_normal()) => "maintenance",
+        DegradationLevel::Normal => "ok",
+    };
+    Json(serde_json::json!({ "status": status, "mode": mode, "degradation": degradation }))
+}
+
+fn status_routes(source: Arc<StatusSource>) -> Router {
+    let (stats_source, history_source, crash_source) = (Arc::clone(&source), Arc::clone(&source), Arc::clone(&source));
+    Router::new()
+        .route("/status", get(move || async move { Json(Report::new("status", source.status().await)) }))
+        .route("/stats", get(move || async move {
+            match stats_source.stats().await {
+                Ok(stats) => Json(Report::new("stats", stats)).into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            }
+        }))
+        .route("/history/:address", get(move |Path(address): Path<String>| async move {
+            match history_source.history(&address) {
+                Ok(history) => Json(Report::new("history", history)).into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            }
+        }))
+        .route("/crashes", get(move || async move {
+            match crash_source.crashes() {
+                Ok(crashes) => Json(Report::new("crashes", crashes)).into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            }
+        }))
+}
+
+async fn serve_metrics(headers: HeaderMap) -> impl IntoResponse {
+    let families = prometheus::gather();
+    
+    // Exemplars are only part of the OpenMetrics exposition format
+    let wants_openmetrics = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .map(|accept| accept.contains("application/openmetrics-text"))
+        .unwrap_or(false);
+    
+    if wants_openmetrics {
+        debug!("📈 Rendering OpenMetrics exposition with exemplars");
+        return ([(header::CONTENT_TYPE, OPENMETRICS_CONTENT_TYPE.to_string())], encode_openmetrics(&families));
+    }
+    
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    if let Err(e) = encoder.encode(&families, &mut buffer) {
+        return ([(header::CONTENT_TYPE, "text/plain".to_string())], format!("# encode error: {}\n", e));
+    }
+    
+    (
+        [(header::CONTENT_TYPE, encoder.format_type().to_string())],
+        String::from_utf8_lossy(&buffer).into_owned(),
+    )
+}
+
+fn encode_openmetrics(families: &[MetricFamily]) -> String {
+    let mut out = String::new();
+    
+    for family in families {
+        let (family_name, kind) = match family.get_field_type() {
+            MetricType::COUNTER => (family.get_name().trim_end_matches("_total"), "counter"),
+            MetricType::GAUGE => (family.get_name(), "gauge"),
+            MetricType::HISTOGRAM => (family.get_name(), "histogram"),
+            _ => (family.get_name(), "unknown"),
+        };
+        
+        let _ = writeln!(out, "# TYPE {} {}", family_name, kind);
+        let _ = writeln!(out, "# HELP {} {}", family_name, family.get_help());
+        
+        for metric in family.get_metric() {
+            let labels: Vec<(String, String)> = metric
+                .get_label()
+                .iter()
+                .map(|l| (l.get_name().to_string(), l.get_value().to_string()))
+                .collect();
+            
+            match family.get_field_type() {
+                MetricType::COUNTER => {
+                    let _ = writeln!(out, "{}_total{} {}", family_name, format_labels(&labels, None),
+                                     metric.get_counter().get_value());
+                }
+                MetricType::GAUGE => {
+                    let _ = writeln!(out, "{}{} {}", family_name, format_labels(&labels, None),
+                                     metric.get_gauge().get_value());
+                }
+                MetricType::HISTOGRAM => {
+                    let histogram = metric.get_histogram();
+                    let stage = labels
+                        .iter()
+                        .find(|(name, _)| name == "stage")
+                        .map(|(_, value)| value.as_str());
+                    
+                    for (i, bucket) in histogram.get_bucket().iter().enumerate() {
+                        let le = format!("{}", bucket.get_upper_bound());
+                        let _ = write!(out, "{}_bucket{} {}", family_name,
+                                       format_labels(&labels, Some(&le)), bucket.get_cumulative_count());
+                        write_exemplar(&mut out, family_name, stage, i);
+                        out.push('\n');
+                    }
+                    
+                    let _ = write!(out, "{}_bucket{} {}", family_name,
+                                   format_labels(&labels, Some("+Inf")), histogram.get_sample_count());
+                    write_exemplar(&mut out, family_name, stage, histogram.get_bucket().len());
+                    out.push('\n');
+                    
+                    let _ = writeln!(out, "{}_sum{} {}", family_name, format_labels(&labels, None),
+                                     histogram.get_sample_sum());
+                    let _ = writeln!(out, "{}_count{} {}", family_name, format_labels(&labels, None),
+                                     histogram.get_sample_count());
+                }
+                _ => {}
+            }
+        }
+    }
+    
+    out.push_str("# EOF\n");
+    out
+}
+
+fn write_exemplar(out: &mut String, family_name: &str, stage: Option<&str>, bucket: usize) {
+    if family_name != STAGE_LATENCY_METRIC {
+        return;
+    }
+    
+    if let Some(exemplar) = stage.and_then(|s| pipeline_latency().exemplar(s, bucket)) {
+        let _ = write!(out, " # {{trace_id=\"{}\"}} {} {:.3}",
+                       escape_label(&exemplar.trace_id), exemplar.value, exemplar.timestamp);
+    }
+}
+
+fn format_labels(labels: &[(String, String)], le: Option<&str>) -> String {
+    let mut parts: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape_label(value)))
+        .collect();
+    
+    if let Some(le) = le {
+        parts.push(format!("le=\"{}\"", le));
+    }
+    
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", parts.join(","))
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prometheus::IntCounter;
+    
+    #[test]
+    fn register_once_shares_the_first_collector() {
+        static COUNTER: OnceLock<IntCounter> = OnceLock::new();
+        let build = || IntCounter::new("dagshield_test_shared_total", "Counter built twice");
+        register_once(&COUNTER, build).unwrap().inc();
+        let rebuilt = register_once(&COUNTER, build).unwrap();
+        assert_eq!(rebuilt.get(), 1);
+        assert!(prometheus::gather().iter().any(|family| family.get_name() == "dagshield_test_shared_total"));
+    }
+    
+    #[test]
+    fn register_once_reports_name_clashes() {
+        static FIRST: OnceLock<IntCounter> = OnceLock::new();
+        static SECOND: OnceLock<IntCounter> = OnceLock::new();
+        let build = || IntCounter::new("dagshield_test_clash_total", "Counter registered under two cells");
+        register_once(&FIRST, build).unwrap();
+        assert!(register_once(&SECOND, build).is_err());
+        assert!(SECOND.get().is_none());
+    }
+}
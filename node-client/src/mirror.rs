@@ -0,0 +1,321 @@
+//! Traffic mirroring: shadow-deploying a second detection pipeline on live transactions
+//!
+//! A sample of the transactions the live pipeline judges is copied, with its verdict, to a
+//! shadow detector built from another `[ai]` config, so a new model, rule set or threshold can
+//! be judged on real traffic. The shadow shares nothing with the node: it has no storage,
+//! alert cache or registry attached, and its verdicts go nowhere but the divergence report.
+//! Mirroring never slows the live pipeline; when the shadow falls behind, copies are dropped.
+
+use anyhow::{Context, Result};
+use axum::{response::IntoResponse, routing::{get, post}, Json, Router};
+use prometheus::{IntCounterVec, Opts};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+use crate::ai::{ThreatDetectionResult, ThreatDetector};
+use crate::config::{AIConfig, MirrorConfig};
+use crate::dag::Transaction;
+use crate::governor::ResourceGovernor;
+use crate::metrics::register_once;
+use crate::status::Report;
+use crate::threat::ThreatClass;
+
+/// Only the `[ai]` table of the shadow config file is read
+#[derive(Deserialize)]
+struct ShadowConfigFile {
+    ai: AIConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MirroredVerdict {
+    pub threat_type: ThreatClass,
+    pub confidence: f32,
+    pub flagged: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DivergenceKind {
+    /// Flagged by the live pipeline only
+    LiveOnly,
+    /// Flagged by the shadow only
+    ShadowOnly,
+    /// Flagged alike, but classed as different threats
+    ThreatType,
+    /// Flagged alike, with confidences further apart than `confidence_tolerance`
+    Confidence,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Divergence {
+    pub transaction_id: String,
+    pub chain_id: u64,
+    pub kind: DivergenceKind,
+    pub live: MirroredVerdict,
+    pub shadow: MirroredVerdict,
+    pub at: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct MirrorTally {
+    mirrored: u64,
+    dropped: u64,
+    failed: u64,
+    agreed: u64,
+    live_only: u64,
+    shadow_only: u64,
+    threat_type: u64,
+    confidence: u64,
+    /// Sum of shadow minus live confidence over compared transactions
+    confidence_delta: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MirrorReport {
+    /// Unix time the comparison window opened, at start or the last reset
+    pub since: u64,
+    pub generated_at: u64,
+    pub live_fingerprint: String,
+    pub shadow_fingerprint: String,
+    /// Copies handed to the shadow
+    pub mirrored: u64,
+    /// Copies dropped because the shadow was behind
+    pub dropped: u64,
+    /// Copies the shadow failed to judge
+    pub failed: u64,
+    pub compared: u64,
+    pub agreed: u64,
+    pub live_only: u64,
+    pub shadow_only: u64,
+    pub threat_type_changed: u64,
+    pub confidence_drift: u64,
+    /// Mean of shadow minus live confidence; positive when the shadow is the more suspicious
+    pub mean_confidence_delta: Option<f64>,
+    /// Most recent divergences, newest last
+    pub divergences: Vec<Divergence>,
+}
+
+pub struct TrafficMirror {
+    config: MirrorConfig,
+    live: Arc<ThreatDetector>,
+    shadow: ThreatDetector,
+    queue: mpsc::Sender<(Transaction, ThreatDetectionResult)>,
+    queued: Mutex<mpsc::Receiver<(Transaction, ThreatDetectionResult)>>,
+    /// Opening of the comparison window, its counts and most recent divergences
+    window: parking_lot::Mutex<(u64, MirrorTally, VecDeque<Divergence>)>,
+    transactions: IntCounterVec,
+}
+
+impl TrafficMirror {
+    pub async fn new(config: &MirrorConfig, live: Arc<ThreatDetector>, governor: Arc<ResourceGovernor>) -> Result<Self> {
+        let content = std::fs::read_to_string(&config.shadow_config_path)
+            .with_context(|| format!("Failed to read shadow config {}", config.shadow_config_path))?;
+        let shadow_config: ShadowConfigFile = toml::from_str(&content)
+            .with_context(|| format!("Invalid shadow config {}", config.shadow_config_path))?;
+        info!("🪞 Building shadow detection pipeline from {}", config.shadow_config_path);
+        // Inference shares the node's governor, so the shadow is throttled with the live pipeline
+        let mut shadow = ThreatDetector::new(&shadow_config.ai, governor).await?;
+        // The drift gauges report the live model
+        shadow.unexport_drift_metrics()?;
+        
+        static TRANSACTIONS: OnceLock<IntCounterVec> = OnceLock::new();
+        let transactions = register_once(&TRANSACTIONS, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_mirror_transactions_total", "Live transactions mirrored to the shadow pipeline, by outcome"),
+                &["outcome"],
+            )
+        })?;
+        
+        let (queue, queued) = mpsc::channel(config.queue_capacity.max(1));
+        Ok(Self {
+            config: config.clone(),
+            live,
+            shadow,
+            queue,
+            queued: Mutex::new(queued),
+            window: parking_lot::Mutex::new((now_secs(), MirrorTally::default(), VecDeque::new())),
+            transactions,
+        })
+    }
+    
+    /// Copy a live verdict to the shadow, when sampled; never waits
+    pub fn offer(&self, transaction: &Transaction, live: &ThreatDetectionResult) {
+        if !self.sampled(&transaction.id) {
+            return;
+        }
+        match self.queue.try_send((transaction.clone(), live.clone())) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                self.window.lock().1.dropped += 1;
+                self.transactions.with_label_values(&["dropped"]).inc();
+            }
+            Err(TrySendError::Closed(_)) => {}
+        }
+    }
+    
+    /// Judge mirrored transactions with the shadow, writing a report every `report_interval_secs`
+    pub async fn start(&self) -> Result<()> {
+        info!("🪞 Mirroring {:.0}% of live transactions to the shadow pipeline", self.config.sample_rate.clamp(0.0, 1.0) * 100.0);
+        self.shadow.warm_up().await;
+        
+        let mut queued = self.queued.lock().await;
+        let mut reports = tokio::time::interval(Duration::from_secs(self.config.report_interval_secs.max(60)));
+        // The first tick is immediate, and there is nothing to report yet
+        reports.tick().await;
+        
+        loop {
+            tokio::select! {
+                mirrored = queued.recv() => {
+                    let Some((transaction, live)) = mirrored else {
+                        return Ok(());
+                    };
+                    self.compare(&transaction, &live).await;
+                }
+                _ = reports.tick() => {
+                    let report = self.report();
+                    info!("🪞 Mirror: {} compared, {} agreed, {} flagged by live only, {} by shadow only, {} dropped",
+                          report.compared, report.agreed, report.live_only, report.shadow_only, report.dropped);
+                    match self.write_report(&report) {
+                        Ok(path) => debug!("Mirror report written to {}", path.display()),
+                        Err(e) => warn!("⚠️ Failed to write mirror report: {:#}", e),
+                    }
+                }
+            }
+        }
+    }
+    
+    async fn compare(&self, transaction: &Transaction, live: &ThreatDetectionResult) {
+        let shadow = match self.shadow.detect_threat(transaction).await {
+            Ok(shadow) => shadow,
+            Err(e) => {
+                debug!("Shadow pipeline failed on {}: {:#}", transaction.id, e);
+                let mut window = self.window.lock();
+                window.1.mirrored += 1;
+                window.1.failed += 1;
+                self.transactions.with_label_values(&["failed"]).inc();
+                return;
+            }
+        };
+        
+        let live = MirroredVerdict {
+            flagged: self.live.is_flagged(transaction, live),
+            threat_type: live.threat_type.clone(),
+            confidence: live.confidence,
+        };
+        let shadow = MirroredVerdict {
+            flagged: self.shadow.is_flagged(transaction, &shadow),
+            threat_type: shadow.threat_type,
+            confidence: shadow.confidence,
+        };
+        let kind = match (live.flagged, shadow.flagged) {
+            (true, false) => Some(DivergenceKind::LiveOnly),
+            (false, true) => Some(DivergenceKind::ShadowOnly),
+            (true, true) if live.threat_type != shadow.threat_type => Some(DivergenceKind::ThreatType),
+            _ if (live.confidence - shadow.confidence).abs() > self.config.confidence_tolerance => Some(DivergenceKind::Confidence),
+            _ => None,
+        };
+        
+        let mut window = self.window.lock();
+        let (_, tally, divergences) = &mut *window;
+        tally.mirrored += 1;
+        tally.confidence_delta += (shadow.confidence - live.confidence) as f64;
+        let Some(kind) = kind else {
+            tally.agreed += 1;
+            self.transactions.with_label_values(&["agreed"]).inc();
+            return;
+        };
+        
+        match kind {
+            DivergenceKind::LiveOnly => tally.live_only += 1,
+            DivergenceKind::ShadowOnly => tally.shadow_only += 1,
+            DivergenceKind::ThreatType => tally.threat_type += 1,
+            DivergenceKind::Confidence => tally.confidence += 1,
+        }
+        self.transactions.with_label_values(&["diverged"]).inc();
+        divergences.push_back(Divergence {
+            transaction_id: transaction.id.clone(),
+            chain_id: transaction.chain_id,
+            kind,
+            live,
+            shadow,
+            at: now_secs(),
+        });
+        while divergences.len() > self.config.max_divergences {
+            divergences.pop_front();
+        }
+    }
+    
+    pub fn report(&self) -> MirrorReport {
+        let window = self.window.lock();
+        let (since, tally, divergences) = &*window;
+        let compared = tally.mirrored - tally.failed;
+        MirrorReport {
+            since: *since,
+            generated_at: now_secs(),
+            live_fingerprint: self.live.pipeline_fingerprint(),
+            shadow_fingerprint: self.shadow.pipeline_fingerprint(),
+            mirrored: tally.mirrored,
+            dropped: tally.dropped,
+            failed: tally.failed,
+            compared,
+            agreed: tally.agreed,
+            live_only: tally.live_only,
+            shadow_only: tally.shadow_only,
+            threat_type_changed: tally.threat_type,
+            confidence_drift: tally.confidence,
+            mean_confidence_delta: (compared > 0).then(|| tally.confidence_delta / compared as f64),
+            divergences: divergences.iter().cloned().collect(),
+        }
+    }
+    
+    /// Open a new comparison window, e.g. after changing the shadow's rules
+    pub fn reset(&self) -> MirrorReport {
+        let report = self.report();
+        *self.window.lock() = (now_secs(), MirrorTally::default(), VecDeque::new());
+        report
+    }
+    
+    fn write_report(&self, report: &MirrorReport) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.config.report_dir)
+            .with_context(|| format!("Failed to create {}", self.config.report_dir))?;
+        let path = PathBuf::from(&self.config.report_dir).join(format!("mirror-{}.json", report.generated_at));
+        std::fs::write(&path, serde_json::to_string_pretty(report)? + "\n")?;
+        Ok(path)
+    }
+    
+    /// Sampling by transaction ID, so a transaction seen twice is mirrored both times or neither
+    fn sampled(&self, transaction_id: &str) -> bool {
+        let rate = self.config.sample_rate.clamp(0.0, 1.0);
+        if rate >= 1.0 {
+            return true;
+        }
+        let mut hasher = DefaultHasher::new();
+        transaction_id.hash(&mut hasher);
+        (hasher.finish() as f64 / u64::MAX as f64) < rate
+    }
+}
+
+fn now_secs() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
+
+/// `GET /mirror` reports the current comparison window; `POST /mirror/reset` closes it and opens
+/// a new one, answering with the closed window's report
+pub fn admin_routes(mirror: Arc<TrafficMirror>) -> Router {
+    let reporting = Arc::clone(&mirror);
+    Router::new()
+        .route("/mirror", get(move || async move {
+            Json(Report::new("mirror", reporting.report())).into_response()
+        }))
+        .route("/mirror/reset", post(move || async move {
+            Json(Report::new("mirror", mirror.reset())).into_response()
+        }))
+}
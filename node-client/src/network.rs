@@ -0,0 +1,587 @@
+//! P2P networking: signed threat intel and receipt gossip, intel queries, verdict cross-checks and DAG sync between DAGShield nodes
+
+use anyhow::{Context, Result};
+use dashmap::DashMap;
+use ethers::core::rand::seq::IteratorRandom;
+use libp2p::futures::StreamExt;
+use libp2p::request_response::{self, ProtocolSupport, ResponseChannel};
+use libp2p::swarm::{NetworkBehaviour, SwarmEvent};
+use libp2p::{gossipsub, mdns, noise, tcp, yamux, Multiaddr, PeerId, StreamProtocol, Swarm};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::chaos;
+use crate::config::NetworkConfig;
+use crate::crosscheck::{CrossChecker, VerdictQuery, VerdictResponse};
+use crate::dag_sync::{DagSync, DagSyncRequest, DagSyncResponse};
+use crate::gossip::{GossipAuth, SignedGossip};
+use crate::peers::{PeerLedger, ServeDecision};
+use crate::receipts::{DetectionReceipt, ReceiptBook};
+use crate::storage::NodeStorage;
+use crate::threat::ThreatClass;
+
+// Version 2 wraps every message in a node-signed envelope
+const INTEL_TOPIC: &str = "dagshield/intel/2";
+const RECEIPT_TOPIC: &str = "dagshield/receipts/2";
+const INTEL_PROTOCOL: &str = "/dagshield/intel-query/1";
+const VERDICT_PROTOCOL: &str = "/dagshield/verdict-check/1";
+const DAG_SYNC_PROTOCOL: &str = "/dagshield/dag-sync/1";
+/// Known intel kept for answering peers' queries
+const MAX_KNOWN_INTEL: usize = 50_000;
+/// Intel requests answered per scheduling tick
+const SERVE_BATCH: usize = 32;
+const SERVE_TICK: Duration = Duration::from_millis(100);
+
+/// A threat finding shared with peers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreatIntel {
+    pub target_address: String,
+    pub chain_id: u64,
+    pub threat_type: ThreatClass,
+    pub confidence: u32,
+    pub tx_hash: String,
+    pub evidence_cid: Option<String>,
+    pub reported_at: u64,
+}
+
+impl ThreatIntel {
+    fn key(&self) -> String {
+        format!("{}:{}", self.chain_id, self.target_address.to_lowercase())
+    }
+    
+    fn is_valid(&self) -> bool {
+        self.target_address.starts_with("0x")
+            && self.target_address.len() == 42
+            && !self.threat_type.as_str().is_empty()
+            && self.confidence <= 100
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntelQuery {
+    pub chain_id: u64,
+    pub addresses: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum IntelResponse {
+    Intel(Vec<ThreatIntel>),
+    /// The request was refused or dropped; retry later or elsewhere
+    Busy,
+}
+
+#[derive(NetworkBehaviour)]
+struct ShieldBehaviour {
+    gossipsub: gossipsub::Behaviour,
+    mdns: mdns::tokio::Behaviour,
+    intel: request_response::json::Behaviour<IntelQuery, IntelResponse>,
+    verdicts: request_response::json::Behaviour<VerdictQuery, VerdictResponse>,
+    dag_sync: request_response::json::Behaviour<DagSyncRequest, DagSyncResponse>,
+}
+
+enum NetworkCommand {
+    Publish(ThreatIntel),
+    PublishReceipt(DetectionReceipt),
+}
+
+struct PendingRequest {
+    peer: PeerId,
+    query: IntelQuery,
+    channel: ResponseChannel<IntelResponse>,
+}
+
+pub struct NetworkManager {
+    config: NetworkConfig,
+    node_id: String,
+    keypair: libp2p::identity::Keypair,
+    ledger: Arc<PeerLedger>,
+    known_intel: DashMap<String, ThreatIntel>,
+    cross_checker: OnceLock<Arc<CrossChecker>>,
+    dag_sync: OnceLock<Arc<DagSync>>,
+    receipt_book: OnceLock<Arc<ReceiptBook>>,
+    gossip_auth: OnceLock<Arc<GossipAuth>>,
+    command_tx: mpsc::UnboundedSender<NetworkCommand>,
+    command_rx: tokio::sync::Mutex<Option<mpsc::UnboundedReceiver<NetworkCommand>>>,
+}
+
+impl NetworkManager {
+    pub async fn new(config: &NetworkConfig, node_id: &str, storage: Arc<NodeStorage>) -> Result<Self> {
+        let keypair = libp2p::identity::Keypair::generate_ed25519();
+        let (command_tx, command_rx) = mpsc::unbounded_channel();
+        
+        info!("🌐 Network peer ID: {}", keypair.public().to_peer_id());
+        
+        Ok(Self {
+            config: config.clone(),
+            node_id: node_id.to_string(),
+            keypair,
+            ledger: Arc::new(PeerLedger::new(config, storage)?),
+            known_intel: DashMap::new(),
+            cross_checker: OnceLock::new(),
+            dag_sync: OnceLock::new(),
+            receipt_book: OnceLock::new(),
+            gossip_auth: OnceLock::new(),
+            command_tx,
+            command_rx: tokio::sync::Mutex::new(Some(command_rx)),
+        })
+    }
+    
+    pub fn ledger(&self) -> Arc<PeerLedger> {
+        Arc::clone(&self.ledger)
+    }
+    
+    /// Compare this node's verdicts with its peers' every round
+    pub fn attach_cross_checker(&self, checker: Arc<CrossChecker>) {
+        if self.cross_checker.set(checker).is_err() {
+            warn!("⚠️ Cross-checker already attached to network manager");
+        }
+    }
+    
+    /// Catch up with peers' DAGs and reconcile tips with one every round
+    pub fn attach_dag_sync(&self, sync: Arc<DagSync>) {
+        if self.dag_sync.set(sync).is_err() {
+            warn!("⚠️ DAG sync already attached to network manager");
+        }
+    }
+    
+    /// Keep the first-seen receipts peers gossip
+    pub fn attach_receipt_book(&self, book: Arc<ReceiptBook>) {
+        if self.receipt_book.set(book).is_err() {
+            warn!("⚠️ Receipt book already attached to network manager");
+        }
+    }
+    
+    /// Sign gossip with the node key, and hold peers to what they sign
+    pub fn attach_gossip_auth(&self, auth: Arc<GossipAuth>) {
+        if self.gossip_auth.set(auth).is_err() {
+            warn!("⚠️ Gossip signing already attached to network manager");
+        }
+    }
+    
+    /// Share a signed first-seen receipt with the mesh
+    pub fn publish_receipt(&self, receipt: DetectionReceipt) {
+        if self.command_tx.send(NetworkCommand::PublishReceipt(receipt)).is_err() {
+            debug!("Network manager stopped, receipt not published");
+        }
+    }
+    
+    /// Share a finding with the mesh
+    pub fn publish_intel(&self, intel: ThreatIntel) {
+        self.remember(intel.clone());
+        if self.command_tx.send(NetworkCommand::Publish(intel)).is_err() {
+            debug!("Network manager stopped, intel not published");
+        }
+    }
+    
+    /// Store intel for answering queries; returns whether it was new
+    fn remember(&self, intel: ThreatIntel) -> bool {
+        if self.known_intel.len() >= MAX_KNOWN_INTEL {
+            // Evict the oldest report to make room
+            let oldest = self.known_intel
+                .iter()
+                .min_by_key(|entry| entry.value().reported_at)
+                .map(|entry| entry.key().clone());
+            if let Some(key) = oldest {
+                self.known_intel.remove(&key);
+            }
+        }
+        self.known_intel.insert(intel.key(), intel).is_none()
+    }
+    
+    fn answer(&self, query: &IntelQuery) -> IntelResponse {
+        let intel = query.addresses
+            .iter()
+            .filter_map(|address| {
+                let key = format!("{}:{}", query.chain_id, address.to_lowercase());
+                self.known_intel.get(&key).map(|entry| entry.value().clone())
+            })
+            .collect();
+        IntelResponse::Intel(intel)
+    }
+    
+    fn build_swarm(&self) -> Result<Swarm<ShieldBehaviour>> {
+        let swarm = libp2p::SwarmBuilder::with_existing_identity(self.keypair.clone())
+            .with_tokio()
+            .with_tcp(tcp::Config::default(), noise::Config::new, yamux::Config::default)?
+            .with_behaviour(|key| {
+                let gossipsub = gossipsub::Behaviour::new(
+                    gossipsub::MessageAuthenticity::Signed(key.clone()),
+                    gossipsub::Config::default(),
+                )?;
+                let mdns = mdns::tokio::Behaviour::new(mdns::Config::default(), key.public().to_peer_id())?;
+                let intel = request_response::json::Behaviour::new(
+                    [(StreamProtocol::new(INTEL_PROTOCOL), ProtocolSupport::Full)],
+                    request_response::Config::default(),
+                );
+                let verdicts = request_response::json::Behaviour::new(
+                    [(StreamProtocol::new(VERDICT_PROTOCOL), ProtocolSupport::Full)],
+                    request_response::Config::default(),
+                );
+                let dag_sync = request_response::json::Behaviour::new(
+                    [(StreamProtocol::new(DAG_SYNC_PROTOCOL), ProtocolSupport::Full)],
+                    request_response::Config::default(),
+                );
+                Ok(ShieldBehaviour { gossipsub, mdns, intel, verdicts, dag_sync })
+            })?
+            .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(60)))
+            .build();
+        
+        Ok(swarm)
+    }
+    
+    pub async fn start(&self) -> Result<()> {
+        let mut commands = self.command_rx
+            .lock()
+            .await
+            .take()
+            .context("Network manager already started")?;
+        
+        let mut swarm = self.build_swarm()?;
+        let topic = gossipsub::IdentTopic::new(INTEL_TOPIC);
+        swarm.behaviour_mut().gossipsub.subscribe(&topic)?;
+        let receipt_topic = gossipsub::IdentTopic::new(RECEIPT_TOPIC);
+        if self.receipt_book.get().is_some() {
+            swarm.behaviour_mut().gossipsub.subscribe(&receipt_topic)?;
+        }
+        swarm.listen_on(format!("/ip4/0.0.0.0/tcp/{}", self.config.listen_port).parse()?)?;
+        
+        for peer in &self.config.bootstrap_peers {
+            match peer.parse::<Multiaddr>() {
+                Ok(addr) => {
+                    if let Err(e) = swarm.dial(addr) {
+                        warn!("Failed to dial bootstrap peer {}: {}", peer, e);
+                    }
+                }
+                Err(e) => warn!("Invalid bootstrap peer address {}: {}", peer, e),
+            }
+        }
+        
+        let ledger = Arc::clone(&self.ledger);
+        tokio::spawn(async move {
+            if let Err(e) = ledger.start().await {
+                warn!("Peer ledger error: {}", e);
+            }
+        });
+        
+        info!("🌐 Network manager started for node {} on port {}", self.node_id, self.config.listen_port);
+        if self.gossip_auth.get().is_none() {
+            warn!("⚠️ Gossip is not signed with a node key; signing peers will reject it");
+        }
+        
+        let mut queued: VecDeque<PendingRequest> = VecDeque::new();
+        let mut deferred: VecDeque<PendingRequest> = VecDeque::new();
+        let mut serve_tick = tokio::time::interval(SERVE_TICK);
+        let crosscheck_secs = self.cross_checker.get().map_or(3600, |checker| checker.interval_secs().max(1));
+        let mut crosscheck_tick = tokio::time::interval(Duration::from_secs(crosscheck_secs));
+        let sync_secs = self.dag_sync.get().map_or(3600, |sync| sync.interval_secs().max(1));
+        let mut sync_tick = tokio::time::interval(Duration::from_secs(sync_secs));
+        
+        loop {
+            tokio::select! {
+                event = swarm.select_next_some() => {
+                    self.handle_event(&mut swarm, event, &mut queued, &mut deferred);
+                }
+                Some(command) = commands.recv() => match command {
+                    NetworkCommand::Publish(intel) => {
+                        let mut payload = match self.seal(INTEL_TOPIC, &intel).await {
+                            Ok(payload) => payload,
+                            Err(e) => {
+                                warn!("⚠️ Failed to sign intel: {:#}", e);
+                                continue;
+                            }
+                        };
+                        chaos::corrupt_gossip(&mut payload);
+                        if let Err(e) = swarm.behaviour_mut().gossipsub.publish(topic.clone(), payload) {
+                            debug!("Intel not published: {}", e);
+                        }
+                    }
+                    NetworkCommand::PublishReceipt(receipt) => {
+                        let payload = match self.seal(RECEIPT_TOPIC, &receipt).await {
+                            Ok(payload) => payload,
+                            Err(e) => {
+                                warn!("⚠️ Failed to sign receipt: {:#}", e);
+                                continue;
+                            }
+                        };
+                        if let Err(e) = swarm.behaviour_mut().gossipsub.publish(receipt_topic.clone(), payload) {
+                            debug!("Receipt not published: {}", e);
+                        }
+                    }
+                },
+                _ = serve_tick.tick() => {
+                    self.serve_pending(&mut swarm, &mut queued, &mut deferred);
+                }
+                _ = crosscheck_tick.tick(), if self.cross_checker.get().is_some() => {
+                    self.start_crosscheck_round(&mut swarm);
+                }
+                _ = sync_tick.tick(), if self.dag_sync.get().is_some() => {
+                    self.start_sync_round(&mut swarm);
+                }
+            }
+        }
+    }
+    
+    /// Encode a message for `topic`, in a signed envelope when a node key is attached
+    async fn seal<T: Serialize>(&self, topic: &str, message: &T) -> Result<Vec<u8>> {
+        match self.gossip_auth.get() {
+            Some(auth) => auth.seal(topic, message).await,
+            None => Ok(serde_json::to_vec(message)?),
+        }
+    }
+    
+    fn handle_event(
+        &self,
+        swarm: &mut Swarm<ShieldBehaviour>,
+        event: SwarmEvent<ShieldBehaviourEvent>,
+        queued: &mut VecDeque<PendingRequest>,
+        deferred: &mut VecDeque<PendingRequest>,
+    ) {
+        match event {
+            SwarmEvent::NewListenAddr { address, .. } => {
+                info!("🌐 Listening on {}", address);
+            }
+            SwarmEvent::ConnectionEstablished { peer_id, .. } => {
+                let peer = peer_id.to_string();
+                if self.ledger.is_blocked(&peer) {
+                    debug!("Disconnecting blocked peer {}", peer);
+                    let _ = swarm.disconnect_peer_id(peer_id);
+                    return;
+                }
+                self.ledger.peer_connected(&peer);
+                swarm.behaviour_mut().gossipsub.add_explicit_peer(&peer_id);
+            }
+            SwarmEvent::ConnectionClosed { peer_id, num_established: 0, .. } => {
+                self.ledger.peer_disconnected(&peer_id.to_string());
+            }
+            SwarmEvent::Behaviour(ShieldBehaviourEvent::Mdns(mdns::Event::Discovered(peers))) => {
+                for (peer_id, addr) in peers {
+                    if swarm.connected_peers().count() >= self.config.max_peers {
+                        break;
+                    }
+                    if !self.ledger.is_blocked(&peer_id.to_string()) {
+                        let _ = swarm.dial(addr);
+                    }
+                }
+            }
+            SwarmEvent::Behaviour(ShieldBehaviourEvent::Gossipsub(gossipsub::Event::Message {
+                propagation_source,
+                message,
+                ..
+            })) => {
+                let peer = message.source.unwrap_or(propagation_source).to_string();
+                if self.ledger.is_blocked(&peer) {
+                    return;
+                }
+                // Unverifiable envelopes prove nothing about their signer and only count against the peer
+                let signed = match self.gossip_auth.get() {
+                    Some(auth) => match auth.open(&peer, message.topic.as_str(), &message.data) {
+                        Ok(signed) => Some(signed),
+                        Err(e) => {
+                            debug!("Rejected gossip from {}: {:#}", peer, e);
+                            self.ledger.record_invalid(&peer);
+                            return;
+                        }
+                    },
+                    None => None,
+                };
+                let payload = signed.as_ref().map_or(&message.data[..], |signed| signed.payload.as_bytes());
+                
+                if message.topic.as_str() == RECEIPT_TOPIC {
+                    let Some(book) = self.receipt_book.get() else {
+                        return;
+                    };
+                    let receipt = match serde_json::from_slice::<DetectionReceipt>(payload) {
+                        Ok(receipt) => receipt,
+                        Err(e) => {
+                            self.reject_payload(&peer, signed.as_ref(), &format!("Malformed receipt: {}", e));
+                            return;
+                        }
+                    };
+                    // Nodes gossip only the receipts they signed themselves
+                    let invalid = match (&signed, receipt.verify()) {
+                        (_, Err(e)) => Some(format!("{:#}", e)),
+                        (Some(signed), Ok(())) if signed.signer != receipt.node => {
+                            Some(format!("Gossiped a receipt signed by {:?}", receipt.node))
+                        }
+                        _ => None,
+                    };
+                    if let Some(reason) = invalid {
+                        self.reject_payload(&peer, signed.as_ref(), &reason);
+                        return;
+                    }
+                    match book.accept(receipt) {
+                        Ok(useful) => self.ledger.record_intel(&peer, useful),
+                        Err(e) => {
+                            debug!("Rejected receipt from {}: {:#}", peer, e);
+                            self.ledger.record_invalid(&peer);
+                        }
+                    }
+                    return;
+                }
+                match serde_json::from_slice::<ThreatIntel>(payload) {
+                    Ok(intel) if intel.is_valid() => {
+                        let useful = self.remember(intel);
+                        self.ledger.record_intel(&peer, useful);
+                    }
+                    Ok(intel) => self.reject_payload(&peer, signed.as_ref(), &format!("Invalid intel for {}", intel.target_address)),
+                    Err(e) => self.reject_payload(&peer, signed.as_ref(), &format!("Malformed intel: {}", e)),
+                }
+            }
+            SwarmEvent::Behaviour(ShieldBehaviourEvent::Intel(request_response::Event::Message {
+                peer,
+                message: request_response::Message::Request { request, channel, .. },
+            })) => {
+                let congested = queued.len() + deferred.len() >= self.config.reciprocity.congestion_queue_depth;
+                let pending = PendingRequest { peer, query: request, channel };
+                
+                match self.ledger.serve_decision(&peer.to_string(), congested) {
+                    ServeDecision::Serve => queued.push_back(pending),
+                    ServeDecision::Deprioritize => {
+                        deferred.push_back(pending);
+                        if deferred.len() > self.config.reciprocity.max_deferred_requests {
+                            if let Some(dropped) = deferred.pop_front() {
+                                self.ledger.record_dropped(&dropped.peer.to_string());
+                                let _ = swarm.behaviour_mut().intel.send_response(dropped.channel, IntelResponse::Busy);
+                            }
+                        }
+                    }
+                    ServeDecision::Refuse => {
+                        let _ = swarm.behaviour_mut().intel.send_response(pending.channel, IntelResponse::Busy);
+                    }
+                }
+            }
+            SwarmEvent::Behaviour(ShieldBehaviourEvent::Intel(request_response::Event::Message {
+                peer,
+                message: request_response::Message::Response { response: IntelResponse::Intel(intel), .. },
+            })) => {
+                for item in intel {
+                    if item.is_valid() {
+                        let useful = self.remember(item);
+                        self.ledger.record_intel(&peer.to_string(), useful);
+                    } else {
+                        self.ledger.record_invalid(&peer.to_string());
+                    }
+                }
+            }
+            SwarmEvent::Behaviour(ShieldBehaviourEvent::Verdicts(request_response::Event::Message {
+                peer,
+                message: request_response::Message::Request { request, channel, .. },
+            })) => {
+                if self.ledger.is_blocked(&peer.to_string()) {
+                    return;
+                }
+                let response = self.cross_checker
+                    .get()
+                    .map_or(VerdictResponse { digests: Vec::new() }, |checker| checker.answer(&request));
+                let _ = swarm.behaviour_mut().verdicts.send_response(channel, response);
+            }
+            SwarmEvent::Behaviour(ShieldBehaviourEvent::Verdicts(request_response::Event::Message {
+                peer,
+                message: request_response::Message::Response { response, .. },
+            })) => {
+                if let Some(checker) = self.cross_checker.get() {
+                    checker.collect(&peer, response);
+                }
+            }
+            SwarmEvent::Behaviour(ShieldBehaviourEvent::DagSync(request_response::Event::Message {
+                peer,
+                message: request_response::Message::Request { request, channel, .. },
+            })) => {
+                let Some(sync) = self.dag_sync.get() else {
+                    return;
+                };
+                if self.ledger.is_blocked(&peer.to_string()) {
+                    return;
+                }
+                match sync.answer(&request) {
+                    Ok(response) => {
+                        let _ = swarm.behaviour_mut().dag_sync.send_response(channel, response);
+                    }
+                    // Dropping the channel fails the peer's request
+                    Err(e) => warn!("⚠️ Failed to answer DAG sync request from {}: {:#}", peer, e),
+                }
+            }
+            SwarmEvent::Behaviour(ShieldBehaviourEvent::DagSync(request_response::Event::Message {
+                peer,
+                message: request_response::Message::Response { response, .. },
+            })) => {
+                if let Some(sync) = self.dag_sync.get() {
+                    for request in sync.collect(&peer, response) {
+                        swarm.behaviour_mut().dag_sync.send_request(&peer, request);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    /// Close the last cross-check round and ask a random set of peers about a fresh sample
+    /// Count a rejected payload against the peer and, when its envelope was signed, keep it as evidence
+    fn reject_payload(&self, peer: &str, signed: Option<&SignedGossip>, reason: &str) {
+        debug!("Rejected gossip payload from {}: {}", peer, reason);
+        self.ledger.record_invalid(peer);
+        if let (Some(auth), Some(signed)) = (self.gossip_auth.get(), signed) {
+            auth.invalid_payload(peer, signed, reason);
+        }
+    }
+    
+    fn start_crosscheck_round(&self, swarm: &mut Swarm<ShieldBehaviour>) {
+        let Some(checker) = self.cross_checker.get() else {
+            return;
+        };
+        let Some(query) = checker.next_round() else {
+            return;
+        };
+        
+        let peers: Vec<PeerId> = swarm
+            .connected_peers()
+            .filter(|peer| !self.ledger.is_blocked(&peer.to_string()))
+            .copied()
+            .choose_multiple(&mut ethers::core::rand::thread_rng(), checker.peers_per_round());
+        debug!("🔁 Cross-checking {} verdicts with {} peers", query.tx_hashes.len(), peers.len());
+        for peer in peers {
+            swarm.behaviour_mut().verdicts.send_request(&peer, query.clone());
+        }
+    }
+    
+    /// Reconcile DAG tips with a random peer, asking for its checkpoints while this node has none
+    fn start_sync_round(&self, swarm: &mut Swarm<ShieldBehaviour>) {
+        let Some(sync) = self.dag_sync.get() else {
+            return;
+        };
+        let peer = swarm
+            .connected_peers()
+            .filter(|peer| !self.ledger.is_blocked(&peer.to_string()))
+            .copied()
+            .choose(&mut ethers::core::rand::thread_rng());
+        let Some(peer) = peer else {
+            return;
+        };
+        debug!("🔃 Syncing DAG with {}", peer);
+        for request in sync.next_round() {
+            swarm.behaviour_mut().dag_sync.send_request(&peer, request);
+        }
+    }
+    
+    /// Answer queued requests first; deferred free-riders only get leftover capacity
+    fn serve_pending(
+        &self,
+        swarm: &mut Swarm<ShieldBehaviour>,
+        queued: &mut VecDeque<PendingRequest>,
+        deferred: &mut VecDeque<PendingRequest>,
+    ) {
+        for _ in 0..SERVE_BATCH {
+            let Some(pending) = queued.pop_front().or_else(|| deferred.pop_front()) else {
+                break;
+            };
+            let response = self.answer(&pending.query);
+            if swarm.behaviour_mut().intel.send_response(pending.channel, response).is_ok() {
+                self.ledger.record_served(&pending.peer.to_string());
+            }
+        }
+    }
+}
@@ -1,19 +1,71 @@
 //! Core DAGShield node implementation
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::{RwLock, mpsc};
+use tokio::sync::{Notify, RwLock};
 use tracing::{info, warn, error, debug};
 use uuid::Uuid;
 
-use crate::config::NodeConfig;
-use crate::dag::DAGProcessor;
-use crate::ai::ThreatDetector;
-use crate::blockchain::BlockchainClient;
-use crate::network::NetworkManager;
+use crate::archive::Archiver;
+use crate::chain_watch::{self, ChainWatch};
+use crate::config::{ExecutorKind, NodeConfig};
+use crate::crosscheck::CrossChecker;
+use crate::crash::Supervisor;
+use crate::cursor::EventCursor;
+use crate::dag::{DAGProcessor, DagShape, ShapeBenchmark, Transaction};
+use crate::degradation::{Degradation, Subsystem};
+use crate::ai::bytecode::BytecodeAnalyzer;
+use crate::ai::graph::{AddressGraph, GraphFeatures};
+use crate::ai::pipeline::{Detection, DetectionPipeline, UrgencyCheck};
+use crate::ai::robustness::RobustnessReport;
+use crate::ai::{ThreatDetectionResult, ThreatDetector, ThreatLabel};
+use crate::backtest::Backtester;
+use crate::alert_cache::VerifiedAlertCache;
+use crate::audit::{AuditEvent, AuditLog};
+use crate::blockchain::{self, BlockchainClient};
+use crate::challenge::ChallengeSpec;
+use crate::chaos;
+use crate::commitment::{DetectionLeaf, EpochCommitter};
+use crate::dag_sync::DagSync;
+use crate::network::{NetworkManager, ThreatIntel};
+use crate::retention::RetentionJanitor;
+use crate::rewards::RewardTracker;
+use crate::stake::StakeManager;
+use crate::rollback::ArtifactGuard;
+use crate::updater::Updater;
 use crate::energy::EnergyMonitor;
-use crate::metrics::MetricsCollector;
-use crate::storage::NodeStorage;
+use crate::executor::EvmCallExecutor;
+use crate::finality::EpochFinalizer;
+use crate::fleet::FleetAgent;
+use crate::gas_oracle::GasOracle;
+use crate::gossip::GossipAuth;
+use crate::history::ReportHistory;
+use crate::heartbeat::HeartbeatPacer;
+use crate::governor::{ResourceGovernor, WorkClass, WorkDecision, WorkToken};
+use crate::ipfs::{spawn_model_pin, EvidenceBundle, IpfsClient};
+use crate::light_client::LightClientServer;
+use crate::loadgen::{self, LoadGenerator};
+use crate::maintenance::{MaintenanceControl, Stage};
+use crate::memory::MemoryBudget;
+use crate::ingest::BlockIngester;
+use crate::mempool::MempoolScanner;
+use crate::report_queue::{Failure, ReportQueue};
+use crate::reporting_guard::ReportingGuard;
+use crate::metrics::{pipeline_latency, MetricsCollector, PipelineStage};
+use crate::mirror::TrafficMirror;
+use crate::pattern_feed::PatternFeed;
+use crate::penalty::{Penalty, PenaltyMonitor};
+use crate::query::QueryEngine;
+use crate::receipts::{DetectionReceipt, ReceiptBook};
+use crate::screening::ScreeningServer;
+use crate::stats_report::StatsReporter;
+use crate::status::StatusSource;
+use crate::storage::{self, NodeStorage};
+use crate::threat::ThreatClass;
+use crate::watchlist::{WatchlistAlert, Watchlists};
 
 #[derive(Debug, Clone)]
 pub struct NodeStats {
@@ -22,6 +74,67 @@ pub struct NodeStats {
     pub reputation_score: u32,
     pub energy_efficiency: u32,
     pub uptime_seconds: u64,
+    /// Current main-loop interval, adapted to ingestion and threat rates
+    pub heartbeat_interval_secs: f64,
+    /// Slashes, deactivations and reputation drops since startup
+    pub penalties: u64,
+    pub last_penalty: Option<Penalty>,
+}
+
+/// Verdicts the detection consumer handled, drained by each heartbeat
+#[derive(Debug, Default)]
+struct DetectionCounts {
+    scored: AtomicUsize,
+    flagged: AtomicUsize,
+}
+
+impl DetectionCounts {
+    /// Scored and flagged transactions since the last call
+    fn take(&self) -> (usize, usize) {
+        (self.scored.swap(0, Ordering::Relaxed), self.flagged.swap(0, Ordering::Relaxed))
+    }
+}
+
+/// Namespace in `NodeStorage` holding the threat reports this node submitted
+pub const THREAT_REPORT_NAMESPACE: &str = "threat_reports";
+
+/// Namespace in `NodeStorage` recording which detection pipeline produced each threat report
+pub const REPORT_PIPELINE_NAMESPACE: &str = "threat_report_pipeline";
+const PIPELINE_NAMESPACE: &str = "pipeline";
+const SWEPT_FINGERPRINT_KEY: &str = "swept_fingerprint";
+
+/// Challenges this node submitted a solution to, keyed by challenge id, until their deadline passes
+const SOLVED_CHALLENGE_NAMESPACE: &str = "solved_challenges";
+
+/// How long a drain waits for the DAG to empty
+const DRAIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Kept beside a [`ThreatReportRecord`] under the same key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportPipeline {
+    pub pipeline_fingerprint: String,
+    /// Produced by a model, pattern set or thresholds that have since been replaced
+    pub stale: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreatReportRecord {
+    pub transaction_id: String,
+    pub target_address: String,
+    pub chain_id: u64,
+    pub threat_type: ThreatClass,
+    pub confidence: u32,
+    pub tx_hash: String,
+    /// IPFS CID of the pinned evidence bundle, if pinning succeeded
+    pub evidence_cid: Option<String>,
+    pub reported_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SolvedChallenge {
+    tx_hash: String,
+    deadline: u64,
+    solved_at: u64,
 }
 
 #[derive(Debug)]
@@ -37,152 +150,1413 @@ pub struct DAGShieldNode {
     config: NodeConfig,
     dag_processor: Arc<DAGProcessor>,
     threat_detector: Option<Arc<ThreatDetector>>,
+    /// Streams processed transactions from the DAG through detection; `None` without AI
+    detection: Option<Arc<DetectionPipeline>>,
+    /// Verdicts handled since the last heartbeat
+    detection_counts: Arc<DetectionCounts>,
     blockchain_client: Arc<BlockchainClient>,
     network_manager: Arc<NetworkManager>,
     energy_monitor: Arc<EnergyMonitor>,
     metrics_collector: Arc<MetricsCollector>,
     storage: Arc<NodeStorage>,
+    governor: Arc<ResourceGovernor>,
+    memory_budget: Arc<MemoryBudget>,
+    alert_cache: Option<Arc<VerifiedAlertCache>>,
+    ipfs: Option<Arc<IpfsClient>>,
+    gas_oracle: Option<Arc<GasOracle>>,
+    artifact_guard: Option<Arc<ArtifactGuard>>,
+    updater: Option<Arc<Updater>>,
+    stats_reporter: Option<Arc<StatsReporter>>,
+    stake_manager: Arc<StakeManager>,
+    penalty_monitor: Option<Arc<PenaltyMonitor>>,
+    reporting_guard: Option<Arc<ReportingGuard>>,
+    report_queue: Arc<ReportQueue>,
+    watchlists: Option<Arc<Watchlists>>,
+    address_graph: Option<Arc<AddressGraph>>,
+    cross_checker: Option<Arc<CrossChecker>>,
+    pattern_feed: Option<Arc<PatternFeed>>,
+    backtester: Option<Arc<Backtester>>,
+    epoch_committer: Option<Arc<EpochCommitter>>,
+    mempool: Option<Arc<MempoolScanner>>,
+    block_ingester: Option<Arc<BlockIngester>>,
+    epoch_finalizer: Option<Arc<EpochFinalizer>>,
+    dag_sync: Option<Arc<DagSync>>,
+    mirror: Option<Arc<TrafficMirror>>,
+    receipts: Option<Arc<ReceiptBook>>,
+    gossip_auth: Option<Arc<GossipAuth>>,
+    loadgen: Option<Arc<LoadGenerator>>,
+    light_client: Option<Arc<LightClientServer>>,
+    chain_watch: Option<Arc<ChainWatch>>,
+    retention: Arc<RetentionJanitor>,
+    report_history: Arc<ReportHistory>,
+    audit_log: Arc<AuditLog>,
     stats: Arc<RwLock<NodeStats>>,
-    shutdown_tx: Option<mpsc::Sender<()>>,
+    shutdown: Arc<Notify>,
+    maintenance: Arc<MaintenanceControl>,
+    degradation: Arc<Degradation>,
+    /// Restarts panicked subsystem tasks and keeps their crash reports
+    supervisor: Arc<Supervisor>,
+    /// Registration is retried from the heartbeat when the chain was unreachable at startup
+    registered: Arc<AtomicBool>,
 }
 
 impl DAGShieldNode {
+    /// `enable_ai` can only turn detection off; it runs when the config's `enable_ai` allows it too
     pub async fn new(
-        config: NodeConfig,
+        mut config: NodeConfig,
         node_id: Option<String>,
         enable_ai: bool,
     ) -> Result<Self> {
         let node_id = node_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+        let enable_ai = enable_ai && config.enable_ai;
         
         info!("🔧 Initializing DAGShield node components...");
+        info!("🧩 Capabilities: ai={} oracle={} cross_chain={} p2p={} admin_api={}",
+              enable_ai, config.enable_oracle, config.enable_cross_chain, config.enable_p2p, config.enable_admin_api);
+        
+        // Detect the chain before anything is built for it
+        chain_watch::resolve_chain_id(&mut config.blockchain).await?;
         
         // Initialize storage
         let storage = Arc::new(NodeStorage::new(&config.storage).await?);
         
+        // Which subsystems have failed over to their fallbacks
+        let degradation = Arc::new(Degradation::new()?);
+        
+        // Catch panics in subsystem tasks, report and restart them
+        let supervisor = Arc::new(Supervisor::new(&config.crash_reports, &node_id, Arc::clone(&storage))?);
+        
+        // Initialize the inference pool and the DAG and heavy-work budgets
+        let governor = Arc::new(ResourceGovernor::new(&config.workers, &config.work_tokens)?);
+        
         // Initialize DAG processor
-        let dag_processor = Arc::new(DAGProcessor::new(&config).await?);
+        let dag_processor = Arc::new(DAGProcessor::new(&config, Arc::clone(&governor)).await?);
+        if config.dag_checkpoints.enabled {
+            dag_processor.attach_checkpoint_store(Arc::clone(&storage))?;
+        }
+        if config.executor.kind == ExecutorKind::EvmCall {
+            let rpc_url = if config.executor.rpc_url.is_empty() { &config.blockchain.rpc_url } else { &config.executor.rpc_url };
+            let executor = EvmCallExecutor::new(&config.executor, rpc_url, config.blockchain.chain_id)?;
+            dag_processor.attach_executor(Arc::new(executor));
+        }
         
         // Initialize AI threat detector (optional)
         let threat_detector = if enable_ai {
-            Some(Arc::new(ThreatDetector::new(&config.ai).await?))
+            let detector = Arc::new(ThreatDetector::new(&config.ai, Arc::clone(&governor)).await?);
+            detector.attach_degradation(Arc::clone(&degradation));
+            detector.attach_pattern_store(Arc::clone(&storage)).await?;
+            detector.attach_calibration_store(Arc::clone(&storage)).await?;
+            Some(detector)
         } else {
             None
         };
         
         // Initialize blockchain client
         let blockchain_client = Arc::new(BlockchainClient::new(&config.blockchain).await?);
+        chain_watch::remember_chain(&storage, config.blockchain.chain_id)?;
+        
+        // Compare every configured RPC with the config, holding writes while the write endpoint disagrees
+        let chain_watch = if config.chain_watch.enabled {
+            let watch = Arc::new(ChainWatch::new(&config.chain_watch, &config, Arc::clone(&blockchain_client))?);
+            watch.check().await;
+            Some(watch)
+        } else {
+            None
+        };
+        
+        // Scan target contract code on the node's chain
+        if let (Some(detector), true) = (&threat_detector, config.ai.bytecode.enabled) {
+            let analyzer = BytecodeAnalyzer::new(&config.ai.bytecode, Arc::clone(&blockchain_client))?;
+            detector.attach_bytecode_analyzer(Arc::new(analyzer));
+        }
+        
+        // Track per-chain fee percentiles for transaction pricing
+        let gas_oracle = if config.gas_oracle.enabled {
+            let oracle = Arc::new(GasOracle::new(&config.gas_oracle)?);
+            blockchain_client.attach_gas_oracle(Arc::clone(&oracle));
+            Some(oracle)
+        } else {
+            None
+        };
         
         // Initialize network manager
-        let network_manager = Arc::new(NetworkManager::new(&config.network, &node_id).await?);
+        let network_manager = Arc::new(NetworkManager::new(&config.network, &node_id, Arc::clone(&storage)).await?);
+        
+        // Compare verdicts with peers to catch a drifted or misconfigured node
+        let cross_checker = if threat_detector.is_some() && config.enable_p2p && config.network.cross_check.enabled {
+            let checker = Arc::new(CrossChecker::new(&config.network.cross_check)?);
+            network_manager.attach_cross_checker(Arc::clone(&checker));
+            Some(checker)
+        } else {
+            None
+        };
         
         // Initialize energy monitor
-        let energy_monitor = Arc::new(EnergyMonitor::new(&config.energy).await?);
+        let energy_monitor = Arc::new(EnergyMonitor::new(&config.energy, Arc::clone(&governor)).await?);
+        energy_monitor.attach_history(Arc::clone(&storage));
+        
+        // Initialize the local cache of network-verified alerts
+        let alert_cache = if config.alert_cache.enabled {
+            let cache = Arc::new(VerifiedAlertCache::new(&config.alert_cache, Arc::clone(&storage))?);
+            if let Some(detector) = &threat_detector {
+                detector.attach_alert_cache(Arc::clone(&cache));
+            }
+            Some(cache)
+        } else {
+            None
+        };
+        
+        // Initialize IPFS client for evidence and model distribution
+        let ipfs = if config.ipfs.enabled {
+            Some(Arc::new(IpfsClient::new(&config.ipfs)?))
+        } else {
+            None
+        };
+        
+        // Roll back model/pattern updates that lose accuracy
+        let artifact_guard = if config.rollback.enabled {
+            let guard = Arc::new(ArtifactGuard::new(&config.rollback, Arc::clone(&storage), &config.ai.model_path)?);
+            if let Some(detector) = &threat_detector {
+                detector.attach_artifact_guard(Arc::clone(&guard));
+            }
+            Some(guard)
+        } else {
+            None
+        };
+        
+        // Pull signed threat pattern bundles
+        let pattern_feed = match (&threat_detector, config.pattern_feed.enabled) {
+            (Some(detector), true) => Some(Arc::new(PatternFeed::new(
+                &config.pattern_feed,
+                config.ai.update_interval_hours,
+                Arc::clone(detector),
+                Arc::clone(&storage),
+                ipfs.clone(),
+                artifact_guard.clone(),
+            )?)),
+            (None, true) => {
+                warn!("⚠️ Pattern feed enabled but AI detection is disabled, not following it");
+                None
+            }
+            _ => None,
+        };
+        
+        // Replay known exploits through the pipeline to track detection coverage
+        let backtester = match (&threat_detector, config.backtest.enabled) {
+            (Some(_), true) => Some(Arc::new(Backtester::new(&config)?)),
+            (None, true) => {
+                warn!("⚠️ Backtesting enabled but AI detection is disabled, not running it");
+                None
+            }
+            _ => None,
+        };
+        
+        // Experimental: commit each epoch's detections as a Merkle root
+        let epoch_committer = match (&threat_detector, config.commitments.enabled) {
+            (Some(_), true) => {
+                let blockchain = config.commitments.post_on_chain.then(|| Arc::clone(&blockchain_client));
+                Some(Arc::new(EpochCommitter::new(&config.commitments, Arc::clone(&storage), blockchain)?))
+            }
+            (None, true) => {
+                warn!("⚠️ Detection commitments enabled but AI detection is disabled, not committing");
+                None
+            }
+            _ => None,
+        };
+        
+        // Scan pending transactions ahead of their confirmation
+        let mempool = match (&threat_detector, config.mempool.enabled) {
+            (Some(_), true) => Some(Arc::new(MempoolScanner::new(&config.mempool, &config.blockchain, Arc::clone(&dag_processor))?)),
+            (None, true) => {
+                warn!("⚠️ Mempool scanning enabled but AI detection is disabled, not scanning");
+                None
+            }
+            _ => None,
+        };
+        
+        // Feed confirmed blocks into the DAG
+        let block_ingester = match (&threat_detector, config.ingest.enabled) {
+            (Some(_), true) => Some(Arc::new(BlockIngester::new(
+                &config.ingest,
+                Arc::clone(&blockchain_client),
+                Arc::clone(&dag_processor),
+                Arc::clone(&storage),
+            )?)),
+            (None, true) => {
+                warn!("⚠️ Block ingestion enabled but AI detection is disabled, not ingesting");
+                None
+            }
+            _ => None,
+        };
+        
+        // Commit the epoch roots DAG checkpoints seal
+        let epoch_finalizer = match (config.dag_checkpoints.enabled, config.dag_epochs.commit_on_chain) {
+            (true, true) => Some(Arc::new(EpochFinalizer::new(
+                &config.dag_epochs,
+                Arc::clone(&storage),
+                Arc::clone(&blockchain_client),
+            )?)),
+            (false, true) => {
+                warn!("⚠️ DAG epoch commitments enabled but DAG checkpoints are disabled, not committing");
+                None
+            }
+            _ => None,
+        };
+        
+        // Catch up with peers' DAGs instead of starting empty
+        let dag_sync = if config.enable_p2p && config.dag_sync.enabled {
+            let sync = Arc::new(DagSync::new(
+                &config.dag_sync,
+                Arc::clone(&dag_processor),
+                network_manager.ledger(),
+                Arc::clone(&blockchain_client),
+            )?);
+            network_manager.attach_dag_sync(Arc::clone(&sync));
+            Some(sync)
+        } else {
+            None
+        };
+        
+        // Shadow-deploy a second pipeline on a copy of live traffic
+        let mirror = match (&threat_detector, config.mirror.enabled) {
+            (Some(detector), true) => {
+                let mirror = TrafficMirror::new(&config.mirror, Arc::clone(detector), Arc::clone(&governor)).await?;
+                Some(Arc::new(mirror))
+            }
+            (None, true) => {
+                warn!("⚠️ Traffic mirroring enabled but AI detection is disabled, not mirroring");
+                None
+            }
+            _ => None,
+        };
+        
+        // Sign first-seen receipts for detections and keep the ones peers sign
+        let receipts = match (&threat_detector, config.receipts.enabled) {
+            (Some(_), true) => {
+                let book = Arc::new(ReceiptBook::new(&config.receipts, Arc::clone(&storage), Arc::clone(&blockchain_client))?);
+                if config.enable_p2p {
+                    network_manager.attach_receipt_book(Arc::clone(&book));
+                }
+                Some(book)
+            }
+            (None, true) => {
+                warn!("⚠️ Detection receipts enabled but AI detection is disabled, not issuing receipts");
+                None
+            }
+            _ => None,
+        };
+        
+        // Sign gossip with the node key and keep evidence against peers that misuse theirs
+        let gossip_auth = if config.enable_p2p {
+            let auth = Arc::new(GossipAuth::new(
+                &config.network.gossip,
+                Arc::clone(&storage),
+                Arc::clone(&blockchain_client),
+                ipfs.clone(),
+            )?);
+            network_manager.attach_gossip_auth(Arc::clone(&auth));
+            Some(auth)
+        } else {
+            None
+        };
+        
+        // Synthetic load runs for soak tests, started through the admin API
+        let loadgen = match (&threat_detector, config.loadgen.enabled) {
+            (Some(_), true) => {
+                let generator = LoadGenerator::new(&config.loadgen, Arc::clone(&dag_processor), config.blockchain.chain_id)?;
+                Some(Arc::new(generator))
+            }
+            (None, true) => {
+                warn!("⚠️ Load generator enabled but AI detection is disabled, not serving load runs");
+                None
+            }
+            _ => None,
+        };
+        
+        // Follow the signed release channel
+        let updater = if config.updater.enabled {
+            Some(Arc::new(Updater::new(&config.updater, Arc::clone(&storage))?))
+        } else {
+            None
+        };
+        
+        // Contribute per-chain processing stats to the network dashboard
+        let stats_reporter = if config.stats_reporting.enabled {
+            Some(Arc::new(StatsReporter::new(&config.stats_reporting, Arc::clone(&blockchain_client))?))
+        } else {
+            None
+        };
+        
+        // Watch the stake against the contract minimum
+        let stake_manager = Arc::new(StakeManager::new(&config.node, Arc::clone(&blockchain_client))?);
+        
+        let report_queue = Arc::new(ReportQueue::new(&config.report_queue, Arc::clone(&storage)));
+        
+        // Suspend auto-reporting when the flag rate runs away from its baseline
+        let reporting_guard = match (&threat_detector, config.reporting_guard.enabled) {
+            (Some(_), true) => Some(Arc::new(ReportingGuard::new(
+                &config.reporting_guard,
+                Arc::clone(&storage),
+                Arc::clone(&report_queue),
+            )?)),
+            _ => None,
+        };
         
         // Initialize metrics collector
-        let metrics_collector = Arc::new(MetricsCollector::new(&config.metrics).await?);
+        let metrics_collector = Arc::new(MetricsCollector::new(&config.metrics, config.enable_admin_api).await?);
+        metrics_collector.attach_peer_ledger(network_manager.ledger());
+        metrics_collector.attach_dag_processor(Arc::clone(&dag_processor));
+        if let Some(detector) = &threat_detector {
+            metrics_collector.attach_threat_detector(Arc::clone(detector));
+        }
+        if let Some(guard) = &reporting_guard {
+            metrics_collector.attach_reporting_guard(Arc::clone(guard));
+        }
+        metrics_collector.attach_rewards(Arc::new(RewardTracker::new(
+            Arc::clone(&blockchain_client),
+            Arc::clone(&storage),
+        )));
+        metrics_collector.attach_stake(Arc::clone(&stake_manager));
+        if let Some(mirror) = &mirror {
+            metrics_collector.attach_mirror(Arc::clone(mirror));
+        }
+        if let Some(generator) = &loadgen {
+            metrics_collector.attach_loadgen(Arc::clone(generator));
+        }
+        if let Some(auth) = &gossip_auth {
+            metrics_collector.attach_gossip_auth(Arc::clone(auth));
+        }
+        if let Some(watch) = &chain_watch {
+            metrics_collector.attach_chain_watch(Arc::clone(watch));
+        }
+        
+        // Named model versions, managed through the admin API
+        if let (Some(detector), true) = (&threat_detector, config.ai.registry.enabled) {
+            let registry = detector.attach_model_registry(Arc::clone(&storage))?;
+            metrics_collector.attach_model_registry(registry, Arc::clone(detector));
+        }
+        
+        // Interaction history behind the detector's graph features
+        let address_graph = match (&threat_detector, config.address_graph.enabled) {
+            (Some(detector), true) => {
+                let graph = Arc::new(AddressGraph::new(&config.address_graph, Arc::clone(&storage)));
+                if let Some(cache) = &alert_cache {
+                    graph.attach_alert_cache(Arc::clone(cache));
+                }
+                detector.register_feature_extractor(Arc::new(GraphFeatures(Arc::clone(&graph)))).await;
+                Some(graph)
+            }
+            _ => None,
+        };
+        
+        // Let read replicas pull this node's reports, alerts and graph
+        if config.replication.serve_snapshots {
+            if config.replication.auth_token.is_empty() {
+                bail!("replication.serve_snapshots requires replication.auth_token");
+            }
+            if !config.metrics.enabled {
+                warn!("⚠️ Snapshot serving enabled but metrics are disabled, replicas can't reach it");
+            }
+            metrics_collector.attach_snapshot_source(Arc::clone(&storage), config.replication.auth_token.clone());
+        }
+        
+        let report_history = Arc::new(ReportHistory::new(Arc::clone(&storage))?);
+        
+        // Risk lookups and push warnings for wallets
+        let light_client = config.light_client.enabled.then(|| {
+            Arc::new(LightClientServer::new(&config.light_client, Arc::clone(&report_history), alert_cache.clone()))
+        });
+        
+        // Operator pause, resume and drain controls
+        let maintenance = Arc::new(MaintenanceControl::new());
+        dag_processor.attach_maintenance(Arc::clone(&maintenance));
+        blockchain_client.attach_maintenance(Arc::clone(&maintenance));
+        metrics_collector.attach_maintenance(Arc::clone(&maintenance));
+        metrics_collector.attach_degradation(Arc::clone(&degradation));
+        
+        // Addresses operators want watched closely
+        let watchlists = if config.watchlists.enabled {
+            let watchlists = Arc::new(Watchlists::new(&config.watchlists, Arc::clone(&storage))?);
+            metrics_collector.attach_watchlists(Arc::clone(&watchlists));
+            Some(watchlists)
+        } else {
+            None
+        };
+        
+        // Transactions touching watched addresses jump the detection queue
+        let detection = match &threat_detector {
+            Some(detector) => {
+                let is_urgent = watchlists.clone().map(|watchlists| -> UrgencyCheck {
+                    Arc::new(move |tx: &Transaction| watchlists.matches(tx).is_some())
+                });
+                Some(Arc::new(detector.detection_pipeline(is_urgent)?))
+            }
+            None => None,
+        };
+        
+        // Track memory of the large in-memory caches so they can shrink before the OOM killer steps in
+        let memory_budget = Arc::new(MemoryBudget::new(&config.memory)?);
+        memory_budget.register(dag_processor.clone()).await;
+        if let Some(detector) = &threat_detector {
+            memory_budget.register(detector.clone()).await;
+        }
+        
+        // Counters are rebuilt from the audit log, so they carry across restarts
+        let audit_log = Arc::new(AuditLog::new(Arc::clone(&storage)));
+        let audited = audit_log.replay(None)?;
+        audit_log.record_config("startup", &config, audited.config_digest.as_deref())?;
+        network_manager.ledger().attach_audit_log(Arc::clone(&audit_log));
         
         let stats = Arc::new(RwLock::new(NodeStats {
-            threats_detected: 0,
-            challenges_completed: 0,
-            reputation_score: 100,
-            energy_efficiency: 50,
+            threats_detected: audited.threats_detected,
+            challenges_completed: audited.challenges_completed,
+            reputation_score: audited.reputation_score.unwrap_or(100),
+            energy_efficiency: audited.energy_efficiency.unwrap_or(50),
             uptime_seconds: 0,
+            heartbeat_interval_secs: config.node.heartbeat_interval_secs as f64,
+            penalties: 0,
+            last_penalty: None,
+        }));
+        
+        let penalty_monitor = if config.penalty_alerts.enabled {
+            Some(Arc::new(PenaltyMonitor::new(
+                &config.penalty_alerts,
+                &node_id,
+                Arc::clone(&blockchain_client),
+                Arc::clone(&maintenance),
+                Arc::clone(&stats),
+            )?))
+        } else {
+            None
+        };
+        
+        // The running node's reports for the `status`, `stats` and `history` subcommands
+        metrics_collector.attach_status_source(Arc::new(StatusSource {
+            node_id: node_id.clone(),
+            stats: Arc::clone(&stats),
+            dag_processor: Arc::clone(&dag_processor),
+            threat_detector: threat_detector.clone(),
+            energy_monitor: Arc::clone(&energy_monitor),
+            maintenance: Arc::clone(&maintenance),
+            peer_ledger: network_manager.ledger(),
+            report_history: Arc::clone(&report_history),
+            alert_cache: alert_cache.clone(),
+            degradation: Arc::clone(&degradation),
+            supervisor: Arc::clone(&supervisor),
         }));
         
+        // Read-only SQL over stored data for the `query` subcommand
+        if config.query.enabled {
+            metrics_collector.attach_query_engine(Arc::new(QueryEngine::new(&config.query, Arc::clone(&storage))));
+        }
+        if let Some(committer) = &epoch_committer {
+            metrics_collector.attach_epoch_committer(Arc::clone(committer));
+        }
+        
+        // Expire stored data per category; previews are served even when not scheduled
+        let retention = Arc::new(RetentionJanitor::new(
+            &config.retention,
+            Arc::clone(&storage),
+            ipfs.clone(),
+            Arc::clone(&report_history),
+            Arc::clone(&audit_log),
+        ));
+        metrics_collector.attach_retention(Arc::clone(&retention));
+        if config.archive.enabled {
+            let archiver = Arc::new(Archiver::new(
+                &config.archive,
+                Arc::clone(&storage),
+                ipfs.clone(),
+                blockchain_client.wallet_address(),
+            )?);
+            retention.attach_archiver(Arc::clone(&archiver));
+            metrics_collector.attach_archiver(archiver);
+        }
+        
         Ok(Self {
             node_id,
             config,
             dag_processor,
             threat_detector,
+            detection,
+            detection_counts: Arc::new(DetectionCounts::default()),
             blockchain_client,
             network_manager,
             energy_monitor,
             metrics_collector,
             storage,
+            governor,
+            memory_budget,
+            alert_cache,
+            ipfs,
+            gas_oracle,
+            artifact_guard,
+            updater,
+            stats_reporter,
+            stake_manager,
+            penalty_monitor,
+            reporting_guard,
+            report_queue,
+            watchlists,
+            address_graph,
+            cross_checker,
+            pattern_feed,
+            backtester,
+            epoch_committer,
+            mempool,
+            block_ingester,
+            epoch_finalizer,
+            dag_sync,
+            mirror,
+            receipts,
+            gossip_auth,
+            loadgen,
+            light_client,
+            chain_watch,
+            retention,
+            report_history,
+            audit_log,
             stats,
-            shutdown_tx: None,
+            shutdown: Arc::new(Notify::new()),
+            maintenance,
+            degradation,
+            supervisor,
+            registered: Arc::new(AtomicBool::new(false)),
         })
     }
     
     pub async fn start(&self) -> Result<()> {
         info!("🚀 Starting DAGShield node: {}", self.node_id);
         
-        // Register node on blockchain
-        self.register_on_blockchain().await?;
+        // Warm the models up before the first real transaction reaches them
+        if let Some(detector) = &self.threat_detector {
+            detector.warm_up().await;
+        }
         
-        // Start all components
-        let (shutdown_tx, mut shutdown_rx) = mpsc::channel(1);
+        // Register node on blockchain; an unreachable chain is retried from the heartbeat
+        if self.acts_on_chain() {
+            if let Err(e) = self.register_on_blockchain().await {
+                self.degradation.degrade(Subsystem::ChainRpc, format!("registration failed: {:#}", e));
+            }
+        } else {
+            info!("📡 Relay role: not registering on chain");
+        }
         
+        // Make the current model available to peers
+        if let (Some(ipfs), Some(_)) = (&self.ipfs, &self.threat_detector) {
+            if std::path::Path::new(&self.config.ai.model_path).exists() {
+                spawn_model_pin(Arc::clone(ipfs), Arc::clone(&self.storage), self.config.ai.model_path.clone());
+            }
+        }
+        
+        // Start all components, each restarted by the supervisor when it panics
         // Start DAG processor
         let dag_handle = {
             let processor = Arc::clone(&self.dag_processor);
-            let mut rx = shutdown_rx.resubscribe();
-            tokio::spawn(async move {
-                processor.start().await.unwrap_or_else(|e| {
-                    error!("DAG processor error: {}", e);
-                });
+            self.supervisor.spawn("dag", move || {
+                let processor = Arc::clone(&processor);
+                async move {
+                    processor.start().await.unwrap_or_else(|e| {
+                        error!("DAG processor error: {}", e);
+                    });
+                }
             })
         };
         
+        // Finalize processed subgraphs and prune them from the DAG
+        let checkpoint_handle = self.config.dag_checkpoints.enabled.then(|| {
+            let processor = Arc::clone(&self.dag_processor);
+            self.supervisor.spawn("dag_checkpoints", move || {
+                let processor = Arc::clone(&processor);
+                async move {
+                    processor.run_checkpoints().await.unwrap_or_else(|e| {
+                        error!("DAG checkpoint error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Drop transactions that waited too long for a dependency
+        let orphan_handle = self.config.dag_orphans.enabled.then(|| {
+            let processor = Arc::clone(&self.dag_processor);
+            self.supervisor.spawn("dag_orphans", move || {
+                let processor = Arc::clone(&processor);
+                async move {
+                    processor.run_orphan_expiry().await.unwrap_or_else(|e| {
+                        error!("DAG orphan expiry error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Stream processed transactions through the detection workers to reporting
+        let mut detection_handles = Vec::new();
+        if let (Some(pipeline), Some(detector)) = (&self.detection, &self.threat_detector) {
+            self.dag_processor.attach_detection(pipeline.ingest());
+            info!("🔍 Starting {} detection workers", pipeline.workers());
+            for _ in 0..pipeline.workers() {
+                let pipeline = Arc::clone(pipeline);
+                detection_handles.push(self.supervisor.spawn("detection", move || {
+                    let pipeline = Arc::clone(&pipeline);
+                    async move {
+                        pipeline.run_worker().await;
+                    }
+                }));
+            }
+            
+            let node = self.clone();
+            let pipeline = Arc::clone(pipeline);
+            let detector = Arc::clone(detector);
+            detection_handles.push(self.supervisor.spawn("verdicts", move || {
+                let node = node.clone();
+                let pipeline = Arc::clone(&pipeline);
+                let detector = Arc::clone(&detector);
+                async move {
+                    node.handle_detections(&pipeline, &detector).await;
+                }
+            }));
+        }
+        
+        // Feed pending transactions into the DAG
+        let mempool_handle = self.mempool.as_ref().map(|scanner| {
+            let scanner = Arc::clone(scanner);
+            self.supervisor.spawn("mempool", move || {
+                let scanner = Arc::clone(&scanner);
+                async move {
+                    scanner.start().await.unwrap_or_else(|e| {
+                        error!("Mempool scanner error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Feed confirmed blocks into the DAG
+        let ingest_handle = self.block_ingester.as_ref().map(|ingester| {
+            let ingester = Arc::clone(ingester);
+            self.supervisor.spawn("block_ingest", move || {
+                let ingester = Arc::clone(&ingester);
+                async move {
+                    ingester.start().await.unwrap_or_else(|e| {
+                        error!("Block ingestion error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Commit sealed DAG epochs on-chain
+        let finality_handle = self.epoch_finalizer.as_ref().map(|finalizer| {
+            let finalizer = Arc::clone(finalizer);
+            self.supervisor.spawn("dag_epochs", move || {
+                let finalizer = Arc::clone(&finalizer);
+                async move {
+                    finalizer.start().await.unwrap_or_else(|e| {
+                        error!("DAG epoch finality error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Add what peers' DAGs hold and this node's lacks
+        let sync_handle = self.dag_sync.as_ref().map(|sync| {
+            let sync = Arc::clone(sync);
+            self.supervisor.spawn("dag_sync", move || {
+                let sync = Arc::clone(&sync);
+                async move {
+                    sync.start().await.unwrap_or_else(|e| {
+                        error!("DAG sync error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Judge mirrored transactions with the shadow pipeline
+        let mirror_handle = self.mirror.as_ref().map(|mirror| {
+            let mirror = Arc::clone(mirror);
+            self.supervisor.spawn("mirror", move || {
+                let mirror = Arc::clone(&mirror);
+                async move {
+                    mirror.start().await.unwrap_or_else(|e| {
+                        error!("Traffic mirror error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Prune expired receipt chains and settle first-reporter claims
+        let receipts_handle = self.receipts.as_ref().map(|book| {
+            let book = Arc::clone(book);
+            self.supervisor.spawn("receipts", move || {
+                let book = Arc::clone(&book);
+                async move {
+                    book.start().await.unwrap_or_else(|e| {
+                        error!("Receipt book error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Prune and submit gossip slashing evidence
+        let gossip_handle = self.gossip_auth.as_ref().map(|auth| {
+            let auth = Arc::clone(auth);
+            self.supervisor.spawn("gossip_evidence", move || {
+                let auth = Arc::clone(&auth);
+                async move {
+                    auth.start().await.unwrap_or_else(|e| {
+                        error!("Gossip evidence error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Keep checking the configured RPCs serve the configured chain
+        let chain_watch_handle = self.chain_watch.as_ref().map(|watch| {
+            let watch = Arc::clone(watch);
+            self.supervisor.spawn("chain_watch", move || {
+                let watch = Arc::clone(&watch);
+                async move {
+                    watch.start().await.unwrap_or_else(|e| {
+                        error!("Chain watch error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Start chain event listener on every deployment, resuming from each chain's persisted cursor
+        let listener_handle = if self.config.enable_oracle {
+            let client = Arc::clone(&self.blockchain_client);
+            let cursors = client
+                .chain_ids()
+                .into_iter()
+                .map(|chain_id| Ok((chain_id, EventCursor::load(Arc::clone(&self.storage), "dagshield_contract", chain_id, 0)?)))
+                .collect::<Result<HashMap<_, _>>>()?;
+            // Shared across restarts, so a restarted listener resumes where the last one stopped
+            let cursors = Arc::new(tokio::sync::Mutex::new(cursors));
+            Some(self.supervisor.spawn("listener", move || {
+                let client = Arc::clone(&client);
+                let cursors = Arc::clone(&cursors);
+                async move {
+                    let mut cursors = cursors.lock().await;
+                    client.listen_for_events(&mut cursors).await.unwrap_or_else(|e| {
+                        error!("Chain event listener error: {}", e);
+                    });
+                }
+            }))
+        } else {
+            None
+        };
+        
+        // Keep the verified alert cache in sync with indexed events
+        let alert_cache_handle = self.alert_cache.as_ref().filter(|_| self.config.enable_oracle).map(|cache| {
+            let cache = Arc::clone(cache);
+            let client = Arc::clone(&self.blockchain_client);
+            self.supervisor.spawn("alert_cache", move || {
+                let cache = Arc::clone(&cache);
+                let client = Arc::clone(&client);
+                async move {
+                    cache.start(client).await.unwrap_or_else(|e| {
+                        error!("Alert cache error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Grade this node's verdicts as the network verifies or rejects the alerts on them
+        let feedback_handle = match (&self.alert_cache, &self.threat_detector) {
+            (Some(cache), Some(detector)) => {
+                let cache = Arc::clone(cache);
+                let detector = Arc::clone(detector);
+                let history = Arc::clone(&self.report_history);
+                Some(self.supervisor.spawn("feedback", move || {
+                    let cache = Arc::clone(&cache);
+                    let detector = Arc::clone(&detector);
+                    let history = Arc::clone(&history);
+                    async move {
+                        let mut outcomes = cache.subscribe_outcomes();
+                        loop {
+                            let outcome = match outcomes.recv().await {
+                                Ok(outcome) => outcome,
+                                Err(tokio::sync::broadcast::error::RecvError::Lagged(missed)) => {
+                                    warn!("⚠️ Missed {} alert outcomes, their verdicts stay ungraded", missed);
+                                    continue;
+                                }
+                                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                            };
+                            let label = if outcome.verified { ThreatLabel::Malicious } else { ThreatLabel::Benign };
+                            let reports = history.reports(&outcome.alert.target_address).unwrap_or_else(|e| {
+                                warn!("Failed to look up reports for alert {}: {}", outcome.alert.alert_id, e);
+                                Vec::new()
+                            });
+                            for entry in reports {
+                                let record = entry.record;
+                                if record.chain_id != outcome.alert.chain_id || record.threat_type != outcome.alert.threat_type {
+                                    continue;
+                                }
+                                if let Err(e) = detector.record_feedback(&record.transaction_id, label).await {
+                                    debug!("Not grading {}: {}", record.transaction_id, e);
+                                }
+                            }
+                        }
+                    }
+                }))
+            }
+            _ => None,
+        };
+        
+        // Start gas price sampling
+        let gas_oracle_handle = self.gas_oracle.as_ref().map(|oracle| {
+            let oracle = Arc::clone(oracle);
+            self.supervisor.spawn("gas_oracle", move || {
+                let oracle = Arc::clone(&oracle);
+                async move {
+                    oracle.start().await.unwrap_or_else(|e| {
+                        error!("Gas oracle error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Start RPC endpoint health checks
+        let rpc_health_handle = {
+            let client = Arc::clone(&self.blockchain_client);
+            self.supervisor.spawn("rpc_health", move || {
+                let client = Arc::clone(&client);
+                async move {
+                    client.monitor_endpoints().await.unwrap_or_else(|e| {
+                        error!("RPC health check error: {}", e);
+                    });
+                }
+            })
+        };
+        
+        // Start rollback of regressing model/pattern updates
+        let rollback_handle = self.artifact_guard.as_ref().map(|guard| {
+            let guard = Arc::clone(guard);
+            let detector = self.threat_detector.clone();
+            self.supervisor.spawn("rollback", move || {
+                let guard = Arc::clone(&guard);
+                let detector = detector.clone();
+                async move {
+                    guard.start(detector).await.unwrap_or_else(|e| {
+                        error!("Artifact guard error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Start release channel checks
+        let updater_handle = self.updater.as_ref().map(|updater| {
+            let updater = Arc::clone(updater);
+            self.supervisor.spawn("updater", move || {
+                let updater = Arc::clone(&updater);
+                async move {
+                    updater.start().await.unwrap_or_else(|e| {
+                        error!("Updater error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Start following the threat pattern feed
+        let pattern_feed_handle = self.pattern_feed.as_ref().map(|feed| {
+            let feed = Arc::clone(feed);
+            self.supervisor.spawn("pattern_feed", move || {
+                let feed = Arc::clone(&feed);
+                async move {
+                    feed.start().await.unwrap_or_else(|e| {
+                        error!("Pattern feed error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Start periodic exploit backtests
+        let backtest_handle = match (&self.backtester, &self.threat_detector) {
+            (Some(backtester), Some(detector)) => {
+                let backtester = Arc::clone(backtester);
+                let detector = Arc::clone(detector);
+                let governor = Arc::clone(&self.governor);
+                Some(self.supervisor.spawn("backtest", move || {
+                    let backtester = Arc::clone(&backtester);
+                    let detector = Arc::clone(&detector);
+                    let governor = Arc::clone(&governor);
+                    async move {
+                        backtester.start(detector, governor).await.unwrap_or_else(|e| {
+                            error!("Backtester error: {}", e);
+                        });
+                    }
+                }))
+            }
+            _ => None,
+        };
+        
+        // Commit detection epochs as they close
+        let commitment_handle = self.epoch_committer.as_ref().map(|committer| {
+            let committer = Arc::clone(committer);
+            self.supervisor.spawn("commitments", move || {
+                let committer = Arc::clone(&committer);
+                async move {
+                    committer.start().await.unwrap_or_else(|e| {
+                        error!("Epoch committer error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Persist and age out the address graph
+        let address_graph_handle = self.address_graph.as_ref().map(|graph| {
+            let graph = Arc::clone(graph);
+            self.supervisor.spawn("address_graph", move || {
+                let graph = Arc::clone(&graph);
+                async move {
+                    graph.start().await.unwrap_or_else(|e| {
+                        error!("Address graph error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Start per-chain stats reporting
+        let stats_report_handle = self.stats_reporter.as_ref().map(|reporter| {
+            let reporter = Arc::clone(reporter);
+            self.supervisor.spawn("stats_report", move || {
+                let reporter = Arc::clone(&reporter);
+                async move {
+                    reporter.start().await.unwrap_or_else(|e| {
+                        error!("Stats reporter error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Start stake checks
+        let stake_handle = {
+            let manager = Arc::clone(&self.stake_manager);
+            self.supervisor.spawn("stake", move || {
+                let manager = Arc::clone(&manager);
+                async move {
+                    manager.start().await.unwrap_or_else(|e| {
+                        error!("Stake manager error: {}", e);
+                    });
+                }
+            })
+        };
+        
+        // Watch for penalties on the node
+        let penalty_handle = self.penalty_monitor.as_ref().map(|monitor| {
+            let monitor = Arc::clone(monitor);
+            self.supervisor.spawn("penalties", move || {
+                let monitor = Arc::clone(&monitor);
+                async move {
+                    monitor.start().await.unwrap_or_else(|e| {
+                        error!("Penalty monitor error: {}", e);
+                    });
+                }
+            })
+        });
+        
         // Start network manager
-        let network_handle = {
+        let network_handle = self.config.enable_p2p.then(|| {
             let manager = Arc::clone(&self.network_manager);
-            tokio::spawn(async move {
-                manager.start().await.unwrap_or_else(|e| {
-                    error!("Network manager error: {}", e);
-                });
+            let degradation = Arc::clone(&self.degradation);
+            self.supervisor.spawn("network", move || {
+                let manager = Arc::clone(&manager);
+                let degradation = Arc::clone(&degradation);
+                async move {
+                    // Detection and on-chain reporting carry on without peers
+                    let reason = match manager.start().await {
+                        Ok(()) => "network manager stopped".to_string(),
+                        Err(e) => {
+                            error!("Network manager error: {}", e);
+                            format!("{:#}", e)
+                        }
+                    };
+                    degradation.degrade(Subsystem::P2p, reason);
+                }
             })
-        };
+        });
         
         // Start energy monitor
         let energy_handle = {
             let monitor = Arc::clone(&self.energy_monitor);
-            tokio::spawn(async move {
-                monitor.start().await.unwrap_or_else(|e| {
-                    error!("Energy monitor error: {}", e);
-                });
+            self.supervisor.spawn("energy", move || {
+                let monitor = Arc::clone(&monitor);
+                async move {
+                    monitor.start().await.unwrap_or_else(|e| {
+                        error!("Energy monitor error: {}", e);
+                    });
+                }
+            })
+        };
+        
+        // Start memory budget enforcement
+        let memory_handle = {
+            let budget = Arc::clone(&self.memory_budget);
+            self.supervisor.spawn("memory", move || {
+                let budget = Arc::clone(&budget);
+                async move {
+                    budget.start().await.unwrap_or_else(|e| {
+                        error!("Memory budget error: {}", e);
+                    });
+                }
+            })
+        };
+        
+        // Switch to RAM mode alerts when storage fails
+        let degradation_handle = {
+            let degradation = Arc::clone(&self.degradation);
+            let storage = Arc::clone(&self.storage);
+            self.supervisor.spawn("degradation", move || {
+                let degradation = Arc::clone(&degradation);
+                let storage = Arc::clone(&storage);
+                async move {
+                    degradation.watch_storage(storage).await;
+                }
             })
         };
         
         // Start metrics collector
         let metrics_handle = {
             let collector = Arc::clone(&self.metrics_collector);
-            tokio::spawn(async move {
-                collector.start().await.unwrap_or_else(|e| {
-                    error!("Metrics collector error: {}", e);
-                });
+            self.supervisor.spawn("metrics", move || {
+                let collector = Arc::clone(&collector);
+                async move {
+                    collector.start().await.unwrap_or_else(|e| {
+                        error!("Metrics collector error: {}", e);
+                    });
+                }
+            })
+        };
+        
+        // Hot-reload operator detection rules
+        let rules_handle = self.threat_detector.as_ref().map(|detector| {
+            let engine = detector.rule_engine();
+            let interval = self.config.ai.rule_reload_interval_secs;
+            self.supervisor.spawn("rules", move || {
+                let engine = Arc::clone(&engine);
+                async move {
+                    engine.watch(interval).await.unwrap_or_else(|e| {
+                        error!("Rule watcher error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Hot-reload the ONNX model when it is replaced on disk
+        let model_handle = self.threat_detector.as_ref().map(|detector| {
+            let detector = Arc::clone(detector);
+            let interval = self.config.ai.model_reload_interval_secs;
+            self.supervisor.spawn("model", move || {
+                let detector = Arc::clone(&detector);
+                async move {
+                    detector.watch_model(interval).await.unwrap_or_else(|e| {
+                        error!("Model watcher error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Keep the phishing domain blocklists current
+        let domains_handle = self.threat_detector.as_ref().map(|detector| {
+            let detector = Arc::clone(detector);
+            self.supervisor.spawn("domains", move || {
+                let detector = Arc::clone(&detector);
+                async move {
+                    detector.watch_domain_feeds().await.unwrap_or_else(|e| {
+                        error!("Phishing feed watcher error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Start the tenant screening API
+        let screening_handle = match (&self.threat_detector, self.config.screening.enabled) {
+            (Some(detector), true) => {
+                let server = Arc::new(ScreeningServer::new(
+                    &self.config,
+                    Some(Arc::clone(detector)),
+                    self.alert_cache.clone(),
+                    Arc::clone(&self.report_history),
+                    Arc::clone(&self.storage),
+                )?);
+                Some(self.supervisor.spawn("screening", move || {
+                    let server = Arc::clone(&server);
+                    async move {
+                        server.start().await.unwrap_or_else(|e| {
+                            error!("Screening API error: {}", e);
+                        });
+                    }
+                }))
+            }
+            (None, true) => {
+                warn!("⚠️ Screening API enabled but AI detection is disabled, not starting it");
+                None
+            }
+            _ => None,
+        };
+        
+        // Expire data past its retention
+        let retention_handle = self.config.retention.enabled.then(|| {
+            let janitor = Arc::clone(&self.retention);
+            self.supervisor.spawn("retention", move || {
+                let janitor = Arc::clone(&janitor);
+                async move {
+                    janitor.start().await.unwrap_or_else(|e| {
+                        error!("Retention janitor error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Start the wallet light-client service
+        let light_client_handle = self.light_client.as_ref().map(|server| {
+            let server = Arc::clone(server);
+            self.supervisor.spawn("light_client", move || {
+                let server = Arc::clone(&server);
+                async move {
+                    server.start().await.unwrap_or_else(|e| {
+                        error!("Wallet light-client service error: {}", e);
+                    });
+                }
+            })
+        });
+        
+        // Start fleet agent when the node is centrally managed
+        let fleet_handle = if self.config.fleet.enabled {
+            let agent = Arc::new(FleetAgent::new(
+                &self.config,
+                &self.node_id,
+                Arc::clone(&self.stats),
+                Arc::clone(&self.energy_monitor),
+                self.threat_detector.clone(),
+                Arc::clone(&self.storage),
+                self.ipfs.clone(),
+                self.artifact_guard.clone(),
+                Arc::clone(&self.audit_log),
+            )?);
+            Some(self.supervisor.spawn("fleet", move || {
+                let agent = Arc::clone(&agent);
+                async move {
+                    agent.start().await.unwrap_or_else(|e| {
+                        error!("Fleet agent error: {}", e);
+                    });
+                }
+            }))
+        } else {
+            None
+        };
+        
+        // Drain and checkpoint on operator request
+        let drain_handle = {
+            let node = self.clone();
+            self.supervisor.spawn("drain", move || {
+                let node = node.clone();
+                async move {
+                    loop {
+                        node.maintenance.drain_requested().await;
+                        match node.drain_and_checkpoint().await {
+                            Ok(checkpoint_at) => node.maintenance.drained(checkpoint_at).await,
+                            Err(e) => {
+                                error!("Drain failed: {}", e);
+                                node.maintenance.drain_failed().await;
+                            }
+                        }
+                    }
+                }
             })
         };
         
         // Main event loop
         let main_handle = {
             let node = self.clone();
-            tokio::spawn(async move {
-                node.run_main_loop().await.unwrap_or_else(|e| {
-                    error!("Main loop error: {}", e);
-                });
+            self.supervisor.spawn("main_loop", move || {
+                let node = node.clone();
+                async move {
+                    node.run_main_loop().await.unwrap_or_else(|e| {
+                        error!("Main loop error: {}", e);
+                    });
+                }
             })
         };
         
+        // Let fault-injection tests kill individual subsystems
+        chaos::register_task("dag", &dag_handle);
+        if let Some(handle) = &listener_handle {
+            chaos::register_task("listener", handle);
+        }
+        if let Some(handle) = &network_handle {
+            chaos::register_task("network", handle);
+        }
+        chaos::register_task("energy", &energy_handle);
+        chaos::register_task("memory", &memory_handle);
+        chaos::register_task("main_loop", &main_handle);
+        if let Some(handle) = &alert_cache_handle {
+            chaos::register_task("alert_cache", handle);
+        }
+        if let Some(handle) = &gas_oracle_handle {
+            chaos::register_task("gas_oracle", handle);
+        }
+        chaos::register_task("rpc_health", &rpc_health_handle);
+        chaos::register_task("stake", &stake_handle);
+        if let Some(handle) = &penalty_handle {
+            chaos::register_task("penalties", handle);
+        }
+        if let Some(handle) = &mempool_handle {
+            chaos::register_task("mempool", handle);
+        }
+        if let Some(handle) = &ingest_handle {
+            chaos::register_task("block_ingest", handle);
+        }
+        if let Some(handle) = &finality_handle {
+            chaos::register_task("dag_epochs", handle);
+        }
+        if let Some(handle) = &sync_handle {
+            chaos::register_task("dag_sync", handle);
+        }
+        
         // Wait for shutdown signal
-        shutdown_rx.recv().await;
+        self.shutdown.notified().await;
         
         info!("🛑 Shutting down node components...");
+        if let Some(generator) = &self.loadgen {
+            generator.stop();
+        }
+        if let Some(handle) = checkpoint_handle {
+            handle.abort();
+        }
+        if let Some(handle) = orphan_handle {
+            handle.abort();
+        }
         
         // Stop all components
         dag_handle.abort();
-        network_handle.abort();
+        for handle in detection_handles {
+            handle.abort();
+        }
+        if let Some(handle) = listener_handle {
+            handle.abort();
+        }
+        if let Some(handle) = network_handle {
+            handle.abort();
+        }
         energy_handle.abort();
         metrics_handle.abort();
+        memory_handle.abort();
+        degradation_handle.abort();
         main_handle.abort();
+        drain_handle.abort();
+        if let Some(handle) = fleet_handle {
+            handle.abort();
+        }
+        if let Some(handle) = rules_handle {
+            handle.abort();
+        }
+        if let Some(handle) = model_handle {
+            handle.abort();
+        }
+        if let Some(handle) = domains_handle {
+            handle.abort();
+        }
+        if let Some(handle) = stats_report_handle {
+            handle.abort();
+        }
+        stake_handle.abort();
+        if let Some(handle) = penalty_handle {
+            handle.abort();
+        }
+        if let Some(handle) = address_graph_handle {
+            handle.abort();
+        }
+        if let Some(graph) = &self.address_graph {
+            if let Err(e) = graph.flush() {
+                warn!("Failed to flush address graph on shutdown: {}", e);
+            }
+        }
+        if let Some(handle) = screening_handle {
+            handle.abort();
+        }
+        if let Some(handle) = alert_cache_handle {
+            handle.abort();
+        }
+        if let Some(handle) = feedback_handle {
+            handle.abort();
+        }
+        if let Some(handle) = gas_oracle_handle {
+            handle.abort();
+        }
+        rpc_health_handle.abort();
+        if let Some(handle) = rollback_handle {
+            handle.abort();
+        }
+        if let Some(handle) = updater_handle {
+            handle.abort();
+        }
+        if let Some(handle) = pattern_feed_handle {
+            handle.abort();
+        }
+        if let Some(handle) = backtest_handle {
+            handle.abort();
+        }
+        if let Some(handle) = commitment_handle {
+            handle.abort();
+        }
+        if let Some(handle) = mempool_handle {
+            handle.abort();
+        }
+        if let Some(handle) = ingest_handle {
+            handle.abort();
+        }
+        if let Some(handle) = finality_handle {
+            handle.abort();
+        }
+        if let Some(handle) = sync_handle {
+            handle.abort();
+        }
+        if let Some(handle) = mirror_handle {
+            handle.abort();
+        }
+        if let Some(handle) = receipts_handle {
+            handle.abort();
+        }
+        if let Some(handle) = gossip_handle {
+            handle.abort();
+        }
+        if let Some(handle) = chain_watch_handle {
+            handle.abort();
+        }
+        if let Some(handle) = light_client_handle {
+            handle.abort();
+        }
+        if let Some(handle) = retention_handle {
+            handle.abort();
+        }
         
         Ok(())
     }
     
-    pub async fn stop(&self) -> Result<()> {
-        if let Some(tx) = &self.shutdown_tx {
-            let _ = tx.send(()).await;
+    /// Stop taking on threat processing and challenges until resumed
+    pub fn pause(&self) {
+        self.maintenance.pause(Stage::Processing);
+    }
+    
+    pub fn resume(&self) {
+        self.maintenance.resume(Stage::Processing);
+    }
+    
+    pub fn is_paused(&self) -> bool {
+        self.maintenance.is_paused(Stage::Processing)
+    }
+    
+    /// Resolves once a self-update has been installed and the node should restart into it
+    pub async fn restart_requested(&self) {
+        match &self.updater {
+            Some(updater) => updater.restart_signal().notified().await,
+            None => std::future::pending().await,
         }
+    }
+    
+    pub async fn stop(&self) -> Result<()> {
+        // Stores a permit, so a stop issued before start() reaches its wait is not lost
+        self.shutdown.notify_one();
         Ok(())
     }
     
@@ -191,86 +1565,467 @@ impl DAGShieldNode {
         
         let tx_hash = self.blockchain_client.register_node(
             &self.node_id,
-            self.config.node.stake_amount,
+            self.config.node.stake_amount_gwei,
         ).await?;
         
+        self.registered.store(true, Ordering::Relaxed);
         info!("✅ Node registered on blockchain: {}", tx_hash);
         Ok(())
     }
     
+    /// Sensors report and oracles vote on chain; a pure relay never touches it
+    fn acts_on_chain(&self) -> bool {
+        self.threat_detector.is_some() || self.config.enable_oracle
+    }
+    
+    /// Try the chain again while reports queue locally: register if startup couldn't, else probe the RPC
+    async fn probe_chain(&self) {
+        // Reachable is not enough while the chain watch holds writes
+        let probe = if let Some(reason) = self.blockchain_client.writes_held() {
+            Err(anyhow::anyhow!(reason))
+        } else if self.registered.load(Ordering::Relaxed) {
+            self.blockchain_client.get_wallet_balance().await.map(|_| ())
+        } else {
+            self.register_on_blockchain().await
+        };
+        match probe {
+            Ok(()) => self.degradation.recover(Subsystem::ChainRpc),
+            Err(e) => self.degradation.degrade(Subsystem::ChainRpc, format!("{:#}", e)),
+        }
+    }
+    
     async fn run_main_loop(&self) -> Result<()> {
-        let mut heartbeat_interval = tokio::time::interval(
-            std::time::Duration::from_secs(self.config.node.heartbeat_interval_secs)
-        );
+        let mut pacer = HeartbeatPacer::new(&self.config.node)?;
+        let mut last_beat = tokio::time::Instant::now();
         
         loop {
-            heartbeat_interval.tick().await;
+            tokio::time::sleep_until(last_beat + pacer.interval()).await;
+            let elapsed = last_beat.elapsed();
+            last_beat = tokio::time::Instant::now();
+            
+            if self.is_paused() {
+                debug!("⏸️ Heartbeat - Node {} is paused", self.node_id);
+                continue;
+            }
             
-            // Process pending threats
+            if self.degradation.is_degraded(Subsystem::ChainRpc) {
+                self.probe_chain().await;
+            }
+            let chain_up = !self.degradation.is_degraded(Subsystem::ChainRpc);
+            
+            // Retry held-back threat reports
             if let Some(detector) = &self.threat_detector {
-                self.process_threats(detector).await?;
+                if let Err(e) = self.mark_stale_reports(detector) {
+                    self.step_failed("Marking stale reports", &e);
+                }
+                if chain_up && !self.maintenance.is_paused(Stage::Reporting) {
+                    if let Err(e) = self.submit_deferred_reports(detector).await {
+                        self.step_failed("Submitting deferred reports", &e);
+                    }
+                }
             }
             
             // Check for challenges
-            self.check_challenges().await?;
+            if chain_up && self.config.enable_oracle {
+                if let Err(e) = self.check_challenges().await {
+                    self.step_failed("Checking challenges", &e);
+                }
+            }
             
             // Update stats
-            self.update_stats().await?;
+            let (transactions, threats) = self.detection_counts.take();
+            let interval = pacer.observe(elapsed, transactions, threats);
+            if let Err(e) = self.update_stats(elapsed, interval).await {
+                self.step_failed("Updating stats", &e);
+            }
             
             // Energy efficiency check
-            self.optimize_energy_usage().await?;
+            if let Err(e) = self.optimize_energy_usage().await {
+                self.step_failed("Energy check", &e);
+            }
             
             debug!("💓 Heartbeat - Node {} is healthy", self.node_id);
         }
     }
     
-    async fn process_threats(&self, detector: &Arc<ThreatDetector>) -> Result<()> {
-        // Get pending transactions from DAG processor
-        let transactions = self.dag_processor.get_pending_transactions().await?;
+    /// Degrade the subsystem a heartbeat step failed in; the node runs on either way, and a failure
+    /// of neither the chain nor storage is only logged
+    fn step_failed(&self, step: &str, error: &anyhow::Error) {
+        if blockchain::is_rpc_failure(error) {
+            self.degradation.degrade(Subsystem::ChainRpc, format!("{:#}", error));
+        } else if storage::is_storage_failure(error) {
+            self.degradation.degrade(Subsystem::Storage, format!("{}: {:#}", step, error));
+        } else {
+            error!("❌ {} failed: {:#}", step, error);
+        }
+    }
+    
+    /// Handle verdicts as the detection workers produce them; runs until the task is aborted
+    async fn handle_detections(&self, pipeline: &DetectionPipeline, detector: &Arc<ThreatDetector>) {
+        while let Some(Detection { transaction, result }) = pipeline.next_detection().await {
+            // Verdicts back up, and with them detection and the DAG, while processing is paused
+            while self.is_paused() {
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            }
+            
+            // Synthetic load ends at the verdict: never recorded, reported or shared
+            if loadgen::is_synthetic(&transaction) {
+                if let Some(generator) = &self.loadgen {
+                    let flagged = result
+                        .map(|result| detector.is_flagged(&transaction, &result))
+                        .map_err(|e| format!("{:#}", e));
+                    generator.record_outcome(&transaction, flagged);
+                }
+                continue;
+            }
+            
+            // Recorded after detection, so graph features describe the history before each transaction
+            if let Some(graph) = &self.address_graph {
+                graph.record(&transaction);
+            }
+            
+            let flagged = match result {
+                Ok(result) => self.handle_verdict(detector, &transaction, &result).await.unwrap_or_else(|e| {
+                    error!("Failed to handle the verdict on {}: {}", transaction.id, e);
+                    false
+                }),
+                Err(e) => {
+                    warn!("❌ Detection failed for {}: {}", transaction.id, e);
+                    continue;
+                }
+            };
+            self.detection_counts.scored.fetch_add(1, Ordering::Relaxed);
+            if flagged {
+                self.detection_counts.flagged.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+    
+    /// Alert and report on one verdict; returns whether it was flagged
+    async fn handle_verdict(&self, detector: &Arc<ThreatDetector>, tx: &Transaction, result: &ThreatDetectionResult) -> Result<bool> {
+        // Watched addresses are held to a stricter threshold
+        let watched = self.watchlists
+            .as_ref()
+            .and_then(|watchlists| watchlists.matches(tx).map(|entry| (watchlists, entry)));
+        let threshold = watched
+            .as_ref()
+            .map_or(self.config.ai.confidence_threshold_for(tx.chain_id), |(watchlists, entry)| watchlists.threshold_for(entry));
+        let flagged = result.confidence > threshold;
+        if let Some(mirror) = &self.mirror {
+            mirror.offer(tx, result);
+        }
+        if let Some(reporter) = &self.stats_reporter {
+            reporter.record_verdict(tx.chain_id, flagged);
+        }
+        if let Some(guard) = &self.reporting_guard {
+            guard.record(flagged);
+        }
+        if let Some(checker) = &self.cross_checker {
+            checker.record(tx, result, flagged);
+        }
         
-        if transactions.is_empty() {
-            return Ok();
+        if let Some((watchlists, entry)) = watched {
+            if watchlists.should_alert(flagged) {
+                watchlists.alert(WatchlistAlert {
+                    address: entry.address,
+                    label: entry.label,
+                    category: entry.category,
+                    transaction_id: tx.id.clone(),
+                    chain_id: tx.chain_id,
+                    from: tx.from.clone(),
+                    to: tx.to.clone(),
+                    flagged,
+                    verdict: result.clone(),
+                });
+            }
         }
         
-        debug!("🔍 Processing {} transactions for threats", transactions.len());
+        if flagged {
+            info!("🚨 Threat detected: {} (confidence: {:.2})", 
+                  result.threat_type, result.confidence);
+            
+            // Signed before reporting, so the receipt is in the chain pinned with the evidence
+            if let Some(book) = self.receipts.as_ref().filter(|_| !tx.peer_synced) {
+                match book.issue(tx, &result.threat_type).await {
+                    Ok(Some(receipt)) if self.config.enable_p2p && !self.degradation.is_degraded(Subsystem::P2p) => {
+                        self.network_manager.publish_receipt(receipt);
+                    }
+                    Ok(_) => {}
+                    Err(e) => warn!("⚠️ Failed to sign a receipt for {}: {:#}", tx.id, e),
+                }
+            }
+            
+            // Detection carries on; a held-back report goes out once reporting resumes or the chain answers
+            let held_for_review = self.reporting_guard.as_ref().filter(|guard| guard.is_suspended());
+            let deferred = if tx.peer_synced {
+                debug!("🔃 {} came from a peer's DAG, not reporting it on-chain", tx.id);
+                false
+            } else if let Some(guard) = held_for_review {
+                debug!("🛑 Auto-reporting suspended, holding {} for review", tx.id);
+                guard.hold(tx, result)?;
+                false
+            } else if self.maintenance.is_paused(Stage::Reporting) {
+                debug!("⏸️ Reporting paused, deferred report for {}", tx.id);
+                true
+            } else if self.degradation.is_degraded(Subsystem::ChainRpc) {
+                debug!("📥 Chain unreachable, queued report for {}", tx.id);
+                true
+            } else if let Err(e) = self.report_threat(detector, tx, result).await {
+                if blockchain::is_rpc_failure(&e) {
+                    self.degradation.degrade(Subsystem::ChainRpc, format!("{:#}", e));
+                } else {
+                    warn!("⚠️ Failed to report {}, queued it for retry: {:#}", tx.id, e);
+                }
+                true
+            } else {
+                false
+            };
+            if deferred && !self.report_queue.push(tx, result)? {
+                debug!("🔁 {} repeats a queued report of {} on {}", tx.id, result.threat_type, tx.target_address);
+            }
+            
+            if let Some(server) = &self.light_client {
+                server.warn(tx, result);
+            }
+            if let Some(committer) = &self.epoch_committer {
+                let leaf = DetectionLeaf {
+                    transaction_id: tx.id.clone(),
+                    chain_id: tx.chain_id,
+                    threat_type: result.threat_type.to_string(),
+                    confidence_bps: (result.confidence * 10_000.0) as u32,
+                    threshold_bps: (threshold * 10_000.0) as u32,
+                    detected_at: chrono::Utc::now().timestamp() as u64,
+                };
+                if let Err(e) = committer.record(leaf, detector.model_hash(tx.chain_id).as_deref()) {
+                    warn!("⚠️ Failed to add {} to the epoch commitment: {:#}", tx.id, e);
+                }
+            }
+            
+            // Update stats
+            self.audit_log.record(AuditEvent::ThreatDetected {
+                transaction_id: tx.id.clone(),
+                threat_type: result.threat_type.clone(),
+                confidence: result.confidence,
+            });
+            let mut stats = self.stats.write().await;
+            stats.threats_detected += 1;
+        }
         
-        // Batch process transactions through AI
-        let results = detector.detect_threats_batch(&transactions).await?;
+        Ok(flagged)
+    }
+    
+    /// Flag stored reports from an older detection pipeline once the pipeline changes
+    fn mark_stale_reports(&self, detector: &ThreatDetector) -> Result<()> {
+        let current = detector.pipeline_fingerprint();
+        let swept: Option<String> = self.storage.get(PIPELINE_NAMESPACE, SWEPT_FINGERPRINT_KEY)?;
+        if swept.as_deref() == Some(current.as_str()) {
+            return Ok(());
+        }
         
-        for (tx, result) in transactions.iter().zip(results.iter()) {
-            if result.confidence > self.config.ai.confidence_threshold {
-                info!("🚨 Threat detected: {} (confidence: {:.2})", 
-                      result.threat_type, result.confidence);
-                
-                // Report to blockchain
-                self.blockchain_client.report_threat(
-                    &result.threat_type,
-                    &tx.target_address,
-                    (result.confidence * 100.0) as u32,
-                    tx.chain_id,
-                ).await?;
-                
-                // Update stats
-                let mut stats = self.stats.write().await;
-                stats.threats_detected += 1;
+        let mut batch = self.storage.batch();
+        let mut marked = 0;
+        for (tx_hash, mut pipeline) in self.storage.scan::<ReportPipeline>(REPORT_PIPELINE_NAMESPACE)? {
+            if !pipeline.stale && pipeline.pipeline_fingerprint != current {
+                pipeline.stale = true;
+                batch.put(REPORT_PIPELINE_NAMESPACE, &tx_hash, &pipeline)?;
+                marked += 1;
             }
         }
+        batch.put(PIPELINE_NAMESPACE, SWEPT_FINGERPRINT_KEY, &current)?;
+        self.storage.commit(batch)?;
         
+        if marked > 0 {
+            info!("🧬 Marked {} threat reports from earlier detection pipelines as stale", marked);
+        }
         Ok(())
     }
     
+    /// Report a flagged transaction on-chain, record it and share it with peers
+    async fn report_threat(&self, detector: &Arc<ThreatDetector>, tx: &Transaction, result: &ThreatDetectionResult) -> Result<()> {
+        let _reporting_timer = pipeline_latency().start(PipelineStage::Reporting, &tx.id);
+        let receipts = match &self.receipts {
+            Some(book) => book.chain_for(tx, &result.threat_type).unwrap_or_else(|e| {
+                warn!("⚠️ Failed to read the receipt chain for {}: {:#}", tx.id, e);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        let evidence_cid = self.pin_evidence(tx, result, receipts.clone()).await;
+        let confidence = (result.confidence * 100.0) as u32;
+        let tx_hash = self.blockchain_client.report_threat(
+            &result.threat_type,
+            &tx.target_address,
+            confidence,
+            tx.chain_id,
+        ).await?;
+        if let Some(reporter) = &self.stats_reporter {
+            reporter.record_reported(tx.chain_id);
+        }
+        
+        let record = ThreatReportRecord {
+            transaction_id: tx.id.clone(),
+            target_address: tx.target_address.clone(),
+            chain_id: tx.chain_id,
+            threat_type: result.threat_type.clone(),
+            confidence,
+            tx_hash: tx_hash.clone(),
+            evidence_cid,
+            reported_at: chrono::Utc::now().timestamp() as u64,
+        };
+        let pipeline = ReportPipeline {
+            pipeline_fingerprint: detector.pipeline_fingerprint(),
+            stale: false,
+        };
+        let mut batch = self.storage.batch();
+        batch.put(THREAT_REPORT_NAMESPACE, &tx_hash, &record)?;
+        batch.put(REPORT_PIPELINE_NAMESPACE, &tx_hash, &pipeline)?;
+        self.storage.commit(batch)?;
+        self.report_history.record(record.clone());
+        
+        // The report stands whether or not the claim goes through
+        if let Some(book) = &self.receipts {
+            if let Err(e) = book.claim(&receipts, record.evidence_cid.as_deref()).await {
+                warn!("⚠️ Failed to claim first report of {}: {:#}", record.target_address, e);
+            }
+        }
+        
+        // Chain-only mode: the report stands on its own without peers
+        if !self.config.enable_p2p || self.degradation.is_degraded(Subsystem::P2p) {
+            return Ok(());
+        }
+        self.network_manager.publish_intel(ThreatIntel {
+            target_address: record.target_address.clone(),
+            chain_id: record.chain_id,
+            threat_type: record.threat_type.clone(),
+            confidence: record.confidence,
+            tx_hash: record.tx_hash.clone(),
+            evidence_cid: record.evidence_cid.clone(),
+            reported_at: record.reported_at,
+        });
+        
+        Ok(())
+    }
+    
+    /// Submit the queued reports that are due, held back while reporting was paused or the chain
+    /// was unreachable
+    async fn submit_deferred_reports(&self, detector: &Arc<ThreatDetector>) -> Result<()> {
+        let deferred = self.report_queue.due()?;
+        if deferred.is_empty() {
+            return Ok(());
+        }
+        
+        info!("📤 Submitting {} deferred reports", deferred.len());
+        let total = deferred.len();
+        for (submitted, (key, report)) in deferred.into_iter().enumerate() {
+            if let Err(e) = self.report_threat(detector, &report.transaction, &report.result).await {
+                let target = report.transaction.target_address.clone();
+                match self.report_queue.failed(&key, report, &e)? {
+                    // Given up on; the reports behind it may still go through
+                    Failure::Dead => {
+                        error!("❌ Giving up on the deferred report on {}, moved to the dead-letter list: {:#}", target, e);
+                        continue;
+                    }
+                    // Only a chain that is down holds up the reports behind this one
+                    Failure::Retry(backoff) if blockchain::is_rpc_failure(&e) => {
+                        warn!("⚠️ Deferred report failed, retrying it in {}s; {} reports stay queued", backoff.as_secs(), total - submitted);
+                        self.degradation.degrade(Subsystem::ChainRpc, format!("{:#}", e));
+                        return Ok(());
+                    }
+                    Failure::Retry(backoff) => {
+                        warn!("⚠️ Deferred report on {} failed, retrying it in {}s: {:#}", target, backoff.as_secs(), e);
+                        continue;
+                    }
+                }
+            }
+            self.report_queue.submitted(&key)?;
+        }
+        Ok(())
+    }
+    
+    /// Stop ingestion, let the DAG empty, then flush the graph and storage to disk
+    async fn drain_and_checkpoint(&self) -> Result<u64> {
+        info!("🚰 Draining the pipeline for maintenance");
+        
+        let deadline = tokio::time::Instant::now() + DRAIN_TIMEOUT;
+        while !self.dag_processor.all_transactions_processed().await? {
+            if tokio::time::Instant::now() >= deadline {
+                bail!("DAG still busy after {}s, giving up on the drain", DRAIN_TIMEOUT.as_secs());
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        }
+        while self.detection.as_ref().is_some_and(|pipeline| !pipeline.is_idle()) {
+            if tokio::time::Instant::now() >= deadline {
+                bail!("Detection still busy after {}s, giving up on the drain", DRAIN_TIMEOUT.as_secs());
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        }
+        
+        if let Some(graph) = &self.address_graph {
+            graph.flush()?;
+        }
+        self.storage.flush().await?;
+        
+        let checkpoint_at = chrono::Utc::now().timestamp() as u64;
+        info!("✅ Pipeline drained and checkpointed, ingestion stays paused until resumed");
+        Ok(checkpoint_at)
+    }
+    
+    /// Pin the evidence behind a report; failures only cost the CID, never the report itself
+    async fn pin_evidence(&self, tx: &Transaction, result: &ThreatDetectionResult, receipts: Vec<DetectionReceipt>) -> Option<String> {
+        let ipfs = self.ipfs.as_ref()?;
+        let bundle = EvidenceBundle {
+            version: 1,
+            node_id: self.node_id.clone(),
+            transaction: tx.clone(),
+            verdict: result.clone(),
+            detected_at: chrono::Utc::now().timestamp() as u64,
+            receipts,
+        };
+        
+        match ipfs.pin_evidence(&bundle).await {
+            Ok(cid) => {
+                debug!("📌 Evidence for {} pinned as {}", tx.id, cid);
+                Some(cid)
+            }
+            Err(e) => {
+                warn!("Failed to pin evidence for {}: {}", tx.id, e);
+                None
+            }
+        }
+    }
+    
     async fn check_challenges(&self) -> Result<()> {
         let challenges = self.blockchain_client.get_active_challenges().await?;
+        let now = chrono::Utc::now().timestamp() as u64;
+        
+        // Forget solved challenges once they are past their deadline and off the contract's list
+        for (id, solved) in self.storage.scan::<SolvedChallenge>(SOLVED_CHALLENGE_NAMESPACE)? {
+            if solved.deadline < now {
+                self.storage.delete(SOLVED_CHALLENGE_NAMESPACE, &id)?;
+            }
+        }
         
         for challenge in challenges {
+            if self.storage.get::<SolvedChallenge>(SOLVED_CHALLENGE_NAMESPACE, &challenge.id)?.is_some() {
+                continue;
+            }
             if let Some(solution) = self.solve_challenge(&challenge).await? {
                 info!("🎯 Submitting solution for challenge: {}", challenge.id);
                 
-                self.blockchain_client.submit_challenge_solution(
+                let tx_hash = self.blockchain_client.submit_challenge_solution(
+                    challenge.chain_id,
                     &challenge.id,
                     &solution,
                 ).await?;
+                self.storage.put(SOLVED_CHALLENGE_NAMESPACE, &challenge.id, &SolvedChallenge {
+                    tx_hash,
+                    deadline: challenge.deadline,
+                    solved_at: chrono::Utc::now().timestamp() as u64,
+                })?;
                 
+                self.audit_log.record(AuditEvent::ChallengeCompleted {
+                    challenge_id: challenge.id.clone(),
+                });
                 let mut stats = self.stats.write().await;
                 stats.challenges_completed += 1;
             }
@@ -280,38 +2035,63 @@ impl DAGShieldNode {
     }
     
     async fn solve_challenge(&self, challenge: &Challenge) -> Result<Option<String>> {
-        match challenge.challenge_type.as_str() {
-            "threat_detection_accuracy" => {
+        // Challenge data is untrusted on-chain input; never hand it to a solver unvalidated
+        let spec = match ChallengeSpec::parse(&challenge.challenge_type, &challenge.data) {
+            Ok(spec) => spec,
+            Err(e) => {
+                warn!("Rejecting malformed challenge {} ({}): {}", challenge.id, challenge.challenge_type, e);
+                return Ok(None);
+            }
+        };
+        
+        match spec {
+            ChallengeSpec::ThreatDetectionAccuracy(accuracy) => {
                 // Use AI to solve threat detection challenge
                 if let Some(detector) = &self.threat_detector {
-                    detector.solve_accuracy_challenge(&challenge.data).await
+                    detector.solve_accuracy_challenge(&accuracy).await
                 } else {
                     Ok(None)
                 }
             }
-            "dag_processing_speed" => {
+            ChallengeSpec::DagProcessingSpeed(speed) => {
                 // Use DAG processor to solve speed challenge
-                self.dag_processor.solve_speed_challenge(&challenge.data).await
+                self.dag_processor.solve_speed_challenge(&speed).await
             }
-            "energy_efficiency" => {
+            ChallengeSpec::EnergyEfficiency(efficiency) => {
                 // Use energy monitor to solve efficiency challenge
-                self.energy_monitor.solve_efficiency_challenge(&challenge.data).await
-            }
-            _ => {
-                warn!("Unknown challenge type: {}", challenge.challenge_type);
-                Ok(None)
+                self.energy_monitor.solve_efficiency_challenge(&efficiency).await
             }
         }
     }
     
-    async fn update_stats(&self) -> Result<()> {
+    async fn update_stats(&self, elapsed: std::time::Duration, heartbeat_interval: std::time::Duration) -> Result<()> {
         let energy_stats = self.energy_monitor.get_current_stats().await?;
-        let reputation = self.blockchain_client.get_node_reputation(&self.node_id).await?;
+        // The last known reputation stands while the chain is unreachable
+        let reputation = if !self.acts_on_chain() || self.degradation.is_degraded(Subsystem::ChainRpc) {
+            None
+        } else {
+            match self.blockchain_client.get_node_reputation(&self.node_id).await {
+                Ok(reputation) => Some(reputation),
+                Err(e) => {
+                    self.step_failed("Reading the node's reputation", &e);
+                    None
+                }
+            }
+        };
         
         let mut stats = self.stats.write().await;
+        if stats.energy_efficiency != energy_stats.efficiency_score {
+            self.audit_log.record(AuditEvent::EnergyEfficiencyChanged { score: energy_stats.efficiency_score });
+        }
+        if let Some(reputation) = reputation {
+            if stats.reputation_score != reputation {
+                self.audit_log.record(AuditEvent::ReputationChanged { score: reputation });
+            }
+            stats.reputation_score = reputation;
+        }
         stats.energy_efficiency = energy_stats.efficiency_score;
-        stats.reputation_score = reputation;
-        stats.uptime_seconds += self.config.node.heartbeat_interval_secs;
+        stats.uptime_seconds += elapsed.as_secs_f64().round() as u64;
+        stats.heartbeat_interval_secs = heartbeat_interval.as_secs_f64();
         
         Ok(())
     }
@@ -330,6 +2110,11 @@ impl DAGShieldNode {
         Ok(())
     }
     
+    /// Whether the node is past start-up work and detecting at full speed
+    pub fn is_ready(&self) -> bool {
+        self.threat_detector.as_ref().is_none_or(|detector| detector.is_ready())
+    }
+    
     pub fn get_node_id(&self) -> &str {
         &self.node_id
     }
@@ -342,27 +2127,61 @@ impl DAGShieldNode {
         self.energy_monitor.get_current_stats().await
     }
     
+    /// Shared governor, for subsystems that need to ask before running heavy work
+    pub fn governor(&self) -> Arc<ResourceGovernor> {
+        Arc::clone(&self.governor)
+    }
+    
     // Benchmark methods
-    pub async fn benchmark_dag_processing(&self, tx_count: usize) -> Result<BenchmarkResults> {
-        self.dag_processor.benchmark(tx_count).await
+    /// Run `tx_count` transactions through the DAG once per shape, one shape after another
+    pub async fn benchmark_dag_processing(&self, tx_count: usize, shapes: &[DagShape]) -> Result<Vec<ShapeBenchmark>> {
+        let _token = self.benchmark_token()?;
+        let mut results = Vec::with_capacity(shapes.len());
+        for shape in shapes {
+            results.push(self.dag_processor.benchmark(tx_count, *shape).await?);
+        }
+        Ok(results)
     }
     
     pub async fn benchmark_ai_detection(&self, sample_count: usize) -> Result<BenchmarkResults> {
         if let Some(detector) = &self.threat_detector {
+            let _token = self.benchmark_token()?;
             detector.benchmark(sample_count).await
         } else {
             Err(anyhow::anyhow!("AI detection not enabled"))
         }
     }
+    
+    pub async fn benchmark_robustness(&self, sample_count: usize) -> Result<RobustnessReport> {
+        if let Some(detector) = &self.threat_detector {
+            let _token = self.benchmark_token()?;
+            detector.benchmark_robustness(sample_count).await
+        } else {
+            Err(anyhow::anyhow!("AI detection not enabled"))
+        }
+    }
+    
+    fn benchmark_token(&self) -> Result<WorkToken> {
+        match self.governor.try_acquire_work(WorkClass::Benchmark) {
+            WorkDecision::Granted(token) => Ok(token),
+            WorkDecision::Deferred { reason, retry_after } => Err(anyhow::anyhow!(
+                "Benchmark deferred ({}), retry in {}s", reason, retry_after.as_secs()
+            )),
+        }
+    }
 }
 
 // Helper structs
 #[derive(Debug, Clone)]
 pub struct Challenge {
     pub id: String,
+    /// The chain whose deployment posted it
+    pub chain_id: u64,
     pub challenge_type: String,
     pub data: String,
+    /// In wei
     pub reward: u64,
+    /// Unix seconds
     pub deadline: u64,
 }
 
@@ -381,13 +2200,50 @@ impl Clone for DAGShieldNode {
             config: self.config.clone(),
             dag_processor: Arc::clone(&self.dag_processor),
             threat_detector: self.threat_detector.as_ref().map(Arc::clone),
+            detection: self.detection.as_ref().map(Arc::clone),
+            detection_counts: Arc::clone(&self.detection_counts),
             blockchain_client: Arc::clone(&self.blockchain_client),
             network_manager: Arc::clone(&self.network_manager),
             energy_monitor: Arc::clone(&self.energy_monitor),
             metrics_collector: Arc::clone(&self.metrics_collector),
             storage: Arc::clone(&self.storage),
+            governor: Arc::clone(&self.governor),
+            memory_budget: Arc::clone(&self.memory_budget),
+            alert_cache: self.alert_cache.as_ref().map(Arc::clone),
+            ipfs: self.ipfs.as_ref().map(Arc::clone),
+            gas_oracle: self.gas_oracle.as_ref().map(Arc::clone),
+            artifact_guard: self.artifact_guard.as_ref().map(Arc::clone),
+            updater: self.updater.as_ref().map(Arc::clone),
+            stats_reporter: self.stats_reporter.as_ref().map(Arc::clone),
+            stake_manager: Arc::clone(&self.stake_manager),
+            penalty_monitor: self.penalty_monitor.as_ref().map(Arc::clone),
+            reporting_guard: self.reporting_guard.as_ref().map(Arc::clone),
+            report_queue: Arc::clone(&self.report_queue),
+            watchlists: self.watchlists.as_ref().map(Arc::clone),
+            address_graph: self.address_graph.as_ref().map(Arc::clone),
+            cross_checker: self.cross_checker.as_ref().map(Arc::clone),
+            pattern_feed: self.pattern_feed.as_ref().map(Arc::clone),
+            backtester: self.backtester.as_ref().map(Arc::clone),
+            epoch_committer: self.epoch_committer.as_ref().map(Arc::clone),
+            mempool: self.mempool.as_ref().map(Arc::clone),
+            block_ingester: self.block_ingester.as_ref().map(Arc::clone),
+            epoch_finalizer: self.epoch_finalizer.as_ref().map(Arc::clone),
+            dag_sync: self.dag_sync.as_ref().map(Arc::clone),
+            mirror: self.mirror.as_ref().map(Arc::clone),
+            receipts: self.receipts.as_ref().map(Arc::clone),
+            gossip_auth: self.gossip_auth.as_ref().map(Arc::clone),
+            loadgen: self.loadgen.as_ref().map(Arc::clone),
+            light_client: self.light_client.as_ref().map(Arc::clone),
+            chain_watch: self.chain_watch.as_ref().map(Arc::clone),
+            retention: Arc::clone(&self.retention),
+            report_history: Arc::clone(&self.report_history),
+            audit_log: Arc::clone(&self.audit_log),
             stats: Arc::clone(&self.stats),
-            shutdown_tx: None, // Don't clone shutdown channel
+            shutdown: Arc::clone(&self.shutdown),
+            maintenance: Arc::clone(&self.maintenance),
+            degradation: Arc::clone(&self.degradation),
+            supervisor: Arc::clone(&self.supervisor),
+            registered: Arc::clone(&self.registered),
         }
     }
 }
@@ -1,4 +1,8 @@
 use crate::config::Config;
+use crate::cursor::EventCursor;
+use crate::ipfs::{cid_from_digest, IpfsClient};
+use crate::storage::NodeStorage;
+use crate::threat::ThreatClass;
 use ethers::{
     contract::{Contract, ContractFactory},
     core::types::*,
@@ -18,12 +22,21 @@ pub struct ThreatReport {
     pub chain_id: u64,
     pub contract_address: Address,
     pub threat_level: u8,
-    pub threat_type: u8,
+    /// Packed as its numeric code, in contract calls and cross-chain payloads alike
+    #[serde(with = "crate::threat::as_code")]
+    pub threat_type: ThreatClass,
     pub evidence_hash: H256,
     pub confidence: u8,
     pub timestamp: u64,
 }
 
+impl ThreatReport {
+    /// IPFS CID of the evidence bundle committed to by `evidence_hash`
+    pub fn evidence_cid(&self) -> String {
+        cid_from_digest(self.evidence_hash.0)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ChainConnection {
     pub chain_id: u64,
@@ -36,13 +49,20 @@ pub struct OracleManager {
     config: Config,
     wallet: LocalWallet,
     chains: HashMap<u64, ChainConnection>,
+    cursors: HashMap<u64, EventCursor>,
     pending_reports: Vec<ThreatReport>,
+    ipfs: Option<Arc<IpfsClient>>,
 }
 
 impl OracleManager {
-    pub async fn new(config: Config) -> Result<Self, Box<dyn std::error::Error>> {
+    pub async fn new(
+        config: Config,
+        storage: Arc<NodeStorage>,
+        ipfs: Option<Arc<IpfsClient>>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         let wallet = config.private_key.parse::<LocalWallet>()?;
         let mut chains = HashMap::new();
+        let mut cursors = HashMap::new();
 
         // Initialize chain connections
         for chain_config in &config.supported_chains {
@@ -54,13 +74,25 @@ impl OracleManager {
                 relay_contract: chain_config.relay_contract,
             };
             chains.insert(chain_config.chain_id, connection);
+
+            // Resume vote processing where we left off instead of rescanning a fixed window
+            let latest = connection_head(&chains[&chain_config.chain_id]).await?;
+            let cursor = EventCursor::load(
+                Arc::clone(&storage),
+                "oracle_votes",
+                chain_config.chain_id,
+                latest.saturating_sub(100),
+            )?;
+            cursors.insert(chain_config.chain_id, cursor);
         }
 
         Ok(Self {
             config,
             wallet,
             chains,
+            cursors,
             pending_reports: Vec::new(),
+            ipfs,
         })
     }
 
@@ -116,7 +148,7 @@ impl OracleManager {
                     report.chain_id,
                     report.contract_address,
                     report.threat_level,
-                    report.threat_type,
+                    report.threat_type.code(),
                     report.evidence_hash,
                     report.confidence,
                     signature.to_vec(),
@@ -144,10 +176,11 @@ impl OracleManager {
         Ok(())
     }
 
-    async fn participate_in_consensus(&self) -> Result<(), Box<dyn std::error::Error>> {
+    async fn participate_in_consensus(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         // Listen for new threat reports and participate in consensus voting
-        for (chain_id, chain) in &self.chains {
-            if let Err(e) = self.check_pending_votes(*chain_id).await {
+        let chain_ids: Vec<u64> = self.chains.keys().copied().collect();
+        for chain_id in chain_ids {
+            if let Err(e) = self.check_pending_votes(chain_id).await {
                 warn!("Error checking pending votes for chain {}: {}", chain_id, e);
             }
         }
@@ -155,8 +188,9 @@ impl OracleManager {
         Ok(())
     }
 
-    async fn check_pending_votes(&self, chain_id: u64) -> Result<(), Box<dyn std::error::Error>> {
+    async fn check_pending_votes(&mut self, chain_id: u64) -> Result<(), Box<dyn std::error::Error>> {
         let chain = self.chains.get(&chain_id).unwrap();
+        let head = connection_head(chain).await?;
         
         let client = SignerMiddleware::new(
             chain.provider.clone(),
@@ -169,15 +203,23 @@ impl OracleManager {
             Arc::new(client),
         );
 
-        // Get recent ThreatReported events
+        // Get ThreatReported events since the last committed cursor position
+        let from_block = self.cursors.get(&chain_id).ok_or("Missing cursor for chain")?.next_block();
         let filter = oracle_contract
             .event::<(H256, u64, Address, u8)>("ThreatReported")?
-            .from_block(BlockNumber::Latest - 100);
+            .from_block(from_block)
+            .to_block(head);
 
-        let events = filter.query().await?;
+        let events = filter.query_with_meta().await?;
 
-        for event in events {
+        for (event, meta) in events {
             let report_id = event.0;
+            let block_number = meta.block_number.as_u64();
+            let log_index = meta.log_index.as_u64();
+
+            if self.cursors[&chain_id].is_processed(block_number, log_index) {
+                continue;
+            }
             
             // Check if we've already voted
             let has_voted: bool = oracle_contract
@@ -196,8 +238,13 @@ impl OracleManager {
                     info!("Voted on threat report {}: {}", report_id, should_agree);
                 }
             }
+
+            let cursor = self.cursors.get_mut(&chain_id).unwrap();
+            cursor.commit(block_number, log_index, cursor.batch())?;
         }
 
+        self.cursors.get_mut(&chain_id).unwrap().advance_to_block(head)?;
+
         Ok(())
     }
 
@@ -226,6 +273,31 @@ impl OracleManager {
         let confidence = report.6;
         let threat_level = report.2;
 
+        // Re-check the reporter's evidence bundle when we can reach IPFS
+        if let Some(ipfs) = &self.ipfs {
+            let cid = cid_from_digest(report.5.0);
+            let bundle = match ipfs.fetch_evidence(&cid).await {
+                Ok(bundle) => bundle,
+                Err(e) => {
+                    warn!("Evidence {} for report {} unavailable: {}", cid, report_id, e);
+                    return Ok(false);
+                }
+            };
+
+            let target_matches = bundle.transaction.target_address.parse::<Address>().ok() == Some(report.1)
+                && bundle.transaction.chain_id == report.0;
+            let confidence_matches = (bundle.verdict.confidence * 100.0).round() as u8 == confidence;
+            if !target_matches || !confidence_matches {
+                warn!("Evidence {} does not match report {}", cid, report_id);
+                return Ok(false);
+            }
+
+            // Keep the evidence available for other verifiers
+            if let Err(e) = ipfs.pin(&cid).await {
+                warn!("Failed to pin evidence {}: {}", cid, e);
+            }
+        }
+
         // Simple voting logic - agree if confidence > 80% and threat level > 5
         Ok(confidence > 80 && threat_level > 5)
     }
@@ -235,7 +307,7 @@ impl OracleManager {
             ethers::abi::Token::Uint(report.chain_id.into()),
             ethers::abi::Token::Address(report.contract_address),
             ethers::abi::Token::Uint(report.threat_level.into()),
-            ethers::abi::Token::Uint(report.threat_type.into()),
+            ethers::abi::Token::Uint(report.threat_type.code().into()),
             ethers::abi::Token::FixedBytes(report.evidence_hash.as_bytes().to_vec()),
         ]);
 
@@ -314,6 +386,10 @@ impl OracleManager {
     }
 }
 
+async fn connection_head(chain: &ChainConnection) -> Result<u64, Box<dyn std::error::Error>> {
+    Ok(chain.provider.get_block_number().await?.as_u64())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChainConfig {
     pub chain_id: u64,
@@ -0,0 +1,160 @@
+//! Signed threat pattern bundles from a remote feed, merged into the detector's pattern set
+
+use anyhow::{anyhow, bail, Context, Result};
+use ethers::types::Address;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::ai::{ThreatDetector, ThreatPattern};
+use crate::config::PatternFeedConfig;
+use crate::ipfs::IpfsClient;
+use crate::rollback::{ArtifactGuard, ArtifactKind};
+use crate::signature::{parse_signers, verify_signed_payload};
+use crate::storage::NodeStorage;
+
+const PATTERN_FEED_NAMESPACE: &str = "pattern_feed";
+const BUNDLE_ISSUED_KEY: &str = "bundle_issued_at";
+
+/// The bundle as served: `payload` is the JSON-encoded [`PatternBundle`], signed as-is
+#[derive(Debug, Deserialize)]
+struct SignedPatternBundle {
+    payload: String,
+    signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternBundle {
+    /// Unix time the bundle was issued; bundles older than the last applied one are refused
+    pub issued_at: u64,
+    pub patterns: Vec<ThreatPattern>,
+}
+
+/// Polls the configured feed and merges newly issued bundles into the detector.
+///
+/// Bundles go through the artifact guard when one is attached, so a pattern update that
+/// costs accuracy is rolled back and its payload quarantined like a fleet-pushed one.
+pub struct PatternFeed {
+    config: PatternFeedConfig,
+    interval: Duration,
+    client: reqwest::Client,
+    trusted_signers: Vec<Address>,
+    detector: Arc<ThreatDetector>,
+    storage: Arc<NodeStorage>,
+    ipfs: Option<Arc<IpfsClient>>,
+    artifact_guard: Option<Arc<ArtifactGuard>>,
+}
+
+impl PatternFeed {
+    pub fn new(
+        config: &PatternFeedConfig,
+        update_interval_hours: u64,
+        detector: Arc<ThreatDetector>,
+        storage: Arc<NodeStorage>,
+        ipfs: Option<Arc<IpfsClient>>,
+        artifact_guard: Option<Arc<ArtifactGuard>>,
+    ) -> Result<Self> {
+        let trusted_signers = parse_signers(&config.trusted_signers)?;
+        if trusted_signers.is_empty() {
+            bail!("The pattern feed requires at least one trusted bundle signer");
+        }
+        if config.url.starts_with("ipfs://") && ipfs.is_none() {
+            bail!("Pattern feed {} is on IPFS but [ipfs] is disabled", config.url);
+        }
+        
+        Ok(Self {
+            config: config.clone(),
+            interval: Duration::from_secs(update_interval_hours.max(1) * 3600),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(60))
+                .build()?,
+            trusted_signers,
+            detector,
+            storage,
+            ipfs,
+            artifact_guard,
+        })
+    }
+    
+    pub async fn start(&self) -> Result<()> {
+        info!("📋 Following threat pattern feed {} every {}h", self.config.url, self.interval.as_secs() / 3600);
+        
+        let mut interval = tokio::time::interval(self.interval);
+        loop {
+            interval.tick().await;
+            
+            match self.poll().await {
+                Ok(Some(count)) => info!("📋 Merged {} threat patterns from the feed", count),
+                Ok(None) => debug!("📋 No new pattern bundle on the feed"),
+                Err(e) => warn!("Pattern feed update failed: {}", e),
+            }
+        }
+    }
+    
+    /// Apply the feed's bundle if it was issued after the last one applied
+    async fn poll(&self) -> Result<Option<usize>> {
+        let raw = self.fetch().await?;
+        let signed: SignedPatternBundle = serde_json::from_slice(&raw)
+            .context("Malformed signed pattern bundle")?;
+        
+        let signer = verify_signed_payload(signed.payload.as_bytes(), &signed.signature, &self.trusted_signers)?;
+        let bundle: PatternBundle = serde_json::from_str(&signed.payload)
+            .context("Malformed pattern bundle")?;
+        
+        // A replayed older bundle could otherwise bring back retired patterns
+        let last_issued: Option<u64> = self.storage.get(PATTERN_FEED_NAMESPACE, BUNDLE_ISSUED_KEY)?;
+        match last_issued {
+            Some(last_issued) if bundle.issued_at < last_issued => {
+                bail!("Pattern bundle issued at {} is older than the last applied one ({})",
+                      bundle.issued_at, last_issued);
+            }
+            Some(last_issued) if bundle.issued_at == last_issued => return Ok(None),
+            _ => {}
+        }
+        debug!("🔏 Pattern bundle issued at {} signed by {:?}", bundle.issued_at, signer);
+        
+        let count = bundle.patterns.len();
+        let payload = serde_json::to_vec(&bundle.patterns)?;
+        match &self.artifact_guard {
+            Some(guard) => {
+                guard.ensure_not_quarantined(ArtifactKind::Patterns, &payload)?;
+                // Keep the full pattern set the bundle merges into, so it can be restored
+                let previous: Vec<ThreatPattern> = self.detector.get_threat_patterns().await.into_values().collect();
+                self.detector.update_threat_patterns(bundle.patterns).await?;
+                guard.artifact_applied(ArtifactKind::Patterns, &payload, Some(&serde_json::to_vec(&previous)?))?;
+            }
+            None => self.detector.update_threat_patterns(bundle.patterns).await?,
+        }
+        
+        self.storage.put(PATTERN_FEED_NAMESPACE, BUNDLE_ISSUED_KEY, &bundle.issued_at)?;
+        Ok(Some(count))
+    }
+    
+    async fn fetch(&self) -> Result<Vec<u8>> {
+        let content = match self.config.url.strip_prefix("ipfs://") {
+            Some(cid) => {
+                let ipfs = self.ipfs.as_ref().ok_or_else(|| anyhow!("IPFS client not available"))?;
+                ipfs.fetch(cid).await?
+            }
+            None => {
+                if !self.config.url.starts_with("https://") {
+                    bail!("Pattern feed URL must be https:// or ipfs://, got {}", self.config.url);
+                }
+                self.client
+                    .get(&self.config.url)
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .bytes()
+                    .await?
+                    .to_vec()
+            }
+        };
+        
+        if content.len() > self.config.max_bundle_bytes {
+            bail!("Pattern bundle is {} bytes, above the {} byte limit", content.len(), self.config.max_bundle_bytes);
+        }
+        Ok(content)
+    }
+}
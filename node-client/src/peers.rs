@@ -0,0 +1,341 @@
+//! Per-peer intel accounting, the peer blocklist and the reciprocity policy for serving peers
+
+use anyhow::Result;
+use dashmap::DashMap;
+use prometheus::{IntCounterVec, Opts};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::audit::{AuditEvent, AuditLog};
+use crate::config::{NetworkConfig, ReciprocityConfig};
+use crate::metrics::register_once;
+use crate::storage::NodeStorage;
+
+const PEER_ACCOUNT_NAMESPACE: &str = "peer_accounts";
+
+/// A peer is blocked until `until`, or for good when it is `None`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerBlock {
+    pub reason: String,
+    pub since: u64,
+    pub until: Option<u64>,
+}
+
+/// What a peer has given to and taken from this node
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PeerAccount {
+    pub peer_id: String,
+    /// Intel messages received from the peer
+    pub intel_received: u64,
+    /// Received intel that was well-formed and new to this node
+    pub useful_intel_received: u64,
+    pub invalid_messages: u64,
+    pub requests_served: u64,
+    pub requests_deprioritized: u64,
+    pub requests_refused: u64,
+    pub first_seen: u64,
+    pub last_seen: u64,
+    pub block: Option<PeerBlock>,
+}
+
+impl PeerAccount {
+    fn new(peer_id: &str, now: u64) -> Self {
+        Self {
+            peer_id: peer_id.to_string(),
+            first_seen: now,
+            last_seen: now,
+            ..Default::default()
+        }
+    }
+    
+    /// Useful intel given per request taken; peers that never asked anything count as pure givers
+    pub fn give_take_ratio(&self) -> f64 {
+        if self.requests_served == 0 {
+            return self.useful_intel_received as f64;
+        }
+        self.useful_intel_received as f64 / self.requests_served as f64
+    }
+    
+    fn is_blocked(&self, now: u64) -> bool {
+        match &self.block {
+            Some(block) => block.until.is_none_or(|until| now < until),
+            None => false,
+        }
+    }
+}
+
+/// One row of the peers admin view
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerSummary {
+    pub peer_id: String,
+    pub connected: bool,
+    pub intel_received: u64,
+    pub useful_intel_received: u64,
+    pub invalid_messages: u64,
+    pub requests_served: u64,
+    pub requests_deprioritized: u64,
+    pub requests_refused: u64,
+    pub give_take_ratio: f64,
+    pub free_rider: bool,
+    pub blocked: Option<PeerBlock>,
+    pub last_seen: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServeDecision {
+    Serve,
+    /// Serve only once peers that reciprocate have been answered
+    Deprioritize,
+    Refuse,
+}
+
+impl ServeDecision {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ServeDecision::Serve => "serve",
+            ServeDecision::Deprioritize => "deprioritize",
+            ServeDecision::Refuse => "refuse",
+        }
+    }
+}
+
+/// Tracks give/take per peer and decides how intel requests are served.
+///
+/// Accounts survive restarts so a free-rider cannot reset its standing by reconnecting;
+/// blocks from `network.blocked_peers` are permanent, automatic ones expire.
+pub struct PeerLedger {
+    config: ReciprocityConfig,
+    storage: Arc<NodeStorage>,
+    accounts: DashMap<String, PeerAccount>,
+    static_blocklist: HashSet<String>,
+    connected: DashMap<String, ()>,
+    decisions: IntCounterVec,
+    audit_log: OnceLock<Arc<AuditLog>>,
+}
+
+impl PeerLedger {
+    pub fn new(config: &NetworkConfig, storage: Arc<NodeStorage>) -> Result<Self> {
+        static DECISIONS: OnceLock<IntCounterVec> = OnceLock::new();
+        let decisions = register_once(&DECISIONS, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_peer_requests_total", "Intel requests from peers by serve decision"),
+                &["decision"],
+            )
+        })?;
+        
+        let accounts = DashMap::new();
+        for (peer_id, account) in storage.scan::<PeerAccount>(PEER_ACCOUNT_NAMESPACE)? {
+            accounts.insert(peer_id, account);
+        }
+        info!("🤝 Peer ledger loaded: {} known peers, {} statically blocked",
+              accounts.len(), config.blocked_peers.len());
+        
+        Ok(Self {
+            config: config.reciprocity.clone(),
+            storage,
+            accounts,
+            static_blocklist: config.blocked_peers.iter().cloned().collect(),
+            connected: DashMap::new(),
+            decisions,
+            audit_log: OnceLock::new(),
+        })
+    }
+    
+    /// Record blocks and unblocks in the node's audit log
+    pub fn attach_audit_log(&self, audit_log: Arc<AuditLog>) {
+        if self.audit_log.set(audit_log).is_err() {
+            warn!("⚠️ Audit log already attached to peer ledger");
+        }
+    }
+    
+    fn audit(&self, event: AuditEvent) {
+        if let Some(audit_log) = self.audit_log.get() {
+            audit_log.record(event);
+        }
+    }
+    
+    fn account(&self, peer_id: &str) -> dashmap::mapref::one::RefMut<'_, String, PeerAccount> {
+        let now = now_secs();
+        let mut account = self.accounts
+            .entry(peer_id.to_string())
+            .or_insert_with(|| PeerAccount::new(peer_id, now));
+        account.last_seen = now;
+        account
+    }
+    
+    pub fn peer_connected(&self, peer_id: &str) {
+        self.connected.insert(peer_id.to_string(), ());
+        self.account(peer_id);
+    }
+    
+    pub fn peer_disconnected(&self, peer_id: &str) {
+        self.connected.remove(peer_id);
+    }
+    
+    pub fn is_blocked(&self, peer_id: &str) -> bool {
+        if self.static_blocklist.contains(peer_id) {
+            return true;
+        }
+        self.accounts
+            .get(peer_id)
+            .is_some_and(|account| account.is_blocked(now_secs()))
+    }
+    
+    /// Record intel sent by a peer; `useful` when it was valid and not already known
+    pub fn record_intel(&self, peer_id: &str, useful: bool) {
+        let mut account = self.account(peer_id);
+        account.intel_received += 1;
+        if useful {
+            account.useful_intel_received += 1;
+        }
+    }
+    
+    /// Record a malformed or unverifiable message, blocking the peer past the configured limit
+    pub fn record_invalid(&self, peer_id: &str) {
+        let mut account = self.account(peer_id);
+        account.invalid_messages += 1;
+        
+        let limit = self.config.auto_block_invalid_messages;
+        if limit > 0 && account.invalid_messages >= limit && !account.is_blocked(now_secs()) {
+            let now = now_secs();
+            warn!("🚫 Blocking peer {} for {}s after {} invalid messages",
+                  peer_id, self.config.block_duration_secs, account.invalid_messages);
+            let block = PeerBlock {
+                reason: format!("{} invalid messages", account.invalid_messages),
+                since: now,
+                until: Some(now + self.config.block_duration_secs),
+            };
+            account.block = Some(block.clone());
+            // Start counting afresh once the block expires
+            account.invalid_messages = 0;
+            drop(account);
+            self.audit(AuditEvent::PeerBlocked { peer_id: peer_id.to_string(), block });
+        }
+    }
+    
+    /// Block a peer, for `duration` or permanently
+    pub fn block(&self, peer_id: &str, reason: &str, duration: Option<Duration>) {
+        let now = now_secs();
+        info!("🚫 Blocking peer {}: {}", peer_id, reason);
+        let block = PeerBlock {
+            reason: reason.to_string(),
+            since: now,
+            until: duration.map(|d| now + d.as_secs()),
+        };
+        self.account(peer_id).block = Some(block.clone());
+        self.audit(AuditEvent::PeerBlocked { peer_id: peer_id.to_string(), block });
+    }
+    
+    pub fn unblock(&self, peer_id: &str) {
+        if let Some(mut account) = self.accounts.get_mut(peer_id) {
+            if account.block.take().is_some() {
+                drop(account);
+                self.audit(AuditEvent::PeerUnblocked { peer_id: peer_id.to_string() });
+            }
+        }
+    }
+    
+    fn is_free_rider(&self, account: &PeerAccount) -> bool {
+        account.requests_served >= self.config.grace_requests
+            && account.give_take_ratio() < self.config.min_give_take_ratio
+    }
+    
+    /// Decide how to serve an intel request; free-riders only lose priority while congested
+    pub fn serve_decision(&self, peer_id: &str, congested: bool) -> ServeDecision {
+        let decision = if self.is_blocked(peer_id) {
+            ServeDecision::Refuse
+        } else if !self.config.enabled || !congested {
+            ServeDecision::Serve
+        } else if self.is_free_rider(&self.account(peer_id)) {
+            ServeDecision::Deprioritize
+        } else {
+            ServeDecision::Serve
+        };
+        
+        match decision {
+            ServeDecision::Refuse => self.account(peer_id).requests_refused += 1,
+            ServeDecision::Deprioritize => self.account(peer_id).requests_deprioritized += 1,
+            ServeDecision::Serve => {}
+        }
+        self.decisions.with_label_values(&[decision.as_str()]).inc();
+        debug!("🤝 Intel request from {}: {}", peer_id, decision.as_str());
+        
+        decision
+    }
+    
+    /// Record a request that was answered
+    pub fn record_served(&self, peer_id: &str) {
+        self.account(peer_id).requests_served += 1;
+    }
+    
+    /// Record a deferred request that was dropped because the deferred queue was full
+    pub fn record_dropped(&self, peer_id: &str) {
+        self.account(peer_id).requests_refused += 1;
+        self.decisions.with_label_values(&[ServeDecision::Refuse.as_str()]).inc();
+    }
+    
+    /// Per-peer give/take ratios, lowest first so free-riders top the list
+    pub fn summaries(&self) -> Vec<PeerSummary> {
+        let now = now_secs();
+        let mut summaries: Vec<PeerSummary> = self.accounts
+            .iter()
+            .map(|entry| {
+                let account = entry.value();
+                let blocked = if self.static_blocklist.contains(&account.peer_id) {
+                    Some(PeerBlock {
+                        reason: "network.blocked_peers".to_string(),
+                        since: account.first_seen,
+                        until: None,
+                    })
+                } else {
+                    account.block.clone().filter(|_| account.is_blocked(now))
+                };
+                PeerSummary {
+                    peer_id: account.peer_id.clone(),
+                    connected: self.connected.contains_key(&account.peer_id),
+                    intel_received: account.intel_received,
+                    useful_intel_received: account.useful_intel_received,
+                    invalid_messages: account.invalid_messages,
+                    requests_served: account.requests_served,
+                    requests_deprioritized: account.requests_deprioritized,
+                    requests_refused: account.requests_refused,
+                    give_take_ratio: account.give_take_ratio(),
+                    free_rider: self.is_free_rider(account),
+                    blocked,
+                    last_seen: account.last_seen,
+                }
+            })
+            .collect();
+        
+        summaries.sort_by(|a, b| a.give_take_ratio.total_cmp(&b.give_take_ratio));
+        summaries
+    }
+    
+    /// Persist all accounts so standing survives restarts
+    pub fn flush(&self) -> Result<()> {
+        let mut batch = self.storage.batch();
+        for entry in self.accounts.iter() {
+            batch.put(PEER_ACCOUNT_NAMESPACE, entry.key(), entry.value())?;
+        }
+        self.storage.commit(batch)?;
+        debug!("🤝 Flushed {} peer accounts", self.accounts.len());
+        Ok(())
+    }
+    
+    pub async fn start(&self) -> Result<()> {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.flush_interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.flush() {
+                warn!("Failed to flush peer accounts: {}", e);
+            }
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
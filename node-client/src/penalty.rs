@@ -0,0 +1,224 @@
+//! Alerts when the contract penalizes this node
+//!
+//! `NodeSlashed` and `NodeDeactivated` events for the node's address arrive from the event
+//! listener as soon as it reads them. Independently, every `check_interval_secs` the node's
+//! reputation and active flag are read from the contract, which catches reputation lost to a
+//! rejected report and deactivations while the listener is off. A slash resets the reputation
+//! baseline, as its own alert covers the reputation it cost.
+//!
+//! Each penalty is counted in the node stats and runs the configured actions: log it, post it to
+//! `webhook_url`, or pause on-chain reporting until the operator resumes it through the
+//! maintenance API. Leaving the network with `stake withdraw --all` is not a penalty.
+
+use anyhow::Result;
+use ethers::types::U256;
+use ethers::utils::format_ether;
+use parking_lot::Mutex;
+use prometheus::{IntCounterVec, Opts};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::{broadcast::error::RecvError, RwLock};
+use tracing::{error, info, warn};
+
+use crate::blockchain::BlockchainClient;
+use crate::config::{PenaltyAction, PenaltyAlertConfig};
+use crate::maintenance::{MaintenanceControl, Stage};
+use crate::metrics::register_once;
+use crate::node::NodeStats;
+
+/// Deactivation reason the contract gives when the node leaves on its own
+const VOLUNTARY_DEACTIVATION: &str = "unstaked";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PenaltyKind {
+    Slashed {
+        /// In wei
+        amount: U256,
+        reason: String,
+    },
+    Deactivated {
+        reason: String,
+    },
+    ReputationDrop {
+        from: u32,
+        to: u32,
+    },
+}
+
+impl PenaltyKind {
+    fn label(&self) -> &'static str {
+        match self {
+            PenaltyKind::Slashed { .. } => "slashed",
+            PenaltyKind::Deactivated { .. } => "deactivated",
+            PenaltyKind::ReputationDrop { .. } => "reputation_drop",
+        }
+    }
+}
+
+impl std::fmt::Display for PenaltyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PenaltyKind::Slashed { amount, reason } => write!(f, "slashed {} for {}", format_ether(*amount), reason),
+            PenaltyKind::Deactivated { reason } => write!(f, "deactivated ({})", reason),
+            PenaltyKind::ReputationDrop { from, to } => write!(f, "reputation dropped from {} to {}", from, to),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Penalty {
+    /// Unix seconds the node noticed it
+    pub at: u64,
+    #[serde(flatten)]
+    pub kind: PenaltyKind,
+}
+
+/// Reputation and active flag as last read from the contract
+#[derive(Debug, Clone, Copy)]
+struct Standing {
+    reputation: Option<u32>,
+    active: bool,
+}
+
+pub struct PenaltyMonitor {
+    config: PenaltyAlertConfig,
+    node_id: String,
+    blockchain: Arc<BlockchainClient>,
+    maintenance: Arc<MaintenanceControl>,
+    stats: Arc<RwLock<NodeStats>>,
+    http: reqwest::Client,
+    penalties: IntCounterVec,
+    standing: Mutex<Option<Standing>>,
+}
+
+impl PenaltyMonitor {
+    pub fn new(
+        config: &PenaltyAlertConfig,
+        node_id: &str,
+        blockchain: Arc<BlockchainClient>,
+        maintenance: Arc<MaintenanceControl>,
+        stats: Arc<RwLock<NodeStats>>,
+    ) -> Result<Self> {
+        static PENALTIES: OnceLock<IntCounterVec> = OnceLock::new();
+        let penalties = register_once(&PENALTIES, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_node_penalties_total", "Slashes, deactivations and reputation drops of this node"),
+                &["kind"],
+            )
+        })?;
+        
+        Ok(Self {
+            config: config.clone(),
+            node_id: node_id.to_string(),
+            blockchain,
+            maintenance,
+            stats,
+            http: reqwest::Client::builder().timeout(Duration::from_secs(10)).build()?,
+            penalties,
+            standing: Mutex::new(None),
+        })
+    }
+    
+    /// Follow penalty events and check the node's standing until the task is aborted
+    pub async fn start(&self) -> Result<()> {
+        let mut events = self.blockchain.subscribe_penalties();
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.check_interval_secs.max(1)));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Err(e) = self.check_standing().await {
+                        warn!("Penalty check failed: {:#}", e);
+                    }
+                }
+                received = events.recv() => match received {
+                    Ok(kind) => self.on_event(kind).await,
+                    Err(RecvError::Lagged(missed)) => warn!("⚠️ Missed {} penalty events; the next check reads the standing", missed),
+                    Err(RecvError::Closed) => return Ok(()),
+                },
+            }
+        }
+    }
+    
+    async fn on_event(&self, kind: PenaltyKind) {
+        {
+            let mut standing = self.standing.lock();
+            if let Some(standing) = standing.as_mut() {
+                match &kind {
+                    PenaltyKind::Slashed { .. } => standing.reputation = None,
+                    PenaltyKind::Deactivated { .. } => standing.active = false,
+                    PenaltyKind::ReputationDrop { .. } => {}
+                }
+            }
+        }
+        if matches!(&kind, PenaltyKind::Deactivated { reason } if reason == VOLUNTARY_DEACTIVATION) {
+            info!("🚪 Node {} left the network", self.node_id);
+            return;
+        }
+        self.raise(kind).await;
+    }
+    
+    async fn check_standing(&self) -> Result<()> {
+        let (reputation, active) = self.blockchain.get_node_standing().await?;
+        let previous = self.standing.lock().replace(Standing { reputation: Some(reputation), active });
+        let Some(previous) = previous else {
+            return Ok(());
+        };
+        
+        if previous.active && !active {
+            self.raise(PenaltyKind::Deactivated { reason: "no longer active on the contract".to_string() }).await;
+        }
+        if let Some(from) = previous.reputation {
+            if from >= reputation + self.config.reputation_drop_threshold.max(1) {
+                self.raise(PenaltyKind::ReputationDrop { from, to: reputation }).await;
+            }
+        }
+        Ok(())
+    }
+    
+    async fn raise(&self, kind: PenaltyKind) {
+        self.penalties.with_label_values(&[kind.label()]).inc();
+        let penalty = Penalty {
+            at: chrono::Utc::now().timestamp() as u64,
+            kind,
+        };
+        {
+            let mut stats = self.stats.write().await;
+            stats.penalties += 1;
+            stats.last_penalty = Some(penalty.clone());
+        }
+        
+        for action in &self.config.actions {
+            match action {
+                PenaltyAction::Log => error!("⚔️ Node {} was penalized: {}", self.node_id, penalty.kind),
+                PenaltyAction::Webhook => self.post(&penalty),
+                PenaltyAction::PauseReporting => {
+                    if self.maintenance.pause(Stage::Reporting) {
+                        warn!("⏸️ Paused reporting after a penalty; resume it through the maintenance API");
+                    }
+                }
+            }
+        }
+    }
+    
+    fn post(&self, penalty: &Penalty) {
+        let Some(url) = self.config.webhook_url.clone() else {
+            warn!("⚠️ Penalty webhook action configured without penalty_alerts.webhook_url");
+            return;
+        };
+        let body = serde_json::json!({ "event": "node_penalized", "node_id": self.node_id, "penalty": penalty });
+        let http = self.http.clone();
+        tokio::spawn(async move {
+            let result = http
+                .post(&url)
+                .json(&body)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(e) = result {
+                error!("❌ Penalty alert delivery failed: {}", e.without_url());
+            }
+        });
+    }
+}
@@ -0,0 +1,242 @@
+//! `preflight`: check a deployment before the node goes live
+//!
+//! Runs every check the node would otherwise trip over at startup or, worse, hours later: the
+//! config, the chain RPC, the deployed contracts, the wallet's funds, the model, storage and the
+//! listening ports. Each check passes, warns or fails on its own, so one run lists every problem.
+
+use anyhow::{bail, Context, Result};
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::signers::Signer;
+use ethers::types::U256;
+use ort::session::builder::GraphOptimizationLevel;
+use ort::session::Session;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+use crate::config::{NodeConfig, SignerConfig};
+use crate::contract_guard::parse_checksummed_address;
+use crate::keys;
+use crate::rollback::ArtifactGuard;
+use crate::signer::NodeSigner;
+use crate::storage::NodeStorage;
+
+const PREFLIGHT_NAMESPACE: &str = "preflight";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    /// The node will run, but not as configured or not for long
+    Warn,
+    Fail,
+    /// Not applicable to this node's roles, or blocked by an earlier failure
+    Skip,
+}
+
+impl CheckStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pass => "PASS",
+            Self::Warn => "WARN",
+            Self::Fail => "FAIL",
+            Self::Skip => "SKIP",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightReport {
+    pub checks: Vec<PreflightCheck>,
+}
+
+impl PreflightReport {
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.status != CheckStatus::Fail)
+    }
+    
+    fn record(&mut self, name: impl Into<String>, outcome: Result<(CheckStatus, String)>) {
+        let (status, detail) = outcome.unwrap_or_else(|e| (CheckStatus::Fail, format!("{:#}", e)));
+        self.checks.push(PreflightCheck { name: name.into(), status, detail });
+    }
+    
+    fn skip(&mut self, name: impl Into<String>, reason: &str) {
+        self.record(name, Ok((CheckStatus::Skip, reason.to_string())));
+    }
+}
+
+/// Run every check against `config`; run it with the node stopped, as it opens storage and binds ports
+pub async fn run(config: &NodeConfig) -> PreflightReport {
+    let mut report = PreflightReport { checks: Vec::new() };
+    
+    report.record("config", check_config(config));
+    
+    let provider = match check_rpc(config).await {
+        Ok((provider, detail)) => {
+            report.record("chain rpc", Ok((CheckStatus::Pass, detail)));
+            Some(provider)
+        }
+        Err(e) => {
+            report.record("chain rpc", Err(e));
+            None
+        }
+    };
+    
+    let contracts = [
+        ("core contract", Some(&config.blockchain.contract_address)),
+        ("oracle contract", config.blockchain.oracle_address.as_ref()),
+        ("token contract", config.blockchain.token_address.as_ref()),
+    ];
+    for (name, address) in contracts {
+        match (&provider, address) {
+            (_, None) => report.skip(name, "not configured"),
+            (None, Some(_)) => report.skip(name, "chain RPC unreachable"),
+            (Some(provider), Some(address)) => report.record(name, check_contract(provider, address).await),
+        }
+    }
+    
+    match &provider {
+        Some(provider) => report.record("wallet balance", check_wallet(config, provider).await),
+        None => report.skip("wallet balance", "chain RPC unreachable"),
+    }
+    
+    if config.enable_ai {
+        report.record("model", check_model(&config.ai.model_path, config.ai.model_checksum.as_deref()));
+        for chain in &config.ai.chain_models {
+            if let Some(path) = &chain.model_path {
+                report.record(format!("model (chain {})", chain.chain_id), check_model(path, None));
+            }
+        }
+    } else {
+        report.skip("model", "AI detection disabled");
+    }
+    
+    report.record("storage", check_storage(config).await);
+    
+    let ports = [
+        ("p2p port", config.enable_p2p, config.network.listen_port),
+        ("metrics port", config.metrics.enabled, config.metrics.port),
+        ("screening port", config.screening.enabled, config.screening.listen_port),
+    ];
+    for (name, enabled, port) in ports {
+        if enabled {
+            report.record(name, check_port(port).await);
+        } else {
+            report.skip(name, "disabled");
+        }
+    }
+    
+    report
+}
+
+fn check_config(config: &NodeConfig) -> Result<(CheckStatus, String)> {
+    // Other signers are reached in the wallet check
+    if matches!(config.blockchain.signer, SignerConfig::Local) {
+        keys::load_wallet(&config.blockchain)?;
+    }
+    parse_checksummed_address(&config.blockchain.contract_address)?;
+    for address in [&config.blockchain.oracle_address, &config.blockchain.token_address].into_iter().flatten() {
+        parse_checksummed_address(address)?;
+    }
+    
+    let mut ports = vec![config.metrics.port];
+    if config.enable_p2p {
+        ports.push(config.network.listen_port);
+    }
+    if config.screening.enabled {
+        ports.push(config.screening.listen_port);
+    }
+    ports.sort_unstable();
+    if let Some(pair) = ports.windows(2).find(|pair| pair[0] == pair[1]) {
+        bail!("Port {} is configured for more than one listener", pair[0]);
+    }
+    
+    Ok((CheckStatus::Pass, "parsed, keys and addresses valid".to_string()))
+}
+
+async fn check_rpc(config: &NodeConfig) -> Result<(Provider<Http>, String)> {
+    let provider = Provider::<Http>::try_from(&config.blockchain.rpc_url)?;
+    let chain_id = provider
+        .get_chainid()
+        .await
+        .with_context(|| format!("{} is unreachable", config.blockchain.rpc_url))?
+        .as_u64();
+    // chain_id = 0 takes whatever the RPC serves
+    if config.blockchain.chain_id != 0 && chain_id != config.blockchain.chain_id {
+        bail!("{} serves chain {}, but the config says {}", config.blockchain.rpc_url, chain_id, config.blockchain.chain_id);
+    }
+    let block = provider.get_block_number().await?;
+    Ok((provider, format!("{} on chain {}, block {}", config.blockchain.rpc_url, chain_id, block)))
+}
+
+async fn check_contract(provider: &Provider<Http>, configured: &str) -> Result<(CheckStatus, String)> {
+    let address = parse_checksummed_address(configured)?;
+    let code = provider.get_code(address, None).await?;
+    if code.is_empty() {
+        bail!("No contract code at {}", configured);
+    }
+    Ok((CheckStatus::Pass, format!("{} ({} bytes of code)", configured, code.len())))
+}
+
+async fn check_wallet(config: &NodeConfig, provider: &Provider<Http>) -> Result<(CheckStatus, String)> {
+    let wallet = NodeSigner::connect(&config.blockchain).await?;
+    let balance = provider.get_balance(wallet.address(), None).await?;
+    let one_transaction = U256::from(config.blockchain.gas_limit) * U256::from(config.blockchain.gas_price_gwei) * U256::exp10(9);
+    let stake = U256::from(config.node.stake_amount_gwei) * U256::exp10(9);
+    let detail = format!("{:?} holds {} wei", wallet.address(), balance);
+    
+    if balance < one_transaction {
+        bail!("{}, less than the gas for one transaction ({} wei)", detail, one_transaction);
+    }
+    if balance < stake + one_transaction {
+        // Already registered nodes have staked; only a first start needs the stake
+        return Ok((CheckStatus::Warn, format!("{}, not enough to stake {} wei on first registration", detail, stake)));
+    }
+    Ok((CheckStatus::Pass, detail))
+}
+
+fn check_model(path: &str, expected_checksum: Option<&str>) -> Result<(CheckStatus, String)> {
+    let model_bytes = std::fs::read(path).with_context(|| format!("Cannot read model {}", path))?;
+    let hash = ArtifactGuard::artifact_hash(&model_bytes);
+    
+    Session::builder()?
+        .with_optimization_level(GraphOptimizationLevel::Level3)?
+        .commit_from_memory(&model_bytes)
+        .with_context(|| format!("Model {} does not load", path))?;
+    
+    match expected_checksum {
+        Some(expected) if !expected.eq_ignore_ascii_case(&hash) => {
+            bail!("Model {} has checksum {}, expected {}", path, hash, expected)
+        }
+        Some(_) => Ok((CheckStatus::Pass, format!("{} loads, checksum matches", path))),
+        None => Ok((CheckStatus::Warn, format!("{} loads, no checksum pinned (blake3 {})", path, hash))),
+    }
+}
+
+async fn check_storage(config: &NodeConfig) -> Result<(CheckStatus, String)> {
+    let storage = NodeStorage::new(&config.storage)
+        .await
+        .with_context(|| format!("Cannot open {}; is a node already running on it?", config.storage.data_dir))?;
+    // A write that fails drops storage into RAM mode instead of erroring
+    storage.put(PREFLIGHT_NAMESPACE, "probe", &chrono::Utc::now().timestamp())?;
+    storage.delete(PREFLIGHT_NAMESPACE, "probe")?;
+    storage.flush().await?;
+    if let Some(reason) = storage.failure() {
+        bail!("{} is not writable: {}", config.storage.data_dir, reason);
+    }
+    Ok((CheckStatus::Pass, format!("{} writable, {} bytes on disk", config.storage.data_dir, storage.size_on_disk()?)))
+}
+
+async fn check_port(port: u16) -> Result<(CheckStatus, String)> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Cannot bind {}", addr))?;
+    Ok((CheckStatus::Pass, format!("{} free", addr)))
+}
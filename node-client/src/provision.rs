@@ -0,0 +1,246 @@
+//! Headless provisioning of new devices from a signed bundle
+//!
+//! `dagshield-node provision` fetches a bundle from `--url`, or takes it from the text of a
+//! scanned QR code, checks that a trusted operator key signed it for this device's token, then
+//! writes the device's config and enrolls it with the fleet controller. A bundle never carries a
+//! private key, only where the device's key comes from, so one bundle can be printed on a label
+//! or baked into cloud-init user data.
+
+use anyhow::{anyhow, bail, Context, Result};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::Address;
+use ethers::utils::{hex, keccak256};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::Path;
+use tracing::{info, warn};
+
+use crate::config::NodeConfig;
+use crate::fleet;
+use crate::signature::{parse_signers, verify_signed_payload};
+
+/// QR codes either hold a bundle inline or point at one: `dagshield://provision?url=...&token=...`
+pub const PROVISION_URI_PREFIX: &str = "dagshield://provision";
+
+/// The bundle as served: `payload` is the JSON-encoded [`ProvisioningPayload`], signed as-is
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvisioningBundle {
+    pub payload: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvisioningPayload {
+    /// Hex keccak256 of the token the bundle was issued for, so a leaked bundle is useless alone
+    pub token_hash: String,
+    pub issued_at: u64,
+    pub expires_at: u64,
+    /// The node config as TOML; its `blockchain.private_key` is replaced as `key` says
+    pub config: String,
+    pub key: KeyReference,
+    /// Identity to enroll under; the key's address when unset
+    #[serde(default)]
+    pub node_id: Option<String>,
+    #[serde(default)]
+    pub fleet: Option<FleetEnrollment>,
+}
+
+/// Where the device's signing key comes from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum KeyReference {
+    /// Generate a fresh key on the device; it never leaves it
+    Generate,
+    /// Read the key from a file put on the device by the installer or image
+    File { path: String },
+    /// Read the key from an environment variable, e.g. one set by cloud-init
+    Env { var: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetEnrollment {
+    pub controller_url: String,
+    /// The fleet the controller signs its updates for
+    pub fleet_id: String,
+    /// Operator keys accepted on later fleet updates
+    pub trusted_signers: Vec<String>,
+}
+
+pub struct ProvisionOptions {
+    /// Device token; may instead come from a `dagshield://provision` QR code
+    pub token: Option<String>,
+    pub url: Option<String>,
+    /// Scanned QR text, or `-` to read it from stdin
+    pub qr: Option<String>,
+    /// Operator keys accepted on the bundle
+    pub trusted_signers: Vec<String>,
+    pub config_path: String,
+    /// Replace an existing config file
+    pub force: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Provisioned {
+    pub config_path: String,
+    pub node_id: String,
+    /// Address of the device's key, to fund and stake from
+    pub address: Address,
+    pub signed_by: Address,
+    /// Fleet controller the device enrolled with
+    pub controller_url: Option<String>,
+    /// Whether the controller accepted the enrollment now rather than on first start
+    pub enrolled: bool,
+}
+
+/// Fetch, verify and apply a provisioning bundle
+pub async fn provision(options: &ProvisionOptions) -> Result<Provisioned> {
+    let trusted_signers = parse_signers(&options.trusted_signers)?;
+    if trusted_signers.is_empty() {
+        bail!("Provisioning requires at least one --trusted-signer");
+    }
+    if Path::new(&options.config_path).exists() && !options.force {
+        bail!("{} already exists; pass --force to replace it", options.config_path);
+    }
+    
+    let (bundle, token) = match (&options.url, &options.qr) {
+        (Some(url), None) => {
+            let token = options.token.clone().context("--token is required with --url")?;
+            (fetch_bundle(url, &token).await?, token)
+        }
+        (None, Some(qr)) => read_qr(qr, options.token.as_deref()).await?,
+        _ => bail!("Pass exactly one of --url or --qr"),
+    };
+    
+    let signed_by = verify_signed_payload(bundle.payload.as_bytes(), &bundle.signature, &trusted_signers)
+        .context("Provisioning bundle rejected")?;
+    let payload: ProvisioningPayload = serde_json::from_str(&bundle.payload)
+        .context("Malformed provisioning payload")?;
+    
+    let token_hash = hex::encode(keccak256(token.as_bytes()));
+    if !payload.token_hash.trim_start_matches("0x").eq_ignore_ascii_case(&token_hash) {
+        bail!("Provisioning bundle was issued for a different token");
+    }
+    let now = chrono::Utc::now().timestamp() as u64;
+    if now >= payload.expires_at {
+        bail!("Provisioning bundle expired at {}", payload.expires_at);
+    }
+    
+    let mut config: NodeConfig = toml::from_str(&payload.config)
+        .context("Provisioning bundle holds an invalid config")?;
+    let wallet = resolve_key(&payload.key)?;
+    config.blockchain.private_key = hex::encode(wallet.signer().to_bytes());
+    if let Some(fleet) = &payload.fleet {
+        config.fleet.enabled = true;
+        config.fleet.controller_url = fleet.controller_url.clone();
+        config.fleet.fleet_id = fleet.fleet_id.clone();
+        config.fleet.trusted_signers = fleet.trusted_signers.clone();
+        // Config updates from the controller land where this config is written
+        config.fleet.config_path = options.config_path.clone();
+    }
+    write_config(&config, &options.config_path)?;
+    info!("📝 Wrote provisioned config to {}", options.config_path);
+    
+    let node_id = payload.node_id.clone().unwrap_or_else(|| format!("{:?}", wallet.address()));
+    let enrolled = match &payload.fleet {
+        // The agent enrolls again on every start, so a controller out of reach now is not fatal
+        Some(_) => match fleet::enroll(&config.fleet, &node_id, config.blockchain.chain_id).await {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("⚠️ Fleet enrollment failed, the node retries when started: {:#}", e);
+                false
+            }
+        },
+        None => false,
+    };
+    
+    Ok(Provisioned {
+        config_path: options.config_path.clone(),
+        node_id,
+        address: wallet.address(),
+        signed_by,
+        controller_url: payload.fleet.map(|fleet| fleet.controller_url),
+        enrolled,
+    })
+}
+
+async fn fetch_bundle(url: &str, token: &str) -> Result<ProvisioningBundle> {
+    if !url.starts_with("https://") {
+        warn!("⚠️ Fetching provisioning bundle over plain HTTP exposes the device token");
+    }
+    let bundle = reqwest::Client::new()
+        .get(url)
+        .bearer_auth(token)
+        .send()
+        .await?
+        .error_for_status()
+        .with_context(|| format!("Provisioning server refused {}", url))?
+        .json::<ProvisioningBundle>()
+        .await
+        .context("Malformed provisioning bundle")?;
+    Ok(bundle)
+}
+
+/// Resolve QR text into a bundle and the token it is checked against
+async fn read_qr(qr: &str, token: Option<&str>) -> Result<(ProvisioningBundle, String)> {
+    let text = if qr == "-" {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text)?;
+        text
+    } else {
+        qr.to_string()
+    };
+    let text = text.trim();
+    
+    if !text.starts_with(PROVISION_URI_PREFIX) {
+        let bundle = serde_json::from_str(text)
+            .context("QR text is neither a provisioning link nor a bundle")?;
+        let token = token.context("--token is required with an inline bundle")?;
+        return Ok((bundle, token.to_string()));
+    }
+    
+    let link = reqwest::Url::parse(text).context("Malformed provisioning link")?;
+    let param = |name: &str| link.query_pairs().find(|(key, _)| key == name).map(|(_, value)| value.into_owned());
+    let url = param("url").context("Provisioning link has no url")?;
+    // A token typed in beats one printed next to the bundle link
+    let token = token.map(str::to_string).or_else(|| param("token"))
+        .context("Provisioning link has no token; pass --token")?;
+    Ok((fetch_bundle(&url, &token).await?, token))
+}
+
+fn resolve_key(key: &KeyReference) -> Result<LocalWallet> {
+    let secret = match key {
+        KeyReference::Generate => {
+            let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+            info!("🔑 Generated device key {:?}", wallet.address());
+            return Ok(wallet);
+        }
+        KeyReference::File { path } => std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read device key from {}", path))?,
+        KeyReference::Env { var } => std::env::var(var)
+            .map_err(|_| anyhow!("Device key variable {} is not set", var))?,
+    };
+    secret
+        .trim()
+        .trim_start_matches("0x")
+        .parse::<LocalWallet>()
+        .map_err(|e| anyhow!("Invalid device key: {}", e))
+}
+
+/// Write the config readable by its owner only, as it now holds the device key
+fn write_config(config: &NodeConfig, path: &str) -> Result<()> {
+    let content = toml::to_string_pretty(config)?;
+    if let Some(parent) = Path::new(path).parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    
+    let mut file = std::fs::File::create(path).with_context(|| format!("Failed to write {}", path))?;
+    // Narrowed before the key is written, including over a config replaced with --force
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    Ok(())
+}
@@ -0,0 +1,223 @@
+//! Read-only SQL over the node's stored data, for ad-hoc questions without export scripts
+//!
+//! Each query runs against an in-memory SQLite snapshot of storage, rebuilt at most every
+//! `snapshot_ttl_secs`, with these tables:
+//!
+//! - `detections`: threat reports this node submitted
+//! - `events`: `ThreatDetected` events indexed from the contract
+//! - `energy`: the energy monitor's stored readings
+//! - `audit_log`: the audit log, one row per event with its fields as JSON in `details`
+//!
+//! The snapshot is opened query-only and cannot attach other databases, so a query cannot
+//! write anywhere; one running past `timeout_ms` is interrupted.
+
+use anyhow::{bail, Result};
+use axum::{http::StatusCode, response::IntoResponse, routing::post, Json, Router};
+use ethers::utils::hex;
+use rusqlite::limits::Limit;
+use rusqlite::types::ValueRef;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::audit::{AuditRecord, AUDIT_NAMESPACE};
+use crate::blockchain::{IndexedThreatAlert, THREAT_ALERT_NAMESPACE};
+use crate::config::QueryConfig;
+use crate::energy::{EnergyMetrics, ENERGY_HISTORY_NAMESPACE};
+use crate::node::{ThreatReportRecord, THREAT_REPORT_NAMESPACE};
+use crate::status::Report;
+use crate::storage::NodeStorage;
+
+const SCHEMA: &str = "
+    CREATE TABLE detections (
+        transaction_id TEXT NOT NULL,
+        target_address TEXT NOT NULL,
+        chain_id INTEGER NOT NULL,
+        threat_type TEXT NOT NULL,
+        confidence INTEGER NOT NULL,
+        tx_hash TEXT NOT NULL,
+        evidence_cid TEXT,
+        reported_at INTEGER NOT NULL
+    );
+    CREATE TABLE events (
+        alert_id TEXT NOT NULL,
+        reporter TEXT NOT NULL,
+        chain_id INTEGER NOT NULL,
+        threat_type TEXT NOT NULL,
+        confidence INTEGER NOT NULL,
+        timestamp INTEGER NOT NULL
+    );
+    CREATE TABLE energy (
+        timestamp INTEGER NOT NULL,
+        cpu_usage_percent REAL NOT NULL,
+        memory_usage_percent REAL NOT NULL,
+        power_consumption_watts REAL NOT NULL,
+        battery_level_percent REAL,
+        temperature_celsius REAL NOT NULL,
+        efficiency_score INTEGER NOT NULL,
+        carbon_footprint_kg_per_hour REAL NOT NULL
+    );
+    CREATE TABLE audit_log (
+        at_ms INTEGER NOT NULL,
+        event TEXT NOT NULL,
+        details TEXT NOT NULL
+    );
+";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryRequest {
+    pub sql: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// More rows matched than `max_rows`
+    pub truncated: bool,
+    /// Unix time of the storage snapshot the query ran against
+    pub snapshot_at: u64,
+}
+
+struct Snapshot {
+    connection: Connection,
+    taken: Instant,
+    taken_at: u64,
+}
+
+pub struct QueryEngine {
+    config: QueryConfig,
+    storage: Arc<NodeStorage>,
+    /// Also serializes queries, which each hold the snapshot for their duration
+    snapshot: parking_lot::Mutex<Option<Snapshot>>,
+}
+
+impl QueryEngine {
+    pub fn new(config: &QueryConfig, storage: Arc<NodeStorage>) -> Self {
+        Self {
+            config: config.clone(),
+            storage,
+            snapshot: parking_lot::Mutex::new(None),
+        }
+    }
+    
+    /// Run one read-only statement, off the async workers
+    pub async fn query(self: &Arc<Self>, sql: String) -> Result<QueryResult> {
+        let engine = Arc::clone(self);
+        tokio::task::spawn_blocking(move || engine.run(&sql)).await?
+    }
+    
+    fn run(&self, sql: &str) -> Result<QueryResult> {
+        let mut snapshot = self.snapshot.lock();
+        let stale = snapshot
+            .as_ref()
+            .is_none_or(|snapshot| snapshot.taken.elapsed() >= Duration::from_secs(self.config.snapshot_ttl_secs));
+        if stale {
+            *snapshot = Some(self.take_snapshot()?);
+        }
+        let snapshot = snapshot.as_ref().expect("taken above");
+        
+        let deadline = Instant::now() + Duration::from_millis(self.config.timeout_ms);
+        snapshot.connection.progress_handler(1000, Some(move || Instant::now() > deadline));
+        
+        let mut statement = snapshot.connection.prepare(sql)?;
+        if !statement.readonly() {
+            bail!("Only read-only statements can be run");
+        }
+        let columns: Vec<String> = statement.column_names().into_iter().map(str::to_string).collect();
+        let mut rows = Vec::new();
+        let mut truncated = false;
+        let mut results = statement.query([])?;
+        while let Some(row) = results.next()? {
+            if rows.len() == self.config.max_rows {
+                truncated = true;
+                break;
+            }
+            rows.push((0..columns.len()).map(|i| row.get_ref(i).map(json_value)).collect::<rusqlite::Result<_>>()?);
+        }
+        
+        debug!("🔎 Query returned {} rows{}", rows.len(), if truncated { " (truncated)" } else { "" });
+        Ok(QueryResult { columns, rows, truncated, snapshot_at: snapshot.taken_at })
+    }
+    
+    fn take_snapshot(&self) -> Result<Snapshot> {
+        let started = Instant::now();
+        let mut connection = Connection::open_in_memory()?;
+        connection.set_limit(Limit::SQLITE_LIMIT_ATTACHED, 0);
+        connection.execute_batch(SCHEMA)?;
+        
+        let transaction = connection.transaction()?;
+        {
+            let mut insert = transaction.prepare("INSERT INTO detections VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)")?;
+            for (_, record) in self.storage.scan::<ThreatReportRecord>(THREAT_REPORT_NAMESPACE)? {
+                insert.execute(params![
+                    record.transaction_id, record.target_address, record.chain_id as i64, record.threat_type.to_string(),
+                    record.confidence, record.tx_hash, record.evidence_cid, record.reported_at as i64,
+                ])?;
+            }
+            
+            let mut insert = transaction.prepare("INSERT INTO events VALUES (?1, ?2, ?3, ?4, ?5, ?6)")?;
+            for (_, alert) in self.storage.scan::<IndexedThreatAlert>(THREAT_ALERT_NAMESPACE)? {
+                insert.execute(params![
+                    alert.alert_id, alert.reporter, alert.chain_id as i64, alert.threat_type.to_string(),
+                    alert.confidence, alert.timestamp as i64,
+                ])?;
+            }
+            
+            let mut insert = transaction.prepare("INSERT INTO energy VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)")?;
+            for (_, reading) in self.storage.scan::<EnergyMetrics>(ENERGY_HISTORY_NAMESPACE)? {
+                insert.execute(params![
+                    reading.timestamp as i64, reading.cpu_usage_percent, reading.memory_usage_percent,
+                    reading.power_consumption_watts, reading.battery_level_percent, reading.temperature_celsius,
+                    reading.efficiency_score, reading.carbon_footprint_kg_per_hour,
+                ])?;
+            }
+            
+            let mut insert = transaction.prepare("INSERT INTO audit_log VALUES (?1, ?2, ?3)")?;
+            for (_, record) in self.storage.scan::<AuditRecord>(AUDIT_NAMESPACE)? {
+                let (event, details) = match serde_json::to_value(&record.event)? {
+                    serde_json::Value::Object(tagged) => tagged.into_iter().next().unwrap_or_default(),
+                    other => (other.to_string(), serde_json::Value::Null),
+                };
+                insert.execute(params![record.at_ms as i64, event, details.to_string()])?;
+            }
+        }
+        transaction.commit()?;
+        
+        connection.pragma_update(None, "query_only", true)?;
+        info!("🗃️ Query snapshot of storage taken in {:?}", started.elapsed());
+        Ok(Snapshot {
+            connection,
+            taken: Instant::now(),
+            taken_at: chrono::Utc::now().timestamp() as u64,
+        })
+    }
+}
+
+fn json_value(value: ValueRef<'_>) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(integer) => integer.into(),
+        ValueRef::Real(real) => real.into(),
+        ValueRef::Text(text) => String::from_utf8_lossy(text).into(),
+        ValueRef::Blob(blob) => format!("0x{}", hex::encode(blob)).into(),
+    }
+}
+
+/// `POST /query` with `{"sql": "..."}`, answered with a `query` report
+pub fn admin_routes(engine: Arc<QueryEngine>) -> Router {
+    Router::new().route(
+        "/query",
+        post(move |Json(request): Json<QueryRequest>| async move {
+            match engine.query(request.sql).await {
+                Ok(result) => Json(Report::new("query", result)).into_response(),
+                Err(e) => {
+                    warn!("Rejected query: {:#}", e);
+                    (StatusCode::BAD_REQUEST, format!("{:#}", e)).into_response()
+                }
+            }
+        }),
+    )
+}
@@ -0,0 +1,318 @@
+//! Signed first-seen receipts for detections, exchanged between nodes
+//!
+//! When a node first flags a threat to an address it signs a receipt over the detection's hash,
+//! a timestamp that never runs backwards and its own address. Receipts are gossiped to peers,
+//! checked and kept as one chain per detection, earliest first. The chain is pinned with the
+//! evidence behind a report, and a node whose receipt heads it claims the first-reporter reward
+//! on-chain; a peer holding an earlier receipt can supersede the claim until the window closes.
+
+use anyhow::{bail, Result};
+use ethers::abi::{encode, Token};
+use ethers::types::{Address, Signature, U256};
+use ethers::utils::{hex, keccak256};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::blockchain::BlockchainClient;
+use crate::config::ReceiptConfig;
+use crate::dag::Transaction;
+use crate::storage::NodeStorage;
+use crate::threat::ThreatClass;
+
+/// Receipt chains, keyed by hex detection hash
+pub const RECEIPT_NAMESPACE: &str = "detection_receipts";
+/// First-reporter claims this node made and has not settled yet
+pub const RECEIPT_CLAIM_NAMESPACE: &str = "first_reporter_claims";
+const RECEIPT_CLOCK_NAMESPACE: &str = "receipt_clock";
+const RECEIPT_CLOCK_KEY: &str = "last_issued";
+
+/// `FIRST_REPORTER_CLAIM_WINDOW` in the contract
+const CLAIM_WINDOW_SECS: u64 = 3600;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionReceipt {
+    /// 0x-hex keccak256 of `abi.encode(chainId, targetAddress, threatType)`
+    pub detection_hash: String,
+    pub chain_id: u64,
+    /// Lowercased, as hashed
+    pub target_address: String,
+    pub threat_type: String,
+    /// The transaction the node saw it in
+    pub transaction_id: String,
+    pub node: Address,
+    /// Unix milliseconds; never earlier than the node's previous receipt
+    pub observed_at: u64,
+    /// personal_sign by `node` of keccak256(abi.encode(detectionHash, observedAt, node))
+    pub signature: String,
+}
+
+impl DetectionReceipt {
+    fn digest(&self) -> Result<[u8; 32]> {
+        Ok(receipt_digest(parse_hash(&self.detection_hash)?, self.observed_at, self.node))
+    }
+    
+    /// Check the hash matches the detection and the signature is the node's own
+    pub fn verify(&self) -> Result<()> {
+        let expected = detection_hash(self.chain_id, &self.target_address, &self.threat_type);
+        if parse_hash(&self.detection_hash)? != expected {
+            bail!("Receipt hash does not match its detection");
+        }
+        let signature: Signature = self.signature
+            .trim_start_matches("0x")
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Malformed receipt signature: {}", e))?;
+        let signer = signature.recover(&self.digest()?[..])?;
+        if signer != self.node {
+            bail!("Receipt for {:?} signed by {:?}", self.node, signer);
+        }
+        Ok(())
+    }
+}
+
+/// Hash identifying a detection across nodes: the same threat to the same address on one chain
+pub fn detection_hash(chain_id: u64, target_address: &str, threat_type: &str) -> [u8; 32] {
+    keccak256(encode(&[
+        Token::Uint(U256::from(chain_id)),
+        Token::String(target_address.to_lowercase()),
+        Token::String(threat_type.to_string()),
+    ]))
+}
+
+fn receipt_digest(detection_hash: [u8; 32], observed_at: u64, node: Address) -> [u8; 32] {
+    keccak256(encode(&[
+        Token::FixedBytes(detection_hash.to_vec()),
+        Token::Uint(U256::from(observed_at)),
+        Token::Address(node),
+    ]))
+}
+
+/// Hash committed on-chain with a claim over the receipt chain pinned with the evidence
+pub fn receipt_chain_hash(chain: &[DetectionReceipt]) -> Result<[u8; 32]> {
+    let digests = chain.iter().map(DetectionReceipt::digest).collect::<Result<Vec<_>>>()?;
+    Ok(keccak256(digests.concat()))
+}
+
+fn parse_hash(hash: &str) -> Result<[u8; 32]> {
+    hex::decode(hash.trim_start_matches("0x"))?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Invalid detection hash length"))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingClaim {
+    target_address: String,
+    /// The chain the claim was made on; claims stored without one were made on the configured chain
+    #[serde(default)]
+    chain_id: u64,
+    claimed_at: u64,
+}
+
+pub struct ReceiptBook {
+    config: ReceiptConfig,
+    storage: Arc<NodeStorage>,
+    blockchain: Arc<BlockchainClient>,
+    node: Address,
+    /// Last timestamp issued, so receipts stay ordered across clock steps and restarts
+    clock: parking_lot::Mutex<u64>,
+    /// Serialises read-modify-write of chains between local detections and gossip
+    writes: parking_lot::Mutex<()>,
+}
+
+impl ReceiptBook {
+    pub fn new(config: &ReceiptConfig, storage: Arc<NodeStorage>, blockchain: Arc<BlockchainClient>) -> Result<Self> {
+        let last_issued = storage.get::<u64>(RECEIPT_CLOCK_NAMESPACE, RECEIPT_CLOCK_KEY)?.unwrap_or(0);
+        Ok(Self {
+            config: config.clone(),
+            node: blockchain.wallet_address(),
+            storage,
+            blockchain,
+            clock: parking_lot::Mutex::new(last_issued),
+            writes: parking_lot::Mutex::new(()),
+        })
+    }
+    
+    /// Sign a receipt for a flagged transaction, unless this node already holds one for the detection
+    pub async fn issue(&self, transaction: &Transaction, threat_type: &ThreatClass) -> Result<Option<DetectionReceipt>> {
+        let hash = detection_hash(transaction.chain_id, &transaction.target_address, threat_type.as_str());
+        let key = format!("0x{}", hex::encode(hash));
+        if self.chain(&key)?.iter().any(|receipt| receipt.node == self.node) {
+            return Ok(None);
+        }
+        
+        let observed_at = self.tick();
+        let signature = self.blockchain.sign_message(&receipt_digest(hash, observed_at, self.node)).await?;
+        let receipt = DetectionReceipt {
+            detection_hash: key.clone(),
+            chain_id: transaction.chain_id,
+            target_address: transaction.target_address.to_lowercase(),
+            threat_type: threat_type.as_str().to_string(),
+            transaction_id: transaction.id.clone(),
+            node: self.node,
+            observed_at,
+            signature,
+        };
+        
+        let _writing = self.writes.lock();
+        let mut chain = self.chain(&key)?;
+        // Another verdict on the same detection may have signed while this one did
+        if chain.iter().any(|receipt| receipt.node == self.node) {
+            return Ok(None);
+        }
+        insert(&mut chain, receipt.clone());
+        let mut batch = self.storage.batch();
+        batch.put(RECEIPT_NAMESPACE, &key, &chain)?;
+        batch.put(RECEIPT_CLOCK_NAMESPACE, RECEIPT_CLOCK_KEY, &observed_at)?;
+        self.storage.commit(batch)?;
+        
+        debug!("🧾 Signed first-seen receipt for {} ({})", receipt.target_address, receipt.threat_type);
+        Ok(Some(receipt))
+    }
+    
+    /// Check and store a peer's receipt; returns whether it added to what this node knew
+    pub fn accept(&self, mut receipt: DetectionReceipt) -> Result<bool> {
+        receipt.verify()?;
+        // Stored under the same key this node would issue under
+        receipt.detection_hash = format!("0x{}", hex::encode(parse_hash(&receipt.detection_hash)?));
+        let now = now_millis();
+        if receipt.observed_at > now + self.config.max_clock_skew_secs * 1000 {
+            bail!("Receipt stamped {}ms ahead of this node's clock", receipt.observed_at - now);
+        }
+        if receipt.observed_at + self.retention_millis() < now {
+            return Ok(false);
+        }
+        
+        let _writing = self.writes.lock();
+        let mut chain = self.chain(&receipt.detection_hash)?;
+        // A node's earliest receipt is the one that counts
+        if chain.iter().any(|known| known.node == receipt.node && known.observed_at <= receipt.observed_at) {
+            return Ok(false);
+        }
+        let key = receipt.detection_hash.clone();
+        insert(&mut chain, receipt);
+        self.storage.put(RECEIPT_NAMESPACE, &key, &chain)?;
+        Ok(true)
+    }
+    
+    /// A detection's receipts, earliest first
+    pub fn chain(&self, detection_hash: &str) -> Result<Vec<DetectionReceipt>> {
+        Ok(self.storage.get::<Vec<DetectionReceipt>>(RECEIPT_NAMESPACE, detection_hash)?.unwrap_or_default())
+    }
+    
+    pub fn chain_for(&self, transaction: &Transaction, threat_type: &ThreatClass) -> Result<Vec<DetectionReceipt>> {
+        let hash = detection_hash(transaction.chain_id, &transaction.target_address, threat_type.as_str());
+        self.chain(&format!("0x{}", hex::encode(hash)))
+    }
+    
+    /// Claim the first-reporter reward when this node's receipt heads `chain`, the chain pinned
+    /// with the evidence at `evidence_cid`
+    pub async fn claim(&self, chain: &[DetectionReceipt], evidence_cid: Option<&str>) -> Result<()> {
+        if !self.config.claim_rewards {
+            return Ok(());
+        }
+        let Some(first) = chain.first() else {
+            return Ok(());
+        };
+        if first.node != self.node {
+            debug!("🥈 {:?} saw {} first, not claiming", first.node, first.target_address);
+            return Ok(());
+        }
+        if self.storage.get::<PendingClaim>(RECEIPT_CLAIM_NAMESPACE, &first.detection_hash)?.is_some() {
+            return Ok(());
+        }
+        
+        let tx_hash = self.blockchain.claim_first_reporter(
+            first.chain_id,
+            parse_hash(&first.detection_hash)?,
+            first.observed_at,
+            &first.signature,
+            receipt_chain_hash(chain)?,
+            evidence_cid.unwrap_or_default(),
+        ).await?;
+        info!("🥇 Claimed first report of {} ({}) with {} receipts: {}",
+              first.target_address, first.threat_type, chain.len(), tx_hash);
+        
+        self.storage.put(RECEIPT_CLAIM_NAMESPACE, &first.detection_hash, &PendingClaim {
+            target_address: first.target_address.clone(),
+            chain_id: first.chain_id,
+            claimed_at: chrono::Utc::now().timestamp() as u64,
+        })?;
+        Ok(())
+    }
+    
+    /// Prune expired receipt chains and settle claims whose window has closed
+    pub async fn start(&self) -> Result<()> {
+        let mut sweeps = tokio::time::interval(Duration::from_secs(self.config.sweep_interval_secs.max(60)));
+        loop {
+            sweeps.tick().await;
+            if let Err(e) = self.prune() {
+                warn!("⚠️ Failed to prune receipt chains: {:#}", e);
+            }
+            if let Err(e) = self.settle_claims().await {
+                warn!("⚠️ Failed to settle first-reporter claims: {:#}", e);
+            }
+        }
+    }
+    
+    fn prune(&self) -> Result<()> {
+        let cutoff = now_millis().saturating_sub(self.retention_millis());
+        let mut batch = self.storage.batch();
+        let mut pruned = 0;
+        for (key, chain) in self.storage.scan::<Vec<DetectionReceipt>>(RECEIPT_NAMESPACE)? {
+            if chain.first().is_none_or(|first| first.observed_at < cutoff) {
+                batch.delete(RECEIPT_NAMESPACE, &key);
+                pruned += 1;
+            }
+        }
+        if pruned > 0 {
+            self.storage.commit(batch)?;
+            debug!("🧾 Pruned {} expired receipt chains", pruned);
+        }
+        Ok(())
+    }
+    
+    async fn settle_claims(&self) -> Result<()> {
+        let now = chrono::Utc::now().timestamp() as u64;
+        for (key, claim) in self.storage.scan::<PendingClaim>(RECEIPT_CLAIM_NAMESPACE)? {
+            if now < claim.claimed_at + CLAIM_WINDOW_SECS {
+                continue;
+            }
+            // Settlement pays whoever holds the claim by then, which may be a peer that superseded it
+            match self.blockchain.settle_first_reporter(claim.chain_id, parse_hash(&key)?).await {
+                Ok(tx_hash) => {
+                    info!("🏅 Settled first-reporter claim for {}: {}", claim.target_address, tx_hash);
+                    self.storage.delete(RECEIPT_CLAIM_NAMESPACE, &key)?;
+                }
+                Err(e) if now > claim.claimed_at + self.config.retained_days * 86_400 => {
+                    warn!("⚠️ Giving up on first-reporter claim for {}: {:#}", claim.target_address, e);
+                    self.storage.delete(RECEIPT_CLAIM_NAMESPACE, &key)?;
+                }
+                Err(e) => debug!("First-reporter claim for {} not settled yet: {:#}", claim.target_address, e),
+            }
+        }
+        Ok(())
+    }
+    
+    /// Next receipt timestamp: the wall clock, or just past the last one issued if it stepped back
+    fn tick(&self) -> u64 {
+        let mut last = self.clock.lock();
+        *last = now_millis().max(*last + 1);
+        *last
+    }
+    
+    fn retention_millis(&self) -> u64 {
+        self.config.retained_days.saturating_mul(86_400_000)
+    }
+}
+
+/// Add a receipt, replacing any later one from the same node, keeping the chain earliest first
+fn insert(chain: &mut Vec<DetectionReceipt>, receipt: DetectionReceipt) {
+    chain.retain(|known| known.node != receipt.node);
+    chain.push(receipt);
+    chain.sort_by_key(|known| (known.observed_at, known.node));
+}
+
+fn now_millis() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
+}
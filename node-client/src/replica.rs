@@ -0,0 +1,189 @@
+//! Read replicas: nodes that mirror a primary's storage and serve the query APIs from it
+//!
+//! The primary serves snapshots of its shareable namespaces on the metrics port. A replica
+//! pulls one every `sync_interval_secs`, swaps it into its own storage and reloads its
+//! in-memory indexes; it never runs detection or talks to the chain.
+
+use anyhow::{anyhow, bail, Result};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use prometheus::{IntCounterVec, IntGauge, Opts};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use crate::ai::graph::ADDRESS_GRAPH_NAMESPACE;
+use crate::ai::THREAT_PATTERN_NAMESPACE;
+use crate::alert_cache::{VerifiedAlertCache, VERIFIED_ALERT_NAMESPACE};
+use crate::blockchain::THREAT_ALERT_NAMESPACE;
+use crate::config::ReplicationConfig;
+use crate::history::ReportHistory;
+use crate::metrics::register_once;
+use crate::node::{REPORT_PIPELINE_NAMESPACE, THREAT_REPORT_NAMESPACE};
+use crate::storage::NodeStorage;
+use crate::watchlist::WATCHLIST_NAMESPACE;
+
+/// Namespaces shipped to replicas; cursors, billing, update and rollback state stay node-local
+pub const REPLICATED_NAMESPACES: &[&str] = &[
+    THREAT_REPORT_NAMESPACE,
+    REPORT_PIPELINE_NAMESPACE,
+    THREAT_ALERT_NAMESPACE,
+    VERIFIED_ALERT_NAMESPACE,
+    WATCHLIST_NAMESPACE,
+    THREAT_PATTERN_NAMESPACE,
+    ADDRESS_GRAPH_NAMESPACE,
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StorageSnapshot {
+    pub taken_at: u64,
+    /// Raw storage keys and values, as exported by the primary
+    pub entries: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+/// `GET /replication/snapshot`: the replicated namespaces as a bincode-encoded [`StorageSnapshot`]
+pub fn snapshot_routes(storage: Arc<NodeStorage>, auth_token: String) -> Router {
+    Router::new().route(
+        "/replication/snapshot",
+        get(move |headers: HeaderMap| async move {
+            if !authorized(&headers, &auth_token) {
+                return StatusCode::UNAUTHORIZED.into_response();
+            }
+            
+            let snapshot = tokio::task::spawn_blocking(move || -> Result<Vec<u8>> {
+                let snapshot = StorageSnapshot {
+                    taken_at: now_secs(),
+                    entries: storage.export_namespaces(REPLICATED_NAMESPACES)?,
+                };
+                Ok(bincode::serialize(&snapshot)?)
+            })
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result);
+            
+            match snapshot {
+                Ok(bytes) => ([(header::CONTENT_TYPE, "application/octet-stream")], bytes).into_response(),
+                Err(e) => {
+                    error!("Failed to export storage snapshot: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            }
+        }),
+    )
+}
+
+fn authorized(headers: &HeaderMap, auth_token: &str) -> bool {
+    let presented = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    // Compared as hashes so the check runs in constant time
+    match presented {
+        Some(token) if !auth_token.is_empty() => blake3::hash(token.as_bytes()) == blake3::hash(auth_token.as_bytes()),
+        _ => false,
+    }
+}
+
+/// Keeps a replica's storage in step with its primary
+pub struct ReplicaSync {
+    config: ReplicationConfig,
+    client: reqwest::Client,
+    storage: Arc<NodeStorage>,
+    alert_cache: Arc<VerifiedAlertCache>,
+    history: Arc<ReportHistory>,
+    syncs: IntCounterVec,
+    snapshot_taken_at: IntGauge,
+}
+
+impl ReplicaSync {
+    pub fn new(
+        config: &ReplicationConfig,
+        storage: Arc<NodeStorage>,
+        alert_cache: Arc<VerifiedAlertCache>,
+        history: Arc<ReportHistory>,
+    ) -> Result<Self> {
+        if config.auth_token.is_empty() {
+            bail!("Read replicas need replication.auth_token to fetch snapshots");
+        }
+        
+        static SYNCS: OnceLock<IntCounterVec> = OnceLock::new();
+        static SNAPSHOT_TAKEN_AT: OnceLock<IntGauge> = OnceLock::new();
+        let syncs = register_once(&SYNCS, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_replica_syncs_total", "Storage snapshots pulled from the primary"),
+                &["result"],
+            )
+        })?;
+        let snapshot_taken_at = register_once(&SNAPSHOT_TAKEN_AT, || {
+            IntGauge::new(
+                "dagshield_replica_snapshot_taken_at_seconds",
+                "Unix time the primary took the snapshot this replica serves",
+            )
+        })?;
+        
+        Ok(Self {
+            config: config.clone(),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(300))
+                .build()?,
+            storage,
+            alert_cache,
+            history,
+            syncs,
+            snapshot_taken_at,
+        })
+    }
+    
+    pub async fn start(&self) -> Result<()> {
+        info!("🪞 Replicating {} every {}s", self.config.primary_url, self.config.sync_interval_secs);
+        
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.sync_interval_secs.max(10)));
+        loop {
+            interval.tick().await;
+            
+            match self.sync().await {
+                Ok(entries) => {
+                    self.syncs.with_label_values(&["ok"]).inc();
+                    debug!("🪞 Synced {} entries from the primary", entries);
+                }
+                Err(e) => {
+                    self.syncs.with_label_values(&["error"]).inc();
+                    warn!("Replica sync failed, serving the previous snapshot: {}", e);
+                }
+            }
+        }
+    }
+    
+    async fn sync(&self) -> Result<usize> {
+        let bytes = self.client
+            .get(format!("{}/replication/snapshot", self.config.primary_url.trim_end_matches('/')))
+            .bearer_auth(&self.config.auth_token)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        
+        let storage = Arc::clone(&self.storage);
+        let (taken_at, imported) = tokio::task::spawn_blocking(move || -> Result<(u64, usize)> {
+            let snapshot: StorageSnapshot = bincode::deserialize(&bytes)
+                .map_err(|e| anyhow!("Malformed storage snapshot: {}", e))?;
+            let imported = storage.import_namespaces(REPLICATED_NAMESPACES, snapshot.entries)?;
+            Ok((snapshot.taken_at, imported))
+        })
+        .await??;
+        
+        self.alert_cache.reload()?;
+        self.history.reload()?;
+        self.snapshot_taken_at.set(taken_at as i64);
+        
+        Ok(imported)
+    }
+}
+
+fn now_secs() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
@@ -0,0 +1,314 @@
+//! Threat reports waiting to go on-chain
+//!
+//! A flagged result that cannot be reported right away, because reporting is paused, the chain is
+//! unreachable or the submission failed, is queued in `NodeStorage` so outages and restarts do not
+//! drop it. Reports are keyed by threat type, target and chain within a `dedup_window_secs` window
+//! of the transaction's time: the same threat seen again while its first report waits, e.g. when
+//! it is re-ingested after a restart, is queued once. Keys start with the window, so the queue
+//! drains oldest first.
+//!
+//! A report whose submission fails is retried after `initial_backoff_secs`, doubling with every
+//! further failure up to `max_backoff_secs`, so one that keeps failing does not hold up the rest.
+//! One the contract reverted would revert again, and one still failing after `max_attempts` is not
+//! getting through: both move to a dead-letter list in `NodeStorage` for the operator instead.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::ai::ThreatDetectionResult;
+use crate::blockchain::is_reverted;
+use crate::config::ReportQueueConfig;
+use crate::dag::Transaction;
+use crate::storage::NodeStorage;
+
+/// Namespace in `NodeStorage` holding queued reports, keyed by [`ReportQueue::key`]
+pub const DEFERRED_REPORT_NAMESPACE: &str = "deferred_reports";
+/// Namespace in `NodeStorage` holding reports given up on, under their queue keys
+pub const DEAD_REPORT_NAMESPACE: &str = "dead_reports";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeferredReport {
+    pub transaction: Transaction,
+    pub result: ThreatDetectionResult,
+    /// Failed submissions so far
+    #[serde(default)]
+    pub attempts: u32,
+    /// Unix seconds before which the report is not retried
+    #[serde(default)]
+    pub retry_at: u64,
+}
+
+impl DeferredReport {
+    pub fn new(transaction: Transaction, result: ThreatDetectionResult) -> Self {
+        Self {
+            transaction,
+            result,
+            attempts: 0,
+            retry_at: 0,
+        }
+    }
+}
+
+/// A report taken off the queue without being submitted
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeadReport {
+    pub report: DeferredReport,
+    /// Why the last submission failed
+    pub error: String,
+    /// Unix seconds
+    pub dead_at: u64,
+}
+
+/// What became of a report whose submission failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// Queued again, to be retried after this long
+    Retry(Duration),
+    /// Moved to the dead-letter list
+    Dead,
+}
+
+pub struct ReportQueue {
+    config: ReportQueueConfig,
+    storage: Arc<NodeStorage>,
+}
+
+impl ReportQueue {
+    pub fn new(config: &ReportQueueConfig, storage: Arc<NodeStorage>) -> Self {
+        Self {
+            config: config.clone(),
+            storage,
+        }
+    }
+    
+    /// Shared by every report of the same threat on the same target and chain within a window
+    pub fn key(&self, transaction: &Transaction, result: &ThreatDetectionResult) -> String {
+        let window_secs = self.config.dedup_window_secs.max(1);
+        format!(
+            "{:020}-{}-{}-{}",
+            transaction.timestamp / window_secs * window_secs,
+            transaction.chain_id,
+            result.threat_type,
+            transaction.target_address.to_lowercase(),
+        )
+    }
+    
+    /// Queue a report unless the same threat is already waiting; false for a duplicate
+    pub fn push(&self, transaction: &Transaction, result: &ThreatDetectionResult) -> Result<bool> {
+        let key = self.key(transaction, result);
+        if self.storage.get::<DeferredReport>(DEFERRED_REPORT_NAMESPACE, &key)?.is_some() {
+            return Ok(false);
+        }
+        self.storage.put(DEFERRED_REPORT_NAMESPACE, &key, &DeferredReport::new(transaction.clone(), result.clone()))?;
+        Ok(true)
+    }
+    
+    /// Reports whose backoff has passed, oldest first
+    pub fn due(&self) -> Result<Vec<(String, DeferredReport)>> {
+        let now = chrono::Utc::now().timestamp() as u64;
+        Ok(self
+            .storage
+            .scan::<DeferredReport>(DEFERRED_REPORT_NAMESPACE)?
+            .into_iter()
+            .filter(|(_, report)| report.retry_at <= now)
+            .collect())
+    }
+    
+    pub fn submitted(&self, key: &str) -> Result<()> {
+        self.storage.delete(DEFERRED_REPORT_NAMESPACE, key)
+    }
+    
+    /// Put a report back after a failed submission, or move it to the dead-letter list once it
+    /// reverted or has used up `max_attempts`
+    pub fn failed(&self, key: &str, mut report: DeferredReport, error: &anyhow::Error) -> Result<Failure> {
+        report.attempts += 1;
+        let now = chrono::Utc::now().timestamp() as u64;
+        if is_reverted(error) || report.attempts >= self.config.max_attempts {
+            let mut batch = self.storage.batch();
+            batch.delete(DEFERRED_REPORT_NAMESPACE, key);
+            batch.put(DEAD_REPORT_NAMESPACE, key, &DeadReport {
+                report,
+                error: format!("{:#}", error),
+                dead_at: now,
+            })?;
+            self.storage.commit(batch)?;
+            return Ok(Failure::Dead);
+        }
+
+        let backoff = self
+            .config
+            .initial_backoff_secs
+            .saturating_mul(1u64 << (report.attempts - 1).min(32))
+            .min(self.config.max_backoff_secs);
+        report.retry_at = now + backoff;
+        self.storage.put(DEFERRED_REPORT_NAMESPACE, key, &report)?;
+        Ok(Failure::Retry(Duration::from_secs(backoff)))
+    }
+
+    /// Reports given up on, oldest first
+    pub fn dead(&self) -> Result<Vec<(String, DeadReport)>> {
+        self.storage.scan(DEAD_REPORT_NAMESPACE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::Reverted;
+    use crate::config::StorageConfig;
+    use crate::threat::ThreatClass;
+    use ethers::types::{TxHash, U256};
+
+    async fn storage(dir: &tempfile::TempDir) -> Arc<NodeStorage> {
+        let config = StorageConfig {
+            data_dir: dir.path().to_string_lossy().into_owned(),
+            max_db_size_gb: 1,
+            backup_interval_hours: 0,
+        };
+        Arc::new(NodeStorage::new(&config).await.unwrap())
+    }
+
+    fn queue(storage: &Arc<NodeStorage>) -> ReportQueue {
+        let config = ReportQueueConfig {
+            initial_backoff_secs: 30,
+            max_backoff_secs: 100,
+            max_attempts: 4,
+            ..ReportQueueConfig::default()
+        };
+        ReportQueue::new(&config, Arc::clone(storage))
+    }
+
+    fn flagged(target: &str) -> (Transaction, ThreatDetectionResult) {
+        let transaction = Transaction {
+            id: format!("0x{}", target),
+            from: "0xsender".to_string(),
+            to: target.to_string(),
+            target_address: target.to_string(),
+            chain_id: 1,
+            data: Vec::new(),
+            timestamp: 1_700_000_000,
+            dependencies: Vec::new(),
+            blob_versioned_hashes: Vec::new(),
+            value: U256::zero(),
+            logs: Vec::new(),
+            origin: None,
+            nonce: None,
+            storage_slots: Vec::new(),
+            priority_fee: U256::zero(),
+            peer_synced: false,
+        };
+        let result = ThreatDetectionResult {
+            threat_type: ThreatClass::Phishing,
+            confidence: 0.9,
+            risk_score: 90,
+            explanation: String::new(),
+            recommended_action: String::new(),
+            ensemble: None,
+            approval: None,
+        };
+        (transaction, result)
+    }
+
+    /// Push a report and take it straight back off the queue, as a submission would
+    fn next(queue: &ReportQueue, target: &str) -> (String, DeferredReport) {
+        let (transaction, result) = flagged(target);
+        queue.push(&transaction, &result).unwrap();
+        let key = queue.key(&transaction, &result);
+        let report = queue.storage.get(DEFERRED_REPORT_NAMESPACE, &key).unwrap().unwrap();
+        (key, report)
+    }
+
+    fn stored(queue: &ReportQueue, key: &str) -> DeferredReport {
+        queue.storage.get(DEFERRED_REPORT_NAMESPACE, key).unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn backoff_doubles_up_to_the_maximum() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = queue(&storage(&dir).await);
+        let (key, mut report) = next(&queue, "0xtarget");
+        let timeout = anyhow::anyhow!("request timed out");
+
+        let mut waits = Vec::new();
+        for _ in 0..3 {
+            waits.push(queue.failed(&key, report, &timeout).unwrap());
+            report = stored(&queue, &key);
+        }
+        assert_eq!(
+            waits,
+            [30, 60, 100].map(|secs| Failure::Retry(Duration::from_secs(secs)))
+        );
+        assert_eq!(report.attempts, 3);
+        assert!(report.retry_at > chrono::Utc::now().timestamp() as u64);
+        assert!(queue.due().unwrap().is_empty(), "a backed-off report is not due");
+    }
+
+    #[tokio::test]
+    async fn reports_move_to_the_dead_letter_list_after_max_attempts() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = queue(&storage(&dir).await);
+        let (key, mut report) = next(&queue, "0xtarget");
+        let timeout = anyhow::anyhow!("request timed out");
+
+        for _ in 0..3 {
+            assert!(matches!(queue.failed(&key, report, &timeout).unwrap(), Failure::Retry(_)));
+            report = stored(&queue, &key);
+        }
+        assert_eq!(queue.failed(&key, report, &timeout).unwrap(), Failure::Dead);
+
+        assert!(queue.storage.get::<DeferredReport>(DEFERRED_REPORT_NAMESPACE, &key).unwrap().is_none());
+        let dead = queue.dead().unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].0, key);
+        assert_eq!(dead[0].1.report.attempts, 4);
+        assert_eq!(dead[0].1.error, "request timed out");
+    }
+
+    #[tokio::test]
+    async fn reverted_reports_are_not_retried() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = queue(&storage(&dir).await);
+        let (key, report) = next(&queue, "0xtarget");
+        let reverted = anyhow::Error::new(Reverted {
+            tx_hash: TxHash::zero(),
+            chain_id: 1,
+            block: 7,
+        })
+        .context("Failed to report the threat");
+
+        assert_eq!(queue.failed(&key, report, &reverted).unwrap(), Failure::Dead);
+        assert!(queue.due().unwrap().is_empty());
+        assert_eq!(queue.dead().unwrap()[0].1.report.attempts, 1);
+
+        let (key, report) = next(&queue, "0xother");
+        let rejected = anyhow::anyhow!("(code: 3, message: execution reverted: already reported)");
+        assert_eq!(queue.failed(&key, report, &rejected).unwrap(), Failure::Dead);
+        assert_eq!(queue.dead().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn queued_reports_survive_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let (failed_key, due_key) = {
+            let storage = storage(&dir).await;
+            let queue = queue(&storage);
+            let (failed_key, report) = next(&queue, "0xfailed");
+            queue.failed(&failed_key, report, &anyhow::anyhow!("connection refused")).unwrap();
+            let (due_key, _) = next(&queue, "0xdue");
+            storage.flush().await.unwrap();
+            (failed_key, due_key)
+        };
+
+        let queue = queue(&storage(&dir).await);
+        let due: Vec<String> = queue.due().unwrap().into_iter().map(|(key, _)| key).collect();
+        assert_eq!(due, [due_key]);
+        let report = stored(&queue, &failed_key);
+        assert_eq!(report.attempts, 1);
+        assert!(report.retry_at > chrono::Utc::now().timestamp() as u64);
+
+        let (transaction, result) = flagged("0xfailed");
+        assert!(!queue.push(&transaction, &result).unwrap(), "still queued, so not queued twice");
+    }
+}
@@ -0,0 +1,373 @@
+//! Detection-rate guard against a misbehaving model draining the wallet with reports
+//!
+//! Every verdict is counted in a rolling window of `window_secs`. Normal windows feed a baseline
+//! of the last `baseline_windows` flag rates; once a window has `min_verdicts` verdicts and flags
+//! more than `trip_multiplier` times the baseline, automatic on-chain reporting is suspended.
+//! Flagged transactions then queue for review, together with the reports that were waiting for
+//! the chain, and the operator is alerted. Reports the operator approves go out with the next
+//! heartbeat; reporting resumes only when the operator says so. The suspension and the baseline
+//! survive restarts.
+
+use anyhow::Result;
+use axum::{http::StatusCode, response::IntoResponse, routing::{get, post}, Json, Router};
+use prometheus::{Gauge, IntGauge};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+use crate::ai::ThreatDetectionResult;
+use crate::config::ReportingGuardConfig;
+use crate::dag::Transaction;
+use crate::metrics::register_once;
+use crate::report_queue::{DeferredReport, ReportQueue, DEFERRED_REPORT_NAMESPACE};
+use crate::status::Report;
+use crate::storage::NodeStorage;
+use crate::threat::ThreatClass;
+
+/// Flagged results held for the operator while auto-reporting is suspended, keyed by transaction id
+pub const REVIEW_NAMESPACE: &str = "review_reports";
+const GUARD_NAMESPACE: &str = "reporting_guard";
+const GUARD_KEY: &str = "state";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Suspension {
+    pub since: u64,
+    /// Flag rate of the window that tripped the guard
+    pub rate: f64,
+    pub baseline: f64,
+    pub verdicts: u64,
+    pub flagged: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct GuardState {
+    /// Flag rates of recent normal windows, oldest first
+    baseline: VecDeque<f64>,
+    suspension: Option<Suspension>,
+}
+
+struct Window {
+    started: Instant,
+    verdicts: u64,
+    flagged: u64,
+}
+
+impl Window {
+    fn new() -> Self {
+        Self { started: Instant::now(), verdicts: 0, flagged: 0 }
+    }
+    
+    fn rate(&self) -> f64 {
+        if self.verdicts == 0 {
+            0.0
+        } else {
+            self.flagged as f64 / self.verdicts as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeldReport {
+    pub transaction_id: String,
+    pub target_address: String,
+    pub chain_id: u64,
+    pub threat_type: ThreatClass,
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardStatus {
+    pub suspension: Option<Suspension>,
+    pub window_verdicts: u64,
+    pub window_rate: f64,
+    /// `None` until a first normal window completes; the guard does not trip before
+    pub baseline_rate: Option<f64>,
+    pub held: Vec<HeldReport>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReviewRequest {
+    /// Held reports to decide on; every held report when empty
+    pub transaction_ids: Vec<String>,
+    /// Submit them on-chain, rather than discard them
+    pub approve: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewOutcome {
+    pub approved: usize,
+    pub discarded: usize,
+    /// Still held for review
+    pub held: usize,
+}
+
+pub struct ReportingGuard {
+    config: ReportingGuardConfig,
+    storage: Arc<NodeStorage>,
+    /// Approved reports go back into it
+    queue: Arc<ReportQueue>,
+    state: parking_lot::Mutex<(GuardState, Window)>,
+    suspended: AtomicBool,
+    http: reqwest::Client,
+    suspended_gauge: IntGauge,
+    rate_gauge: Gauge,
+    baseline_gauge: Gauge,
+}
+
+impl ReportingGuard {
+    pub fn new(config: &ReportingGuardConfig, storage: Arc<NodeStorage>, queue: Arc<ReportQueue>) -> Result<Self> {
+        let state: GuardState = storage.get(GUARD_NAMESPACE, GUARD_KEY)?.unwrap_or_default();
+        if let Some(suspension) = &state.suspension {
+            warn!("⚠️ Auto-reporting is still suspended since {}: flag rate {:.1}% against a {:.1}% baseline",
+                  suspension.since, suspension.rate * 100.0, suspension.baseline * 100.0);
+        }
+        
+        static SUSPENDED_GAUGE: OnceLock<IntGauge> = OnceLock::new();
+        static RATE_GAUGE: OnceLock<Gauge> = OnceLock::new();
+        static BASELINE_GAUGE: OnceLock<Gauge> = OnceLock::new();
+        let suspended_gauge = register_once(&SUSPENDED_GAUGE, || {
+            IntGauge::new("dagshield_reporting_suspended", "Whether the detection-rate guard suspended auto-reporting (1) or not (0)")
+        })?;
+        let rate_gauge = register_once(&RATE_GAUGE, || {
+            Gauge::new("dagshield_detection_flag_rate", "Share of verdicts flagged in the guard's current window")
+        })?;
+        let baseline_gauge = register_once(&BASELINE_GAUGE, || {
+            Gauge::new("dagshield_detection_flag_rate_baseline", "Flag rate the detection-rate guard compares against")
+        })?;
+        suspended_gauge.set(state.suspension.is_some() as i64);
+        
+        let guard = Self {
+            config: config.clone(),
+            storage,
+            queue,
+            suspended: AtomicBool::new(state.suspension.is_some()),
+            state: parking_lot::Mutex::new((state, Window::new())),
+            http: reqwest::Client::builder().timeout(Duration::from_secs(10)).build()?,
+            suspended_gauge,
+            rate_gauge,
+            baseline_gauge,
+        };
+        if let Some(baseline) = guard.baseline(&guard.state.lock().0) {
+            guard.baseline_gauge.set(baseline);
+        }
+        Ok(guard)
+    }
+    
+    pub fn is_suspended(&self) -> bool {
+        self.suspended.load(Ordering::SeqCst)
+    }
+    
+    /// Count a verdict, suspending auto-reporting when the window's flag rate runs away
+    pub fn record(&self, flagged: bool) {
+        let tripped = {
+            let mut guard = self.state.lock();
+            let (state, window) = &mut *guard;
+            if window.started.elapsed() >= Duration::from_secs(self.config.window_secs.max(1)) {
+                self.roll(state, window);
+            }
+            window.verdicts += 1;
+            if flagged {
+                window.flagged += 1;
+            }
+            self.rate_gauge.set(window.rate());
+            
+            match self.baseline(state) {
+                Some(baseline) if state.suspension.is_none()
+                    && window.verdicts >= self.config.min_verdicts
+                    && window.rate() > baseline * self.config.trip_multiplier => {
+                    let suspension = Suspension {
+                        since: chrono::Utc::now().timestamp() as u64,
+                        rate: window.rate(),
+                        baseline,
+                        verdicts: window.verdicts,
+                        flagged: window.flagged,
+                    };
+                    state.suspension = Some(suspension.clone());
+                    self.suspended.store(true, Ordering::SeqCst);
+                    self.persist(state);
+                    Some(suspension)
+                }
+                _ => None,
+            }
+        };
+        
+        if let Some(suspension) = tripped {
+            self.suspended_gauge.set(1);
+            error!("🛑 {:.1}% of the last {} verdicts flagged, {:.1}x the {:.1}% baseline: auto-reporting suspended, \
+                    flagged transactions are held for review", suspension.rate * 100.0, suspension.verdicts,
+                   suspension.rate / suspension.baseline, suspension.baseline * 100.0);
+            if let Err(e) = self.hold_deferred() {
+                warn!("⚠️ Failed to hold queued reports for review: {:#}", e);
+            }
+            self.alert(suspension);
+        }
+    }
+    
+    /// Close the window, adding its rate to the baseline when it was a normal one
+    fn roll(&self, state: &mut GuardState, window: &mut Window) {
+        let normal = state.suspension.is_none()
+            && window.verdicts >= self.config.min_verdicts
+            && self.baseline(state).is_none_or(|baseline| window.rate() <= baseline * self.config.trip_multiplier);
+        if normal {
+            state.baseline.push_back(window.rate());
+            while state.baseline.len() > self.config.baseline_windows.max(1) {
+                state.baseline.pop_front();
+            }
+            if let Some(baseline) = self.baseline(state) {
+                self.baseline_gauge.set(baseline);
+            }
+            self.persist(state);
+        }
+        *window = Window::new();
+    }
+    
+    fn baseline(&self, state: &GuardState) -> Option<f64> {
+        if state.baseline.is_empty() {
+            return None;
+        }
+        let mean = state.baseline.iter().sum::<f64>() / state.baseline.len() as f64;
+        Some(mean.max(self.config.min_baseline_rate))
+    }
+    
+    fn persist(&self, state: &GuardState) {
+        if let Err(e) = self.storage.put(GUARD_NAMESPACE, GUARD_KEY, state) {
+            warn!("⚠️ Failed to store the reporting guard state: {:#}", e);
+        }
+    }
+    
+    /// Queue a flagged result for the operator instead of reporting it
+    pub fn hold(&self, transaction: &Transaction, result: &ThreatDetectionResult) -> Result<()> {
+        self.storage.put(REVIEW_NAMESPACE, &transaction.id, &DeferredReport::new(transaction.clone(), result.clone()))
+    }
+    
+    /// Reports waiting for the chain came from the same model; they wait for review too
+    fn hold_deferred(&self) -> Result<()> {
+        let deferred = self.storage.scan::<DeferredReport>(DEFERRED_REPORT_NAMESPACE)?;
+        if deferred.is_empty() {
+            return Ok(());
+        }
+        let mut batch = self.storage.batch();
+        for (key, report) in &deferred {
+            batch.put(REVIEW_NAMESPACE, &report.transaction.id, report)?;
+            batch.delete(DEFERRED_REPORT_NAMESPACE, key);
+        }
+        self.storage.commit(batch)?;
+        info!("📥 Holding {} queued reports for review", deferred.len());
+        Ok(())
+    }
+    
+    fn alert(&self, suspension: Suspension) {
+        let Some(url) = self.config.webhook_url.clone() else {
+            return;
+        };
+        let http = self.http.clone();
+        tokio::spawn(async move {
+            let result = http
+                .post(&url)
+                .json(&serde_json::json!({ "event": "reporting_suspended", "suspension": suspension }))
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(e) = result {
+                error!("❌ Reporting guard alert delivery failed: {}", e.without_url());
+            }
+        });
+    }
+    
+    /// Lift the suspension; the baseline is kept, the current window starts over
+    pub fn resume(&self) -> Option<Suspension> {
+        let mut guard = self.state.lock();
+        let (state, window) = &mut *guard;
+        let lifted = state.suspension.take();
+        *window = Window::new();
+        self.persist(state);
+        self.suspended.store(false, Ordering::SeqCst);
+        self.suspended_gauge.set(0);
+        if lifted.is_some() {
+            info!("▶️ Auto-reporting resumed by the operator");
+        }
+        lifted
+    }
+    
+    /// Approve held reports into the deferred queue, or discard them
+    pub fn review(&self, request: &ReviewRequest) -> Result<ReviewOutcome> {
+        let held = self.storage.scan::<DeferredReport>(REVIEW_NAMESPACE)?;
+        let total = held.len();
+        let mut batch = self.storage.batch();
+        let mut decided = 0;
+        for (key, report) in held {
+            if !request.transaction_ids.is_empty() && !request.transaction_ids.contains(&key) {
+                continue;
+            }
+            if request.approve {
+                let queue_key = self.queue.key(&report.transaction, &report.result);
+                batch.put(DEFERRED_REPORT_NAMESPACE, &queue_key, &DeferredReport::new(report.transaction, report.result))?;
+            }
+            batch.delete(REVIEW_NAMESPACE, &key);
+            decided += 1;
+        }
+        self.storage.commit(batch)?;
+        
+        let outcome = ReviewOutcome {
+            approved: if request.approve { decided } else { 0 },
+            discarded: if request.approve { 0 } else { decided },
+            held: total - decided,
+        };
+        info!("🧾 Review: {} held reports approved, {} discarded, {} still held",
+              outcome.approved, outcome.discarded, outcome.held);
+        Ok(outcome)
+    }
+    
+    pub fn status(&self) -> Result<GuardStatus> {
+        let held = self.storage
+            .scan::<DeferredReport>(REVIEW_NAMESPACE)?
+            .into_iter()
+            .map(|(_, report)| HeldReport {
+                transaction_id: report.transaction.id,
+                target_address: report.transaction.target_address,
+                chain_id: report.transaction.chain_id,
+                threat_type: report.result.threat_type,
+                confidence: report.result.confidence,
+            })
+            .collect();
+        let guard = self.state.lock();
+        let (state, window) = &*guard;
+        Ok(GuardStatus {
+            suspension: state.suspension.clone(),
+            window_verdicts: window.verdicts,
+            window_rate: window.rate(),
+            baseline_rate: self.baseline(state),
+            held,
+        })
+    }
+}
+
+/// `GET /reporting/guard`, `POST /reporting/guard/resume` and `POST /reporting/review`
+pub fn admin_routes(guard: Arc<ReportingGuard>) -> Router {
+    let status = Arc::clone(&guard);
+    let resume = Arc::clone(&guard);
+    Router::new()
+        .route("/reporting/guard", get(move || async move {
+            match status.status() {
+                Ok(status) => Json(Report::new("reporting_guard", status)).into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)).into_response(),
+            }
+        }))
+        .route("/reporting/guard/resume", post(move || async move {
+            resume.resume();
+            match resume.status() {
+                Ok(status) => Json(Report::new("reporting_guard", status)).into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)).into_response(),
+            }
+        }))
+        .route("/reporting/review", post(move |Json(request): Json<ReviewRequest>| async move {
+            match guard.review(&request) {
+                Ok(outcome) => Json(Report::new("review", outcome)).into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)).into_response(),
+            }
+        }))
+}
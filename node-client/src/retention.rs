@@ -0,0 +1,273 @@
+//! Scheduled janitor enforcing how long each category of data is kept
+//!
+//! Each category has its own retention in days, unset meaning kept forever, so a small-disk
+//! edge device can keep a week of everything while an enterprise operator keeps the audit log
+//! for years. A sweep can be previewed as a dry run, which counts what would go without
+//! touching anything. With an archiver attached, expiring reports and their evidence are exported
+//! to cold storage first, and nothing is expired when the export fails.
+
+use anyhow::{Context, Result};
+use axum::{http::StatusCode, response::IntoResponse, routing::{get, post}, Json, Router};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::archive::{Archiver, ARCHIVED_NAMESPACE};
+use crate::audit::AuditLog;
+use crate::config::RetentionConfig;
+use crate::energy::{EnergyMetrics, ENERGY_HISTORY_NAMESPACE};
+use crate::history::ReportHistory;
+use crate::ipfs::IpfsClient;
+use crate::node::{ThreatReportRecord, REPORT_PIPELINE_NAMESPACE, THREAT_REPORT_NAMESPACE};
+use crate::status::Report;
+use crate::storage::NodeStorage;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataCategory {
+    /// Threat reports this node submitted
+    Detections,
+    /// Evidence bundles this node pinned on IPFS
+    Evidence,
+    /// The energy monitor's stored readings
+    Energy,
+    /// The audit log, compacted so the counters rebuilt from it survive
+    Audit,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategorySweep {
+    pub category: DataCategory,
+    /// `None` when the category is kept forever
+    pub retention_days: Option<u64>,
+    /// Unix time before which entries expire
+    pub cutoff: Option<u64>,
+    /// Entries removed, or on a dry run that would be
+    pub expired: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    pub ran_at: u64,
+    pub sweeps: Vec<CategorySweep>,
+    /// Expiring reports exported to the archive, or on a dry run that would be
+    pub archived: usize,
+}
+
+pub struct RetentionJanitor {
+    config: RetentionConfig,
+    storage: Arc<NodeStorage>,
+    /// Unpins expired evidence; without it evidence pins are left alone
+    ipfs: Option<Arc<IpfsClient>>,
+    history: Arc<ReportHistory>,
+    audit_log: Arc<AuditLog>,
+    archiver: OnceLock<Arc<Archiver>>,
+    /// One sweep at a time, whether scheduled or requested
+    sweeping: tokio::sync::Mutex<()>,
+}
+
+impl RetentionJanitor {
+    pub fn new(
+        config: &RetentionConfig,
+        storage: Arc<NodeStorage>,
+        ipfs: Option<Arc<IpfsClient>>,
+        history: Arc<ReportHistory>,
+        audit_log: Arc<AuditLog>,
+    ) -> Self {
+        Self {
+            config: config.clone(),
+            storage,
+            ipfs,
+            history,
+            audit_log,
+            archiver: OnceLock::new(),
+            sweeping: tokio::sync::Mutex::new(()),
+        }
+    }
+    
+    /// Archive expiring reports before they are expired
+    pub fn attach_archiver(&self, archiver: Arc<Archiver>) {
+        let _ = self.archiver.set(archiver);
+    }
+    
+    /// Sweep every `interval_secs`, as a dry run when `retention.dry_run` is set
+    pub async fn start(&self) -> Result<()> {
+        info!("🧹 Retention janitor sweeping every {}s{}", self.config.interval_secs,
+              if self.config.dry_run { " (dry run)" } else { "" });
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval_secs.max(60)));
+        loop {
+            interval.tick().await;
+            match self.sweep(self.config.dry_run).await {
+                Ok(report) => log_report(&report),
+                Err(e) => error!("❌ Retention sweep failed: {:#}", e),
+            }
+        }
+    }
+    
+    pub async fn sweep(&self, dry_run: bool) -> Result<RetentionReport> {
+        let _sweeping = self.sweeping.lock().await;
+        let now = chrono::Utc::now().timestamp() as u64;
+        let cutoff = |days: Option<u64>| days.map(|days| now.saturating_sub(days * 86_400));
+        
+        let detections_cutoff = cutoff(self.config.detections_days);
+        // Evidence never outlives the report that points at it
+        let evidence_cutoff = match (cutoff(self.config.evidence_days), detections_cutoff) {
+            (Some(evidence), Some(detections)) => Some(evidence.max(detections)),
+            (evidence, detections) => evidence.or(detections),
+        };
+        
+        let mut sweeps = Vec::new();
+        let (detections, evidence, archived) = self.sweep_reports(detections_cutoff, evidence_cutoff, dry_run).await?;
+        sweeps.push(CategorySweep {
+            category: DataCategory::Detections,
+            retention_days: self.config.detections_days,
+            cutoff: detections_cutoff,
+            expired: detections,
+        });
+        sweeps.push(CategorySweep {
+            category: DataCategory::Evidence,
+            retention_days: self.config.evidence_days,
+            cutoff: evidence_cutoff,
+            expired: evidence,
+        });
+        
+        let energy_cutoff = cutoff(self.config.energy_days);
+        sweeps.push(CategorySweep {
+            category: DataCategory::Energy,
+            retention_days: self.config.energy_days,
+            cutoff: energy_cutoff,
+            expired: match energy_cutoff {
+                Some(cutoff) => self.sweep_energy(cutoff, dry_run)?,
+                None => 0,
+            },
+        });
+        
+        let audit_cutoff = cutoff(self.config.audit_days);
+        sweeps.push(CategorySweep {
+            category: DataCategory::Audit,
+            retention_days: self.config.audit_days,
+            cutoff: audit_cutoff,
+            expired: match audit_cutoff {
+                Some(cutoff) => self.audit_log.compact(cutoff * 1000, dry_run)?,
+                None => 0,
+            },
+        });
+        
+        Ok(RetentionReport { dry_run, ran_at: now, sweeps, archived })
+    }
+    
+    /// Delete expired reports and unpin expired evidence, returning how many of each and how many
+    /// reports were archived first
+    async fn sweep_reports(&self, detections_cutoff: Option<u64>, evidence_cutoff: Option<u64>, dry_run: bool) -> Result<(usize, usize, usize)> {
+        if detections_cutoff.is_none() && evidence_cutoff.is_none() {
+            return Ok((0, 0, 0));
+        }
+        let expired = |cutoff: Option<u64>, record: &ThreatReportRecord| cutoff.is_some_and(|cutoff| record.reported_at < cutoff);
+        let expiring: Vec<(String, ThreatReportRecord)> = self.storage
+            .scan::<ThreatReportRecord>(THREAT_REPORT_NAMESPACE)?
+            .into_iter()
+            .filter(|(_, record)| {
+                expired(detections_cutoff, record) || (record.evidence_cid.is_some() && expired(evidence_cutoff, record))
+            })
+            .collect();
+        
+        let archived = match self.archiver.get() {
+            Some(archiver) if dry_run => archiver.pending(&expiring)?,
+            Some(archiver) => archiver
+                .archive(&expiring)
+                .await
+                .context("Archiving expiring reports failed; nothing was expired")?,
+            None => 0,
+        };
+        
+        let mut batch = self.storage.batch();
+        let (mut detections, mut evidence) = (0, 0);
+        for (tx_hash, mut record) in expiring {
+            let delete = expired(detections_cutoff, &record);
+            let expired_cid = record.evidence_cid.clone().filter(|_| expired(evidence_cutoff, &record));
+            if let (Some(cid), Some(ipfs)) = (expired_cid, &self.ipfs) {
+                if dry_run {
+                    evidence += 1;
+                } else {
+                    match ipfs.unpin(&cid).await {
+                        Ok(()) => {
+                            evidence += 1;
+                            record.evidence_cid = None;
+                            if !delete {
+                                batch.put(THREAT_REPORT_NAMESPACE, &tx_hash, &record)?;
+                            }
+                        }
+                        // The report is kept too, and both are retried on the next sweep
+                        Err(e) => {
+                            warn!("⚠️ Failed to unpin expired evidence {}: {}", cid, e);
+                            continue;
+                        }
+                    }
+                }
+            }
+            if delete {
+                detections += 1;
+                batch.delete(THREAT_REPORT_NAMESPACE, &tx_hash);
+                batch.delete(REPORT_PIPELINE_NAMESPACE, &tx_hash);
+                batch.delete(ARCHIVED_NAMESPACE, &tx_hash);
+            }
+        }
+        
+        if !dry_run && !batch.is_empty() {
+            self.storage.commit(batch)?;
+            if detections > 0 {
+                self.history.reload()?;
+            }
+        }
+        Ok((detections, evidence, archived))
+    }
+    
+    fn sweep_energy(&self, cutoff: u64, dry_run: bool) -> Result<usize> {
+        let mut batch = self.storage.batch();
+        let mut expired = 0;
+        for (key, reading) in self.storage.scan::<EnergyMetrics>(ENERGY_HISTORY_NAMESPACE)? {
+            if reading.timestamp >= cutoff {
+                break;
+            }
+            expired += 1;
+            batch.delete(ENERGY_HISTORY_NAMESPACE, &key);
+        }
+        if !dry_run && !batch.is_empty() {
+            self.storage.commit(batch)?;
+        }
+        Ok(expired)
+    }
+}
+
+fn log_report(report: &RetentionReport) {
+    if report.archived > 0 {
+        info!("🗄️ {} {} expiring reports", if report.dry_run { "Would archive" } else { "Archived" }, report.archived);
+    }
+    for sweep in report.sweeps.iter().filter(|sweep| sweep.expired > 0) {
+        info!("🧹 {} {} {:?} entries past {} days", if report.dry_run { "Would expire" } else { "Expired" },
+              sweep.expired, sweep.category, sweep.retention_days.unwrap_or_default());
+    }
+}
+
+/// `GET /retention` previews a sweep as a dry run; `POST /retention/sweep` runs one
+pub fn admin_routes(janitor: Arc<RetentionJanitor>) -> Router {
+    let preview = Arc::clone(&janitor);
+    Router::new()
+        .route("/retention", get(move || async move {
+            match preview.sweep(true).await {
+                Ok(report) => Json(Report::new("retention", report)).into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)).into_response(),
+            }
+        }))
+        .route("/retention/sweep", post(move || async move {
+            match janitor.sweep(false).await {
+                Ok(report) => {
+                    log_report(&report);
+                    Json(Report::new("retention", report)).into_response()
+                }
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)).into_response(),
+            }
+        }))
+}
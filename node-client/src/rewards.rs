@@ -0,0 +1,155 @@
+//! What the node has earned and collected
+//!
+//! Each deployment accrues a reward to the node for every verified report, solved challenge or
+//! upheld dispute, announcing each with a `RewardDistributed` event, and pays them all out when
+//! the node calls `claimRewards` on it. The event listener records both kinds of event for the
+//! node's address on every chain in a local ledger, committed with that chain's cursor, so
+//! earnings stay visible after they are claimed. The ledger only grows while `enable_oracle` runs
+//! the listener.
+//!
+//! `dagshield-node rewards` shows the ledger and what is claimable now; `--claim` collects it,
+//! signing from the CLI process like stake changes do. The admin API only shows rewards.
+
+use anyhow::Result;
+use axum::{http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use ethers::types::{Address, U256};
+use ethers::utils::format_ether;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::blockchain::BlockchainClient;
+use crate::status::Report;
+use crate::storage::NodeStorage;
+
+/// Namespace in `NodeStorage` holding the reward ledger, keyed by [`ledger_key`]
+pub const REWARD_LEDGER_NAMESPACE: &str = "reward_ledger";
+/// Ledger entries shown in a summary
+const RECENT_ENTRIES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardEntryKind {
+    Earned,
+    Claimed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RewardEntry {
+    pub kind: RewardEntryKind,
+    /// In wei
+    pub amount: U256,
+    /// The contract's `rewardType` for an earned reward, e.g. `threat_detection`
+    pub reward_type: Option<String>,
+    pub block_number: u64,
+}
+
+/// Orders the ledger by position on chain; the chain ID keeps positions on different chains apart
+pub fn ledger_key(chain_id: u64, block_number: u64, log_index: u64) -> String {
+    format!("{:020}-{:06}-{}", block_number, log_index, chain_id)
+}
+
+/// Amounts in ether
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RewardSummary {
+    pub address: Address,
+    /// Claimable now, per the contracts
+    pub pending: String,
+    /// Over the whole ledger
+    pub earned: String,
+    pub claimed: String,
+    /// Earned per reward type
+    pub earned_by_type: BTreeMap<String, String>,
+    /// Latest ledger entries, newest first
+    pub recent: Vec<RewardEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimOutcome {
+    /// In ether; zero when there was nothing to claim
+    pub amount: String,
+    /// One per chain there was something to claim on
+    pub tx_hashes: Vec<String>,
+}
+
+pub struct RewardTracker {
+    blockchain: Arc<BlockchainClient>,
+    storage: Arc<NodeStorage>,
+}
+
+impl RewardTracker {
+    pub fn new(blockchain: Arc<BlockchainClient>, storage: Arc<NodeStorage>) -> Self {
+        Self { blockchain, storage }
+    }
+    
+    /// Every ledger entry, oldest first
+    pub fn ledger(&self) -> Result<Vec<RewardEntry>> {
+        Ok(self
+            .storage
+            .scan::<RewardEntry>(REWARD_LEDGER_NAMESPACE)?
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect())
+    }
+    
+    pub async fn summary(&self) -> Result<RewardSummary> {
+        let mut pending = U256::zero();
+        for chain_id in self.blockchain.chain_ids() {
+            pending += self.blockchain.pending_rewards(chain_id).await?;
+        }
+        let ledger = self.ledger()?;
+        
+        let mut earned = U256::zero();
+        let mut claimed = U256::zero();
+        let mut earned_by_type: BTreeMap<String, U256> = BTreeMap::new();
+        for entry in &ledger {
+            match entry.kind {
+                RewardEntryKind::Earned => {
+                    earned += entry.amount;
+                    let reward_type = entry.reward_type.clone().unwrap_or_default();
+                    *earned_by_type.entry(reward_type).or_default() += entry.amount;
+                }
+                RewardEntryKind::Claimed => claimed += entry.amount,
+            }
+        }
+        
+        Ok(RewardSummary {
+            address: self.blockchain.wallet_address(),
+            pending: format_ether(pending),
+            earned: format_ether(earned),
+            claimed: format_ether(claimed),
+            earned_by_type: earned_by_type
+                .into_iter()
+                .map(|(reward_type, amount)| (reward_type, format_ether(amount)))
+                .collect(),
+            recent: ledger.into_iter().rev().take(RECENT_ENTRIES).collect(),
+        })
+    }
+}
+
+/// Claim whatever each deployment holds for the node; the ledger records it once the
+/// listener reads the `RewardsClaimed` events
+pub async fn claim(blockchain: &BlockchainClient) -> Result<ClaimOutcome> {
+    let mut amount = U256::zero();
+    let mut tx_hashes = Vec::new();
+    for chain_id in blockchain.chain_ids() {
+        if let Some((claimed, tx_hash)) = blockchain.claim_rewards(chain_id).await? {
+            amount += claimed;
+            tx_hashes.push(tx_hash);
+        }
+    }
+    Ok(ClaimOutcome {
+        amount: format_ether(amount),
+        tx_hashes,
+    })
+}
+
+/// `GET /rewards` summarises; claiming is CLI-only
+pub fn admin_routes(tracker: Arc<RewardTracker>) -> Router {
+    Router::new().route("/rewards", get(move || async move {
+        match tracker.summary().await {
+            Ok(summary) => Json(Report::new("rewards", summary)).into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)).into_response(),
+        }
+    }))
+}
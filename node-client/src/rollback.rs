@@ -0,0 +1,301 @@
+//! Automatic rollback of model and pattern updates that lose accuracy, with artifact quarantine
+
+use anyhow::{bail, Context, Result};
+use parking_lot::Mutex;
+use prometheus::{IntCounterVec, Opts};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::Notify;
+use tracing::{error, info};
+
+use crate::ai::{ThreatDetector, ThreatPattern};
+use crate::config::RollbackConfig;
+use crate::metrics::register_once;
+use crate::storage::NodeStorage;
+
+const ARTIFACT_STATE_NAMESPACE: &str = "artifact_state";
+const ARTIFACT_QUARANTINE_NAMESPACE: &str = "artifact_quarantine";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArtifactKind {
+    Model,
+    Patterns,
+}
+
+impl ArtifactKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArtifactKind::Model => "model",
+            ArtifactKind::Patterns => "patterns",
+        }
+    }
+}
+
+/// Where a graded verdict came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutcomeSource {
+    /// Operator or tenant feedback on live verdicts
+    Feedback,
+    /// Labelled cases from network-issued accuracy challenges
+    PeerAudit,
+}
+
+/// The update currently in force for one artifact kind, and how it has fared so far
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ActiveArtifact {
+    hash: String,
+    /// Snapshot of what the update replaced, restored on rollback
+    previous_path: Option<PathBuf>,
+    applied_at: u64,
+    baseline_accuracy: Option<f64>,
+    samples: u64,
+    correct: u64,
+    evaluating: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantinedArtifact {
+    pub kind: ArtifactKind,
+    pub hash: String,
+    pub reason: String,
+    pub quarantined_at: u64,
+}
+
+struct GuardState {
+    recent: VecDeque<bool>,
+    model: Option<ActiveArtifact>,
+    patterns: Option<ActiveArtifact>,
+    pending_rollback: Option<(ArtifactKind, String)>,
+}
+
+impl GuardState {
+    fn active_mut(&mut self, kind: ArtifactKind) -> &mut Option<ActiveArtifact> {
+        match kind {
+            ArtifactKind::Model => &mut self.model,
+            ArtifactKind::Patterns => &mut self.patterns,
+        }
+    }
+}
+
+/// Watches accuracy after each model or pattern update and undoes updates that regress it.
+///
+/// Before an update is applied the replaced artifact is snapshotted under the data directory.
+/// Graded outcomes after the update are compared with the accuracy before it; on a regression
+/// the snapshot is restored and the update's hash is quarantined so it is refused if offered again.
+pub struct ArtifactGuard {
+    config: RollbackConfig,
+    storage: Arc<NodeStorage>,
+    artifact_dir: PathBuf,
+    model_path: String,
+    state: Mutex<GuardState>,
+    rollback_ready: Notify,
+    rollbacks: IntCounterVec,
+}
+
+impl ArtifactGuard {
+    pub fn new(config: &RollbackConfig, storage: Arc<NodeStorage>, model_path: &str) -> Result<Self> {
+        let artifact_dir = Path::new(storage.data_dir()).join("artifacts");
+        std::fs::create_dir_all(&artifact_dir)
+            .with_context(|| format!("Failed to create artifact directory {}", artifact_dir.display()))?;
+        
+        static ROLLBACKS: OnceLock<IntCounterVec> = OnceLock::new();
+        let rollbacks = register_once(&ROLLBACKS, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_artifact_rollbacks_total", "Model and pattern updates rolled back for accuracy regressions"),
+                &["kind"],
+            )
+        })?;
+        
+        let state = GuardState {
+            recent: VecDeque::with_capacity(config.baseline_window),
+            model: storage.get(ARTIFACT_STATE_NAMESPACE, ArtifactKind::Model.as_str())?,
+            patterns: storage.get(ARTIFACT_STATE_NAMESPACE, ArtifactKind::Patterns.as_str())?,
+            pending_rollback: None,
+        };
+        
+        Ok(Self {
+            config: config.clone(),
+            storage,
+            artifact_dir,
+            model_path: model_path.to_string(),
+            state: Mutex::new(state),
+            rollback_ready: Notify::new(),
+            rollbacks,
+        })
+    }
+    
+    pub fn artifact_hash(payload: &[u8]) -> String {
+        blake3::hash(payload).to_hex().to_string()
+    }
+    
+    pub fn is_quarantined(&self, payload: &[u8]) -> Result<Option<QuarantinedArtifact>> {
+        self.storage.get(ARTIFACT_QUARANTINE_NAMESPACE, &Self::artifact_hash(payload))
+    }
+    
+    /// Refuse updates that were rolled back before
+    pub fn ensure_not_quarantined(&self, kind: ArtifactKind, payload: &[u8]) -> Result<()> {
+        if let Some(entry) = self.is_quarantined(payload)? {
+            bail!("{} artifact {} is quarantined: {}", kind.as_str(), entry.hash, entry.reason);
+        }
+        Ok(())
+    }
+    
+    /// Record that an update was applied; `previous` is what it replaced, if anything
+    pub fn artifact_applied(&self, kind: ArtifactKind, payload: &[u8], previous: Option<&[u8]>) -> Result<()> {
+        let hash = Self::artifact_hash(payload);
+        
+        let previous_path = match previous {
+            Some(bytes) => {
+                let path = self.artifact_dir.join(format!("{}-{}.bin", kind.as_str(), Self::artifact_hash(bytes)));
+                std::fs::write(&path, bytes)
+                    .with_context(|| format!("Failed to snapshot previous {} artifact", kind.as_str()))?;
+                Some(path)
+            }
+            None => None,
+        };
+        
+        let mut state = self.state.lock();
+        let baseline_accuracy = accuracy_of(&state.recent, self.config.min_samples);
+        let active = ActiveArtifact {
+            hash: hash.clone(),
+            previous_path,
+            applied_at: now_secs(),
+            baseline_accuracy,
+            samples: 0,
+            correct: 0,
+            evaluating: self.config.enabled,
+        };
+        self.storage.put(ARTIFACT_STATE_NAMESPACE, kind.as_str(), &active)?;
+        *state.active_mut(kind) = Some(active);
+        
+        info!("🧪 Evaluating {} update {} against baseline accuracy {}",
+              kind.as_str(), &hash[..12],
+              baseline_accuracy.map_or("n/a".to_string(), |a| format!("{:.4}", a)));
+        Ok(())
+    }
+    
+    /// Feed one graded verdict; triggers a rollback once an update has clearly regressed
+    pub fn record_outcome(&self, source: OutcomeSource, correct: bool) {
+        let mut state = self.state.lock();
+        
+        if state.recent.len() >= self.config.baseline_window.max(1) {
+            state.recent.pop_front();
+        }
+        state.recent.push_back(correct);
+        
+        if !self.config.enabled || state.pending_rollback.is_some() {
+            return;
+        }
+        
+        // Outcomes count towards every update still on probation; the newest one is blamed first
+        let mut regressed: Option<(ArtifactKind, u64, String)> = None;
+        for kind in [ArtifactKind::Model, ArtifactKind::Patterns] {
+            let Some(active) = state.active_mut(kind).as_mut().filter(|a| a.evaluating) else {
+                continue;
+            };
+            active.samples += 1;
+            if correct {
+                active.correct += 1;
+            }
+            if active.samples < self.config.min_samples {
+                continue;
+            }
+            
+            let accuracy = active.correct as f64 / active.samples as f64;
+            let floor = active.baseline_accuracy
+                .map(|baseline| baseline - self.config.max_accuracy_drop)
+                .unwrap_or(self.config.min_accuracy);
+            
+            if accuracy < floor {
+                let reason = format!("accuracy {:.4} below {:.4} after {} {:?} outcomes",
+                                     accuracy, floor, active.samples, source);
+                if regressed.as_ref().is_none_or(|(_, applied_at, _)| active.applied_at > *applied_at) {
+                    regressed = Some((kind, active.applied_at, reason));
+                }
+            } else {
+                info!("✅ {} update {} kept: accuracy {:.4} over {} outcomes",
+                      kind.as_str(), &active.hash[..12], accuracy, active.samples);
+                active.evaluating = false;
+            }
+            let _ = self.storage.put(ARTIFACT_STATE_NAMESPACE, kind.as_str(), &*active);
+        }
+        
+        if let Some((kind, _, reason)) = regressed {
+            state.pending_rollback = Some((kind, reason));
+            self.rollback_ready.notify_one();
+        }
+    }
+    
+    /// Perform rollbacks as regressions are detected
+    pub async fn start(&self, detector: Option<Arc<ThreatDetector>>) -> Result<()> {
+        loop {
+            self.rollback_ready.notified().await;
+            
+            let pending = self.state.lock().pending_rollback.clone();
+            if let Some((kind, reason)) = pending {
+                if let Err(e) = self.roll_back(kind, &reason, detector.as_deref()).await {
+                    error!("🚨 Failed to roll back {} update ({}): {}", kind.as_str(), reason, e);
+                }
+                self.state.lock().pending_rollback = None;
+            }
+        }
+    }
+    
+    async fn roll_back(&self, kind: ArtifactKind, reason: &str, detector: Option<&ThreatDetector>) -> Result<()> {
+        let active = self.state.lock().active_mut(kind).clone()
+            .context("No active artifact to roll back")?;
+        
+        let previous = match &active.previous_path {
+            Some(path) => Some(std::fs::read(path)
+                .with_context(|| format!("Failed to read snapshot {}", path.display()))?),
+            None => None,
+        };
+        
+        match (kind, previous) {
+            (ArtifactKind::Model, Some(bytes)) => {
+                let tmp_path = format!("{}.rollback-tmp", self.model_path);
+                std::fs::write(&tmp_path, &bytes)?;
+                std::fs::rename(&tmp_path, &self.model_path)?;
+                if let Some(detector) = detector {
+                    detector.reload_model().await?;
+                }
+            }
+            (ArtifactKind::Patterns, Some(bytes)) => {
+                let patterns: Vec<ThreatPattern> = serde_json::from_slice(&bytes)?;
+                if let Some(detector) = detector {
+                    detector.replace_threat_patterns(patterns).await?;
+                }
+            }
+            (_, None) => bail!("No previous {} artifact was kept", kind.as_str()),
+        }
+        
+        let entry = QuarantinedArtifact {
+            kind,
+            hash: active.hash.clone(),
+            reason: reason.to_string(),
+            quarantined_at: now_secs(),
+        };
+        self.storage.put(ARTIFACT_QUARANTINE_NAMESPACE, &active.hash, &entry)?;
+        self.storage.delete(ARTIFACT_STATE_NAMESPACE, kind.as_str())?;
+        *self.state.lock().active_mut(kind) = None;
+        
+        self.rollbacks.with_label_values(&[kind.as_str()]).inc();
+        error!("🚨 Rolled back {} update {} and quarantined it: {}", kind.as_str(), active.hash, reason);
+        Ok(())
+    }
+}
+
+/// Accuracy over `outcomes`, or `None` when there are too few to be meaningful
+fn accuracy_of(outcomes: &VecDeque<bool>, min_samples: u64) -> Option<f64> {
+    if (outcomes.len() as u64) < min_samples.max(1) {
+        return None;
+    }
+    let correct = outcomes.iter().filter(|c| **c).count();
+    Some(correct as f64 / outcomes.len() as f64)
+}
+
+fn now_secs() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
@@ -0,0 +1,371 @@
+//! RPC endpoint pool with health-based failover
+//!
+//! A chain's `rpc_url` and `fallback_rpc_urls` form one pool behind a single provider. Requests
+//! go to the first endpoint in config order that is up and keeping up with the chain; an
+//! endpoint failing at the transport level (unreachable, timing out, answering garbage) lets the
+//! request fall through to the next. JSON-RPC errors are answers, not failures: a revert is not
+//! retried elsewhere.
+//!
+//! After `max_failures` consecutive failures an endpoint is skipped for `cooldown_secs`. A health
+//! check every `health_check_interval_secs` reads each endpoint's head and latency; one more than
+//! `max_lag_blocks` behind the best head is skipped until it catches up. Endpoints that are down
+//! or lagging are still tried, last, when no other is left.
+
+use async_trait::async_trait;
+use ethers::providers::{Http, HttpClientError, JsonRpcClient, Provider};
+use ethers::types::U64;
+use parking_lot::Mutex;
+use prometheus::{GaugeVec, IntCounterVec, IntGaugeVec, Opts};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Debug;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::chain_watch::redact;
+use crate::config::RpcPoolConfig;
+use crate::metrics::register_once;
+
+/// Weight of the newest sample in an endpoint's latency average
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+pub type PooledProvider = Provider<RpcPool>;
+
+/// Per-endpoint health, shared by every chain's pool
+#[derive(Clone)]
+pub struct RpcMetrics {
+    up: IntGaugeVec,
+    latency: GaugeVec,
+    errors: IntCounterVec,
+    lag: IntGaugeVec,
+}
+
+impl RpcMetrics {
+    pub fn new() -> anyhow::Result<Self> {
+        static UP: OnceLock<IntGaugeVec> = OnceLock::new();
+        static LATENCY: OnceLock<GaugeVec> = OnceLock::new();
+        static ERRORS: OnceLock<IntCounterVec> = OnceLock::new();
+        static LAG: OnceLock<IntGaugeVec> = OnceLock::new();
+        let labels = &["chain_id", "endpoint"];
+        Ok(Self {
+            up: register_once(&UP, || {
+                IntGaugeVec::new(Opts::new("dagshield_rpc_endpoint_up", "Whether the RPC endpoint is used: reachable and keeping up with the chain"), labels)
+            })?,
+            latency: register_once(&LATENCY, || {
+                GaugeVec::new(Opts::new("dagshield_rpc_endpoint_latency_seconds", "Smoothed RPC endpoint response time"), labels)
+            })?,
+            errors: register_once(&ERRORS, || {
+                IntCounterVec::new(Opts::new("dagshield_rpc_endpoint_errors_total", "Transport-level RPC endpoint failures"), labels)
+            })?,
+            lag: register_once(&LAG, || {
+                IntGaugeVec::new(Opts::new("dagshield_rpc_endpoint_lag_blocks", "Blocks the RPC endpoint's head is behind the pool's best"), labels)
+            })?,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    consecutive_failures: u32,
+    down_until: Option<Instant>,
+    lagging: bool,
+    latency: Option<Duration>,
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    /// Scheme, host and port only, as URLs often carry API keys
+    label: String,
+    http: Http,
+    health: Mutex<Health>,
+}
+
+pub struct RpcPool {
+    chain_id: String,
+    config: RpcPoolConfig,
+    endpoints: Vec<Endpoint>,
+    metrics: RpcMetrics,
+}
+
+impl Debug for RpcPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RpcPool")
+            .field("chain_id", &self.chain_id)
+            .field("endpoints", &self.endpoints.iter().map(|endpoint| &endpoint.label).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl RpcPool {
+    /// `urls` in order of preference
+    pub fn new(chain_id: u64, urls: &[String], config: &RpcPoolConfig, metrics: RpcMetrics) -> anyhow::Result<Self> {
+        if urls.is_empty() {
+            anyhow::bail!("Chain {} has no RPC endpoint", chain_id);
+        }
+        let chain_id = chain_id.to_string();
+        let endpoints = urls
+            .iter()
+            .map(|url| {
+                let label = redact(url);
+                metrics.up.with_label_values(&[&chain_id, &label]).set(1);
+                Ok(Endpoint {
+                    label,
+                    http: url.parse()?,
+                    health: Mutex::new(Health::default()),
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        
+        Ok(Self {
+            chain_id,
+            config: config.clone(),
+            endpoints,
+            metrics,
+        })
+    }
+    
+    pub fn into_provider(self) -> PooledProvider {
+        Provider::new(self)
+    }
+    
+    /// Endpoint indices to try, usable ones first, each group in config order
+    fn candidates(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let (usable, unusable): (Vec<usize>, Vec<usize>) = (0..self.endpoints.len()).partition(|index| {
+            let health = self.endpoints[*index].health.lock();
+            !health.lagging && health.down_until.is_none_or(|until| until <= now)
+        });
+        usable.into_iter().chain(unusable).collect()
+    }
+    
+    fn record_success(&self, index: usize, elapsed: Duration) {
+        let endpoint = &self.endpoints[index];
+        let labels = [self.chain_id.as_str(), endpoint.label.as_str()];
+        let mut health = endpoint.health.lock();
+        if health.down_until.take().is_some() {
+            info!("🔌 RPC endpoint {} on chain {} is back", endpoint.label, self.chain_id);
+        }
+        health.consecutive_failures = 0;
+        let latency = match health.latency {
+            Some(average) => average.mul_f64(1.0 - LATENCY_SMOOTHING) + elapsed.mul_f64(LATENCY_SMOOTHING),
+            None => elapsed,
+        };
+        health.latency = Some(latency);
+        self.metrics.latency.with_label_values(&labels).set(latency.as_secs_f64());
+        self.metrics.up.with_label_values(&labels).set(i64::from(!health.lagging));
+    }
+    
+    fn record_failure(&self, index: usize, error: &HttpClientError) {
+        let endpoint = &self.endpoints[index];
+        let labels = [self.chain_id.as_str(), endpoint.label.as_str()];
+        self.metrics.errors.with_label_values(&labels).inc();
+        let mut health = endpoint.health.lock();
+        health.consecutive_failures += 1;
+        if health.consecutive_failures >= self.config.max_failures {
+            if health.down_until.is_none() {
+                warn!("🔌 RPC endpoint {} on chain {} is down after {} failures ({}); failing over",
+                      endpoint.label, self.chain_id, health.consecutive_failures, error);
+                self.metrics.up.with_label_values(&labels).set(0);
+            }
+            health.down_until = Some(Instant::now() + Duration::from_secs(self.config.cooldown_secs));
+        }
+    }
+    
+    /// Read every endpoint's head and mark those too far behind the best one as lagging
+    pub async fn check_health(&self) {
+        let mut heads = Vec::with_capacity(self.endpoints.len());
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            let started = Instant::now();
+            match endpoint.http.request::<_, U64>("eth_blockNumber", ()).await {
+                Ok(head) => {
+                    self.record_success(index, started.elapsed());
+                    heads.push(Some(head.as_u64()));
+                }
+                Err(e) => {
+                    self.record_failure(index, &e);
+                    heads.push(None);
+                }
+            }
+        }
+        
+        let Some(best) = heads.iter().flatten().max().copied() else {
+            return;
+        };
+        for (endpoint, head) in self.endpoints.iter().zip(heads) {
+            let Some(head) = head else {
+                continue;
+            };
+            let labels = [self.chain_id.as_str(), endpoint.label.as_str()];
+            let lag = best - head;
+            let lagging = lag > self.config.max_lag_blocks;
+            let mut health = endpoint.health.lock();
+            if lagging != health.lagging {
+                if lagging {
+                    warn!("🔌 RPC endpoint {} on chain {} is {} blocks behind; failing over", endpoint.label, self.chain_id, lag);
+                } else {
+                    info!("🔌 RPC endpoint {} on chain {} caught up", endpoint.label, self.chain_id);
+                }
+                health.lagging = lagging;
+            }
+            self.metrics.lag.with_label_values(&labels).set(lag as i64);
+            self.metrics.up.with_label_values(&labels).set(i64::from(!lagging && health.down_until.is_none()));
+        }
+    }
+    
+    pub fn health_check_interval(&self) -> Duration {
+        Duration::from_secs(self.config.health_check_interval_secs.max(1))
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for RpcPool {
+    type Error = HttpClientError;
+    
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        // Serialized once, to be sent to as many endpoints as it takes
+        let params = serde_json::to_value(params)
+            .map_err(|err| HttpClientError::SerdeJson { err, text: String::new() })?;
+        let mut last_error = None;
+        for index in self.candidates() {
+            let started = Instant::now();
+            match self.endpoints[index].http.request::<_, R>(method, &params).await {
+                Ok(result) => {
+                    self.record_success(index, started.elapsed());
+                    return Ok(result);
+                }
+                // The endpoint answered; another would answer the same
+                Err(HttpClientError::JsonRpcError(e)) => {
+                    self.record_success(index, started.elapsed());
+                    return Err(HttpClientError::JsonRpcError(e));
+                }
+                Err(e) => {
+                    self.record_failure(index, &e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.expect("a pool has at least one endpoint"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    /// A JSON-RPC endpoint answering `eth_blockNumber` with its head, or 503 while it is down
+    struct FakeNode {
+        url: String,
+        head: AtomicU64,
+        up: AtomicBool,
+    }
+
+    async fn node(head: u64) -> Arc<FakeNode> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let node = Arc::new(FakeNode {
+            url: format!("http://{}", listener.local_addr().unwrap()),
+            head: AtomicU64::new(head),
+            up: AtomicBool::new(true),
+        });
+        let app = Router::new()
+            .route("/", post(|State(node): State<Arc<FakeNode>>, Json(request): Json<serde_json::Value>| async move {
+                if !node.up.load(Ordering::SeqCst) {
+                    return Err(StatusCode::SERVICE_UNAVAILABLE);
+                }
+                let head = format!("{:#x}", node.head.load(Ordering::SeqCst));
+                Ok(Json(serde_json::json!({ "jsonrpc": "2.0", "id": request["id"], "result": head })))
+            }))
+            .with_state(Arc::clone(&node));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        node
+    }
+
+    /// An address nothing listens on
+    async fn unreachable() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    fn pool(urls: &[String], cooldown_secs: u64) -> RpcPool {
+        let config = RpcPoolConfig {
+            max_failures: 2,
+            cooldown_secs,
+            health_check_interval_secs: 15,
+            max_lag_blocks: 5,
+        };
+        RpcPool::new(1, urls, &config, RpcMetrics::new().unwrap()).unwrap()
+    }
+
+    async fn head(pool: &RpcPool) -> Result<u64, HttpClientError> {
+        pool.request::<_, U64>("eth_blockNumber", ()).await.map(|head| head.as_u64())
+    }
+
+    #[tokio::test]
+    async fn requests_fail_over_and_skip_a_failing_endpoint() {
+        let live = node(100).await;
+        let pool = pool(&[unreachable().await, live.url.clone()], 60);
+
+        assert_eq!(head(&pool).await.unwrap(), 100);
+        assert_eq!(pool.candidates(), [0, 1], "still preferred after one failure");
+        assert_eq!(head(&pool).await.unwrap(), 100);
+        assert_eq!(pool.candidates(), [1, 0], "down after max_failures");
+    }
+
+    #[tokio::test]
+    async fn lagging_endpoints_are_tried_last() {
+        let behind = node(90).await;
+        let best = node(100).await;
+        let pool = pool(&[behind.url.clone(), best.url.clone()], 60);
+
+        pool.check_health().await;
+        assert_eq!(pool.candidates(), [1, 0]);
+        assert_eq!(head(&pool).await.unwrap(), 100);
+
+        behind.head.store(98, Ordering::SeqCst);
+        pool.check_health().await;
+        assert_eq!(pool.candidates(), [0, 1], "caught up within max_lag_blocks");
+    }
+
+    #[tokio::test]
+    async fn a_demoted_endpoint_recovers_after_its_cooldown() {
+        let flaky = node(100).await;
+        let fallback = node(100).await;
+        let pool = pool(&[flaky.url.clone(), fallback.url.clone()], 1);
+
+        flaky.up.store(false, Ordering::SeqCst);
+        head(&pool).await.unwrap();
+        head(&pool).await.unwrap();
+        assert_eq!(pool.candidates(), [1, 0]);
+
+        flaky.up.store(true, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert_eq!(pool.candidates(), [0, 1], "tried first again once the cooldown is over");
+        head(&pool).await.unwrap();
+        let health = pool.endpoints[0].health.lock();
+        assert_eq!(health.consecutive_failures, 0);
+        assert!(health.down_until.is_none());
+    }
+
+    #[tokio::test]
+    async fn with_every_endpoint_down_all_are_still_tried() {
+        let first = node(100).await;
+        let second = node(100).await;
+        let pool = pool(&[first.url.clone(), second.url.clone()], 60);
+
+        first.up.store(false, Ordering::SeqCst);
+        second.up.store(false, Ordering::SeqCst);
+        for _ in 0..2 {
+            assert!(!matches!(head(&pool).await, Ok(_) | Err(HttpClientError::JsonRpcError(_))));
+        }
+        assert_eq!(pool.candidates(), [0, 1], "in config order when none is usable");
+
+        second.up.store(true, Ordering::SeqCst);
+        assert_eq!(head(&pool).await.unwrap(), 100, "a down endpoint answering again is used");
+    }
+}
@@ -0,0 +1,277 @@
+//! Optional process hardening on Linux: landlock filesystem rules and a seccomp syscall filter
+
+use anyhow::Result;
+use tracing::{info, warn};
+
+use crate::config::{KeySource, NodeConfig};
+
+/// System paths the node needs read access to for TLS, DNS and hardware monitoring
+#[cfg(target_os = "linux")]
+const SYSTEM_READ_PATHS: &[&str] = &[
+    "/etc/ssl",
+    "/etc/ca-certificates",
+    "/etc/resolv.conf",
+    "/etc/hosts",
+    "/etc/nsswitch.conf",
+    "/proc",
+    "/sys",
+    "/dev/urandom",
+    "/usr/share/zoneinfo",
+];
+
+/// Where the dynamic loader looks for a library given by name, besides `LD_LIBRARY_PATH`
+#[cfg(target_os = "linux")]
+const LOADER_SEARCH_PATHS: &[&str] = &[
+    "/etc/ld.so.cache",
+    "/lib",
+    "/lib64",
+    "/usr/lib",
+    "/usr/lib64",
+    "/usr/local/lib",
+];
+
+/// Syscalls the node never needs; denied with EPERM unless listed in `allow_syscalls`
+#[cfg(target_os = "linux")]
+const DENIED_SYSCALLS: &[&str] = &[
+    "ptrace",
+    "process_vm_readv",
+    "process_vm_writev",
+    "execve",
+    "execveat",
+    "mount",
+    "umount2",
+    "pivot_root",
+    "chroot",
+    "kexec_load",
+    "init_module",
+    "finit_module",
+    "delete_module",
+    "bpf",
+    "perf_event_open",
+    "keyctl",
+    "add_key",
+    "request_key",
+    "swapon",
+    "swapoff",
+    "reboot",
+    "setns",
+    "unshare",
+    "userfaultfd",
+];
+
+/// Apply the configured sandbox to the current thread.
+///
+/// Landlock and seccomp restrictions are inherited by threads created afterwards, so this
+/// must run before the async runtime and worker pools spawn their threads.
+pub fn apply(config: &NodeConfig, config_path: &str) -> Result<()> {
+    if !config.sandbox.enabled {
+        return Ok(());
+    }
+    
+    #[cfg(target_os = "linux")]
+    {
+        linux::apply_landlock(config, config_path)?;
+        linux::apply_seccomp(&config.sandbox.allow_syscalls)?;
+        info!("🔒 Process sandbox applied");
+    }
+    
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = config_path;
+        warn!("⚠️ Sandbox mode is only supported on Linux; continuing without it");
+    }
+    
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::*;
+    use anyhow::{anyhow, Context};
+    use landlock::{
+        path_beneath_rules, Access, AccessFs, Ruleset, RulesetAttr, RulesetCreatedAttr, RulesetStatus, ABI,
+    };
+    use seccompiler::{BpfProgram, SeccompAction, SeccompFilter, SeccompRule};
+    use std::collections::BTreeMap;
+    use std::path::{Path, PathBuf};
+    
+    use crate::updater;
+    
+    pub fn apply_landlock(config: &NodeConfig, config_path: &str) -> Result<()> {
+        let abi = ABI::V2;
+        
+        let mut read_paths: Vec<String> = SYSTEM_READ_PATHS.iter().map(|p| p.to_string()).collect();
+        read_paths.push(config_path.to_string());
+        read_paths.extend(config.sandbox.extra_read_paths.iter().cloned());
+        // Unlocked when the blockchain client starts, after the sandbox is applied
+        if let Some(KeySource::Keystore { path, .. }) = &config.blockchain.key {
+            read_paths.push(path.clone());
+        }
+        
+        // The model directory stays readable so retrained models can be picked up
+        if let Some(model_dir) = Path::new(&config.ai.model_path).parent() {
+            read_paths.push(model_dir.to_string_lossy().into_owned());
+        }
+        // ONNX Runtime is loaded with the first model, after the sandbox is applied
+        read_paths.extend(onnxruntime_read_paths());
+        
+        let mut write_paths = vec![config.storage.data_dir.clone()];
+        write_paths.extend(config.sandbox.extra_write_paths.iter().cloned());
+        // Also used by the `backtest` subcommand, whether or not periodic backtests are enabled
+        read_paths.push(config.backtest.exploits_file.clone());
+        write_paths.push(config.backtest.report_dir.clone());
+        if config.ai.registry.enabled {
+            // Promoted versions are written over the model
+            write_paths.push(config.ai.registry.dir.clone());
+            if let Some(model_dir) = Path::new(&config.ai.model_path).parent() {
+                write_paths.push(model_dir.to_string_lossy().into_owned());
+            }
+        }
+        if config.fleet.enabled {
+            // Fleet-managed nodes receive model and config updates on disk
+            if let Some(model_dir) = Path::new(&config.ai.model_path).parent() {
+                write_paths.push(model_dir.to_string_lossy().into_owned());
+            }
+            if let Some(config_dir) = Path::new(&config.fleet.config_path).parent() {
+                write_paths.push(config_dir.to_string_lossy().into_owned());
+            }
+        }
+        if config.updater.enabled {
+            // New binaries are staged next to the one they replace
+            if let Ok(binary) = updater::binary_path(&config.updater) {
+                if let Some(binary_dir) = binary.parent() {
+                    write_paths.push(binary_dir.to_string_lossy().into_owned());
+                }
+            }
+        }
+        
+        // Paths that don't exist yet can't be opened as rule anchors
+        let existing = |paths: Vec<String>| -> Vec<String> {
+            paths
+                .into_iter()
+                .filter(|p| !p.is_empty() && Path::new(p).exists())
+                .collect()
+        };
+        let read_paths = existing(read_paths);
+        let write_paths = existing(write_paths);
+        
+        let status = Ruleset::default()
+            .handle_access(AccessFs::from_all(abi))?
+            .create()?
+            .add_rules(path_beneath_rules(&read_paths, AccessFs::from_read(abi)))?
+            .add_rules(path_beneath_rules(&write_paths, AccessFs::from_all(abi)))?
+            .restrict_self()
+            .context("Failed to apply landlock ruleset")?;
+        
+        match status.ruleset {
+            RulesetStatus::FullyEnforced => info!("🔒 Landlock enforced: {} read-only, {} read-write paths",
+                                                  read_paths.len(), write_paths.len()),
+            RulesetStatus::PartiallyEnforced => warn!("⚠️ Landlock only partially enforced by this kernel"),
+            RulesetStatus::NotEnforced => warn!("⚠️ Landlock not supported by this kernel; filesystem is unrestricted"),
+        }
+        
+        Ok(())
+    }
+    
+    /// Where ONNX Runtime will be loaded from: `ORT_DYLIB_PATH` or the default library name,
+    /// resolved as `ort` does, against the executable's directory first
+    fn onnxruntime_read_paths() -> Vec<String> {
+        let library = PathBuf::from(
+            std::env::var("ORT_DYLIB_PATH")
+                .ok()
+                .filter(|path| !path.is_empty())
+                .unwrap_or_else(|| "libonnxruntime.so".to_string()),
+        );
+        let beside_binary = std::env::current_exe()
+            .ok()
+            .and_then(|exe| Some(exe.parent()?.join(&library)))
+            .filter(|path| path.exists());
+        let resolved = match beside_binary {
+            _ if library.is_absolute() => library,
+            Some(path) => path,
+            // A bare name is searched for by the dynamic loader
+            None if library.components().count() == 1 => {
+                let mut paths: Vec<String> = std::env::var("LD_LIBRARY_PATH")
+                    .unwrap_or_default()
+                    .split(':')
+                    .map(str::to_string)
+                    .collect();
+                paths.extend(LOADER_SEARCH_PATHS.iter().map(|p| p.to_string()));
+                return paths;
+            }
+            None => library,
+        };
+        
+        // The library's directory, and the real file's when it is a link to a versioned one
+        [Some(resolved.clone()), std::fs::canonicalize(&resolved).ok()]
+            .into_iter()
+            .flatten()
+            .filter_map(|path| Some(path.parent()?.to_string_lossy().into_owned()))
+            .collect()
+    }
+    
+    pub fn apply_seccomp(allow_syscalls: &[String]) -> Result<()> {
+        let mut rules: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
+        
+        for name in DENIED_SYSCALLS {
+            if allow_syscalls.iter().any(|allowed| allowed == name) {
+                info!("🔓 Syscall {} allowed by sandbox.allow_syscalls", name);
+                continue;
+            }
+            
+            match syscall_number(name) {
+                Some(nr) => {
+                    rules.insert(nr, vec![]);
+                }
+                None => warn!("Syscall {} not available on this architecture", name),
+            }
+        }
+        
+        let arch = std::env::consts::ARCH
+            .try_into()
+            .map_err(|e| anyhow!("Unsupported seccomp architecture: {:?}", e))?;
+        
+        let filter = SeccompFilter::new(
+            rules,
+            SeccompAction::Allow,
+            SeccompAction::Errno(libc::EPERM as u32),
+            arch,
+        )?;
+        let program: BpfProgram = filter.try_into()?;
+        seccompiler::apply_filter(&program).context("Failed to install seccomp filter")?;
+        
+        info!("🔒 Seccomp filter installed");
+        Ok(())
+    }
+    
+    fn syscall_number(name: &str) -> Option<i64> {
+        let nr = match name {
+            "ptrace" => libc::SYS_ptrace,
+            "process_vm_readv" => libc::SYS_process_vm_readv,
+            "process_vm_writev" => libc::SYS_process_vm_writev,
+            "execve" => libc::SYS_execve,
+            "execveat" => libc::SYS_execveat,
+            "mount" => libc::SYS_mount,
+            "umount2" => libc::SYS_umount2,
+            "pivot_root" => libc::SYS_pivot_root,
+            "chroot" => libc::SYS_chroot,
+            "kexec_load" => libc::SYS_kexec_load,
+            "init_module" => libc::SYS_init_module,
+            "finit_module" => libc::SYS_finit_module,
+            "delete_module" => libc::SYS_delete_module,
+            "bpf" => libc::SYS_bpf,
+            "perf_event_open" => libc::SYS_perf_event_open,
+            "keyctl" => libc::SYS_keyctl,
+            "add_key" => libc::SYS_add_key,
+            "request_key" => libc::SYS_request_key,
+            "swapon" => libc::SYS_swapon,
+            "swapoff" => libc::SYS_swapoff,
+            "reboot" => libc::SYS_reboot,
+            "setns" => libc::SYS_setns,
+            "unshare" => libc::SYS_unshare,
+            "userfaultfd" => libc::SYS_userfaultfd,
+            _ => return None,
+        };
+        Some(nr)
+    }
+}
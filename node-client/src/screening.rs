@@ -0,0 +1,313 @@
+//! HTTP screening API and webhook delivery for external customers
+
+use anyhow::Result;
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use crate::ai::{ThreatDetectionResult, ThreatDetector};
+use crate::alert_cache::{VerifiedAlert, VerifiedAlertCache};
+use crate::config::{AIConfig, NodeConfig, ScreeningConfig};
+use crate::dag::Transaction;
+use crate::history::{AddressRisk, ReportHistory, ReportHistoryEntry};
+use crate::storage::NodeStorage;
+use crate::tenant::{PolicyDecision, Tenant, TenantRegistry, TenantUsage};
+use crate::threat::ThreatClass;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreeningResponse {
+    pub tenant_id: String,
+    pub transaction_id: String,
+    pub policy: PolicyDecision,
+    /// Whether the verdict crosses the tenant's threshold (or the tenant denylists an address)
+    pub flagged: bool,
+    pub verdict: ThreatDetectionResult,
+}
+
+#[derive(Debug, Serialize)]
+struct WebhookAccepted {
+    accepted: usize,
+}
+
+struct ScreeningState {
+    registry: TenantRegistry,
+    /// `None` on read replicas, which answer queries but never screen
+    detector: Option<Arc<ThreatDetector>>,
+    alert_cache: Option<Arc<VerifiedAlertCache>>,
+    history: Arc<ReportHistory>,
+    /// Supplies the per-chain default threshold for tenants without their own
+    ai: AIConfig,
+    http: reqwest::Client,
+}
+
+pub struct ScreeningServer {
+    config: ScreeningConfig,
+    state: Arc<ScreeningState>,
+}
+
+impl ScreeningServer {
+    pub fn new(
+        config: &NodeConfig,
+        detector: Option<Arc<ThreatDetector>>,
+        alert_cache: Option<Arc<VerifiedAlertCache>>,
+        history: Arc<ReportHistory>,
+        storage: Arc<NodeStorage>,
+    ) -> Result<Self> {
+        let registry = TenantRegistry::new(&config.screening, storage)?;
+        
+        Ok(Self {
+            config: config.screening.clone(),
+            state: Arc::new(ScreeningState {
+                registry,
+                detector,
+                alert_cache,
+                history,
+                ai: config.ai.clone(),
+                http: reqwest::Client::builder().timeout(Duration::from_secs(10)).build()?,
+            }),
+        })
+    }
+    
+    pub async fn start(&self) -> Result<()> {
+        let app = Router::new()
+            .route("/v1/screen", post(screen))
+            .route("/v1/webhook", post(screen_webhook))
+            .route("/v1/alerts/:address", get(address_alerts))
+            .route("/v1/history/:address", get(address_history))
+            .route("/v1/risk/:address", get(address_risk))
+            .route("/v1/usage", get(usage))
+            .with_state(Arc::clone(&self.state));
+        
+        let addr = std::net::SocketAddr::from(([0, 0, 0, 0], self.config.listen_port));
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        info!("🛡️ Screening API listening on http://{}", addr);
+        
+        // Persist billing counters periodically, and once more when the server stops
+        let state = Arc::clone(&self.state);
+        let flush_interval = Duration::from_secs(self.config.usage_flush_interval_secs.max(1));
+        let flusher = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(flush_interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = state.registry.flush_usage() {
+                    error!("Failed to flush tenant usage: {}", e);
+                }
+            }
+        });
+        
+        let result = axum::serve(listener, app).await;
+        flusher.abort();
+        self.state.registry.flush_usage()?;
+        
+        Ok(result?)
+    }
+}
+
+impl ScreeningState {
+    fn authenticate(&self, headers: &HeaderMap) -> Result<Arc<Tenant>, ApiError> {
+        let key = headers
+            .get("x-api-key")
+            .and_then(|v| v.to_str().ok())
+            .or_else(|| {
+                headers
+                    .get(axum::http::header::AUTHORIZATION)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.strip_prefix("Bearer "))
+            })
+            .ok_or(ApiError::Unauthorized)?;
+        
+        self.registry.authenticate(key).ok_or(ApiError::Unauthorized)
+    }
+    
+    fn network_alerts(&self, address: &str) -> Vec<VerifiedAlert> {
+        self.alert_cache
+            .as_ref()
+            .map(|cache| cache.lookup(address))
+            .unwrap_or_default()
+    }
+    
+    fn acquire(&self, tenant: &Tenant, count: usize) -> Result<(), ApiError> {
+        if tenant.try_acquire(count as u32) {
+            Ok(())
+        } else {
+            self.registry.record_rate_limited(tenant);
+            Err(ApiError::RateLimited)
+        }
+    }
+    
+    /// Apply the tenant's lists, then its threshold to the detector's verdict
+    async fn screen(&self, tenant: &Tenant, transaction: &Transaction) -> Result<ScreeningResponse, ApiError> {
+        let policy = tenant.policy_for(transaction);
+        
+        let (verdict, flagged, outcome) = match policy {
+            PolicyDecision::Denylisted => (policy_verdict(ThreatClass::from("denylisted"), 1.0, "Address is on the tenant denylist", "Block transaction"), true, "denylisted"),
+            PolicyDecision::Allowlisted => (policy_verdict(ThreatClass::Safe, 0.0, "Address is on the tenant allowlist", "None"), false, "allowlisted"),
+            PolicyDecision::Screen => {
+                let detector = self.detector.as_ref().ok_or(ApiError::ReadOnly)?;
+                let verdict = detector.detect_threat(transaction).await?;
+                let flagged = !verdict.threat_type.is_safe()
+                    && verdict.confidence >= tenant.confidence_threshold(self.ai.confidence_threshold_for(transaction.chain_id));
+                (verdict, flagged, if flagged { "flagged" } else { "clean" })
+            }
+        };
+        
+        self.registry.record_screening(tenant, flagged, outcome);
+        debug!("🛡️ Tenant {} screened {}: {}", tenant.id(), transaction.id, outcome);
+        
+        Ok(ScreeningResponse {
+            tenant_id: tenant.id().to_string(),
+            transaction_id: transaction.id.clone(),
+            policy,
+            flagged,
+            verdict,
+        })
+    }
+}
+
+fn policy_verdict(threat_type: ThreatClass, confidence: f32, explanation: &str, action: &str) -> ThreatDetectionResult {
+    ThreatDetectionResult {
+        threat_type,
+        confidence,
+        risk_score: (confidence * 100.0) as u32,
+        explanation: explanation.to_string(),
+        recommended_action: action.to_string(),
+        ensemble: None,
+        approval: None,
+    }
+}
+
+async fn screen(
+    State(state): State<Arc<ScreeningState>>,
+    headers: HeaderMap,
+    Json(transaction): Json<Transaction>,
+) -> Result<Json<ScreeningResponse>, ApiError> {
+    let tenant = state.authenticate(&headers)?;
+    state.acquire(&tenant, 1)?;
+    
+    Ok(Json(state.screen(&tenant, &transaction).await?))
+}
+
+/// Accept a batch for asynchronous screening; results are POSTed to the tenant's webhook URL
+async fn screen_webhook(
+    State(state): State<Arc<ScreeningState>>,
+    headers: HeaderMap,
+    Json(transactions): Json<Vec<Transaction>>,
+) -> Result<(StatusCode, Json<WebhookAccepted>), ApiError> {
+    let tenant = state.authenticate(&headers)?;
+    if state.detector.is_none() {
+        return Err(ApiError::ReadOnly);
+    }
+    let url = tenant
+        .webhook_url()
+        .ok_or_else(|| ApiError::BadRequest("Tenant has no webhook_url configured".to_string()))?
+        .to_string();
+    state.acquire(&tenant, transactions.len())?;
+    
+    let accepted = transactions.len();
+    let task_state = Arc::clone(&state);
+    tokio::spawn(async move {
+        let mut results = Vec::with_capacity(transactions.len());
+        for transaction in &transactions {
+            match task_state.screen(&tenant, transaction).await {
+                Ok(response) => results.push(response),
+                Err(ApiError::Internal(e)) => warn!("Screening {} for tenant {} failed: {}", transaction.id, tenant.id(), e),
+                Err(_) => warn!("Screening {} for tenant {} failed", transaction.id, tenant.id()),
+            }
+        }
+        
+        let delivery = task_state.http
+            .post(&url)
+            .header("x-dagshield-tenant", tenant.id())
+            .json(&results)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = delivery {
+            error!("❌ Webhook delivery to tenant {} failed: {}", tenant.id(), e);
+        }
+    });
+    
+    Ok((StatusCode::ACCEPTED, Json(WebhookAccepted { accepted })))
+}
+
+/// Network-verified alerts against an address, served from the local alert cache
+async fn address_alerts(
+    State(state): State<Arc<ScreeningState>>,
+    headers: HeaderMap,
+    Path(address): Path<String>,
+) -> Result<Json<Vec<VerifiedAlert>>, ApiError> {
+    let tenant = state.authenticate(&headers)?;
+    state.acquire(&tenant, 1)?;
+    
+    Ok(Json(state.network_alerts(&address)))
+}
+
+/// Threat reports this node (or, on a replica, its primary) submitted against an address
+async fn address_history(
+    State(state): State<Arc<ScreeningState>>,
+    headers: HeaderMap,
+    Path(address): Path<String>,
+) -> Result<Json<Vec<ReportHistoryEntry>>, ApiError> {
+    let tenant = state.authenticate(&headers)?;
+    state.acquire(&tenant, 1)?;
+    
+    Ok(Json(state.history.reports(&address)?))
+}
+
+async fn address_risk(
+    State(state): State<Arc<ScreeningState>>,
+    headers: HeaderMap,
+    Path(address): Path<String>,
+) -> Result<Json<AddressRisk>, ApiError> {
+    let tenant = state.authenticate(&headers)?;
+    state.acquire(&tenant, 1)?;
+    
+    Ok(Json(state.history.risk(&address, &state.network_alerts(&address))?))
+}
+
+async fn usage(
+    State(state): State<Arc<ScreeningState>>,
+    headers: HeaderMap,
+) -> Result<Json<TenantUsage>, ApiError> {
+    let tenant = state.authenticate(&headers)?;
+    Ok(Json(state.registry.usage(&tenant)?))
+}
+
+enum ApiError {
+    Unauthorized,
+    RateLimited,
+    ReadOnly,
+    BadRequest(String),
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        ApiError::Internal(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "Invalid or missing API key".to_string()),
+            ApiError::RateLimited => (StatusCode::TOO_MANY_REQUESTS, "Tenant rate limit exceeded".to_string()),
+            ApiError::ReadOnly => (StatusCode::SERVICE_UNAVAILABLE, "This node is a read replica and does not screen".to_string()),
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::Internal(e) => {
+                error!("Screening request failed: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Screening failed".to_string())
+            }
+        };
+        
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
@@ -0,0 +1,310 @@
+//! Service manager integration: systemd readiness and watchdog notifications, the Windows
+//! service control handler, and the process exit codes service managers act on
+
+use anyhow::Result;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, info, warn};
+
+/// Clean shutdown
+pub const EXIT_SUCCESS: i32 = 0;
+/// Runtime failure; a restart may help
+pub const EXIT_FAILURE: i32 = 1;
+/// Invalid or unreadable configuration (sysexits `EX_CONFIG`); restarting will not help
+pub const EXIT_CONFIG: i32 = 78;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceEvent {
+    Stop,
+    Pause,
+    Resume,
+}
+
+/// The service manager the node runs under, or a terminal when there is none.
+///
+/// Create it with [`ServiceHost::from_env`] before the sandbox is applied, so the
+/// systemd notification socket is opened while the process is still unrestricted.
+pub struct ServiceHost {
+    #[cfg(target_os = "linux")]
+    notifier: Option<Arc<systemd::Notifier>>,
+    #[cfg(windows)]
+    status: Option<windows::StatusReporter>,
+    /// Only the Windows service handler sends; elsewhere it keeps the channel open
+    #[cfg_attr(not(windows), allow(dead_code))]
+    events_tx: mpsc::UnboundedSender<ServiceEvent>,
+    events_rx: Mutex<mpsc::UnboundedReceiver<ServiceEvent>>,
+}
+
+impl ServiceHost {
+    pub fn from_env() -> Self {
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        
+        Self {
+            #[cfg(target_os = "linux")]
+            notifier: systemd::Notifier::from_env().map(Arc::new),
+            #[cfg(windows)]
+            status: None,
+            events_tx,
+            events_rx: Mutex::new(events_rx),
+        }
+    }
+    
+    /// The node finished starting up
+    pub fn ready(&self) {
+        #[cfg(target_os = "linux")]
+        if let Some(notifier) = &self.notifier {
+            notifier.notify("READY=1\nSTATUS=Running");
+            notifier.start_watchdog();
+        }
+        #[cfg(windows)]
+        if let Some(status) = &self.status {
+            status.running();
+        }
+        debug!("🧭 Reported readiness to the service manager");
+    }
+    
+    /// The node is shutting down
+    pub fn stopping(&self) {
+        #[cfg(target_os = "linux")]
+        if let Some(notifier) = &self.notifier {
+            notifier.notify("STOPPING=1");
+        }
+        #[cfg(windows)]
+        if let Some(status) = &self.status {
+            status.stop_pending();
+        }
+    }
+    
+    /// The node paused or resumed its work
+    pub fn paused(&self, paused: bool) {
+        #[cfg(target_os = "linux")]
+        if let Some(notifier) = &self.notifier {
+            notifier.notify(if paused { "STATUS=Paused" } else { "STATUS=Running" });
+        }
+        #[cfg(windows)]
+        if let Some(status) = &self.status {
+            if paused {
+                status.paused();
+            } else {
+                status.running();
+            }
+        }
+    }
+    
+    /// Next request from the service manager or a terminal signal
+    pub async fn next_event(&self) -> Result<ServiceEvent> {
+        let mut events = self.events_rx.lock().await;
+        
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => {
+                result?;
+                Ok(ServiceEvent::Stop)
+            }
+            result = terminate_signal() => {
+                result?;
+                Ok(ServiceEvent::Stop)
+            }
+            Some(event) = events.recv() => Ok(event),
+        }
+    }
+}
+
+/// Service managers stop units with SIGTERM, terminals with SIGINT
+#[cfg(unix)]
+async fn terminate_signal() -> Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+    
+    signal(SignalKind::terminate())?.recv().await;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn terminate_signal() -> Result<()> {
+    std::future::pending().await
+}
+
+#[cfg(target_os = "linux")]
+mod systemd {
+    use super::*;
+    use std::os::unix::net::UnixDatagram;
+    use std::time::Duration;
+    
+    /// sd_notify(3) over the socket systemd passes in `NOTIFY_SOCKET`
+    pub struct Notifier {
+        socket: UnixDatagram,
+        path: String,
+        watchdog_interval: Option<Duration>,
+    }
+    
+    impl Notifier {
+        pub fn from_env() -> Option<Self> {
+            let path = std::env::var("NOTIFY_SOCKET").ok()?;
+            let socket = match UnixDatagram::unbound() {
+                Ok(socket) => socket,
+                Err(e) => {
+                    warn!("Failed to open systemd notification socket: {}", e);
+                    return None;
+                }
+            };
+            
+            // Only the main process is watched; WATCHDOG_PID is unset when that is implied
+            let watchdog_for_us = std::env::var("WATCHDOG_PID")
+                .map(|pid| pid == std::process::id().to_string())
+                .unwrap_or(true);
+            let watchdog_interval = std::env::var("WATCHDOG_USEC")
+                .ok()
+                .and_then(|usec| usec.parse::<u64>().ok())
+                .filter(|_| watchdog_for_us)
+                .map(|usec| Duration::from_micros(usec / 2));
+            
+            info!("🧭 Running under systemd (watchdog: {})",
+                  watchdog_interval.map_or("off".to_string(), |d| format!("every {:?}", d)));
+            Some(Self { socket, path, watchdog_interval })
+        }
+        
+        pub fn notify(&self, state: &str) {
+            let result = match self.path.strip_prefix('@') {
+                Some(name) => {
+                    use std::os::linux::net::SocketAddrExt;
+                    std::os::unix::net::SocketAddr::from_abstract_name(name)
+                        .and_then(|addr| self.socket.send_to_addr(state.as_bytes(), &addr))
+                }
+                None => self.socket.send_to(state.as_bytes(), &self.path),
+            };
+            if let Err(e) = result {
+                warn!("Failed to notify systemd ({}): {}", state.replace('\n', " "), e);
+            }
+        }
+        
+        /// Ping the watchdog at half its timeout for as long as the runtime keeps scheduling tasks
+        pub fn start_watchdog(self: &Arc<Self>) {
+            let Some(interval) = self.watchdog_interval else {
+                return;
+            };
+            let notifier = Arc::clone(self);
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(interval);
+                loop {
+                    ticker.tick().await;
+                    notifier.notify("WATCHDOG=1");
+                }
+            });
+        }
+    }
+}
+
+/// Windows service mode (`dagshield-node --service`), e.g. after
+/// `sc create DAGShieldNode binPath= "C:\dagshield\dagshield-node.exe --service --config C:\dagshield\config.toml"`
+#[cfg(windows)]
+pub mod windows {
+    use super::*;
+    use std::ffi::OsString;
+    use std::sync::Mutex as StdMutex;
+    use std::time::Duration;
+    use windows_service::service::{
+        ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus, ServiceType,
+    };
+    use windows_service::service_control_handler::{self, ServiceControlHandlerResult, ServiceStatusHandle};
+    use windows_service::{define_windows_service, service_dispatcher};
+    
+    pub const SERVICE_NAME: &str = "DAGShieldNode";
+    
+    type Runner = Box<dyn FnOnce(ServiceHost) -> i32 + Send>;
+    
+    static RUNNER: StdMutex<Option<Runner>> = StdMutex::new(None);
+    
+    define_windows_service!(ffi_service_main, service_main);
+    
+    #[derive(Clone)]
+    pub struct StatusReporter {
+        handle: ServiceStatusHandle,
+    }
+    
+    impl StatusReporter {
+        fn set(&self, state: ServiceState, exit_code: ServiceExitCode) {
+            let controls_accepted = match state {
+                ServiceState::Running | ServiceState::Paused => {
+                    ServiceControlAccept::STOP | ServiceControlAccept::PAUSE_CONTINUE | ServiceControlAccept::SHUTDOWN
+                }
+                _ => ServiceControlAccept::empty(),
+            };
+            let status = ServiceStatus {
+                service_type: ServiceType::OWN_PROCESS,
+                current_state: state,
+                controls_accepted,
+                exit_code,
+                checkpoint: 0,
+                wait_hint: Duration::from_secs(30),
+                process_id: None,
+            };
+            if let Err(e) = self.handle.set_service_status(status) {
+                warn!("Failed to report service status: {}", e);
+            }
+        }
+        
+        pub fn running(&self) {
+            self.set(ServiceState::Running, ServiceExitCode::Win32(0));
+        }
+        
+        pub fn paused(&self) {
+            self.set(ServiceState::Paused, ServiceExitCode::Win32(0));
+        }
+        
+        pub fn stop_pending(&self) {
+            self.set(ServiceState::StopPending, ServiceExitCode::Win32(0));
+        }
+        
+        fn stopped(&self, code: i32) {
+            let exit_code = match code {
+                EXIT_SUCCESS => ServiceExitCode::Win32(0),
+                code => ServiceExitCode::ServiceSpecific(code as u32),
+            };
+            self.set(ServiceState::Stopped, exit_code);
+        }
+    }
+    
+    /// Hand the process to the service control manager; blocks until the service stops
+    pub fn run(runner: impl FnOnce(ServiceHost) -> i32 + Send + 'static) -> Result<()> {
+        *RUNNER.lock().expect("runner lock poisoned") = Some(Box::new(runner));
+        service_dispatcher::start(SERVICE_NAME, ffi_service_main)?;
+        Ok(())
+    }
+    
+    fn service_main(_arguments: Vec<OsString>) {
+        let mut host = ServiceHost::from_env();
+        let events = host.events_tx.clone();
+        
+        let handler = move |control| match control {
+            ServiceControl::Stop | ServiceControl::Shutdown => {
+                let _ = events.send(ServiceEvent::Stop);
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Pause => {
+                let _ = events.send(ServiceEvent::Pause);
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Continue => {
+                let _ = events.send(ServiceEvent::Resume);
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+            _ => ServiceControlHandlerResult::NotImplemented,
+        };
+        
+        let reporter = match service_control_handler::register(SERVICE_NAME, handler) {
+            Ok(handle) => StatusReporter { handle },
+            Err(e) => {
+                warn!("Failed to register service control handler: {}", e);
+                return;
+            }
+        };
+        reporter.set(ServiceState::StartPending, ServiceExitCode::Win32(0));
+        host.status = Some(reporter.clone());
+        
+        let code = match RUNNER.lock().expect("runner lock poisoned").take() {
+            Some(runner) => runner(host),
+            None => EXIT_FAILURE,
+        };
+        reporter.stopped(code);
+    }
+}
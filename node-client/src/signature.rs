@@ -0,0 +1,32 @@
+//! Verification of payloads signed by trusted operator keys
+
+use anyhow::{anyhow, bail, Result};
+use ethers::types::{Address, Signature};
+
+/// Parse a list of trusted signer addresses from config
+pub fn parse_signers(signers: &[String]) -> Result<Vec<Address>> {
+    signers
+        .iter()
+        .map(|s| s.parse::<Address>().map_err(|e| anyhow!("Invalid trusted signer '{}': {}", s, e)))
+        .collect()
+}
+
+/// Verify an Ethereum personal_sign signature over `message` and return the signer,
+/// failing unless it is one of `trusted_signers`
+pub fn verify_signed_payload(message: &[u8], signature: &str, trusted_signers: &[Address]) -> Result<Address> {
+    if trusted_signers.is_empty() {
+        bail!("No trusted signers configured; refusing unsigned-equivalent payload");
+    }
+    
+    let signature: Signature = signature
+        .trim_start_matches("0x")
+        .parse()
+        .map_err(|e| anyhow!("Malformed signature: {}", e))?;
+    
+    let signer = signature.recover(message)?;
+    if !trusted_signers.contains(&signer) {
+        bail!("Payload signed by untrusted key {:?}", signer);
+    }
+    
+    Ok(signer)
+}
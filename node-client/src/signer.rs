@@ -0,0 +1,335 @@
+//! What signs for the node
+//!
+//! `blockchain.signer` picks the backend behind every transaction and signed message: the
+//! in-memory key from `private_key` or `key`, an AWS or Google Cloud KMS key, or a remote JSON-RPC
+//! signer such as Clef or Web3Signer, so nodes with real stake need no hot key on the device. A
+//! Ledger signs through Clef. KMS backends are behind the `aws-kms` and `gcp-kms` features.
+//!
+//! KMS keys return DER signatures without a recovery ID; it is found by recovering the signer
+//! with each candidate, against the address the key is known to have.
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::transaction::eip712::Eip712;
+use ethers::types::{Address, Bytes, Signature, H256, U256};
+use ethers::utils::rlp::Rlp;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::config::{BlockchainConfig, SignerConfig};
+use crate::contract_guard::parse_checksummed_address;
+use crate::keys;
+
+/// secp256k1 group order, for normalizing KMS signatures to low-s
+const SECP256K1_ORDER: &str = "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141";
+
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct SigningError(String);
+
+fn fail(error: impl std::fmt::Display) -> SigningError {
+    SigningError(error.to_string())
+}
+
+#[derive(Debug)]
+enum Backend {
+    Local(LocalWallet),
+    #[cfg(feature = "aws-kms")]
+    AwsKms(ethers::signers::AwsSigner),
+    #[cfg(feature = "gcp-kms")]
+    GcpKms(GcpKmsSigner),
+    Remote(RemoteSigner),
+}
+
+/// The node's signer, whichever backend it is; clones share the backend
+#[derive(Debug, Clone)]
+pub struct NodeSigner {
+    backend: Arc<Backend>,
+    address: Address,
+    chain_id: u64,
+}
+
+impl NodeSigner {
+    pub async fn connect(config: &BlockchainConfig) -> anyhow::Result<Self> {
+        if !matches!(config.signer, SignerConfig::Local) && (config.key.is_some() || !config.private_key.is_empty()) {
+            warn!("⚠️ blockchain.private_key and blockchain.key are ignored with the {:?} signer", config.signer);
+        }
+        let backend = match &config.signer {
+            SignerConfig::Local => Backend::Local(keys::load_wallet(config)?),
+            #[cfg(feature = "aws-kms")]
+            SignerConfig::AwsKms { key_id, region } => {
+                let region = match region.as_str() {
+                    "" => rusoto_core::Region::default(),
+                    region => region.parse()?,
+                };
+                let kms = rusoto_kms::KmsClient::new(region);
+                Backend::AwsKms(
+                    ethers::signers::AwsSigner::new(kms, key_id, config.chain_id)
+                        .await
+                        .with_context(|| format!("Failed to load AWS KMS key {}", key_id))?,
+                )
+            }
+            #[cfg(feature = "gcp-kms")]
+            SignerConfig::GcpKms { key_version, address } => {
+                Backend::GcpKms(GcpKmsSigner::connect(key_version, parse_checksummed_address(address)?).await?)
+            }
+            SignerConfig::Remote { url, address, auth_token_env } => {
+                let auth_token = match auth_token_env.as_str() {
+                    "" => None,
+                    var => Some(std::env::var(var).with_context(|| format!("Signer token variable {} is not set", var))?),
+                };
+                Backend::Remote(RemoteSigner {
+                    url: url.clone(),
+                    address: parse_checksummed_address(address)?,
+                    auth_token,
+                    http: reqwest::Client::new(),
+                })
+            }
+            #[allow(unreachable_patterns)]
+            other => bail!("This build has no {:?} signer; rebuild with its feature enabled", other),
+        };
+        
+        let address = match &backend {
+            Backend::Local(wallet) => wallet.address(),
+            #[cfg(feature = "aws-kms")]
+            Backend::AwsKms(aws) => aws.address(),
+            #[cfg(feature = "gcp-kms")]
+            Backend::GcpKms(gcp) => gcp.address,
+            Backend::Remote(remote) => remote.address,
+        };
+        if !matches!(backend, Backend::Local(_)) {
+            info!("🔏 Signing as {:?} with the {:?} signer", address, config.signer);
+        }
+        Ok(Self {
+            backend: Arc::new(backend),
+            address,
+            chain_id: config.chain_id,
+        })
+    }
+}
+
+#[async_trait]
+impl Signer for NodeSigner {
+    type Error = SigningError;
+    
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(&self, message: S) -> Result<Signature, Self::Error> {
+        match &*self.backend {
+            Backend::Local(wallet) => wallet.sign_message(message).await.map_err(fail),
+            #[cfg(feature = "aws-kms")]
+            Backend::AwsKms(aws) => aws.sign_message(message).await.map_err(fail),
+            #[cfg(feature = "gcp-kms")]
+            Backend::GcpKms(gcp) => gcp.sign_digest(ethers::utils::hash_message(message)).await,
+            Backend::Remote(remote) => remote.sign_message(message.as_ref()).await,
+        }
+    }
+    
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        let mut tx = tx.clone();
+        if tx.chain_id().is_none() {
+            tx.set_chain_id(self.chain_id);
+        }
+        match &*self.backend {
+            Backend::Local(wallet) => wallet.sign_transaction(&tx).await.map_err(fail),
+            #[cfg(feature = "aws-kms")]
+            Backend::AwsKms(aws) => aws.sign_transaction(&tx).await.map_err(fail),
+            #[cfg(feature = "gcp-kms")]
+            Backend::GcpKms(gcp) => {
+                let chain_id = tx.chain_id().map_or(self.chain_id, |chain_id| chain_id.as_u64());
+                let mut signature = gcp.sign_digest(tx.sighash()).await?;
+                // EIP-155
+                signature.v = signature.v - 27 + 35 + chain_id * 2;
+                Ok(signature)
+            }
+            Backend::Remote(remote) => remote.sign_transaction(&tx).await,
+        }
+    }
+    
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(&self, payload: &T) -> Result<Signature, Self::Error> {
+        match &*self.backend {
+            Backend::Local(wallet) => wallet.sign_typed_data(payload).await.map_err(fail),
+            #[cfg(feature = "aws-kms")]
+            Backend::AwsKms(aws) => aws.sign_typed_data(payload).await.map_err(fail),
+            #[cfg(feature = "gcp-kms")]
+            Backend::GcpKms(gcp) => gcp.sign_digest(H256(payload.encode_eip712().map_err(fail)?)).await,
+            // Only the struct hash is available here, not the typed data such signers display
+            Backend::Remote(_) => Err(SigningError("The remote signer does not sign typed data".to_string())),
+        }
+    }
+    
+    fn address(&self) -> Address {
+        self.address
+    }
+    
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+    
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        Self { chain_id: chain_id.into(), ..self }
+    }
+}
+
+/// Ethereum JSON-RPC signing (`eth_sign`, `eth_signTransaction`), as Clef and Web3Signer serve it
+#[derive(Debug)]
+struct RemoteSigner {
+    url: String,
+    address: Address,
+    auth_token: Option<String>,
+    http: reqwest::Client,
+}
+
+impl RemoteSigner {
+    async fn sign_message(&self, message: &[u8]) -> Result<Signature, SigningError> {
+        let result = self.call("eth_sign", json!([self.address, Bytes::from(message.to_vec())])).await?;
+        let signature: Bytes = serde_json::from_value(result).map_err(fail)?;
+        Signature::try_from(signature.as_ref()).map_err(fail)
+    }
+    
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, SigningError> {
+        let mut tx = tx.clone();
+        tx.set_from(self.address);
+        let result = self.call("eth_signTransaction", json!([tx])).await?;
+        // Clef wraps the raw transaction in an object
+        let raw = result.get("raw").cloned().unwrap_or(result);
+        let raw: Bytes = serde_json::from_value(raw).map_err(fail)?;
+        let (signed, signature) = TypedTransaction::decode_signed(&Rlp::new(&raw)).map_err(fail)?;
+        if signed.sighash() != tx.sighash() {
+            return Err(SigningError("The remote signer signed a different transaction".to_string()));
+        }
+        Ok(signature)
+    }
+    
+    async fn call(&self, method: &str, params: Value) -> Result<Value, SigningError> {
+        let mut request = self.http
+            .post(&self.url)
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }));
+        if let Some(token) = &self.auth_token {
+            request = request.bearer_auth(token);
+        }
+        let mut response: Value = request
+            .send()
+            .await
+            .map_err(fail)?
+            .error_for_status()
+            .map_err(fail)?
+            .json()
+            .await
+            .map_err(fail)?;
+        if let Some(error) = response.get("error") {
+            return Err(SigningError(format!("Remote signer refused {}: {}", method, error)));
+        }
+        Ok(response["result"].take())
+    }
+}
+
+/// A Cloud KMS `EC_SIGN_SECP256K1_SHA256` key version, signing 32-byte digests as they are
+#[cfg(feature = "gcp-kms")]
+struct GcpKmsSigner {
+    key_version: String,
+    address: Address,
+    auth: Arc<dyn google_cloud_token::TokenSource>,
+    http: reqwest::Client,
+}
+
+#[cfg(feature = "gcp-kms")]
+impl std::fmt::Debug for GcpKmsSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GcpKmsSigner")
+            .field("key_version", &self.key_version)
+            .field("address", &self.address)
+            .finish()
+    }
+}
+
+#[cfg(feature = "gcp-kms")]
+impl GcpKmsSigner {
+    async fn connect(key_version: &str, address: Address) -> anyhow::Result<Self> {
+        use google_cloud_token::TokenSourceProvider;
+
+        let signer = Self {
+            key_version: key_version.to_string(),
+            address,
+            auth: google_cloud_auth::token::DefaultTokenSourceProvider::new(
+                google_cloud_auth::project::Config::default().with_scopes(&["https://www.googleapis.com/auth/cloudkms"]),
+            )
+            .await
+            .context("No Google Cloud credentials")?
+            .token_source(),
+            http: reqwest::Client::new(),
+        };
+        // Fails here rather than on the first report when the key is not the configured address's
+        signer.sign_digest(ethers::utils::hash_message("dagshield signer check")).await?;
+        Ok(signer)
+    }
+    
+    async fn sign_digest(&self, digest: H256) -> Result<Signature, SigningError> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+        
+        // Already carries the `Bearer` scheme
+        let authorization = self.auth.token().await.map_err(fail)?;
+        let response: Value = self.http
+            .post(format!("https://cloudkms.googleapis.com/v1/{}:asymmetricSign", self.key_version))
+            .header(reqwest::header::AUTHORIZATION, authorization)
+            .json(&json!({ "digest": { "sha256": STANDARD.encode(digest.as_bytes()) } }))
+            .send()
+            .await
+            .map_err(fail)?
+            .error_for_status()
+            .map_err(fail)?
+            .json()
+            .await
+            .map_err(fail)?;
+        let der = response["signature"]
+            .as_str()
+            .ok_or_else(|| SigningError("Cloud KMS returned no signature".to_string()))
+            .and_then(|signature| STANDARD.decode(signature).map_err(fail))?;
+        recoverable_signature(&der, digest, self.address)
+    }
+}
+
+/// A KMS key's DER signature over `digest` as a low-s signature with `v` 27 or 28
+#[cfg_attr(not(feature = "gcp-kms"), allow(dead_code))]
+fn recoverable_signature(der: &[u8], digest: H256, address: Address) -> Result<Signature, SigningError> {
+    let (r, mut s) = der_signature(der).ok_or_else(|| SigningError("Malformed DER signature".to_string()))?;
+    let order = U256::from_str_radix(SECP256K1_ORDER, 16).map_err(fail)?;
+    if s > order / 2 {
+        s = order - s;
+    }
+    for v in [27, 28] {
+        let signature = Signature { r, s, v };
+        if signature.recover(digest).ok() == Some(address) {
+            return Ok(signature);
+        }
+    }
+    Err(SigningError(format!("The KMS key does not belong to {:?}", address)))
+}
+
+/// `SEQUENCE { INTEGER r, INTEGER s }`
+fn der_signature(der: &[u8]) -> Option<(U256, U256)> {
+    let [0x30, length, body @ ..] = der else {
+        return None;
+    };
+    if *length as usize != body.len() {
+        return None;
+    }
+    let (r, rest) = der_integer(body)?;
+    let (s, rest) = der_integer(rest)?;
+    rest.is_empty().then_some((r, s))
+}
+
+fn der_integer(der: &[u8]) -> Option<(U256, &[u8])> {
+    let [0x02, length, rest @ ..] = der else {
+        return None;
+    };
+    let length = *length as usize;
+    if length == 0 || rest.len() < length {
+        return None;
+    }
+    let (value, rest) = rest.split_at(length);
+    // A leading zero keeps the integer positive
+    let value = value.strip_prefix(&[0]).unwrap_or(value);
+    (value.len() <= 32).then(|| (U256::from_big_endian(value), rest))
+}
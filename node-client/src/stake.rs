@@ -0,0 +1,183 @@
+//! The node's stake over its lifetime
+//!
+//! The contract requires `MIN_STAKE` of an active node. A slash can leave the stake below it,
+//! and a node that stays there for the contract's grace period may be deactivated by anyone.
+//! Every `check_interval_secs` the stake is compared against the minimum: within
+//! `warn_margin_percent` of it the node warns, below it the node logs when it can be deactivated.
+//! With `auto_restake` the node tops its stake back up to `stake_amount_gwei` from its own wallet as
+//! soon as it is within the margin.
+//!
+//! `dagshield-node stake` shows the stake; `stake add` and `stake withdraw` change it. Stake above
+//! the minimum can be withdrawn at any time; `stake withdraw --all` deactivates the node first.
+//! Changes are signed by the CLI process itself, never over the admin API, which only shows the
+//! stake: anyone who reaches its port could otherwise move the node's funds.
+
+use anyhow::{bail, Result};
+use axum::{http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use ethers::types::{Address, U256};
+use ethers::utils::{format_ether, parse_ether};
+use prometheus::{Gauge, IntGauge};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::blockchain::BlockchainClient;
+use crate::config::{NodeSettings, StakeConfig};
+use crate::metrics::register_once;
+use crate::status::Report;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakeStatus {
+    pub address: Address,
+    pub active: bool,
+    /// In wei
+    pub stake: U256,
+    /// The contract's `MIN_STAKE`, in wei
+    pub minimum: U256,
+    /// Unix seconds from which the contract lets anyone deactivate the node, while its stake is
+    /// below the minimum
+    pub deactivates_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakeChange {
+    /// In order of sending
+    pub tx_hashes: Vec<String>,
+    pub status: StakeStatus,
+}
+
+pub struct StakeManager {
+    config: StakeConfig,
+    /// `stake_amount_gwei`, what auto re-staking tops up to
+    target: U256,
+    blockchain: Arc<BlockchainClient>,
+    stake: Gauge,
+    deactivates_at: IntGauge,
+}
+
+impl StakeManager {
+    pub fn new(settings: &NodeSettings, blockchain: Arc<BlockchainClient>) -> Result<Self> {
+        static STAKE: OnceLock<Gauge> = OnceLock::new();
+        static DEACTIVATES_AT: OnceLock<IntGauge> = OnceLock::new();
+        let stake = register_once(&STAKE, || {
+            Gauge::new("dagshield_node_stake", "The node's stake on the contract, in tokens")
+        })?;
+        let deactivates_at = register_once(&DEACTIVATES_AT, || {
+            IntGauge::new(
+                "dagshield_node_stake_deactivation_timestamp_seconds",
+                "When the node can be deactivated for insufficient stake; 0 while it has enough",
+            )
+        })?;
+        
+        Ok(Self {
+            config: settings.stake.clone(),
+            target: U256::from(settings.stake_amount_gwei) * U256::exp10(9),
+            blockchain,
+            stake,
+            deactivates_at,
+        })
+    }
+    
+    pub async fn status(&self) -> Result<StakeStatus> {
+        let status = self.blockchain.stake_status().await?;
+        self.stake.set(format_ether(status.stake).parse().unwrap_or_default());
+        self.deactivates_at.set(status.deactivates_at.unwrap_or_default() as i64);
+        Ok(status)
+    }
+    
+    pub async fn add(&self, amount: &str) -> Result<StakeChange> {
+        let amount = parse_ether(amount)?;
+        if amount.is_zero() {
+            bail!("Nothing to add");
+        }
+        let tx_hash = self.blockchain.add_stake(amount).await?;
+        Ok(StakeChange {
+            tx_hashes: vec![tx_hash],
+            status: self.status().await?,
+        })
+    }
+    
+    /// Withdraw `amount` tokens, or leave the network and withdraw everything without one
+    pub async fn withdraw(&self, amount: Option<&str>) -> Result<StakeChange> {
+        let status = self.status().await?;
+        let mut tx_hashes = Vec::new();
+        let amount = match amount {
+            Some(amount) => {
+                let amount = parse_ether(amount)?;
+                if status.active && amount + status.minimum > status.stake {
+                    bail!("Only {} is above the minimum stake; withdraw everything to leave the network",
+                          format_ether(status.stake.saturating_sub(status.minimum)));
+                }
+                amount
+            }
+            None => {
+                if status.active {
+                    warn!("🚪 Deactivating node {:?} to withdraw its whole stake", status.address);
+                    tx_hashes.push(self.blockchain.deactivate_node().await?);
+                }
+                status.stake
+            }
+        };
+        if amount.is_zero() {
+            bail!("Nothing to withdraw");
+        }
+        
+        tx_hashes.push(self.blockchain.withdraw_stake(amount).await?);
+        Ok(StakeChange {
+            tx_hashes,
+            status: self.status().await?,
+        })
+    }
+    
+    /// Check the stake every `check_interval_secs` until the task is aborted
+    pub async fn start(&self) -> Result<()> {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.check_interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.check().await {
+                warn!("Stake check failed: {:#}", e);
+            }
+        }
+    }
+    
+    async fn check(&self) -> Result<()> {
+        let status = self.status().await?;
+        if !status.active {
+            if !status.stake.is_zero() {
+                warn!("🪙 Node {:?} is not active; {} stake can be withdrawn", status.address, format_ether(status.stake));
+            }
+            return Ok(());
+        }
+        
+        let threshold = status.minimum * (100 + self.config.warn_margin_percent) / 100;
+        if status.stake >= threshold {
+            return Ok(());
+        }
+        match status.deactivates_at {
+            Some(at) => error!("🪙 Stake {} is below the {} minimum; the node can be deactivated from {} unless it is topped up",
+                               format_ether(status.stake), format_ether(status.minimum),
+                               chrono::DateTime::from_timestamp(at as i64, 0).map_or(at.to_string(), |at| at.to_rfc3339())),
+            None => warn!("🪙 Stake {} is within {}% of the {} minimum", format_ether(status.stake),
+                          self.config.warn_margin_percent, format_ether(status.minimum)),
+        }
+        
+        if self.config.auto_restake {
+            let top_up = self.target.max(threshold) - status.stake;
+            info!("🪙 Re-staking {} to get back to {}", format_ether(top_up), format_ether(status.stake + top_up));
+            self.blockchain.add_stake(top_up).await?;
+            self.status().await?;
+        }
+        Ok(())
+    }
+}
+
+/// `GET /stake` shows the stake; changing it is CLI-only
+pub fn admin_routes(manager: Arc<StakeManager>) -> Router {
+    Router::new().route("/stake", get(move || async move {
+        match manager.status().await {
+            Ok(status) => Json(Report::new("stake", status)).into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)).into_response(),
+        }
+    }))
+}
@@ -0,0 +1,200 @@
+//! Periodic, signed per-chain processing statistics for the network stats aggregator
+
+use anyhow::Result;
+use parking_lot::Mutex;
+use prometheus::{IntCounterVec, Opts};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::blockchain::BlockchainClient;
+use crate::config::StatsReportingConfig;
+use crate::metrics::register_once;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ChainCounters {
+    pub transactions_analyzed: u64,
+    pub threats_flagged: u64,
+    pub threats_reported: u64,
+}
+
+impl ChainCounters {
+    fn merge(&mut self, other: &ChainCounters) {
+        self.transactions_analyzed += other.transactions_analyzed;
+        self.threats_flagged += other.threats_flagged;
+        self.threats_reported += other.threats_reported;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainStats {
+    pub chain_id: u64,
+    #[serde(flatten)]
+    pub counters: ChainCounters,
+}
+
+/// One reporting interval, as signed by the node wallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsReport {
+    pub node_address: String,
+    pub node_version: String,
+    pub period_start: u64,
+    pub period_end: u64,
+    pub uptime_secs: u64,
+    pub chains: Vec<ChainStats>,
+}
+
+/// The report as submitted: `payload` is the JSON-encoded [`StatsReport`], signed as-is
+#[derive(Debug, Serialize)]
+struct SignedStatsReport {
+    payload: String,
+    signature: String,
+}
+
+/// Counts per-chain processing and submits it to the stats aggregator every interval.
+///
+/// The contract's `getNetworkStats` only covers stake and alerts; processing volume is
+/// aggregated off-chain from these reports. Counts are rounded and low-volume chains left
+/// out before signing, so a report cannot be tied to individual transactions.
+pub struct StatsReporter {
+    config: StatsReportingConfig,
+    client: reqwest::Client,
+    blockchain: Arc<BlockchainClient>,
+    started: Instant,
+    period_start: Mutex<u64>,
+    counters: Mutex<BTreeMap<u64, ChainCounters>>,
+    submissions: IntCounterVec,
+}
+
+impl StatsReporter {
+    pub fn new(config: &StatsReportingConfig, blockchain: Arc<BlockchainClient>) -> Result<Self> {
+        static SUBMISSIONS: OnceLock<IntCounterVec> = OnceLock::new();
+        let submissions = register_once(&SUBMISSIONS, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_stats_submissions_total", "Per-chain stats reports submitted to the aggregator"),
+                &["result"],
+            )
+        })?;
+        
+        Ok(Self {
+            config: config.clone(),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()?,
+            blockchain,
+            started: Instant::now(),
+            period_start: Mutex::new(now_secs()),
+            counters: Mutex::new(BTreeMap::new()),
+            submissions,
+        })
+    }
+    
+    /// Count one analysed transaction and its verdict
+    pub fn record_verdict(&self, chain_id: u64, flagged: bool) {
+        let mut counters = self.counters.lock();
+        let chain = counters.entry(chain_id).or_default();
+        chain.transactions_analyzed += 1;
+        if flagged {
+            chain.threats_flagged += 1;
+        }
+    }
+    
+    /// Count a threat report that made it on-chain
+    pub fn record_reported(&self, chain_id: u64) {
+        self.counters.lock().entry(chain_id).or_default().threats_reported += 1;
+    }
+    
+    pub async fn start(&self) -> Result<()> {
+        info!("📊 Reporting per-chain stats to {} every {}s", self.config.endpoint, self.config.interval_secs);
+        
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval_secs.max(60)));
+        // The first tick fires immediately; there is nothing to report yet
+        interval.tick().await;
+        
+        loop {
+            interval.tick().await;
+            
+            let period_end = now_secs();
+            let period_start = std::mem::replace(&mut *self.period_start.lock(), period_end);
+            let counters = std::mem::take(&mut *self.counters.lock());
+            
+            match self.submit(period_start, period_end, &counters).await {
+                Ok(chains) => {
+                    self.submissions.with_label_values(&["ok"]).inc();
+                    debug!("📊 Submitted stats for {} chains", chains);
+                }
+                Err(e) => {
+                    self.submissions.with_label_values(&["error"]).inc();
+                    warn!("Stats submission failed, carrying counts over: {}", e);
+                    // Fold the unsent counts into the next period
+                    *self.period_start.lock() = period_start;
+                    let mut current = self.counters.lock();
+                    for (chain_id, unsent) in &counters {
+                        current.entry(*chain_id).or_default().merge(unsent);
+                    }
+                }
+            }
+        }
+    }
+    
+    async fn submit(&self, period_start: u64, period_end: u64, counters: &BTreeMap<u64, ChainCounters>) -> Result<usize> {
+        let report = StatsReport {
+            node_address: format!("{:?}", self.blockchain.wallet_address()),
+            node_version: env!("CARGO_PKG_VERSION").to_string(),
+            period_start,
+            period_end,
+            uptime_secs: round_down(self.started.elapsed().as_secs(), self.config.uptime_granularity_secs),
+            chains: self.private_chain_stats(counters),
+        };
+        
+        let payload = serde_json::to_string(&report)?;
+        let signature = self.blockchain.sign_message(payload.as_bytes()).await?;
+        
+        self.client
+            .post(&self.config.endpoint)
+            .json(&SignedStatsReport { payload, signature })
+            .send()
+            .await?
+            .error_for_status()?;
+        
+        Ok(report.chains.len())
+    }
+    
+    /// Round counts to the configured granularity and drop chains below the reporting floor
+    fn private_chain_stats(&self, counters: &BTreeMap<u64, ChainCounters>) -> Vec<ChainStats> {
+        let granularity = self.config.count_granularity;
+        
+        counters
+            .iter()
+            .filter(|(_, c)| c.transactions_analyzed >= self.config.min_transactions_per_chain)
+            .map(|(chain_id, c)| ChainStats {
+                chain_id: *chain_id,
+                counters: ChainCounters {
+                    transactions_analyzed: round_nearest(c.transactions_analyzed, granularity),
+                    threats_flagged: round_nearest(c.threats_flagged, granularity),
+                    threats_reported: round_nearest(c.threats_reported, granularity),
+                },
+            })
+            .collect()
+    }
+}
+
+fn round_nearest(value: u64, granularity: u64) -> u64 {
+    if granularity <= 1 {
+        return value;
+    }
+    (value + granularity / 2) / granularity * granularity
+}
+
+fn round_down(value: u64, granularity: u64) -> u64 {
+    if granularity <= 1 {
+        return value;
+    }
+    value / granularity * granularity
+}
+
+fn now_secs() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
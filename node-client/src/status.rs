@@ -0,0 +1,254 @@
+//! Machine-readable node reports, served on the metrics port and printed by `--output json`
+//!
+//! These shapes are a stable interface for scripts and fleet tooling. Fields may be added in any
+//! release; renaming or removing one, or changing what it means, bumps [`SCHEMA_VERSION`].
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::ai::robustness::RobustnessReport;
+use crate::ai::{LatencyPercentiles, ModelStats, ThreatDetector};
+use crate::alert_cache::VerifiedAlertCache;
+use crate::crash::{CrashReport, Supervisor};
+use crate::dag::{DAGProcessor, DAGStats, ShapeBenchmark};
+use crate::degradation::{Degradation, DegradationReport};
+use crate::energy::EnergyMonitor;
+use crate::history::{AddressRisk, ReportHistory, ReportHistoryEntry};
+use crate::maintenance::{MaintenanceControl, MaintenanceMode};
+use crate::node::{EnergyStats, NodeStats};
+use crate::peers::PeerLedger;
+use crate::penalty::Penalty;
+
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// The envelope every JSON document is printed in: which report it is, then the report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report<T> {
+    /// `status`, `stats`, `benchmark`, `history`, `peers`, `preflight`, `crashes`, `query`, `retention`, `model_stats`, `provision`, `keygen`, `mirror`, `loadgen`, `gossip_evidence`, `chain_check`, `archive`, `archive_query`, `reporting_guard`, `review`, `rewards`, `reward_claim`, `stake` or `stake_change`
+    pub kind: String,
+    pub schema_version: u32,
+    pub data: T,
+}
+
+impl<T> Report<T> {
+    pub fn new(kind: &str, data: T) -> Self {
+        Self {
+            kind: kind.to_string(),
+            schema_version: SCHEMA_VERSION,
+            data,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeStatus {
+    pub node_id: String,
+    pub version: String,
+    pub uptime_seconds: u64,
+    pub ai_enabled: bool,
+    /// Models loaded and warmed up; always true without AI
+    #[serde(default)]
+    pub ready: bool,
+    /// Fingerprint of the detection pipeline verdicts are produced with; `None` without AI
+    pub pipeline_fingerprint: Option<String>,
+    pub connected_peers: usize,
+    pub maintenance: MaintenanceMode,
+    pub degradation: DegradationReport,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsReport {
+    pub threats_detected: u64,
+    pub challenges_completed: u64,
+    pub reputation_score: u32,
+    pub energy_efficiency: u32,
+    pub uptime_seconds: u64,
+    /// Current main-loop interval; it adapts to activity between the configured bounds
+    #[serde(default)]
+    pub heartbeat_interval_secs: f64,
+    /// Slashes, deactivations and reputation drops since startup
+    #[serde(default)]
+    pub penalties: u64,
+    #[serde(default)]
+    pub last_penalty: Option<Penalty>,
+    pub dag: DagReport,
+    /// `None` when AI detection is disabled
+    pub model: Option<ModelReport>,
+    pub energy: EnergyReport,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagReport {
+    pub total_nodes: usize,
+    pub processed_nodes: usize,
+    pub pending_nodes: usize,
+    pub queue_size: usize,
+    #[serde(default)]
+    pub conflicted_nodes: usize,
+    #[serde(default)]
+    pub orphaned_nodes: usize,
+    pub parallel_efficiency: f64,
+}
+
+impl From<DAGStats> for DagReport {
+    fn from(stats: DAGStats) -> Self {
+        Self {
+            total_nodes: stats.total_nodes,
+            processed_nodes: stats.processed_nodes,
+            pending_nodes: stats.pending_nodes,
+            queue_size: stats.queue_size,
+            conflicted_nodes: stats.conflicted_nodes,
+            orphaned_nodes: stats.orphaned_nodes,
+            parallel_efficiency: stats.parallel_efficiency,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelReport {
+    pub total_predictions: u64,
+    pub accurate_predictions: u64,
+    /// `None` until enough verdicts are graded
+    pub precision: Option<f64>,
+    pub recall: Option<f64>,
+    pub f1: Option<f64>,
+    pub inference_latency: LatencyPercentiles,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+}
+
+impl From<ModelStats> for ModelReport {
+    fn from(stats: ModelStats) -> Self {
+        Self {
+            precision: stats.precision(),
+            recall: stats.recall(),
+            f1: stats.f1(),
+            total_predictions: stats.total_predictions,
+            accurate_predictions: stats.accurate_predictions,
+            inference_latency: stats.inference_latency,
+            cache_hits: stats.cache_hits,
+            cache_misses: stats.cache_misses,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergyReport {
+    pub power_watts: f32,
+    pub efficiency_score: u32,
+    pub carbon_footprint_kg_per_hour: f64,
+}
+
+impl From<EnergyStats> for EnergyReport {
+    fn from(stats: EnergyStats) -> Self {
+        Self {
+            power_watts: stats.power_watts,
+            efficiency_score: stats.efficiency_score,
+            carbon_footprint_kg_per_hour: stats.carbon_footprint_kg_per_hour,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub dag: DagBenchmark,
+    pub ai: AiBenchmark,
+    /// Evasion rates of disguised malicious samples
+    pub robustness: RobustnessReport,
+    pub energy: EnergyReport,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagBenchmark {
+    /// Over every shape
+    pub transactions: usize,
+    pub duration_ms: f64,
+    pub tps: f64,
+    /// Mean over the shapes
+    pub parallel_efficiency: f64,
+    #[serde(default)]
+    pub shapes: Vec<ShapeBenchmark>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiBenchmark {
+    pub samples: usize,
+    pub duration_ms: f64,
+    /// Percent
+    pub accuracy: f64,
+    pub avg_latency_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressHistory {
+    pub risk: AddressRisk,
+    /// Oldest first
+    pub reports: Vec<ReportHistoryEntry>,
+}
+
+/// The running node's components behind `/status`, `/stats`, `/history/:address` and `/crashes`
+pub struct StatusSource {
+    pub node_id: String,
+    pub stats: Arc<RwLock<NodeStats>>,
+    pub dag_processor: Arc<DAGProcessor>,
+    pub threat_detector: Option<Arc<ThreatDetector>>,
+    pub energy_monitor: Arc<EnergyMonitor>,
+    pub maintenance: Arc<MaintenanceControl>,
+    pub peer_ledger: Arc<PeerLedger>,
+    pub report_history: Arc<ReportHistory>,
+    pub alert_cache: Option<Arc<VerifiedAlertCache>>,
+    pub degradation: Arc<Degradation>,
+    pub supervisor: Arc<Supervisor>,
+}
+
+impl StatusSource {
+    pub async fn status(&self) -> NodeStatus {
+        NodeStatus {
+            node_id: self.node_id.clone(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_seconds: self.stats.read().await.uptime_seconds,
+            ai_enabled: self.threat_detector.is_some(),
+            ready: self.threat_detector.as_ref().is_none_or(|detector| detector.is_ready()),
+            pipeline_fingerprint: self.threat_detector.as_ref().map(|detector| detector.pipeline_fingerprint()),
+            connected_peers: self.peer_ledger.summaries().iter().filter(|peer| peer.connected).count(),
+            maintenance: self.maintenance.mode().await,
+            degradation: self.degradation.report(),
+        }
+    }
+    
+    pub async fn stats(&self) -> Result<StatsReport> {
+        let stats = self.stats.read().await.clone();
+        let model = match &self.threat_detector {
+            Some(detector) => Some(detector.get_model_stats().await.into()),
+            None => None,
+        };
+        Ok(StatsReport {
+            threats_detected: stats.threats_detected,
+            challenges_completed: stats.challenges_completed,
+            reputation_score: stats.reputation_score,
+            energy_efficiency: stats.energy_efficiency,
+            uptime_seconds: stats.uptime_seconds,
+            heartbeat_interval_secs: stats.heartbeat_interval_secs,
+            penalties: stats.penalties,
+            last_penalty: stats.last_penalty,
+            dag: self.dag_processor.get_dag_stats().await?.into(),
+            model,
+            energy: self.energy_monitor.get_current_stats().await?.into(),
+        })
+    }
+    
+    /// Crash reports of panicked subsystem tasks, oldest first
+    pub fn crashes(&self) -> Result<Vec<CrashReport>> {
+        self.supervisor.reports()
+    }
+
+    pub fn history(&self, address: &str) -> Result<AddressHistory> {
+        let alerts = self.alert_cache.as_ref().map(|cache| cache.lookup(address)).unwrap_or_default();
+        Ok(AddressHistory {
+            risk: self.report_history.risk(address, &alerts)?,
+            reports: self.report_history.reports(address)?,
+        })
+    }
+}
@@ -0,0 +1,240 @@
+//! Persistent node storage backed by an embedded sled database
+//!
+//! When a write fails the disk is no longer trusted with new data: storage switches to read-only
+//! RAM mode, where the database is still read but every later write lands in an in-memory overlay.
+//! The node keeps running on that overlay until it is restarted, which loses the writes since.
+
+use anyhow::{bail, Context, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::{debug, error, info};
+
+use crate::chaos;
+use crate::config::StorageConfig;
+
+/// Key/value storage grouped into namespaces (`<namespace>/<key>`).
+/// Writes that must land together go through a [`StorageBatch`] and are applied atomically.
+pub struct NodeStorage {
+    db: sled::Db,
+    config: StorageConfig,
+    /// Why the first write failed; set once, when storage switches to RAM mode
+    failure: parking_lot::RwLock<Option<String>>,
+    /// Writes made in RAM mode, shadowing the database; `None` marks a deletion
+    overlay: parking_lot::RwLock<BTreeMap<Vec<u8>, Option<Vec<u8>>>>,
+}
+
+impl NodeStorage {
+    pub async fn new(config: &StorageConfig) -> Result<Self> {
+        info!("💾 Opening node storage at: {}", config.data_dir);
+        
+        std::fs::create_dir_all(&config.data_dir)
+            .with_context(|| format!("Failed to create data directory {}", config.data_dir))?;
+        
+        let db = sled::Config::new()
+            .path(Path::new(&config.data_dir).join("node.db"))
+            .cache_capacity(64 * 1024 * 1024)
+            .flush_every_ms(Some(1000))
+            .open()
+            .context("Failed to open node database")?;
+        
+        info!("✅ Node storage opened ({} keys)", db.len());
+        
+        Ok(Self {
+            db,
+            config: config.clone(),
+            failure: parking_lot::RwLock::new(None),
+            overlay: parking_lot::RwLock::new(BTreeMap::new()),
+        })
+    }
+    
+    pub fn get<T: DeserializeOwned>(&self, namespace: &str, key: &str) -> Result<Option<T>> {
+        let key = namespaced_key(namespace, key);
+        if let Some(entry) = self.overlay.read().get(&key) {
+            return Ok(entry.as_deref().map(bincode::deserialize).transpose()?);
+        }
+        match self.db.get(key)? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+    
+    pub fn put<T: Serialize>(&self, namespace: &str, key: &str, value: &T) -> Result<()> {
+        self.apply(vec![(namespaced_key(namespace, key), Some(bincode::serialize(value)?))])
+    }
+    
+    pub fn delete(&self, namespace: &str, key: &str) -> Result<()> {
+        self.apply(vec![(namespaced_key(namespace, key), None)])
+    }
+    
+    /// All entries of a namespace, in key order
+    pub fn scan<T: DeserializeOwned>(&self, namespace: &str) -> Result<Vec<(String, T)>> {
+        let prefix = namespaced_key(namespace, "");
+        let mut entries = Vec::new();
+        
+        for (key, value) in self.scan_raw(&prefix)? {
+            let key = String::from_utf8_lossy(&key[prefix.len()..]).into_owned();
+            entries.push((key, bincode::deserialize(&value)?));
+        }
+        
+        Ok(entries)
+    }
+    
+    /// Raw entries of the given namespaces, for shipping to a read replica
+    pub fn export_namespaces(&self, namespaces: &[&str]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut entries = Vec::new();
+        for namespace in namespaces {
+            entries.extend(self.scan_raw(&namespaced_key(namespace, ""))?);
+        }
+        Ok(entries)
+    }
+    
+    /// Database entries under `prefix` with the RAM mode overlay applied
+    fn scan_raw(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut entries = BTreeMap::new();
+        for item in self.db.scan_prefix(prefix) {
+            let (key, value) = item?;
+            entries.insert(key.to_vec(), value.to_vec());
+        }
+        
+        let overlay = self.overlay.read();
+        let shadowed = overlay.range(prefix.to_vec()..).take_while(|(key, _)| key.starts_with(prefix));
+        for (key, value) in shadowed {
+            match value {
+                Some(value) => entries.insert(key.clone(), value.clone()),
+                None => entries.remove(key),
+            };
+        }
+        Ok(entries.into_iter().collect())
+    }
+    
+    /// Replace the given namespaces with exported entries, atomically.
+    ///
+    /// Entries outside `namespaces` are ignored, so a snapshot can't write into local-only state.
+    pub fn import_namespaces(&self, namespaces: &[&str], entries: Vec<(Vec<u8>, Vec<u8>)>) -> Result<usize> {
+        if let Some(failure) = self.failure() {
+            bail!("Storage is in read-only RAM mode, not importing a snapshot: {}", failure);
+        }
+        let prefixes: Vec<Vec<u8>> = namespaces.iter().map(|n| namespaced_key(n, "")).collect();
+        let mut batch = sled::Batch::default();
+        
+        for prefix in &prefixes {
+            for item in self.db.scan_prefix(prefix) {
+                batch.remove(item?.0);
+            }
+        }
+        let mut imported = 0;
+        for (key, value) in entries {
+            if prefixes.iter().any(|prefix| key.starts_with(prefix)) {
+                batch.insert(key, value);
+                imported += 1;
+            }
+        }
+        
+        chaos::storage_write();
+        self.db.apply_batch(batch)?;
+        Ok(imported)
+    }
+    
+    pub fn batch(&self) -> StorageBatch {
+        StorageBatch::default()
+    }
+    
+    /// Apply every write in the batch atomically: either all of them become visible or none do
+    pub fn commit(&self, batch: StorageBatch) -> Result<()> {
+        debug!("💾 Committing storage batch with {} operations", batch.ops.len());
+        self.apply(batch.ops)
+    }
+    
+    /// Write to disk, or to the overlay once a write has failed
+    fn apply(&self, ops: Vec<(Vec<u8>, Option<Vec<u8>>)>) -> Result<()> {
+        if self.failure.read().is_none() {
+            let mut batch = sled::Batch::default();
+            for (key, value) in &ops {
+                match value {
+                    Some(value) => batch.insert(key.as_slice(), value.as_slice()),
+                    None => batch.remove(key.as_slice()),
+                }
+            }
+            chaos::storage_write();
+            match self.db.apply_batch(batch) {
+                Ok(()) => return Ok(()),
+                Err(e) => self.enter_ram_mode(&e),
+            }
+        }
+        
+        let mut overlay = self.overlay.write();
+        for (key, value) in ops {
+            overlay.insert(key, value);
+        }
+        Ok(())
+    }
+    
+    fn enter_ram_mode(&self, cause: &sled::Error) {
+        let mut failure = self.failure.write();
+        if failure.is_none() {
+            error!("🚨 Storage write failed, switching to read-only RAM mode: {}", cause);
+            *failure = Some(cause.to_string());
+        }
+    }
+    
+    /// Why storage switched to RAM mode; `None` while writes reach the disk
+    pub fn failure(&self) -> Option<String> {
+        self.failure.read().clone()
+    }
+    
+    /// Writes held only in RAM, lost if the node stops
+    pub fn pending_writes(&self) -> usize {
+        self.overlay.read().len()
+    }
+    
+    /// A no-op in RAM mode, where nothing new reaches the disk
+    pub async fn flush(&self) -> Result<()> {
+        if self.failure.read().is_some() {
+            return Ok(());
+        }
+        if let Err(e) = self.db.flush_async().await {
+            self.enter_ram_mode(&e);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+    
+    pub fn size_on_disk(&self) -> Result<u64> {
+        Ok(self.db.size_on_disk()?)
+    }
+    
+    pub fn data_dir(&self) -> &str {
+        &self.config.data_dir
+    }
+}
+
+/// Writes in order; `None` deletes the key
+#[derive(Default)]
+pub struct StorageBatch {
+    ops: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+impl StorageBatch {
+    pub fn put<T: Serialize>(&mut self, namespace: &str, key: &str, value: &T) -> Result<()> {
+        self.ops.push((namespaced_key(namespace, key), Some(bincode::serialize(value)?)));
+        Ok(())
+    }
+    
+    pub fn delete(&mut self, namespace: &str, key: &str) {
+        self.ops.push((namespaced_key(namespace, key), None));
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
+/// Whether `error` came from reading the database or decoding what it held
+pub fn is_storage_failure(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| cause.is::<sled::Error>() || cause.is::<bincode::Error>())
+}
+
+fn namespaced_key(namespace: &str, key: &str) -> Vec<u8> {
+    format!("{}/{}", namespace, key).into_bytes()
+}
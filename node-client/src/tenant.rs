@@ -0,0 +1,253 @@
+//! Tenant contexts for the screening API: authentication, policies, rate limits and usage
+
+use anyhow::{bail, Result};
+use parking_lot::Mutex;
+use prometheus::{IntCounterVec, Opts};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use tracing::{debug, info};
+
+use crate::config::{ScreeningConfig, TenantConfig};
+use crate::dag::Transaction;
+use crate::metrics::register_once;
+use crate::storage::NodeStorage;
+
+const TENANT_USAGE_NAMESPACE: &str = "tenant_usage";
+
+/// Outcome of applying a tenant's address lists before any detection runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyDecision {
+    Allowlisted,
+    Denylisted,
+    Screen,
+}
+
+/// Billing counters for one tenant and calendar month
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TenantUsage {
+    pub tenant_id: String,
+    pub period: String,
+    pub screenings: u64,
+    pub flagged: u64,
+    pub rate_limited: u64,
+}
+
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(per_minute: u32) -> Self {
+        let capacity = per_minute.max(1) as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: capacity / 60.0,
+            last_refill: Instant::now(),
+        }
+    }
+    
+    fn try_take(&mut self, count: u32) -> bool {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+        
+        if self.tokens >= count as f64 {
+            self.tokens -= count as f64;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Usage accumulated since the last flush to storage
+#[derive(Default)]
+struct PendingUsage {
+    screenings: AtomicU64,
+    flagged: AtomicU64,
+    rate_limited: AtomicU64,
+}
+
+pub struct Tenant {
+    config: TenantConfig,
+    allowlist: HashSet<String>,
+    denylist: HashSet<String>,
+    limiter: Mutex<TokenBucket>,
+    pending: PendingUsage,
+}
+
+impl Tenant {
+    fn new(config: TenantConfig) -> Self {
+        let normalize = |list: &[String]| list.iter().map(|a| a.to_lowercase()).collect::<HashSet<_>>();
+        
+        Self {
+            allowlist: normalize(&config.allowlist),
+            denylist: normalize(&config.denylist),
+            limiter: Mutex::new(TokenBucket::new(config.rate_limit_per_minute)),
+            pending: PendingUsage::default(),
+            config,
+        }
+    }
+    
+    pub fn id(&self) -> &str {
+        &self.config.id
+    }
+    
+    pub fn webhook_url(&self) -> Option<&str> {
+        self.config.webhook_url.as_deref()
+    }
+    
+    /// The tenant's own threshold, or the node-wide default
+    pub fn confidence_threshold(&self, default: f32) -> f32 {
+        self.config.confidence_threshold.unwrap_or(default)
+    }
+    
+    /// Denylist entries win over allowlist entries on any address the transaction touches
+    pub fn policy_for(&self, transaction: &Transaction) -> PolicyDecision {
+        let addresses = [&transaction.from, &transaction.to, &transaction.target_address]
+            .map(|a| a.to_lowercase());
+        
+        if addresses.iter().any(|a| self.denylist.contains(a)) {
+            PolicyDecision::Denylisted
+        } else if self.allowlist.contains(&addresses[2]) || self.allowlist.contains(&addresses[1]) {
+            PolicyDecision::Allowlisted
+        } else {
+            PolicyDecision::Screen
+        }
+    }
+    
+    /// Take `count` screenings from the tenant's rate limit
+    pub fn try_acquire(&self, count: u32) -> bool {
+        self.limiter.lock().try_take(count)
+    }
+}
+
+/// All configured tenants, indexed by the hash of their API key
+pub struct TenantRegistry {
+    tenants: HashMap<String, Arc<Tenant>>,
+    by_key: HashMap<[u8; 32], String>,
+    storage: Arc<NodeStorage>,
+    screenings: IntCounterVec,
+}
+
+impl TenantRegistry {
+    pub fn new(config: &ScreeningConfig, storage: Arc<NodeStorage>) -> Result<Self> {
+        let mut tenants = HashMap::new();
+        let mut by_key = HashMap::new();
+        
+        for tenant in &config.tenants {
+            if tenant.id.is_empty() || tenant.api_key.is_empty() {
+                bail!("Screening tenants need a non-empty id and api_key");
+            }
+            if by_key.insert(*blake3::hash(tenant.api_key.as_bytes()).as_bytes(), tenant.id.clone()).is_some() {
+                bail!("Tenant {} reuses another tenant's API key", tenant.id);
+            }
+            if tenants.insert(tenant.id.clone(), Arc::new(Tenant::new(tenant.clone()))).is_some() {
+                bail!("Duplicate screening tenant id: {}", tenant.id);
+            }
+        }
+        
+        static SCREENINGS: OnceLock<IntCounterVec> = OnceLock::new();
+        let screenings = register_once(&SCREENINGS, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_tenant_screenings_total", "Screening requests per tenant and outcome"),
+                &["tenant", "outcome"],
+            )
+        })?;
+        
+        info!("🏢 Loaded {} screening tenants", tenants.len());
+        
+        Ok(Self {
+            tenants,
+            by_key,
+            storage,
+            screenings,
+        })
+    }
+    
+    pub fn authenticate(&self, api_key: &str) -> Option<Arc<Tenant>> {
+        let hash = blake3::hash(api_key.as_bytes());
+        self.by_key
+            .get(hash.as_bytes())
+            .and_then(|id| self.tenants.get(id))
+            .cloned()
+    }
+    
+    /// Count a completed screening; `outcome` is the label used in the tenant metrics
+    pub fn record_screening(&self, tenant: &Tenant, flagged: bool, outcome: &str) {
+        tenant.pending.screenings.fetch_add(1, Ordering::Relaxed);
+        if flagged {
+            tenant.pending.flagged.fetch_add(1, Ordering::Relaxed);
+        }
+        self.screenings.with_label_values(&[tenant.id(), outcome]).inc();
+    }
+    
+    pub fn record_rate_limited(&self, tenant: &Tenant) {
+        tenant.pending.rate_limited.fetch_add(1, Ordering::Relaxed);
+        self.screenings.with_label_values(&[tenant.id(), "rate_limited"]).inc();
+    }
+    
+    /// Add pending counters onto each tenant's stored usage for the current month
+    pub fn flush_usage(&self) -> Result<()> {
+        let period = current_period();
+        let mut batch = self.storage.batch();
+        
+        for tenant in self.tenants.values() {
+            let screenings = tenant.pending.screenings.swap(0, Ordering::Relaxed);
+            let flagged = tenant.pending.flagged.swap(0, Ordering::Relaxed);
+            let rate_limited = tenant.pending.rate_limited.swap(0, Ordering::Relaxed);
+            if screenings == 0 && rate_limited == 0 {
+                continue;
+            }
+            
+            let mut usage = self.stored_usage(tenant.id(), &period)?;
+            usage.screenings += screenings;
+            usage.flagged += flagged;
+            usage.rate_limited += rate_limited;
+            batch.put(TENANT_USAGE_NAMESPACE, &usage_key(tenant.id(), &period), &usage)?;
+        }
+        
+        if !batch.is_empty() {
+            self.storage.commit(batch)?;
+            debug!("🏢 Flushed tenant usage for {}", period);
+        }
+        
+        Ok(())
+    }
+    
+    /// Current month's usage for a tenant, including counters not yet flushed
+    pub fn usage(&self, tenant: &Tenant) -> Result<TenantUsage> {
+        let mut usage = self.stored_usage(tenant.id(), &current_period())?;
+        usage.screenings += tenant.pending.screenings.load(Ordering::Relaxed);
+        usage.flagged += tenant.pending.flagged.load(Ordering::Relaxed);
+        usage.rate_limited += tenant.pending.rate_limited.load(Ordering::Relaxed);
+        Ok(usage)
+    }
+    
+    fn stored_usage(&self, tenant_id: &str, period: &str) -> Result<TenantUsage> {
+        Ok(self.storage
+            .get(TENANT_USAGE_NAMESPACE, &usage_key(tenant_id, period))?
+            .unwrap_or_else(|| TenantUsage {
+                tenant_id: tenant_id.to_string(),
+                period: period.to_string(),
+                ..Default::default()
+            }))
+    }
+}
+
+fn current_period() -> String {
+    chrono::Utc::now().format("%Y-%m").to_string()
+}
+
+fn usage_key(tenant_id: &str, period: &str) -> String {
+    format!("{}:{}", tenant_id, period)
+}
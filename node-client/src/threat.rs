@@ -0,0 +1,168 @@
+//! Threat taxonomy shared by detection, on-chain reporting and cross-chain messages
+//!
+//! A class travels as its snake_case name in JSON, in storage and in contract calls taking a
+//! string, and as its numeric [`code`](ThreatClass::code) where a contract or message packs it
+//! into a byte. Names this release doesn't know, from newer peers, pattern feeds or operator
+//! rules, are kept verbatim as [`ThreatClass::Other`] rather than rejected.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Code carried for classes without one of their own
+pub const OTHER_CODE: u8 = 255;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ThreatClass {
+    #[default]
+    Safe,
+    Phishing,
+    RugPull,
+    FlashLoanAttack,
+    SmartContractExploit,
+    /// Front- and back-running a victim's swap
+    Sandwich,
+    /// An approval that hands a drainer the sender's tokens
+    ApprovalDrainer,
+    PriceManipulation,
+    /// A token that can be bought but not sold
+    Honeypot,
+    /// Dust from a lookalike address, waiting to be copied out of the history
+    AddressPoisoning,
+    Other(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    None,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// 0 for none up to 4 for critical, as packed into oracle reports
+    pub fn level(&self) -> u8 {
+        *self as u8
+    }
+}
+
+impl ThreatClass {
+    const KNOWN: [ThreatClass; 10] = [
+        Self::Safe,
+        Self::Phishing,
+        Self::RugPull,
+        Self::FlashLoanAttack,
+        Self::SmartContractExploit,
+        Self::Sandwich,
+        Self::ApprovalDrainer,
+        Self::PriceManipulation,
+        Self::Honeypot,
+        Self::AddressPoisoning,
+    ];
+    
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Safe => "safe",
+            Self::Phishing => "phishing",
+            Self::RugPull => "rug_pull",
+            Self::FlashLoanAttack => "flash_loan_attack",
+            Self::SmartContractExploit => "smart_contract_exploit",
+            Self::Sandwich => "sandwich",
+            Self::ApprovalDrainer => "approval_drainer",
+            Self::PriceManipulation => "price_manipulation",
+            Self::Honeypot => "honeypot",
+            Self::AddressPoisoning => "address_poisoning",
+            Self::Other(name) => name,
+        }
+    }
+    
+    /// Stable numeric code; [`OTHER_CODE`] for [`ThreatClass::Other`]
+    pub fn code(&self) -> u8 {
+        match self {
+            Self::Safe => 0,
+            Self::Phishing => 1,
+            Self::RugPull => 2,
+            Self::FlashLoanAttack => 3,
+            Self::SmartContractExploit => 4,
+            Self::Sandwich => 5,
+            Self::ApprovalDrainer => 6,
+            Self::PriceManipulation => 7,
+            Self::Honeypot => 8,
+            Self::AddressPoisoning => 9,
+            Self::Other(_) => OTHER_CODE,
+        }
+    }
+    
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::KNOWN.into_iter().find(|class| class.code() == code)
+    }
+    
+    /// How bad a confirmed instance is before its confidence is taken into account
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            Self::Safe => Severity::None,
+            Self::AddressPoisoning => Severity::Low,
+            Self::Sandwich | Self::Honeypot | Self::Other(_) => Severity::Medium,
+            Self::Phishing | Self::PriceManipulation | Self::FlashLoanAttack => Severity::High,
+            Self::RugPull | Self::SmartContractExploit | Self::ApprovalDrainer => Severity::Critical,
+        }
+    }
+    
+    pub fn is_safe(&self) -> bool {
+        *self == Self::Safe
+    }
+}
+
+impl From<&str> for ThreatClass {
+    fn from(name: &str) -> Self {
+        Self::KNOWN
+            .into_iter()
+            .find(|class| class.as_str() == name)
+            .unwrap_or_else(|| Self::Other(name.to_string()))
+    }
+}
+
+impl From<String> for ThreatClass {
+    fn from(name: String) -> Self {
+        Self::from(name.as_str())
+    }
+}
+
+impl fmt::Display for ThreatClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for ThreatClass {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ThreatClass {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(String::deserialize(deserializer)?.into())
+    }
+}
+
+/// `#[serde(with = "crate::threat::as_code")]` for fields packed as the numeric code.
+///
+/// [`ThreatClass::Other`] can't be told apart at this width and comes back as `other`.
+pub mod as_code {
+    use super::{ThreatClass, OTHER_CODE};
+    use serde::{Deserialize, Deserializer, Serializer};
+    
+    pub fn serialize<S: Serializer>(class: &ThreatClass, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(class.code())
+    }
+    
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ThreatClass, D::Error> {
+        let code = u8::deserialize(deserializer)?;
+        Ok(ThreatClass::from_code(code).unwrap_or_else(|| {
+            ThreatClass::Other(if code == OTHER_CODE { "other".to_string() } else { format!("code_{}", code) })
+        }))
+    }
+}
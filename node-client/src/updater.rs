@@ -0,0 +1,461 @@
+//! Self-update from a signed release manifest, restarting through the service manager
+//!
+//! Releases ship one binary per target triple. A freshly installed binary counts its starts in
+//! `<binary>.pending` until it is up; one that fails to come up `max_boot_attempts` times is
+//! swapped back for `<binary>.previous`.
+
+use anyhow::{anyhow, bail, Context, Result};
+use ethers::types::Address;
+use ethers::utils::hex;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{debug, error, info, warn};
+
+use crate::config::UpdaterConfig;
+use crate::signature::{parse_signers, verify_signed_payload};
+use crate::storage::NodeStorage;
+
+const UPDATER_NAMESPACE: &str = "updater";
+const MANIFEST_ISSUED_KEY: &str = "manifest_issued_at";
+const MAX_BINARY_BYTES: usize = 256 * 1024 * 1024;
+/// Target triple this binary was built for
+const TARGET: &str = env!("BUILD_TARGET");
+
+/// The manifest as served: `payload` is the JSON-encoded [`ReleaseManifest`], signed as-is
+#[derive(Debug, Deserialize)]
+struct SignedManifest {
+    payload: String,
+    signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseManifest {
+    /// Unix time the manifest was issued; older manifests than the last accepted one are refused
+    pub issued_at: u64,
+    pub channels: HashMap<String, Release>,
+}
+
+impl ReleaseManifest {
+    /// The channel's release and its binary for `target`
+    pub fn release_for(&self, channel: &str, target: &str) -> Result<(&Release, &ReleaseBinary)> {
+        let release = self.channels
+            .get(channel)
+            .ok_or_else(|| anyhow!("Manifest has no '{}' channel", channel))?;
+        let binary = release.targets
+            .get(target)
+            .ok_or_else(|| anyhow!("Release v{} on the {} channel has no binary for {}", release.version, channel, target))?;
+        Ok((release, binary))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Release {
+    pub version: String,
+    /// Binaries by target triple, e.g. `x86_64-unknown-linux-gnu`
+    pub targets: HashMap<String, ReleaseBinary>,
+    #[serde(default)]
+    pub notes: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseBinary {
+    pub url: String,
+    /// Hex SHA-256 of the binary
+    pub sha256: String,
+}
+
+/// An installed binary that has not come up yet, kept as `<binary>.pending`
+#[derive(Debug, Serialize, Deserialize)]
+struct PendingUpdate {
+    version: String,
+    /// Times it has been started so far
+    boots: u32,
+}
+
+/// What to do with a start of the binary
+#[derive(Debug, PartialEq, Eq)]
+enum Boot {
+    Proceed,
+    /// The previous binary is back in place and should be started instead
+    RolledBack,
+}
+
+/// Checks the configured release channel and swaps in newer, verified binaries.
+///
+/// The previous binary is kept next to the new one as `<binary>.previous`. Once a new binary
+/// is in place the node is asked to restart; see [`restart`] for how that happens.
+pub struct Updater {
+    config: UpdaterConfig,
+    client: reqwest::Client,
+    trusted_signers: Vec<Address>,
+    binary: PathBuf,
+    storage: Arc<NodeStorage>,
+    restart: Arc<Notify>,
+}
+
+impl Updater {
+    pub fn new(config: &UpdaterConfig, storage: Arc<NodeStorage>) -> Result<Self> {
+        let trusted_signers = parse_signers(&config.trusted_signers)?;
+        if trusted_signers.is_empty() {
+            bail!("Self-update requires at least one trusted release signer");
+        }
+        
+        Ok(Self {
+            config: config.clone(),
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(300))
+                .build()?,
+            trusted_signers,
+            binary: binary_path(config)?,
+            storage,
+            restart: Arc::new(Notify::new()),
+        })
+    }
+    
+    /// Notified once a new binary has been installed and the node should restart into it
+    pub fn restart_signal(&self) -> Arc<Notify> {
+        Arc::clone(&self.restart)
+    }
+    
+    pub async fn start(&self) -> Result<()> {
+        info!("📦 Following the {} release channel (running v{} for {})", self.config.channel, env!("CARGO_PKG_VERSION"), TARGET);
+        
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.check_interval_secs.max(60)));
+        loop {
+            interval.tick().await;
+            
+            match self.check_and_install().await {
+                Ok(Some(version)) => {
+                    info!("📦 Installed v{}, restarting into it", version);
+                    self.restart.notify_one();
+                    return Ok(());
+                }
+                Ok(None) => debug!("📦 No newer release on the {} channel", self.config.channel),
+                Err(e) => warn!("Update check failed: {}", e),
+            }
+        }
+    }
+    
+    /// Install the channel's release if it is newer than the running binary
+    async fn check_and_install(&self) -> Result<Option<String>> {
+        let manifest = self.fetch_manifest().await?;
+        let (release, release_binary) = manifest.release_for(&self.config.channel, TARGET)?;
+        
+        if !is_newer(&release.version, env!("CARGO_PKG_VERSION"))? {
+            return Ok(None);
+        }
+        
+        info!("📦 Downloading v{} from {}", release.version, release_binary.url);
+        let binary = self.client
+            .get(&release_binary.url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        if binary.len() > MAX_BINARY_BYTES {
+            bail!("Release binary is {} bytes, above the {} byte limit", binary.len(), MAX_BINARY_BYTES);
+        }
+        
+        let digest = hex::encode(Sha256::digest(&binary));
+        if !digest.eq_ignore_ascii_case(release_binary.sha256.trim_start_matches("0x")) {
+            bail!("Release v{} checksum mismatch: expected {}, got {}", release.version, release_binary.sha256, digest);
+        }
+        
+        self.install(&binary, &release.version)?;
+        self.storage.put(UPDATER_NAMESPACE, MANIFEST_ISSUED_KEY, &manifest.issued_at)?;
+        
+        Ok(Some(release.version.clone()))
+    }
+    
+    async fn fetch_manifest(&self) -> Result<ReleaseManifest> {
+        let signed: SignedManifest = self.client
+            .get(&self.config.manifest_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let manifest = verify_manifest(&signed, &self.trusted_signers)?;
+        
+        // A replayed older manifest could otherwise pin the node to a vulnerable release
+        let last_issued: Option<u64> = self.storage.get(UPDATER_NAMESPACE, MANIFEST_ISSUED_KEY)?;
+        if let Some(last_issued) = last_issued {
+            if manifest.issued_at < last_issued {
+                bail!("Release manifest issued at {} is older than the last accepted one ({})",
+                      manifest.issued_at, last_issued);
+            }
+        }
+        
+        Ok(manifest)
+    }
+    
+    /// Replace the binary, keeping the old one as `<binary>.previous`
+    fn install(&self, binary: &[u8], version: &str) -> Result<()> {
+        let staged = with_suffix(&self.binary, "update");
+        let previous = with_suffix(&self.binary, "previous");
+        
+        std::fs::write(&staged, binary)
+            .with_context(|| format!("Failed to stage update at {}", staged.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+        }
+        
+        std::fs::copy(&self.binary, &previous)
+            .with_context(|| format!("Failed to back up {}", self.binary.display()))?;
+        // Marked first, so the new binary is never started unwatched; the old one ignores the mark
+        write_pending(&self.binary, &PendingUpdate { version: version.to_string(), boots: 0 })?;
+        // A rename within the directory is atomic, so a crash never leaves a partial binary
+        std::fs::rename(&staged, &self.binary)
+            .with_context(|| format!("Failed to replace {}", self.binary.display()))?;
+        
+        Ok(())
+    }
+}
+
+/// The manifest's payload, once signed by one of `trusted_signers`
+fn verify_manifest(signed: &SignedManifest, trusted_signers: &[Address]) -> Result<ReleaseManifest> {
+    let signer = verify_signed_payload(signed.payload.as_bytes(), &signed.signature, trusted_signers)?;
+    debug!("🔏 Release manifest signed by {:?}", signer);
+    serde_json::from_str(&signed.payload).context("Malformed release manifest")
+}
+
+/// Count a start of a freshly installed binary, rolling back to `<binary>.previous` once it has
+/// failed to come up `max_boot_attempts` times; the exit code to restart with after a rollback
+pub fn check_boot(config: &UpdaterConfig, sandboxed: bool) -> Result<Option<i32>> {
+    let binary = binary_path(config)?;
+    match count_boot(&binary, env!("CARGO_PKG_VERSION"), config.max_boot_attempts)? {
+        Boot::Proceed => Ok(None),
+        Boot::RolledBack => restart(config, sandboxed).map(Some),
+    }
+}
+
+/// The installed binary is up: keep it
+pub fn confirm_boot(config: &UpdaterConfig) -> Result<()> {
+    let binary = binary_path(config)?;
+    if read_pending(&binary)?.is_some() {
+        clear_pending(&binary)?;
+        info!("📦 v{} is up, keeping it", env!("CARGO_PKG_VERSION"));
+    }
+    Ok(())
+}
+
+fn count_boot(binary: &Path, version: &str, max_boot_attempts: u32) -> Result<Boot> {
+    let Some(mut pending) = read_pending(binary)? else {
+        return Ok(Boot::Proceed);
+    };
+    if pending.version != version {
+        // Left from an update this binary is not, e.g. one that was rolled back by hand
+        clear_pending(binary)?;
+        return Ok(Boot::Proceed);
+    }
+    if pending.boots >= max_boot_attempts {
+        let previous = with_suffix(binary, "previous");
+        error!("🚨 v{} failed to come up {} times, rolling back to {}", version, pending.boots, previous.display());
+        std::fs::rename(&previous, binary)
+            .with_context(|| format!("Failed to restore {}", previous.display()))?;
+        clear_pending(binary)?;
+        return Ok(Boot::RolledBack);
+    }
+    
+    pending.boots += 1;
+    write_pending(binary, &pending)?;
+    Ok(Boot::Proceed)
+}
+
+fn read_pending(binary: &Path) -> Result<Option<PendingUpdate>> {
+    let path = with_suffix(binary, "pending");
+    match std::fs::read(&path) {
+        Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes).with_context(|| format!("Malformed {}", path.display()))?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(anyhow::Error::from(e).context(format!("Failed to read {}", path.display()))),
+    }
+}
+
+fn write_pending(binary: &Path, pending: &PendingUpdate) -> Result<()> {
+    let path = with_suffix(binary, "pending");
+    std::fs::write(&path, serde_json::to_vec(pending)?).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn clear_pending(binary: &Path) -> Result<()> {
+    let path = with_suffix(binary, "pending");
+    std::fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))
+}
+
+/// The binary updates are installed over
+pub fn binary_path(config: &UpdaterConfig) -> Result<PathBuf> {
+    if config.binary_path.is_empty() {
+        Ok(std::env::current_exe()?)
+    } else {
+        Ok(PathBuf::from(&config.binary_path))
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Restart into the installed binary once the node has shut down.
+///
+/// Outside systemd, and when the sandbox allows `execve`, the process re-executes itself with
+/// the same arguments. Otherwise this returns `restart_exit_code` for the process to exit
+/// with, so the service manager starts the new binary.
+pub fn restart(config: &UpdaterConfig, sandboxed: bool) -> Result<i32> {
+    let under_systemd = std::env::var_os("INVOCATION_ID").is_some();
+    
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        
+        if !under_systemd && !sandboxed {
+            let binary = binary_path(config)?;
+            info!("♻️ Re-executing {}", binary.display());
+            let error = std::process::Command::new(&binary)
+                .args(std::env::args_os().skip(1))
+                .exec();
+            warn!("Failed to re-execute {}: {}", binary.display(), error);
+        }
+    }
+    
+    info!("♻️ Exiting with code {} for the service manager to restart the node", config.restart_exit_code);
+    Ok(config.restart_exit_code)
+}
+
+/// Whether `candidate` is a newer semantic version than `current`, either with a leading `v`
+fn is_newer(candidate: &str, current: &str) -> Result<bool> {
+    let parse = |version: &str| {
+        Version::parse(version.trim_start_matches('v')).with_context(|| format!("Invalid version '{}'", version))
+    };
+    Ok(parse(candidate)? > parse(current)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::core::rand::thread_rng;
+    use ethers::signers::{LocalWallet, Signer};
+    
+    fn manifest() -> ReleaseManifest {
+        let release = |version: &str, targets: &[&str]| Release {
+            version: version.to_string(),
+            targets: targets
+                .iter()
+                .map(|target| {
+                    let binary = ReleaseBinary {
+                        url: format!("https://releases.example/{}/{}", version, target),
+                        sha256: "00".repeat(32),
+                    };
+                    (target.to_string(), binary)
+                })
+                .collect(),
+            notes: String::new(),
+        };
+        ReleaseManifest {
+            issued_at: 1_700_000_000,
+            channels: HashMap::from([
+                ("stable".to_string(), release("1.4.0", &["x86_64-unknown-linux-gnu", "aarch64-unknown-linux-gnu"])),
+                ("beta".to_string(), release("1.5.0-beta.2", &["x86_64-unknown-linux-gnu"])),
+            ]),
+        }
+    }
+    
+    async fn signed_by(wallet: &LocalWallet, manifest: &ReleaseManifest) -> SignedManifest {
+        let payload = serde_json::to_string(manifest).unwrap();
+        let signature = wallet.sign_message(payload.as_bytes()).await.unwrap().to_string();
+        SignedManifest { payload, signature }
+    }
+    
+    #[tokio::test]
+    async fn manifests_must_be_signed_by_a_trusted_key() {
+        let trusted = LocalWallet::new(&mut thread_rng());
+        let stranger = LocalWallet::new(&mut thread_rng());
+        let signers = [trusted.address()];
+        
+        let signed = signed_by(&trusted, &manifest()).await;
+        assert_eq!(verify_manifest(&signed, &signers).unwrap().issued_at, 1_700_000_000);
+        
+        assert!(verify_manifest(&signed_by(&stranger, &manifest()).await, &signers).is_err());
+        
+        let mut tampered = signed_by(&trusted, &manifest()).await;
+        tampered.payload = tampered.payload.replace("1.4.0", "9.9.9");
+        assert!(verify_manifest(&tampered, &signers).is_err());
+        
+        let garbled = SignedManifest { signature: "0xdeadbeef".to_string(), ..signed_by(&trusted, &manifest()).await };
+        assert!(verify_manifest(&garbled, &signers).is_err());
+    }
+    
+    #[test]
+    fn versions_order_by_semver() {
+        assert!(is_newer("1.4.0", "1.3.9").unwrap());
+        assert!(is_newer("v1.10.0", "1.9.0").unwrap(), "numeric, not lexical");
+        assert!(is_newer("1.4.0", "1.4.0-rc.1").unwrap(), "a release follows its pre-releases");
+        assert!(is_newer("1.4.0-rc.10", "1.4.0-rc.2").unwrap());
+        assert!(is_newer("1.4.0-rc.1", "1.4.0-beta.3").unwrap());
+        assert!(!is_newer("1.4.0", "1.4.0").unwrap());
+        assert!(!is_newer("1.3.0", "1.4.0").unwrap());
+        assert!(is_newer("1.4", "1.3.0").is_err());
+        assert!(is_newer("latest", "1.3.0").is_err());
+    }
+    
+    #[test]
+    fn releases_are_picked_by_channel_and_target() {
+        let manifest = manifest();
+        
+        let (release, binary) = manifest.release_for("stable", "aarch64-unknown-linux-gnu").unwrap();
+        assert_eq!(release.version, "1.4.0");
+        assert_eq!(binary.url, "https://releases.example/1.4.0/aarch64-unknown-linux-gnu");
+        let (release, _) = manifest.release_for("beta", "x86_64-unknown-linux-gnu").unwrap();
+        assert_eq!(release.version, "1.5.0-beta.2");
+        
+        assert!(manifest.release_for("beta", "aarch64-unknown-linux-gnu").is_err());
+        assert!(manifest.release_for("nightly", "x86_64-unknown-linux-gnu").is_err());
+    }
+    
+    #[test]
+    fn a_binary_that_never_comes_up_is_rolled_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("dagshield-node");
+        std::fs::write(&binary, "new").unwrap();
+        std::fs::write(with_suffix(&binary, "previous"), "old").unwrap();
+        write_pending(&binary, &PendingUpdate { version: "1.4.0".to_string(), boots: 0 }).unwrap();
+        
+        for _ in 0..3 {
+            assert_eq!(count_boot(&binary, "1.4.0", 3).unwrap(), Boot::Proceed);
+        }
+        assert_eq!(count_boot(&binary, "1.4.0", 3).unwrap(), Boot::RolledBack);
+        assert_eq!(std::fs::read_to_string(&binary).unwrap(), "old");
+        assert!(read_pending(&binary).unwrap().is_none());
+        
+        // The restored binary starts as usual
+        assert_eq!(count_boot(&binary, "1.3.0", 3).unwrap(), Boot::Proceed);
+    }
+    
+    #[test]
+    fn a_binary_that_comes_up_is_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("dagshield-node");
+        std::fs::write(&binary, "new").unwrap();
+        write_pending(&binary, &PendingUpdate { version: "1.4.0".to_string(), boots: 2 }).unwrap();
+        
+        assert_eq!(count_boot(&binary, "1.4.0", 3).unwrap(), Boot::Proceed);
+        clear_pending(&binary).unwrap();
+        assert_eq!(count_boot(&binary, "1.4.0", 3).unwrap(), Boot::Proceed);
+        assert_eq!(std::fs::read_to_string(&binary).unwrap(), "new");
+        
+        // A mark left for another version is dropped, not counted
+        write_pending(&binary, &PendingUpdate { version: "1.5.0".to_string(), boots: 3 }).unwrap();
+        assert_eq!(count_boot(&binary, "1.4.0", 3).unwrap(), Boot::Proceed);
+        assert!(read_pending(&binary).unwrap().is_none());
+    }
+}
@@ -0,0 +1,202 @@
+//! Operator watchlists: closer scrutiny and immediate alerts for sensitive addresses
+
+use anyhow::{bail, Result};
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use parking_lot::RwLock;
+use prometheus::{IntCounterVec, Opts};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::ai::ThreatDetectionResult;
+use crate::config::{WatchedAddress, WatchlistConfig};
+use crate::dag::Transaction;
+use crate::metrics::register_once;
+use crate::storage::NodeStorage;
+
+pub const WATCHLIST_NAMESPACE: &str = "watchlist";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchlistAlert {
+    pub address: String,
+    pub label: String,
+    pub category: String,
+    pub transaction_id: String,
+    pub chain_id: u64,
+    pub from: String,
+    pub to: String,
+    pub flagged: bool,
+    pub verdict: ThreatDetectionResult,
+}
+
+/// Watched addresses from config plus those added at runtime, which are persisted.
+///
+/// Transactions touching a watched address are processed first, judged against a stricter
+/// threshold, and alerted on straight away; alerts are sent one by one, never batched or throttled.
+pub struct Watchlists {
+    config: WatchlistConfig,
+    storage: Arc<NodeStorage>,
+    /// Keyed by lowercase address
+    entries: RwLock<HashMap<String, WatchedAddress>>,
+    http: reqwest::Client,
+    alerts: IntCounterVec,
+}
+
+impl Watchlists {
+    pub fn new(config: &WatchlistConfig, storage: Arc<NodeStorage>) -> Result<Self> {
+        let mut entries = HashMap::new();
+        for entry in &config.addresses {
+            entries.insert(entry.address.to_lowercase(), entry.clone());
+        }
+        for (address, entry) in storage.scan::<WatchedAddress>(WATCHLIST_NAMESPACE)? {
+            entries.insert(address, entry);
+        }
+        info!("👁️ Watching {} addresses", entries.len());
+        
+        static ALERTS: OnceLock<IntCounterVec> = OnceLock::new();
+        let alerts = register_once(&ALERTS, || {
+            IntCounterVec::new(
+                Opts::new("dagshield_watchlist_alerts_total", "Alerts fired for transactions touching watched addresses"),
+                &["channel", "result"],
+            )
+        })?;
+        
+        Ok(Self {
+            config: config.clone(),
+            storage,
+            entries: RwLock::new(entries),
+            http: reqwest::Client::builder().timeout(Duration::from_secs(10)).build()?,
+            alerts,
+        })
+    }
+    
+    /// The watched address a transaction touches, if any
+    pub fn matches(&self, transaction: &Transaction) -> Option<WatchedAddress> {
+        let entries = self.entries.read();
+        [&transaction.target_address, &transaction.to, &transaction.from]
+            .into_iter()
+            .find_map(|address| entries.get(&address.to_lowercase()).cloned())
+    }
+    
+    pub fn threshold_for(&self, entry: &WatchedAddress) -> f32 {
+        entry.confidence_threshold.unwrap_or(self.config.confidence_threshold)
+    }
+    
+    pub fn list(&self) -> Vec<WatchedAddress> {
+        let mut entries: Vec<WatchedAddress> = self.entries.read().values().cloned().collect();
+        entries.sort_by(|a, b| a.address.cmp(&b.address));
+        entries
+    }
+    
+    pub fn add(&self, entry: WatchedAddress) -> Result<()> {
+        if !entry.address.starts_with("0x") || entry.address.len() != 42 {
+            bail!("Invalid address '{}'", entry.address);
+        }
+        let key = entry.address.to_lowercase();
+        self.storage.put(WATCHLIST_NAMESPACE, &key, &entry)?;
+        info!("👁️ Watching {} ({})", entry.address, entry.label);
+        self.entries.write().insert(key, entry);
+        Ok(())
+    }
+    
+    /// Stop watching an address; returns whether it was watched
+    pub fn remove(&self, address: &str) -> Result<bool> {
+        let key = address.to_lowercase();
+        self.storage.delete(WATCHLIST_NAMESPACE, &key)?;
+        let removed = self.entries.write().remove(&key).is_some();
+        if removed {
+            info!("👁️ Stopped watching {}", address);
+        }
+        Ok(removed)
+    }
+    
+    /// Whether a verdict on a watched transaction should be alerted on
+    pub fn should_alert(&self, flagged: bool) -> bool {
+        flagged || self.config.alert_on_any_activity
+    }
+    
+    /// Send the alert to every configured channel without waiting for delivery
+    pub fn alert(self: &Arc<Self>, alert: WatchlistAlert) {
+        warn!("👁️ Watched address {} ({}) touched by {}: {} (confidence: {:.2})",
+              alert.address, alert.label, alert.transaction_id, alert.verdict.threat_type, alert.verdict.confidence);
+        
+        let watchlists = Arc::clone(self);
+        tokio::spawn(async move {
+            if let Some(url) = &watchlists.config.webhook_url {
+                let result = watchlists.http
+                    .post(url)
+                    .json(&alert)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status());
+                watchlists.record_delivery("webhook", result.map(|_| ()));
+            }
+            
+            if let (Some(token), Some(chat_id)) = (&watchlists.config.telegram_bot_token, &watchlists.config.telegram_chat_id) {
+                let text = format!(
+                    "🚨 DAGShield watchlist alert\n{} ({}, {})\ntx {} on chain {}\n{} — confidence {:.2}\n{}",
+                    alert.address, alert.label, alert.category,
+                    alert.transaction_id, alert.chain_id,
+                    alert.verdict.threat_type, alert.verdict.confidence, alert.verdict.explanation,
+                );
+                let result = watchlists.http
+                    .post(format!("https://api.telegram.org/bot{}/sendMessage", token))
+                    .json(&serde_json::json!({ "chat_id": chat_id, "text": text }))
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status());
+                watchlists.record_delivery("telegram", result.map(|_| ()));
+            }
+        });
+    }
+    
+    fn record_delivery(&self, channel: &str, result: reqwest::Result<()>) {
+        match result {
+            Ok(()) => self.alerts.with_label_values(&[channel, "ok"]).inc(),
+            Err(e) => {
+                self.alerts.with_label_values(&[channel, "error"]).inc();
+                // reqwest errors can include the URL, and with it the Telegram bot token
+                error!("❌ Watchlist {} alert delivery failed: {}", channel, e.without_url());
+            }
+        }
+    }
+}
+
+/// `GET /watchlist`, `PUT /watchlist` and `DELETE /watchlist/:address`
+pub fn admin_routes(watchlists: Arc<Watchlists>) -> Router {
+    let list = Arc::clone(&watchlists);
+    let add = Arc::clone(&watchlists);
+    
+    Router::new()
+        .route(
+            "/watchlist",
+            get(move || async move { Json(list.list()) })
+                .put(move |Json(entry): Json<WatchedAddress>| async move {
+                    match add.add(entry) {
+                        Ok(()) => StatusCode::NO_CONTENT,
+                        Err(e) => {
+                            warn!("Rejected watchlist entry: {}", e);
+                            StatusCode::BAD_REQUEST
+                        }
+                    }
+                }),
+        )
+        .route(
+            "/watchlist/:address",
+            axum::routing::delete(move |Path(address): Path<String>| async move {
+                match watchlists.remove(&address) {
+                    Ok(true) => StatusCode::NO_CONTENT,
+                    Ok(false) => StatusCode::NOT_FOUND,
+                    Err(e) => {
+                        error!("Failed to remove {} from the watchlist: {}", address, e);
+                        StatusCode::INTERNAL_SERVER_ERROR
+                    }
+                }
+            }),
+        )
+}
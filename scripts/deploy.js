@@ -34,10 +34,20 @@ async function main() {
 
   // Create initial challenge
   const challengeType = "threat_detection_accuracy"
+  const challengeData = JSON.stringify({
+    version: 1,
+    cases: [
+      {
+        transaction: { id: "sample_1", from: "0x01", to: "0x02", target_address: "0x02", chain_id: 1, data: [], timestamp: 0, dependencies: [] },
+        expected_threat_type: "safe",
+      },
+    ],
+    min_accuracy: 0.9,
+  })
   const expectedResult = ethers.keccak256(ethers.toUtf8Bytes("sample_threat_signature"))
   const challengeReward = ethers.parseEther("1000") // 1000 tokens
 
-  await dagShield.createChallenge(challengeType, expectedResult, challengeReward)
+  await dagShield.createChallenge(challengeType, challengeData, expectedResult, challengeReward)
   console.log("✅ Created initial challenge")
 
   console.log("\n🎉 Deployment completed successfully!")
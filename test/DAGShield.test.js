@@ -119,13 +119,196 @@ describe("DAGShield", () => {
     })
   })
 
+  describe("Detection Epoch Commitments", () => {
+    const epoch = 480000
+    const modelHash = ethers.keccak256(ethers.toUtf8Bytes("model"))
+    const root = ethers.keccak256(ethers.toUtf8Bytes("root"))
+
+    beforeEach(async () => {
+      const stakeAmount = ethers.parseEther("100")
+      await dagShield.connect(node1).registerNode("node_001", { value: stakeAmount })
+    })
+
+    it("Should commit an epoch once", async () => {
+      const proof = ethers.toUtf8Bytes("proof")
+
+      await expect(dagShield.connect(node1).commitDetectionEpoch(epoch, modelHash, root, 3, proof))
+        .to.emit(dagShield, "DetectionEpochCommitted")
+        .withArgs(node1.address, epoch, modelHash, root, 3, ethers.keccak256(proof))
+
+      const commitment = await dagShield.getEpochCommitment(node1.address, epoch, modelHash)
+      expect(commitment.root).to.equal(root)
+      expect(commitment.detections).to.equal(3)
+
+      await expect(
+        dagShield.connect(node1).commitDetectionEpoch(epoch, modelHash, root, 3, "0x"),
+      ).to.be.revertedWith("Epoch already committed")
+    })
+
+    it("Should reject commitments from unregistered nodes", async () => {
+      await expect(
+        dagShield.connect(node2).commitDetectionEpoch(epoch, modelHash, root, 3, "0x"),
+      ).to.be.revertedWith("Node not registered")
+    })
+  })
+
+  describe("DAG Epoch Commitments", () => {
+    const root = ethers.keccak256(ethers.toUtf8Bytes("dag epoch"))
+
+    beforeEach(async () => {
+      const stakeAmount = ethers.parseEther("100")
+      await dagShield.connect(node1).registerNode("node_001", { value: stakeAmount })
+    })
+
+    it("Should commit a DAG epoch once", async () => {
+      await expect(dagShield.connect(node1).commitDagEpoch(7, root, 120))
+        .to.emit(dagShield, "DagEpochCommitted")
+        .withArgs(node1.address, 7, root, 120)
+
+      const committed = await dagShield.getDagEpoch(node1.address, 7)
+      expect(committed.root).to.equal(root)
+      expect(committed.transactions).to.equal(120)
+
+      await expect(dagShield.connect(node1).commitDagEpoch(7, root, 120)).to.be.revertedWith("Epoch already committed")
+    })
+
+    it("Should reject empty epochs and unregistered nodes", async () => {
+      await expect(dagShield.connect(node1).commitDagEpoch(8, ethers.ZeroHash, 0)).to.be.revertedWith("Empty epoch")
+      await expect(dagShield.connect(node2).commitDagEpoch(8, root, 1)).to.be.revertedWith("Node not registered")
+    })
+  })
+
+  describe("First Reporter Claims", () => {
+    const coder = ethers.AbiCoder.defaultAbiCoder()
+    const detectionHash = ethers.keccak256(
+      coder.encode(["uint256", "string", "string"], [1, "0x1234567890123456789012345678901234567890", "phishing"]),
+    )
+    const chainHash = ethers.keccak256(ethers.toUtf8Bytes("receipts"))
+
+    const signReceipt = (signer, firstSeenAt) =>
+      signer.signMessage(
+        ethers.getBytes(
+          ethers.keccak256(coder.encode(["bytes32", "uint256", "address"], [detectionHash, firstSeenAt, signer.address])),
+        ),
+      )
+
+    beforeEach(async () => {
+      const stakeAmount = ethers.parseEther("100")
+      await dagShield.connect(node1).registerNode("node_001", { value: stakeAmount })
+      await dagShield.connect(node2).registerNode("node_002", { value: stakeAmount })
+    })
+
+    it("Should let an earlier receipt supersede a claim", async () => {
+      const now = BigInt((await ethers.provider.getBlock("latest")).timestamp) * 1000n
+
+      await expect(
+        dagShield.connect(node1).claimFirstReporter(detectionHash, now, await signReceipt(node1, now), chainHash, "cid1"),
+      )
+        .to.emit(dagShield, "FirstReporterClaimed")
+        .withArgs(detectionHash, node1.address, now, chainHash, "cid1")
+
+      await expect(
+        dagShield.connect(node2).claimFirstReporter(detectionHash, now + 1n, await signReceipt(node2, now + 1n), chainHash, "cid2"),
+      ).to.be.revertedWith("Not the earliest receipt")
+
+      await dagShield.connect(node2).claimFirstReporter(detectionHash, now - 5n, await signReceipt(node2, now - 5n), chainHash, "cid2")
+      const claim = await dagShield.getFirstReporterClaim(detectionHash)
+      expect(claim.claimant).to.equal(node2.address)
+      expect(claim.firstSeenAt).to.equal(now - 5n)
+    })
+
+    it("Should reject a receipt signed by another key", async () => {
+      const now = BigInt((await ethers.provider.getBlock("latest")).timestamp) * 1000n
+
+      await expect(
+        dagShield.connect(node2).claimFirstReporter(detectionHash, now, await signReceipt(node1, now), chainHash, "cid"),
+      ).to.be.revertedWith("Invalid receipt signature")
+    })
+
+    it("Should settle once the claim window closes", async () => {
+      const now = BigInt((await ethers.provider.getBlock("latest")).timestamp) * 1000n
+      await dagShield.connect(node1).claimFirstReporter(detectionHash, now, await signReceipt(node1, now), chainHash, "cid")
+
+      await expect(dagShield.settleFirstReporter(detectionHash)).to.be.revertedWith("Claim window open")
+
+      await ethers.provider.send("evm_increaseTime", [3600])
+      await ethers.provider.send("evm_mine", [])
+      await expect(dagShield.settleFirstReporter(detectionHash))
+        .to.emit(dagShield, "RewardDistributed")
+        .withArgs(node1.address, ethers.parseEther("10.1"), "first_reporter")
+      await expect(dagShield.settleFirstReporter(detectionHash)).to.be.revertedWith("Claim already settled")
+    })
+  })
+
+  describe("Gossip Disputes", () => {
+    const coder = ethers.AbiCoder.defaultAbiCoder()
+    const topicHash = ethers.keccak256(ethers.toUtf8Bytes("dagshield/intel/2"))
+    const payloadA = ethers.keccak256(ethers.toUtf8Bytes('{"confidence":90}'))
+    const payloadB = ethers.keccak256(ethers.toUtf8Bytes('{"confidence":10}'))
+    const evidenceHash = ethers.keccak256(ethers.toUtf8Bytes("evidence"))
+
+    const signGossip = (signer, sequence, payloadHash) =>
+      signer.signMessage(
+        ethers.getBytes(ethers.keccak256(coder.encode(["bytes32", "uint256", "bytes32"], [topicHash, sequence, payloadHash]))),
+      )
+
+    beforeEach(async () => {
+      const stakeAmount = ethers.parseEther("100")
+      await dagShield.connect(node1).registerNode("node_001", { value: stakeAmount })
+      await dagShield.connect(node2).registerNode("node_002", { value: stakeAmount })
+    })
+
+    it("Should slash a node that signed two messages under one sequence number", async () => {
+      const sigA = await signGossip(node2, 7, payloadA)
+      const sigB = await signGossip(node2, 7, payloadB)
+
+      await expect(dagShield.connect(node1).submitEquivocationEvidence(node2.address, topicHash, 7, payloadA, sigA, payloadB, sigB))
+        .to.emit(dagShield, "NodeSlashed")
+        .withArgs(node2.address, ethers.parseEther("10"), "gossip_equivocation")
+        .and.to.emit(dagShield, "GossipEquivocationSlashed")
+        .withArgs(node2.address, node1.address, topicHash, 7)
+
+      await expect(
+        dagShield.connect(node1).submitEquivocationEvidence(node2.address, topicHash, 7, payloadA, sigA, payloadB, sigB),
+      ).to.be.revertedWith("Equivocation already slashed")
+    })
+
+    it("Should reject equivocation evidence the offender did not sign", async () => {
+      const sigA = await signGossip(node2, 7, payloadA)
+      const sigB = await signGossip(node1, 7, payloadB)
+
+      await expect(
+        dagShield.connect(node1).submitEquivocationEvidence(node2.address, topicHash, 7, payloadA, sigA, payloadB, sigB),
+      ).to.be.revertedWith("Invalid gossip signature")
+      await expect(
+        dagShield.connect(node1).submitEquivocationEvidence(node2.address, topicHash, 7, payloadA, sigA, payloadA, sigA),
+      ).to.be.revertedWith("Messages are identical")
+    })
+
+    it("Should slash only when the owner upholds a dispute", async () => {
+      await expect(dagShield.connect(node1).openDispute(node2.address, evidenceHash, "cid"))
+        .to.emit(dagShield, "DisputeOpened")
+        .withArgs(0, node2.address, node1.address, evidenceHash, "cid")
+      await expect(dagShield.connect(node1).resolveDispute(0, true)).to.be.reverted
+
+      await expect(dagShield.resolveDispute(0, true))
+        .to.emit(dagShield, "NodeSlashed")
+        .withArgs(node2.address, ethers.parseEther("10"), "gossip_dispute")
+      await expect(dagShield.resolveDispute(0, false)).to.be.revertedWith("Dispute already resolved")
+
+      await dagShield.connect(node2).openDispute(node1.address, evidenceHash, "cid")
+      await expect(dagShield.resolveDispute(1, false)).to.emit(dagShield, "DisputeResolved").withArgs(1, false)
+      expect((await dagShield.getDispute(1)).upheld).to.be.false
+    })
+  })
+
   describe("Challenges", () => {
     it("Should create and complete challenges", async () => {
       const challengeType = "threat_detection"
       const expectedResult = ethers.keccak256(ethers.toUtf8Bytes("correct_answer"))
       const reward = ethers.parseEther("100")
 
-      await dagShield.createChallenge(challengeType, expectedResult, reward)
+      await dagShield.createChallenge(challengeType, "{}", expectedResult, reward)
 
       // Register a node
       const stakeAmount = ethers.parseEther("100")
@@ -141,6 +324,100 @@ describe("DAGShield", () => {
 
       await expect(dagShield.connect(node1).submitChallengeSolution(challengeId, expectedResult)).to.not.be.reverted
     })
+
+    it("Should list only open challenges with their data", async () => {
+      const expectedResult = ethers.keccak256(ethers.toUtf8Bytes("answer"))
+      const data = JSON.stringify({ version: 1, transactions: 100, target_tps: 50 })
+      await expect(dagShield.createChallenge("dag_processing_speed", data, expectedResult, 100)).to.emit(
+        dagShield,
+        "ChallengeCreated",
+      )
+
+      const [open] = await dagShield.getActiveChallenges()
+      expect(open.challengeType).to.equal("dag_processing_speed")
+      expect(open.data).to.equal(data)
+
+      await dagShield.connect(node1).registerNode("node_001", { value: ethers.parseEther("100") })
+      await dagShield.connect(node1).submitChallengeSolution(open.id, expectedResult)
+      expect(await dagShield.getActiveChallenges()).to.have.lengthOf(0)
+
+      await dagShield.createChallenge("dag_processing_speed", data, expectedResult, 100)
+      expect(await dagShield.getActiveChallenges()).to.have.lengthOf(1)
+      await ethers.provider.send("evm_increaseTime", [3601])
+      await ethers.provider.send("evm_mine", [])
+      expect(await dagShield.getActiveChallenges()).to.have.lengthOf(0)
+    })
+  })
+
+  describe("Stake Lifecycle", () => {
+    const stakeAmount = ethers.parseEther("100")
+
+    beforeEach(async () => {
+      await dagShield.connect(node1).registerNode("node_001", { value: stakeAmount })
+    })
+
+    it("Should top up and withdraw stake above the minimum", async () => {
+      await expect(dagShield.connect(node1).addStake({ value: ethers.parseEther("20") }))
+        .to.emit(dagShield, "StakeAdded")
+        .withArgs(node1.address, ethers.parseEther("20"), ethers.parseEther("120"))
+
+      await expect(dagShield.connect(node1).withdrawStake(ethers.parseEther("30"))).to.be.revertedWith(
+        "Stake would fall below minimum",
+      )
+      await expect(dagShield.connect(node1).withdrawStake(ethers.parseEther("20")))
+        .to.emit(dagShield, "StakeWithdrawn")
+        .withArgs(node1.address, ethers.parseEther("20"), stakeAmount)
+        .and.to.changeEtherBalance(node1, ethers.parseEther("20"))
+      expect((await dagShield.getNode(node1.address)).stake).to.equal(stakeAmount)
+    })
+
+    it("Should release the whole stake once the node leaves", async () => {
+      await expect(dagShield.connect(node1).deactivateNode())
+        .to.emit(dagShield, "NodeDeactivated")
+        .withArgs(node1.address, "unstaked")
+      expect((await dagShield.getNetworkStats())[0]).to.equal(0)
+
+      await dagShield.connect(node1).withdrawStake(stakeAmount)
+      expect(await dagShield.nodeStakes(node1.address)).to.equal(0)
+    })
+
+    it("Should deactivate a node left below the minimum past the grace period", async () => {
+      await dagShield.slashNode(node1.address, "false_report")
+      expect(await dagShield.understakedSince(node1.address)).to.be.gt(0)
+      await expect(dagShield.deactivateUnderstakedNode(node1.address)).to.be.revertedWith("Grace period not over")
+
+      await ethers.provider.send("evm_increaseTime", [3 * 24 * 3600])
+      await ethers.provider.send("evm_mine", [])
+      await expect(dagShield.connect(node2).deactivateUnderstakedNode(node1.address))
+        .to.emit(dagShield, "NodeDeactivated")
+        .withArgs(node1.address, "insufficient_stake")
+    })
+
+    it("Should clear the grace period when the stake is topped back up", async () => {
+      await dagShield.slashNode(node1.address, "false_report")
+      await dagShield.connect(node1).addStake({ value: ethers.parseEther("10") })
+      expect(await dagShield.understakedSince(node1.address)).to.equal(0)
+    })
+  })
+
+  describe("Reward Claims", () => {
+    it("Should accrue rewards until they are claimed", async () => {
+      const expectedResult = ethers.keccak256(ethers.toUtf8Bytes("answer"))
+      await dagShield.createChallenge("dag_processing_speed", "{}", expectedResult, 100)
+      await dagShield.connect(node1).registerNode("node_001", { value: ethers.parseEther("100") })
+      const [challenge] = await dagShield.getActiveChallenges()
+
+      await expect(dagShield.connect(node1).claimRewards()).to.be.revertedWith("No rewards to claim")
+      await dagShield.connect(node1).submitChallengeSolution(challenge.id, expectedResult)
+      const pending = await dagShield.pendingRewards(node1.address)
+      expect(pending).to.equal(ethers.parseEther("10.1"))
+
+      await expect(dagShield.connect(node1).claimRewards())
+        .to.emit(dagShield, "RewardsClaimed")
+        .withArgs(node1.address, pending)
+        .and.to.changeEtherBalance(node1, pending)
+      expect(await dagShield.pendingRewards(node1.address)).to.equal(0)
+    })
   })
 
   describe("Token Integration", () => {